@@ -17,12 +17,13 @@
 
 //! Distributed execution context.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use ballista_core::config::BallistaConfig;
+use ballista_core::serde::extension::ExtensionCodecs;
 use ballista_core::utils::create_df_ctx_with_ballista_query_planner;
 
 use datafusion::catalog::TableReference;
@@ -30,7 +31,12 @@ use datafusion::dataframe::DataFrame;
 use datafusion::datasource::TableProvider;
 use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::dataframe_impl::DataFrameImpl;
+use datafusion::execution::plugin::{self, PluginRegistrar};
+use datafusion::logical_plan::registry::FunctionRegistry;
 use datafusion::logical_plan::{CreateExternalTable, LogicalPlan, TableScan};
+use datafusion::physical_plan::udaf::AggregateUDF;
+use datafusion::physical_plan::udf::ScalarUDF;
+use datafusion::physical_plan::udwf::WindowUDF;
 use datafusion::prelude::{AvroReadOptions, CsvReadOptions};
 use datafusion::sql::parser::FileType;
 
@@ -43,6 +49,12 @@ struct BallistaContextState {
     scheduler_port: u16,
     /// Tables that have been registered with this context
     tables: HashMap<String, Arc<dyn TableProvider>>,
+    /// Scalar functions that have been registered with this context
+    scalar_functions: HashMap<String, Arc<ScalarUDF>>,
+    /// Aggregate functions that have been registered with this context
+    aggregate_functions: HashMap<String, Arc<AggregateUDF>>,
+    /// Codecs of the user-defined plan nodes that have been registered with this context
+    codecs: Arc<ExtensionCodecs>,
 }
 
 impl BallistaContextState {
@@ -56,19 +68,30 @@ impl BallistaContextState {
             scheduler_host,
             scheduler_port,
             tables: HashMap::new(),
+            scalar_functions: HashMap::new(),
+            aggregate_functions: HashMap::new(),
+            codecs: Arc::new(ExtensionCodecs::new()),
         }
     }
 
+    /// Starts an in-proc scheduler and executor, which look up the user-defined
+    /// functions of the plans in `functions` and the codecs of their user-defined
+    /// nodes in `codecs`, and returns the scheduler port
     #[cfg(feature = "standalone")]
-    pub async fn new_standalone(
-        config: &BallistaConfig,
+    async fn start_standalone(
         concurrent_tasks: usize,
-    ) -> ballista_core::error::Result<Self> {
+        functions: Arc<dyn FunctionRegistry + Send + Sync>,
+        codecs: Arc<ExtensionCodecs>,
+    ) -> ballista_core::error::Result<u16> {
         use ballista_core::serde::protobuf::scheduler_grpc_client::SchedulerGrpcClient;
 
         log::info!("Running in local mode. Scheduler will be run in-proc");
 
-        let addr = ballista_scheduler::new_standalone_scheduler().await?;
+        let addr = ballista_scheduler::new_standalone_scheduler(
+            functions.clone(),
+            codecs.clone(),
+        )
+        .await?;
 
         let scheduler = loop {
             match SchedulerGrpcClient::connect(format!(
@@ -85,13 +108,14 @@ impl BallistaContextState {
             }
         };
 
-        ballista_executor::new_standalone_executor(scheduler, concurrent_tasks).await?;
-        Ok(Self {
-            config: config.clone(),
-            scheduler_host: "localhost".to_string(),
-            scheduler_port: addr.port(),
-            tables: HashMap::new(),
-        })
+        ballista_executor::new_standalone_executor(
+            scheduler,
+            concurrent_tasks,
+            functions,
+            codecs,
+        )
+        .await?;
+        Ok(addr.port())
     }
 
     pub fn config(&self) -> &BallistaConfig {
@@ -99,6 +123,48 @@ impl BallistaContextState {
     }
 }
 
+impl PluginRegistrar for BallistaContextState {
+    fn register_udf(&mut self, f: ScalarUDF) {
+        self.scalar_functions.insert(f.name.clone(), Arc::new(f));
+    }
+
+    fn register_udaf(&mut self, f: AggregateUDF) {
+        self.aggregate_functions.insert(f.name.clone(), Arc::new(f));
+    }
+}
+
+impl FunctionRegistry for BallistaContextState {
+    fn udfs(&self) -> HashSet<String> {
+        self.scalar_functions.keys().cloned().collect()
+    }
+
+    fn udf(&self, name: &str) -> Result<Arc<ScalarUDF>> {
+        self.scalar_functions.get(name).cloned().ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "There is no UDF named \"{}\" in the registry",
+                name
+            ))
+        })
+    }
+
+    fn udaf(&self, name: &str) -> Result<Arc<AggregateUDF>> {
+        self.aggregate_functions.get(name).cloned().ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "There is no UDAF named \"{}\" in the registry",
+                name
+            ))
+        })
+    }
+
+    fn udwf(&self, name: &str) -> Result<Arc<WindowUDF>> {
+        Err(DataFusionError::Plan(format!(
+            "There is no UDWF named \"{}\" in the registry",
+            name
+        )))
+    }
+}
+
+#[derive(Clone)]
 pub struct BallistaContext {
     state: Arc<Mutex<BallistaContextState>>,
 }
@@ -118,12 +184,18 @@ impl BallistaContext {
         config: &BallistaConfig,
         concurrent_tasks: usize,
     ) -> ballista_core::error::Result<Self> {
-        let state =
-            BallistaContextState::new_standalone(config, concurrent_tasks).await?;
-
-        Ok(Self {
-            state: Arc::new(Mutex::new(state)),
-        })
+        let context = Self::remote("localhost", 0, config);
+        // the in-proc scheduler and executor use the functions and codecs registered
+        // with this context
+        let port = BallistaContextState::start_standalone(
+            concurrent_tasks,
+            Arc::new(context.clone()),
+            context.extension_codecs(),
+        )
+        .await?;
+        context.state.lock().unwrap().scheduler_port = port;
+
+        Ok(context)
     }
 
     /// Create a DataFrame representing an Avro table scan
@@ -142,6 +214,7 @@ impl BallistaContext {
                 &guard.scheduler_host,
                 guard.scheduler_port,
                 guard.config(),
+                guard.codecs.clone(),
             )
         };
         let df = ctx.read_avro(path, options).await?;
@@ -160,6 +233,7 @@ impl BallistaContext {
                 &guard.scheduler_host,
                 guard.scheduler_port,
                 guard.config(),
+                guard.codecs.clone(),
             )
         };
         let df = ctx.read_parquet(path).await?;
@@ -182,6 +256,7 @@ impl BallistaContext {
                 &guard.scheduler_host,
                 guard.scheduler_port,
                 guard.config(),
+                guard.codecs.clone(),
             )
         };
         let df = ctx.read_csv(path, options).await?;
@@ -211,6 +286,33 @@ impl BallistaContext {
         Ok(())
     }
 
+    /// Registers a scalar UDF that can be referenced from SQL queries. Remote
+    /// schedulers and executors must have registered it as well, e.g. by loading
+    /// the plugin that declares it, whereas standalone ones use this context.
+    pub fn register_udf(&self, f: ScalarUDF) {
+        self.state.lock().unwrap().register_udf(f);
+    }
+
+    /// Registers an aggregate UDF that can be referenced from SQL queries. Remote
+    /// schedulers and executors must have registered it as well.
+    pub fn register_udaf(&self, f: AggregateUDF) {
+        self.state.lock().unwrap().register_udaf(f);
+    }
+
+    /// Loads the shared library at `path` and registers the functions it
+    /// declares, see [`datafusion::execution::plugin`]. The scheduler and
+    /// executors must load the same plugin through their `plugins` setting.
+    pub fn load_plugin(&self, path: impl AsRef<Path>) -> Result<()> {
+        plugin::load_plugin(path, &mut *self.state.lock().unwrap())
+    }
+
+    /// The codecs and planners of the user-defined plan nodes of this context,
+    /// with which the codecs of the nodes of its queries must be registered.
+    /// Remote schedulers and executors must have registered them as well.
+    pub fn extension_codecs(&self) -> Arc<ExtensionCodecs> {
+        self.state.lock().unwrap().codecs.clone()
+    }
+
     pub async fn register_csv(
         &self,
         name: &str,
@@ -259,10 +361,11 @@ impl BallistaContext {
                 &state.scheduler_host,
                 state.scheduler_port,
                 state.config(),
+                state.codecs.clone(),
             )
         };
 
-        // register tables with DataFusion context
+        // register tables and functions with DataFusion context
         {
             let state = self.state.lock().unwrap();
             for (name, prov) in &state.tables {
@@ -271,6 +374,12 @@ impl BallistaContext {
                     Arc::clone(prov),
                 )?;
             }
+            for f in state.scalar_functions.values() {
+                ctx.register_udf(f.as_ref().clone());
+            }
+            for f in state.aggregate_functions.values() {
+                ctx.register_udaf(f.as_ref().clone());
+            }
         }
 
         let plan = ctx.create_logical_plan(sql)?;
@@ -313,6 +422,24 @@ impl BallistaContext {
     }
 }
 
+impl FunctionRegistry for BallistaContext {
+    fn udfs(&self) -> HashSet<String> {
+        self.state.lock().unwrap().udfs()
+    }
+
+    fn udf(&self, name: &str) -> Result<Arc<ScalarUDF>> {
+        self.state.lock().unwrap().udf(name)
+    }
+
+    fn udaf(&self, name: &str) -> Result<Arc<AggregateUDF>> {
+        self.state.lock().unwrap().udaf(name)
+    }
+
+    fn udwf(&self, name: &str) -> Result<Arc<WindowUDF>> {
+        self.state.lock().unwrap().udwf(name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[tokio::test]
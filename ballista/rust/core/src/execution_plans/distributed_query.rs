@@ -16,13 +16,14 @@
 // under the License.
 
 use std::any::Any;
-use std::convert::TryInto;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::client::BallistaClient;
 use crate::config::BallistaConfig;
+use crate::serde::extension::ExtensionCodecs;
+use crate::serde::logical_plan::to_proto::serialize_logical_plan;
 use crate::serde::protobuf::{
     execute_query_params::Query, job_status, scheduler_grpc_client::SchedulerGrpcClient,
     ExecuteQueryParams, GetJobStatusParams, GetJobStatusResult, KeyValuePair,
@@ -55,14 +56,22 @@ pub struct DistributedQueryExec {
     config: BallistaConfig,
     /// Logical plan to execute
     plan: LogicalPlan,
+    /// Codecs of the user-defined nodes of the plan
+    codecs: Arc<ExtensionCodecs>,
 }
 
 impl DistributedQueryExec {
-    pub fn new(scheduler_url: String, config: BallistaConfig, plan: LogicalPlan) -> Self {
+    pub fn new(
+        scheduler_url: String,
+        config: BallistaConfig,
+        plan: LogicalPlan,
+        codecs: Arc<ExtensionCodecs>,
+    ) -> Self {
         Self {
             scheduler_url,
             config,
             plan,
+            codecs,
         }
     }
 }
@@ -93,6 +102,7 @@ impl ExecutionPlan for DistributedQueryExec {
             self.scheduler_url.clone(),
             self.config.clone(),
             self.plan.clone(),
+            self.codecs.clone(),
         )))
     }
 
@@ -113,8 +123,7 @@ impl ExecutionPlan for DistributedQueryExec {
         let job_id = scheduler
             .execute_query(ExecuteQueryParams {
                 query: Some(Query::LogicalPlan(
-                    (&self.plan)
-                        .try_into()
+                    serialize_logical_plan(&self.plan, &self.codecs)
                         .map_err(|e| DataFusionError::Execution(format!("{:?}", e)))?,
                 )),
                 settings: self
@@ -143,8 +143,7 @@ impl ExecutionPlan for ShuffleStreamReaderExec {
 
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
         info!("ShuffleStreamReaderExec::execute({})", partition);
-        let output_rows=
-            MetricBuilder::new(&self.metrics).output_rows(partition);
+        let output_rows = MetricBuilder::new(&self.metrics).output_rows(partition);
         let (sender, receiver): (
             Sender<ArrowResult<RecordBatch>>,
             Receiver<ArrowResult<RecordBatch>>,
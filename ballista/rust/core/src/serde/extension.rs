@@ -0,0 +1,226 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Serialization of the plan nodes that are defined outside of DataFusion and
+//! Ballista.
+//!
+//! Such nodes are encoded by codecs that are registered by name with the
+//! [`ExtensionCodecs`] of every process that serializes or deserializes them:
+//! logical nodes with the client's context and the scheduler, execution plans
+//! with the scheduler and the executors. The scheduler also needs the
+//! [`ExtensionPlanner`]s that turn the logical nodes into execution plans, see
+//! [`ExtensionCodecs::register_extension_planner`].
+//!
+//! User-defined functions are referenced by name instead, and looked up in the
+//! [`FunctionRegistry`](datafusion::logical_plan::registry::FunctionRegistry)
+//! passed to deserialization.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use datafusion::logical_plan::{LogicalPlan, UserDefinedLogicalNode};
+use datafusion::physical_plan::planner::ExtensionPlanner;
+use datafusion::physical_plan::ExecutionPlan;
+
+use crate::error::BallistaError;
+use crate::serde::proto_error;
+
+/// Encodes and decodes the [`UserDefinedLogicalNode`]s of one or more kinds
+pub trait LogicalExtensionCodec: Send + Sync {
+    /// Encodes the node, without its inputs, or returns `None` if this codec
+    /// doesn't handle nodes of its kind.
+    fn try_encode(
+        &self,
+        node: &dyn UserDefinedLogicalNode,
+    ) -> Result<Option<Vec<u8>>, BallistaError>;
+
+    /// Decodes a node encoded by this codec, given its decoded inputs
+    fn try_decode(
+        &self,
+        buf: &[u8],
+        inputs: Vec<LogicalPlan>,
+    ) -> Result<Arc<dyn UserDefinedLogicalNode + Send + Sync>, BallistaError>;
+}
+
+/// Encodes and decodes the [`ExecutionPlan`]s of one or more kinds that Ballista
+/// doesn't serialize itself
+pub trait PhysicalExtensionCodec: Send + Sync {
+    /// Encodes the plan, without its children, or returns `None` if this codec
+    /// doesn't handle plans of its kind.
+    fn try_encode(
+        &self,
+        plan: &dyn ExecutionPlan,
+    ) -> Result<Option<Vec<u8>>, BallistaError>;
+
+    /// Decodes a plan encoded by this codec, given its decoded children
+    fn try_decode(
+        &self,
+        buf: &[u8],
+        inputs: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>, BallistaError>;
+}
+
+/// The codecs of the plan nodes that are defined outside of DataFusion and
+/// Ballista, and the planners of the user-defined logical nodes, which a
+/// context, scheduler or executor passes to the serialization of its plans
+#[derive(Default)]
+pub struct ExtensionCodecs {
+    logical_codecs: RwLock<BTreeMap<String, Arc<dyn LogicalExtensionCodec>>>,
+    physical_codecs: RwLock<BTreeMap<String, Arc<dyn PhysicalExtensionCodec>>>,
+    planners: RwLock<Vec<Arc<dyn ExtensionPlanner + Send + Sync>>>,
+}
+
+impl ExtensionCodecs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a codec for user-defined logical nodes under `name`, which the
+    /// encoded nodes are tagged with so that they can be decoded by the codec
+    /// registered under the same name in another process.
+    pub fn register_logical_extension_codec(
+        &self,
+        name: impl Into<String>,
+        codec: Arc<dyn LogicalExtensionCodec>,
+    ) {
+        self.logical_codecs
+            .write()
+            .unwrap()
+            .insert(name.into(), codec);
+    }
+
+    /// Registers a codec for execution plans under `name`, see
+    /// [`register_logical_extension_codec`](Self::register_logical_extension_codec).
+    pub fn register_physical_extension_codec(
+        &self,
+        name: impl Into<String>,
+        codec: Arc<dyn PhysicalExtensionCodec>,
+    ) {
+        self.physical_codecs
+            .write()
+            .unwrap()
+            .insert(name.into(), codec);
+    }
+
+    /// Registers a planner for user-defined logical nodes, which the scheduler
+    /// uses to create the physical plans of the queries it receives.
+    pub fn register_extension_planner(
+        &self,
+        planner: Arc<dyn ExtensionPlanner + Send + Sync>,
+    ) {
+        self.planners.write().unwrap().push(planner);
+    }
+
+    /// The planners registered with
+    /// [`register_extension_planner`](Self::register_extension_planner)
+    pub fn extension_planners(&self) -> Vec<Arc<dyn ExtensionPlanner + Send + Sync>> {
+        self.planners.read().unwrap().clone()
+    }
+
+    /// Encodes the node with the first codec that handles it, returning the name
+    /// of that codec as well
+    pub(crate) fn encode_logical_extension(
+        &self,
+        node: &dyn UserDefinedLogicalNode,
+    ) -> Result<(String, Vec<u8>), BallistaError> {
+        for (name, codec) in self.logical_codecs.read().unwrap().iter() {
+            if let Some(buf) = codec.try_encode(node)? {
+                return Ok((name.clone(), buf));
+            }
+        }
+        Err(BallistaError::NotImplemented(format!(
+            "No codec is registered for the logical extension node {:?}",
+            node
+        )))
+    }
+
+    pub(crate) fn decode_logical_extension(
+        &self,
+        name: &str,
+        buf: &[u8],
+        inputs: Vec<LogicalPlan>,
+    ) -> Result<Arc<dyn UserDefinedLogicalNode + Send + Sync>, BallistaError> {
+        let codec = self
+            .logical_codecs
+            .read()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| {
+                proto_error(format!(
+                    "There is no logical extension codec named {}",
+                    name
+                ))
+            })?;
+        codec.try_decode(buf, inputs)
+    }
+
+    /// Encodes the plan with the first codec that handles it, returning the name
+    /// of that codec as well
+    pub(crate) fn encode_physical_extension(
+        &self,
+        plan: &dyn ExecutionPlan,
+    ) -> Result<(String, Vec<u8>), BallistaError> {
+        for (name, codec) in self.physical_codecs.read().unwrap().iter() {
+            if let Some(buf) = codec.try_encode(plan)? {
+                return Ok((name.clone(), buf));
+            }
+        }
+        Err(BallistaError::General(format!(
+            "physical plan to_proto unsupported plan {:?}",
+            plan
+        )))
+    }
+
+    pub(crate) fn decode_physical_extension(
+        &self,
+        name: &str,
+        buf: &[u8],
+        inputs: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>, BallistaError> {
+        let codec = self
+            .physical_codecs
+            .read()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| {
+                proto_error(format!(
+                    "There is no physical extension codec named {}",
+                    name
+                ))
+            })?;
+        codec.try_decode(buf, inputs)
+    }
+}
+
+impl fmt::Debug for ExtensionCodecs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ExtensionCodecs")
+            .field(
+                "logical_codecs",
+                &self.logical_codecs.read().unwrap().keys(),
+            )
+            .field(
+                "physical_codecs",
+                &self.physical_codecs.read().unwrap().keys(),
+            )
+            .field("planners", &self.planners.read().unwrap().len())
+            .finish()
+    }
+}
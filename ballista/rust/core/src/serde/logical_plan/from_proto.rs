@@ -17,11 +17,12 @@
 
 //! Serde code to convert from protocol buffers to Rust data structures.
 
+use crate::convert_required;
 use crate::error::BallistaError;
+use crate::serde::extension::ExtensionCodecs;
 use crate::serde::{
     from_proto_binary_op, get_by_uri, proto_error, protobuf, str_to_byte,
 };
-use crate::{convert_box_required, convert_required};
 use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use datafusion::datasource::file_format::avro::AvroFormat;
 use datafusion::datasource::file_format::csv::CsvFormat;
@@ -29,6 +30,8 @@ use datafusion::datasource::file_format::parquet::ParquetFormat;
 use datafusion::datasource::file_format::FileFormat;
 use datafusion::datasource::listing::{ListingOptions, ListingTable};
 use datafusion::datasource::object_store::{FileMeta, ObjectStoreRegistry, SizedFile};
+use datafusion::logical_plan::plan::Extension;
+use datafusion::logical_plan::registry::FunctionRegistry;
 use datafusion::logical_plan::window_frames::{
     WindowFrame, WindowFrameBound, WindowFrameUnits,
 };
@@ -50,306 +53,332 @@ use std::{
     unimplemented,
 };
 
-impl TryInto<LogicalPlan> for &protobuf::LogicalPlanNode {
-    type Error = BallistaError;
+/// Converts a protobuf logical plan into a [`LogicalPlan`], looking up the
+/// user-defined functions that it references by name in `registry` and
+/// decoding its user-defined nodes with the codecs registered with `codecs`
+pub fn parse_logical_plan(
+    proto: &protobuf::LogicalPlanNode,
+    registry: &dyn FunctionRegistry,
+    codecs: &ExtensionCodecs,
+) -> Result<LogicalPlan, BallistaError> {
+    let plan = proto.logical_plan_type.as_ref().ok_or_else(|| {
+        proto_error(format!(
+            "logical_plan::from_proto() Unsupported logical plan '{:?}'",
+            proto
+        ))
+    })?;
+    match plan {
+        LogicalPlanType::Values(values) => {
+            let n_cols = values.n_cols as usize;
+            let values: Vec<Vec<Expr>> = if values.values_list.is_empty() {
+                Ok(Vec::new())
+            } else if values.values_list.len() % n_cols != 0 {
+                Err(BallistaError::General(format!(
+                    "Invalid values list length, expect {} to be divisible by {}",
+                    values.values_list.len(),
+                    n_cols
+                )))
+            } else {
+                values
+                    .values_list
+                    .chunks_exact(n_cols)
+                    .map(|r| {
+                        r.iter()
+                            .map(|v| parse_expr(v, registry))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            }?;
+            LogicalPlanBuilder::values(values)?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::Projection(projection) => {
+            let input: LogicalPlan =
+                parse_required_plan(&projection.input, registry, codecs)?;
+            let x: Vec<Expr> = projection
+                .expr
+                .iter()
+                .map(|expr| parse_expr(expr, registry))
+                .collect::<Result<Vec<_>, _>>()?;
+            LogicalPlanBuilder::from(input)
+                .project_with_alias(
+                    x,
+                    projection.optional_alias.as_ref().map(|a| match a {
+                        protobuf::projection_node::OptionalAlias::Alias(alias) => {
+                            alias.clone()
+                        }
+                    }),
+                )?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::Selection(selection) => {
+            let input: LogicalPlan =
+                parse_required_plan(&selection.input, registry, codecs)?;
+            let expr: Expr = parse_expr(
+                selection.expr.as_ref().ok_or_else(|| {
+                    BallistaError::General("expression required".to_string())
+                })?,
+                registry,
+            )?;
+            LogicalPlanBuilder::from(input)
+                .filter(expr)?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::Window(window) => {
+            let input: LogicalPlan =
+                parse_required_plan(&window.input, registry, codecs)?;
+            let window_expr = window
+                .window_expr
+                .iter()
+                .map(|expr| parse_expr(expr, registry))
+                .collect::<Result<Vec<Expr>, _>>()?;
+            LogicalPlanBuilder::from(input)
+                .window(window_expr)?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::Aggregate(aggregate) => {
+            let input: LogicalPlan =
+                parse_required_plan(&aggregate.input, registry, codecs)?;
+            let group_expr = aggregate
+                .group_expr
+                .iter()
+                .map(|expr| parse_expr(expr, registry))
+                .collect::<Result<Vec<Expr>, _>>()?;
+            let aggr_expr = aggregate
+                .aggr_expr
+                .iter()
+                .map(|expr| parse_expr(expr, registry))
+                .collect::<Result<Vec<Expr>, _>>()?;
+            LogicalPlanBuilder::from(input)
+                .aggregate(group_expr, aggr_expr)?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::ListingScan(scan) => {
+            let schema: Schema = convert_required!(scan.schema)?;
 
-    fn try_into(self) -> Result<LogicalPlan, Self::Error> {
-        let plan = self.logical_plan_type.as_ref().ok_or_else(|| {
-            proto_error(format!(
-                "logical_plan::from_proto() Unsupported logical plan '{:?}'",
-                self
-            ))
-        })?;
-        match plan {
-            LogicalPlanType::Values(values) => {
-                let n_cols = values.n_cols as usize;
-                let values: Vec<Vec<Expr>> = if values.values_list.is_empty() {
-                    Ok(Vec::new())
-                } else if values.values_list.len() % n_cols != 0 {
-                    Err(BallistaError::General(format!(
-                        "Invalid values list length, expect {} to be divisible by {}",
-                        values.values_list.len(),
-                        n_cols
-                    )))
-                } else {
-                    values
-                        .values_list
-                        .chunks_exact(n_cols)
-                        .map(|r| {
-                            r.iter()
-                                .map(|v| v.try_into())
-                                .collect::<Result<Vec<_>, _>>()
-                        })
-                        .collect::<Result<Vec<_>, _>>()
-                }?;
-                LogicalPlanBuilder::values(values)?
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::Projection(projection) => {
-                let input: LogicalPlan = convert_box_required!(projection.input)?;
-                let x: Vec<Expr> = projection
-                    .expr
-                    .iter()
-                    .map(|expr| expr.try_into())
-                    .collect::<Result<Vec<_>, _>>()?;
-                LogicalPlanBuilder::from(input)
-                    .project_with_alias(
-                        x,
-                        projection.optional_alias.as_ref().map(|a| match a {
-                            protobuf::projection_node::OptionalAlias::Alias(alias) => {
-                                alias.clone()
-                            }
-                        }),
-                    )?
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::Selection(selection) => {
-                let input: LogicalPlan = convert_box_required!(selection.input)?;
-                let expr: Expr = selection
-                    .expr
-                    .as_ref()
-                    .ok_or_else(|| {
-                        BallistaError::General("expression required".to_string())
-                    })?
-                    .try_into()?;
-                LogicalPlanBuilder::from(input)
-                    .filter(expr)?
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::Window(window) => {
-                let input: LogicalPlan = convert_box_required!(window.input)?;
-                let window_expr = window
-                    .window_expr
-                    .iter()
-                    .map(|expr| expr.try_into())
-                    .collect::<Result<Vec<Expr>, _>>()?;
-                LogicalPlanBuilder::from(input)
-                    .window(window_expr)?
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::Aggregate(aggregate) => {
-                let input: LogicalPlan = convert_box_required!(aggregate.input)?;
-                let group_expr = aggregate
-                    .group_expr
+            let mut projection = None;
+            if let Some(columns) = &scan.projection {
+                let column_indices = columns
+                    .columns
                     .iter()
-                    .map(|expr| expr.try_into())
-                    .collect::<Result<Vec<Expr>, _>>()?;
-                let aggr_expr = aggregate
-                    .aggr_expr
-                    .iter()
-                    .map(|expr| expr.try_into())
-                    .collect::<Result<Vec<Expr>, _>>()?;
-                LogicalPlanBuilder::from(input)
-                    .aggregate(group_expr, aggr_expr)?
-                    .build()
-                    .map_err(|e| e.into())
+                    .map(|name| schema.index_of(name))
+                    .collect::<Result<Vec<usize>, _>>()?;
+                projection = Some(column_indices);
             }
-            LogicalPlanType::ListingScan(scan) => {
-                let schema: Schema = convert_required!(scan.schema)?;
-
-                let mut projection = None;
-                if let Some(columns) = &scan.projection {
-                    let column_indices = columns
-                        .columns
-                        .iter()
-                        .map(|name| schema.index_of(name))
-                        .collect::<Result<Vec<usize>, _>>()?;
-                    projection = Some(column_indices);
-                }
 
-                let filters = scan
-                    .filters
-                    .iter()
-                    .map(|e| e.try_into())
-                    .collect::<Result<Vec<_>, _>>()?;
+            let filters = scan
+                .filters
+                .iter()
+                .map(|e| parse_expr(e, registry))
+                .collect::<Result<Vec<_>, _>>()?;
 
-                let file_format: Arc<dyn FileFormat> =
-                    match scan.file_format_type.as_ref().ok_or_else(|| {
-                        proto_error(format!(
-                            "logical_plan::from_proto() Unsupported file format '{:?}'",
-                            self
-                        ))
-                    })? {
-                        &FileFormatType::Parquet(protobuf::ParquetFormat {
-                            enable_pruning,
-                        }) => Arc::new(
-                            ParquetFormat::default().with_enable_pruning(enable_pruning),
-                        ),
-                        FileFormatType::Csv(protobuf::CsvFormat {
-                            has_header,
-                            delimiter,
-                        }) => Arc::new(
-                            CsvFormat::default()
-                                .with_has_header(*has_header)
-                                .with_delimiter(str_to_byte(delimiter)?),
-                        ),
-                        FileFormatType::Avro(..) => Arc::new(AvroFormat::default()),
-                    };
+            let file_format: Arc<dyn FileFormat> = match scan
+                .file_format_type
+                .as_ref()
+                .ok_or_else(|| {
+                    proto_error(format!(
+                        "logical_plan::from_proto() Unsupported file format '{:?}'",
+                        proto
+                    ))
+                })? {
+                &FileFormatType::Parquet(protobuf::ParquetFormat { enable_pruning }) => {
+                    Arc::new(ParquetFormat::default().with_enable_pruning(enable_pruning))
+                }
+                FileFormatType::Csv(protobuf::CsvFormat {
+                    has_header,
+                    delimiter,
+                }) => Arc::new(
+                    CsvFormat::default()
+                        .with_has_header(*has_header)
+                        .with_delimiter(str_to_byte(delimiter)?),
+                ),
+                FileFormatType::Avro(..) => Arc::new(AvroFormat::default()),
+            };
 
-                let options = ListingOptions {
-                    file_extension: scan.file_extension.clone(),
-                    format: file_format,
-                    table_partition_cols: scan.table_partition_cols.clone(),
-                    collect_stat: scan.collect_stat,
-                    target_partitions: scan.target_partitions as usize,
-                };
+            let options = ListingOptions {
+                file_extension: scan.file_extension.clone(),
+                format: file_format,
+                table_partition_cols: scan.table_partition_cols.clone(),
+                collect_stat: scan.collect_stat,
+                target_partitions: scan.target_partitions as usize,
+                file_sort_order: None,
+            };
 
-                // Get the object store by the uri
-                let (store, relative_path) = get_by_uri(&scan.path).unwrap();
-                let provider = ListingTable::new(
-                    store,
-                    relative_path.to_owned(),
-                    Arc::new(schema),
-                    options,
-                );
+            // Get the object store by the uri
+            let (store, relative_path) = get_by_uri(&scan.path).unwrap();
+            let provider = ListingTable::new(
+                store,
+                relative_path.to_owned(),
+                Arc::new(schema),
+                options,
+            );
 
-                LogicalPlanBuilder::scan_with_filters(
-                    &scan.table_name,
-                    Arc::new(provider),
-                    projection,
-                    filters,
-                )?
+            LogicalPlanBuilder::scan_with_filters(
+                &scan.table_name,
+                Arc::new(provider),
+                projection,
+                filters,
+            )?
+            .build()
+            .map_err(|e| e.into())
+        }
+        LogicalPlanType::Sort(sort) => {
+            let input: LogicalPlan = parse_required_plan(&sort.input, registry, codecs)?;
+            let sort_expr: Vec<Expr> = sort
+                .expr
+                .iter()
+                .map(|expr| parse_expr(expr, registry))
+                .collect::<Result<Vec<Expr>, _>>()?;
+            LogicalPlanBuilder::from(input)
+                .sort(sort_expr)?
                 .build()
                 .map_err(|e| e.into())
-            }
-            LogicalPlanType::Sort(sort) => {
-                let input: LogicalPlan = convert_box_required!(sort.input)?;
-                let sort_expr: Vec<Expr> = sort
-                    .expr
-                    .iter()
-                    .map(|expr| expr.try_into())
-                    .collect::<Result<Vec<Expr>, _>>()?;
-                LogicalPlanBuilder::from(input)
-                    .sort(sort_expr)?
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::Repartition(repartition) => {
-                use datafusion::logical_plan::Partitioning;
-                let input: LogicalPlan = convert_box_required!(repartition.input)?;
-                use protobuf::repartition_node::PartitionMethod;
-                let pb_partition_method = repartition.partition_method.clone().ok_or_else(|| {
+        }
+        LogicalPlanType::Repartition(repartition) => {
+            use datafusion::logical_plan::Partitioning;
+            let input: LogicalPlan =
+                parse_required_plan(&repartition.input, registry, codecs)?;
+            use protobuf::repartition_node::PartitionMethod;
+            let pb_partition_method = repartition.partition_method.clone().ok_or_else(|| {
                     BallistaError::General(String::from(
                         "Protobuf deserialization error, RepartitionNode was missing required field 'partition_method'",
                     ))
                 })?;
 
-                let partitioning_scheme = match pb_partition_method {
-                    PartitionMethod::Hash(protobuf::HashRepartition {
-                        hash_expr: pb_hash_expr,
-                        partition_count,
-                    }) => Partitioning::Hash(
-                        pb_hash_expr
-                            .iter()
-                            .map(|pb_expr| pb_expr.try_into())
-                            .collect::<Result<Vec<_>, _>>()?,
-                        partition_count as usize,
-                    ),
-                    PartitionMethod::RoundRobin(batch_size) => {
-                        Partitioning::RoundRobinBatch(batch_size as usize)
-                    }
-                };
+            let partitioning_scheme = match pb_partition_method {
+                PartitionMethod::Hash(protobuf::HashRepartition {
+                    hash_expr: pb_hash_expr,
+                    partition_count,
+                }) => Partitioning::Hash(
+                    pb_hash_expr
+                        .iter()
+                        .map(|pb_expr| parse_expr(pb_expr, registry))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    partition_count as usize,
+                ),
+                PartitionMethod::RoundRobin(batch_size) => {
+                    Partitioning::RoundRobinBatch(batch_size as usize)
+                }
+            };
 
-                LogicalPlanBuilder::from(input)
-                    .repartition(partitioning_scheme)?
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::EmptyRelation(empty_relation) => {
-                LogicalPlanBuilder::empty(empty_relation.produce_one_row)
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::CreateExternalTable(create_extern_table) => {
-                let pb_schema = (create_extern_table.schema.clone()).ok_or_else(|| {
+            LogicalPlanBuilder::from(input)
+                .repartition(partitioning_scheme)?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::EmptyRelation(empty_relation) => {
+            LogicalPlanBuilder::empty(empty_relation.produce_one_row)
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::CreateExternalTable(create_extern_table) => {
+            let pb_schema = (create_extern_table.schema.clone()).ok_or_else(|| {
                     BallistaError::General(String::from(
                         "Protobuf deserialization error, CreateExternalTableNode was missing required field schema.",
                     ))
                 })?;
 
-                let pb_file_type: protobuf::FileType =
-                    create_extern_table.file_type.try_into()?;
+            let pb_file_type: protobuf::FileType =
+                create_extern_table.file_type.try_into()?;
 
-                Ok(LogicalPlan::CreateExternalTable(CreateExternalTable {
-                    schema: pb_schema.try_into()?,
-                    name: create_extern_table.name.clone(),
-                    location: create_extern_table.location.clone(),
-                    file_type: pb_file_type.into(),
-                    has_header: create_extern_table.has_header,
-                }))
-            }
-            LogicalPlanType::Analyze(analyze) => {
-                let input: LogicalPlan = convert_box_required!(analyze.input)?;
-                LogicalPlanBuilder::from(input)
-                    .explain(analyze.verbose, true)?
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::Explain(explain) => {
-                let input: LogicalPlan = convert_box_required!(explain.input)?;
-                LogicalPlanBuilder::from(input)
-                    .explain(explain.verbose, false)?
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::Limit(limit) => {
-                let input: LogicalPlan = convert_box_required!(limit.input)?;
-                LogicalPlanBuilder::from(input)
-                    .limit(limit.limit as usize)?
-                    .build()
-                    .map_err(|e| e.into())
-            }
-            LogicalPlanType::Join(join) => {
-                let left_keys: Vec<Column> =
-                    join.left_join_column.iter().map(|i| i.into()).collect();
-                let right_keys: Vec<Column> =
-                    join.right_join_column.iter().map(|i| i.into()).collect();
-                let join_type =
-                    protobuf::JoinType::from_i32(join.join_type).ok_or_else(|| {
-                        proto_error(format!(
-                            "Received a JoinNode message with unknown JoinType {}",
-                            join.join_type
-                        ))
-                    })?;
-                let join_constraint = protobuf::JoinConstraint::from_i32(
-                    join.join_constraint,
-                )
-                .ok_or_else(|| {
+            Ok(LogicalPlan::CreateExternalTable(CreateExternalTable {
+                schema: pb_schema.try_into()?,
+                name: create_extern_table.name.clone(),
+                location: create_extern_table.location.clone(),
+                file_type: pb_file_type.into(),
+                has_header: create_extern_table.has_header,
+            }))
+        }
+        LogicalPlanType::Analyze(analyze) => {
+            let input: LogicalPlan =
+                parse_required_plan(&analyze.input, registry, codecs)?;
+            LogicalPlanBuilder::from(input)
+                .explain(analyze.verbose, true)?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::Explain(explain) => {
+            let input: LogicalPlan =
+                parse_required_plan(&explain.input, registry, codecs)?;
+            LogicalPlanBuilder::from(input)
+                .explain(explain.verbose, false)?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::Limit(limit) => {
+            let input: LogicalPlan = parse_required_plan(&limit.input, registry, codecs)?;
+            LogicalPlanBuilder::from(input)
+                .limit(limit.limit as usize)?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::Join(join) => {
+            let left_keys: Vec<Column> =
+                join.left_join_column.iter().map(|i| i.into()).collect();
+            let right_keys: Vec<Column> =
+                join.right_join_column.iter().map(|i| i.into()).collect();
+            let join_type =
+                protobuf::JoinType::from_i32(join.join_type).ok_or_else(|| {
                     proto_error(format!(
-                        "Received a JoinNode message with unknown JoinConstraint {}",
-                        join.join_constraint
+                        "Received a JoinNode message with unknown JoinType {}",
+                        join.join_type
                     ))
                 })?;
+            let join_constraint = protobuf::JoinConstraint::from_i32(
+                join.join_constraint,
+            )
+            .ok_or_else(|| {
+                proto_error(format!(
+                    "Received a JoinNode message with unknown JoinConstraint {}",
+                    join.join_constraint
+                ))
+            })?;
 
-                let builder = LogicalPlanBuilder::from(convert_box_required!(join.left)?);
-                let builder = match join_constraint.into() {
-                    JoinConstraint::On => builder.join(
-                        &convert_box_required!(join.right)?,
-                        join_type.into(),
-                        (left_keys, right_keys),
-                    )?,
-                    JoinConstraint::Using => builder.join_using(
-                        &convert_box_required!(join.right)?,
-                        join_type.into(),
-                        left_keys,
-                    )?,
-                };
+            let builder = LogicalPlanBuilder::from(parse_required_plan(
+                &join.left, registry, codecs,
+            )?);
+            let builder = match join_constraint.into() {
+                JoinConstraint::On => builder.join(
+                    &parse_required_plan(&join.right, registry, codecs)?,
+                    join_type.into(),
+                    (left_keys, right_keys),
+                )?,
+                JoinConstraint::Using => builder.join_using(
+                    &parse_required_plan(&join.right, registry, codecs)?,
+                    join_type.into(),
+                    left_keys,
+                )?,
+            };
 
-                builder.build().map_err(|e| e.into())
-            }
-            LogicalPlanType::CrossJoin(crossjoin) => {
-                let left = convert_box_required!(crossjoin.left)?;
-                let right = convert_box_required!(crossjoin.right)?;
+            builder.build().map_err(|e| e.into())
+        }
+        LogicalPlanType::CrossJoin(crossjoin) => {
+            let left = parse_required_plan(&crossjoin.left, registry, codecs)?;
+            let right = parse_required_plan(&crossjoin.right, registry, codecs)?;
 
-                LogicalPlanBuilder::from(left)
-                    .cross_join(&right)?
-                    .build()
-                    .map_err(|e| e.into())
-            }
+            LogicalPlanBuilder::from(left)
+                .cross_join(&right)?
+                .build()
+                .map_err(|e| e.into())
+        }
+        LogicalPlanType::Extension(extension) => {
+            let inputs = extension
+                .inputs
+                .iter()
+                .map(|input| parse_logical_plan(input, registry, codecs))
+                .collect::<Result<Vec<LogicalPlan>, _>>()?;
+            Ok(LogicalPlan::Extension(Extension {
+                node: codecs.decode_logical_extension(
+                    &extension.codec,
+                    &extension.node,
+                    inputs,
+                )?,
+            }))
         }
     }
 }
@@ -852,47 +881,49 @@ impl TryInto<datafusion::scalar::ScalarValue> for &protobuf::ScalarValue {
     }
 }
 
-impl TryInto<Expr> for &protobuf::LogicalExprNode {
-    type Error = BallistaError;
-
-    fn try_into(self) -> Result<Expr, Self::Error> {
-        use datafusion::physical_plan::window_functions;
-        use protobuf::logical_expr_node::ExprType;
-        use protobuf::window_expr_node;
-        use protobuf::WindowExprNode;
+/// Converts a protobuf logical expression into an [`Expr`], looking up the
+/// user-defined functions that it references by name in `registry`
+pub fn parse_expr(
+    proto: &protobuf::LogicalExprNode,
+    registry: &dyn FunctionRegistry,
+) -> Result<Expr, BallistaError> {
+    use datafusion::physical_plan::window_functions;
+    use protobuf::logical_expr_node::ExprType;
+    use protobuf::window_expr_node;
+    use protobuf::WindowExprNode;
 
-        let expr_type = self
-            .expr_type
-            .as_ref()
-            .ok_or_else(|| proto_error("Unexpected empty logical expression"))?;
-        match expr_type {
-            ExprType::BinaryExpr(binary_expr) => Ok(Expr::BinaryExpr {
-                left: Box::new(parse_required_expr(&binary_expr.l)?),
-                op: from_proto_binary_op(&binary_expr.op)?,
-                right: Box::new(parse_required_expr(&binary_expr.r)?),
-            }),
-            ExprType::Column(column) => Ok(Expr::Column(column.into())),
-            ExprType::Literal(literal) => {
-                use datafusion::scalar::ScalarValue;
-                let scalar_value: datafusion::scalar::ScalarValue = literal.try_into()?;
-                Ok(Expr::Literal(scalar_value))
-            }
-            ExprType::WindowExpr(expr) => {
-                let window_function = expr
-                    .window_function
-                    .as_ref()
-                    .ok_or_else(|| proto_error("Received empty window function"))?;
-                let partition_by = expr
-                    .partition_by
-                    .iter()
-                    .map(|e| e.try_into())
-                    .collect::<Result<Vec<_>, _>>()?;
-                let order_by = expr
-                    .order_by
-                    .iter()
-                    .map(|e| e.try_into())
-                    .collect::<Result<Vec<_>, _>>()?;
-                let window_frame = expr
+    let expr_type = proto
+        .expr_type
+        .as_ref()
+        .ok_or_else(|| proto_error("Unexpected empty logical expression"))?;
+    match expr_type {
+        ExprType::BinaryExpr(binary_expr) => Ok(Expr::BinaryExpr {
+            left: Box::new(parse_required_expr(&binary_expr.l, registry)?),
+            op: from_proto_binary_op(&binary_expr.op)?,
+            right: Box::new(parse_required_expr(&binary_expr.r, registry)?),
+        }),
+        ExprType::Column(column) => Ok(Expr::Column(column.into())),
+        ExprType::Literal(literal) => {
+            use datafusion::scalar::ScalarValue;
+            let scalar_value: datafusion::scalar::ScalarValue = literal.try_into()?;
+            Ok(Expr::Literal(scalar_value))
+        }
+        ExprType::WindowExpr(expr) => {
+            let window_function = expr
+                .window_function
+                .as_ref()
+                .ok_or_else(|| proto_error("Received empty window function"))?;
+            let partition_by = expr
+                .partition_by
+                .iter()
+                .map(|e| parse_expr(e, registry))
+                .collect::<Result<Vec<_>, _>>()?;
+            let order_by = expr
+                .order_by
+                .iter()
+                .map(|e| parse_expr(e, registry))
+                .collect::<Result<Vec<_>, _>>()?;
+            let window_frame = expr
                     .window_frame
                     .as_ref()
                     .map::<Result<WindowFrame, _>, _>(|e| match e {
@@ -909,212 +940,280 @@ impl TryInto<Expr> for &protobuf::LogicalExprNode {
                     })
                     .transpose()?;
 
-                match window_function {
-                    window_expr_node::WindowFunction::AggrFunction(i) => {
-                        let aggr_function = protobuf::AggregateFunction::from_i32(*i)
-                            .ok_or_else(|| {
-                                proto_error(format!(
-                                    "Received an unknown aggregate window function: {}",
-                                    i
-                                ))
-                            })?;
-
-                        Ok(Expr::WindowFunction {
-                            fun: window_functions::WindowFunction::AggregateFunction(
-                                AggregateFunction::from(aggr_function),
-                            ),
-                            args: vec![parse_required_expr(&expr.expr)?],
-                            partition_by,
-                            order_by,
-                            window_frame,
-                        })
-                    }
-                    window_expr_node::WindowFunction::BuiltInFunction(i) => {
-                        let built_in_function =
-                            protobuf::BuiltInWindowFunction::from_i32(*i).ok_or_else(
-                                || {
-                                    proto_error(format!(
-                                        "Received an unknown built-in window function: {}",
-                                        i
-                                    ))
-                                },
-                            )?;
+            match window_function {
+                window_expr_node::WindowFunction::AggrFunction(i) => {
+                    let aggr_function = protobuf::AggregateFunction::from_i32(*i)
+                        .ok_or_else(|| {
+                            proto_error(format!(
+                                "Received an unknown aggregate window function: {}",
+                                i
+                            ))
+                        })?;
 
-                        Ok(Expr::WindowFunction {
-                            fun: window_functions::WindowFunction::BuiltInWindowFunction(
-                                BuiltInWindowFunction::from(built_in_function),
-                            ),
-                            args: vec![parse_required_expr(&expr.expr)?],
-                            partition_by,
-                            order_by,
-                            window_frame,
-                        })
-                    }
+                    Ok(Expr::WindowFunction {
+                        fun: window_functions::WindowFunction::AggregateFunction(
+                            AggregateFunction::from(aggr_function),
+                        ),
+                        args: vec![parse_required_expr(&expr.expr, registry)?],
+                        partition_by,
+                        order_by,
+                        window_frame,
+                    })
                 }
-            }
-            ExprType::AggregateExpr(expr) => {
-                let aggr_function =
-                    protobuf::AggregateFunction::from_i32(expr.aggr_function)
+                window_expr_node::WindowFunction::BuiltInFunction(i) => {
+                    let built_in_function = protobuf::BuiltInWindowFunction::from_i32(*i)
                         .ok_or_else(|| {
                             proto_error(format!(
-                                "Received an unknown aggregate function: {}",
-                                expr.aggr_function
+                                "Received an unknown built-in window function: {}",
+                                i
                             ))
                         })?;
-                let fun = AggregateFunction::from(aggr_function);
 
-                Ok(Expr::AggregateFunction {
-                    fun,
-                    args: vec![parse_required_expr(&expr.expr)?],
-                    distinct: false, //TODO
-                })
-            }
-            ExprType::Alias(alias) => Ok(Expr::Alias(
-                Box::new(parse_required_expr(&alias.expr)?),
-                alias.alias.clone(),
-            )),
-            ExprType::IsNullExpr(is_null) => {
-                Ok(Expr::IsNull(Box::new(parse_required_expr(&is_null.expr)?)))
-            }
-            ExprType::IsNotNullExpr(is_not_null) => Ok(Expr::IsNotNull(Box::new(
-                parse_required_expr(&is_not_null.expr)?,
-            ))),
-            ExprType::NotExpr(not) => {
-                Ok(Expr::Not(Box::new(parse_required_expr(&not.expr)?)))
+                    Ok(Expr::WindowFunction {
+                        fun: window_functions::WindowFunction::BuiltInWindowFunction(
+                            BuiltInWindowFunction::from(built_in_function),
+                        ),
+                        args: vec![parse_required_expr(&expr.expr, registry)?],
+                        partition_by,
+                        order_by,
+                        window_frame,
+                    })
+                }
+                window_expr_node::WindowFunction::Udwf(name) => {
+                    Ok(Expr::WindowFunction {
+                        fun: window_functions::WindowFunction::WindowUDF(
+                            registry.udwf(name)?,
+                        ),
+                        args: parse_optional_expr(&expr.expr, registry)?
+                            .into_iter()
+                            .collect(),
+                        partition_by,
+                        order_by,
+                        window_frame,
+                    })
+                }
             }
-            ExprType::Between(between) => Ok(Expr::Between {
-                expr: Box::new(parse_required_expr(&between.expr)?),
-                negated: between.negated,
-                low: Box::new(parse_required_expr(&between.low)?),
-                high: Box::new(parse_required_expr(&between.high)?),
-            }),
-            ExprType::Case(case) => {
-                let when_then_expr = case
-                    .when_then_expr
+        }
+        ExprType::AggregateExpr(expr) => {
+            let aggr_function = protobuf::AggregateFunction::from_i32(expr.aggr_function)
+                .ok_or_else(|| {
+                    proto_error(format!(
+                        "Received an unknown aggregate function: {}",
+                        expr.aggr_function
+                    ))
+                })?;
+            let fun = AggregateFunction::from(aggr_function);
+
+            Ok(Expr::AggregateFunction {
+                fun,
+                args: vec![parse_required_expr(&expr.expr, registry)?],
+                distinct: expr.distinct,
+                filter: parse_optional_expr(&expr.filter, registry)?.map(Box::new),
+                order_by: expr
+                    .order_by
                     .iter()
-                    .map(|e| {
-                        Ok((
-                            Box::new(match &e.when_expr {
-                                Some(e) => e.try_into(),
-                                None => Err(proto_error("Missing required expression")),
-                            }?),
-                            Box::new(match &e.then_expr {
-                                Some(e) => e.try_into(),
-                                None => Err(proto_error("Missing required expression")),
-                            }?),
-                        ))
-                    })
-                    .collect::<Result<Vec<(Box<Expr>, Box<Expr>)>, BallistaError>>()?;
-                Ok(Expr::Case {
-                    expr: parse_optional_expr(&case.expr)?.map(Box::new),
-                    when_then_expr,
-                    else_expr: parse_optional_expr(&case.else_expr)?.map(Box::new),
+                    .map(|e| parse_expr(e, registry))
+                    .collect::<Result<Vec<_>, _>>()?,
+            })
+        }
+        ExprType::ScalarUdfExpr(expr) => Ok(Expr::ScalarUDF {
+            fun: registry.udf(&expr.fun_name)?,
+            args: expr
+                .args
+                .iter()
+                .map(|e| parse_expr(e, registry))
+                .collect::<Result<Vec<_>, _>>()?,
+        }),
+        ExprType::AggregateUdfExpr(expr) => Ok(Expr::AggregateUDF {
+            fun: registry.udaf(&expr.fun_name)?,
+            args: expr
+                .args
+                .iter()
+                .map(|e| parse_expr(e, registry))
+                .collect::<Result<Vec<_>, _>>()?,
+            distinct: expr.distinct,
+            filter: parse_optional_expr(&expr.filter, registry)?.map(Box::new),
+            order_by: expr
+                .order_by
+                .iter()
+                .map(|e| parse_expr(e, registry))
+                .collect::<Result<Vec<_>, _>>()?,
+        }),
+        ExprType::Alias(alias) => Ok(Expr::Alias(
+            Box::new(parse_required_expr(&alias.expr, registry)?),
+            alias.alias.clone(),
+        )),
+        ExprType::IsNullExpr(is_null) => Ok(Expr::IsNull(Box::new(parse_required_expr(
+            &is_null.expr,
+            registry,
+        )?))),
+        ExprType::IsNotNullExpr(is_not_null) => Ok(Expr::IsNotNull(Box::new(
+            parse_required_expr(&is_not_null.expr, registry)?,
+        ))),
+        ExprType::NotExpr(not) => Ok(Expr::Not(Box::new(parse_required_expr(
+            &not.expr, registry,
+        )?))),
+        ExprType::Between(between) => Ok(Expr::Between {
+            expr: Box::new(parse_required_expr(&between.expr, registry)?),
+            negated: between.negated,
+            low: Box::new(parse_required_expr(&between.low, registry)?),
+            high: Box::new(parse_required_expr(&between.high, registry)?),
+        }),
+        ExprType::Case(case) => {
+            let when_then_expr = case
+                .when_then_expr
+                .iter()
+                .map(|e| {
+                    Ok((
+                        Box::new(match &e.when_expr {
+                            Some(e) => parse_expr(e, registry),
+                            None => Err(proto_error("Missing required expression")),
+                        }?),
+                        Box::new(match &e.then_expr {
+                            Some(e) => parse_expr(e, registry),
+                            None => Err(proto_error("Missing required expression")),
+                        }?),
+                    ))
                 })
-            }
-            ExprType::Cast(cast) => {
-                let expr = Box::new(parse_required_expr(&cast.expr)?);
-                let arrow_type: &protobuf::ArrowType = cast
+                .collect::<Result<Vec<(Box<Expr>, Box<Expr>)>, BallistaError>>()?;
+            Ok(Expr::Case {
+                expr: parse_optional_expr(&case.expr, registry)?.map(Box::new),
+                when_then_expr,
+                else_expr: parse_optional_expr(&case.else_expr, registry)?.map(Box::new),
+            })
+        }
+        ExprType::Cast(cast) => {
+            let expr = Box::new(parse_required_expr(&cast.expr, registry)?);
+            let arrow_type: &protobuf::ArrowType = cast
                     .arrow_type
                     .as_ref()
                     .ok_or_else(|| proto_error("Protobuf deserialization error: CastNode message missing required field 'arrow_type'"))?;
-                let data_type = arrow_type.try_into()?;
-                Ok(Expr::Cast { expr, data_type })
-            }
-            ExprType::TryCast(cast) => {
-                let expr = Box::new(parse_required_expr(&cast.expr)?);
-                let arrow_type: &protobuf::ArrowType = cast
+            let data_type = arrow_type.try_into()?;
+            Ok(Expr::Cast { expr, data_type })
+        }
+        ExprType::TryCast(cast) => {
+            let expr = Box::new(parse_required_expr(&cast.expr, registry)?);
+            let arrow_type: &protobuf::ArrowType = cast
                     .arrow_type
                     .as_ref()
                     .ok_or_else(|| proto_error("Protobuf deserialization error: CastNode message missing required field 'arrow_type'"))?;
-                let data_type = arrow_type.try_into()?;
-                Ok(Expr::TryCast { expr, data_type })
-            }
-            ExprType::Sort(sort) => Ok(Expr::Sort {
-                expr: Box::new(parse_required_expr(&sort.expr)?),
-                asc: sort.asc,
-                nulls_first: sort.nulls_first,
-            }),
-            ExprType::Negative(negative) => Ok(Expr::Negative(Box::new(
-                parse_required_expr(&negative.expr)?,
-            ))),
-            ExprType::InList(in_list) => Ok(Expr::InList {
-                expr: Box::new(parse_required_expr(&in_list.expr)?),
-                list: in_list
-                    .list
-                    .iter()
-                    .map(|expr| expr.try_into())
-                    .collect::<Result<Vec<_>, _>>()?,
-                negated: in_list.negated,
-            }),
-            ExprType::Wildcard(_) => Ok(Expr::Wildcard),
-            ExprType::ScalarFunction(expr) => {
-                let scalar_function = protobuf::ScalarFunction::from_i32(expr.fun)
-                    .ok_or_else(|| {
-                        proto_error(format!(
-                            "Received an unknown scalar function: {}",
-                            expr.fun
-                        ))
-                    })?;
-                let args = &expr.args;
+            let data_type = arrow_type.try_into()?;
+            Ok(Expr::TryCast { expr, data_type })
+        }
+        ExprType::Sort(sort) => Ok(Expr::Sort {
+            expr: Box::new(parse_required_expr(&sort.expr, registry)?),
+            asc: sort.asc,
+            nulls_first: sort.nulls_first,
+        }),
+        ExprType::Negative(negative) => Ok(Expr::Negative(Box::new(
+            parse_required_expr(&negative.expr, registry)?,
+        ))),
+        ExprType::InList(in_list) => Ok(Expr::InList {
+            expr: Box::new(parse_required_expr(&in_list.expr, registry)?),
+            list: in_list
+                .list
+                .iter()
+                .map(|expr| parse_expr(expr, registry))
+                .collect::<Result<Vec<_>, _>>()?,
+            negated: in_list.negated,
+        }),
+        ExprType::Wildcard(_) => Ok(Expr::Wildcard),
+        ExprType::ScalarFunction(expr) => {
+            let scalar_function = protobuf::ScalarFunction::from_i32(expr.fun)
+                .ok_or_else(|| {
+                    proto_error(format!(
+                        "Received an unknown scalar function: {}",
+                        expr.fun
+                    ))
+                })?;
+            let args = &expr.args;
 
-                match scalar_function {
-                    protobuf::ScalarFunction::Sqrt => Ok(sqrt((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Sin => Ok(sin((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Cos => Ok(cos((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Tan => Ok(tan((&args[0]).try_into()?)),
-                    // protobuf::ScalarFunction::Asin => Ok(asin(&args[0]).try_into()?)),
-                    // protobuf::ScalarFunction::Acos => Ok(acos(&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Atan => Ok(atan((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Exp => Ok(exp((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Log2 => Ok(log2((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Ln => Ok(ln((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Log10 => Ok(log10((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Floor => Ok(floor((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Ceil => Ok(ceil((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Round => Ok(round((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Trunc => Ok(trunc((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Abs => Ok(abs((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Signum => {
-                        Ok(signum((&args[0]).try_into()?))
-                    }
-                    protobuf::ScalarFunction::Octetlength => {
-                        Ok(length((&args[0]).try_into()?))
-                    }
-                    // // protobuf::ScalarFunction::Concat => Ok(concat((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Lower => Ok(lower((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Upper => Ok(upper((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Trim => Ok(trim((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Ltrim => Ok(ltrim((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Rtrim => Ok(rtrim((&args[0]).try_into()?)),
-                    // protobuf::ScalarFunction::Totimestamp => Ok(to_timestamp((&args[0]).try_into()?)),
-                    // protobuf::ScalarFunction::Array => Ok(array((&args[0]).try_into()?)),
-                    // // protobuf::ScalarFunction::Nullif => Ok(nulli((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Datepart => {
-                        Ok(date_part((&args[0]).try_into()?, (&args[1]).try_into()?))
-                    }
-                    protobuf::ScalarFunction::Datetrunc => {
-                        Ok(date_trunc((&args[0]).try_into()?, (&args[1]).try_into()?))
-                    }
-                    // protobuf::ScalarFunction::Md5 => Ok(md5((&args[0]).try_into()?)),
-                    protobuf::ScalarFunction::Sha224 => {
-                        Ok(sha224((&args[0]).try_into()?))
-                    }
-                    protobuf::ScalarFunction::Sha256 => {
-                        Ok(sha256((&args[0]).try_into()?))
-                    }
-                    protobuf::ScalarFunction::Sha384 => {
-                        Ok(sha384((&args[0]).try_into()?))
-                    }
-                    protobuf::ScalarFunction::Sha512 => {
-                        Ok(sha512((&args[0]).try_into()?))
-                    }
-                    protobuf::ScalarFunction::Digest => {
-                        Ok(digest((&args[0]).try_into()?, (&args[1]).try_into()?))
-                    }
-                    _ => Err(proto_error(
-                        "Protobuf deserialization error: Unsupported scalar function",
-                    )),
+            match scalar_function {
+                protobuf::ScalarFunction::Sqrt => {
+                    Ok(sqrt(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Sin => Ok(sin(parse_expr(&args[0], registry)?)),
+                protobuf::ScalarFunction::Cos => Ok(cos(parse_expr(&args[0], registry)?)),
+                protobuf::ScalarFunction::Tan => Ok(tan(parse_expr(&args[0], registry)?)),
+                // protobuf::ScalarFunction::Asin => Ok(asin(parse_expr(&args[0], registry)?)),
+                // protobuf::ScalarFunction::Acos => Ok(acos(parse_expr(&args[0], registry)?)),
+                protobuf::ScalarFunction::Atan => {
+                    Ok(atan(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Exp => Ok(exp(parse_expr(&args[0], registry)?)),
+                protobuf::ScalarFunction::Log2 => {
+                    Ok(log2(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Ln => Ok(ln(parse_expr(&args[0], registry)?)),
+                protobuf::ScalarFunction::Log10 => {
+                    Ok(log10(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Floor => {
+                    Ok(floor(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Ceil => {
+                    Ok(ceil(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Round => {
+                    Ok(round(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Trunc => {
+                    Ok(trunc(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Abs => Ok(abs(parse_expr(&args[0], registry)?)),
+                protobuf::ScalarFunction::Signum => {
+                    Ok(signum(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Octetlength => {
+                    Ok(length(parse_expr(&args[0], registry)?))
+                }
+                // // protobuf::ScalarFunction::Concat => Ok(concat(parse_expr(&args[0], registry)?)),
+                protobuf::ScalarFunction::Lower => {
+                    Ok(lower(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Upper => {
+                    Ok(upper(parse_expr(&args[0], registry)?))
                 }
+                protobuf::ScalarFunction::Trim => {
+                    Ok(trim(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Ltrim => {
+                    Ok(ltrim(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Rtrim => {
+                    Ok(rtrim(parse_expr(&args[0], registry)?))
+                }
+                // protobuf::ScalarFunction::Totimestamp => Ok(to_timestamp(parse_expr(&args[0], registry)?)),
+                // protobuf::ScalarFunction::Array => Ok(array(parse_expr(&args[0], registry)?)),
+                // // protobuf::ScalarFunction::Nullif => Ok(nulli(parse_expr(&args[0], registry)?)),
+                protobuf::ScalarFunction::Datepart => Ok(date_part(
+                    parse_expr(&args[0], registry)?,
+                    parse_expr(&args[1], registry)?,
+                )),
+                protobuf::ScalarFunction::Datetrunc => Ok(date_trunc(
+                    parse_expr(&args[0], registry)?,
+                    parse_expr(&args[1], registry)?,
+                )),
+                // protobuf::ScalarFunction::Md5 => Ok(md5(parse_expr(&args[0], registry)?)),
+                protobuf::ScalarFunction::Sha224 => {
+                    Ok(sha224(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Sha256 => {
+                    Ok(sha256(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Sha384 => {
+                    Ok(sha384(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Sha512 => {
+                    Ok(sha512(parse_expr(&args[0], registry)?))
+                }
+                protobuf::ScalarFunction::Digest => Ok(digest(
+                    parse_expr(&args[0], registry)?,
+                    parse_expr(&args[1], registry)?,
+                )),
+                _ => Err(proto_error(
+                    "Protobuf deserialization error: Unsupported scalar function",
+                )),
             }
         }
     }
@@ -1208,20 +1307,33 @@ impl Into<datafusion::sql::parser::FileType> for protobuf::FileType {
     }
 }
 
+fn parse_required_plan(
+    p: &Option<Box<protobuf::LogicalPlanNode>>,
+    registry: &dyn FunctionRegistry,
+    codecs: &ExtensionCodecs,
+) -> Result<LogicalPlan, BallistaError> {
+    match p {
+        Some(plan) => parse_logical_plan(plan, registry, codecs),
+        None => Err(proto_error("Missing required field in protobuf")),
+    }
+}
+
 fn parse_required_expr(
     p: &Option<Box<protobuf::LogicalExprNode>>,
+    registry: &dyn FunctionRegistry,
 ) -> Result<Expr, BallistaError> {
     match p {
-        Some(expr) => expr.as_ref().try_into(),
+        Some(expr) => parse_expr(expr, registry),
         None => Err(proto_error("Missing required expression")),
     }
 }
 
 fn parse_optional_expr(
     p: &Option<Box<protobuf::LogicalExprNode>>,
+    registry: &dyn FunctionRegistry,
 ) -> Result<Option<Expr>, BallistaError> {
     match p {
-        Some(expr) => expr.as_ref().try_into().map(Some),
+        Some(expr) => parse_expr(expr, registry).map(Some),
         None => Ok(None),
     }
 }
@@ -22,41 +22,70 @@ pub mod to_proto;
 mod roundtrip_tests {
 
     use super::super::{super::error::Result, protobuf};
+    use super::from_proto::{parse_expr, parse_logical_plan};
+    use super::to_proto::serialize_logical_plan;
     use crate::error::BallistaError;
+    use crate::serde::extension::{ExtensionCodecs, LogicalExtensionCodec};
     use core::panic;
+    use datafusion::logical_plan::plan::Extension;
     use datafusion::logical_plan::Repartition;
+    use datafusion::logical_plan::{DFSchema, DFSchemaRef, UserDefinedLogicalNode};
     use datafusion::{
         arrow::datatypes::{DataType, Field, IntervalUnit, Schema, TimeUnit},
         datasource::object_store::local::LocalFileSystem,
+        error::DataFusionError,
         logical_plan::{
             col, CreateExternalTable, Expr, LogicalPlan, LogicalPlanBuilder,
             Partitioning, ToDFSchema,
         },
+        logical_plan::{create_udaf, create_udf, create_udwf},
+        physical_plan::aggregates::AccumulatorFunctionImplementation,
         physical_plan::functions::BuiltinScalarFunction::Sqrt,
+        physical_plan::functions::{ScalarFunctionImplementation, Volatility},
+        physical_plan::udwf::PartitionEvaluatorFactory,
+        physical_plan::window_functions::WindowFunction,
         prelude::*,
         scalar::ScalarValue,
         sql::parser::FileType,
     };
     use protobuf::arrow_type;
+    use std::any::Any;
+    use std::fmt;
     use std::{convert::TryInto, sync::Arc};
 
     //Given a identity of a LogicalPlan converts it to protobuf and back, using debug formatting to test equality.
     macro_rules! roundtrip_test {
-        ($initial_struct:ident, $proto_type:ty, $struct_type:ty) => {
-            let proto: $proto_type = (&$initial_struct).try_into()?;
+        ($initial_struct:ident, $codecs:expr) => {
+            let proto: protobuf::LogicalPlanNode =
+                serialize_logical_plan(&$initial_struct, &$codecs)?;
 
-            let round_trip: $struct_type = (&proto).try_into()?;
+            let round_trip: LogicalPlan =
+                parse_logical_plan(&proto, &ExecutionContext::new(), &$codecs)?;
 
             assert_eq!(
                 format!("{:?}", $initial_struct),
                 format!("{:?}", round_trip)
             );
         };
-        ($initial_struct:ident, $struct_type:ty) => {
-            roundtrip_test!($initial_struct, protobuf::LogicalPlanNode, $struct_type);
+        ($initial_struct:ident) => {
+            roundtrip_test!($initial_struct, ExtensionCodecs::new());
+        };
+    }
+
+    //Given a identity of an Expr converts it to protobuf and back, looking up its functions in the given context.
+    macro_rules! roundtrip_expr_test {
+        ($initial_struct:ident, $ctx:expr) => {
+            let proto: protobuf::LogicalExprNode = (&$initial_struct).try_into()?;
+
+            let round_trip: Expr = parse_expr(&proto, &$ctx)?;
+
+            assert_eq!(
+                format!("{:?}", $initial_struct),
+                format!("{:?}", round_trip)
+            );
         };
         ($initial_struct:ident) => {
-            roundtrip_test!($initial_struct, protobuf::LogicalPlanNode, LogicalPlan);
+            roundtrip_expr_test!($initial_struct, ExecutionContext::new());
         };
     }
 
@@ -869,7 +898,7 @@ mod roundtrip_tests {
     fn roundtrip_not() -> Result<()> {
         let test_expr = Expr::Not(Box::new(Expr::Literal((1.0).into())));
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -878,7 +907,7 @@ mod roundtrip_tests {
     fn roundtrip_is_null() -> Result<()> {
         let test_expr = Expr::IsNull(Box::new(col("id")));
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -887,7 +916,7 @@ mod roundtrip_tests {
     fn roundtrip_is_not_null() -> Result<()> {
         let test_expr = Expr::IsNotNull(Box::new(col("id")));
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -901,7 +930,7 @@ mod roundtrip_tests {
             high: Box::new(Expr::Literal((3.0).into())),
         };
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -917,7 +946,7 @@ mod roundtrip_tests {
             else_expr: Some(Box::new(Expr::Literal((4.0).into()))),
         };
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -929,7 +958,7 @@ mod roundtrip_tests {
             data_type: DataType::Boolean,
         };
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -942,7 +971,7 @@ mod roundtrip_tests {
             nulls_first: true,
         };
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -951,7 +980,7 @@ mod roundtrip_tests {
     fn roundtrip_negative() -> Result<()> {
         let test_expr = Expr::Negative(Box::new(Expr::Literal((1.0).into())));
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -964,7 +993,7 @@ mod roundtrip_tests {
             negated: true,
         };
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -973,7 +1002,7 @@ mod roundtrip_tests {
     fn roundtrip_wildcard() -> Result<()> {
         let test_expr = Expr::Wildcard;
 
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
 
         Ok(())
     }
@@ -984,8 +1013,252 @@ mod roundtrip_tests {
             fun: Sqrt,
             args: vec![col("col")],
         };
-        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+        roundtrip_expr_test!(test_expr);
+
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_udwf() -> Result<()> {
+        let partition_evaluator: PartitionEvaluatorFactory =
+            Arc::new(|| Err(DataFusionError::NotImplemented("dummy_window".to_owned())));
+        let udwf = create_udwf(
+            "dummy_window",
+            vec![DataType::Int64],
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            partition_evaluator,
+        );
+        let mut ctx = ExecutionContext::new();
+        ctx.register_udwf(udwf.clone());
+
+        let test_expr = Expr::WindowFunction {
+            fun: WindowFunction::WindowUDF(Arc::new(udwf)),
+            args: vec![col("a")],
+            partition_by: vec![col("b")],
+            order_by: vec![col("c").sort(true, true)],
+            window_frame: None,
+        };
+        roundtrip_expr_test!(test_expr, ctx);
+
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_udf_and_udaf() -> Result<()> {
+        let fun: ScalarFunctionImplementation =
+            Arc::new(|_| Err(DataFusionError::NotImplemented("dummy_udf".to_owned())));
+        let udf = create_udf(
+            "dummy_udf",
+            vec![DataType::Int64],
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            fun,
+        );
+        let mut ctx = ExecutionContext::new();
+        ctx.register_udf(udf.clone());
+
+        let accumulator: AccumulatorFunctionImplementation =
+            Arc::new(|| Err(DataFusionError::NotImplemented("dummy_udaf".to_owned())));
+        let udaf = create_udaf(
+            "dummy_udaf",
+            DataType::Int64,
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            accumulator,
+            Arc::new(vec![DataType::Int64]),
+        );
+        ctx.register_udaf(udaf.clone());
+
+        let test_expr = udaf.call(vec![udf.call(vec![col("a")])]);
+        roundtrip_expr_test!(test_expr, ctx);
+
+        let distinct_expr = Expr::AggregateUDF {
+            fun: Arc::new(udaf),
+            args: vec![col("a")],
+            distinct: true,
+            filter: Some(Box::new(col("b").is_not_null())),
+            order_by: vec![],
+        };
+        roundtrip_expr_test!(distinct_expr, ctx);
+
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_aggregate_clauses() -> Result<()> {
+        let test_expr = Expr::AggregateFunction {
+            fun: datafusion::physical_plan::aggregates::AggregateFunction::ArrayAgg,
+            args: vec![col("a")],
+            distinct: false,
+            filter: Some(Box::new(col("b").gt(lit(1)))),
+            order_by: vec![col("c").sort(false, true)],
+        };
+        roundtrip_expr_test!(test_expr);
+
+        let distinct_expr = Expr::AggregateFunction {
+            fun: datafusion::physical_plan::aggregates::AggregateFunction::Count,
+            args: vec![col("a")],
+            distinct: true,
+            filter: None,
+            order_by: vec![],
+        };
+        roundtrip_expr_test!(distinct_expr);
 
         Ok(())
     }
+
+    #[test]
+    fn roundtrip_unregistered_udf() -> Result<()> {
+        let fun: ScalarFunctionImplementation =
+            Arc::new(|_| Err(DataFusionError::NotImplemented("unknown".to_owned())));
+        let udf = create_udf(
+            "unregistered_udf",
+            vec![DataType::Int64],
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            fun,
+        );
+
+        let proto: protobuf::LogicalExprNode = (&udf.call(vec![col("a")])).try_into()?;
+        let result = parse_expr(&proto, &ExecutionContext::new());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("There is no UDF named \"unregistered_udf\" in the registry"));
+
+        Ok(())
+    }
+
+    #[derive(Debug)]
+    struct TopKNode {
+        k: usize,
+        input: LogicalPlan,
+    }
+
+    impl UserDefinedLogicalNode for TopKNode {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn inputs(&self) -> Vec<&LogicalPlan> {
+            vec![&self.input]
+        }
+
+        fn schema(&self) -> &DFSchemaRef {
+            self.input.schema()
+        }
+
+        fn expressions(&self) -> Vec<Expr> {
+            vec![]
+        }
+
+        fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "TopK: k={}", self.k)
+        }
+
+        fn from_template(
+            &self,
+            _exprs: &[Expr],
+            inputs: &[LogicalPlan],
+        ) -> Arc<dyn UserDefinedLogicalNode + Send + Sync> {
+            Arc::new(TopKNode {
+                k: self.k,
+                input: inputs[0].clone(),
+            })
+        }
+    }
+
+    struct TopKCodec {}
+
+    impl LogicalExtensionCodec for TopKCodec {
+        fn try_encode(
+            &self,
+            node: &dyn UserDefinedLogicalNode,
+        ) -> Result<Option<Vec<u8>>> {
+            Ok(node
+                .as_any()
+                .downcast_ref::<TopKNode>()
+                .map(|node| (node.k as u64).to_le_bytes().to_vec()))
+        }
+
+        fn try_decode(
+            &self,
+            buf: &[u8],
+            mut inputs: Vec<LogicalPlan>,
+        ) -> Result<Arc<dyn UserDefinedLogicalNode + Send + Sync>> {
+            let k = buf
+                .try_into()
+                .map_err(|_| BallistaError::General("Invalid TopK node".to_owned()))?;
+            Ok(Arc::new(TopKNode {
+                k: u64::from_le_bytes(k) as usize,
+                input: inputs.remove(0),
+            }))
+        }
+    }
+
+    #[test]
+    fn roundtrip_extension() -> Result<()> {
+        let codecs = ExtensionCodecs::new();
+        codecs.register_logical_extension_codec("topk", Arc::new(TopKCodec {}));
+
+        let input = LogicalPlanBuilder::empty(true).build()?;
+        let plan = LogicalPlan::Extension(Extension {
+            node: Arc::new(TopKNode { k: 3, input }),
+        });
+        roundtrip_test!(plan, codecs);
+
+        Ok(())
+    }
+
+    #[derive(Debug)]
+    struct UnknownNode {
+        schema: DFSchemaRef,
+    }
+
+    impl UserDefinedLogicalNode for UnknownNode {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn inputs(&self) -> Vec<&LogicalPlan> {
+            vec![]
+        }
+
+        fn schema(&self) -> &DFSchemaRef {
+            &self.schema
+        }
+
+        fn expressions(&self) -> Vec<Expr> {
+            vec![]
+        }
+
+        fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Unknown")
+        }
+
+        fn from_template(
+            &self,
+            _exprs: &[Expr],
+            _inputs: &[LogicalPlan],
+        ) -> Arc<dyn UserDefinedLogicalNode + Send + Sync> {
+            Arc::new(UnknownNode {
+                schema: self.schema.clone(),
+            })
+        }
+    }
+
+    #[test]
+    fn extension_without_codec() {
+        let plan = LogicalPlan::Extension(Extension {
+            node: Arc::new(UnknownNode {
+                schema: Arc::new(DFSchema::empty()),
+            }),
+        });
+        let result = serialize_logical_plan(&plan, &ExtensionCodecs::new());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("No codec is registered for the logical extension node"));
+    }
 }
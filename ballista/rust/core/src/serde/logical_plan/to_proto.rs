@@ -20,6 +20,7 @@
 //! processes.
 
 use super::super::proto_error;
+use crate::serde::extension::ExtensionCodecs;
 use crate::serde::{byte_to_string, protobuf, BallistaError};
 use datafusion::arrow::datatypes::{
     DataType, Field, IntervalUnit, Schema, SchemaRef, TimeUnit,
@@ -565,352 +566,379 @@ impl TryFrom<&datafusion::scalar::ScalarValue> for protobuf::ScalarValue {
     }
 }
 
-impl TryInto<protobuf::LogicalPlanNode> for &LogicalPlan {
-    type Error = BallistaError;
+/// Converts a logical plan into its protobuf representation, encoding the
+/// user-defined nodes with the codecs registered with `codecs`
+pub fn serialize_logical_plan(
+    plan: &LogicalPlan,
+    codecs: &ExtensionCodecs,
+) -> Result<protobuf::LogicalPlanNode, BallistaError> {
+    use protobuf::logical_plan_node::LogicalPlanType;
+    match plan {
+        LogicalPlan::Values(Values { values, .. }) => {
+            let n_cols = if values.is_empty() {
+                0
+            } else {
+                values[0].len()
+            } as u64;
+            let values_list = values
+                .iter()
+                .flatten()
+                .map(|v| v.try_into())
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Values(protobuf::ValuesNode {
+                    n_cols,
+                    values_list,
+                })),
+            })
+        }
+        LogicalPlan::TableScan(TableScan {
+            table_name,
+            source,
+            filters,
+            projection,
+            ..
+        }) => {
+            let schema = source.schema();
+            let source = source.as_any();
 
-    fn try_into(self) -> Result<protobuf::LogicalPlanNode, Self::Error> {
-        use protobuf::logical_plan_node::LogicalPlanType;
-        match self {
-            LogicalPlan::Values(Values { values, .. }) => {
-                let n_cols = if values.is_empty() {
-                    0
+            let projection = match projection {
+                None => None,
+                Some(columns) => {
+                    let column_names = columns
+                        .iter()
+                        .map(|i| schema.field(*i).name().to_owned())
+                        .collect();
+                    Some(protobuf::ProjectionColumns {
+                        columns: column_names,
+                    })
+                }
+            };
+            let schema: protobuf::Schema = schema.as_ref().into();
+
+            let filters: Vec<protobuf::LogicalExprNode> = filters
+                .iter()
+                .map(|filter| filter.try_into())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if let Some(listing_table) = source.downcast_ref::<ListingTable>() {
+                let any = listing_table.options().format.as_any();
+                let file_format_type = if let Some(parquet) =
+                    any.downcast_ref::<ParquetFormat>()
+                {
+                    FileFormatType::Parquet(protobuf::ParquetFormat {
+                        enable_pruning: parquet.enable_pruning(),
+                    })
+                } else if let Some(csv) = any.downcast_ref::<CsvFormat>() {
+                    FileFormatType::Csv(protobuf::CsvFormat {
+                        delimiter: byte_to_string(csv.delimiter())?,
+                        has_header: csv.has_header(),
+                    })
+                } else if any.is::<AvroFormat>() {
+                    FileFormatType::Avro(protobuf::AvroFormat {})
                 } else {
-                    values[0].len()
-                } as u64;
-                let values_list = values
-                    .iter()
-                    .flatten()
-                    .map(|v| v.try_into())
-                    .collect::<Result<Vec<_>, _>>()?;
+                    return Err(proto_error(format!(
+                            "Error converting file format, {:?} is invalid as a datafusion foramt.",
+                            listing_table.options().format
+                        )));
+                };
                 Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Values(
-                        protobuf::ValuesNode {
-                            n_cols,
-                            values_list,
+                    logical_plan_type: Some(LogicalPlanType::ListingScan(
+                        protobuf::ListingTableScanNode {
+                            file_format_type: Some(file_format_type),
+                            table_name: table_name.to_owned(),
+                            collect_stat: listing_table.options().collect_stat,
+                            file_extension: listing_table
+                                .options()
+                                .file_extension
+                                .clone(),
+                            table_partition_cols: listing_table
+                                .options()
+                                .table_partition_cols
+                                .clone(),
+                            path: listing_table.table_full_path(),
+                            schema: Some(schema),
+                            projection,
+                            filters,
+                            target_partitions: listing_table.options().target_partitions
+                                as u32,
                         },
                     )),
                 })
+            } else {
+                Err(BallistaError::General(format!(
+                    "logical plan to_proto unsupported table provider {:?}",
+                    source
+                )))
             }
-            LogicalPlan::TableScan(TableScan {
-                table_name,
-                source,
-                filters,
-                projection,
-                ..
-            }) => {
-                let schema = source.schema();
-                let source = source.as_any();
-
-                let projection = match projection {
-                    None => None,
-                    Some(columns) => {
-                        let column_names = columns
+        }
+        LogicalPlan::Projection(Projection {
+            expr, input, alias, ..
+        }) => Ok(protobuf::LogicalPlanNode {
+            logical_plan_type: Some(LogicalPlanType::Projection(Box::new(
+                protobuf::ProjectionNode {
+                    input: Some(Box::new(serialize_logical_plan(
+                        input.as_ref(),
+                        codecs,
+                    )?)),
+                    expr: expr.iter().map(|expr| expr.try_into()).collect::<Result<
+                        Vec<_>,
+                        BallistaError,
+                    >>(
+                    )?,
+                    optional_alias: alias
+                        .clone()
+                        .map(protobuf::projection_node::OptionalAlias::Alias),
+                },
+            ))),
+        }),
+        LogicalPlan::Filter(Filter { predicate, input }) => {
+            let input = serialize_logical_plan(input.as_ref(), codecs)?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Selection(Box::new(
+                    protobuf::SelectionNode {
+                        input: Some(Box::new(input)),
+                        expr: Some(predicate.try_into()?),
+                    },
+                ))),
+            })
+        }
+        LogicalPlan::Window(Window {
+            input, window_expr, ..
+        }) => {
+            let input = serialize_logical_plan(input.as_ref(), codecs)?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Window(Box::new(
+                    protobuf::WindowNode {
+                        input: Some(Box::new(input)),
+                        window_expr: window_expr
                             .iter()
-                            .map(|i| schema.field(*i).name().to_owned())
-                            .collect();
-                        Some(protobuf::ProjectionColumns {
-                            columns: column_names,
-                        })
-                    }
-                };
-                let schema: protobuf::Schema = schema.as_ref().into();
+                            .map(|expr| expr.try_into())
+                            .collect::<Result<Vec<_>, _>>()?,
+                    },
+                ))),
+            })
+        }
+        LogicalPlan::Aggregate(Aggregate {
+            group_expr,
+            aggr_expr,
+            input,
+            ..
+        }) => {
+            let input = serialize_logical_plan(input.as_ref(), codecs)?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Aggregate(Box::new(
+                    protobuf::AggregateNode {
+                        input: Some(Box::new(input)),
+                        group_expr: group_expr
+                            .iter()
+                            .map(|expr| expr.try_into())
+                            .collect::<Result<Vec<_>, _>>()?,
+                        aggr_expr: aggr_expr
+                            .iter()
+                            .map(|expr| expr.try_into())
+                            .collect::<Result<Vec<_>, _>>()?,
+                    },
+                ))),
+            })
+        }
+        LogicalPlan::Join(Join {
+            left,
+            right,
+            on,
+            join_type,
+            join_constraint,
+            null_equals_null,
+            ..
+        }) => {
+            let left = serialize_logical_plan(left.as_ref(), codecs)?;
+            let right = serialize_logical_plan(right.as_ref(), codecs)?;
+            let (left_join_column, right_join_column) =
+                on.iter().map(|(l, r)| (l.into(), r.into())).unzip();
+            let join_type: protobuf::JoinType = join_type.to_owned().into();
+            let join_constraint: protobuf::JoinConstraint =
+                join_constraint.to_owned().into();
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Join(Box::new(
+                    protobuf::JoinNode {
+                        left: Some(Box::new(left)),
+                        right: Some(Box::new(right)),
+                        join_type: join_type.into(),
+                        join_constraint: join_constraint.into(),
+                        left_join_column,
+                        right_join_column,
+                        null_equals_null: *null_equals_null,
+                    },
+                ))),
+            })
+        }
+        LogicalPlan::Limit(Limit { input, n }) => {
+            let input = serialize_logical_plan(input.as_ref(), codecs)?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Limit(Box::new(
+                    protobuf::LimitNode {
+                        input: Some(Box::new(input)),
+                        limit: *n as u32,
+                    },
+                ))),
+            })
+        }
+        LogicalPlan::Sort(Sort { input, expr }) => {
+            let input = serialize_logical_plan(input.as_ref(), codecs)?;
+            let selection_expr: Vec<protobuf::LogicalExprNode> = expr
+                .iter()
+                .map(|expr| expr.try_into())
+                .collect::<Result<Vec<_>, BallistaError>>()?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Sort(Box::new(
+                    protobuf::SortNode {
+                        input: Some(Box::new(input)),
+                        expr: selection_expr,
+                    },
+                ))),
+            })
+        }
+        LogicalPlan::Repartition(Repartition {
+            input,
+            partitioning_scheme,
+        }) => {
+            use datafusion::logical_plan::Partitioning;
+            let input = serialize_logical_plan(input.as_ref(), codecs)?;
 
-                let filters: Vec<protobuf::LogicalExprNode> = filters
-                    .iter()
-                    .map(|filter| filter.try_into())
-                    .collect::<Result<Vec<_>, _>>()?;
+            //Assumed common usize field was batch size
+            //Used u64 to avoid any nastyness involving large values, most data clusters are probably uniformly 64 bits any ways
+            use protobuf::repartition_node::PartitionMethod;
 
-                if let Some(listing_table) = source.downcast_ref::<ListingTable>() {
-                    let any = listing_table.options().format.as_any();
-                    let file_format_type = if let Some(parquet) =
-                        any.downcast_ref::<ParquetFormat>()
-                    {
-                        FileFormatType::Parquet(protobuf::ParquetFormat {
-                            enable_pruning: parquet.enable_pruning(),
-                        })
-                    } else if let Some(csv) = any.downcast_ref::<CsvFormat>() {
-                        FileFormatType::Csv(protobuf::CsvFormat {
-                            delimiter: byte_to_string(csv.delimiter())?,
-                            has_header: csv.has_header(),
-                        })
-                    } else if any.is::<AvroFormat>() {
-                        FileFormatType::Avro(protobuf::AvroFormat {})
-                    } else {
-                        return Err(proto_error(format!(
-                            "Error converting file format, {:?} is invalid as a datafusion foramt.",
-                            listing_table.options().format
-                        )));
-                    };
-                    Ok(protobuf::LogicalPlanNode {
-                        logical_plan_type: Some(LogicalPlanType::ListingScan(
-                            protobuf::ListingTableScanNode {
-                                file_format_type: Some(file_format_type),
-                                table_name: table_name.to_owned(),
-                                collect_stat: listing_table.options().collect_stat,
-                                file_extension: listing_table
-                                    .options()
-                                    .file_extension
-                                    .clone(),
-                                table_partition_cols: listing_table
-                                    .options()
-                                    .table_partition_cols
-                                    .clone(),
-                                path: listing_table.table_full_path(),
-                                schema: Some(schema),
-                                projection,
-                                filters,
-                                target_partitions: listing_table
-                                    .options()
-                                    .target_partitions
-                                    as u32,
-                            },
-                        )),
+            let pb_partition_method = match partitioning_scheme {
+                Partitioning::Hash(exprs, partition_count) => {
+                    PartitionMethod::Hash(protobuf::HashRepartition {
+                        hash_expr: exprs
+                            .iter()
+                            .map(|expr| expr.try_into())
+                            .collect::<Result<Vec<_>, BallistaError>>()?,
+                        partition_count: *partition_count as u64,
                     })
-                } else {
-                    Err(BallistaError::General(format!(
-                        "logical plan to_proto unsupported table provider {:?}",
-                        source
-                    )))
                 }
-            }
-            LogicalPlan::Projection(Projection {
-                expr, input, alias, ..
-            }) => Ok(protobuf::LogicalPlanNode {
-                logical_plan_type: Some(LogicalPlanType::Projection(Box::new(
-                    protobuf::ProjectionNode {
-                        input: Some(Box::new(input.as_ref().try_into()?)),
-                        expr: expr.iter().map(|expr| expr.try_into()).collect::<Result<
-                            Vec<_>,
-                            BallistaError,
-                        >>(
-                        )?,
-                        optional_alias: alias
-                            .clone()
-                            .map(protobuf::projection_node::OptionalAlias::Alias),
+                Partitioning::RoundRobinBatch(batch_size) => {
+                    PartitionMethod::RoundRobin(*batch_size as u64)
+                }
+            };
+
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Repartition(Box::new(
+                    protobuf::RepartitionNode {
+                        input: Some(Box::new(input)),
+                        partition_method: Some(pb_partition_method),
                     },
                 ))),
-            }),
-            LogicalPlan::Filter(Filter { predicate, input }) => {
-                let input: protobuf::LogicalPlanNode = input.as_ref().try_into()?;
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Selection(Box::new(
-                        protobuf::SelectionNode {
-                            input: Some(Box::new(input)),
-                            expr: Some(predicate.try_into()?),
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::Window(Window {
-                input, window_expr, ..
-            }) => {
-                let input: protobuf::LogicalPlanNode = input.as_ref().try_into()?;
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Window(Box::new(
-                        protobuf::WindowNode {
-                            input: Some(Box::new(input)),
-                            window_expr: window_expr
-                                .iter()
-                                .map(|expr| expr.try_into())
-                                .collect::<Result<Vec<_>, _>>()?,
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::Aggregate(Aggregate {
-                group_expr,
-                aggr_expr,
-                input,
-                ..
-            }) => {
-                let input: protobuf::LogicalPlanNode = input.as_ref().try_into()?;
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Aggregate(Box::new(
-                        protobuf::AggregateNode {
-                            input: Some(Box::new(input)),
-                            group_expr: group_expr
-                                .iter()
-                                .map(|expr| expr.try_into())
-                                .collect::<Result<Vec<_>, _>>()?,
-                            aggr_expr: aggr_expr
-                                .iter()
-                                .map(|expr| expr.try_into())
-                                .collect::<Result<Vec<_>, _>>()?,
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::Join(Join {
-                left,
-                right,
-                on,
-                join_type,
-                join_constraint,
-                null_equals_null,
-                ..
-            }) => {
-                let left: protobuf::LogicalPlanNode = left.as_ref().try_into()?;
-                let right: protobuf::LogicalPlanNode = right.as_ref().try_into()?;
-                let (left_join_column, right_join_column) =
-                    on.iter().map(|(l, r)| (l.into(), r.into())).unzip();
-                let join_type: protobuf::JoinType = join_type.to_owned().into();
-                let join_constraint: protobuf::JoinConstraint =
-                    join_constraint.to_owned().into();
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Join(Box::new(
-                        protobuf::JoinNode {
-                            left: Some(Box::new(left)),
-                            right: Some(Box::new(right)),
-                            join_type: join_type.into(),
-                            join_constraint: join_constraint.into(),
-                            left_join_column,
-                            right_join_column,
-                            null_equals_null: *null_equals_null,
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::Limit(Limit { input, n }) => {
-                let input: protobuf::LogicalPlanNode = input.as_ref().try_into()?;
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Limit(Box::new(
-                        protobuf::LimitNode {
-                            input: Some(Box::new(input)),
-                            limit: *n as u32,
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::Sort(Sort { input, expr }) => {
-                let input: protobuf::LogicalPlanNode = input.as_ref().try_into()?;
-                let selection_expr: Vec<protobuf::LogicalExprNode> = expr
-                    .iter()
-                    .map(|expr| expr.try_into())
-                    .collect::<Result<Vec<_>, BallistaError>>()?;
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Sort(Box::new(
-                        protobuf::SortNode {
-                            input: Some(Box::new(input)),
-                            expr: selection_expr,
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::Repartition(Repartition {
-                input,
-                partitioning_scheme,
-            }) => {
-                use datafusion::logical_plan::Partitioning;
-                let input: protobuf::LogicalPlanNode = input.as_ref().try_into()?;
-
-                //Assumed common usize field was batch size
-                //Used u64 to avoid any nastyness involving large values, most data clusters are probably uniformly 64 bits any ways
-                use protobuf::repartition_node::PartitionMethod;
+            })
+        }
+        LogicalPlan::EmptyRelation(EmptyRelation {
+            produce_one_row, ..
+        }) => Ok(protobuf::LogicalPlanNode {
+            logical_plan_type: Some(LogicalPlanType::EmptyRelation(
+                protobuf::EmptyRelationNode {
+                    produce_one_row: *produce_one_row,
+                },
+            )),
+        }),
+        LogicalPlan::CreateExternalTable(CreateExternalTable {
+            name,
+            location,
+            file_type,
+            has_header,
+            schema: df_schema,
+        }) => {
+            use datafusion::sql::parser::FileType;
 
-                let pb_partition_method = match partitioning_scheme {
-                    Partitioning::Hash(exprs, partition_count) => {
-                        PartitionMethod::Hash(protobuf::HashRepartition {
-                            hash_expr: exprs
-                                .iter()
-                                .map(|expr| expr.try_into())
-                                .collect::<Result<Vec<_>, BallistaError>>()?,
-                            partition_count: *partition_count as u64,
-                        })
-                    }
-                    Partitioning::RoundRobinBatch(batch_size) => {
-                        PartitionMethod::RoundRobin(*batch_size as u64)
-                    }
-                };
+            let pb_file_type: protobuf::FileType = match file_type {
+                FileType::NdJson => protobuf::FileType::NdJson,
+                FileType::Parquet => protobuf::FileType::Parquet,
+                FileType::CSV => protobuf::FileType::Csv,
+                FileType::Avro => protobuf::FileType::Avro,
+            };
 
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Repartition(Box::new(
-                        protobuf::RepartitionNode {
-                            input: Some(Box::new(input)),
-                            partition_method: Some(pb_partition_method),
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::EmptyRelation(EmptyRelation {
-                produce_one_row, ..
-            }) => Ok(protobuf::LogicalPlanNode {
-                logical_plan_type: Some(LogicalPlanType::EmptyRelation(
-                    protobuf::EmptyRelationNode {
-                        produce_one_row: *produce_one_row,
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::CreateExternalTable(
+                    protobuf::CreateExternalTableNode {
+                        name: name.clone(),
+                        location: location.clone(),
+                        file_type: pb_file_type as i32,
+                        has_header: *has_header,
+                        schema: Some(df_schema.into()),
                     },
                 )),
-            }),
-            LogicalPlan::CreateExternalTable(CreateExternalTable {
-                name,
-                location,
-                file_type,
-                has_header,
-                schema: df_schema,
-            }) => {
-                use datafusion::sql::parser::FileType;
-
-                let pb_file_type: protobuf::FileType = match file_type {
-                    FileType::NdJson => protobuf::FileType::NdJson,
-                    FileType::Parquet => protobuf::FileType::Parquet,
-                    FileType::CSV => protobuf::FileType::Csv,
-                    FileType::Avro => protobuf::FileType::Avro,
-                };
-
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::CreateExternalTable(
-                        protobuf::CreateExternalTableNode {
-                            name: name.clone(),
-                            location: location.clone(),
-                            file_type: pb_file_type as i32,
-                            has_header: *has_header,
-                            schema: Some(df_schema.into()),
-                        },
-                    )),
-                })
-            }
-            LogicalPlan::Analyze(a) => {
-                let input: protobuf::LogicalPlanNode = a.input.as_ref().try_into()?;
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Analyze(Box::new(
-                        protobuf::AnalyzeNode {
-                            input: Some(Box::new(input)),
-                            verbose: a.verbose,
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::Explain(a) => {
-                let input: protobuf::LogicalPlanNode = a.plan.as_ref().try_into()?;
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::Explain(Box::new(
-                        protobuf::ExplainNode {
-                            input: Some(Box::new(input)),
-                            verbose: a.verbose,
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::Extension { .. } => unimplemented!(),
-            LogicalPlan::Union(_) => unimplemented!(),
-            LogicalPlan::CrossJoin(CrossJoin { left, right, .. }) => {
-                let left: protobuf::LogicalPlanNode = left.as_ref().try_into()?;
-                let right: protobuf::LogicalPlanNode = right.as_ref().try_into()?;
-                Ok(protobuf::LogicalPlanNode {
-                    logical_plan_type: Some(LogicalPlanType::CrossJoin(Box::new(
-                        protobuf::CrossJoinNode {
-                            left: Some(Box::new(left)),
-                            right: Some(Box::new(right)),
-                        },
-                    ))),
-                })
-            }
-            LogicalPlan::CreateMemoryTable(_) => Err(proto_error(
-                "Error converting CreateMemoryTable. Not yet supported in Ballista",
-            )),
-            LogicalPlan::DropTable(_) => Err(proto_error(
-                "Error converting DropTable. Not yet supported in Ballista",
-            )),
+            })
         }
+        LogicalPlan::Analyze(a) => {
+            let input = serialize_logical_plan(a.input.as_ref(), codecs)?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Analyze(Box::new(
+                    protobuf::AnalyzeNode {
+                        input: Some(Box::new(input)),
+                        verbose: a.verbose,
+                    },
+                ))),
+            })
+        }
+        LogicalPlan::Explain(a) => {
+            let input = serialize_logical_plan(a.plan.as_ref(), codecs)?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Explain(Box::new(
+                    protobuf::ExplainNode {
+                        input: Some(Box::new(input)),
+                        verbose: a.verbose,
+                    },
+                ))),
+            })
+        }
+        LogicalPlan::Extension(extension) => {
+            let (codec, node) =
+                codecs.encode_logical_extension(extension.node.as_ref())?;
+            let inputs = extension
+                .node
+                .inputs()
+                .into_iter()
+                .map(|input| serialize_logical_plan(input, codecs))
+                .collect::<Result<Vec<protobuf::LogicalPlanNode>, BallistaError>>()?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::Extension(
+                    protobuf::LogicalExtensionNode {
+                        codec,
+                        node,
+                        inputs,
+                    },
+                )),
+            })
+        }
+        LogicalPlan::Union(_) => unimplemented!(),
+        LogicalPlan::CrossJoin(CrossJoin { left, right, .. }) => {
+            let left = serialize_logical_plan(left.as_ref(), codecs)?;
+            let right = serialize_logical_plan(right.as_ref(), codecs)?;
+            Ok(protobuf::LogicalPlanNode {
+                logical_plan_type: Some(LogicalPlanType::CrossJoin(Box::new(
+                    protobuf::CrossJoinNode {
+                        left: Some(Box::new(left)),
+                        right: Some(Box::new(right)),
+                    },
+                ))),
+            })
+        }
+        LogicalPlan::CreateMemoryTable(_) => Err(proto_error(
+            "Error converting CreateMemoryTable. Not yet supported in Ballista",
+        )),
+        LogicalPlan::DropTable(_) => Err(proto_error(
+            "Error converting DropTable. Not yet supported in Ballista",
+        )),
+        LogicalPlan::SetVariable(_) => Err(proto_error(
+            "Error converting SetVariable. Not yet supported in Ballista",
+        )),
+        LogicalPlan::Prepare(_) => Err(proto_error(
+            "Error converting Prepare. Not yet supported in Ballista",
+        )),
+        LogicalPlan::Unnest(_) => Err(proto_error(
+            "Error converting Unnest. Not yet supported in Ballista",
+        )),
     }
 }
 
@@ -983,6 +1011,15 @@ impl TryInto<protobuf::LogicalExprNode> for &Expr {
                             protobuf::BuiltInWindowFunction::from(fun).into(),
                         )
                     }
+                    WindowFunction::WindowUDF(fun) => {
+                        if args.len() > 1 {
+                            return Err(BallistaError::NotImplemented(format!(
+                                "Window function {} with more than one argument",
+                                fun.name
+                            )));
+                        }
+                        protobuf::window_expr_node::WindowFunction::Udwf(fun.name.clone())
+                    }
                 };
                 let arg_expr: Option<Box<protobuf::LogicalExprNode>> = if !args.is_empty()
                 {
@@ -1014,7 +1051,11 @@ impl TryInto<protobuf::LogicalExprNode> for &Expr {
                 })
             }
             Expr::AggregateFunction {
-                ref fun, ref args, ..
+                ref fun,
+                ref args,
+                distinct,
+                ref filter,
+                ref order_by,
             } => {
                 let aggr_function = match fun {
                     AggregateFunction::ApproxDistinct => {
@@ -1040,12 +1081,26 @@ impl TryInto<protobuf::LogicalExprNode> for &Expr {
                 let aggregate_expr = Box::new(protobuf::AggregateExprNode {
                     aggr_function: aggr_function.into(),
                     expr: Some(Box::new(arg.try_into()?)),
+                    distinct: *distinct,
+                    filter: filter
+                        .as_ref()
+                        .map(|e| e.as_ref().try_into().map(Box::new))
+                        .transpose()?,
+                    order_by: order_by.iter().map(|e| e.try_into()).collect::<Result<
+                        Vec<_>,
+                        BallistaError,
+                    >>(
+                    )?,
                 });
                 Ok(protobuf::LogicalExprNode {
                     expr_type: Some(ExprType::AggregateExpr(aggregate_expr)),
                 })
             }
             Expr::ScalarVariable(_) => unimplemented!(),
+            Expr::Placeholder { id, .. } => Err(proto_error(format!(
+                "Error converting placeholder {}. Placeholders must be bound before the plan is serialized",
+                id
+            ))),
             Expr::ScalarFunction { ref fun, ref args } => {
                 let fun: protobuf::ScalarFunction = fun.try_into()?;
                 let args: Vec<protobuf::LogicalExprNode> = args
@@ -1063,8 +1118,42 @@ impl TryInto<protobuf::LogicalExprNode> for &Expr {
                     ),
                 })
             }
-            Expr::ScalarUDF { .. } => unimplemented!(),
-            Expr::AggregateUDF { .. } => unimplemented!(),
+            Expr::ScalarUDF { fun, args } => Ok(protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::ScalarUdfExpr(protobuf::ScalarUdfExprNode {
+                    fun_name: fun.name.clone(),
+                    args: args
+                        .iter()
+                        .map(|e| e.try_into())
+                        .collect::<Result<Vec<_>, BallistaError>>()?,
+                })),
+            }),
+            Expr::AggregateUDF {
+                fun,
+                args,
+                distinct,
+                filter,
+                order_by,
+            } => Ok(protobuf::LogicalExprNode {
+                expr_type: Some(ExprType::AggregateUdfExpr(Box::new(
+                    protobuf::AggregateUdfExprNode {
+                        fun_name: fun.name.clone(),
+                        args: args.iter().map(|e| e.try_into()).collect::<Result<
+                            Vec<_>,
+                            BallistaError,
+                        >>(
+                        )?,
+                        distinct: *distinct,
+                        filter: filter
+                            .as_ref()
+                            .map(|e| e.as_ref().try_into().map(Box::new))
+                            .transpose()?,
+                        order_by: order_by
+                            .iter()
+                            .map(|e| e.try_into())
+                            .collect::<Result<Vec<_>, BallistaError>>()?,
+                    },
+                ))),
+            }),
             Expr::Not(expr) => {
                 let expr = Box::new(protobuf::Not {
                     expr: Some(Box::new(expr.as_ref().try_into()?)),
@@ -21,6 +21,7 @@
 use std::sync::Arc;
 use std::{convert::TryInto, io::Cursor};
 
+use datafusion::execution::context::ExecutionContext;
 use datafusion::logical_plan::{JoinConstraint, JoinType, Operator};
 use datafusion::physical_plan::aggregates::AggregateFunction;
 use datafusion::physical_plan::window_functions::BuiltInWindowFunction;
@@ -36,6 +37,7 @@ pub mod protobuf {
     include!(concat!(env!("OUT_DIR"), "/ballista.protobuf.rs"));
 }
 
+pub mod extension;
 pub mod logical_plan;
 pub mod physical_plan;
 pub mod scheduler;
@@ -52,6 +54,17 @@ pub(crate) fn proto_error<S: Into<String>>(message: S) -> BallistaError {
     BallistaError::General(message.into())
 }
 
+/// Loads the plugins at the comma-separated `paths`, see
+/// [`datafusion::execution::plugin`], into a new context, which can then be used
+/// as the function registry to deserialize the plans that reference their functions.
+pub fn load_plugins(paths: &str) -> Result<ExecutionContext, BallistaError> {
+    let mut ctx = ExecutionContext::new();
+    for path in paths.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        ctx.load_plugin(path)?;
+    }
+    Ok(ctx)
+}
+
 /// Get object store by uri with pull path
 pub(crate) fn get_by_uri(
     uri: &str,
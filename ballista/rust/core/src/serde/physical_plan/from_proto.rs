@@ -25,6 +25,7 @@ use crate::error::BallistaError;
 use crate::execution_plans::{
     ShuffleReaderExec, ShuffleStreamReaderExec, ShuffleWriterExec, UnresolvedShuffleExec,
 };
+use crate::serde::extension::ExtensionCodecs;
 use crate::serde::protobuf::repartition_exec_node::PartitionMethod;
 use crate::serde::protobuf::ShuffleReaderPartition;
 use crate::serde::scheduler::ExecutorMeta;
@@ -32,7 +33,7 @@ use crate::serde::scheduler::PartitionLocation;
 use crate::serde::{
     from_proto_binary_op, get_by_uri, proto_error, protobuf, str_to_byte,
 };
-use crate::{convert_box_required, convert_required, into_required};
+use crate::{convert_required, into_required};
 use chrono::{TimeZone, Utc};
 use datafusion::arrow::datatypes::{DataType, Schema, SchemaRef};
 use datafusion::catalog::catalog::{
@@ -43,9 +44,8 @@ use datafusion::datasource::PartitionedFile;
 use datafusion::execution::context::{
     ExecutionConfig, ExecutionContextState, ExecutionProps,
 };
-use datafusion::logical_plan::{
-    window_frames::WindowFrame, DFSchema, Expr, JoinConstraint, JoinType,
-};
+use datafusion::logical_plan::registry::FunctionRegistry;
+use datafusion::logical_plan::{DFSchema, Expr, JoinConstraint, JoinType};
 use datafusion::physical_plan::aggregates::{create_aggregate_expr, AggregateFunction};
 use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
 use datafusion::physical_plan::file_format::{
@@ -75,7 +75,7 @@ use datafusion::physical_plan::{
     projection::ProjectionExec,
     repartition::RepartitionExec,
     sort::{SortExec, SortOptions},
-    Partitioning,
+    udaf, Partitioning,
 };
 use datafusion::physical_plan::{
     AggregateExpr, ColumnStatistics, ExecutionPlan, PhysicalExpr, Statistics, WindowExpr,
@@ -85,367 +85,394 @@ use log::{debug, info};
 use protobuf::physical_expr_node::ExprType;
 use protobuf::physical_plan_node::PhysicalPlanType;
 
-impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
-    type Error = BallistaError;
-
-    fn try_into(self) -> Result<Arc<dyn ExecutionPlan>, Self::Error> {
-        let plan = self.physical_plan_type.as_ref().ok_or_else(|| {
-            proto_error(format!(
-                "physical_plan::from_proto() Unsupported physical plan '{:?}'",
-                self
-            ))
-        })?;
-        match plan {
-            PhysicalPlanType::Projection(projection) => {
-                let input: Arc<dyn ExecutionPlan> =
-                    convert_box_required!(projection.input)?;
-                let exprs = projection
-                    .expr
-                    .iter()
-                    .zip(projection.expr_name.iter())
-                    .map(|(expr, name)| Ok((expr.try_into()?, name.to_string())))
-                    .collect::<Result<Vec<(Arc<dyn PhysicalExpr>, String)>, Self::Error>>(
-                    )?;
-                Ok(Arc::new(ProjectionExec::try_new(exprs, input)?))
-            }
-            PhysicalPlanType::Filter(filter) => {
-                let input: Arc<dyn ExecutionPlan> = convert_box_required!(filter.input)?;
-                let predicate = filter
-                    .expr
-                    .as_ref()
-                    .ok_or_else(|| {
-                        BallistaError::General(
-                            "filter (FilterExecNode) in PhysicalPlanNode is missing."
-                                .to_owned(),
-                        )
-                    })?
-                    .try_into()?;
-                Ok(Arc::new(FilterExec::try_new(predicate, input)?))
-            }
-            PhysicalPlanType::CsvScan(scan) => Ok(Arc::new(CsvExec::new(
-                scan.base_conf.as_ref().unwrap().try_into()?,
-                scan.has_header,
-                str_to_byte(&scan.delimiter)?,
-            ))),
-            PhysicalPlanType::ParquetScan(scan) => {
-                Ok(Arc::new(ParquetExec::new(
-                    scan.base_conf.as_ref().unwrap().try_into()?,
-                    // TODO predicate should be de-serialized
-                    None,
-                )))
-            }
-            PhysicalPlanType::AvroScan(scan) => Ok(Arc::new(AvroExec::new(
+/// Converts a protobuf physical plan into an [`ExecutionPlan`], looking up the
+/// user-defined functions that it references by name in `registry` and
+/// decoding the plans that Ballista doesn't serialize itself with the codecs
+/// registered with `codecs`
+pub fn parse_physical_plan(
+    proto: &protobuf::PhysicalPlanNode,
+    registry: &dyn FunctionRegistry,
+    codecs: &ExtensionCodecs,
+) -> Result<Arc<dyn ExecutionPlan>, BallistaError> {
+    let plan = proto.physical_plan_type.as_ref().ok_or_else(|| {
+        proto_error(format!(
+            "physical_plan::from_proto() Unsupported physical plan '{:?}'",
+            proto
+        ))
+    })?;
+    match plan {
+        PhysicalPlanType::Projection(projection) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&projection.input, registry, codecs)?;
+            let exprs = projection
+                .expr
+                .iter()
+                .zip(projection.expr_name.iter())
+                .map(|(expr, name)| {
+                    Ok((parse_physical_expr(expr, registry)?, name.to_string()))
+                })
+                .collect::<Result<Vec<(Arc<dyn PhysicalExpr>, String)>, BallistaError>>(
+                )?;
+            Ok(Arc::new(ProjectionExec::try_new(exprs, input)?))
+        }
+        PhysicalPlanType::Filter(filter) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&filter.input, registry, codecs)?;
+            let predicate = parse_physical_expr(
+                filter.expr.as_ref().ok_or_else(|| {
+                    BallistaError::General(
+                        "filter (FilterExecNode) in PhysicalPlanNode is missing."
+                            .to_owned(),
+                    )
+                })?,
+                registry,
+            )?;
+            Ok(Arc::new(FilterExec::try_new(predicate, input)?))
+        }
+        PhysicalPlanType::CsvScan(scan) => Ok(Arc::new(CsvExec::new(
+            scan.base_conf.as_ref().unwrap().try_into()?,
+            scan.has_header,
+            str_to_byte(&scan.delimiter)?,
+        ))),
+        PhysicalPlanType::ParquetScan(scan) => {
+            Ok(Arc::new(ParquetExec::new(
                 scan.base_conf.as_ref().unwrap().try_into()?,
-            ))),
-            PhysicalPlanType::CoalesceBatches(coalesce_batches) => {
-                let input: Arc<dyn ExecutionPlan> =
-                    convert_box_required!(coalesce_batches.input)?;
-                Ok(Arc::new(CoalesceBatchesExec::new(
-                    input,
-                    coalesce_batches.target_batch_size as usize,
-                )))
-            }
-            PhysicalPlanType::Merge(merge) => {
-                let input: Arc<dyn ExecutionPlan> = convert_box_required!(merge.input)?;
-                Ok(Arc::new(CoalescePartitionsExec::new(input)))
-            }
-            PhysicalPlanType::Repartition(repart) => {
-                let input: Arc<dyn ExecutionPlan> = convert_box_required!(repart.input)?;
-                match repart.partition_method {
-                    Some(PartitionMethod::Hash(ref hash_part)) => {
-                        let expr = hash_part
-                            .hash_expr
-                            .iter()
-                            .map(|e| e.try_into())
-                            .collect::<Result<Vec<Arc<dyn PhysicalExpr>>, _>>()?;
-
-                        Ok(Arc::new(RepartitionExec::try_new(
-                            input,
-                            Partitioning::Hash(
-                                expr,
-                                hash_part.partition_count.try_into().unwrap(),
-                            ),
-                        )?))
-                    }
-                    Some(PartitionMethod::RoundRobin(partition_count)) => {
-                        Ok(Arc::new(RepartitionExec::try_new(
-                            input,
-                            Partitioning::RoundRobinBatch(
-                                partition_count.try_into().unwrap(),
-                            ),
-                        )?))
-                    }
-                    Some(PartitionMethod::Unknown(partition_count)) => {
-                        Ok(Arc::new(RepartitionExec::try_new(
-                            input,
-                            Partitioning::UnknownPartitioning(
-                                partition_count.try_into().unwrap(),
-                            ),
-                        )?))
-                    }
-                    _ => Err(BallistaError::General(
-                        "Invalid partitioning scheme".to_owned(),
-                    )),
+                // TODO predicate should be de-serialized
+                None,
+            )))
+        }
+        PhysicalPlanType::AvroScan(scan) => Ok(Arc::new(AvroExec::new(
+            scan.base_conf.as_ref().unwrap().try_into()?,
+        ))),
+        PhysicalPlanType::CoalesceBatches(coalesce_batches) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&coalesce_batches.input, registry, codecs)?;
+            Ok(Arc::new(CoalesceBatchesExec::new(
+                input,
+                coalesce_batches.target_batch_size as usize,
+            )))
+        }
+        PhysicalPlanType::Merge(merge) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&merge.input, registry, codecs)?;
+            Ok(Arc::new(CoalescePartitionsExec::new(input)))
+        }
+        PhysicalPlanType::Repartition(repart) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&repart.input, registry, codecs)?;
+            match repart.partition_method {
+                Some(PartitionMethod::Hash(ref hash_part)) => {
+                    let expr = hash_part
+                        .hash_expr
+                        .iter()
+                        .map(|e| parse_physical_expr(e, registry))
+                        .collect::<Result<Vec<Arc<dyn PhysicalExpr>>, _>>()?;
+
+                    Ok(Arc::new(RepartitionExec::try_new(
+                        input,
+                        Partitioning::Hash(
+                            expr,
+                            hash_part.partition_count.try_into().unwrap(),
+                        ),
+                    )?))
                 }
+                Some(PartitionMethod::RoundRobin(partition_count)) => {
+                    Ok(Arc::new(RepartitionExec::try_new(
+                        input,
+                        Partitioning::RoundRobinBatch(
+                            partition_count.try_into().unwrap(),
+                        ),
+                    )?))
+                }
+                Some(PartitionMethod::Unknown(partition_count)) => {
+                    Ok(Arc::new(RepartitionExec::try_new(
+                        input,
+                        Partitioning::UnknownPartitioning(
+                            partition_count.try_into().unwrap(),
+                        ),
+                    )?))
+                }
+                _ => Err(BallistaError::General(
+                    "Invalid partitioning scheme".to_owned(),
+                )),
             }
-            PhysicalPlanType::GlobalLimit(limit) => {
-                let input: Arc<dyn ExecutionPlan> = convert_box_required!(limit.input)?;
-                Ok(Arc::new(GlobalLimitExec::new(input, limit.limit as usize)))
-            }
-            PhysicalPlanType::LocalLimit(limit) => {
-                let input: Arc<dyn ExecutionPlan> = convert_box_required!(limit.input)?;
-                Ok(Arc::new(LocalLimitExec::new(input, limit.limit as usize)))
-            }
-            PhysicalPlanType::Window(window_agg) => {
-                let input: Arc<dyn ExecutionPlan> =
-                    convert_box_required!(window_agg.input)?;
-                let input_schema = window_agg
-                    .input_schema
-                    .as_ref()
-                    .ok_or_else(|| {
-                        BallistaError::General(
-                            "input_schema in WindowAggrNode is missing.".to_owned(),
-                        )
-                    })?
-                    .clone();
-                let physical_schema: SchemaRef =
-                    SchemaRef::new((&input_schema).try_into()?);
-
-                let physical_window_expr: Vec<Arc<dyn WindowExpr>> = window_agg
-                    .window_expr
-                    .iter()
-                    .zip(window_agg.window_expr_name.iter())
-                    .map(|(expr, name)| {
-                        let expr_type = expr.expr_type.as_ref().ok_or_else(|| {
-                            proto_error("Unexpected empty window physical expression")
-                        })?;
-
-                        match expr_type {
-                            ExprType::WindowExpr(window_node) => Ok(create_window_expr(
-                                &convert_required!(window_node.window_function)?,
-                                name.to_owned(),
-                                &[convert_box_required!(window_node.expr)?],
-                                &[],
-                                &[],
-                                Some(WindowFrame::default()),
-                                &physical_schema,
-                            )?),
-                            _ => Err(BallistaError::General(
-                                "Invalid expression for WindowAggrExec".to_string(),
-                            )),
-                        }
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
+        }
+        PhysicalPlanType::GlobalLimit(limit) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&limit.input, registry, codecs)?;
+            Ok(Arc::new(GlobalLimitExec::new(input, limit.limit as usize)))
+        }
+        PhysicalPlanType::LocalLimit(limit) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&limit.input, registry, codecs)?;
+            Ok(Arc::new(LocalLimitExec::new(input, limit.limit as usize)))
+        }
+        PhysicalPlanType::Window(window_agg) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&window_agg.input, registry, codecs)?;
+            let input_schema = window_agg
+                .input_schema
+                .as_ref()
+                .ok_or_else(|| {
+                    BallistaError::General(
+                        "input_schema in WindowAggrNode is missing.".to_owned(),
+                    )
+                })?
+                .clone();
+            let physical_schema: SchemaRef = SchemaRef::new((&input_schema).try_into()?);
+
+            let physical_window_expr: Vec<Arc<dyn WindowExpr>> = window_agg
+                .window_expr
+                .iter()
+                .zip(window_agg.window_expr_name.iter())
+                .map(|(expr, name)| {
+                    let expr_type = expr.expr_type.as_ref().ok_or_else(|| {
+                        proto_error("Unexpected empty window physical expression")
+                    })?;
 
-                Ok(Arc::new(WindowAggExec::try_new(
-                    physical_window_expr,
-                    input,
-                    Arc::new((&input_schema).try_into()?),
-                )?))
-            }
-            PhysicalPlanType::HashAggregate(hash_agg) => {
-                let input: Arc<dyn ExecutionPlan> =
-                    convert_box_required!(hash_agg.input)?;
-                let mode = protobuf::AggregateMode::from_i32(hash_agg.mode).ok_or_else(|| {
+                    match expr_type {
+                        ExprType::WindowExpr(window_node) => Ok(create_window_expr(
+                            &parse_required_window_function(
+                                &window_node.window_function,
+                                registry,
+                            )?,
+                            name.to_owned(),
+                            &[parse_required_expr(&window_node.expr, registry)?],
+                            &[],
+                            &[],
+                            None,
+                            &physical_schema,
+                        )?),
+                        _ => Err(BallistaError::General(
+                            "Invalid expression for WindowAggrExec".to_string(),
+                        )),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Arc::new(WindowAggExec::try_new(
+                physical_window_expr,
+                input,
+                Arc::new((&input_schema).try_into()?),
+            )?))
+        }
+        PhysicalPlanType::HashAggregate(hash_agg) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&hash_agg.input, registry, codecs)?;
+            let mode = protobuf::AggregateMode::from_i32(hash_agg.mode).ok_or_else(|| {
                     proto_error(format!(
                         "Received a HashAggregateNode message with unknown AggregateMode {}",
                         hash_agg.mode
                     ))
                 })?;
-                let agg_mode: AggregateMode = match mode {
-                    protobuf::AggregateMode::Partial => AggregateMode::Partial,
-                    protobuf::AggregateMode::Final => AggregateMode::Final,
-                    protobuf::AggregateMode::FinalPartitioned => {
-                        AggregateMode::FinalPartitioned
-                    }
-                };
-                let group = hash_agg
-                    .group_expr
-                    .iter()
-                    .zip(hash_agg.group_expr_name.iter())
-                    .map(|(expr, name)| {
-                        expr.try_into().map(|expr| (expr, name.to_string()))
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                let input_schema = hash_agg
-                    .input_schema
-                    .as_ref()
-                    .ok_or_else(|| {
-                        BallistaError::General(
-                            "input_schema in HashAggregateNode is missing.".to_owned(),
-                        )
-                    })?
-                    .clone();
-                let physical_schema: SchemaRef =
-                    SchemaRef::new((&input_schema).try_into()?);
-
-                let physical_aggr_expr: Vec<Arc<dyn AggregateExpr>> = hash_agg
-                    .aggr_expr
-                    .iter()
-                    .zip(hash_agg.aggr_expr_name.iter())
-                    .map(|(expr, name)| {
-                        let expr_type = expr.expr_type.as_ref().ok_or_else(|| {
-                            proto_error("Unexpected empty aggregate physical expression")
-                        })?;
+            let agg_mode: AggregateMode = match mode {
+                protobuf::AggregateMode::Partial => AggregateMode::Partial,
+                protobuf::AggregateMode::Final => AggregateMode::Final,
+                protobuf::AggregateMode::FinalPartitioned => {
+                    AggregateMode::FinalPartitioned
+                }
+            };
+            let group = hash_agg
+                .group_expr
+                .iter()
+                .zip(hash_agg.group_expr_name.iter())
+                .map(|(expr, name)| {
+                    parse_physical_expr(expr, registry)
+                        .map(|expr| (expr, name.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let input_schema = hash_agg
+                .input_schema
+                .as_ref()
+                .ok_or_else(|| {
+                    BallistaError::General(
+                        "input_schema in HashAggregateNode is missing.".to_owned(),
+                    )
+                })?
+                .clone();
+            let physical_schema: SchemaRef = SchemaRef::new((&input_schema).try_into()?);
+
+            let physical_aggr_expr: Vec<Arc<dyn AggregateExpr>> = hash_agg
+                .aggr_expr
+                .iter()
+                .zip(hash_agg.aggr_expr_name.iter())
+                .map(|(expr, name)| {
+                    let expr_type = expr.expr_type.as_ref().ok_or_else(|| {
+                        proto_error("Unexpected empty aggregate physical expression")
+                    })?;
 
-                        match expr_type {
-                            ExprType::AggregateExpr(agg_node) => {
-                                let aggr_function =
-                                    protobuf::AggregateFunction::from_i32(
-                                        agg_node.aggr_function,
-                                    )
-                                    .ok_or_else(
-                                        || {
-                                            proto_error(format!(
-                                                "Received an unknown aggregate function: {}",
-                                                agg_node.aggr_function
-                                            ))
-                                        },
-                                    )?;
-
-                                Ok(create_aggregate_expr(
-                                    &aggr_function.into(),
-                                    false,
-                                    &[convert_box_required!(agg_node.expr)?],
-                                    &physical_schema,
-                                    name.to_string(),
-                                )?)
-                            }
-                            _ => Err(BallistaError::General(
-                                "Invalid aggregate  expression for HashAggregateExec"
-                                    .to_string(),
-                            )),
+                    match expr_type {
+                        ExprType::AggregateExpr(agg_node) => {
+                            let aggr_function = protobuf::AggregateFunction::from_i32(
+                                agg_node.aggr_function,
+                            )
+                            .ok_or_else(|| {
+                                proto_error(format!(
+                                    "Received an unknown aggregate function: {}",
+                                    agg_node.aggr_function
+                                ))
+                            })?;
+
+                            Ok(create_aggregate_expr(
+                                &aggr_function.into(),
+                                false,
+                                &[parse_required_expr(&agg_node.expr, registry)?],
+                                &physical_schema,
+                                name.to_string(),
+                            )?)
                         }
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
+                        ExprType::AggregateUdfExpr(agg_node) => {
+                            let args = agg_node
+                                .args
+                                .iter()
+                                .map(|e| parse_physical_expr(e, registry))
+                                .collect::<Result<Vec<_>, _>>()?;
+                            Ok(udaf::create_aggregate_expr(
+                                &registry.udaf(&agg_node.fun_name)?,
+                                &args,
+                                &physical_schema,
+                                name.to_string(),
+                            )?)
+                        }
+                        _ => Err(BallistaError::General(
+                            "Invalid aggregate  expression for HashAggregateExec"
+                                .to_string(),
+                        )),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Arc::new(HashAggregateExec::try_new(
+                agg_mode,
+                group,
+                physical_aggr_expr,
+                input,
+                Arc::new((&input_schema).try_into()?),
+            )?))
+        }
+        PhysicalPlanType::HashJoin(hashjoin) => {
+            let left: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&hashjoin.left, registry, codecs)?;
+            let right: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&hashjoin.right, registry, codecs)?;
+            let on: Vec<(Column, Column)> = hashjoin
+                .on
+                .iter()
+                .map(|col| {
+                    let left = into_required!(col.left)?;
+                    let right = into_required!(col.right)?;
+                    Ok((left, right))
+                })
+                .collect::<Result<_, BallistaError>>()?;
+            let join_type =
+                protobuf::JoinType::from_i32(hashjoin.join_type).ok_or_else(|| {
+                    proto_error(format!(
+                        "Received a HashJoinNode message with unknown JoinType {}",
+                        hashjoin.join_type
+                    ))
+                })?;
 
-                Ok(Arc::new(HashAggregateExec::try_new(
-                    agg_mode,
-                    group,
-                    physical_aggr_expr,
+            let partition_mode = protobuf::PartitionMode::from_i32(
+                hashjoin.partition_mode,
+            )
+            .ok_or_else(|| {
+                proto_error(format!(
+                    "Received a HashJoinNode message with unknown PartitionMode {}",
+                    hashjoin.partition_mode
+                ))
+            })?;
+            let partition_mode = match partition_mode {
+                protobuf::PartitionMode::CollectLeft => PartitionMode::CollectLeft,
+                protobuf::PartitionMode::Partitioned => PartitionMode::Partitioned,
+            };
+            Ok(Arc::new(HashJoinExec::try_new(
+                left,
+                right,
+                on,
+                &join_type.into(),
+                partition_mode,
+                &hashjoin.null_equals_null,
+            )?))
+        }
+        PhysicalPlanType::CrossJoin(crossjoin) => {
+            let left: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&crossjoin.left, registry, codecs)?;
+            let right: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&crossjoin.right, registry, codecs)?;
+            Ok(Arc::new(CrossJoinExec::try_new(left, right)?))
+        }
+        PhysicalPlanType::ShuffleWriter(shuffle_writer) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&shuffle_writer.input, registry, codecs)?;
+
+            let output_partitioning = parse_protobuf_hash_partitioning(
+                shuffle_writer.output_partitioning.as_ref(),
+                registry,
+            )?;
+            if !shuffle_writer.push_shuffle {
+                Ok(Arc::new(ShuffleWriterExec::try_new_pull_shuffle(
+                    shuffle_writer.job_id.clone(),
+                    shuffle_writer.stage_id as usize,
                     input,
-                    Arc::new((&input_schema).try_into()?),
+                    "".to_string(), // this is intentional but hacky - the executor will fill this in
+                    output_partitioning,
                 )?))
-            }
-            PhysicalPlanType::HashJoin(hashjoin) => {
-                let left: Arc<dyn ExecutionPlan> = convert_box_required!(hashjoin.left)?;
-                let right: Arc<dyn ExecutionPlan> =
-                    convert_box_required!(hashjoin.right)?;
-                let on: Vec<(Column, Column)> = hashjoin
-                    .on
-                    .iter()
-                    .map(|col| {
-                        let left = into_required!(col.left)?;
-                        let right = into_required!(col.right)?;
-                        Ok((left, right))
-                    })
-                    .collect::<Result<_, Self::Error>>()?;
-                let join_type = protobuf::JoinType::from_i32(hashjoin.join_type)
-                    .ok_or_else(|| {
-                        proto_error(format!(
-                            "Received a HashJoinNode message with unknown JoinType {}",
-                            hashjoin.join_type
-                        ))
-                    })?;
-
-                let partition_mode =
-                    protobuf::PartitionMode::from_i32(hashjoin.partition_mode)
-                        .ok_or_else(|| {
-                            proto_error(format!(
-                        "Received a HashJoinNode message with unknown PartitionMode {}",
-                        hashjoin.partition_mode
-                    ))
-                        })?;
-                let partition_mode = match partition_mode {
-                    protobuf::PartitionMode::CollectLeft => PartitionMode::CollectLeft,
-                    protobuf::PartitionMode::Partitioned => PartitionMode::Partitioned,
-                };
-                Ok(Arc::new(HashJoinExec::try_new(
-                    left,
-                    right,
-                    on,
-                    &join_type.into(),
-                    partition_mode,
-                    &hashjoin.null_equals_null,
+            } else {
+                let _execs: Vec<ExecutorMeta> = shuffle_writer
+                    .execs
+                    .to_owned()
+                    .into_iter()
+                    .map(|e| e.into())
+                    .collect();
+                Ok(Arc::new(ShuffleWriterExec::try_new_push_shuffle(
+                    shuffle_writer.job_id.clone(),
+                    shuffle_writer.stage_id as usize,
+                    input,
+                    _execs,
+                    output_partitioning,
                 )?))
             }
-            PhysicalPlanType::CrossJoin(crossjoin) => {
-                let left: Arc<dyn ExecutionPlan> = convert_box_required!(crossjoin.left)?;
-                let right: Arc<dyn ExecutionPlan> =
-                    convert_box_required!(crossjoin.right)?;
-                Ok(Arc::new(CrossJoinExec::try_new(left, right)?))
-            }
-            PhysicalPlanType::ShuffleWriter(shuffle_writer) => {
-                let input: Arc<dyn ExecutionPlan> =
-                    convert_box_required!(shuffle_writer.input)?;
-
-                let output_partitioning = parse_protobuf_hash_partitioning(
-                    shuffle_writer.output_partitioning.as_ref(),
-                )?;
-                if !shuffle_writer.push_shuffle {
-                    Ok(Arc::new(ShuffleWriterExec::try_new_pull_shuffle(
-                        shuffle_writer.job_id.clone(),
-                        shuffle_writer.stage_id as usize,
-                        input,
-                        "".to_string(), // this is intentional but hacky - the executor will fill this in
-                        output_partitioning,
-                    )?))
-                } else {
-                    let _execs: Vec<ExecutorMeta> = shuffle_writer
-                        .execs
-                        .to_owned()
-                        .into_iter()
-                        .map(|e| e.into())
-                        .collect();
-                    Ok(Arc::new(ShuffleWriterExec::try_new_push_shuffle(
-                        shuffle_writer.job_id.clone(),
-                        shuffle_writer.stage_id as usize,
-                        input,
-                        _execs,
-                        output_partitioning,
-                    )?))
-                }
-            }
-            PhysicalPlanType::ShuffleStreamReader(shuffle_stream_reader) => {
-                let schema = Arc::new(convert_required!(shuffle_stream_reader.schema)?);
-                let shuffle_reader = ShuffleStreamReaderExec::new(
-                    shuffle_stream_reader.stage_id as usize,
-                    schema,
-                    shuffle_stream_reader.partition_count as usize,
-                );
-                Ok(Arc::new(shuffle_reader))
-            }
-            PhysicalPlanType::ShuffleReader(shuffle_reader) => {
-                let schema = Arc::new(convert_required!(shuffle_reader.schema)?);
-                let partition_location: Vec<Vec<PartitionLocation>> = shuffle_reader
-                    .partition
-                    .iter()
-                    .map(|p| {
-                        p.location
-                            .iter()
-                            .map(|l| l.clone().try_into())
-                            .collect::<Result<Vec<_>, _>>()
-                    })
-                    .collect::<Result<Vec<_>, BallistaError>>()?;
-                let shuffle_reader =
-                    ShuffleReaderExec::try_new(partition_location, schema)?;
-                Ok(Arc::new(shuffle_reader))
-            }
-            PhysicalPlanType::Empty(empty) => {
-                let schema = Arc::new(convert_required!(empty.schema)?);
-                Ok(Arc::new(EmptyExec::new(empty.produce_one_row, schema)))
-            }
-            PhysicalPlanType::Sort(sort) => {
-                let input: Arc<dyn ExecutionPlan> = convert_box_required!(sort.input)?;
-                let exprs = sort
+        }
+        PhysicalPlanType::ShuffleStreamReader(shuffle_stream_reader) => {
+            let schema = Arc::new(convert_required!(shuffle_stream_reader.schema)?);
+            let shuffle_reader = ShuffleStreamReaderExec::new(
+                shuffle_stream_reader.stage_id as usize,
+                schema,
+                shuffle_stream_reader.partition_count as usize,
+            );
+            Ok(Arc::new(shuffle_reader))
+        }
+        PhysicalPlanType::ShuffleReader(shuffle_reader) => {
+            let schema = Arc::new(convert_required!(shuffle_reader.schema)?);
+            let partition_location: Vec<Vec<PartitionLocation>> = shuffle_reader
+                .partition
+                .iter()
+                .map(|p| {
+                    p.location
+                        .iter()
+                        .map(|l| l.clone().try_into())
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, BallistaError>>()?;
+            let shuffle_reader = ShuffleReaderExec::try_new(partition_location, schema)?;
+            Ok(Arc::new(shuffle_reader))
+        }
+        PhysicalPlanType::Empty(empty) => {
+            let schema = Arc::new(convert_required!(empty.schema)?);
+            Ok(Arc::new(EmptyExec::new(empty.produce_one_row, schema)))
+        }
+        PhysicalPlanType::Sort(sort) => {
+            let input: Arc<dyn ExecutionPlan> =
+                parse_required_plan(&sort.input, registry, codecs)?;
+            let exprs = sort
                     .expr
                     .iter()
                     .map(|expr| {
                         let expr = expr.expr_type.as_ref().ok_or_else(|| {
                             proto_error(format!(
                                 "physical_plan::from_proto() Unexpected expr {:?}",
-                                self
+                                proto
                             ))
                         })?;
                         if let protobuf::physical_expr_node::ExprType::Sort(sort_expr) = expr {
@@ -455,12 +482,12 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
                                 .ok_or_else(|| {
                                     proto_error(format!(
                                         "physical_plan::from_proto() Unexpected sort expr {:?}",
-                                        self
+                                        proto
                                     ))
                                 })?
                                 .as_ref();
                             Ok(PhysicalSortExpr {
-                                expr: expr.try_into()?,
+                                expr: parse_physical_expr(expr, registry)?,
                                 options: SortOptions {
                                     descending: !sort_expr.asc,
                                     nulls_first: sort_expr.nulls_first,
@@ -469,24 +496,38 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
                         } else {
                             Err(BallistaError::General(format!(
                                 "physical_plan::from_proto() {:?}",
-                                self
+                                proto
                             )))
                         }
                     })
                     .collect::<Result<Vec<_>, _>>()?;
-                Ok(Arc::new(SortExec::try_new(exprs, input)?))
-            }
-            PhysicalPlanType::Unresolved(unresolved_shuffle) => {
-                let schema = Arc::new(convert_required!(unresolved_shuffle.schema)?);
-                Ok(Arc::new(UnresolvedShuffleExec {
-                    stage_id: unresolved_shuffle.stage_id as usize,
-                    schema,
-                    input_partition_count: unresolved_shuffle.input_partition_count
-                        as usize,
-                    output_partition_count: unresolved_shuffle.output_partition_count
-                        as usize,
-                }))
-            }
+            let fetch = if sort.fetch < 0 {
+                None
+            } else {
+                Some(sort.fetch as usize)
+            };
+            Ok(Arc::new(
+                SortExec::new_with_partitioning(exprs, input, sort.preserve_partitioning)
+                    .with_fetch(fetch),
+            ))
+        }
+        PhysicalPlanType::Unresolved(unresolved_shuffle) => {
+            let schema = Arc::new(convert_required!(unresolved_shuffle.schema)?);
+            Ok(Arc::new(UnresolvedShuffleExec {
+                stage_id: unresolved_shuffle.stage_id as usize,
+                schema,
+                input_partition_count: unresolved_shuffle.input_partition_count as usize,
+                output_partition_count: unresolved_shuffle.output_partition_count
+                    as usize,
+            }))
+        }
+        PhysicalPlanType::Extension(extension) => {
+            let inputs = extension
+                .inputs
+                .iter()
+                .map(|input| parse_physical_plan(input, registry, codecs))
+                .collect::<Result<Vec<Arc<dyn ExecutionPlan>>, _>>()?;
+            codecs.decode_physical_extension(&extension.codec, &extension.node, inputs)
         }
     }
 }
@@ -542,177 +583,241 @@ impl From<&protobuf::ScalarFunction> for BuiltinScalarFunction {
     }
 }
 
-impl TryFrom<&protobuf::PhysicalExprNode> for Arc<dyn PhysicalExpr> {
-    type Error = BallistaError;
-
-    fn try_from(expr: &protobuf::PhysicalExprNode) -> Result<Self, Self::Error> {
-        let expr_type = expr
-            .expr_type
-            .as_ref()
-            .ok_or_else(|| proto_error("Unexpected empty physical expression"))?;
+/// Converts a protobuf physical expression into a [`PhysicalExpr`], looking up
+/// the user-defined functions that it references by name in `registry`
+pub fn parse_physical_expr(
+    proto: &protobuf::PhysicalExprNode,
+    registry: &dyn FunctionRegistry,
+) -> Result<Arc<dyn PhysicalExpr>, BallistaError> {
+    let expr_type = proto
+        .expr_type
+        .as_ref()
+        .ok_or_else(|| proto_error("Unexpected empty physical expression"))?;
+
+    let pexpr: Arc<dyn PhysicalExpr> = match expr_type {
+        ExprType::Column(c) => {
+            let pcol: Column = c.into();
+            Arc::new(pcol)
+        }
+        ExprType::Literal(scalar) => {
+            Arc::new(Literal::new(convert_required!(scalar.value)?))
+        }
+        ExprType::BinaryExpr(binary_expr) => Arc::new(BinaryExpr::new(
+            parse_required_expr(&binary_expr.l, registry)?,
+            from_proto_binary_op(&binary_expr.op)?,
+            parse_required_expr(&binary_expr.r, registry)?,
+        )),
+        ExprType::AggregateExpr(_) => {
+            return Err(BallistaError::General(
+                "Cannot convert aggregate expr node to physical expression".to_owned(),
+            ));
+        }
+        ExprType::AggregateUdfExpr(_) => {
+            return Err(BallistaError::General(
+                "Cannot convert aggregate UDF expr node to physical expression"
+                    .to_owned(),
+            ));
+        }
+        ExprType::WindowExpr(_) => {
+            return Err(BallistaError::General(
+                "Cannot convert window expr node to physical expression".to_owned(),
+            ));
+        }
+        ExprType::Sort(_) => {
+            return Err(BallistaError::General(
+                "Cannot convert sort expr node to physical expression".to_owned(),
+            ));
+        }
+        ExprType::IsNullExpr(e) => {
+            Arc::new(IsNullExpr::new(parse_required_expr(&e.expr, registry)?))
+        }
+        ExprType::IsNotNullExpr(e) => {
+            Arc::new(IsNotNullExpr::new(parse_required_expr(&e.expr, registry)?))
+        }
+        ExprType::NotExpr(e) => {
+            Arc::new(NotExpr::new(parse_required_expr(&e.expr, registry)?))
+        }
+        ExprType::Negative(e) => {
+            Arc::new(NegativeExpr::new(parse_required_expr(&e.expr, registry)?))
+        }
+        ExprType::InList(e) => Arc::new(InListExpr::new(
+            parse_required_expr(&e.expr, registry)?,
+            e.list
+                .iter()
+                .map(|x| parse_physical_expr(x, registry))
+                .collect::<Result<Vec<_>, _>>()?,
+            e.negated,
+        )),
+        ExprType::Case(e) => Arc::new(CaseExpr::try_new(
+            e.expr
+                .as_ref()
+                .map(|e| parse_physical_expr(e, registry))
+                .transpose()?,
+            e.when_then_expr
+                .iter()
+                .map(|e| {
+                    Ok((
+                        parse_required_physical_expr(&e.when_expr, registry)?,
+                        parse_required_physical_expr(&e.then_expr, registry)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, BallistaError>>()?
+                .as_slice(),
+            e.else_expr
+                .as_ref()
+                .map(|e| parse_physical_expr(e, registry))
+                .transpose()?,
+        )?),
+        ExprType::Cast(e) => Arc::new(CastExpr::new(
+            parse_required_expr(&e.expr, registry)?,
+            convert_required!(e.arrow_type)?,
+            DEFAULT_DATAFUSION_CAST_OPTIONS,
+        )),
+        ExprType::TryCast(e) => Arc::new(TryCastExpr::new(
+            parse_required_expr(&e.expr, registry)?,
+            convert_required!(e.arrow_type)?,
+        )),
+        ExprType::ScalarFunction(e) => {
+            let scalar_function =
+                protobuf::ScalarFunction::from_i32(e.fun).ok_or_else(|| {
+                    proto_error(
+                        format!("Received an unknown scalar function: {}", e.fun,),
+                    )
+                })?;
 
-        let pexpr: Arc<dyn PhysicalExpr> = match expr_type {
-            ExprType::Column(c) => {
-                let pcol: Column = c.into();
-                Arc::new(pcol)
-            }
-            ExprType::Literal(scalar) => {
-                Arc::new(Literal::new(convert_required!(scalar.value)?))
-            }
-            ExprType::BinaryExpr(binary_expr) => Arc::new(BinaryExpr::new(
-                convert_box_required!(&binary_expr.l)?,
-                from_proto_binary_op(&binary_expr.op)?,
-                convert_box_required!(&binary_expr.r)?,
-            )),
-            ExprType::AggregateExpr(_) => {
-                return Err(BallistaError::General(
-                    "Cannot convert aggregate expr node to physical expression"
-                        .to_owned(),
-                ));
-            }
-            ExprType::WindowExpr(_) => {
-                return Err(BallistaError::General(
-                    "Cannot convert window expr node to physical expression".to_owned(),
-                ));
-            }
-            ExprType::Sort(_) => {
-                return Err(BallistaError::General(
-                    "Cannot convert sort expr node to physical expression".to_owned(),
-                ));
-            }
-            ExprType::IsNullExpr(e) => {
-                Arc::new(IsNullExpr::new(convert_box_required!(e.expr)?))
-            }
-            ExprType::IsNotNullExpr(e) => {
-                Arc::new(IsNotNullExpr::new(convert_box_required!(e.expr)?))
-            }
-            ExprType::NotExpr(e) => {
-                Arc::new(NotExpr::new(convert_box_required!(e.expr)?))
-            }
-            ExprType::Negative(e) => {
-                Arc::new(NegativeExpr::new(convert_box_required!(e.expr)?))
-            }
-            ExprType::InList(e) => Arc::new(InListExpr::new(
-                convert_box_required!(e.expr)?,
-                e.list
-                    .iter()
-                    .map(|x| x.try_into())
-                    .collect::<Result<Vec<_>, _>>()?,
-                e.negated,
-            )),
-            ExprType::Case(e) => Arc::new(CaseExpr::try_new(
-                e.expr.as_ref().map(|e| e.as_ref().try_into()).transpose()?,
-                e.when_then_expr
-                    .iter()
-                    .map(|e| {
-                        Ok((
-                            convert_required!(e.when_expr)?,
-                            convert_required!(e.then_expr)?,
-                        ))
-                    })
-                    .collect::<Result<Vec<_>, BallistaError>>()?
-                    .as_slice(),
-                e.else_expr
-                    .as_ref()
-                    .map(|e| e.as_ref().try_into())
-                    .transpose()?,
-            )?),
-            ExprType::Cast(e) => Arc::new(CastExpr::new(
-                convert_box_required!(e.expr)?,
-                convert_required!(e.arrow_type)?,
-                DEFAULT_DATAFUSION_CAST_OPTIONS,
-            )),
-            ExprType::TryCast(e) => Arc::new(TryCastExpr::new(
-                convert_box_required!(e.expr)?,
-                convert_required!(e.arrow_type)?,
-            )),
-            ExprType::ScalarFunction(e) => {
-                let scalar_function = protobuf::ScalarFunction::from_i32(e.fun)
-                    .ok_or_else(|| {
-                        proto_error(format!(
-                            "Received an unknown scalar function: {}",
-                            e.fun,
-                        ))
-                    })?;
+            let args = e
+                .args
+                .iter()
+                .map(|x| parse_physical_expr(x, registry))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let catalog_list = Arc::new(MemoryCatalogList::new()) as Arc<dyn CatalogList>;
+
+            let ctx_state = ExecutionContextState {
+                catalog_list,
+                scalar_functions: Default::default(),
+                var_provider: Default::default(),
+                aggregate_functions: Default::default(),
+                window_functions: Default::default(),
+                table_functions: Default::default(),
+                config: ExecutionConfig::new(),
+                execution_props: ExecutionProps::new(),
+                object_store_registry: Arc::new(ObjectStoreRegistry::new()),
+                prepared_statements: Default::default(),
+            };
+
+            let fun_expr =
+                functions::create_physical_fun(&(&scalar_function).into(), &ctx_state)?;
+
+            Arc::new(ScalarFunctionExpr::new(
+                &e.name,
+                fun_expr,
+                args,
+                &convert_required!(e.return_type)?,
+            ))
+        }
+        ExprType::ScalarUdf(e) => {
+            let udf = registry.udf(&e.name)?;
+            let args = e
+                .args
+                .iter()
+                .map(|x| parse_physical_expr(x, registry))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Arc::new(ScalarFunctionExpr::new(
+                &e.name,
+                udf.fun.clone(),
+                args,
+                &convert_required!(e.return_type)?,
+            ))
+        }
+    };
 
-                let args = e
-                    .args
-                    .iter()
-                    .map(|x| x.try_into())
-                    .collect::<Result<Vec<_>, _>>()?;
+    Ok(pexpr)
+}
 
-                let catalog_list =
-                    Arc::new(MemoryCatalogList::new()) as Arc<dyn CatalogList>;
-
-                let ctx_state = ExecutionContextState {
-                    catalog_list,
-                    scalar_functions: Default::default(),
-                    var_provider: Default::default(),
-                    aggregate_functions: Default::default(),
-                    config: ExecutionConfig::new(),
-                    execution_props: ExecutionProps::new(),
-                    object_store_registry: Arc::new(ObjectStoreRegistry::new()),
-                };
-
-                let fun_expr = functions::create_physical_fun(
-                    &(&scalar_function).into(),
-                    &ctx_state,
-                )?;
+fn parse_window_function(
+    proto: &protobuf::physical_window_expr_node::WindowFunction,
+    registry: &dyn FunctionRegistry,
+) -> Result<WindowFunction, BallistaError> {
+    match proto {
+        protobuf::physical_window_expr_node::WindowFunction::AggrFunction(n) => {
+            let f = protobuf::AggregateFunction::from_i32(*n).ok_or_else(|| {
+                proto_error(format!(
+                    "Received an unknown window aggregate function: {}",
+                    n
+                ))
+            })?;
 
-                Arc::new(ScalarFunctionExpr::new(
-                    &e.name,
-                    fun_expr,
-                    args,
-                    &convert_required!(e.return_type)?,
+            Ok(WindowFunction::AggregateFunction(f.into()))
+        }
+        protobuf::physical_window_expr_node::WindowFunction::BuiltInFunction(n) => {
+            let f = protobuf::BuiltInWindowFunction::from_i32(*n).ok_or_else(|| {
+                proto_error(format!(
+                    "Received an unknown window builtin function: {}",
+                    n
                 ))
-            }
-        };
+            })?;
 
-        Ok(pexpr)
+            Ok(WindowFunction::BuiltInWindowFunction(f.into()))
+        }
+        protobuf::physical_window_expr_node::WindowFunction::Udwf(name) => {
+            Ok(WindowFunction::WindowUDF(registry.udwf(name)?))
+        }
     }
 }
 
-impl TryFrom<&protobuf::physical_window_expr_node::WindowFunction> for WindowFunction {
-    type Error = BallistaError;
+fn parse_required_plan(
+    p: &Option<Box<protobuf::PhysicalPlanNode>>,
+    registry: &dyn FunctionRegistry,
+    codecs: &ExtensionCodecs,
+) -> Result<Arc<dyn ExecutionPlan>, BallistaError> {
+    match p {
+        Some(plan) => parse_physical_plan(plan, registry, codecs),
+        None => Err(proto_error("Missing required field in protobuf")),
+    }
+}
 
-    fn try_from(
-        expr: &protobuf::physical_window_expr_node::WindowFunction,
-    ) -> Result<Self, Self::Error> {
-        match expr {
-            protobuf::physical_window_expr_node::WindowFunction::AggrFunction(n) => {
-                let f = protobuf::AggregateFunction::from_i32(*n).ok_or_else(|| {
-                    proto_error(format!(
-                        "Received an unknown window aggregate function: {}",
-                        n
-                    ))
-                })?;
+fn parse_required_expr(
+    p: &Option<Box<protobuf::PhysicalExprNode>>,
+    registry: &dyn FunctionRegistry,
+) -> Result<Arc<dyn PhysicalExpr>, BallistaError> {
+    match p {
+        Some(expr) => parse_physical_expr(expr, registry),
+        None => Err(proto_error("Missing required field in protobuf")),
+    }
+}
 
-                Ok(WindowFunction::AggregateFunction(f.into()))
-            }
-            protobuf::physical_window_expr_node::WindowFunction::BuiltInFunction(n) => {
-                let f =
-                    protobuf::BuiltInWindowFunction::from_i32(*n).ok_or_else(|| {
-                        proto_error(format!(
-                            "Received an unknown window builtin function: {}",
-                            n
-                        ))
-                    })?;
+fn parse_required_physical_expr(
+    p: &Option<protobuf::PhysicalExprNode>,
+    registry: &dyn FunctionRegistry,
+) -> Result<Arc<dyn PhysicalExpr>, BallistaError> {
+    match p {
+        Some(expr) => parse_physical_expr(expr, registry),
+        None => Err(proto_error("Missing required field in protobuf")),
+    }
+}
 
-                Ok(WindowFunction::BuiltInWindowFunction(f.into()))
-            }
-        }
+fn parse_required_window_function(
+    p: &Option<protobuf::physical_window_expr_node::WindowFunction>,
+    registry: &dyn FunctionRegistry,
+) -> Result<WindowFunction, BallistaError> {
+    match p {
+        Some(fun) => parse_window_function(fun, registry),
+        None => Err(proto_error("Missing required field in protobuf")),
     }
 }
 
 pub fn parse_protobuf_hash_partitioning(
     partitioning: Option<&protobuf::PhysicalHashRepartition>,
+    registry: &dyn FunctionRegistry,
 ) -> Result<Option<Partitioning>, BallistaError> {
     match partitioning {
         Some(hash_part) => {
             let expr = hash_part
                 .hash_expr
                 .iter()
-                .map(|e| e.try_into())
+                .map(|e| parse_physical_expr(e, registry))
                 .collect::<Result<Vec<Arc<dyn PhysicalExpr>>, _>>()?;
 
             Ok(Some(Partitioning::Hash(
@@ -828,6 +933,7 @@ impl TryInto<PhysicalPlanConfig> for &protobuf::FileScanExecConf {
             batch_size: self.batch_size as usize,
             limit: self.limit.as_ref().map(|sl| sl.limit as usize),
             table_partition_cols: vec![],
+            output_ordering: None,
         })
     }
 }
@@ -20,23 +20,29 @@ pub mod to_proto;
 
 #[cfg(test)]
 mod roundtrip_tests {
-    use std::{convert::TryInto, sync::Arc};
+    use std::sync::Arc;
 
     use datafusion::{
         arrow::{
             compute::kernels::sort::SortOptions,
             datatypes::{DataType, Field, Schema},
         },
-        logical_plan::{JoinType, Operator},
+        error::DataFusionError,
+        execution::context::ExecutionContext,
+        logical_plan::{create_udaf, create_udf, JoinType, Operator},
         physical_plan::{
+            aggregates::AccumulatorFunctionImplementation,
             empty::EmptyExec,
             expressions::{binary, col, lit, InListExpr, NotExpr},
             expressions::{Avg, Column, PhysicalSortExpr},
             filter::FilterExec,
+            functions::{ScalarFunctionImplementation, Volatility},
             hash_aggregate::{AggregateMode, HashAggregateExec},
             hash_join::{HashJoinExec, PartitionMode},
             limit::{GlobalLimitExec, LocalLimitExec},
             sort::SortExec,
+            udaf, udf,
+            union::UnionExec,
             AggregateExpr, ColumnarValue, Distribution, ExecutionPlan, Partitioning,
             PhysicalExpr,
         },
@@ -44,12 +50,29 @@ mod roundtrip_tests {
     };
 
     use super::super::super::error::Result;
+    use super::super::extension::{ExtensionCodecs, PhysicalExtensionCodec};
     use super::super::protobuf;
+    use super::from_proto::parse_physical_plan;
+    use super::to_proto::serialize_physical_plan;
     use crate::execution_plans::ShuffleWriterExec;
 
     fn roundtrip_test(exec_plan: Arc<dyn ExecutionPlan>) -> Result<()> {
-        let proto: protobuf::PhysicalPlanNode = exec_plan.clone().try_into()?;
-        let result_exec_plan: Arc<dyn ExecutionPlan> = (&proto).try_into()?;
+        roundtrip_test_with_context(
+            exec_plan,
+            ExecutionContext::new(),
+            ExtensionCodecs::new(),
+        )
+    }
+
+    fn roundtrip_test_with_context(
+        exec_plan: Arc<dyn ExecutionPlan>,
+        ctx: ExecutionContext,
+        codecs: ExtensionCodecs,
+    ) -> Result<()> {
+        let proto: protobuf::PhysicalPlanNode =
+            serialize_physical_plan(exec_plan.clone(), &codecs)?;
+        let result_exec_plan: Arc<dyn ExecutionPlan> =
+            parse_physical_plan(&proto, &ctx, &codecs)?;
         assert_eq!(
             format!("{:?}", exec_plan),
             format!("{:?}", result_exec_plan)
@@ -201,4 +224,89 @@ mod roundtrip_tests {
             Some(Partitioning::Hash(vec![Arc::new(Column::new("a", 0))], 4)),
         )?))
     }
+
+    #[test]
+    fn roundtrip_hash_aggregate_with_udf_and_udaf() -> Result<()> {
+        let field_a = Field::new("a", DataType::Int64, false);
+        let field_b = Field::new("b", DataType::Int64, false);
+        let schema = Arc::new(Schema::new(vec![field_a, field_b]));
+
+        let fun: ScalarFunctionImplementation =
+            Arc::new(|_| Err(DataFusionError::NotImplemented("dummy_udf".to_owned())));
+        let udf = create_udf(
+            "physical_dummy_udf",
+            vec![DataType::Int64],
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            fun,
+        );
+        let mut ctx = ExecutionContext::new();
+        ctx.register_udf(udf.clone());
+
+        let accumulator: AccumulatorFunctionImplementation =
+            Arc::new(|| Err(DataFusionError::NotImplemented("dummy_udaf".to_owned())));
+        let udaf = create_udaf(
+            "physical_dummy_udaf",
+            DataType::Int64,
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            accumulator,
+            Arc::new(vec![DataType::Int64]),
+        );
+        ctx.register_udaf(udaf.clone());
+
+        let groups: Vec<(Arc<dyn PhysicalExpr>, String)> =
+            vec![(col("a", &schema)?, "unused".to_string())];
+        let udf_expr = udf::create_physical_expr(&udf, &[col("b", &schema)?], &schema)?;
+        let aggregates: Vec<Arc<dyn AggregateExpr>> = vec![udaf::create_aggregate_expr(
+            &udaf,
+            &[udf_expr],
+            &schema,
+            "physical_dummy_udaf(physical_dummy_udf(b))",
+        )?];
+
+        roundtrip_test_with_context(
+            Arc::new(HashAggregateExec::try_new(
+                AggregateMode::Final,
+                groups,
+                aggregates,
+                Arc::new(EmptyExec::new(false, schema.clone())),
+                schema,
+            )?),
+            ctx,
+            ExtensionCodecs::new(),
+        )
+    }
+
+    struct UnionCodec {}
+
+    impl PhysicalExtensionCodec for UnionCodec {
+        fn try_encode(&self, plan: &dyn ExecutionPlan) -> Result<Option<Vec<u8>>> {
+            Ok(plan.as_any().downcast_ref::<UnionExec>().map(|_| vec![]))
+        }
+
+        fn try_decode(
+            &self,
+            _buf: &[u8],
+            inputs: Vec<Arc<dyn ExecutionPlan>>,
+        ) -> Result<Arc<dyn ExecutionPlan>> {
+            Ok(Arc::new(UnionExec::new(inputs)))
+        }
+    }
+
+    #[test]
+    fn roundtrip_extension() -> Result<()> {
+        let codecs = ExtensionCodecs::new();
+        codecs.register_physical_extension_codec("union", Arc::new(UnionCodec {}));
+
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        roundtrip_test_with_context(
+            Arc::new(UnionExec::new(vec![
+                Arc::new(EmptyExec::new(false, schema.clone())),
+                Arc::new(EmptyExec::new(true, schema)),
+            ])),
+            ExecutionContext::new(),
+            codecs,
+        )
+    }
 }
@@ -64,6 +64,7 @@ use datafusion::physical_plan::{AggregateExpr, ExecutionPlan, PhysicalExpr};
 use datafusion::physical_plan::hash_aggregate::HashAggregateExec;
 use protobuf::physical_plan_node::PhysicalPlanType;
 
+use crate::serde::extension::ExtensionCodecs;
 use crate::serde::protobuf::repartition_exec_node::PartitionMethod;
 use crate::serde::scheduler::PartitionLocation;
 use crate::serde::{protobuf, BallistaError};
@@ -77,303 +78,301 @@ use crate::{
 use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
 use datafusion::physical_plan::functions::{BuiltinScalarFunction, ScalarFunctionExpr};
 use datafusion::physical_plan::repartition::RepartitionExec;
+use datafusion::physical_plan::udaf::AggregateFunctionExpr;
 
-impl TryInto<protobuf::PhysicalPlanNode> for Arc<dyn ExecutionPlan> {
-    type Error = BallistaError;
-
-    fn try_into(self) -> Result<protobuf::PhysicalPlanNode, Self::Error> {
-        let plan = self.as_any();
+/// Converts an execution plan into its protobuf representation, encoding the
+/// plans that Ballista doesn't serialize itself with the codecs registered with
+/// `codecs`
+pub fn serialize_physical_plan(
+    plan: Arc<dyn ExecutionPlan>,
+    codecs: &ExtensionCodecs,
+) -> Result<protobuf::PhysicalPlanNode, BallistaError> {
+    let plan_clone = plan.clone();
+    let plan = plan.as_any();
 
-        if let Some(exec) = plan.downcast_ref::<ProjectionExec>() {
-            let input: protobuf::PhysicalPlanNode = exec.input().to_owned().try_into()?;
-            let expr = exec
-                .expr()
-                .iter()
-                .map(|expr| expr.0.clone().try_into())
-                .collect::<Result<Vec<_>, Self::Error>>()?;
-            let expr_name = exec.expr().iter().map(|expr| expr.1.clone()).collect();
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::Projection(Box::new(
-                    protobuf::ProjectionExecNode {
-                        input: Some(Box::new(input)),
-                        expr,
-                        expr_name,
-                    },
-                ))),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<FilterExec>() {
-            let input: protobuf::PhysicalPlanNode = exec.input().to_owned().try_into()?;
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::Filter(Box::new(
-                    protobuf::FilterExecNode {
-                        input: Some(Box::new(input)),
-                        expr: Some(exec.predicate().clone().try_into()?),
-                    },
-                ))),
-            })
-        } else if let Some(limit) = plan.downcast_ref::<GlobalLimitExec>() {
-            let input: protobuf::PhysicalPlanNode =
-                limit.input().to_owned().try_into()?;
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::GlobalLimit(Box::new(
-                    protobuf::GlobalLimitExecNode {
-                        input: Some(Box::new(input)),
-                        limit: limit.limit() as u32,
-                    },
-                ))),
-            })
-        } else if let Some(limit) = plan.downcast_ref::<LocalLimitExec>() {
-            let input: protobuf::PhysicalPlanNode =
-                limit.input().to_owned().try_into()?;
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::LocalLimit(Box::new(
-                    protobuf::LocalLimitExecNode {
-                        input: Some(Box::new(input)),
-                        limit: limit.limit() as u32,
-                    },
-                ))),
+    if let Some(exec) = plan.downcast_ref::<ProjectionExec>() {
+        let input = serialize_physical_plan(exec.input().to_owned(), codecs)?;
+        let expr = exec
+            .expr()
+            .iter()
+            .map(|expr| expr.0.clone().try_into())
+            .collect::<Result<Vec<_>, BallistaError>>()?;
+        let expr_name = exec.expr().iter().map(|expr| expr.1.clone()).collect();
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::Projection(Box::new(
+                protobuf::ProjectionExecNode {
+                    input: Some(Box::new(input)),
+                    expr,
+                    expr_name,
+                },
+            ))),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<FilterExec>() {
+        let input = serialize_physical_plan(exec.input().to_owned(), codecs)?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::Filter(Box::new(
+                protobuf::FilterExecNode {
+                    input: Some(Box::new(input)),
+                    expr: Some(exec.predicate().clone().try_into()?),
+                },
+            ))),
+        })
+    } else if let Some(limit) = plan.downcast_ref::<GlobalLimitExec>() {
+        let input = serialize_physical_plan(limit.input().to_owned(), codecs)?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::GlobalLimit(Box::new(
+                protobuf::GlobalLimitExecNode {
+                    input: Some(Box::new(input)),
+                    limit: limit.limit() as u32,
+                },
+            ))),
+        })
+    } else if let Some(limit) = plan.downcast_ref::<LocalLimitExec>() {
+        let input = serialize_physical_plan(limit.input().to_owned(), codecs)?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::LocalLimit(Box::new(
+                protobuf::LocalLimitExecNode {
+                    input: Some(Box::new(input)),
+                    limit: limit.limit() as u32,
+                },
+            ))),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<HashJoinExec>() {
+        let left = serialize_physical_plan(exec.left().to_owned(), codecs)?;
+        let right = serialize_physical_plan(exec.right().to_owned(), codecs)?;
+        let on: Vec<protobuf::JoinOn> = exec
+            .on()
+            .iter()
+            .map(|tuple| protobuf::JoinOn {
+                left: Some(protobuf::PhysicalColumn {
+                    name: tuple.0.name().to_string(),
+                    index: tuple.0.index() as u32,
+                }),
+                right: Some(protobuf::PhysicalColumn {
+                    name: tuple.1.name().to_string(),
+                    index: tuple.1.index() as u32,
+                }),
             })
-        } else if let Some(exec) = plan.downcast_ref::<HashJoinExec>() {
-            let left: protobuf::PhysicalPlanNode = exec.left().to_owned().try_into()?;
-            let right: protobuf::PhysicalPlanNode = exec.right().to_owned().try_into()?;
-            let on: Vec<protobuf::JoinOn> = exec
-                .on()
-                .iter()
-                .map(|tuple| protobuf::JoinOn {
-                    left: Some(protobuf::PhysicalColumn {
-                        name: tuple.0.name().to_string(),
-                        index: tuple.0.index() as u32,
-                    }),
-                    right: Some(protobuf::PhysicalColumn {
-                        name: tuple.1.name().to_string(),
-                        index: tuple.1.index() as u32,
-                    }),
-                })
-                .collect();
-            let join_type: protobuf::JoinType = exec.join_type().to_owned().into();
+            .collect();
+        let join_type: protobuf::JoinType = exec.join_type().to_owned().into();
 
-            let partition_mode = match exec.partition_mode() {
-                PartitionMode::CollectLeft => protobuf::PartitionMode::CollectLeft,
-                PartitionMode::Partitioned => protobuf::PartitionMode::Partitioned,
-            };
+        let partition_mode = match exec.partition_mode() {
+            PartitionMode::CollectLeft => protobuf::PartitionMode::CollectLeft,
+            PartitionMode::Partitioned => protobuf::PartitionMode::Partitioned,
+        };
 
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::HashJoin(Box::new(
-                    protobuf::HashJoinExecNode {
-                        left: Some(Box::new(left)),
-                        right: Some(Box::new(right)),
-                        on,
-                        join_type: join_type.into(),
-                        partition_mode: partition_mode.into(),
-                        null_equals_null: *exec.null_equals_null(),
-                    },
-                ))),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<CrossJoinExec>() {
-            let left: protobuf::PhysicalPlanNode = exec.left().to_owned().try_into()?;
-            let right: protobuf::PhysicalPlanNode = exec.right().to_owned().try_into()?;
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::CrossJoin(Box::new(
-                    protobuf::CrossJoinExecNode {
-                        left: Some(Box::new(left)),
-                        right: Some(Box::new(right)),
-                    },
-                ))),
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::HashJoin(Box::new(
+                protobuf::HashJoinExecNode {
+                    left: Some(Box::new(left)),
+                    right: Some(Box::new(right)),
+                    on,
+                    join_type: join_type.into(),
+                    partition_mode: partition_mode.into(),
+                    null_equals_null: *exec.null_equals_null(),
+                },
+            ))),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<CrossJoinExec>() {
+        let left = serialize_physical_plan(exec.left().to_owned(), codecs)?;
+        let right = serialize_physical_plan(exec.right().to_owned(), codecs)?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::CrossJoin(Box::new(
+                protobuf::CrossJoinExecNode {
+                    left: Some(Box::new(left)),
+                    right: Some(Box::new(right)),
+                },
+            ))),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<HashAggregateExec>() {
+        let groups = exec
+            .group_expr()
+            .iter()
+            .map(|expr| expr.0.to_owned().try_into())
+            .collect::<Result<Vec<_>, BallistaError>>()?;
+        let group_names = exec
+            .group_expr()
+            .iter()
+            .map(|expr| expr.1.to_owned())
+            .collect();
+        let agg = exec
+            .aggr_expr()
+            .iter()
+            .map(|expr| expr.to_owned().try_into())
+            .collect::<Result<Vec<_>, BallistaError>>()?;
+        let agg_names = exec
+            .aggr_expr()
+            .iter()
+            .map(|expr| match expr.field() {
+                Ok(field) => Ok(field.name().clone()),
+                Err(e) => Err(BallistaError::DataFusionError(e)),
             })
-        } else if let Some(exec) = plan.downcast_ref::<HashAggregateExec>() {
-            let groups = exec
-                .group_expr()
-                .iter()
-                .map(|expr| expr.0.to_owned().try_into())
-                .collect::<Result<Vec<_>, BallistaError>>()?;
-            let group_names = exec
-                .group_expr()
-                .iter()
-                .map(|expr| expr.1.to_owned())
-                .collect();
-            let agg = exec
-                .aggr_expr()
-                .iter()
-                .map(|expr| expr.to_owned().try_into())
-                .collect::<Result<Vec<_>, BallistaError>>()?;
-            let agg_names = exec
-                .aggr_expr()
-                .iter()
-                .map(|expr| match expr.field() {
-                    Ok(field) => Ok(field.name().clone()),
-                    Err(e) => Err(BallistaError::DataFusionError(e)),
-                })
-                .collect::<Result<_, Self::Error>>()?;
+            .collect::<Result<_, BallistaError>>()?;
 
-            let agg_mode = match exec.mode() {
-                AggregateMode::Partial => protobuf::AggregateMode::Partial,
-                AggregateMode::Final => protobuf::AggregateMode::Final,
-                AggregateMode::FinalPartitioned => {
-                    protobuf::AggregateMode::FinalPartitioned
-                }
-            };
-            let input_schema = exec.input_schema();
-            let input: protobuf::PhysicalPlanNode = exec.input().to_owned().try_into()?;
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::HashAggregate(Box::new(
-                    protobuf::HashAggregateExecNode {
-                        group_expr: groups,
-                        group_expr_name: group_names,
-                        aggr_expr: agg,
-                        aggr_expr_name: agg_names,
-                        mode: agg_mode as i32,
-                        input: Some(Box::new(input)),
-                        input_schema: Some(input_schema.as_ref().into()),
-                    },
-                ))),
-            })
-        } else if let Some(empty) = plan.downcast_ref::<EmptyExec>() {
-            let schema = empty.schema().as_ref().into();
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::Empty(
-                    protobuf::EmptyExecNode {
-                        produce_one_row: empty.produce_one_row(),
-                        schema: Some(schema),
-                    },
-                )),
-            })
-        } else if let Some(coalesce_batches) = plan.downcast_ref::<CoalesceBatchesExec>()
-        {
-            let input: protobuf::PhysicalPlanNode =
-                coalesce_batches.input().to_owned().try_into()?;
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::CoalesceBatches(Box::new(
-                    protobuf::CoalesceBatchesExecNode {
-                        input: Some(Box::new(input)),
-                        target_batch_size: coalesce_batches.target_batch_size() as u32,
-                    },
-                ))),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<CsvExec>() {
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::CsvScan(
-                    protobuf::CsvScanExecNode {
-                        base_conf: Some(exec.base_config().try_into()?),
-                        has_header: exec.has_header(),
-                        delimiter: byte_to_string(exec.delimiter())?,
-                    },
-                )),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<ParquetExec>() {
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::ParquetScan(
-                    protobuf::ParquetScanExecNode {
-                        base_conf: Some(exec.base_config().try_into()?),
-                        // TODO serialize predicates
-                    },
-                )),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<AvroExec>() {
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::AvroScan(
-                    protobuf::AvroScanExecNode {
-                        base_conf: Some(exec.base_config().try_into()?),
-                    },
-                )),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<ShuffleStreamReaderExec>() {
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::ShuffleStreamReader(
-                    protobuf::ShuffleStreamReaderExecNode {
-                        stage_id: exec.stage_id as u32,
-                        partition_count: exec.partition_count as u64,
-                        schema: Some(exec.schema().as_ref().into()),
-                    },
-                )),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<ShuffleReaderExec>() {
-            let mut partition = vec![];
-            for location in &exec.partition {
-                partition.push(protobuf::ShuffleReaderPartition {
-                    location: location
+        let agg_mode = match exec.mode() {
+            AggregateMode::Partial => protobuf::AggregateMode::Partial,
+            AggregateMode::Final => protobuf::AggregateMode::Final,
+            AggregateMode::FinalPartitioned => protobuf::AggregateMode::FinalPartitioned,
+        };
+        let input_schema = exec.input_schema();
+        let input = serialize_physical_plan(exec.input().to_owned(), codecs)?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::HashAggregate(Box::new(
+                protobuf::HashAggregateExecNode {
+                    group_expr: groups,
+                    group_expr_name: group_names,
+                    aggr_expr: agg,
+                    aggr_expr_name: agg_names,
+                    mode: agg_mode as i32,
+                    input: Some(Box::new(input)),
+                    input_schema: Some(input_schema.as_ref().into()),
+                },
+            ))),
+        })
+    } else if let Some(empty) = plan.downcast_ref::<EmptyExec>() {
+        let schema = empty.schema().as_ref().into();
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::Empty(protobuf::EmptyExecNode {
+                produce_one_row: empty.produce_one_row(),
+                schema: Some(schema),
+            })),
+        })
+    } else if let Some(coalesce_batches) = plan.downcast_ref::<CoalesceBatchesExec>() {
+        let input = serialize_physical_plan(coalesce_batches.input().to_owned(), codecs)?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::CoalesceBatches(Box::new(
+                protobuf::CoalesceBatchesExecNode {
+                    input: Some(Box::new(input)),
+                    target_batch_size: coalesce_batches.target_batch_size() as u32,
+                },
+            ))),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<CsvExec>() {
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::CsvScan(
+                protobuf::CsvScanExecNode {
+                    base_conf: Some(exec.base_config().try_into()?),
+                    has_header: exec.has_header(),
+                    delimiter: byte_to_string(exec.delimiter())?,
+                },
+            )),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<ParquetExec>() {
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::ParquetScan(
+                protobuf::ParquetScanExecNode {
+                    base_conf: Some(exec.base_config().try_into()?),
+                    // TODO serialize predicates
+                },
+            )),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<AvroExec>() {
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::AvroScan(
+                protobuf::AvroScanExecNode {
+                    base_conf: Some(exec.base_config().try_into()?),
+                },
+            )),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<ShuffleStreamReaderExec>() {
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::ShuffleStreamReader(
+                protobuf::ShuffleStreamReaderExecNode {
+                    stage_id: exec.stage_id as u32,
+                    partition_count: exec.partition_count as u64,
+                    schema: Some(exec.schema().as_ref().into()),
+                },
+            )),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<ShuffleReaderExec>() {
+        let mut partition = vec![];
+        for location in &exec.partition {
+            partition.push(protobuf::ShuffleReaderPartition {
+                location: location
+                    .iter()
+                    .map(|l| l.clone().try_into())
+                    .collect::<Result<Vec<_>, _>>()?,
+            });
+        }
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::ShuffleReader(
+                protobuf::ShuffleReaderExecNode {
+                    partition,
+                    schema: Some(exec.schema().as_ref().into()),
+                },
+            )),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<CoalescePartitionsExec>() {
+        let input = serialize_physical_plan(exec.input().to_owned(), codecs)?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::Merge(Box::new(
+                protobuf::CoalescePartitionsExecNode {
+                    input: Some(Box::new(input)),
+                },
+            ))),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<RepartitionExec>() {
+        let input = serialize_physical_plan(exec.input().to_owned(), codecs)?;
+
+        let pb_partition_method = match exec.partitioning() {
+            Partitioning::Hash(exprs, partition_count) => {
+                PartitionMethod::Hash(protobuf::PhysicalHashRepartition {
+                    hash_expr: exprs
                         .iter()
-                        .map(|l| l.clone().try_into())
-                        .collect::<Result<Vec<_>, _>>()?,
-                });
+                        .map(|expr| expr.clone().try_into())
+                        .collect::<Result<Vec<_>, BallistaError>>()?,
+                    partition_count: *partition_count as u64,
+                })
             }
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::ShuffleReader(
-                    protobuf::ShuffleReaderExecNode {
-                        partition,
-                        schema: Some(exec.schema().as_ref().into()),
-                    },
-                )),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<CoalescePartitionsExec>() {
-            let input: protobuf::PhysicalPlanNode = exec.input().to_owned().try_into()?;
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::Merge(Box::new(
-                    protobuf::CoalescePartitionsExecNode {
-                        input: Some(Box::new(input)),
-                    },
-                ))),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<RepartitionExec>() {
-            let input: protobuf::PhysicalPlanNode = exec.input().to_owned().try_into()?;
-
-            let pb_partition_method = match exec.partitioning() {
-                Partitioning::Hash(exprs, partition_count) => {
-                    PartitionMethod::Hash(protobuf::PhysicalHashRepartition {
-                        hash_expr: exprs
-                            .iter()
-                            .map(|expr| expr.clone().try_into())
-                            .collect::<Result<Vec<_>, BallistaError>>()?,
-                        partition_count: *partition_count as u64,
-                    })
-                }
-                Partitioning::RoundRobinBatch(partition_count) => {
-                    PartitionMethod::RoundRobin(*partition_count as u64)
-                }
-                Partitioning::UnknownPartitioning(partition_count) => {
-                    PartitionMethod::Unknown(*partition_count as u64)
-                }
-            };
+            Partitioning::RoundRobinBatch(partition_count) => {
+                PartitionMethod::RoundRobin(*partition_count as u64)
+            }
+            Partitioning::UnknownPartitioning(partition_count) => {
+                PartitionMethod::Unknown(*partition_count as u64)
+            }
+        };
 
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::Repartition(Box::new(
-                    protobuf::RepartitionExecNode {
-                        input: Some(Box::new(input)),
-                        partition_method: Some(pb_partition_method),
-                    },
-                ))),
-            })
-        } else if let Some(exec) = plan.downcast_ref::<SortExec>() {
-            let input: protobuf::PhysicalPlanNode = exec.input().to_owned().try_into()?;
-            let expr = exec
-                .expr()
-                .iter()
-                .map(|expr| {
-                    let sort_expr = Box::new(protobuf::PhysicalSortExprNode {
-                        expr: Some(Box::new(expr.expr.to_owned().try_into()?)),
-                        asc: !expr.options.descending,
-                        nulls_first: expr.options.nulls_first,
-                    });
-                    Ok(protobuf::PhysicalExprNode {
-                        expr_type: Some(protobuf::physical_expr_node::ExprType::Sort(
-                            sort_expr,
-                        )),
-                    })
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::Repartition(Box::new(
+                protobuf::RepartitionExecNode {
+                    input: Some(Box::new(input)),
+                    partition_method: Some(pb_partition_method),
+                },
+            ))),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<SortExec>() {
+        let input = serialize_physical_plan(exec.input().to_owned(), codecs)?;
+        let expr = exec
+            .expr()
+            .iter()
+            .map(|expr| {
+                let sort_expr = Box::new(protobuf::PhysicalSortExprNode {
+                    expr: Some(Box::new(expr.expr.to_owned().try_into()?)),
+                    asc: !expr.options.descending,
+                    nulls_first: expr.options.nulls_first,
+                });
+                Ok(protobuf::PhysicalExprNode {
+                    expr_type: Some(protobuf::physical_expr_node::ExprType::Sort(
+                        sort_expr,
+                    )),
                 })
-                .collect::<Result<Vec<_>, Self::Error>>()?;
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::Sort(Box::new(
-                    protobuf::SortExecNode {
-                        input: Some(Box::new(input)),
-                        expr,
-                    },
-                ))),
             })
-        } else if let Some(exec) = plan.downcast_ref::<ShuffleWriterExec>() {
-            let input: protobuf::PhysicalPlanNode =
-                exec.children()[0].to_owned().try_into()?;
-            // note that we use shuffle_output_partitioning() rather than output_partitioning()
-            // to get the true output partitioning
-            let output_partitioning = match exec.shuffle_output_partitioning() {
+            .collect::<Result<Vec<_>, BallistaError>>()?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::Sort(Box::new(
+                protobuf::SortExecNode {
+                    input: Some(Box::new(input)),
+                    expr,
+                    fetch: exec.fetch().map(|fetch| fetch as i64).unwrap_or(-1),
+                    preserve_partitioning: exec.preserve_partitioning(),
+                },
+            ))),
+        })
+    } else if let Some(exec) = plan.downcast_ref::<ShuffleWriterExec>() {
+        let input = serialize_physical_plan(exec.children()[0].to_owned(), codecs)?;
+        // note that we use shuffle_output_partitioning() rather than output_partitioning()
+        // to get the true output partitioning
+        let output_partitioning = match exec.shuffle_output_partitioning() {
                 Some(Partitioning::Hash(exprs, partition_count)) => {
                     Some(protobuf::PhysicalHashRepartition {
                         hash_expr: exprs
@@ -391,53 +390,63 @@ impl TryInto<protobuf::PhysicalPlanNode> for Arc<dyn ExecutionPlan> {
                     )))
                 }
             };
-            match &exec.output_loc {
-                OutputLocation::LocalDir(_) => Ok(protobuf::PhysicalPlanNode {
+        match &exec.output_loc {
+            OutputLocation::LocalDir(_) => Ok(protobuf::PhysicalPlanNode {
+                physical_plan_type: Some(PhysicalPlanType::ShuffleWriter(Box::new(
+                    protobuf::ShuffleWriterExecNode {
+                        job_id: exec.job_id().to_string(),
+                        stage_id: exec.stage_id() as u32,
+                        input: Some(Box::new(input)),
+                        output_partitioning,
+                        push_shuffle: false,
+                        execs: Vec::new(),
+                    },
+                ))),
+            }),
+            OutputLocation::Executors(executors) => {
+                let _execs: Vec<protobuf::ExecutorMetadata> =
+                    executors.to_owned().into_iter().map(|e| e.into()).collect();
+                Ok(protobuf::PhysicalPlanNode {
                     physical_plan_type: Some(PhysicalPlanType::ShuffleWriter(Box::new(
                         protobuf::ShuffleWriterExecNode {
                             job_id: exec.job_id().to_string(),
                             stage_id: exec.stage_id() as u32,
                             input: Some(Box::new(input)),
                             output_partitioning,
-                            push_shuffle: false,
-                            execs: Vec::new(),
+                            push_shuffle: true,
+                            execs: _execs,
                         },
                     ))),
-                }),
-                OutputLocation::Executors(executors) => {
-                    let _execs: Vec<protobuf::ExecutorMetadata> =
-                        executors.to_owned().into_iter().map(|e| e.into()).collect();
-                    Ok(protobuf::PhysicalPlanNode {
-                        physical_plan_type: Some(PhysicalPlanType::ShuffleWriter(
-                            Box::new(protobuf::ShuffleWriterExecNode {
-                                job_id: exec.job_id().to_string(),
-                                stage_id: exec.stage_id() as u32,
-                                input: Some(Box::new(input)),
-                                output_partitioning,
-                                push_shuffle: true,
-                                execs: _execs,
-                            }),
-                        )),
-                    })
-                }
+                })
             }
-        } else if let Some(exec) = plan.downcast_ref::<UnresolvedShuffleExec>() {
-            Ok(protobuf::PhysicalPlanNode {
-                physical_plan_type: Some(PhysicalPlanType::Unresolved(
-                    protobuf::UnresolvedShuffleExecNode {
-                        stage_id: exec.stage_id as u32,
-                        schema: Some(exec.schema().as_ref().into()),
-                        input_partition_count: exec.input_partition_count as u32,
-                        output_partition_count: exec.output_partition_count as u32,
-                    },
-                )),
-            })
-        } else {
-            Err(BallistaError::General(format!(
-                "physical plan to_proto unsupported plan {:?}",
-                self
-            )))
         }
+    } else if let Some(exec) = plan.downcast_ref::<UnresolvedShuffleExec>() {
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::Unresolved(
+                protobuf::UnresolvedShuffleExecNode {
+                    stage_id: exec.stage_id as u32,
+                    schema: Some(exec.schema().as_ref().into()),
+                    input_partition_count: exec.input_partition_count as u32,
+                    output_partition_count: exec.output_partition_count as u32,
+                },
+            )),
+        })
+    } else {
+        let (codec, node) = codecs.encode_physical_extension(plan_clone.as_ref())?;
+        let inputs = plan_clone
+            .children()
+            .into_iter()
+            .map(|input| serialize_physical_plan(input, codecs))
+            .collect::<Result<Vec<protobuf::PhysicalPlanNode>, BallistaError>>()?;
+        Ok(protobuf::PhysicalPlanNode {
+            physical_plan_type: Some(PhysicalPlanType::Extension(
+                protobuf::PhysicalExtensionNode {
+                    codec,
+                    node,
+                    inputs,
+                },
+            )),
+        })
     }
 }
 
@@ -445,6 +454,22 @@ impl TryInto<protobuf::PhysicalExprNode> for Arc<dyn AggregateExpr> {
     type Error = BallistaError;
 
     fn try_into(self) -> Result<protobuf::PhysicalExprNode, Self::Error> {
+        if let Some(expr) = self.as_any().downcast_ref::<AggregateFunctionExpr>() {
+            return Ok(protobuf::PhysicalExprNode {
+                expr_type: Some(
+                    protobuf::physical_expr_node::ExprType::AggregateUdfExpr(
+                        protobuf::PhysicalAggregateUdfNode {
+                            fun_name: expr.fun().name.clone(),
+                            args: expr
+                                .expressions()
+                                .into_iter()
+                                .map(|e| e.try_into())
+                                .collect::<Result<Vec<_>, BallistaError>>()?,
+                        },
+                    ),
+                ),
+            });
+        }
         let aggr_function = if self.as_any().downcast_ref::<Avg>().is_some() {
             Ok(protobuf::AggregateFunction::Avg.into())
         } else if self.as_any().downcast_ref::<Sum>().is_some() {
@@ -612,23 +637,35 @@ impl TryFrom<Arc<dyn PhysicalExpr>> for protobuf::PhysicalExprNode {
                 )),
             })
         } else if let Some(expr) = expr.downcast_ref::<ScalarFunctionExpr>() {
-            let fun: BuiltinScalarFunction =
-                BuiltinScalarFunction::from_str(expr.name())?;
-            let fun: protobuf::ScalarFunction = (&fun).try_into()?;
             let args: Vec<protobuf::PhysicalExprNode> = expr
                 .args()
                 .iter()
                 .map(|e| e.to_owned().try_into())
                 .collect::<Result<Vec<_>, _>>()?;
-            Ok(protobuf::PhysicalExprNode {
-                expr_type: Some(protobuf::physical_expr_node::ExprType::ScalarFunction(
-                    protobuf::PhysicalScalarFunctionNode {
+            // functions that are not built in are user-defined ones, which are
+            // looked up by name when deserializing
+            let expr_type = match BuiltinScalarFunction::from_str(expr.name()) {
+                Ok(fun) => {
+                    let fun: protobuf::ScalarFunction = (&fun).try_into()?;
+                    protobuf::physical_expr_node::ExprType::ScalarFunction(
+                        protobuf::PhysicalScalarFunctionNode {
+                            name: expr.name().to_string(),
+                            fun: fun.into(),
+                            args,
+                            return_type: Some(expr.return_type().into()),
+                        },
+                    )
+                }
+                Err(_) => protobuf::physical_expr_node::ExprType::ScalarUdf(
+                    protobuf::PhysicalScalarUdfNode {
                         name: expr.name().to_string(),
-                        fun: fun.into(),
                         args,
                         return_type: Some(expr.return_type().into()),
                     },
-                )),
+                ),
+            };
+            Ok(protobuf::PhysicalExprNode {
+                expr_type: Some(expr_type),
             })
         } else {
             Err(BallistaError::General(format!(
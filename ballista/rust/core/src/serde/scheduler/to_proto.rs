@@ -18,6 +18,8 @@
 use std::convert::TryInto;
 
 use crate::error::BallistaError;
+use crate::serde::extension::ExtensionCodecs;
+use crate::serde::physical_plan::to_proto::serialize_physical_plan;
 use crate::serde::protobuf;
 use crate::serde::protobuf::action::ActionType;
 use crate::serde::scheduler::{
@@ -60,21 +62,22 @@ impl TryInto<protobuf::Action> for Action {
     }
 }
 
-impl TryInto<protobuf::ExecutePartition> for ExecutePartition {
-    type Error = BallistaError;
-
-    fn try_into(self) -> Result<protobuf::ExecutePartition, Self::Error> {
-        Ok(protobuf::ExecutePartition {
-            job_id: self.job_id,
-            stage_id: self.stage_id as u32,
-            partition_id: self.partition_id.iter().map(|n| *n as u32).collect(),
-            plan: Some(self.plan.try_into()?),
-            partition_location: vec![],
-            output_partitioning: hash_partitioning_to_proto(
-                self.output_partitioning.as_ref(),
-            )?,
-        })
-    }
+/// Converts the partitions to execute into their protobuf representation,
+/// encoding the plans that Ballista doesn't serialize itself with `codecs`
+pub fn execute_partition_to_proto(
+    partition: ExecutePartition,
+    codecs: &ExtensionCodecs,
+) -> Result<protobuf::ExecutePartition, BallistaError> {
+    Ok(protobuf::ExecutePartition {
+        job_id: partition.job_id,
+        stage_id: partition.stage_id as u32,
+        partition_id: partition.partition_id.iter().map(|n| *n as u32).collect(),
+        plan: Some(serialize_physical_plan(partition.plan, codecs)?),
+        partition_location: vec![],
+        output_partitioning: hash_partitioning_to_proto(
+            partition.output_partitioning.as_ref(),
+        )?,
+    })
 }
 
 #[allow(clippy::from_over_into)]
@@ -27,6 +27,7 @@ use crate::execution_plans::{
     DistributedQueryExec, ShuffleWriterExec, UnresolvedShuffleExec,
 };
 use crate::memory_stream::MemoryStream;
+use crate::serde::extension::ExtensionCodecs;
 use crate::serde::scheduler::PartitionStats;
 
 use crate::client::BallistaClient;
@@ -59,10 +60,12 @@ use datafusion::physical_plan::file_format::{CsvExec, ParquetExec};
 use datafusion::physical_plan::filter::FilterExec;
 use datafusion::physical_plan::hash_aggregate::HashAggregateExec;
 use datafusion::physical_plan::hash_join::HashJoinExec;
+use datafusion::physical_plan::planner::DefaultPhysicalPlanner;
 use datafusion::physical_plan::projection::ProjectionExec;
 use datafusion::physical_plan::sort::SortExec;
 use datafusion::physical_plan::{
-    metrics, AggregateExpr, ExecutionPlan, Metric, PhysicalExpr, RecordBatchStream,
+    metrics, AggregateExpr, ExecutionPlan, Metric, PhysicalExpr, PhysicalPlanner,
+    RecordBatchStream,
 };
 use futures::{future, Stream, StreamExt};
 use std::time::Instant;
@@ -269,12 +272,14 @@ pub fn create_df_ctx_with_ballista_query_planner(
     scheduler_host: &str,
     scheduler_port: u16,
     config: &BallistaConfig,
+    codecs: Arc<ExtensionCodecs>,
 ) -> ExecutionContext {
     let scheduler_url = format!("http://{}:{}", scheduler_host, scheduler_port);
     let config = ExecutionConfig::new()
         .with_query_planner(Arc::new(BallistaQueryPlanner::new(
             scheduler_url,
             config.clone(),
+            codecs,
         )))
         .with_target_partitions(config.default_shuffle_partitions());
     ExecutionContext::with_config(config)
@@ -283,13 +288,19 @@ pub fn create_df_ctx_with_ballista_query_planner(
 pub struct BallistaQueryPlanner {
     scheduler_url: String,
     config: BallistaConfig,
+    codecs: Arc<ExtensionCodecs>,
 }
 
 impl BallistaQueryPlanner {
-    pub fn new(scheduler_url: String, config: BallistaConfig) -> Self {
+    pub fn new(
+        scheduler_url: String,
+        config: BallistaConfig,
+        codecs: Arc<ExtensionCodecs>,
+    ) -> Self {
         Self {
             scheduler_url,
             config,
+            codecs,
         }
     }
 }
@@ -310,11 +321,37 @@ impl QueryPlanner for BallistaQueryPlanner {
                 self.scheduler_url.clone(),
                 self.config.clone(),
                 logical_plan.clone(),
+                self.codecs.clone(),
             ))),
         }
     }
 }
 
+/// Plans the user-defined nodes of logical plans with the planners registered
+/// through [`ExtensionCodecs::register_extension_planner`]
+pub struct ExtensionQueryPlanner {
+    codecs: Arc<ExtensionCodecs>,
+}
+
+impl ExtensionQueryPlanner {
+    pub fn new(codecs: Arc<ExtensionCodecs>) -> Self {
+        Self { codecs }
+    }
+}
+
+#[async_trait]
+impl QueryPlanner for ExtensionQueryPlanner {
+    async fn create_physical_plan(
+        &self,
+        logical_plan: &LogicalPlan,
+        ctx_state: &ExecutionContextState,
+    ) -> std::result::Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        DefaultPhysicalPlanner::with_extension_planners(self.codecs.extension_planners())
+            .create_physical_plan(logical_plan, ctx_state)
+            .await
+    }
+}
+
 pub struct WrappedStream {
     stream: Pin<Box<dyn Stream<Item = ArrowResult<RecordBatch>> + Send + Sync>>,
     schema: SchemaRef,
@@ -15,7 +15,6 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use std::convert::TryInto;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, Sender, TryRecvError};
 use std::{sync::Arc, time::Duration};
@@ -33,7 +32,9 @@ use ballista_core::serde::protobuf::{
 use crate::as_task_status;
 use crate::executor::Executor;
 use ballista_core::error::BallistaError;
-use ballista_core::serde::physical_plan::from_proto::parse_protobuf_hash_partitioning;
+use ballista_core::serde::physical_plan::from_proto::{
+    parse_physical_plan, parse_protobuf_hash_partitioning,
+};
 
 pub async fn poll_loop(
     mut scheduler: SchedulerGrpcClient<Channel>,
@@ -116,9 +117,13 @@ async fn run_received_tasks(
     );
     info!("Received task {}", task_id_log);
     available_tasks_slots.fetch_sub(1, Ordering::SeqCst);
-    let plan: Arc<dyn ExecutionPlan> = (&task.plan.unwrap()).try_into().unwrap();
-    let shuffle_output_partitioning =
-        parse_protobuf_hash_partitioning(task.output_partitioning.as_ref())?;
+    let plan: Arc<dyn ExecutionPlan> =
+        parse_physical_plan(&task.plan.unwrap(), executor.functions(), executor.codecs())
+            .unwrap();
+    let shuffle_output_partitioning = parse_protobuf_hash_partitioning(
+        task.output_partitioning.as_ref(),
+        executor.functions(),
+    )?;
 
     tokio::spawn(async move {
         let execution_result = executor
@@ -23,10 +23,13 @@ use arrow::error::Result as ArrowResult;
 use ballista_core::error::BallistaError;
 use ballista_core::execution_plans::ShuffleStreamReaderExec;
 use ballista_core::execution_plans::ShuffleWriterExec;
+use ballista_core::serde::extension::ExtensionCodecs;
 use ballista_core::serde::protobuf;
 use ballista_core::serde::scheduler::ExecutorSpecification;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::error::DataFusionError;
+use datafusion::execution::context::ExecutionContext;
+use datafusion::logical_plan::registry::FunctionRegistry;
 use datafusion::physical_plan::display::DisplayableExecutionPlan;
 use datafusion::physical_plan::{ExecutionPlan, Partitioning};
 use hashbrown::HashMap;
@@ -46,6 +49,12 @@ pub struct Executor {
 
     /// Specification like total task slots
     pub specification: ExecutorSpecification,
+
+    /// Registry of the user-defined functions that the plans of the tasks can reference
+    functions: Arc<dyn FunctionRegistry + Send + Sync>,
+
+    /// Codecs of the plans of the tasks that Ballista doesn't serialize itself
+    codecs: Arc<ExtensionCodecs>,
 }
 
 impl Executor {
@@ -65,8 +74,36 @@ impl Executor {
             work_dir: work_dir.to_owned(),
             channels: RwLock::new(HashMap::new()),
             specification,
+            functions: Arc::new(ExecutionContext::new()),
+            codecs: Arc::new(ExtensionCodecs::new()),
         }
     }
+
+    /// Use the user-defined functions of `functions`, e.g. a context into which
+    /// plugins were loaded, to deserialize the plans of the tasks
+    pub fn with_function_registry(
+        mut self,
+        functions: Arc<dyn FunctionRegistry + Send + Sync>,
+    ) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// The registry of the user-defined functions of this executor
+    pub fn functions(&self) -> &(dyn FunctionRegistry + Send + Sync) {
+        self.functions.as_ref()
+    }
+
+    /// Use the codecs of `codecs` to deserialize the plans of the tasks
+    pub fn with_extension_codecs(mut self, codecs: Arc<ExtensionCodecs>) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
+    /// The codecs of the extension plans of this executor
+    pub fn codecs(&self) -> &ExtensionCodecs {
+        self.codecs.as_ref()
+    }
 }
 
 impl Executor {
@@ -15,7 +15,6 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
@@ -25,7 +24,9 @@ use tonic::transport::{Channel, Server};
 use tonic::{Request, Response, Status};
 
 use ballista_core::error::BallistaError;
-use ballista_core::serde::physical_plan::from_proto::parse_protobuf_hash_partitioning;
+use ballista_core::serde::physical_plan::from_proto::{
+    parse_physical_plan, parse_protobuf_hash_partitioning,
+};
 use ballista_core::serde::protobuf::executor_grpc_server::{
     ExecutorGrpc, ExecutorGrpcServer,
 };
@@ -179,9 +180,16 @@ impl ExecutorServer {
         );
         info!("Start to run task {}", task_id_log);
 
-        let plan: Arc<dyn ExecutionPlan> = (&task.plan.unwrap()).try_into().unwrap();
-        let shuffle_output_partitioning =
-            parse_protobuf_hash_partitioning(task.output_partitioning.as_ref())?;
+        let plan: Arc<dyn ExecutionPlan> = parse_physical_plan(
+            &task.plan.unwrap(),
+            self.executor.functions(),
+            self.executor.codecs(),
+        )
+        .unwrap();
+        let shuffle_output_partitioning = parse_protobuf_hash_partitioning(
+            task.output_partitioning.as_ref(),
+            self.executor.functions(),
+        )?;
 
         let execution_result = self
             .executor
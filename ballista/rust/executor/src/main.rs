@@ -90,6 +90,10 @@ async fn main() -> Result<()> {
     info!("Running with config:");
     info!("work_dir: {}", work_dir);
     info!("concurrent_tasks: {}", opt.concurrent_tasks);
+    info!("plugins: {}", opt.plugins);
+
+    let functions = ballista_core::serde::load_plugins(&opt.plugins)
+        .context("Could not load plugins")?;
 
     let executor_meta = ExecutorRegistration {
         id: Uuid::new_v4().to_string(), // assign this executor a unique ID
@@ -102,10 +106,10 @@ async fn main() -> Result<()> {
     let executor_specification = ExecutorSpecification {
         task_slots: opt.concurrent_tasks as u32,
     };
-    let executor = Arc::new(Executor::new_with_specification(
-        &work_dir,
-        executor_specification,
-    ));
+    let executor = Arc::new(
+        Executor::new_with_specification(&work_dir, executor_specification)
+            .with_function_registry(Arc::new(functions)),
+    );
 
     let scheduler = SchedulerGrpcClient::connect(scheduler_url)
         .await
@@ -20,10 +20,12 @@ use std::sync::Arc;
 use arrow_flight::flight_service_server::FlightServiceServer;
 use ballista_core::{
     error::Result,
+    serde::extension::ExtensionCodecs,
     serde::protobuf::executor_registration::OptionalHost,
     serde::protobuf::{scheduler_grpc_client::SchedulerGrpcClient, ExecutorRegistration},
     BALLISTA_VERSION,
 };
+use datafusion::logical_plan::registry::FunctionRegistry;
 use log::info;
 use tempfile::TempDir;
 use tokio::net::TcpListener;
@@ -35,13 +37,19 @@ use crate::{execution_loop, executor::Executor, flight_service::BallistaFlightSe
 pub async fn new_standalone_executor(
     scheduler: SchedulerGrpcClient<Channel>,
     concurrent_tasks: usize,
+    functions: Arc<dyn FunctionRegistry + Send + Sync>,
+    codecs: Arc<ExtensionCodecs>,
 ) -> Result<()> {
     let work_dir = TempDir::new()?
         .into_path()
         .into_os_string()
         .into_string()
         .unwrap();
-    let executor = Arc::new(Executor::new(&work_dir));
+    let executor = Arc::new(
+        Executor::new(&work_dir)
+            .with_function_registry(functions)
+            .with_extension_codecs(codecs),
+    );
 
     let service = BallistaFlightService::new(executor.clone());
 
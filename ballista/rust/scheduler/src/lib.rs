@@ -95,8 +95,13 @@ use anyhow::Context;
 use ballista_core::config::{BallistaConfig, TaskSchedulingPolicy};
 use ballista_core::error::BallistaError;
 use ballista_core::execution_plans::{ShuffleStreamReaderExec, ShuffleWriterExec};
+use ballista_core::serde::extension::ExtensionCodecs;
+use ballista_core::serde::logical_plan::from_proto::parse_logical_plan;
+use ballista_core::serde::physical_plan::to_proto::serialize_physical_plan;
 use ballista_core::serde::protobuf::executor_grpc_client::ExecutorGrpcClient;
 use ballista_core::serde::scheduler::to_proto::hash_partitioning_to_proto;
+use ballista_core::utils::ExtensionQueryPlanner;
+use datafusion::logical_plan::registry::FunctionRegistry;
 use datafusion::prelude::{ExecutionConfig, ExecutionContext};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::{mpsc, RwLock};
@@ -123,6 +128,8 @@ impl SchedulerServer {
             namespace,
             TaskSchedulingPolicy::PullStaged,
             None,
+            Arc::new(ExecutionContext::new()),
+            Arc::new(ExtensionCodecs::new()),
         )
     }
 
@@ -131,8 +138,14 @@ impl SchedulerServer {
         namespace: String,
         policy: TaskSchedulingPolicy,
         scheduler_env: Option<SchedulerEnv>,
+        functions: Arc<dyn FunctionRegistry + Send + Sync>,
+        codecs: Arc<ExtensionCodecs>,
     ) -> Self {
-        let state = Arc::new(SchedulerState::new(config, namespace));
+        let state = Arc::new(
+            SchedulerState::new(config, namespace)
+                .with_function_registry(functions)
+                .with_extension_codecs(codecs),
+        );
         let state_clone = state.clone();
 
         // TODO: we should elect a leader in the scheduler cluster and run this only in the leader
@@ -260,7 +273,10 @@ impl SchedulerServer {
                         };
 
                         ret[idx].push(TaskDefinition {
-                            plan: Some(plan.try_into().unwrap()),
+                            plan: Some(
+                                serialize_physical_plan(plan, self.state.codecs())
+                                    .unwrap(),
+                            ),
                             task_id: status.partition_id,
                             output_partitioning: hash_partitioning_to_proto(
                                 output_partitioning,
@@ -469,7 +485,10 @@ impl SchedulerGrpc for SchedulerServer {
                             )));
                         };
                         Ok(Some(TaskDefinition {
-                            plan: Some(plan.try_into().unwrap()),
+                            plan: Some(
+                                serialize_physical_plan(plan, self.state.codecs())
+                                    .unwrap(),
+                            ),
                             task_id: status.partition_id,
                             output_partitioning: hash_partitioning_to_proto(
                                 output_partitioning,
@@ -750,7 +769,12 @@ impl SchedulerGrpc for SchedulerServer {
             let plan = match query {
                 Query::LogicalPlan(logical_plan) => {
                     // parse protobuf
-                    (&logical_plan).try_into().map_err(|e| {
+                    parse_logical_plan(
+                        &logical_plan,
+                        self.state.functions(),
+                        self.state.codecs(),
+                    )
+                    .map_err(|e| {
                         let msg = format!("Could not parse logical plan protobuf: {}", e);
                         error!("{}", msg);
                         tonic::Status::internal(msg)
@@ -759,7 +783,8 @@ impl SchedulerGrpc for SchedulerServer {
                 Query::Sql(sql) => {
                     //TODO we can't just create a new context because we need a context that has
                     // tables registered from previous SQL statements that have been executed
-                    let mut ctx = create_datafusion_context(&config);
+                    let mut ctx =
+                        create_datafusion_context(&config, self.state.codecs().clone());
                     let df = ctx.sql(&sql).await.map_err(|e| {
                         let msg = format!("Error parsing SQL: {}", e);
                         error!("{}", msg);
@@ -803,7 +828,8 @@ impl SchedulerGrpc for SchedulerServer {
             };
             tokio::spawn(async move {
                 // create physical plan using DataFusion
-                let datafusion_ctx = create_datafusion_context(&config);
+                let datafusion_ctx =
+                    create_datafusion_context(&config, state.codecs().clone());
                 macro_rules! fail_job {
                     ($code :expr) => {{
                         match $code {
@@ -974,10 +1000,15 @@ impl SchedulerGrpc for SchedulerServer {
     }
 }
 
-/// Create a DataFusion context that is compatible with Ballista
-pub fn create_datafusion_context(config: &BallistaConfig) -> ExecutionContext {
+/// Create a DataFusion context that is compatible with Ballista, planning the
+/// user-defined nodes with the planners registered with `codecs`
+pub fn create_datafusion_context(
+    config: &BallistaConfig,
+    codecs: Arc<ExtensionCodecs>,
+) -> ExecutionContext {
     let config = ExecutionConfig::new()
-        .with_target_partitions(config.default_shuffle_partitions());
+        .with_target_partitions(config.default_shuffle_partitions())
+        .with_query_planner(Arc::new(ExtensionQueryPlanner::new(codecs)));
     ExecutionContext::with_config(config)
 }
 
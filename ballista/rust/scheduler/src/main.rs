@@ -41,6 +41,8 @@ use ballista_scheduler::{
 };
 
 use ballista_core::config::TaskSchedulingPolicy;
+use ballista_core::serde::extension::ExtensionCodecs;
+use datafusion::logical_plan::registry::FunctionRegistry;
 use log::info;
 use tokio::sync::mpsc;
 use tonic::transport::server::Connected;
@@ -64,6 +66,8 @@ async fn start_server(
     namespace: String,
     addr: SocketAddr,
     policy: TaskSchedulingPolicy,
+    functions: Arc<dyn FunctionRegistry + Send + Sync>,
+    codecs: Arc<ExtensionCodecs>,
 ) -> Result<()> {
     info!(
         "Ballista v{} Scheduler listening on {:?}",
@@ -83,6 +87,8 @@ async fn start_server(
                 namespace.clone(),
                 policy,
                 Some(SchedulerEnv { tx_job }),
+                functions,
+                codecs,
             );
             let task_scheduler = TaskScheduler::new(Arc::new(scheduler_server.clone()));
             task_scheduler.start(rx_job);
@@ -93,8 +99,17 @@ async fn start_server(
             namespace.clone(),
             policy,
             None,
+            functions,
+            codecs,
+        ),
+        _ => SchedulerServer::new_with_policy(
+            config_backend.clone(),
+            namespace.clone(),
+            policy,
+            None,
+            functions,
+            codecs,
         ),
-        _ => SchedulerServer::new(config_backend.clone(), namespace.clone()),
     };
 
     Ok(Server::bind(&addr)
@@ -152,6 +167,9 @@ async fn main() -> Result<()> {
         std::process::exit(0);
     }
 
+    let functions = ballista_core::serde::load_plugins(&opt.plugins)
+        .context("Could not load plugins")?;
+
     let namespace = opt.namespace;
     let bind_host = opt.bind_host;
     let port = opt.bind_port;
@@ -194,6 +212,14 @@ async fn main() -> Result<()> {
     };
 
     let policy: TaskSchedulingPolicy = opt.scheduler_policy;
-    start_server(client, namespace, addr, policy).await?;
+    start_server(
+        client,
+        namespace,
+        addr,
+        policy,
+        Arc::new(functions),
+        Arc::new(ExtensionCodecs::new()),
+    )
+    .await?;
     Ok(())
 }
@@ -338,7 +338,11 @@ mod test {
     use crate::test_utils::datafusion_test_context;
     use ballista_core::error::BallistaError;
     use ballista_core::execution_plans::UnresolvedShuffleExec;
+    use ballista_core::serde::extension::ExtensionCodecs;
+    use ballista_core::serde::physical_plan::from_proto::parse_physical_plan;
+    use ballista_core::serde::physical_plan::to_proto::serialize_physical_plan;
     use ballista_core::serde::protobuf;
+    use datafusion::execution::context::ExecutionContext;
     use datafusion::physical_plan::coalesce_batches::CoalesceBatchesExec;
     use datafusion::physical_plan::hash_aggregate::{AggregateMode, HashAggregateExec};
     use datafusion::physical_plan::hash_join::HashJoinExec;
@@ -347,7 +351,6 @@ mod test {
         coalesce_partitions::CoalescePartitionsExec, projection::ProjectionExec,
     };
     use datafusion::physical_plan::{displayable, ExecutionPlan};
-    use std::convert::TryInto;
     use std::sync::Arc;
     use uuid::Uuid;
 
@@ -660,8 +663,11 @@ order by
     fn roundtrip_operator(
         plan: Arc<dyn ExecutionPlan>,
     ) -> Result<Arc<dyn ExecutionPlan>, BallistaError> {
-        let proto: protobuf::PhysicalPlanNode = plan.clone().try_into()?;
-        let result_exec_plan: Arc<dyn ExecutionPlan> = (&proto).try_into()?;
+        let codecs = ExtensionCodecs::new();
+        let proto: protobuf::PhysicalPlanNode =
+            serialize_physical_plan(plan.clone(), &codecs)?;
+        let result_exec_plan: Arc<dyn ExecutionPlan> =
+            parse_physical_plan(&proto, &ExecutionContext::new(), &codecs)?;
         Ok(result_exec_plan)
     }
 }
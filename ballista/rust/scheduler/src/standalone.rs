@@ -16,9 +16,10 @@
 // under the License.
 
 use ballista_core::{
-    error::Result, serde::protobuf::scheduler_grpc_server::SchedulerGrpcServer,
-    BALLISTA_VERSION,
+    config::TaskSchedulingPolicy, error::Result, serde::extension::ExtensionCodecs,
+    serde::protobuf::scheduler_grpc_server::SchedulerGrpcServer, BALLISTA_VERSION,
 };
+use datafusion::logical_plan::registry::FunctionRegistry;
 use log::info;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::net::TcpListener;
@@ -26,12 +27,19 @@ use tonic::transport::Server;
 
 use crate::{state::StandaloneClient, SchedulerServer};
 
-pub async fn new_standalone_scheduler() -> Result<SocketAddr> {
+pub async fn new_standalone_scheduler(
+    functions: Arc<dyn FunctionRegistry + Send + Sync>,
+    codecs: Arc<ExtensionCodecs>,
+) -> Result<SocketAddr> {
     let client = StandaloneClient::try_new_temporary()?;
 
-    let server = SchedulerGrpcServer::new(SchedulerServer::new(
+    let server = SchedulerGrpcServer::new(SchedulerServer::new_with_policy(
         Arc::new(client),
         "ballista".to_string(),
+        TaskSchedulingPolicy::PullStaged,
+        None,
+        functions,
+        codecs,
     ));
     // Let the OS assign a random, free port
     let listener = TcpListener::bind("localhost:0").await?;
@@ -21,12 +21,17 @@ use std::{
     any::type_name, collections::HashMap, convert::TryInto, sync::Arc, time::Duration,
 };
 
+use datafusion::execution::context::ExecutionContext;
+use datafusion::logical_plan::registry::FunctionRegistry;
 use datafusion::physical_plan::ExecutionPlan;
 use futures::{Stream, StreamExt};
 use log::{debug, error, info};
 use prost::Message;
 use tokio::sync::OwnedMutexGuard;
 
+use ballista_core::serde::extension::ExtensionCodecs;
+use ballista_core::serde::physical_plan::from_proto::parse_physical_plan;
+use ballista_core::serde::physical_plan::to_proto::serialize_physical_plan;
 use ballista_core::serde::protobuf::{
     self, job_status, task_status, CompletedJob, CompletedTask, ExecutorHeartbeat,
     ExecutorMetadata, FailedJob, FailedTask, JobStatus, PhysicalPlanNode, RunningJob,
@@ -95,6 +100,10 @@ pub(super) struct SchedulerState {
     // TODO implement clean up logic
     #[allow(dead_code)]
     stage_lineages: Arc<RwLock<HashMap<usize, usize>>>,
+    /// Registry of the user-defined functions that the stage plans can reference
+    functions: Arc<dyn FunctionRegistry + Send + Sync>,
+    /// Codecs and planners of the plan nodes that Ballista doesn't serialize itself
+    codecs: Arc<ExtensionCodecs>,
 }
 
 impl SchedulerState {
@@ -103,9 +112,37 @@ impl SchedulerState {
             config_client,
             namespace,
             stage_lineages: Arc::new(RwLock::new(Default::default())),
+            functions: Arc::new(ExecutionContext::new()),
+            codecs: Arc::new(ExtensionCodecs::new()),
         }
     }
 
+    /// Use the user-defined functions of `functions` to deserialize the plans
+    pub fn with_function_registry(
+        mut self,
+        functions: Arc<dyn FunctionRegistry + Send + Sync>,
+    ) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// The registry of the user-defined functions of this scheduler
+    pub fn functions(&self) -> &(dyn FunctionRegistry + Send + Sync) {
+        self.functions.as_ref()
+    }
+
+    /// Use the codecs and planners of `codecs` to plan queries and to serialize
+    /// the plans
+    pub fn with_extension_codecs(mut self, codecs: Arc<ExtensionCodecs>) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
+    /// The codecs and planners of the extension nodes of this scheduler
+    pub fn codecs(&self) -> &Arc<ExtensionCodecs> {
+        &self.codecs
+    }
+
     pub async fn get_executors_metadata(&self) -> Result<Vec<(ExecutorMeta, Duration)>> {
         let mut result = vec![];
 
@@ -281,7 +318,7 @@ impl SchedulerState {
     ) -> Result<()> {
         let key = get_stage_plan_key(&self.namespace, job_id, stage_id);
         let value = {
-            let proto: PhysicalPlanNode = plan.try_into()?;
+            let proto = serialize_physical_plan(plan, &self.codecs)?;
             encode_protobuf(&proto)?
         };
         self.config_client.clone().put(key, value).await
@@ -301,7 +338,7 @@ impl SchedulerState {
             )));
         }
         let value: PhysicalPlanNode = decode_protobuf(value)?;
-        Ok((&value).try_into()?)
+        parse_physical_plan(&value, self.functions(), &self.codecs)
     }
 
     pub async fn get_all_tasks(&self) -> Result<HashMap<String, TaskStatus>> {
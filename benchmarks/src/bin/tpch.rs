@@ -670,6 +670,7 @@ fn get_table(
         target_partitions,
         collect_stat: true,
         table_partition_cols: vec![],
+        file_sort_order: None,
     };
 
     Ok(Arc::new(ListingTable::new(
@@ -1260,9 +1261,13 @@ mod tests {
 
     mod ballista_round_trip {
         use super::*;
+        use ballista_core::serde::extension::ExtensionCodecs;
+        use ballista_core::serde::logical_plan::from_proto::parse_logical_plan;
+        use ballista_core::serde::logical_plan::to_proto::serialize_logical_plan;
+        use ballista_core::serde::physical_plan::from_proto::parse_physical_plan;
+        use ballista_core::serde::physical_plan::to_proto::serialize_physical_plan;
         use ballista_core::serde::protobuf;
         use datafusion::physical_plan::ExecutionPlan;
-        use std::convert::TryInto;
 
         async fn round_trip_query(n: usize) -> Result<()> {
             let config = ExecutionConfig::new()
@@ -1293,9 +1298,12 @@ mod tests {
             }
 
             // test logical plan round trip
+            let codecs = ExtensionCodecs::new();
             let plan = create_logical_plan(&mut ctx, n)?;
-            let proto: protobuf::LogicalPlanNode = (&plan).try_into().unwrap();
-            let round_trip: LogicalPlan = (&proto).try_into().unwrap();
+            let proto: protobuf::LogicalPlanNode =
+                serialize_logical_plan(&plan, &codecs).unwrap();
+            let round_trip: LogicalPlan =
+                parse_logical_plan(&proto, &ctx, &codecs).unwrap();
             assert_eq!(
                 format!("{:?}", plan),
                 format!("{:?}", round_trip),
@@ -1304,8 +1312,10 @@ mod tests {
 
             // test optimized logical plan round trip
             let plan = ctx.optimize(&plan)?;
-            let proto: protobuf::LogicalPlanNode = (&plan).try_into().unwrap();
-            let round_trip: LogicalPlan = (&proto).try_into().unwrap();
+            let proto: protobuf::LogicalPlanNode =
+                serialize_logical_plan(&plan, &codecs).unwrap();
+            let round_trip: LogicalPlan =
+                parse_logical_plan(&proto, &ctx, &codecs).unwrap();
             assert_eq!(
                 format!("{:?}", plan),
                 format!("{:?}", round_trip),
@@ -1316,8 +1326,9 @@ mod tests {
             if env::var("TPCH_DATA").is_ok() {
                 let physical_plan = ctx.create_physical_plan(&plan).await?;
                 let proto: protobuf::PhysicalPlanNode =
-                    (physical_plan.clone()).try_into().unwrap();
-                let round_trip: Arc<dyn ExecutionPlan> = (&proto).try_into().unwrap();
+                    serialize_physical_plan(physical_plan.clone(), &codecs).unwrap();
+                let round_trip: Arc<dyn ExecutionPlan> =
+                    parse_physical_plan(&proto, &ctx, &codecs).unwrap();
                 assert_eq!(
                     format!("{:?}", physical_plan),
                     format!("{:?}", round_trip),
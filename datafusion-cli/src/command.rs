@@ -40,6 +40,7 @@ pub enum Command {
     SearchFunctions(String),
     QuietMode(Option<bool>),
     OutputFormat(Option<String>),
+    LoadPlugin(String),
 }
 
 pub enum OutputFormat {
@@ -103,6 +104,12 @@ impl Command {
             Self::OutputFormat(_) => Err(DataFusionError::Execution(
                 "Unexpected change output format, this should be handled outside".into(),
             )),
+            Self::LoadPlugin(path) => {
+                ctx.load_plugin(path)?;
+                print_options
+                    .print_batches(&[loaded_plugin_info(path)], now)
+                    .map_err(|e| DataFusionError::Execution(e.to_string()))
+            }
         }
     }
 
@@ -118,11 +125,12 @@ impl Command {
             Self::OutputFormat(_) => {
                 ("\\pset [NAME [VALUE]]", "set table output option\n(format)")
             }
+            Self::LoadPlugin(_) => ("\\load path", "load functions from a plugin"),
         }
     }
 }
 
-const ALL_COMMANDS: [Command; 8] = [
+const ALL_COMMANDS: [Command; 9] = [
     Command::ListTables,
     Command::DescribeTable(String::new()),
     Command::Quit,
@@ -131,6 +139,7 @@ const ALL_COMMANDS: [Command; 8] = [
     Command::SearchFunctions(String::new()),
     Command::QuietMode(None),
     Command::OutputFormat(None),
+    Command::LoadPlugin(String::new()),
 ];
 
 fn all_commands_info() -> RecordBatch {
@@ -152,6 +161,16 @@ fn all_commands_info() -> RecordBatch {
     .expect("This should not fail")
 }
 
+fn loaded_plugin_info(path: &str) -> RecordBatch {
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        "Loaded plugin",
+        DataType::Utf8,
+        false,
+    )]));
+    RecordBatch::try_new(schema, vec![Arc::new(StringArray::from(vec![path]))])
+        .expect("This should not fail")
+}
+
 impl FromStr for Command {
     type Err = ();
 
@@ -179,6 +198,7 @@ impl FromStr for Command {
                 Self::OutputFormat(Some(subcommand.to_string()))
             }
             ("pset", None) => Self::OutputFormat(None),
+            ("load", Some(path)) => Self::LoadPlugin(path.trim().to_string()),
             _ => return Err(()),
         })
     }
@@ -22,6 +22,7 @@ use ballista::prelude::BallistaConfig;
 use datafusion::dataframe::DataFrame;
 use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::context::{ExecutionConfig, ExecutionContext};
+use std::path::Path;
 use std::sync::Arc;
 
 /// The CLI supports using a local DataFusion context or a distributed BallistaContext
@@ -54,4 +55,12 @@ impl Context {
             Context::Remote(ballista) => ballista.sql(sql).await,
         }
     }
+
+    /// load the user-defined functions of the plugin at the given path
+    pub fn load_plugin(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        match self {
+            Context::Local(datafusion) => datafusion.load_plugin(path),
+            Context::Remote(ballista) => ballista.load_plugin(path),
+        }
+    }
 }
@@ -34,15 +34,21 @@ pub enum Function {
     CreateTableAs,
     Insert,
     DropTable,
+    Set,
+    Prepare,
+    Execute,
 }
 
-const ALL_FUNCTIONS: [Function; 7] = [
+const ALL_FUNCTIONS: [Function; 10] = [
     Function::CreateTable,
     Function::CreateTableAs,
     Function::DropTable,
+    Function::Execute,
     Function::Explain,
     Function::Insert,
+    Function::Prepare,
     Function::Select,
+    Function::Set,
     Function::Show,
 ];
 
@@ -106,6 +112,31 @@ Command:     SHOW
 Description: show the value of a run-time parameter
 Syntax:
 SHOW name
+SHOW ALL
+"#
+            }
+            Function::Set => {
+                r#"
+Command:     SET
+Description: change a run-time parameter
+Syntax:
+SET name { TO | = } value
+"#
+            }
+            Function::Prepare => {
+                r#"
+Command:     PREPARE
+Description: prepare a statement for execution
+Syntax:
+PREPARE name [ ( data_type [, ...] ) ] AS statement
+"#
+            }
+            Function::Execute => {
+                r#"
+Command:     EXECUTE
+Description: execute a prepared statement
+Syntax:
+EXECUTE name [ ( parameter [, ...] ) ]
 "#
             }
             Function::CreateTable => {
@@ -164,6 +195,9 @@ impl FromStr for Function {
             "CREATE TABLE AS" => Self::CreateTableAs,
             "INSERT" => Self::Insert,
             "DROP TABLE" => Self::DropTable,
+            "SET" => Self::Set,
+            "PREPARE" => Self::Prepare,
+            "EXECUTE" => Self::Execute,
             _ => return Err(()),
         })
     }
@@ -179,6 +213,9 @@ impl fmt::Display for Function {
             Function::CreateTableAs => write!(f, "CREATE TABLE AS"),
             Function::Insert => write!(f, "INSERT"),
             Function::DropTable => write!(f, "DROP TABLE"),
+            Function::Set => write!(f, "SET"),
+            Function::Prepare => write!(f, "PREPARE"),
+            Function::Execute => write!(f, "EXECUTE"),
         }
     }
 }
@@ -92,6 +92,15 @@ pub async fn main() -> Result<()> {
                 .long("port")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("plugin")
+                .help("Load user-defined functions from plugin(s)")
+                .long("plugin")
+                .multiple(true)
+                .number_of_values(1)
+                .validator(is_valid_file)
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("quiet")
                 .help("Reduce printing other than the results and work quietly")
@@ -131,6 +140,12 @@ pub async fn main() -> Result<()> {
         _ => Context::new_local(&execution_config),
     };
 
+    if let Some(plugin_paths) = matches.values_of("plugin") {
+        for plugin_path in plugin_paths {
+            ctx.load_plugin(plugin_path)?;
+        }
+    }
+
     let format = matches
         .value_of("format")
         .expect("No format is specified")
@@ -0,0 +1,34 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::process::Command;
+
+fn main() -> Result<(), String> {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // plugins must be built with the same compiler as the process loading them,
+    // so the compiler version is embedded into their declaration
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let output = Command::new(&rustc)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("could not run {}: {}", rustc, e))?;
+    let version = String::from_utf8(output.stdout)
+        .map_err(|e| format!("invalid {} version: {}", rustc, e))?;
+    println!("cargo:rustc-env=DATAFUSION_RUSTC_VERSION={}", version.trim());
+    Ok(())
+}
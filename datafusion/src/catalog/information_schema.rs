@@ -21,7 +21,7 @@
 
 use std::{
     any,
-    sync::{Arc, Weak},
+    sync::{Arc, Mutex, Weak},
 };
 
 use arrow::{
@@ -31,6 +31,8 @@ use arrow::{
 };
 
 use crate::datasource::{MemTable, TableProvider, TableType};
+use crate::execution::context::ExecutionContextState;
+use crate::physical_plan::functions::{Signature, Volatility};
 
 use super::{
     catalog::{CatalogList, CatalogProvider},
@@ -40,11 +42,16 @@ use super::{
 const INFORMATION_SCHEMA: &str = "information_schema";
 const TABLES: &str = "tables";
 const COLUMNS: &str = "columns";
+const ROUTINES: &str = "routines";
+const PARAMETERS: &str = "parameters";
 
 /// Wraps another [`CatalogProvider`] and adds a "information_schema"
-/// schema that can introspect on tables in the catalog_list
+/// schema that can introspect on tables in the catalog_list and on the
+/// functions registered in the context
 pub(crate) struct CatalogWithInformationSchema {
     catalog_list: Weak<dyn CatalogList>,
+    /// state of the context, with its registered functions
+    context_state: Weak<Mutex<ExecutionContextState>>,
     /// wrapped provider
     inner: Arc<dyn CatalogProvider>,
 }
@@ -52,10 +59,12 @@ pub(crate) struct CatalogWithInformationSchema {
 impl CatalogWithInformationSchema {
     pub(crate) fn new(
         catalog_list: Weak<dyn CatalogList>,
+        context_state: Weak<Mutex<ExecutionContextState>>,
         inner: Arc<dyn CatalogProvider>,
     ) -> Self {
         Self {
             catalog_list,
+            context_state,
             inner,
         }
     }
@@ -77,8 +86,10 @@ impl CatalogProvider for CatalogWithInformationSchema {
     fn schema(&self, name: &str) -> Option<Arc<dyn SchemaProvider>> {
         if name.eq_ignore_ascii_case(INFORMATION_SCHEMA) {
             Weak::upgrade(&self.catalog_list).map(|catalog_list| {
-                Arc::new(InformationSchemaProvider { catalog_list })
-                    as Arc<dyn SchemaProvider>
+                Arc::new(InformationSchemaProvider {
+                    catalog_list,
+                    context_state: self.context_state.clone(),
+                }) as Arc<dyn SchemaProvider>
             })
         } else {
             self.inner.schema(name)
@@ -94,6 +105,7 @@ impl CatalogProvider for CatalogWithInformationSchema {
 /// table is queried.
 struct InformationSchemaProvider {
     catalog_list: Arc<dyn CatalogList>,
+    context_state: Weak<Mutex<ExecutionContextState>>,
 }
 
 impl InformationSchemaProvider {
@@ -128,6 +140,18 @@ impl InformationSchemaProvider {
                 COLUMNS,
                 TableType::View,
             );
+            builder.add_table(
+                &catalog_name,
+                INFORMATION_SCHEMA,
+                ROUTINES,
+                TableType::View,
+            );
+            builder.add_table(
+                &catalog_name,
+                INFORMATION_SCHEMA,
+                PARAMETERS,
+                TableType::View,
+            );
         }
 
         let mem_table: MemTable = builder.into();
@@ -167,6 +191,67 @@ impl InformationSchemaProvider {
 
         Arc::new(mem_table)
     }
+
+    /// The user-defined functions registered in the context, sorted by name, with
+    /// their routine types
+    fn routines(&self) -> Vec<(String, &'static str, Signature)> {
+        let context_state = match Weak::upgrade(&self.context_state) {
+            Some(context_state) => context_state,
+            None => return vec![],
+        };
+        let state = context_state.lock().unwrap();
+        let scalar_functions = state
+            .scalar_functions
+            .values()
+            .map(|f| (f.name.clone(), "FUNCTION", f.signature.clone()));
+        let aggregate_functions = state
+            .aggregate_functions
+            .values()
+            .map(|f| (f.name.clone(), "AGGREGATE FUNCTION", f.signature.clone()));
+        let window_functions = state
+            .window_functions
+            .values()
+            .map(|f| (f.name.clone(), "WINDOW FUNCTION", f.signature.clone()));
+        let mut routines = scalar_functions
+            .chain(aggregate_functions)
+            .chain(window_functions)
+            .collect::<Vec<_>>();
+        routines.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(b.1)));
+        routines
+    }
+
+    /// Construct the `information_schema.routines` virtual table
+    fn make_routines(&self) -> Arc<dyn TableProvider> {
+        let mut builder = InformationSchemaRoutinesBuilder::new();
+
+        for (name, routine_type, signature) in self.routines() {
+            builder.add_routine(name, routine_type, &signature);
+        }
+
+        let mem_table: MemTable = builder.into();
+
+        Arc::new(mem_table)
+    }
+
+    /// Construct the `information_schema.parameters` virtual table
+    fn make_parameters(&self) -> Arc<dyn TableProvider> {
+        let mut builder = InformationSchemaParametersBuilder::new();
+
+        for (name, _, signature) in self.routines() {
+            for (i, parameter) in signature.parameters.iter().enumerate() {
+                builder.add_parameter(
+                    &name,
+                    i + 1,
+                    &parameter.name,
+                    parameter.default.as_ref().map(|value| value.to_string()),
+                );
+            }
+        }
+
+        let mem_table: MemTable = builder.into();
+
+        Arc::new(mem_table)
+    }
 }
 
 impl SchemaProvider for InformationSchemaProvider {
@@ -175,7 +260,12 @@ impl SchemaProvider for InformationSchemaProvider {
     }
 
     fn table_names(&self) -> Vec<String> {
-        vec![TABLES.to_string(), COLUMNS.to_string()]
+        vec![
+            TABLES.to_string(),
+            COLUMNS.to_string(),
+            ROUTINES.to_string(),
+            PARAMETERS.to_string(),
+        ]
     }
 
     fn table(&self, name: &str) -> Option<Arc<dyn TableProvider>> {
@@ -183,13 +273,20 @@ impl SchemaProvider for InformationSchemaProvider {
             Some(self.make_tables())
         } else if name.eq_ignore_ascii_case("columns") {
             Some(self.make_columns())
+        } else if name.eq_ignore_ascii_case("routines") {
+            Some(self.make_routines())
+        } else if name.eq_ignore_ascii_case("parameters") {
+            Some(self.make_parameters())
         } else {
             None
         }
     }
 
     fn table_exist(&self, name: &str) -> bool {
-        return matches!(name.to_ascii_lowercase().as_str(), TABLES | COLUMNS);
+        return matches!(
+            name.to_ascii_lowercase().as_str(),
+            TABLES | COLUMNS | ROUTINES | PARAMETERS
+        );
     }
 }
 
@@ -499,3 +596,172 @@ impl From<InformationSchemaColumnsBuilder> for MemTable {
         MemTable::try_new(schema, vec![vec![batch]]).unwrap()
     }
 }
+
+/// Builds the `information_schema.ROUTINES` table row by row
+///
+/// Columns are based on https://www.postgresql.org/docs/current/infoschema-routines.html
+struct InformationSchemaRoutinesBuilder {
+    routine_names: StringBuilder,
+    routine_types: StringBuilder,
+    is_deterministics: StringBuilder,
+    signatures: StringBuilder,
+}
+
+impl InformationSchemaRoutinesBuilder {
+    fn new() -> Self {
+        // StringBuilder requires providing an initial capacity, so
+        // pick 10 here arbitrarily as this is not performance
+        // critical code and the number of functions is unavailable here.
+        let default_capacity = 10;
+        Self {
+            routine_names: StringBuilder::new(default_capacity),
+            routine_types: StringBuilder::new(default_capacity),
+            is_deterministics: StringBuilder::new(default_capacity),
+            signatures: StringBuilder::new(default_capacity),
+        }
+    }
+
+    fn add_routine(
+        &mut self,
+        routine_name: impl AsRef<str>,
+        routine_type: impl AsRef<str>,
+        signature: &Signature,
+    ) {
+        // Note: append_value is actually infallable.
+        self.routine_names
+            .append_value(routine_name.as_ref())
+            .unwrap();
+        self.routine_types
+            .append_value(routine_type.as_ref())
+            .unwrap();
+        // "If the function is declared immutable (called deterministic in
+        // the SQL standard), then YES, else NO."
+        let is_deterministic = if signature.volatility == Volatility::Immutable {
+            "YES"
+        } else {
+            "NO"
+        };
+        self.is_deterministics
+            .append_value(is_deterministic)
+            .unwrap();
+        self.signatures
+            .append_value(signature.accepted_signatures().join(", "))
+            .unwrap();
+    }
+}
+
+impl From<InformationSchemaRoutinesBuilder> for MemTable {
+    fn from(value: InformationSchemaRoutinesBuilder) -> MemTable {
+        let schema = Schema::new(vec![
+            Field::new("routine_name", DataType::Utf8, false),
+            Field::new("routine_type", DataType::Utf8, false),
+            Field::new("is_deterministic", DataType::Utf8, false),
+            Field::new("signatures", DataType::Utf8, false),
+        ]);
+
+        let InformationSchemaRoutinesBuilder {
+            mut routine_names,
+            mut routine_types,
+            mut is_deterministics,
+            mut signatures,
+        } = value;
+
+        let schema = Arc::new(schema);
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(routine_names.finish()),
+                Arc::new(routine_types.finish()),
+                Arc::new(is_deterministics.finish()),
+                Arc::new(signatures.finish()),
+            ],
+        )
+        .unwrap();
+
+        MemTable::try_new(schema, vec![vec![batch]]).unwrap()
+    }
+}
+
+/// Builds the `information_schema.PARAMETERS` table row by row
+///
+/// Columns are based on https://www.postgresql.org/docs/current/infoschema-parameters.html
+struct InformationSchemaParametersBuilder {
+    specific_names: StringBuilder,
+    ordinal_positions: UInt64Builder,
+    parameter_names: StringBuilder,
+    parameter_defaults: StringBuilder,
+}
+
+impl InformationSchemaParametersBuilder {
+    fn new() -> Self {
+        // StringBuilder requires providing an initial capacity, so
+        // pick 10 here arbitrarily as this is not performance
+        // critical code and the number of parameters is unavailable here.
+        let default_capacity = 10;
+        Self {
+            specific_names: StringBuilder::new(default_capacity),
+            ordinal_positions: UInt64Builder::new(default_capacity),
+            parameter_names: StringBuilder::new(default_capacity),
+            parameter_defaults: StringBuilder::new(default_capacity),
+        }
+    }
+
+    fn add_parameter(
+        &mut self,
+        specific_name: impl AsRef<str>,
+        parameter_position: usize,
+        parameter_name: impl AsRef<str>,
+        parameter_default: Option<String>,
+    ) {
+        // Note: append_value is actually infallable.
+        self.specific_names
+            .append_value(specific_name.as_ref())
+            .unwrap();
+        self.ordinal_positions
+            .append_value(parameter_position as u64)
+            .unwrap();
+        self.parameter_names
+            .append_value(parameter_name.as_ref())
+            .unwrap();
+        // null for the parameters that have no default value
+        match parameter_default {
+            Some(parameter_default) => {
+                self.parameter_defaults.append_value(parameter_default)
+            }
+            None => self.parameter_defaults.append_null(),
+        }
+        .unwrap();
+    }
+}
+
+impl From<InformationSchemaParametersBuilder> for MemTable {
+    fn from(value: InformationSchemaParametersBuilder) -> MemTable {
+        let schema = Schema::new(vec![
+            Field::new("specific_name", DataType::Utf8, false),
+            Field::new("ordinal_position", DataType::UInt64, false),
+            Field::new("parameter_name", DataType::Utf8, false),
+            Field::new("parameter_default", DataType::Utf8, true),
+        ]);
+
+        let InformationSchemaParametersBuilder {
+            mut specific_names,
+            mut ordinal_positions,
+            mut parameter_names,
+            mut parameter_defaults,
+        } = value;
+
+        let schema = Arc::new(schema);
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(specific_names.finish()),
+                Arc::new(ordinal_positions.finish()),
+                Arc::new(parameter_names.finish()),
+                Arc::new(parameter_defaults.finish()),
+            ],
+        )
+        .unwrap();
+
+        MemTable::try_new(schema, vec![vec![batch]]).unwrap()
+    }
+}
@@ -22,6 +22,7 @@ use crate::error::Result;
 use crate::logical_plan::{
     DFSchema, Expr, FunctionRegistry, JoinType, LogicalPlan, Partitioning,
 };
+use crate::scalar::ScalarValue;
 use std::sync::Arc;
 
 use crate::physical_plan::SendableRecordBatchStream;
@@ -405,4 +406,28 @@ pub trait DataFrame: Send + Sync {
     /// # }
     /// ```
     fn except(&self, dataframe: Arc<dyn DataFrame>) -> Result<Arc<dyn DataFrame>>;
+
+    /// Bind the placeholders `$1`, `$2`, ... of the plan to `param_values`, so that
+    /// a query can be planned once and executed with different parameters
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # use datafusion::scalar::ScalarValue;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// ctx.register_csv("example", "tests/example.csv", CsvReadOptions::new()).await?;
+    /// let df = ctx.sql("SELECT a, b FROM example WHERE a = $1").await?;
+    /// let batches = df
+    ///     .with_param_values(vec![ScalarValue::Int64(Some(1))])?
+    ///     .collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn with_param_values(
+        &self,
+        param_values: Vec<ScalarValue>,
+    ) -> Result<Arc<dyn DataFrame>>;
 }
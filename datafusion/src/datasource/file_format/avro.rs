@@ -360,6 +360,7 @@ mod tests {
                     batch_size,
                     limit,
                     table_partition_cols: vec![],
+                    output_ordering: None,
                 },
                 &[],
             )
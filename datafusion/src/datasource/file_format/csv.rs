@@ -272,6 +272,7 @@ mod tests {
                     batch_size,
                     limit,
                     table_partition_cols: vec![],
+                    output_ordering: None,
                 },
                 &[],
             )
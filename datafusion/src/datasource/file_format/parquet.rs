@@ -617,6 +617,7 @@ mod tests {
                     batch_size,
                     limit,
                     table_partition_cols: vec![],
+                    output_ordering: None,
                 },
                 &[],
             )
@@ -705,6 +706,7 @@ mod tests {
                         batch_size,
                         limit,
                         table_partition_cols: vec![],
+                        output_ordering: None,
                     },
                     &[],
                 )
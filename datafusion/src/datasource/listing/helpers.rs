@@ -95,6 +95,11 @@ impl ExpressionVisitor for ApplicabilityVisitor<'_> {
             | Expr::Case { .. } => Recursion::Continue(self),
 
             Expr::ScalarFunction { fun, .. } => self.visit_volatility(fun.volatility()),
+            // async UDFs can only be evaluated in projections and filters
+            Expr::ScalarUDF { fun, .. } if fun.async_fun.is_some() => {
+                *self.is_applicable = false;
+                Recursion::Stop(self)
+            }
             Expr::ScalarUDF { fun, .. } => {
                 self.visit_volatility(fun.signature.volatility)
             }
@@ -103,7 +108,9 @@ impl ExpressionVisitor for ApplicabilityVisitor<'_> {
             // - AGGREGATE, WINDOW and SORT should not end up in filter conditions, except maybe in some edge cases
             // - Can `Wildcard` be considered as a `Literal`?
             // - ScalarVariable could be `applicable`, but that would require access to the context
+            // - Placeholder has no value until it is bound
             Expr::AggregateUDF { .. }
+            | Expr::Placeholder { .. }
             | Expr::AggregateFunction { .. }
             | Expr::Sort { .. }
             | Expr::WindowFunction { .. }
@@ -19,15 +19,17 @@
 
 use std::{any::Any, sync::Arc};
 
+use arrow::compute::SortOptions;
 use arrow::datatypes::{Field, Schema, SchemaRef};
 use async_trait::async_trait;
 use futures::StreamExt;
 
 use crate::{
-    error::Result,
+    error::{DataFusionError, Result},
     logical_plan::Expr,
     physical_plan::{
         empty::EmptyExec,
+        expressions::{Column, PhysicalSortExpr},
         file_format::{PhysicalPlanConfig, DEFAULT_PARTITION_COLUMN_DATATYPE},
         ExecutionPlan, Statistics,
     },
@@ -62,6 +64,11 @@ pub struct ListingOptions {
     /// Group files to avoid that the number of partitions exceeds
     /// this limit
     pub target_partitions: usize,
+    /// The order in which the rows of every file are known to be sorted, as
+    /// sort expressions on the columns of the table, e.g.
+    /// `vec![col("a").sort(true, false)]`. Scans that read a single file per
+    /// partition report this order, so that sorts on it can be skipped.
+    pub file_sort_order: Option<Vec<Expr>>,
 }
 
 impl ListingOptions {
@@ -71,6 +78,7 @@ impl ListingOptions {
     /// - no input partition to discover
     /// - one target partition
     /// - no stat collection
+    /// - no known file sort order
     pub fn new(format: Arc<dyn FileFormat>) -> Self {
         Self {
             file_extension: String::new(),
@@ -78,6 +86,7 @@ impl ListingOptions {
             table_partition_cols: vec![],
             collect_stat: true,
             target_partitions: 1,
+            file_sort_order: None,
         }
     }
 
@@ -206,6 +215,7 @@ impl TableProvider for ListingTable {
                     batch_size,
                     limit,
                     table_partition_cols: self.options.table_partition_cols.clone(),
+                    output_ordering: self.output_ordering()?,
                 },
                 filters,
             )
@@ -228,6 +238,44 @@ impl TableProvider for ListingTable {
 }
 
 impl ListingTable {
+    /// The `file_sort_order` of the options, on the columns of the table
+    fn output_ordering(&self) -> Result<Option<Vec<PhysicalSortExpr>>> {
+        let file_sort_order = match &self.options.file_sort_order {
+            Some(file_sort_order) => file_sort_order,
+            None => return Ok(None),
+        };
+        file_sort_order
+            .iter()
+            .map(|expr| match expr {
+                Expr::Sort {
+                    expr,
+                    asc,
+                    nulls_first,
+                } => match expr.as_ref() {
+                    Expr::Column(column) => Ok(PhysicalSortExpr {
+                        expr: Arc::new(Column::new(
+                            &column.name,
+                            self.table_schema.index_of(&column.name)?,
+                        )),
+                        options: SortOptions {
+                            descending: !asc,
+                            nulls_first: *nulls_first,
+                        },
+                    }),
+                    other => Err(DataFusionError::Plan(format!(
+                        "The file sort order can only contain columns, got {:?}",
+                        other
+                    ))),
+                },
+                other => Err(DataFusionError::Plan(format!(
+                    "The file sort order must contain sort expressions, got {:?}",
+                    other
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Some)
+    }
+
     /// Get the list of files for a scan as well as the file level statistics.
     /// The list is grouped to let the execution plan know how the files should
     /// be distributed to different threads / executors.
@@ -324,6 +372,46 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn load_table_with_file_sort_order() -> Result<()> {
+        let testdata = crate::test_util::parquet_test_data();
+        let filename = format!("{}/{}", testdata, "alltypes_plain.parquet");
+        let mut opt = ListingOptions::new(Arc::new(ParquetFormat::default()));
+        let schema = opt
+            .infer_schema(Arc::new(LocalFileSystem {}), &filename)
+            .await?;
+        opt.file_sort_order = Some(vec![col("id").sort(true, false)]);
+        let table = ListingTable::new(
+            Arc::new(LocalFileSystem {}),
+            filename.clone(),
+            schema.clone(),
+            opt,
+        );
+        let exec = table.scan(&Some(vec![1, 0]), 1024, &[], None).await?;
+        let ordering: Vec<String> = exec
+            .output_ordering()
+            .expect("sorted scan")
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(ordering, vec!["id@1 ASC NULLS LAST"]);
+
+        // the order of a column that is projected out is lost
+        let exec = table.scan(&Some(vec![1]), 1024, &[], None).await?;
+        assert!(exec.output_ordering().is_none());
+
+        let mut opt = ListingOptions::new(Arc::new(ParquetFormat::default()));
+        opt.file_sort_order = Some(vec![(col("id") + lit(1)).sort(true, false)]);
+        let table =
+            ListingTable::new(Arc::new(LocalFileSystem {}), filename, schema, opt);
+        let err = table.scan(&None, 1024, &[], None).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("The file sort order can only contain columns"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn read_empty_table() -> Result<()> {
         let store = TestObjectStore::new_arc(&[("table/p1=v1/file.avro", 100)]);
@@ -334,6 +422,7 @@ mod tests {
             table_partition_cols: vec![String::from("p1")],
             target_partitions: 4,
             collect_stat: true,
+            file_sort_order: None,
         };
 
         let file_schema = Schema::new(vec![Field::new("a", DataType::Boolean, false)]);
@@ -435,6 +524,7 @@ mod tests {
             table_partition_cols: vec![],
             target_partitions: 2,
             collect_stat: true,
+            file_sort_order: None,
         };
         // here we resolve the schema locally
         let schema = opt
@@ -465,6 +555,7 @@ mod tests {
             table_partition_cols: vec![],
             target_partitions,
             collect_stat: true,
+            file_sort_order: None,
         };
 
         let schema = Schema::new(vec![Field::new("a", DataType::Boolean, false)]);
@@ -0,0 +1,355 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Table functions create a table from constant arguments, and can be used
+//! in the FROM clause of a query: `SELECT * FROM generate_series(1, 10)`
+
+use std::any::Any;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use futures::future::{self, BoxFuture};
+use futures::FutureExt;
+
+use crate::datasource::file_format::parquet::{ParquetFormat, DEFAULT_PARQUET_EXTENSION};
+use crate::datasource::listing::{ListingOptions, ListingTable};
+use crate::datasource::TableProvider;
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::ExecutionContextState;
+use crate::logical_plan::Expr;
+use crate::physical_plan::generate_series::GenerateSeriesExec;
+use crate::physical_plan::ExecutionPlan;
+use crate::scalar::ScalarValue;
+
+/// Implementation of a table function: creates a table from the values of
+/// the arguments of the call, using the state of the session that plans it.
+/// Creating the table may be asynchronous, e.g. to infer its schema from
+/// files, so it is done before planning the query that calls the function.
+pub type TableFunctionImplementation = Arc<
+    dyn Fn(
+            &ExecutionContextState,
+            &[ScalarValue],
+        ) -> BoxFuture<'static, Result<Arc<dyn TableProvider>>>
+        + Send
+        + Sync,
+>;
+
+/// A call of a table function in a query, with its arguments evaluated to
+/// constants
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TableFunctionCall {
+    /// name of the function
+    pub name: String,
+    /// values of the arguments
+    pub args: Vec<ScalarValue>,
+}
+
+/// Logical representation of a table function
+#[derive(Clone)]
+pub struct TableFunction {
+    /// name
+    pub name: String,
+    /// actual implementation, called with the arguments of the function
+    /// once they have been evaluated to constants
+    pub fun: TableFunctionImplementation,
+}
+
+impl Debug for TableFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TableFunction")
+            .field("name", &self.name)
+            .field("fun", &"<FUNC>")
+            .finish()
+    }
+}
+
+impl TableFunction {
+    /// Create a new TableFunction
+    pub fn new(name: impl Into<String>, fun: TableFunctionImplementation) -> Self {
+        Self {
+            name: name.into(),
+            fun,
+        }
+    }
+
+    /// Creates the table for the given arguments
+    pub fn create_table_provider(
+        &self,
+        state: &ExecutionContextState,
+        args: &[ScalarValue],
+    ) -> BoxFuture<'static, Result<Arc<dyn TableProvider>>> {
+        (self.fun)(state, args)
+    }
+}
+
+fn int64_arg(function: &str, args: &[ScalarValue], index: usize) -> Result<i64> {
+    let value = match args.get(index) {
+        Some(ScalarValue::Int8(Some(v))) => Some(*v as i64),
+        Some(ScalarValue::Int16(Some(v))) => Some(*v as i64),
+        Some(ScalarValue::Int32(Some(v))) => Some(*v as i64),
+        Some(ScalarValue::Int64(Some(v))) => Some(*v),
+        Some(ScalarValue::UInt8(Some(v))) => Some(*v as i64),
+        Some(ScalarValue::UInt16(Some(v))) => Some(*v as i64),
+        Some(ScalarValue::UInt32(Some(v))) => Some(*v as i64),
+        Some(ScalarValue::UInt64(Some(v))) => i64::try_from(*v).ok(),
+        _ => None,
+    };
+    value.ok_or_else(|| {
+        DataFusionError::Plan(format!(
+            "Argument {} of {} must be an integer, got {:?}",
+            index + 1,
+            function,
+            args.get(index)
+        ))
+    })
+}
+
+fn string_arg<'a>(
+    function: &str,
+    args: &'a [ScalarValue],
+    index: usize,
+) -> Result<&'a str> {
+    match args.get(index) {
+        Some(ScalarValue::Utf8(Some(v))) | Some(ScalarValue::LargeUtf8(Some(v))) => {
+            Ok(v.as_str())
+        }
+        other => Err(DataFusionError::Plan(format!(
+            "Argument {} of {} must be a string, got {:?}",
+            index + 1,
+            function,
+            other
+        ))),
+    }
+}
+
+fn check_arg_count(
+    function: &str,
+    args: &[ScalarValue],
+    min: usize,
+    max: usize,
+) -> Result<()> {
+    if args.len() < min || args.len() > max {
+        return Err(DataFusionError::Plan(format!(
+            "{} expects between {} and {} arguments, got {}",
+            function,
+            min,
+            max,
+            args.len()
+        )));
+    }
+    Ok(())
+}
+
+/// The table returned by `generate_series`: a single `Int64` column named
+/// `generate_series`, holding the values from `start` to `stop` (inclusive)
+pub struct GenerateSeriesTable {
+    schema: SchemaRef,
+    start: i64,
+    stop: i64,
+    step: i64,
+}
+
+impl GenerateSeriesTable {
+    /// Create a new GenerateSeriesTable, `step` must not be zero
+    pub fn try_new(start: i64, stop: i64, step: i64) -> Result<Self> {
+        if step == 0 {
+            return Err(DataFusionError::Plan(
+                "generate_series step must not be zero".to_string(),
+            ));
+        }
+        Ok(Self {
+            schema: Arc::new(Schema::new(vec![Field::new(
+                "generate_series",
+                DataType::Int64,
+                false,
+            )])),
+            start,
+            stop,
+            step,
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for GenerateSeriesTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    async fn scan(
+        &self,
+        _projection: &Option<Vec<usize>>,
+        batch_size: usize,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        // the table has a single column, so the projection is always [0]
+        Ok(Arc::new(GenerateSeriesExec::try_new(
+            self.schema.clone(),
+            self.start,
+            self.stop,
+            self.step,
+            batch_size,
+            limit,
+        )?))
+    }
+}
+
+/// `generate_series(start, stop[, step])` returns the integers from `start`
+/// to `stop` (inclusive), incremented by `step` (1 by default)
+pub fn generate_series() -> TableFunction {
+    TableFunction::new(
+        "generate_series",
+        Arc::new(|_, args| {
+            let table = || -> Result<Arc<dyn TableProvider>> {
+                check_arg_count("generate_series", args, 2, 3)?;
+                let start = int64_arg("generate_series", args, 0)?;
+                let stop = int64_arg("generate_series", args, 1)?;
+                let step = if args.len() == 3 {
+                    int64_arg("generate_series", args, 2)?
+                } else {
+                    1
+                };
+                Ok(Arc::new(GenerateSeriesTable::try_new(start, stop, step)?))
+            };
+            future::ready(table()).boxed()
+        }),
+    )
+}
+
+/// Splits the path given to `read_parquet` into the directory to list and the
+/// suffix of the files to read in it. Besides plain paths, patterns of the
+/// form `dir/*suffix` (such as `dir/*.parquet`) are supported.
+fn split_file_pattern(path: &str) -> Result<(&str, &str)> {
+    if !path.contains('*') {
+        return Ok((path, DEFAULT_PARQUET_EXTENSION));
+    }
+    match path.rsplit_once('/') {
+        Some((dir, file))
+            if !dir.contains('*')
+                && file.starts_with('*')
+                && !file[1..].contains('*') =>
+        {
+            Ok((dir, &file[1..]))
+        }
+        _ => Err(DataFusionError::NotImplemented(format!(
+            "Unsupported file pattern '{}', only patterns of the form 'dir/*suffix' are supported",
+            path
+        ))),
+    }
+}
+
+/// `read_parquet(path)` reads the parquet files at `path`, which is either a
+/// file, a directory or a pattern such as `dir/*.parquet`. The files are
+/// read with the `target_partitions` and `parquet_pruning` settings of the
+/// session at the time of the call.
+pub fn read_parquet() -> TableFunction {
+    TableFunction::new(
+        "read_parquet",
+        Arc::new(|state, args| {
+            let table = || -> Result<_> {
+                check_arg_count("read_parquet", args, 1, 1)?;
+                let uri = string_arg("read_parquet", args, 0)?;
+                let (object_store, path) = state.object_store_registry.get_by_uri(uri)?;
+                let (dir, suffix) = split_file_pattern(path)?;
+
+                let options = ListingOptions {
+                    format: Arc::new(
+                        ParquetFormat::default()
+                            .with_enable_pruning(state.config.parquet_pruning),
+                    ),
+                    collect_stat: true,
+                    file_extension: suffix.to_owned(),
+                    target_partitions: state.config.target_partitions,
+                    table_partition_cols: vec![],
+                    file_sort_order: None,
+                };
+                Ok((object_store, dir.to_owned(), options))
+            };
+            match table() {
+                Ok((object_store, dir, options)) => async move {
+                    // list the files and read the schema of the first one
+                    let schema = options
+                        .infer_schema(Arc::clone(&object_store), &dir)
+                        .await?;
+                    Ok(
+                        Arc::new(ListingTable::new(object_store, dir, schema, options))
+                            as Arc<dyn TableProvider>,
+                    )
+                }
+                .boxed(),
+                Err(e) => future::err(e).boxed(),
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::context::ExecutionContext;
+
+    #[test]
+    fn file_patterns() -> Result<()> {
+        assert_eq!(
+            split_file_pattern("data/t.parquet")?,
+            ("data/t.parquet", DEFAULT_PARQUET_EXTENSION)
+        );
+        assert_eq!(split_file_pattern("data/*.parquet")?, ("data", ".parquet"));
+        assert_eq!(split_file_pattern("/data/*")?, ("/data", ""));
+        assert!(split_file_pattern("data/*/t.parquet").is_err());
+        assert!(split_file_pattern("data/part-*.parquet").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn generate_series_args() {
+        let state = ExecutionContext::new().state.lock().unwrap().clone();
+        let f = generate_series();
+        assert!(f
+            .create_table_provider(&state, &[ScalarValue::Int64(Some(1))])
+            .await
+            .is_err());
+        assert!(f
+            .create_table_provider(
+                &state,
+                &[
+                    ScalarValue::Int64(Some(1)),
+                    ScalarValue::Utf8(Some("a".to_string()))
+                ]
+            )
+            .await
+            .is_err());
+        assert!(f
+            .create_table_provider(
+                &state,
+                &[
+                    ScalarValue::Int64(Some(1)),
+                    ScalarValue::Int64(Some(3)),
+                    ScalarValue::Int64(Some(0))
+                ]
+            )
+            .await
+            .is_err());
+    }
+}
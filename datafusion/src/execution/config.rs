@@ -0,0 +1,423 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Named configuration settings of an [`ExecutionConfig`], which can be read
+//! and changed with `SHOW <name>` and `SET <name> = <value>`
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use arrow::datatypes::DataType;
+
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::ExecutionConfig;
+use crate::optimizer::aggregate_push_down::AggregatePushDown;
+use crate::optimizer::optimizer::OptimizerRule;
+use crate::physical_plan::datetime_expressions::parse_time_zone;
+
+/// Setting of [`ExecutionConfig::target_partitions`]
+pub const TARGET_PARTITIONS: &str = "datafusion.execution.target_partitions";
+/// Setting of [`ExecutionConfig::batch_size`]
+pub const BATCH_SIZE: &str = "datafusion.execution.batch_size";
+/// Setting of [`ExecutionConfig::time_zone`]
+pub const TIME_ZONE: &str = "datafusion.execution.time_zone";
+/// Setting of [`ExecutionConfig::with_parquet_pruning`]
+pub const PARQUET_PRUNING: &str = "datafusion.execution.parquet_pruning";
+/// Setting of [`ExecutionConfig::optimizer_max_passes`]
+pub const OPTIMIZER_MAX_PASSES: &str = "datafusion.optimizer.max_passes";
+/// Setting of [`ExecutionConfig::disabled_optimizer_rules`]
+pub const DISABLED_OPTIMIZER_RULES: &str = "datafusion.optimizer.disabled_rules";
+/// Setting of [`ExecutionConfig::with_aggregate_push_down`]
+pub const AGGREGATE_PUSH_DOWN: &str = "datafusion.optimizer.aggregate_push_down";
+/// Setting of [`ExecutionConfig::repartition_joins`]
+pub const REPARTITION_JOINS: &str = "datafusion.optimizer.repartition_joins";
+/// Setting of [`ExecutionConfig::repartition_aggregations`]
+pub const REPARTITION_AGGREGATIONS: &str =
+    "datafusion.optimizer.repartition_aggregations";
+/// Setting of [`ExecutionConfig::repartition_windows`]
+pub const REPARTITION_WINDOWS: &str = "datafusion.optimizer.repartition_windows";
+/// Setting of [`ExecutionConfig::runtime_filters`]
+pub const RUNTIME_FILTERS: &str = "datafusion.optimizer.runtime_filters";
+/// Setting of the catalog part of [`ExecutionConfig::with_default_catalog_and_schema`]
+pub const DEFAULT_CATALOG: &str = "datafusion.catalog.default_catalog";
+/// Setting of the schema part of [`ExecutionConfig::with_default_catalog_and_schema`]
+pub const DEFAULT_SCHEMA: &str = "datafusion.catalog.default_schema";
+/// Setting of [`ExecutionConfig::create_default_catalog_and_schema()`]
+pub const CREATE_DEFAULT_CATALOG_AND_SCHEMA: &str =
+    "datafusion.catalog.create_default_catalog_and_schema";
+/// Setting of [`ExecutionConfig::with_information_schema`]
+pub const INFORMATION_SCHEMA: &str = "datafusion.catalog.information_schema";
+
+/// Settings that can't be changed in a running session, as they are only used
+/// when a context is created
+const READ_ONLY_SETTINGS: &[&str] = &[
+    DEFAULT_CATALOG,
+    DEFAULT_SCHEMA,
+    CREATE_DEFAULT_CATALOG_AND_SCHEMA,
+    INFORMATION_SCHEMA,
+];
+
+/// Configuration option meta-data
+#[derive(Debug, Clone)]
+pub struct ConfigEntry {
+    /// Name of the setting
+    pub name: String,
+    /// What the setting controls
+    pub description: String,
+    /// Type that the values of the setting are parsed to
+    pub data_type: DataType,
+    /// Value of the setting in the default configuration
+    pub default_value: Option<String>,
+}
+
+impl ExecutionConfig {
+    /// All available configuration settings
+    pub fn valid_entries() -> HashMap<String, ConfigEntry> {
+        let entries = vec![
+            (
+                TARGET_PARTITIONS,
+                "Number of partitions for query execution. Increasing partitions can increase concurrency",
+                DataType::UInt64,
+            ),
+            (
+                BATCH_SIZE,
+                "Default batch size when reading data sources",
+                DataType::UInt64,
+            ),
+            (
+                TIME_ZONE,
+                "Session time zone, either an IANA name such as 'Europe/Berlin' or a fixed offset such as '+02:00'",
+                DataType::Utf8,
+            ),
+            (
+                PARQUET_PRUNING,
+                "Should the parquet reader use the predicate to skip row groups",
+                DataType::Boolean,
+            ),
+            (
+                OPTIMIZER_MAX_PASSES,
+                "Maximum number of times the logical optimizer rules are all applied",
+                DataType::UInt64,
+            ),
+            (
+                DISABLED_OPTIMIZER_RULES,
+                "Comma separated names of the logical and physical optimizer rules that are skipped",
+                DataType::Utf8,
+            ),
+            (
+                AGGREGATE_PUSH_DOWN,
+                "Should the inputs of joins be pre-aggregated by their join keys",
+                DataType::Boolean,
+            ),
+            (
+                REPARTITION_JOINS,
+                "Should joins be executed in parallel by repartitioning their inputs by the join keys",
+                DataType::Boolean,
+            ),
+            (
+                REPARTITION_AGGREGATIONS,
+                "Should aggregations be executed in parallel by repartitioning their input by the grouping keys",
+                DataType::Boolean,
+            ),
+            (
+                REPARTITION_WINDOWS,
+                "Should window functions be executed in parallel by repartitioning their input by the partition keys",
+                DataType::Boolean,
+            ),
+            (
+                RUNTIME_FILTERS,
+                "Should hash joins filter the rows of their probe side with the keys of their build side",
+                DataType::Boolean,
+            ),
+            (
+                DEFAULT_CATALOG,
+                "Catalog of the tables whose names are not qualified by one",
+                DataType::Utf8,
+            ),
+            (
+                DEFAULT_SCHEMA,
+                "Schema of the tables whose names are not qualified by one",
+                DataType::Utf8,
+            ),
+            (
+                CREATE_DEFAULT_CATALOG_AND_SCHEMA,
+                "Should the default catalog and schema be created with the context",
+                DataType::Boolean,
+            ),
+            (
+                INFORMATION_SCHEMA,
+                "Should the catalogs registered afterwards provide information_schema virtual tables",
+                DataType::Boolean,
+            ),
+        ];
+        let defaults = ExecutionConfig::default();
+        entries
+            .into_iter()
+            .map(|(name, description, data_type)| {
+                let entry = ConfigEntry {
+                    name: name.to_owned(),
+                    description: description.to_owned(),
+                    data_type,
+                    default_value: defaults.get_setting(name).ok(),
+                };
+                (entry.name.clone(), entry)
+            })
+            .collect::<HashMap<_, _>>()
+    }
+
+    /// Returns the current value of the setting `name`
+    pub fn get_setting(&self, name: &str) -> Result<String> {
+        let value = match name {
+            TARGET_PARTITIONS => self.target_partitions.to_string(),
+            BATCH_SIZE => self.batch_size.to_string(),
+            TIME_ZONE => self.time_zone.clone(),
+            PARQUET_PRUNING => self.parquet_pruning.to_string(),
+            OPTIMIZER_MAX_PASSES => self.optimizer_max_passes.to_string(),
+            DISABLED_OPTIMIZER_RULES => {
+                let mut rules = self
+                    .disabled_optimizer_rules
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                rules.sort();
+                rules.join(",")
+            }
+            AGGREGATE_PUSH_DOWN => {
+                let name = AggregatePushDown::new().name().to_owned();
+                self.optimizers
+                    .iter()
+                    .any(|rule| rule.name() == name)
+                    .to_string()
+            }
+            REPARTITION_JOINS => self.repartition_joins.to_string(),
+            REPARTITION_AGGREGATIONS => self.repartition_aggregations.to_string(),
+            REPARTITION_WINDOWS => self.repartition_windows.to_string(),
+            RUNTIME_FILTERS => self.runtime_filters.to_string(),
+            DEFAULT_CATALOG => self.default_catalog.clone(),
+            DEFAULT_SCHEMA => self.default_schema.clone(),
+            CREATE_DEFAULT_CATALOG_AND_SCHEMA => {
+                self.create_default_catalog_and_schema.to_string()
+            }
+            INFORMATION_SCHEMA => self.information_schema.to_string(),
+            _ => return Err(unknown_setting(name)),
+        };
+        Ok(value)
+    }
+
+    /// Changes the setting `name` to `value`, after checking that it is a
+    /// valid value of the setting
+    pub fn set_setting(&mut self, name: &str, value: &str) -> Result<()> {
+        match name {
+            TARGET_PARTITIONS => self.target_partitions = parse_positive(name, value)?,
+            BATCH_SIZE => self.batch_size = parse_positive(name, value)?,
+            TIME_ZONE => {
+                parse_time_zone(value)?;
+                self.time_zone = value.to_owned();
+            }
+            PARQUET_PRUNING => self.parquet_pruning = parse_value(name, value)?,
+            OPTIMIZER_MAX_PASSES => {
+                self.optimizer_max_passes = parse_positive(name, value)?
+            }
+            DISABLED_OPTIMIZER_RULES => {
+                self.disabled_optimizer_rules = parse_rule_names(value)
+            }
+            AGGREGATE_PUSH_DOWN => {
+                self.set_aggregate_push_down(parse_value(name, value)?)
+            }
+            REPARTITION_JOINS => self.repartition_joins = parse_value(name, value)?,
+            REPARTITION_AGGREGATIONS => {
+                self.repartition_aggregations = parse_value(name, value)?
+            }
+            REPARTITION_WINDOWS => self.repartition_windows = parse_value(name, value)?,
+            RUNTIME_FILTERS => self.runtime_filters = parse_value(name, value)?,
+            DEFAULT_CATALOG => self.default_catalog = value.to_owned(),
+            DEFAULT_SCHEMA => self.default_schema = value.to_owned(),
+            CREATE_DEFAULT_CATALOG_AND_SCHEMA => {
+                self.create_default_catalog_and_schema = parse_value(name, value)?
+            }
+            INFORMATION_SCHEMA => self.information_schema = parse_value(name, value)?,
+            _ => return Err(unknown_setting(name)),
+        }
+        Ok(())
+    }
+
+    /// Changes the setting `name` of a running session to `value`, like
+    /// [`Self::set_setting`]. The catalog settings are read-only, and the
+    /// disabled optimizer rules must be registered.
+    pub fn set_session_setting(&mut self, name: &str, value: &str) -> Result<()> {
+        if READ_ONLY_SETTINGS.contains(&name) {
+            return Err(DataFusionError::Plan(format!(
+                "Configuration setting '{}' is read-only",
+                name
+            )));
+        }
+        if name == DISABLED_OPTIMIZER_RULES {
+            let rules = parse_rule_names(value);
+            if let Some(rule) = rules.iter().find(|rule| !self.has_rule(rule)) {
+                return Err(DataFusionError::Plan(format!(
+                    "Unknown optimizer rule '{}' for configuration setting '{}'",
+                    rule, name
+                )));
+            }
+        }
+        self.set_setting(name, value)
+    }
+
+    /// Changes the setting `name` to `value`, see [`Self::set_setting`]
+    pub fn with_setting(mut self, name: &str, value: &str) -> Result<Self> {
+        self.set_setting(name, value)?;
+        Ok(self)
+    }
+
+    /// Returns true if a logical or physical optimizer rule is named `name`
+    fn has_rule(&self, name: &str) -> bool {
+        self.optimizers.iter().any(|rule| rule.name() == name)
+            || self
+                .physical_optimizers
+                .iter()
+                .any(|rule| rule.name() == name)
+    }
+}
+
+fn unknown_setting(name: &str) -> DataFusionError {
+    DataFusionError::Plan(format!("Unknown configuration setting '{}'", name))
+}
+
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    value.trim().parse().map_err(|e| {
+        DataFusionError::Plan(format!(
+            "Failed to parse value '{}' for configuration setting '{}': {}",
+            value, name, e
+        ))
+    })
+}
+
+/// Parses the comma separated names of the disabled optimizer rules
+fn parse_rule_names(value: &str) -> HashSet<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<usize> {
+    match parse_value(name, value)? {
+        0 => Err(DataFusionError::Plan(format!(
+            "Configuration setting '{}' must be greater than zero",
+            name
+        ))),
+        n => Ok(n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config() -> Result<()> {
+        let config = ExecutionConfig::new();
+        let entries = ExecutionConfig::valid_entries();
+        for (name, entry) in &entries {
+            assert_eq!(
+                entry.default_value.as_deref(),
+                Some(config.get_setting(name)?.as_str())
+            );
+        }
+        assert_eq!(entries[BATCH_SIZE].default_value.as_deref(), Some("8192"));
+        assert_eq!(entries[TIME_ZONE].default_value.as_deref(), Some("UTC"));
+        Ok(())
+    }
+
+    #[test]
+    fn custom_config() -> Result<()> {
+        let config = ExecutionConfig::new()
+            .with_setting(BATCH_SIZE, "4096")?
+            .with_setting(TIME_ZONE, "+02:00")?
+            .with_setting(REPARTITION_JOINS, "false")?
+            .with_setting(AGGREGATE_PUSH_DOWN, "true")?
+            .with_setting(DISABLED_OPTIMIZER_RULES, "limit_push_down, eliminate_limit")?;
+        assert_eq!(config.batch_size, 4096);
+        assert_eq!(config.time_zone, "+02:00");
+        assert!(!config.repartition_joins);
+        assert!(!config.is_optimizer_rule_enabled("eliminate_limit"));
+        assert_eq!(config.get_setting(AGGREGATE_PUSH_DOWN)?, "true");
+        assert_eq!(
+            config.get_setting(DISABLED_OPTIMIZER_RULES)?,
+            "eliminate_limit,limit_push_down"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn custom_config_invalid() {
+        let mut config = ExecutionConfig::new();
+        let err = config.set_setting(BATCH_SIZE, "true").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Failed to parse value 'true' for configuration setting \
+             'datafusion.execution.batch_size': invalid digit found in string"
+        );
+        let err = config.set_setting(BATCH_SIZE, "0").unwrap_err();
+        assert!(err.to_string().contains("must be greater than zero"));
+        let err = config.set_setting(TIME_ZONE, "Mars/Olympus").unwrap_err();
+        assert!(err.to_string().contains("Invalid time zone 'Mars/Olympus'"));
+        let err = config.set_setting("datafusion.unknown", "1").unwrap_err();
+        assert!(err.to_string().contains("Unknown configuration setting"));
+        assert_eq!(config.batch_size, 8192);
+    }
+
+    #[test]
+    fn config_of_new_context() -> Result<()> {
+        // the catalog settings can be set before the context is created, and
+        // the disabled rules may be registered afterwards
+        let config = ExecutionConfig::new()
+            .with_setting(INFORMATION_SCHEMA, "true")?
+            .with_setting(DEFAULT_SCHEMA, "other")?
+            .with_setting(DISABLED_OPTIMIZER_RULES, "my_rule")?;
+        assert!(config.information_schema);
+        assert_eq!(config.default_schema, "other");
+        assert!(!config.is_optimizer_rule_enabled("my_rule"));
+        Ok(())
+    }
+
+    #[test]
+    fn session_config_invalid() {
+        let mut config = ExecutionConfig::new();
+        let err = config
+            .set_session_setting(
+                DISABLED_OPTIMIZER_RULES,
+                "limit_push_down, no_such_rule",
+            )
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unknown optimizer rule 'no_such_rule'"));
+        assert!(config.disabled_optimizer_rules.is_empty());
+
+        let err = config
+            .set_session_setting(DEFAULT_SCHEMA, "other")
+            .unwrap_err();
+        assert!(err.to_string().contains("is read-only"));
+        assert_eq!(config.default_schema, "public");
+    }
+}
@@ -35,7 +35,8 @@ use crate::{
     optimizer::eliminate_limit::EliminateLimit,
     physical_optimizer::{
         aggregate_statistics::AggregateStatistics,
-        hash_build_probe_order::HashBuildProbeOrder, optimizer::PhysicalOptimizerRule,
+        hash_build_probe_order::HashBuildProbeOrder, join_reorder::JoinReorder,
+        optimizer::PhysicalOptimizerRule,
     },
 };
 use log::debug;
@@ -48,7 +49,8 @@ use std::{
     sync::Mutex,
 };
 
-use futures::{StreamExt, TryStreamExt};
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt, TryStreamExt};
 use tokio::task::{self, JoinHandle};
 
 use arrow::{csv, datatypes::SchemaRef};
@@ -59,31 +61,40 @@ use crate::catalog::{
     ResolvedTableReference, TableReference,
 };
 use crate::datasource::object_store::{ObjectStore, ObjectStoreRegistry};
+use crate::datasource::table_function::{self, TableFunction, TableFunctionCall};
 use crate::datasource::TableProvider;
 use crate::error::{DataFusionError, Result};
 use crate::execution::dataframe_impl::DataFrameImpl;
 use crate::logical_plan::{
     CreateExternalTable, CreateMemoryTable, DropTable, FunctionRegistry, LogicalPlan,
-    LogicalPlanBuilder, UNNAMED_TABLE,
+    LogicalPlanBuilder, Prepare, SetVariable, UNNAMED_TABLE,
 };
+use crate::optimizer::aggregate_push_down::AggregatePushDown;
 use crate::optimizer::common_subexpr_eliminate::CommonSubexprEliminate;
+use crate::optimizer::eliminate_cross_join::EliminateCrossJoin;
 use crate::optimizer::filter_push_down::FilterPushDown;
+use crate::optimizer::infer_predicates::InferPredicates;
 use crate::optimizer::limit_push_down::LimitPushDown;
 use crate::optimizer::optimizer::OptimizerRule;
 use crate::optimizer::projection_push_down::ProjectionPushDown;
 use crate::optimizer::simplify_expressions::SimplifyExpressions;
+use crate::optimizer::utils::same_plan;
 use crate::physical_optimizer::coalesce_batches::CoalesceBatches;
+use crate::physical_optimizer::enforce_sorting::EnforceSorting;
 use crate::physical_optimizer::merge_exec::AddCoalescePartitionsExec;
+use crate::physical_optimizer::partial_aggregate_push_down::PartialAggregatePushDown;
 use crate::physical_optimizer::repartition::Repartition;
+use crate::physical_optimizer::runtime_filters::AddRuntimeFilters;
 
 use crate::logical_plan::plan::Explain;
 use crate::optimizer::single_distinct_to_groupby::SingleDistinctToGroupBy;
 use crate::physical_plan::planner::DefaultPhysicalPlanner;
 use crate::physical_plan::udf::ScalarUDF;
+use crate::physical_plan::udwf::WindowUDF;
 use crate::physical_plan::ExecutionPlan;
 use crate::physical_plan::PhysicalPlanner;
 use crate::sql::{
-    parser::{DFParser, FileType},
+    parser::{DFParser, FileType, Statement as DFStatement},
     planner::{ContextProvider, SqlToRel},
 };
 use crate::variable::{VarProvider, VarType};
@@ -151,7 +162,7 @@ impl ExecutionContext {
     pub fn with_config(config: ExecutionConfig) -> Self {
         let catalog_list = Arc::new(MemoryCatalogList::new()) as Arc<dyn CatalogList>;
 
-        if config.create_default_catalog_and_schema {
+        let default_catalog = if config.create_default_catalog_and_schema {
             let default_catalog = MemoryCatalogProvider::new();
 
             default_catalog.register_schema(
@@ -159,30 +170,40 @@ impl ExecutionContext {
                 Arc::new(MemorySchemaProvider::new()),
             );
 
-            let default_catalog: Arc<dyn CatalogProvider> = if config.information_schema {
-                Arc::new(CatalogWithInformationSchema::new(
-                    Arc::downgrade(&catalog_list),
-                    Arc::new(default_catalog),
-                ))
-            } else {
-                Arc::new(default_catalog)
-            };
+            Some((config.default_catalog.clone(), default_catalog))
+        } else {
+            None
+        };
 
-            catalog_list
-                .register_catalog(config.default_catalog.clone(), default_catalog);
-        }
+        let table_functions = vec![
+            table_function::generate_series(),
+            table_function::read_parquet(),
+        ]
+        .into_iter()
+        .map(|f| (f.name.clone(), Arc::new(f)))
+        .collect();
 
-        Self {
+        let context = Self {
             state: Arc::new(Mutex::new(ExecutionContextState {
                 catalog_list,
                 scalar_functions: HashMap::new(),
                 var_provider: HashMap::new(),
                 aggregate_functions: HashMap::new(),
+                window_functions: HashMap::new(),
+                table_functions,
                 config,
                 execution_props: ExecutionProps::new(),
                 object_store_registry: Arc::new(ObjectStoreRegistry::new()),
+                prepared_statements: HashMap::new(),
             })),
+        };
+
+        // registered after the state is created, as its information schema refers to it
+        if let Some((name, default_catalog)) = default_catalog {
+            context.register_catalog(name, Arc::new(default_catalog));
         }
+
+        context
     }
 
     /// Creates a dataframe that will execute a SQL query.
@@ -190,7 +211,7 @@ impl ExecutionContext {
     /// This method is `async` because queries of type `CREATE EXTERNAL TABLE`
     /// might require the schema to be inferred.
     pub async fn sql(&mut self, sql: &str) -> Result<Arc<dyn DataFrame>> {
-        let plan = self.create_logical_plan(sql)?;
+        let plan = self.create_logical_plan_async(sql).await?;
         match plan {
             LogicalPlan::CreateExternalTable(CreateExternalTable {
                 ref schema,
@@ -227,6 +248,7 @@ impl ExecutionContext {
                         .config
                         .target_partitions,
                     table_partition_cols: vec![],
+                    file_sort_order: None,
                 };
 
                 // TODO make schema in CreateExternalTable optional instead of empty
@@ -270,6 +292,28 @@ impl ExecutionContext {
                 }
             }
 
+            LogicalPlan::SetVariable(SetVariable {
+                variable, value, ..
+            }) => {
+                self.state
+                    .lock()
+                    .unwrap()
+                    .config
+                    .set_session_setting(&variable, &value)?;
+                let plan = LogicalPlanBuilder::empty(false).build()?;
+                Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)))
+            }
+
+            LogicalPlan::Prepare(Prepare { name, input, .. }) => {
+                self.state
+                    .lock()
+                    .unwrap()
+                    .prepared_statements
+                    .insert(name, (*input).clone());
+                let plan = LogicalPlanBuilder::empty(false).build()?;
+                Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)))
+            }
+
             plan => Ok(Arc::new(DataFrameImpl::new(
                 self.state.clone(),
                 &self.optimize(&plan)?,
@@ -280,19 +324,37 @@ impl ExecutionContext {
     /// Creates a logical plan.
     ///
     /// This function is intended for internal use and should not be called directly.
+    ///
+    /// The tables of the table functions in the query must be created without
+    /// waiting, see [`Self::create_logical_plan_async`] for the ones that read
+    /// files.
     pub fn create_logical_plan(&self, sql: &str) -> Result<LogicalPlan> {
-        let statements = DFParser::parse_sql(sql)?;
-
-        if statements.len() != 1 {
-            return Err(DataFusionError::NotImplemented(
-                "The context currently only supports a single SQL statement".to_string(),
-            ));
+        let statement = parse_single_statement(sql)?;
+        let state = self.state.lock().unwrap().clone();
+        let mut tables = HashMap::new();
+        for (call, table) in state.create_table_function_tables(&statement)? {
+            let table = table.now_or_never().ok_or_else(|| {
+                DataFusionError::Plan(format!(
+                    "Table function '{}' creates its table asynchronously, plan the query with ExecutionContext::create_logical_plan_async",
+                    call.name
+                ))
+            })??;
+            tables.insert(call, table);
         }
+        state.statement_to_plan(&statement, tables)
+    }
 
-        // create a query planner
+    /// Creates a logical plan, like [`Self::create_logical_plan`], after
+    /// creating the tables of the table functions in the query, which may
+    /// have to read files.
+    pub async fn create_logical_plan_async(&self, sql: &str) -> Result<LogicalPlan> {
+        let statement = parse_single_statement(sql)?;
         let state = self.state.lock().unwrap().clone();
-        let query_planner = SqlToRel::new(&state);
-        query_planner.statement_to_plan(&statements[0])
+        let mut tables = HashMap::new();
+        for (call, table) in state.create_table_function_tables(&statement)? {
+            tables.insert(call, table.await?);
+        }
+        state.statement_to_plan(&statement, tables)
     }
 
     /// Registers a variable provider within this context.
@@ -338,6 +400,43 @@ impl ExecutionContext {
             .insert(f.name.clone(), Arc::new(f));
     }
 
+    /// Registers a window UDF within this context, which can then be used with
+    /// an OVER clause in SQL queries.
+    ///
+    /// As for aggregate UDFs, names are looked up using lowercase unless the
+    /// query uses quotes. For example,
+    ///
+    /// `SELECT MY_UDWF(x) OVER (...)` will look for a window function named `"my_udwf"`
+    pub fn register_udwf(&mut self, f: WindowUDF) {
+        self.state
+            .lock()
+            .unwrap()
+            .window_functions
+            .insert(f.name.clone(), Arc::new(f));
+    }
+
+    /// Loads the shared library at `path` and registers the scalar and
+    /// aggregate UDFs it declares within this context.
+    ///
+    /// See [`plugin`](crate::execution::plugin) for how to write such a library.
+    #[cfg(feature = "plugins")]
+    pub fn load_plugin(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        crate::execution::plugin::load_plugin(path, &mut *self.state.lock().unwrap())
+    }
+
+    /// Registers a table function within this context, which can then be
+    /// used in the FROM clause of SQL queries: `SELECT * FROM my_func(1, 'a')`.
+    ///
+    /// As for scalar UDFs, names are looked up using lowercase unless the
+    /// query uses quotes.
+    pub fn register_table_function(&mut self, f: TableFunction) {
+        self.state
+            .lock()
+            .unwrap()
+            .table_functions
+            .insert(f.name.clone(), Arc::new(f));
+    }
+
     /// Creates a DataFrame for reading an Avro data source.
 
     pub async fn read_avro(
@@ -484,6 +583,7 @@ impl ExecutionContext {
             file_extension: DEFAULT_PARQUET_EXTENSION.to_owned(),
             target_partitions,
             table_partition_cols: vec![],
+            file_sort_order: None,
         };
 
         self.register_listing_table(name, uri, listing_options, None)
@@ -524,6 +624,7 @@ impl ExecutionContext {
         let catalog = if state.config.information_schema {
             Arc::new(CatalogWithInformationSchema::new(
                 Arc::downgrade(&state.catalog_list),
+                Arc::downgrade(&self.state),
                 catalog,
             ))
         } else {
@@ -656,12 +757,25 @@ impl ExecutionContext {
             let mut stringified_plans = e.stringified_plans.clone();
 
             // optimize the child plan, capturing the output of each optimizer
-            let plan =
-                self.optimize_internal(e.plan.as_ref(), |optimized_plan, optimizer| {
-                    let optimizer_name = optimizer.name().to_string();
+            let plan = self.optimize_internal(
+                e.plan.as_ref(),
+                |optimized_plan, optimizer, pass| {
+                    let optimizer_name = match pass {
+                        0 => optimizer.name().to_string(),
+                        _ => format!("{} (pass {})", optimizer.name(), pass + 1),
+                    };
                     let plan_type = PlanType::OptimizedLogicalPlan { optimizer_name };
-                    stringified_plans.push(optimized_plan.to_stringified(plan_type));
-                })?;
+                    let stringified_plan = optimized_plan.to_stringified(plan_type);
+                    // after the first pass, only show the rules changing the plan
+                    let changed = match stringified_plans.last() {
+                        Some(previous) => previous.plan != stringified_plan.plan,
+                        None => true,
+                    };
+                    if pass == 0 || changed {
+                        stringified_plans.push(stringified_plan);
+                    }
+                },
+            )?;
 
             Ok(LogicalPlan::Explain(Explain {
                 verbose: e.verbose,
@@ -670,7 +784,7 @@ impl ExecutionContext {
                 schema: e.schema.clone(),
             }))
         } else {
-            self.optimize_internal(plan, |_, _| {})
+            self.optimize_internal(plan, |_, _, _| {})
         }
     }
 
@@ -788,19 +902,33 @@ impl ExecutionContext {
         mut observer: F,
     ) -> Result<LogicalPlan>
     where
-        F: FnMut(&LogicalPlan, &dyn OptimizerRule),
+        F: FnMut(&LogicalPlan, &dyn OptimizerRule, usize),
     {
         let state = &mut self.state.lock().unwrap();
         let execution_props = &mut state.execution_props.clone();
-        let optimizers = &state.config.optimizers;
+        let config = &state.config;
 
         let execution_props = execution_props.start_execution();
 
         let mut new_plan = plan.clone();
         debug!("Logical plan:\n {:?}", plan);
-        for optimizer in optimizers {
-            new_plan = optimizer.optimize(&new_plan, execution_props)?;
-            observer(&new_plan, optimizer.as_ref());
+        for pass in 0..config.optimizer_max_passes {
+            // the plans are only compared when there may be another pass
+            let previous_plan =
+                (pass + 1 < config.optimizer_max_passes).then(|| new_plan.clone());
+            for optimizer in &config.optimizers {
+                if !config.is_optimizer_rule_enabled(optimizer.name()) {
+                    continue;
+                }
+                new_plan = optimizer.optimize(&new_plan, execution_props)?;
+                observer(&new_plan, optimizer.as_ref(), pass);
+            }
+            if let Some(previous_plan) = previous_plan {
+                if same_plan(&new_plan, &previous_plan) {
+                    debug!("Logical plan unchanged by optimizer pass {}", pass + 1);
+                    break;
+                }
+            }
         }
         debug!("Optimized logical plan:\n {:?}", new_plan);
         Ok(new_plan)
@@ -825,6 +953,10 @@ impl FunctionRegistry for ExecutionContext {
     fn udaf(&self, name: &str) -> Result<Arc<AggregateUDF>> {
         self.state.lock().unwrap().udaf(name)
     }
+
+    fn udwf(&self, name: &str) -> Result<Arc<WindowUDF>> {
+        self.state.lock().unwrap().udwf(name)
+    }
 }
 
 /// A planner used to add extensions to DataFusion logical and physical plans.
@@ -862,20 +994,25 @@ pub struct ExecutionConfig {
     /// Default batch size when reading data sources
     pub batch_size: usize,
     /// Responsible for optimizing a logical plan
-    optimizers: Vec<Arc<dyn OptimizerRule + Send + Sync>>,
+    pub(crate) optimizers: Vec<Arc<dyn OptimizerRule + Send + Sync>>,
+    /// Maximum number of times the logical optimizer rules are all applied,
+    /// stopping earlier when a pass does not change the plan
+    pub optimizer_max_passes: usize,
+    /// Names of the logical and physical optimizer rules that are skipped
+    pub disabled_optimizer_rules: HashSet<String>,
     /// Responsible for optimizing a physical execution plan
     pub physical_optimizers: Vec<Arc<dyn PhysicalOptimizerRule + Send + Sync>>,
     /// Responsible for planning `LogicalPlan`s, and `ExecutionPlan`
     query_planner: Arc<dyn QueryPlanner + Send + Sync>,
     /// Default catalog name for table resolution
-    default_catalog: String,
+    pub(crate) default_catalog: String,
     /// Default schema name for table resolution
-    default_schema: String,
+    pub(crate) default_schema: String,
     /// Whether the default catalog and schema should be created automatically
-    create_default_catalog_and_schema: bool,
+    pub(crate) create_default_catalog_and_schema: bool,
     /// Should DataFusion provide access to `information_schema`
     /// virtual tables for displaying schema information
-    information_schema: bool,
+    pub(crate) information_schema: bool,
     /// Should DataFusion repartition data using the join keys to execute joins in parallel
     /// using the provided `target_partitions` level
     pub repartition_joins: bool,
@@ -886,7 +1023,13 @@ pub struct ExecutionConfig {
     /// parallel using the provided `target_partitions` level
     pub repartition_windows: bool,
     /// Should Datafusion parquet reader using the predicate to prune data
-    parquet_pruning: bool,
+    pub(crate) parquet_pruning: bool,
+    /// Should the hash joins that collect their build side filter the rows
+    /// of their probe side with the build side keys. The filters are only
+    /// computed for the keys the probe side filters or prunes with.
+    pub runtime_filters: bool,
+    /// Session time zone, used for `TIMESTAMP WITH TIME ZONE` and `now()`
+    pub time_zone: String,
 }
 
 impl Default for ExecutionConfig {
@@ -901,16 +1044,26 @@ impl Default for ExecutionConfig {
                 Arc::new(CommonSubexprEliminate::new()),
                 Arc::new(EliminateLimit::new()),
                 Arc::new(ProjectionPushDown::new()),
+                Arc::new(InferPredicates::new()),
                 Arc::new(FilterPushDown::new()),
+                // after the filters are pushed down, as the filters of views
+                // or subqueries may only then end up right above a cross join
+                Arc::new(EliminateCrossJoin::new()),
                 Arc::new(LimitPushDown::new()),
                 Arc::new(SingleDistinctToGroupBy::new()),
             ],
+            optimizer_max_passes: 1,
+            disabled_optimizer_rules: HashSet::new(),
             physical_optimizers: vec![
                 Arc::new(AggregateStatistics::new()),
+                Arc::new(PartialAggregatePushDown::new()),
+                Arc::new(JoinReorder::new()),
                 Arc::new(HashBuildProbeOrder::new()),
                 Arc::new(CoalesceBatches::new()),
                 Arc::new(Repartition::new()),
                 Arc::new(AddCoalescePartitionsExec::new()),
+                Arc::new(EnforceSorting::new()),
+                Arc::new(AddRuntimeFilters::new()),
             ],
             query_planner: Arc::new(DefaultQueryPlanner {}),
             default_catalog: "datafusion".to_owned(),
@@ -921,6 +1074,8 @@ impl Default for ExecutionConfig {
             repartition_aggregations: true,
             repartition_windows: true,
             parquet_pruning: true,
+            runtime_filters: true,
+            time_zone: "UTC".to_owned(),
         }
     }
 }
@@ -992,6 +1147,73 @@ impl ExecutionConfig {
         self
     }
 
+    /// Inserts a new [`OptimizerRule`] right before the rule named `name`
+    pub fn add_optimizer_rule_before(
+        mut self,
+        name: &str,
+        optimizer_rule: Arc<dyn OptimizerRule + Send + Sync>,
+    ) -> Result<Self> {
+        let index = rule_index(self.optimizers.iter().map(|rule| rule.name()), name)?;
+        self.optimizers.insert(index, optimizer_rule);
+        Ok(self)
+    }
+
+    /// Inserts a new [`OptimizerRule`] right after the rule named `name`
+    pub fn add_optimizer_rule_after(
+        mut self,
+        name: &str,
+        optimizer_rule: Arc<dyn OptimizerRule + Send + Sync>,
+    ) -> Result<Self> {
+        let index = rule_index(self.optimizers.iter().map(|rule| rule.name()), name)?;
+        self.optimizers.insert(index + 1, optimizer_rule);
+        Ok(self)
+    }
+
+    /// Inserts a new [`PhysicalOptimizerRule`] right before the rule named `name`
+    pub fn add_physical_optimizer_rule_before(
+        mut self,
+        name: &str,
+        optimizer_rule: Arc<dyn PhysicalOptimizerRule + Send + Sync>,
+    ) -> Result<Self> {
+        let names = self.physical_optimizers.iter().map(|rule| rule.name());
+        let index = rule_index(names, name)?;
+        self.physical_optimizers.insert(index, optimizer_rule);
+        Ok(self)
+    }
+
+    /// Inserts a new [`PhysicalOptimizerRule`] right after the rule named `name`
+    pub fn add_physical_optimizer_rule_after(
+        mut self,
+        name: &str,
+        optimizer_rule: Arc<dyn PhysicalOptimizerRule + Send + Sync>,
+    ) -> Result<Self> {
+        let names = self.physical_optimizers.iter().map(|rule| rule.name());
+        let index = rule_index(names, name)?;
+        self.physical_optimizers.insert(index + 1, optimizer_rule);
+        Ok(self)
+    }
+
+    /// Skips the logical or physical optimizer rule named `name`
+    pub fn with_disabled_optimizer_rule(mut self, name: impl Into<String>) -> Self {
+        self.disabled_optimizer_rules.insert(name.into());
+        self
+    }
+
+    /// Customize the maximum number of passes of the logical optimizer rules.
+    /// With more than one pass, the rules are applied again until they no
+    /// longer change the plan.
+    pub fn with_optimizer_max_passes(mut self, n: usize) -> Self {
+        // at least one pass must be run
+        assert!(n > 0);
+        self.optimizer_max_passes = n;
+        self
+    }
+
+    /// Returns true if the optimizer rule named `name` has not been disabled
+    pub fn is_optimizer_rule_enabled(&self, name: &str) -> bool {
+        !self.disabled_optimizer_rules.contains(name)
+    }
+
     /// Selects a name for the default catalog and schema
     pub fn with_default_catalog_and_schema(
         mut self,
@@ -1038,6 +1260,45 @@ impl ExecutionConfig {
         self.parquet_pruning = enabled;
         self
     }
+
+    /// Enables or disables the runtime filters of hash joins, which skip the
+    /// probe side rows whose keys are not in the build side
+    pub fn with_runtime_filters(mut self, enabled: bool) -> Self {
+        self.runtime_filters = enabled;
+        self
+    }
+
+    /// Enables or disables the pre-aggregation of the inputs of joins by
+    /// their join keys, see [`AggregatePushDown`]. It pays off when the
+    /// pre-aggregated input has many rows per join key.
+    pub fn with_aggregate_push_down(mut self, enabled: bool) -> Self {
+        self.set_aggregate_push_down(enabled);
+        self
+    }
+
+    pub(crate) fn set_aggregate_push_down(&mut self, enabled: bool) {
+        let rule = AggregatePushDown::new();
+        self.optimizers.retain(|r| r.name() != rule.name());
+        if enabled {
+            self.optimizers.push(Arc::new(rule));
+        }
+    }
+
+    /// Customize the session time zone, either an IANA name such as
+    /// `Europe/Berlin` or a fixed offset such as `+02:00`
+    pub fn with_time_zone(mut self, time_zone: impl Into<String>) -> Self {
+        self.time_zone = time_zone.into();
+        self
+    }
+}
+
+/// Returns the position of the optimizer rule named `name` in `names`
+fn rule_index<'a>(mut names: impl Iterator<Item = &'a str>, name: &str) -> Result<usize> {
+    names
+        .position(|rule_name| rule_name == name)
+        .ok_or_else(|| {
+            DataFusionError::Plan(format!("There is no optimizer rule named '{}'", name))
+        })
 }
 
 /// Holds per-execution properties and data (such as starting timestamps, etc).
@@ -1060,12 +1321,18 @@ pub struct ExecutionContextState {
     pub var_provider: HashMap<VarType, Arc<dyn VarProvider + Send + Sync>>,
     /// Aggregate functions registered in the context
     pub aggregate_functions: HashMap<String, Arc<AggregateUDF>>,
+    /// Window functions registered in the context
+    pub window_functions: HashMap<String, Arc<WindowUDF>>,
+    /// Table functions registered in the context
+    pub table_functions: HashMap<String, Arc<TableFunction>>,
     /// Context configuration
     pub config: ExecutionConfig,
     /// Execution properties
     pub execution_props: ExecutionProps,
     /// Object Store that are registered with the context
     pub object_store_registry: Arc<ObjectStoreRegistry>,
+    /// Statements prepared with `PREPARE`, not yet bound to parameter values
+    pub prepared_statements: HashMap<String, LogicalPlan>,
 }
 
 impl ExecutionProps {
@@ -1091,9 +1358,12 @@ impl ExecutionContextState {
             scalar_functions: HashMap::new(),
             var_provider: HashMap::new(),
             aggregate_functions: HashMap::new(),
+            window_functions: HashMap::new(),
+            table_functions: HashMap::new(),
             config: ExecutionConfig::new(),
             execution_props: ExecutionProps::new(),
             object_store_registry: Arc::new(ObjectStoreRegistry::new()),
+            prepared_statements: HashMap::new(),
         }
     }
 
@@ -1128,6 +1398,99 @@ impl ExecutionContextState {
                 ))
             })
     }
+
+    /// Starts creating the tables of the calls to table functions in
+    /// `statement`. Calls to unknown functions are left to the planner to
+    /// report.
+    #[allow(clippy::type_complexity)]
+    fn create_table_function_tables(
+        &self,
+        statement: &DFStatement,
+    ) -> Result<
+        Vec<(
+            TableFunctionCall,
+            BoxFuture<'static, Result<Arc<dyn TableProvider>>>,
+        )>,
+    > {
+        Ok(SqlToRel::new(self)
+            .table_function_calls(statement)?
+            .into_iter()
+            .filter_map(|call| {
+                let function = self.table_functions.get(&call.name)?;
+                let table = function.create_table_provider(self, &call.args);
+                Some((call, table))
+            })
+            .collect())
+    }
+
+    /// Plans `statement`, given the tables created by the calls to table
+    /// functions in it
+    fn statement_to_plan(
+        &self,
+        statement: &DFStatement,
+        tables: HashMap<TableFunctionCall, Arc<dyn TableProvider>>,
+    ) -> Result<LogicalPlan> {
+        let provider = StatementContextProvider {
+            state: self,
+            table_function_tables: tables,
+        };
+        SqlToRel::new(&provider).statement_to_plan(statement)
+    }
+}
+
+/// Parses `sql`, which must consist of a single statement
+fn parse_single_statement(sql: &str) -> Result<DFStatement> {
+    let mut statements = DFParser::parse_sql(sql)?;
+    if statements.len() != 1 {
+        return Err(DataFusionError::NotImplemented(
+            "The context currently only supports a single SQL statement".to_string(),
+        ));
+    }
+    Ok(statements.remove(0))
+}
+
+/// The state of a context, together with the tables created by the calls to
+/// table functions in the statement that is planned
+struct StatementContextProvider<'a> {
+    state: &'a ExecutionContextState,
+    table_function_tables: HashMap<TableFunctionCall, Arc<dyn TableProvider>>,
+}
+
+impl ContextProvider for StatementContextProvider<'_> {
+    fn get_table_provider(&self, name: TableReference) -> Option<Arc<dyn TableProvider>> {
+        self.state.get_table_provider(name)
+    }
+
+    fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>> {
+        self.state.get_function_meta(name)
+    }
+
+    fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>> {
+        self.state.get_aggregate_meta(name)
+    }
+
+    fn get_window_meta(&self, name: &str) -> Option<Arc<WindowUDF>> {
+        self.state.get_window_meta(name)
+    }
+
+    fn get_table_function_provider(
+        &self,
+        call: &TableFunctionCall,
+    ) -> Option<Arc<dyn TableProvider>> {
+        self.table_function_tables.get(call).cloned()
+    }
+
+    fn get_time_zone(&self) -> String {
+        self.state.get_time_zone()
+    }
+
+    fn get_config_setting(&self, name: &str) -> Option<String> {
+        self.state.get_config_setting(name)
+    }
+
+    fn get_prepared_statement(&self, name: &str) -> Option<LogicalPlan> {
+        self.state.get_prepared_statement(name)
+    }
 }
 
 impl ContextProvider for ExecutionContextState {
@@ -1144,6 +1507,22 @@ impl ContextProvider for ExecutionContextState {
     fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>> {
         self.aggregate_functions.get(name).cloned()
     }
+
+    fn get_window_meta(&self, name: &str) -> Option<Arc<WindowUDF>> {
+        self.window_functions.get(name).cloned()
+    }
+
+    fn get_time_zone(&self) -> String {
+        self.config.time_zone.clone()
+    }
+
+    fn get_config_setting(&self, name: &str) -> Option<String> {
+        self.config.get_setting(name).ok()
+    }
+
+    fn get_prepared_statement(&self, name: &str) -> Option<LogicalPlan> {
+        self.prepared_statements.get(name).cloned()
+    }
 }
 
 impl FunctionRegistry for ExecutionContextState {
@@ -1172,6 +1551,17 @@ impl FunctionRegistry for ExecutionContextState {
             ))
         })
     }
+
+    fn udwf(&self, name: &str) -> Result<Arc<WindowUDF>> {
+        let result = self.window_functions.get(name);
+
+        result.cloned().ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "There is no UDWF named \"{}\" in the registry",
+                name
+            ))
+        })
+    }
 }
 
 #[cfg(test)]
@@ -1179,9 +1569,12 @@ mod tests {
     use super::*;
     use crate::logical_plan::plan::Projection;
     use crate::logical_plan::TableScan;
-    use crate::logical_plan::{binary_expr, lit, Operator};
-    use crate::physical_plan::functions::{make_scalar_function, Volatility};
+    use crate::logical_plan::{binary_expr, lit, Limit, Operator};
+    use crate::physical_plan::functions::{
+        make_scalar_function, Parameter, ReturnTypeFunction, Signature, Volatility,
+    };
     use crate::physical_plan::{collect, collect_partitioned};
+    use crate::scalar::ScalarValue;
     use crate::test;
     use crate::variable::VarType;
     use crate::{
@@ -1204,6 +1597,7 @@ mod tests {
     use arrow::record_batch::RecordBatch;
     use async_trait::async_trait;
     use std::fs::File;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Weak;
     use std::thread::{self, JoinHandle};
     use std::{io::prelude::*, sync::Mutex};
@@ -3414,6 +3808,106 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn optimizer_rules_at_named_positions() -> Result<()> {
+        let config = ExecutionConfig::new()
+            .add_optimizer_rule_before("filter_push_down", Arc::new(NamedRule("before")))?
+            .add_optimizer_rule_after("filter_push_down", Arc::new(NamedRule("after")))?
+            .add_physical_optimizer_rule_after(
+                "repartition",
+                Arc::new(NamedRule("after")),
+            )?;
+
+        let names: Vec<&str> = config.optimizers.iter().map(|rule| rule.name()).collect();
+        let index = names
+            .iter()
+            .position(|name| *name == "filter_push_down")
+            .unwrap();
+        assert_eq!(
+            names[index - 1..=index + 1],
+            ["before", "filter_push_down", "after"]
+        );
+
+        let names: Vec<&str> = config
+            .physical_optimizers
+            .iter()
+            .map(|rule| rule.name())
+            .collect();
+        let index = names
+            .iter()
+            .position(|name| *name == "repartition")
+            .unwrap();
+        assert_eq!(names[index + 1], "after");
+
+        let err = ExecutionConfig::new()
+            .add_optimizer_rule_before("missing", Arc::new(NamedRule("before")))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: There is no optimizer rule named 'missing'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn optimizer_passes_until_fixed_point() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("id", DataType::Int32, false)]);
+        let plan = LogicalPlanBuilder::scan_empty(Some("employee"), &schema, None)?
+            .limit(3)?
+            .build()?;
+
+        let rule = Arc::new(DecrementLimit::default());
+        let ctx = ExecutionContext::with_config(
+            ExecutionConfig::new()
+                .with_optimizer_rules(vec![rule.clone()])
+                .with_optimizer_max_passes(10),
+        );
+        let optimized_plan = ctx.optimize(&plan)?;
+        assert_eq!(
+            format!("{:?}", optimized_plan),
+            "Limit: 0\n  TableScan: employee projection=None"
+        );
+        // the fourth pass does not change the plan anymore
+        assert_eq!(rule.calls.load(Ordering::SeqCst), 4);
+
+        // only the rules changing the plan are shown after the first pass
+        let explain = LogicalPlanBuilder::from(plan.clone())
+            .explain(true, false)?
+            .build()?;
+        let optimizer_names = match ctx.optimize(&explain)? {
+            LogicalPlan::Explain(e) => e
+                .stringified_plans
+                .into_iter()
+                .filter_map(|p| match p.plan_type {
+                    PlanType::OptimizedLogicalPlan { optimizer_name } => {
+                        Some(optimizer_name)
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            plan => panic!("plan was not an explain: {:?}", plan),
+        };
+        assert_eq!(
+            optimizer_names,
+            vec![
+                "decrement_limit",
+                "decrement_limit (pass 2)",
+                "decrement_limit (pass 3)"
+            ]
+        );
+
+        let rule = Arc::new(DecrementLimit::default());
+        let ctx = ExecutionContext::with_config(
+            ExecutionConfig::new()
+                .with_optimizer_rules(vec![rule.clone()])
+                .with_disabled_optimizer_rule("decrement_limit"),
+        );
+        assert_eq!(format!("{:?}", ctx.optimize(&plan)?), format!("{:?}", plan));
+        assert_eq!(rule.calls.load(Ordering::SeqCst), 0);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn custom_query_planner() -> Result<()> {
         let mut ctx = ExecutionContext::with_config(
@@ -3454,6 +3948,8 @@ mod tests {
             "| table_catalog | table_schema       | table_name | table_type |",
             "+---------------+--------------------+------------+------------+",
             "| datafusion    | information_schema | columns    | VIEW       |",
+            "| datafusion    | information_schema | parameters | VIEW       |",
+            "| datafusion    | information_schema | routines   | VIEW       |",
             "| datafusion    | information_schema | tables     | VIEW       |",
             "+---------------+--------------------+------------+------------+",
         ];
@@ -3481,6 +3977,8 @@ mod tests {
             "+---------------+--------------------+------------+------------+",
             "| datafusion    | information_schema | tables     | VIEW       |",
             "| datafusion    | information_schema | columns    | VIEW       |",
+            "| datafusion    | information_schema | parameters | VIEW       |",
+            "| datafusion    | information_schema | routines   | VIEW       |",
             "| datafusion    | public             | t          | BASE TABLE |",
             "+---------------+--------------------+------------+------------+",
         ];
@@ -3500,6 +3998,8 @@ mod tests {
             "| table_catalog | table_schema       | table_name | table_type |",
             "+---------------+--------------------+------------+------------+",
             "| datafusion    | information_schema | columns    | VIEW       |",
+            "| datafusion    | information_schema | parameters | VIEW       |",
+            "| datafusion    | information_schema | routines   | VIEW       |",
             "| datafusion    | information_schema | tables     | VIEW       |",
             "| datafusion    | public             | t          | BASE TABLE |",
             "| datafusion    | public             | t2         | BASE TABLE |",
@@ -3542,12 +4042,18 @@ mod tests {
             "| table_catalog    | table_schema       | table_name | table_type |",
             "+------------------+--------------------+------------+------------+",
             "| datafusion       | information_schema | columns    | VIEW       |",
+            "| datafusion       | information_schema | parameters | VIEW       |",
+            "| datafusion       | information_schema | routines   | VIEW       |",
             "| datafusion       | information_schema | tables     | VIEW       |",
             "| my_catalog       | information_schema | columns    | VIEW       |",
+            "| my_catalog       | information_schema | parameters | VIEW       |",
+            "| my_catalog       | information_schema | routines   | VIEW       |",
             "| my_catalog       | information_schema | tables     | VIEW       |",
             "| my_catalog       | my_schema          | t1         | BASE TABLE |",
             "| my_catalog       | my_schema          | t2         | BASE TABLE |",
             "| my_other_catalog | information_schema | columns    | VIEW       |",
+            "| my_other_catalog | information_schema | parameters | VIEW       |",
+            "| my_other_catalog | information_schema | routines   | VIEW       |",
             "| my_other_catalog | information_schema | tables     | VIEW       |",
             "| my_other_catalog | my_other_schema    | t3         | BASE TABLE |",
             "+------------------+--------------------+------------+------------+",
@@ -3606,6 +4112,8 @@ mod tests {
             "+---------------+--------------------+------------+-----------------+",
             "| datafusion    | information_schema | tables     | VIEW            |",
             "| datafusion    | information_schema | columns    | VIEW            |",
+            "| datafusion    | information_schema | parameters | VIEW            |",
+            "| datafusion    | information_schema | routines   | VIEW            |",
             "| datafusion    | public             | physical   | BASE TABLE      |",
             "| datafusion    | public             | query      | VIEW            |",
             "| datafusion    | public             | temp       | LOCAL TEMPORARY |",
@@ -3614,6 +4122,59 @@ mod tests {
         assert_batches_sorted_eq!(expected, &result);
     }
 
+    #[tokio::test]
+    async fn information_schema_routines_and_parameters() -> Result<()> {
+        let mut ctx = ExecutionContext::with_config(
+            ExecutionConfig::new().with_information_schema(true),
+        );
+        let signature = Signature::exact(
+            vec![DataType::Utf8, DataType::Int64],
+            Volatility::Immutable,
+        )
+        .with_parameters(vec![
+            Parameter::new("s"),
+            Parameter::with_default("n", ScalarValue::Int64(Some(2))),
+        ]);
+        let return_type: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::Utf8)));
+        let fun = make_scalar_function(|args: &[ArrayRef]| Ok(args[0].clone()));
+        ctx.register_udf(ScalarUDF::new("my_pad", &signature, &return_type, &fun));
+        ctx.register_udaf(create_udaf(
+            "my_avg",
+            DataType::Float64,
+            Arc::new(DataType::Float64),
+            Volatility::Immutable,
+            Arc::new(|| Ok(Box::new(AvgAccumulator::try_new(&DataType::Float64)?))),
+            Arc::new(vec![DataType::UInt64, DataType::Float64]),
+        ));
+
+        let result =
+            plan_and_collect(&mut ctx, "SELECT * from information_schema.routines")
+                .await?;
+        let expected = vec![
+            "+--------------+--------------------+------------------+-------------------+",
+            "| routine_name | routine_type       | is_deterministic | signatures        |",
+            "+--------------+--------------------+------------------+-------------------+",
+            "| my_avg       | AGGREGATE FUNCTION | YES              | (Float64)         |",
+            "| my_pad       | FUNCTION           | YES              | (s Utf8, n Int64) |",
+            "+--------------+--------------------+------------------+-------------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &result);
+
+        let result =
+            plan_and_collect(&mut ctx, "SELECT * from information_schema.parameters")
+                .await?;
+        let expected = vec![
+            "+---------------+------------------+----------------+-------------------+",
+            "| specific_name | ordinal_position | parameter_name | parameter_default |",
+            "+---------------+------------------+----------------+-------------------+",
+            "| my_pad        | 1                | s              |                   |",
+            "| my_pad        | 2                | n              | 2                 |",
+            "+---------------+------------------+----------------+-------------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &result);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn information_schema_show_tables_no_information_schema() {
         let mut ctx = ExecutionContext::with_config(ExecutionConfig::new());
@@ -3644,6 +4205,8 @@ mod tests {
             "| table_catalog | table_schema       | table_name | table_type |",
             "+---------------+--------------------+------------+------------+",
             "| datafusion    | information_schema | columns    | VIEW       |",
+            "| datafusion    | information_schema | parameters | VIEW       |",
+            "| datafusion    | information_schema | routines   | VIEW       |",
             "| datafusion    | information_schema | tables     | VIEW       |",
             "| datafusion    | public             | t          | BASE TABLE |",
             "+---------------+--------------------+------------+------------+",
@@ -3805,7 +4368,140 @@ mod tests {
             .await
             .unwrap_err();
 
-        assert_eq!(err.to_string(), "This feature is not implemented: SHOW SOMETHING_UNKNOWN not implemented. Supported syntax: SHOW <TABLES>");
+        assert_eq!(err.to_string(), "This feature is not implemented: SHOW SOMETHING_UNKNOWN not implemented. Supported syntax: SHOW <TABLES | ALL | setting>");
+    }
+
+    #[tokio::test]
+    async fn set_and_show_variable() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        let other_ctx = ExecutionContext::new();
+
+        plan_and_collect(&mut ctx, "SET datafusion.execution.batch_size = 4096").await?;
+        plan_and_collect(&mut ctx, "SET datafusion.execution.time_zone TO '+02:00'")
+            .await?;
+        assert_eq!(ctx.state.lock().unwrap().config.batch_size, 4096);
+
+        let result =
+            plan_and_collect(&mut ctx, "SHOW datafusion.execution.time_zone").await?;
+        let expected = vec![
+            "+--------------------------------+---------+---------------------------------------------------------------------------------------------------+",
+            "| name                           | setting | description                                                                                       |",
+            "+--------------------------------+---------+---------------------------------------------------------------------------------------------------+",
+            "| datafusion.execution.time_zone | +02:00  | Session time zone, either an IANA name such as 'Europe/Berlin' or a fixed offset such as '+02:00' |",
+            "+--------------------------------+---------+---------------------------------------------------------------------------------------------------+",
+        ];
+        assert_batches_eq!(expected, &result);
+
+        // the settings only apply to the session they are made in
+        assert_eq!(other_ctx.state.lock().unwrap().config.batch_size, 8192);
+        assert_eq!(other_ctx.state.lock().unwrap().config.time_zone, "UTC");
+
+        let result = plan_and_collect(&mut ctx, "SHOW ALL").await?;
+        let rows = result.iter().map(|batch| batch.num_rows()).sum::<usize>();
+        assert_eq!(rows, ExecutionConfig::valid_entries().len());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn set_variable_invalid() {
+        let mut ctx = ExecutionContext::new();
+
+        let err = plan_and_collect(&mut ctx, "SET datafusion.execution.batch_size = 0")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Error during planning: Configuration setting 'datafusion.execution.batch_size' must be greater than zero");
+
+        let err = plan_and_collect(&mut ctx, "SET datafusion.execution.batch_size = foo")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Error during planning: Failed to parse value 'foo' for configuration setting 'datafusion.execution.batch_size': invalid digit found in string");
+        assert_eq!(ctx.state.lock().unwrap().config.batch_size, 8192);
+
+        let err = plan_and_collect(
+            &mut ctx,
+            "SET datafusion.catalog.information_schema = true",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Error during planning: Configuration setting 'datafusion.catalog.information_schema' is read-only");
+
+        let err = plan_and_collect(
+            &mut ctx,
+            "SET datafusion.optimizer.disabled_rules = 'no_such_rule'",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Error during planning: Unknown optimizer rule 'no_such_rule' for configuration setting 'datafusion.optimizer.disabled_rules'");
+    }
+
+    #[tokio::test]
+    async fn prepare_and_execute() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", test::table_with_sequence(1, 10)?)?;
+
+        plan_and_collect(&mut ctx, "PREPARE q(INT) AS SELECT i FROM t WHERE i = $1")
+            .await?;
+        plan_and_collect(
+            &mut ctx,
+            "PREPARE r AS SELECT i FROM t WHERE i BETWEEN ? AND ?",
+        )
+        .await?;
+
+        let result = plan_and_collect(&mut ctx, "EXECUTE q(3)").await?;
+        let expected = vec!["+---+", "| i |", "+---+", "| 3 |", "+---+"];
+        assert_batches_eq!(expected, &result);
+
+        let result = plan_and_collect(&mut ctx, "EXECUTE q(4 + 3)").await?;
+        let expected = vec!["+---+", "| i |", "+---+", "| 7 |", "+---+"];
+        assert_batches_eq!(expected, &result);
+
+        let result = plan_and_collect(&mut ctx, "EXECUTE r(2, 4)").await?;
+        let expected = vec![
+            "+---+", "| i |", "+---+", "| 2 |", "| 3 |", "| 4 |", "+---+",
+        ];
+        assert_batches_eq!(expected, &result);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prepare_and_execute_invalid() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("t", test::table_with_sequence(1, 10)?)?;
+        plan_and_collect(&mut ctx, "PREPARE q AS SELECT i FROM t WHERE i = $1").await?;
+
+        let err = plan_and_collect(&mut ctx, "EXECUTE p(1)")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Prepared statement 'p' does not exist"
+        );
+
+        let err = plan_and_collect(&mut ctx, "EXECUTE q").await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: No value found for placeholder $1"
+        );
+
+        let err = plan_and_collect(&mut ctx, "EXECUTE q(i)")
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Error during planning: Invalid identifier"));
+
+        // placeholders cannot be executed before they are bound
+        let err = plan_and_collect(&mut ctx, "SELECT i FROM t WHERE i = $1")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: Placeholder $1 must be bound to a value before the plan is executed"
+        );
+
+        Ok(())
     }
 
     #[tokio::test]
@@ -4154,6 +4850,66 @@ mod tests {
         }
     }
 
+    /// Optimizer rule that does not change the plans
+    struct NamedRule(&'static str);
+
+    impl OptimizerRule for NamedRule {
+        fn optimize(
+            &self,
+            plan: &LogicalPlan,
+            _: &ExecutionProps,
+        ) -> Result<LogicalPlan> {
+            Ok(plan.clone())
+        }
+
+        fn name(&self) -> &str {
+            self.0
+        }
+    }
+
+    impl PhysicalOptimizerRule for NamedRule {
+        fn optimize(
+            &self,
+            plan: Arc<dyn ExecutionPlan>,
+            _: &ExecutionConfig,
+        ) -> Result<Arc<dyn ExecutionPlan>> {
+            Ok(plan)
+        }
+
+        fn name(&self) -> &str {
+            self.0
+        }
+    }
+
+    /// Optimizer rule that decrements the limit at the root of the plans
+    #[derive(Default)]
+    struct DecrementLimit {
+        calls: AtomicUsize,
+    }
+
+    impl OptimizerRule for DecrementLimit {
+        fn optimize(
+            &self,
+            plan: &LogicalPlan,
+            _: &ExecutionProps,
+        ) -> Result<LogicalPlan> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match plan {
+                LogicalPlan::Limit(Limit { n, input }) if *n > 0 => {
+                    Ok(LogicalPlan::Limit(Limit {
+                        n: n - 1,
+                        input: input.clone(),
+                    }))
+                }
+                _ => Ok(plan.clone()),
+            }
+        }
+
+        fn name(&self) -> &str {
+            "decrement_limit"
+        }
+    }
+
     struct MyQueryPlanner {}
 
     #[async_trait]
@@ -26,6 +26,7 @@ use crate::logical_plan::{
     col, DFSchema, Expr, FunctionRegistry, JoinType, LogicalPlan, LogicalPlanBuilder,
     Partitioning,
 };
+use crate::scalar::ScalarValue;
 use crate::{
     dataframe::*,
     physical_plan::{collect, collect_partitioned},
@@ -249,6 +250,16 @@ impl DataFrame for DataFrameImpl {
             &LogicalPlanBuilder::except(left_plan, right_plan, true)?,
         )))
     }
+
+    fn with_param_values(
+        &self,
+        param_values: Vec<ScalarValue>,
+    ) -> Result<Arc<dyn DataFrame>> {
+        Ok(Arc::new(DataFrameImpl::new(
+            self.ctx_state.clone(),
+            &self.plan.with_param_values(param_values)?,
+        )))
+    }
 }
 
 #[cfg(test)]
@@ -489,6 +500,35 @@ mod tests {
         assert_eq!(format!("{:?}", plan1), format!("{:?}", plan2));
     }
 
+    #[tokio::test]
+    async fn with_param_values() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        register_aggregate_csv(&mut ctx, "aggregate_test_100").await?;
+        let df = ctx
+            .sql("SELECT c1, c2, c3 FROM aggregate_test_100 WHERE c2 = $1 AND c3 > $2")
+            .await?;
+
+        // the same plan can be bound to different values
+        for (c2, c3) in [(3, 100), (5, 0)] {
+            let bound = df.with_param_values(vec![
+                ScalarValue::Int64(Some(c2)),
+                ScalarValue::Int64(Some(c3)),
+            ])?;
+            let sql = format!(
+                "SELECT c1, c2, c3 FROM aggregate_test_100 WHERE c2 = {} AND c3 > {}",
+                c2, c3
+            );
+            let expected = ctx.sql(&sql).await?.collect().await?;
+            let actual = bound.collect().await?;
+            assert_eq!(
+                pretty::pretty_format_batches(&expected)?,
+                pretty::pretty_format_batches(&actual)?
+            );
+        }
+
+        Ok(())
+    }
+
     /// Create a logical plan from a SQL query
     async fn create_plan(sql: &str) -> Result<LogicalPlan> {
         let mut ctx = ExecutionContext::new();
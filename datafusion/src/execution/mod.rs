@@ -17,6 +17,9 @@
 
 //! DataFusion query execution
 
+pub mod config;
 pub mod context;
 pub mod dataframe_impl;
 pub mod options;
+#[cfg(feature = "plugins")]
+pub mod plugin;
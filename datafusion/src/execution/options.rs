@@ -109,6 +109,7 @@ impl<'a> CsvReadOptions<'a> {
             file_extension: self.file_extension.to_owned(),
             target_partitions,
             table_partition_cols: vec![],
+            file_sort_order: None,
         }
     }
 }
@@ -144,6 +145,7 @@ impl<'a> AvroReadOptions<'a> {
             file_extension: self.file_extension.to_owned(),
             target_partitions,
             table_partition_cols: vec![],
+            file_sort_order: None,
         }
     }
 }
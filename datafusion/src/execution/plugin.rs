@@ -0,0 +1,292 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Loading of user-defined functions from shared libraries.
+//!
+//! A plugin is a `cdylib` crate that depends on the same version of DataFusion,
+//! is built with the same Rust compiler, and declares its functions with
+//! [`declare_plugin!`](crate::declare_plugin):
+//!
+//! ```ignore
+//! use datafusion::execution::plugin::PluginRegistrar;
+//!
+//! fn register(registrar: &mut dyn PluginRegistrar) {
+//!     registrar.register_udf(my_udf());
+//!     registrar.register_udaf(my_udaf());
+//! }
+//!
+//! datafusion::declare_plugin!(register);
+//! ```
+//!
+//! The library can then be loaded with [`load_plugin`], e.g. through
+//! [`ExecutionContext::load_plugin`](crate::execution::context::ExecutionContext::load_plugin).
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use libloading::Library;
+
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::ExecutionContextState;
+use crate::physical_plan::udaf::AggregateUDF;
+use crate::physical_plan::udf::ScalarUDF;
+
+/// The version of DataFusion that plugins must have been built against
+pub const DATAFUSION_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The version of the Rust compiler that plugins must have been built with
+pub const RUSTC_VERSION: &str = env!("DATAFUSION_RUSTC_VERSION");
+
+/// The size of the versions in a [`PluginDeclaration`]
+pub const VERSION_LEN: usize = 64;
+
+/// The name of the symbol that [`declare_plugin!`](crate::declare_plugin) exports
+const PLUGIN_DECLARATION_SYMBOL: &[u8] = b"datafusion_plugin_declaration\0";
+
+/// Something that the functions of a plugin can be registered into
+pub trait PluginRegistrar {
+    /// Registers a scalar UDF
+    fn register_udf(&mut self, f: ScalarUDF);
+
+    /// Registers an aggregate UDF
+    fn register_udaf(&mut self, f: AggregateUDF);
+}
+
+/// The entry point of a plugin, exported by [`declare_plugin!`](crate::declare_plugin).
+///
+/// The versions are nul-padded byte arrays at the start of the declaration, so
+/// they can be read and checked before `register`, whose ABI depends on the
+/// compiler, is touched.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PluginDeclaration {
+    /// The version of the Rust compiler the plugin was built with
+    pub rustc_version: [u8; VERSION_LEN],
+    /// The version of DataFusion the plugin was built against
+    pub datafusion_version: [u8; VERSION_LEN],
+    /// Registers the functions of the plugin
+    pub register: fn(&mut dyn PluginRegistrar),
+}
+
+/// Converts `version` to the representation of a [`PluginDeclaration`],
+/// truncating it to [`VERSION_LEN`] bytes
+pub const fn version_bytes(version: &str) -> [u8; VERSION_LEN] {
+    let bytes = version.as_bytes();
+    let mut array = [0; VERSION_LEN];
+    let mut i = 0;
+    while i < bytes.len() && i < VERSION_LEN {
+        array[i] = bytes[i];
+        i += 1;
+    }
+    array
+}
+
+/// Converts a version of a [`PluginDeclaration`] back to a string
+fn version_str(version: &[u8; VERSION_LEN]) -> String {
+    let len = version.iter().position(|b| *b == 0).unwrap_or(VERSION_LEN);
+    String::from_utf8_lossy(&version[..len]).into_owned()
+}
+
+/// Exports the function that registers the functions of a plugin, so that
+/// [`load_plugin`](crate::execution::plugin::load_plugin) can find it.
+#[macro_export]
+macro_rules! declare_plugin {
+    ($register:expr) => {
+        #[doc(hidden)]
+        #[no_mangle]
+        #[allow(non_upper_case_globals)]
+        pub static datafusion_plugin_declaration:
+            $crate::execution::plugin::PluginDeclaration =
+            $crate::execution::plugin::PluginDeclaration {
+                rustc_version: $crate::execution::plugin::version_bytes(
+                    $crate::execution::plugin::RUSTC_VERSION,
+                ),
+                datafusion_version: $crate::execution::plugin::version_bytes(
+                    $crate::execution::plugin::DATAFUSION_VERSION,
+                ),
+                register: $register,
+            };
+    };
+}
+
+lazy_static::lazy_static! {
+    /// The functions of a plugin point into its library, which must therefore
+    /// stay loaded for as long as the process runs.
+    static ref LIBRARIES: Mutex<Vec<Library>> = Mutex::new(vec![]);
+}
+
+/// Loads the shared library at `path` and registers its functions into `registrar`
+pub fn load_plugin(
+    path: impl AsRef<Path>,
+    registrar: &mut dyn PluginRegistrar,
+) -> Result<()> {
+    let path = path.as_ref();
+    let plugin_error = |message: String| {
+        DataFusionError::Execution(format!(
+            "Could not load plugin {}: {}",
+            path.display(),
+            message
+        ))
+    };
+
+    // Safety: loading a library runs its initialisation code, and reading the
+    // declaration assumes that it was exported by `declare_plugin!`. Its
+    // versions lie at the start of its `repr(C)` layout, whichever compiler
+    // built it, and `register` is only read once they match ours.
+    let library =
+        unsafe { Library::new(path) }.map_err(|e| plugin_error(e.to_string()))?;
+    let declaration = unsafe {
+        *library
+            .get::<*const PluginDeclaration>(PLUGIN_DECLARATION_SYMBOL)
+            .map_err(|e| plugin_error(e.to_string()))?
+    };
+    let (rustc_version, datafusion_version) = unsafe {
+        (
+            (*declaration).rustc_version,
+            (*declaration).datafusion_version,
+        )
+    };
+    check_version(&rustc_version, &datafusion_version).map_err(plugin_error)?;
+    let register = unsafe { (*declaration).register };
+    register(registrar);
+
+    LIBRARIES.lock().unwrap().push(library);
+    Ok(())
+}
+
+/// Registers the functions of a plugin that is linked into this process, after
+/// checking that it was built against this version of DataFusion
+pub fn register_plugin(
+    declaration: PluginDeclaration,
+    registrar: &mut dyn PluginRegistrar,
+) -> Result<()> {
+    check_version(&declaration.rustc_version, &declaration.datafusion_version)
+        .map_err(DataFusionError::Execution)?;
+    (declaration.register)(registrar);
+    Ok(())
+}
+
+fn check_version(
+    rustc_version: &[u8; VERSION_LEN],
+    datafusion_version: &[u8; VERSION_LEN],
+) -> std::result::Result<(), String> {
+    if *rustc_version != version_bytes(RUSTC_VERSION) {
+        Err(format!(
+            "the plugin was built with {}, but this was built with {}",
+            version_str(rustc_version),
+            RUSTC_VERSION
+        ))
+    } else if *datafusion_version == version_bytes(DATAFUSION_VERSION) {
+        Ok(())
+    } else {
+        Err(format!(
+            "the plugin was built against DataFusion {}, but this is DataFusion {}",
+            version_str(datafusion_version),
+            DATAFUSION_VERSION
+        ))
+    }
+}
+
+impl PluginRegistrar for ExecutionContextState {
+    fn register_udf(&mut self, f: ScalarUDF) {
+        self.scalar_functions.insert(f.name.clone(), Arc::new(f));
+    }
+
+    fn register_udaf(&mut self, f: AggregateUDF) {
+        self.aggregate_functions.insert(f.name.clone(), Arc::new(f));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{create_udaf, create_udf};
+    use crate::physical_plan::expressions::MaxAccumulator;
+    use crate::physical_plan::functions::{make_scalar_function, Volatility};
+    use arrow::array::ArrayRef;
+    use arrow::datatypes::DataType;
+
+    fn register(registrar: &mut dyn PluginRegistrar) {
+        registrar.register_udf(create_udf(
+            "plugin_identity",
+            vec![DataType::Int64],
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            make_scalar_function(|args: &[ArrayRef]| Ok(args[0].clone())),
+        ));
+        registrar.register_udaf(create_udaf(
+            "plugin_max",
+            DataType::Int64,
+            Arc::new(DataType::Int64),
+            Volatility::Immutable,
+            Arc::new(|| Ok(Box::new(MaxAccumulator::try_new(&DataType::Int64)?))),
+            Arc::new(vec![DataType::Int64]),
+        ));
+    }
+
+    crate::declare_plugin!(register);
+
+    #[test]
+    fn register_declared_plugin() -> Result<()> {
+        let mut state = ExecutionContextState::new();
+        register_plugin(datafusion_plugin_declaration, &mut state)?;
+
+        assert!(state.scalar_functions.contains_key("plugin_identity"));
+        assert!(state.aggregate_functions.contains_key("plugin_max"));
+        Ok(())
+    }
+
+    #[test]
+    fn reject_plugin_of_other_version() {
+        let mut state = ExecutionContextState::new();
+        let declaration = PluginDeclaration {
+            datafusion_version: version_bytes("0.0.0"),
+            ..datafusion_plugin_declaration
+        };
+        let err = register_plugin(declaration, &mut state).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("the plugin was built against DataFusion 0.0.0"));
+        assert!(state.scalar_functions.is_empty());
+    }
+
+    #[test]
+    fn reject_plugin_of_other_compiler() {
+        let mut state = ExecutionContextState::new();
+        let declaration = PluginDeclaration {
+            rustc_version: version_bytes("rustc 0.0.0"),
+            ..datafusion_plugin_declaration
+        };
+        let err = register_plugin(declaration, &mut state).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("the plugin was built with rustc 0.0.0"));
+        assert!(state.scalar_functions.is_empty());
+    }
+
+    #[test]
+    fn load_missing_plugin() {
+        let mut state = ExecutionContextState::new();
+        let err = load_plugin("/does/not/exist.so", &mut state).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("Could not load plugin /does/not/exist.so"));
+    }
+}
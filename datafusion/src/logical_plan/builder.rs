@@ -27,7 +27,7 @@ use crate::datasource::{
 use crate::error::{DataFusionError, Result};
 use crate::logical_plan::plan::{
     Aggregate, Analyze, EmptyRelation, Explain, Filter, Join, Projection, Sort,
-    TableScan, ToStringifiedPlan, Union, Window,
+    TableScan, ToStringifiedPlan, Union, Unnest, Window,
 };
 use crate::optimizer::utils;
 use crate::prelude::*;
@@ -283,6 +283,7 @@ impl LogicalPlanBuilder {
             file_extension: DEFAULT_PARQUET_EXTENSION.to_owned(),
             target_partitions,
             table_partition_cols: vec![],
+            file_sort_order: None,
         };
 
         let path: String = path.into();
@@ -737,6 +738,40 @@ impl LogicalPlanBuilder {
         })))
     }
 
+    /// Apply an unnest: repeat every input row once per element of the list
+    /// `expr`, appending the element as a new column named `output`
+    pub fn unnest(
+        &self,
+        expr: impl Into<Expr>,
+        output: impl Into<Column>,
+    ) -> Result<Self> {
+        let expr = normalize_col(expr.into(), &self.plan)?;
+        let output = output.into();
+        let element_type = match expr.get_type(self.plan.schema())? {
+            DataType::List(field)
+            | DataType::LargeList(field)
+            | DataType::FixedSizeList(field, _) => field.data_type().clone(),
+            other => {
+                return Err(DataFusionError::Plan(format!(
+                    "UNNEST expects a list argument, got {:?} of type {:?}",
+                    expr, other
+                )))
+            }
+        };
+        let mut fields = self.plan.schema().fields().clone();
+        fields.push(DFField::new(
+            output.relation.as_deref(),
+            &output.name,
+            element_type,
+            true,
+        ));
+        Ok(Self::from(LogicalPlan::Unnest(Unnest {
+            input: Arc::new(self.plan.clone()),
+            expr,
+            schema: Arc::new(DFSchema::new(fields)?),
+        })))
+    }
+
     /// Repartition
     pub fn repartition(&self, partitioning_scheme: Partitioning) -> Result<Self> {
         Ok(Self::from(LogicalPlan::Repartition(Repartition {
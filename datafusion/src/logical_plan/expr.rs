@@ -24,7 +24,9 @@ use crate::field_util::get_indexed_field;
 use crate::logical_plan::{
     plan::Aggregate, window_frames, DFField, DFSchema, LogicalPlan,
 };
+use crate::physical_plan::async_udf::AsyncScalarFunction;
 use crate::physical_plan::functions::Volatility;
+use crate::physical_plan::udwf::{PartitionEvaluatorFactory, WindowUDF};
 use crate::physical_plan::{
     aggregates, expressions::binary_operator_data_type, functions, udf::ScalarUDF,
     window_functions,
@@ -231,6 +233,14 @@ pub enum Expr {
     ScalarVariable(Vec<String>),
     /// A constant value.
     Literal(ScalarValue),
+    /// A parameter of a prepared statement, such as `$1`, that is replaced by
+    /// a value before the plan is executed.
+    Placeholder {
+        /// The name of the parameter, e.g. `$1`
+        id: String,
+        /// The type of the parameter, if it was declared or could be inferred
+        data_type: Option<DataType>,
+    },
     /// A binary expression such as "age > 21"
     BinaryExpr {
         /// Left-hand side of the expression
@@ -338,6 +348,12 @@ pub enum Expr {
         args: Vec<Expr>,
         /// Whether this is a DISTINCT aggregation or not
         distinct: bool,
+        /// Condition that the rows must satisfy to be aggregated, i.e. the
+        /// `FILTER (WHERE ...)` clause
+        filter: Option<Box<Expr>>,
+        /// Sort expressions that define the order in which the rows are
+        /// aggregated, i.e. the `ORDER BY` clause within the call
+        order_by: Vec<Expr>,
     },
     /// Represents the call of a window function with arguments.
     WindowFunction {
@@ -358,6 +374,12 @@ pub enum Expr {
         fun: Arc<AggregateUDF>,
         /// List of expressions to feed to the functions as arguments
         args: Vec<Expr>,
+        /// Whether this is a DISTINCT aggregation or not
+        distinct: bool,
+        /// Condition that the rows must satisfy to be aggregated
+        filter: Option<Box<Expr>>,
+        /// Sort expressions that define the order in which the rows are aggregated
+        order_by: Vec<Expr>,
     },
     /// Returns whether the list contains the expr value.
     InList {
@@ -386,6 +408,12 @@ impl Expr {
             Expr::Column(c) => Ok(schema.field_from_column(c)?.data_type().clone()),
             Expr::ScalarVariable(_) => Ok(DataType::Utf8),
             Expr::Literal(l) => Ok(l.get_datatype()),
+            Expr::Placeholder { id, data_type } => data_type.clone().ok_or_else(|| {
+                DataFusionError::Plan(format!(
+                    "The type of placeholder {} could not be inferred, it must be cast or declared in PREPARE",
+                    id
+                ))
+            }),
             Expr::Case { when_then_expr, .. } => when_then_expr[0].1.get_type(schema),
             Expr::Cast { data_type, .. } => Ok(data_type.clone()),
             Expr::TryCast { data_type, .. } => Ok(data_type.clone()),
@@ -462,7 +490,7 @@ impl Expr {
             Expr::Alias(expr, _) => expr.nullable(input_schema),
             Expr::Column(c) => Ok(input_schema.field_from_column(c)?.is_nullable()),
             Expr::Literal(value) => Ok(value.is_null()),
-            Expr::ScalarVariable(_) => Ok(true),
+            Expr::ScalarVariable(_) | Expr::Placeholder { .. } => Ok(true),
             Expr::Case {
                 when_then_expr,
                 else_expr,
@@ -709,6 +737,7 @@ impl Expr {
             Expr::Column(_) => Ok(visitor),
             Expr::ScalarVariable(..) => Ok(visitor),
             Expr::Literal(..) => Ok(visitor),
+            Expr::Placeholder { .. } => Ok(visitor),
             Expr::BinaryExpr { left, right, .. } => {
                 let visitor = left.accept(visitor)?;
                 right.accept(visitor)
@@ -773,12 +802,25 @@ impl Expr {
                     .try_fold(visitor, |visitor, arg| arg.accept(visitor))?;
                 Ok(visitor)
             }
-            Expr::AggregateFunction { args, .. } => args
-                .iter()
-                .try_fold(visitor, |visitor, arg| arg.accept(visitor)),
-            Expr::AggregateUDF { args, .. } => args
-                .iter()
-                .try_fold(visitor, |visitor, arg| arg.accept(visitor)),
+            Expr::AggregateFunction {
+                args,
+                filter,
+                order_by,
+                ..
+            }
+            | Expr::AggregateUDF {
+                args,
+                filter,
+                order_by,
+                ..
+            } => {
+                let visitor = args
+                    .iter()
+                    .chain(filter.as_deref())
+                    .chain(order_by)
+                    .try_fold(visitor, |visitor, arg| arg.accept(visitor))?;
+                Ok(visitor)
+            }
             Expr::InList { expr, list, .. } => {
                 let visitor = expr.accept(visitor)?;
                 list.iter()
@@ -841,6 +883,7 @@ impl Expr {
             Expr::Column(_) => self.clone(),
             Expr::ScalarVariable(names) => Expr::ScalarVariable(names),
             Expr::Literal(value) => Expr::Literal(value),
+            Expr::Placeholder { id, data_type } => Expr::Placeholder { id, data_type },
             Expr::BinaryExpr { left, op, right } => Expr::BinaryExpr {
                 left: rewrite_boxed(left, rewriter)?,
                 op,
@@ -927,14 +970,27 @@ impl Expr {
                 args,
                 fun,
                 distinct,
+                filter,
+                order_by,
             } => Expr::AggregateFunction {
                 args: rewrite_vec(args, rewriter)?,
                 fun,
                 distinct,
+                filter: rewrite_option_box(filter, rewriter)?,
+                order_by: rewrite_vec(order_by, rewriter)?,
             },
-            Expr::AggregateUDF { args, fun } => Expr::AggregateUDF {
+            Expr::AggregateUDF {
+                args,
+                fun,
+                distinct,
+                filter,
+                order_by,
+            } => Expr::AggregateUDF {
                 args: rewrite_vec(args, rewriter)?,
                 fun,
+                distinct,
+                filter: rewrite_option_box(filter, rewriter)?,
+                order_by: rewrite_vec(order_by, rewriter)?,
             },
             Expr::InList {
                 expr,
@@ -984,7 +1040,19 @@ impl std::fmt::Display for Expr {
                 ref args,
                 /// Whether this is a DISTINCT aggregation or not
                 ref distinct,
-            } => fmt_function(f, &fun.to_string(), *distinct, args, true),
+                /// The FILTER clause
+                ref filter,
+                /// The ORDER BY clause
+                ref order_by,
+            } => fmt_aggregate_function(
+                f,
+                &fun.to_string(),
+                *distinct,
+                args,
+                filter,
+                order_by,
+                true,
+            ),
             Expr::ScalarFunction {
                 /// Name of the function
                 ref fun,
@@ -1439,6 +1507,8 @@ pub fn min(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Min,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1448,6 +1518,8 @@ pub fn max(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Max,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1457,6 +1529,8 @@ pub fn sum(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Sum,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1466,6 +1540,8 @@ pub fn avg(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Avg,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1475,6 +1551,8 @@ pub fn count(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Count,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1484,6 +1562,8 @@ pub fn count_distinct(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Count,
         distinct: true,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1632,6 +1712,8 @@ pub fn approx_distinct(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::ApproxDistinct,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1736,7 +1818,19 @@ scalar_expr!(Upper, upper, string);
 scalar_expr!(DatePart, date_part, part, date);
 scalar_expr!(DateTrunc, date_trunc, part, date);
 
-/// returns an array of fixed size with each argument on it.
+// array functions
+scalar_expr!(ArrayAppend, array_append, list, element);
+nary_scalar_expr!(ArrayConcat, array_concat);
+scalar_expr!(ArrayContains, array_contains, list, element);
+scalar_expr!(ArrayDistinct, array_distinct, list);
+nary_scalar_expr!(ArrayJoin, array_join);
+scalar_expr!(ArrayLength, array_length, list);
+scalar_expr!(ArrayPosition, array_position, list, element);
+scalar_expr!(ArraySlice, array_slice, list, from, to);
+scalar_expr!(ArraySort, array_sort, list);
+scalar_expr!(Cardinality, cardinality, list);
+
+/// returns a list with each argument on it.
 pub fn array(args: Vec<Expr>) -> Expr {
     Expr::ScalarFunction {
         fun: functions::BuiltinScalarFunction::Array,
@@ -1765,6 +1859,25 @@ pub fn create_udf(
     )
 }
 
+/// Creates a new async UDF with a specific signature and specific return type.
+/// Like [`create_udf`], the UDF has a fixed return type and a fixed signature.
+/// Its calls are evaluated in batches of rows, see [`AsyncScalarFunction`].
+pub fn create_async_udf(
+    name: &str,
+    input_types: Vec<DataType>,
+    return_type: Arc<DataType>,
+    volatility: Volatility,
+    fun: AsyncScalarFunction,
+) -> ScalarUDF {
+    let return_type: ReturnTypeFunction = Arc::new(move |_| Ok(return_type.clone()));
+    ScalarUDF::new_async(
+        name,
+        &Signature::exact(input_types, volatility),
+        &return_type,
+        fun,
+    )
+}
+
 /// Creates a new UDAF with a specific signature, state type and return type.
 /// The signature and state type must match the `Accumulator's implementation`.
 #[allow(clippy::rc_buffer)]
@@ -1787,6 +1900,25 @@ pub fn create_udaf(
     )
 }
 
+/// Creates a new UDWF with a specific signature and return type.
+/// The signature and return type must match the `PartitionEvaluator's implementation`.
+#[allow(clippy::rc_buffer)]
+pub fn create_udwf(
+    name: &str,
+    input_types: Vec<DataType>,
+    return_type: Arc<DataType>,
+    volatility: Volatility,
+    partition_evaluator: PartitionEvaluatorFactory,
+) -> WindowUDF {
+    let return_type: ReturnTypeFunction = Arc::new(move |_| Ok(return_type.clone()));
+    WindowUDF::new(
+        name,
+        &Signature::exact(input_types, volatility),
+        &return_type,
+        &partition_evaluator,
+    )
+}
+
 fn fmt_function(
     f: &mut fmt::Formatter,
     fun: &str,
@@ -1807,6 +1939,34 @@ fn fmt_function(
     write!(f, "{}({}{})", fun, distinct_str, args.join(", "))
 }
 
+fn fmt_aggregate_function(
+    f: &mut fmt::Formatter,
+    fun: &str,
+    distinct: bool,
+    args: &[Expr],
+    filter: &Option<Box<Expr>>,
+    order_by: &[Expr],
+    display: bool,
+) -> fmt::Result {
+    let args: Vec<String> = match display {
+        true => args.iter().map(|arg| format!("{}", arg)).collect(),
+        false => args.iter().map(|arg| format!("{:?}", arg)).collect(),
+    };
+    let distinct_str = match distinct {
+        true => "DISTINCT ",
+        false => "",
+    };
+    write!(f, "{}({}{}", fun, distinct_str, args.join(", "))?;
+    if !order_by.is_empty() {
+        write!(f, " ORDER BY {:?}", order_by)?;
+    }
+    write!(f, ")")?;
+    if let Some(filter) = filter {
+        write!(f, " FILTER (WHERE {:?})", filter)?;
+    }
+    Ok(())
+}
+
 impl fmt::Debug for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -1814,6 +1974,7 @@ impl fmt::Debug for Expr {
             Expr::Column(c) => write!(f, "{}", c),
             Expr::ScalarVariable(var_names) => write!(f, "{}", var_names.join(".")),
             Expr::Literal(v) => write!(f, "{:?}", v),
+            Expr::Placeholder { id, .. } => write!(f, "{}", id),
             Expr::Case {
                 expr,
                 when_then_expr,
@@ -1896,11 +2057,26 @@ impl fmt::Debug for Expr {
                 fun,
                 distinct,
                 ref args,
-                ..
-            } => fmt_function(f, &fun.to_string(), *distinct, args, true),
-            Expr::AggregateUDF { fun, ref args, .. } => {
-                fmt_function(f, &fun.name, false, args, false)
-            }
+                filter,
+                order_by,
+            } => fmt_aggregate_function(
+                f,
+                &fun.to_string(),
+                *distinct,
+                args,
+                filter,
+                order_by,
+                true,
+            ),
+            Expr::AggregateUDF {
+                fun,
+                ref args,
+                distinct,
+                filter,
+                order_by,
+            } => fmt_aggregate_function(
+                f, &fun.name, *distinct, args, filter, order_by, false,
+            ),
             Expr::Between {
                 expr,
                 negated,
@@ -1949,6 +2125,26 @@ fn create_function_name(
     Ok(format!("{}({}{})", fun, distinct_str, names.join(",")))
 }
 
+fn create_aggregate_name(
+    fun: &str,
+    distinct: bool,
+    args: &[Expr],
+    filter: &Option<Box<Expr>>,
+    order_by: &[Expr],
+    input_schema: &DFSchema,
+) -> Result<String> {
+    let mut name = create_function_name(fun, distinct, args, input_schema)?;
+    if !order_by.is_empty() {
+        // move the closing parenthesis behind the ORDER BY clause
+        name.pop();
+        name += &format!(" ORDER BY {:?})", order_by);
+    }
+    if let Some(filter) = filter {
+        name += &format!(" FILTER (WHERE {})", create_name(filter, input_schema)?);
+    }
+    Ok(name)
+}
+
 /// Returns a readable name of an expression based on the input schema.
 /// This function recursively transverses the expression for names such as "CAST(a > 2)".
 fn create_name(e: &Expr, input_schema: &DFSchema) -> Result<String> {
@@ -1957,6 +2153,7 @@ fn create_name(e: &Expr, input_schema: &DFSchema) -> Result<String> {
         Expr::Column(c) => Ok(c.flat_name()),
         Expr::ScalarVariable(variable_names) => Ok(variable_names.join(".")),
         Expr::Literal(value) => Ok(format!("{:?}", value)),
+        Expr::Placeholder { id, .. } => Ok(id.clone()),
         Expr::BinaryExpr { left, op, right } => {
             let left = create_name(left, input_schema)?;
             let right = create_name(right, input_schema)?;
@@ -2046,15 +2243,30 @@ fn create_name(e: &Expr, input_schema: &DFSchema) -> Result<String> {
             fun,
             distinct,
             args,
-            ..
-        } => create_function_name(&fun.to_string(), *distinct, args, input_schema),
-        Expr::AggregateUDF { fun, args } => {
-            let mut names = Vec::with_capacity(args.len());
-            for e in args {
-                names.push(create_name(e, input_schema)?);
-            }
-            Ok(format!("{}({})", fun.name, names.join(",")))
-        }
+            filter,
+            order_by,
+        } => create_aggregate_name(
+            &fun.to_string(),
+            *distinct,
+            args,
+            filter,
+            order_by,
+            input_schema,
+        ),
+        Expr::AggregateUDF {
+            fun,
+            args,
+            distinct,
+            filter,
+            order_by,
+        } => create_aggregate_name(
+            &fun.name,
+            *distinct,
+            args,
+            filter,
+            order_by,
+            input_schema,
+        ),
         Expr::InList {
             expr,
             list,
@@ -2430,6 +2642,18 @@ mod tests {
 
         test_scalar_expr!(DatePart, date_part, part, date);
         test_scalar_expr!(DateTrunc, date_trunc, part, date);
+
+        test_scalar_expr!(ArrayAppend, array_append, list, element);
+        test_nary_scalar_expr!(ArrayConcat, array_concat, list1, list2);
+        test_scalar_expr!(ArrayContains, array_contains, list, element);
+        test_scalar_expr!(ArrayDistinct, array_distinct, list);
+        test_nary_scalar_expr!(ArrayJoin, array_join, list, delimiter);
+        test_nary_scalar_expr!(ArrayJoin, array_join, list, delimiter, null_string);
+        test_scalar_expr!(ArrayLength, array_length, list);
+        test_scalar_expr!(ArrayPosition, array_position, list, element);
+        test_scalar_expr!(ArraySlice, array_slice, list, from, to);
+        test_scalar_expr!(ArraySort, array_sort, list);
+        test_scalar_expr!(Cardinality, cardinality, list);
     }
 
     #[test]
@@ -18,15 +18,17 @@
 //! via a logical query plan.
 
 use super::display::{GraphvizVisitor, IndentVisitor};
-use super::expr::{Column, Expr};
+use super::expr::{Column, Expr, ExprRewriter, ExpressionVisitor, Recursion};
 use super::extension::UserDefinedLogicalNode;
 use crate::datasource::TableProvider;
 use crate::error::DataFusionError;
 use crate::logical_plan::dfschema::DFSchemaRef;
+use crate::optimizer::utils;
+use crate::scalar::ScalarValue;
 use crate::sql::parser::FileType;
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt::{self, Display},
     sync::Arc,
 };
@@ -155,6 +157,18 @@ pub struct CreateMemoryTable {
     pub input: Arc<LogicalPlan>,
 }
 
+/// Prepares a statement with placeholders such as `$1`, which is executed
+/// with `EXECUTE name(value, ...)`.
+#[derive(Clone)]
+pub struct Prepare {
+    /// The name of the prepared statement
+    pub name: String,
+    /// The declared types of the placeholders `$1`, `$2`, ...
+    pub data_types: Vec<DataType>,
+    /// The logical plan of the statement
+    pub input: Arc<LogicalPlan>,
+}
+
 /// Creates an external table.
 #[derive(Clone)]
 pub struct CreateExternalTable {
@@ -181,6 +195,18 @@ pub struct DropTable {
     pub schema: DFSchemaRef,
 }
 
+/// Changes a configuration setting of the session, see
+/// [`ExecutionConfig::set_session_setting`](crate::execution::context::ExecutionConfig::set_session_setting).
+#[derive(Clone)]
+pub struct SetVariable {
+    /// The name of the setting
+    pub variable: String,
+    /// The new value of the setting
+    pub value: String,
+    /// Dummy schema
+    pub schema: DFSchemaRef,
+}
+
 /// Produces a relation with string representations of
 /// various parts of the plan
 #[derive(Clone)]
@@ -266,6 +292,20 @@ pub struct Sort {
     pub input: Arc<LogicalPlan>,
 }
 
+/// Unnests (explodes) a list expression: every row of the input is repeated
+/// once per element of the list, with the element appended as the last column.
+/// Rows whose list is null or empty produce no output.
+#[derive(Clone)]
+pub struct Unnest {
+    /// The incoming logical plan
+    pub input: Arc<LogicalPlan>,
+    /// The expression to unnest, of type `List`, `LargeList` or `FixedSizeList`
+    pub expr: Expr,
+    /// The schema description of the output: the input fields followed by
+    /// the unnested field
+    pub schema: DFSchemaRef,
+}
+
 /// Join two logical plans on one or more join columns
 #[derive(Clone)]
 pub struct Join {
@@ -321,6 +361,8 @@ pub enum LogicalPlan {
     Repartition(Repartition),
     /// Union multiple inputs
     Union(Union),
+    /// Unnests a list expression, producing one row per list element
+    Unnest(Unnest),
     /// Produces rows from a table provider by reference or from the context
     TableScan(TableScan),
     /// Produces no rows: An empty relation with an empty schema
@@ -333,6 +375,10 @@ pub enum LogicalPlan {
     CreateMemoryTable(CreateMemoryTable),
     /// Drops a table.
     DropTable(DropTable),
+    /// Changes a configuration setting.
+    SetVariable(SetVariable),
+    /// Prepares a statement with placeholders.
+    Prepare(Prepare),
     /// Values expression. See
     /// [Postgres VALUES](https://www.postgresql.org/docs/current/queries-values.html)
     /// documentation for more details.
@@ -372,10 +418,11 @@ impl LogicalPlan {
             LogicalPlan::Analyze(analyze) => &analyze.schema,
             LogicalPlan::Extension(extension) => extension.node.schema(),
             LogicalPlan::Union(Union { schema, .. }) => schema,
-            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. }) => {
-                input.schema()
-            }
+            LogicalPlan::Unnest(Unnest { schema, .. }) => schema,
+            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
+            | LogicalPlan::Prepare(Prepare { input, .. }) => input.schema(),
             LogicalPlan::DropTable(DropTable { schema, .. }) => schema,
+            LogicalPlan::SetVariable(SetVariable { schema, .. }) => schema,
         }
     }
 
@@ -387,6 +434,7 @@ impl LogicalPlan {
             }) => vec![projected_schema],
             LogicalPlan::Values(Values { schema, .. }) => vec![schema],
             LogicalPlan::Window(Window { input, schema, .. })
+            | LogicalPlan::Unnest(Unnest { input, schema, .. })
             | LogicalPlan::Projection(Projection { input, schema, .. })
             | LogicalPlan::Aggregate(Aggregate { input, schema, .. }) => {
                 let mut schemas = input.all_schemas();
@@ -423,8 +471,9 @@ impl LogicalPlan {
             | LogicalPlan::Repartition(Repartition { input, .. })
             | LogicalPlan::Sort(Sort { input, .. })
             | LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
+            | LogicalPlan::Prepare(Prepare { input, .. })
             | LogicalPlan::Filter(Filter { input, .. }) => input.all_schemas(),
-            LogicalPlan::DropTable(_) => vec![],
+            LogicalPlan::DropTable(_) | LogicalPlan::SetVariable(_) => vec![],
         }
     }
 
@@ -464,6 +513,7 @@ impl LogicalPlan {
                 .flat_map(|(l, r)| vec![Expr::Column(l.clone()), Expr::Column(r.clone())])
                 .collect(),
             LogicalPlan::Sort(Sort { expr, .. }) => expr.clone(),
+            LogicalPlan::Unnest(Unnest { expr, .. }) => vec![expr.clone()],
             LogicalPlan::Extension(extension) => extension.node.expressions(),
             // plans without expressions
             LogicalPlan::TableScan { .. }
@@ -471,7 +521,9 @@ impl LogicalPlan {
             | LogicalPlan::Limit(_)
             | LogicalPlan::CreateExternalTable(_)
             | LogicalPlan::CreateMemoryTable(_)
+            | LogicalPlan::Prepare(_)
             | LogicalPlan::DropTable(_)
+            | LogicalPlan::SetVariable(_)
             | LogicalPlan::CrossJoin(_)
             | LogicalPlan::Analyze { .. }
             | LogicalPlan::Explain { .. }
@@ -494,19 +546,20 @@ impl LogicalPlan {
             LogicalPlan::Join(Join { left, right, .. }) => vec![left, right],
             LogicalPlan::CrossJoin(CrossJoin { left, right, .. }) => vec![left, right],
             LogicalPlan::Limit(Limit { input, .. }) => vec![input],
+            LogicalPlan::Unnest(Unnest { input, .. }) => vec![input],
             LogicalPlan::Extension(extension) => extension.node.inputs(),
             LogicalPlan::Union(Union { inputs, .. }) => inputs.iter().collect(),
             LogicalPlan::Explain(explain) => vec![&explain.plan],
             LogicalPlan::Analyze(analyze) => vec![&analyze.input],
-            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. }) => {
-                vec![input]
-            }
+            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
+            | LogicalPlan::Prepare(Prepare { input, .. }) => vec![input],
             // plans without inputs
             LogicalPlan::TableScan { .. }
             | LogicalPlan::EmptyRelation { .. }
             | LogicalPlan::Values { .. }
             | LogicalPlan::CreateExternalTable(_)
-            | LogicalPlan::DropTable(_) => vec![],
+            | LogicalPlan::DropTable(_)
+            | LogicalPlan::SetVariable(_) => vec![],
         }
     }
 
@@ -544,6 +597,143 @@ impl LogicalPlan {
         self.accept(&mut visitor)?;
         Ok(visitor.using_columns)
     }
+
+    /// Returns the declared or inferred types of the placeholders of the plan,
+    /// such as `$1`, with `None` for those whose type is unknown
+    pub fn get_parameter_types(
+        &self,
+    ) -> Result<HashMap<String, Option<DataType>>, DataFusionError> {
+        let mut types = HashMap::new();
+        self.collect_parameter_types(&mut types)?;
+        Ok(types)
+    }
+
+    fn collect_parameter_types(
+        &self,
+        types: &mut HashMap<String, Option<DataType>>,
+    ) -> Result<(), DataFusionError> {
+        let mut exprs = self.expressions();
+        if let LogicalPlan::TableScan(TableScan { filters, .. }) = self {
+            exprs.extend(filters.iter().cloned());
+        }
+        for expr in exprs {
+            expr.accept(PlaceholderTypeVisitor { types: &mut *types })?;
+        }
+        self.inputs()
+            .into_iter()
+            .try_for_each(|input| input.collect_parameter_types(types))
+    }
+
+    /// Replaces the placeholders `$1`, `$2`, ... of the plan by the values
+    /// `param_values[0]`, `param_values[1]`, ..., cast to the types of the
+    /// placeholders. This allows to plan a statement once and to execute it
+    /// with different values.
+    pub fn with_param_values(
+        &self,
+        param_values: Vec<ScalarValue>,
+    ) -> Result<LogicalPlan, DataFusionError> {
+        self.bind_placeholders(&param_values)
+    }
+
+    fn bind_placeholders(
+        &self,
+        param_values: &[ScalarValue],
+    ) -> Result<LogicalPlan, DataFusionError> {
+        let mut binder = PlaceholderBinder { param_values };
+        match self {
+            // cannot be rebuilt by `from_plan`
+            LogicalPlan::Explain(explain) => Ok(LogicalPlan::Explain(Explain {
+                plan: Arc::new(explain.plan.bind_placeholders(param_values)?),
+                ..explain.clone()
+            })),
+            // the filters pushed down into the scan are not among its expressions
+            LogicalPlan::TableScan(scan) => Ok(LogicalPlan::TableScan(TableScan {
+                filters: scan
+                    .filters
+                    .iter()
+                    .map(|expr| expr.clone().rewrite(&mut binder))
+                    .collect::<Result<Vec<_>, _>>()?,
+                ..scan.clone()
+            })),
+            plan => {
+                let inputs = plan
+                    .inputs()
+                    .into_iter()
+                    .map(|input| input.bind_placeholders(param_values))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let exprs = plan
+                    .expressions()
+                    .into_iter()
+                    .map(|expr| expr.rewrite(&mut binder))
+                    .collect::<Result<Vec<_>, _>>()?;
+                utils::from_plan(plan, &exprs, &inputs)
+            }
+        }
+    }
+}
+
+/// Returns the position of the value of the placeholder `$n` in the list of
+/// parameter values, i.e. `n - 1`
+pub(crate) fn placeholder_index(id: &str) -> Result<usize, DataFusionError> {
+    id.strip_prefix('$')
+        .and_then(|n| n.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .map(|n| n - 1)
+        .ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "Invalid placeholder {}, expected one of $1, $2, ...",
+                id
+            ))
+        })
+}
+
+struct PlaceholderTypeVisitor<'a> {
+    types: &'a mut HashMap<String, Option<DataType>>,
+}
+
+impl ExpressionVisitor for PlaceholderTypeVisitor<'_> {
+    fn pre_visit(self, expr: &Expr) -> Result<Recursion<Self>, DataFusionError> {
+        if let Expr::Placeholder { id, data_type } = expr {
+            let entry = self.types.entry(id.clone()).or_insert(None);
+            if entry.is_none() {
+                *entry = data_type.clone();
+            }
+        }
+        Ok(Recursion::Continue(self))
+    }
+}
+
+struct PlaceholderBinder<'a> {
+    param_values: &'a [ScalarValue],
+}
+
+impl ExprRewriter for PlaceholderBinder<'_> {
+    fn mutate(&mut self, expr: Expr) -> Result<Expr, DataFusionError> {
+        match expr {
+            Expr::Placeholder { id, data_type } => {
+                let value =
+                    self.param_values
+                        .get(placeholder_index(&id)?)
+                        .ok_or_else(|| {
+                            DataFusionError::Plan(format!(
+                                "No value found for placeholder {}",
+                                id
+                            ))
+                        })?;
+                let literal = Expr::Literal(value.clone());
+                match data_type {
+                    Some(data_type) if data_type != value.get_datatype() => {
+                        Ok(Expr::Cast {
+                            expr: Box::new(literal),
+                            data_type,
+                        })
+                    }
+                    _ => Ok(literal),
+                }
+            }
+            expr => Ok(expr),
+        }
+    }
 }
 
 /// Logical partitioning schemes supported by the repartition operator.
@@ -641,9 +831,9 @@ impl LogicalPlan {
                 true
             }
             LogicalPlan::Limit(Limit { input, .. }) => input.accept(visitor)?,
-            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. }) => {
-                input.accept(visitor)?
-            }
+            LogicalPlan::Unnest(Unnest { input, .. }) => input.accept(visitor)?,
+            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
+            | LogicalPlan::Prepare(Prepare { input, .. }) => input.accept(visitor)?,
             LogicalPlan::Extension(extension) => {
                 for input in extension.node.inputs() {
                     if !input.accept(visitor)? {
@@ -659,7 +849,8 @@ impl LogicalPlan {
             | LogicalPlan::EmptyRelation(_)
             | LogicalPlan::Values(_)
             | LogicalPlan::CreateExternalTable(_)
-            | LogicalPlan::DropTable(_) => true,
+            | LogicalPlan::DropTable(_)
+            | LogicalPlan::SetVariable(_) => true,
         };
         if !recurse {
             return Ok(false);
@@ -971,6 +1162,12 @@ impl LogicalPlan {
                         }
                     },
                     LogicalPlan::Limit(Limit { ref n, .. }) => write!(f, "Limit: {}", n),
+                    LogicalPlan::Unnest(Unnest {
+                        ref expr, schema, ..
+                    }) => {
+                        let field = schema.field(schema.fields().len() - 1);
+                        write!(f, "Unnest: {:?} AS {}", expr, field.qualified_name())
+                    }
                     LogicalPlan::CreateExternalTable(CreateExternalTable {
                         ref name,
                         ..
@@ -985,6 +1182,16 @@ impl LogicalPlan {
                     LogicalPlan::DropTable(DropTable { name, if_exist, .. }) => {
                         write!(f, "DropTable: {:?} if not exist:={}", name, if_exist)
                     }
+                    LogicalPlan::SetVariable(SetVariable {
+                        variable, value, ..
+                    }) => {
+                        write!(f, "SetVariable: {} = {:?}", variable, value)
+                    }
+                    LogicalPlan::Prepare(Prepare {
+                        name, data_types, ..
+                    }) => {
+                        write!(f, "Prepare: {:?} {:?}", name, data_types)
+                    }
                     LogicalPlan::Explain { .. } => write!(f, "Explain"),
                     LogicalPlan::Analyze { .. } => write!(f, "Analyze"),
                     LogicalPlan::Union(_) => write!(f, "Union"),
@@ -0,0 +1,353 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule to pre-aggregate the input of a join by its join keys
+//! before the join
+use std::collections::HashSet;
+
+use crate::error::Result;
+use crate::execution::context::ExecutionProps;
+use crate::logical_plan::plan::Aggregate;
+use crate::logical_plan::{Column, Expr, JoinType, LogicalPlan, LogicalPlanBuilder};
+use crate::optimizer::optimizer::OptimizerRule;
+use crate::physical_plan::aggregates::AggregateFunction;
+
+use super::utils;
+
+/// Optimization rule that pre-aggregates an input of a join by its join
+/// keys when all the aggregates above the join only use the columns of
+/// that input, e.g.
+///
+/// ```text
+/// Aggregate: groupBy=[[#dim.name]], aggr=[[SUM(#f.x)]]
+///   Join: #f.k = #dim.k
+///     TableScan: f
+///     TableScan: dim
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// Aggregate: groupBy=[[#dim.name]], aggr=[[SUM(#SUM(f.x)) AS SUM(f.x)]]
+///   Join: #f.k = #dim.k
+///     Aggregate: groupBy=[[#f.k]], aggr=[[SUM(#f.x)]]
+///       TableScan: f
+///     TableScan: dim
+/// ```
+///
+/// Only `SUM`, `COUNT`, `MIN` and `MAX` without `DISTINCT` are pushed down,
+/// as their results can be merged with another aggregate: the counts are
+/// summed up. The rows of the pre-aggregated input must all be returned by
+/// the join with nulls when they have no match, i.e. it is an input of an
+/// inner join or the left input of a left join, and the aggregate above the
+/// join must have grouping columns, and only the join keys of that input.
+///
+/// Whether it pays off depends on the number of rows per join key, so this
+/// rule is not enabled by default, see
+/// [`ExecutionConfig::with_aggregate_push_down`](crate::execution::context::ExecutionConfig::with_aggregate_push_down).
+pub struct AggregatePushDown;
+
+impl AggregatePushDown {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for AggregatePushDown {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        execution_props: &ExecutionProps,
+    ) -> Result<LogicalPlan> {
+        let inputs = plan
+            .inputs()
+            .into_iter()
+            .map(|input| self.optimize(input, execution_props))
+            .collect::<Result<Vec<_>>>()?;
+        let plan = utils::from_plan(plan, &plan.expressions(), &inputs)?;
+
+        match &plan {
+            LogicalPlan::Aggregate(aggregate) => {
+                Ok(push_down(aggregate)?.unwrap_or(plan))
+            }
+            _ => Ok(plan),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "aggregate_push_down"
+    }
+}
+
+/// Returns `aggregate` with an input of its join pre-aggregated, if the
+/// aggregates can be pushed below the join
+fn push_down(aggregate: &Aggregate) -> Result<Option<LogicalPlan>> {
+    let join = match aggregate.input.as_ref() {
+        LogicalPlan::Join(join) => join,
+        _ => return Ok(None),
+    };
+    // without any group, an empty join returns a row with a count of 0
+    if aggregate.group_expr.is_empty() {
+        return Ok(None);
+    }
+    let group_columns = match aggregate
+        .group_expr
+        .iter()
+        .map(|expr| match expr {
+            Expr::Column(column) => Some(column),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+    {
+        Some(group_columns) => group_columns,
+        None => return Ok(None),
+    };
+    if !aggregate
+        .aggr_expr
+        .iter()
+        .all(|expr| merge_function(expr).is_some())
+    {
+        return Ok(None);
+    }
+    let mut aggr_columns = HashSet::new();
+    for expr in &aggregate.aggr_expr {
+        utils::expr_to_columns(expr, &mut aggr_columns)?;
+    }
+
+    // the rows of the pre-aggregated input without a match must be returned
+    let sides = [
+        (
+            matches!(join.join_type, JoinType::Inner | JoinType::Left),
+            join.left.as_ref(),
+        ),
+        (
+            matches!(join.join_type, JoinType::Inner | JoinType::Right),
+            join.right.as_ref(),
+        ),
+    ];
+    for (i, (preserved, input)) in sides.into_iter().enumerate() {
+        let mut keys: Vec<Column> = vec![];
+        for (left, right) in &join.on {
+            let key = if i == 0 { left } else { right };
+            if !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+        let is_input_column =
+            |column: &Column| input.schema().field_from_column(column).is_ok();
+        if !preserved
+            || !aggr_columns.iter().all(is_input_column)
+            || !group_columns
+                .iter()
+                .all(|column| !is_input_column(column) || keys.contains(column))
+        {
+            continue;
+        }
+
+        let pre_aggregate = LogicalPlanBuilder::from(input.clone())
+            .aggregate(
+                keys.iter().cloned().map(Expr::Column),
+                aggregate.aggr_expr.clone(),
+            )?
+            .build()?;
+        // the pre-aggregate may be pushed further down its own join
+        let pre_aggregate = match &pre_aggregate {
+            LogicalPlan::Aggregate(pre_aggregate) => push_down(pre_aggregate)?,
+            _ => None,
+        }
+        .unwrap_or(pre_aggregate);
+
+        let mut inputs = vec![join.left.as_ref().clone(), join.right.as_ref().clone()];
+        inputs[i] = pre_aggregate;
+        let join_plan = aggregate.input.as_ref();
+        let join_plan = utils::from_plan(join_plan, &join_plan.expressions(), &inputs)?;
+
+        // merge the pre-aggregated values, keeping the names of the aggregates
+        let group_len = aggregate.group_expr.len();
+        let pre_schema = inputs[i].schema();
+        let aggr_expr = aggregate
+            .aggr_expr
+            .iter()
+            .enumerate()
+            .map(|(j, expr)| {
+                let field = pre_schema.field(keys.len() + j);
+                Expr::AggregateFunction {
+                    fun: merge_function(expr).unwrap(),
+                    args: vec![Expr::Column(field.qualified_column())],
+                    distinct: false,
+                    filter: None,
+                    order_by: vec![],
+                }
+                .alias(aggregate.schema.field(group_len + j).name())
+            })
+            .collect::<Vec<_>>();
+        let plan = LogicalPlanBuilder::from(join_plan)
+            .aggregate(aggregate.group_expr.clone(), aggr_expr)?
+            .build()?;
+
+        // e.g. the sum of decimals is wider than its argument
+        let same_types = plan
+            .schema()
+            .fields()
+            .iter()
+            .zip(aggregate.schema.fields())
+            .all(|(new, old)| new.data_type() == old.data_type());
+        return Ok(if same_types { Some(plan) } else { None });
+    }
+    Ok(None)
+}
+
+/// Returns the function that merges the results of the aggregate `expr`
+/// computed on subsets of its input
+fn merge_function(expr: &Expr) -> Option<AggregateFunction> {
+    match expr {
+        Expr::AggregateFunction {
+            fun,
+            distinct: false,
+            ..
+        } => match fun {
+            AggregateFunction::Sum | AggregateFunction::Count => {
+                Some(AggregateFunction::Sum)
+            }
+            AggregateFunction::Min => Some(AggregateFunction::Min),
+            AggregateFunction::Max => Some(AggregateFunction::Max),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{col, count, lit, max, sum};
+    use crate::test::*;
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let rule = AggregatePushDown::new();
+        let optimized_plan = rule
+            .optimize(plan, &ExecutionProps::new())
+            .expect("failed to optimize plan");
+        let formatted_plan = format!("{:?}", optimized_plan);
+        assert_eq!(formatted_plan, expected);
+        assert_eq!(plan.schema(), optimized_plan.schema());
+    }
+
+    #[test]
+    fn pre_aggregate_by_join_keys() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(&t2, JoinType::Inner, (vec!["t1.a"], vec!["t2.a"]))?
+            .aggregate(
+                vec![col("t2.b")],
+                vec![sum(col("t1.c")), count(col("t1.b")), max(col("t1.a"))],
+            )?
+            .build()?;
+
+        let expected = "Aggregate: groupBy=[[#t2.b]], aggr=[[SUM(#SUM(t1.c)) AS SUM(t1.c), SUM(#COUNT(t1.b)) AS COUNT(t1.b), MAX(#MAX(t1.a)) AS MAX(t1.a)]]\
+        \n  Join: #t1.a = #t2.a\
+        \n    Aggregate: groupBy=[[#t1.a]], aggr=[[SUM(#t1.c), COUNT(#t1.b), MAX(#t1.a)]]\
+        \n      TableScan: t1 projection=None\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn pre_aggregate_through_join_chain() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let t3 = test_table_scan_with_name("t3")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(&t2, JoinType::Inner, (vec!["t1.a"], vec!["t2.a"]))?
+            .join(&t3, JoinType::Inner, (vec!["t2.b"], vec!["t3.b"]))?
+            .aggregate(vec![col("t3.c")], vec![sum(col("t1.c"))])?
+            .build()?;
+
+        let expected =
+            "Aggregate: groupBy=[[#t3.c]], aggr=[[SUM(#SUM(t1.c)) AS SUM(t1.c)]]\
+        \n  Join: #t2.b = #t3.b\
+        \n    Aggregate: groupBy=[[#t2.b]], aggr=[[SUM(#SUM(t1.c)) AS SUM(t1.c)]]\
+        \n      Join: #t1.a = #t2.a\
+        \n        Aggregate: groupBy=[[#t1.a]], aggr=[[SUM(#t1.c)]]\
+        \n          TableScan: t1 projection=None\
+        \n        TableScan: t2 projection=None\
+        \n    TableScan: t3 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn pre_aggregate_preserved_side_of_outer_join() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(&t2, JoinType::Left, (vec!["t1.a"], vec!["t2.a"]))?
+            .aggregate(vec![col("t2.b")], vec![count(lit(1u8))])?
+            .build()?;
+
+        let expected = "Aggregate: groupBy=[[#t2.b]], aggr=[[SUM(#COUNT(UInt8(1))) AS COUNT(UInt8(1))]]\
+        \n  Join: #t1.a = #t2.a\
+        \n    Aggregate: groupBy=[[#t1.a]], aggr=[[COUNT(UInt8(1))]]\
+        \n      TableScan: t1 projection=None\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+
+        // the counts of the rows of t2 without a match would be null
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(&t2, JoinType::Left, (vec!["t1.a"], vec!["t2.a"]))?
+            .aggregate(vec![col("t1.b")], vec![count(col("t2.c"))])?
+            .build()?;
+        assert_optimized_plan_eq(&plan, &format!("{:?}", plan));
+        Ok(())
+    }
+
+    #[test]
+    fn do_not_pre_aggregate() -> Result<()> {
+        let join = || -> Result<LogicalPlanBuilder> {
+            let t1 = test_table_scan_with_name("t1")?;
+            let t2 = test_table_scan_with_name("t2")?;
+            LogicalPlanBuilder::from(t1).join(
+                &t2,
+                JoinType::Inner,
+                (vec!["t1.a"], vec!["t2.a"]),
+            )
+        };
+        let plans = vec![
+            // grouped by a column of t1 that is not a join key
+            join()?
+                .aggregate(vec![col("t1.b")], vec![sum(col("t1.c"))])?
+                .build()?,
+            // aggregates of both inputs
+            join()?
+                .aggregate(vec![col("t2.b")], vec![sum(col("t1.c")), sum(col("t2.c"))])?
+                .build()?,
+            // no groups
+            join()?
+                .aggregate(Vec::<Expr>::new(), vec![count(col("t1.c"))])?
+                .build()?,
+        ];
+        for plan in plans {
+            assert_optimized_plan_eq(&plan, &format!("{:?}", plan));
+        }
+        Ok(())
+    }
+}
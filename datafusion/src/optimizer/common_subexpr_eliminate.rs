@@ -205,11 +205,14 @@ fn optimize(plan: &LogicalPlan, execution_props: &ExecutionProps) -> Result<Logi
         | LogicalPlan::Values(_)
         | LogicalPlan::EmptyRelation(_)
         | LogicalPlan::Limit(_)
+        | LogicalPlan::Unnest(_)
         | LogicalPlan::CreateExternalTable(_)
         | LogicalPlan::Explain { .. }
         | LogicalPlan::Analyze { .. }
         | LogicalPlan::CreateMemoryTable(_)
+        | LogicalPlan::Prepare(_)
         | LogicalPlan::DropTable(_)
+        | LogicalPlan::SetVariable(_)
         | LogicalPlan::Extension { .. } => {
             // apply the optimization to all inputs of the plan
             let expr = plan.expressions();
@@ -379,6 +382,10 @@ impl ExprIdentifierVisitor<'_> {
                 desc.push_str("Literal");
                 desc.push_str(&value.to_string());
             }
+            Expr::Placeholder { id, .. } => {
+                desc.push_str("Placeholder-");
+                desc.push_str(id);
+            }
             Expr::BinaryExpr { op, .. } => {
                 desc.push_str("BinaryExpr-");
                 desc.push_str(&op.to_string());
@@ -436,9 +443,10 @@ impl ExprIdentifierVisitor<'_> {
                 desc.push_str(&fun.to_string());
                 desc.push_str(&distinct.to_string());
             }
-            Expr::AggregateUDF { fun, .. } => {
+            Expr::AggregateUDF { fun, distinct, .. } => {
                 desc.push_str("AggregateUDF-");
                 desc.push_str(&fun.name);
+                desc.push_str(&distinct.to_string());
             }
             Expr::InList { negated, .. } => {
                 desc.push_str("InList-");
@@ -494,6 +502,7 @@ impl ExpressionVisitor for ExprIdentifierVisitor<'_> {
         if matches!(
             expr,
             Expr::Literal(..)
+                | Expr::Placeholder { .. }
                 | Expr::Column(..)
                 | Expr::ScalarVariable(..)
                 | Expr::Alias(..)
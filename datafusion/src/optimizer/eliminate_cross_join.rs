@@ -0,0 +1,407 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule to replace the cross joins under a filter with equality
+//! predicates between their inputs by inner joins.
+use crate::error::Result;
+use crate::execution::context::ExecutionProps;
+use crate::logical_plan::plan::Filter;
+use crate::logical_plan::{
+    combine_filters, or, Column, CrossJoin, DFSchema, Expr, JoinType, LogicalPlan,
+    LogicalPlanBuilder, Operator,
+};
+use crate::optimizer::optimizer::OptimizerRule;
+
+use super::utils;
+
+/// Optimization rule that rewrites `Filter(CrossJoin(..))` into inner joins
+/// on the `<column> = <column>` conjuncts of the filter predicate, e.g.
+///
+/// ```text
+/// Filter: #a.x = #b.y AND #b.z > 1
+///   CrossJoin:
+///     TableScan: a
+///     TableScan: b
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// Filter: #b.z > 1
+///   Join: #a.x = #b.y
+///     TableScan: a
+///     TableScan: b
+/// ```
+///
+/// The conjuncts common to all the branches of a disjunction are also
+/// used, as `(A AND B) OR (A AND C)` is `A AND (B OR C)`.
+///
+/// Nested cross joins are flattened and their inputs are joined from left to
+/// right, picking the first input connected by a predicate to the inputs
+/// already joined, so that cross joins are only used when no predicate
+/// connects the remaining inputs.
+pub struct EliminateCrossJoin;
+
+impl EliminateCrossJoin {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for EliminateCrossJoin {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        execution_props: &ExecutionProps,
+    ) -> Result<LogicalPlan> {
+        match plan {
+            LogicalPlan::Filter(Filter { predicate, input })
+                if matches!(input.as_ref(), LogicalPlan::CrossJoin(_)) =>
+            {
+                let mut inputs = vec![];
+                flatten_cross_join(input, &mut inputs);
+                let inputs = inputs
+                    .into_iter()
+                    .map(|input| self.optimize(input, execution_props))
+                    .collect::<Result<Vec<_>>>()?;
+
+                let mut predicates = vec![];
+                split_conjunction(predicate, &mut predicates);
+                let possible_join_keys: Vec<(Column, Column)> =
+                    predicates.iter().filter_map(as_column_equality).collect();
+
+                let (join, join_keys) = join_inputs(inputs, &possible_join_keys)?;
+
+                // restore the order of the columns of the cross joins
+                let join = if join.schema().fields() != input.schema().fields() {
+                    LogicalPlanBuilder::from(join)
+                        .project(
+                            input
+                                .schema()
+                                .fields()
+                                .iter()
+                                .map(|field| Expr::Column(field.qualified_column())),
+                        )?
+                        .build()?
+                } else {
+                    join
+                };
+
+                let predicates: Vec<Expr> = predicates
+                    .into_iter()
+                    .filter(|predicate| match as_column_equality(predicate) {
+                        Some((l, r)) => {
+                            !join_keys.contains(&(l.clone(), r.clone()))
+                                && !join_keys.contains(&(r, l))
+                        }
+                        None => true,
+                    })
+                    .collect();
+                match combine_filters(&predicates) {
+                    Some(predicate) => {
+                        LogicalPlanBuilder::from(join).filter(predicate)?.build()
+                    }
+                    None => Ok(join),
+                }
+            }
+            _ => utils::optimize_children(self, plan, execution_props),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "eliminate_cross_join"
+    }
+}
+
+/// Collects the inputs of the tree of cross joins `plan`
+fn flatten_cross_join<'a>(plan: &'a LogicalPlan, inputs: &mut Vec<&'a LogicalPlan>) {
+    match plan {
+        LogicalPlan::CrossJoin(CrossJoin { left, right, .. }) => {
+            flatten_cross_join(left, inputs);
+            flatten_cross_join(right, inputs);
+        }
+        other => inputs.push(other),
+    }
+}
+
+/// Joins `inputs` on the `possible_join_keys` between them, and returns the
+/// join keys that were used
+fn join_inputs(
+    mut inputs: Vec<LogicalPlan>,
+    possible_join_keys: &[(Column, Column)],
+) -> Result<(LogicalPlan, Vec<(Column, Column)>)> {
+    let mut all_join_keys = vec![];
+    let mut left = inputs.remove(0);
+    while !inputs.is_empty() {
+        let (position, join_keys) = inputs
+            .iter()
+            .map(|right| {
+                find_join_keys(left.schema(), right.schema(), possible_join_keys)
+            })
+            .enumerate()
+            .find(|(_, join_keys)| !join_keys.is_empty())
+            .unwrap_or_default();
+        let right = inputs.remove(position);
+        let builder = LogicalPlanBuilder::from(left);
+        left = if join_keys.is_empty() {
+            builder.cross_join(&right)?.build()?
+        } else {
+            let (left_keys, right_keys): (Vec<Column>, Vec<Column>) =
+                join_keys.iter().cloned().unzip();
+            builder
+                .join(&right, JoinType::Inner, (left_keys, right_keys))?
+                .build()?
+        };
+        all_join_keys.extend(join_keys);
+    }
+    Ok((left, all_join_keys))
+}
+
+/// Returns the `possible_join_keys` between the columns of `left` and
+/// `right`, as pairs of (left, right) columns
+fn find_join_keys(
+    left: &DFSchema,
+    right: &DFSchema,
+    possible_join_keys: &[(Column, Column)],
+) -> Vec<(Column, Column)> {
+    let mut join_keys = vec![];
+    for (l, r) in possible_join_keys {
+        let key = if left.field_from_column(l).is_ok()
+            && right.field_from_column(r).is_ok()
+        {
+            (l.clone(), r.clone())
+        } else if left.field_from_column(r).is_ok() && right.field_from_column(l).is_ok()
+        {
+            (r.clone(), l.clone())
+        } else {
+            continue;
+        };
+        if !join_keys.contains(&key) {
+            join_keys.push(key);
+        }
+    }
+    join_keys
+}
+
+fn as_column_equality(predicate: &Expr) -> Option<(Column, Column)> {
+    match predicate {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::Eq,
+            right,
+        } => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(l), Expr::Column(r)) => Some((l.clone(), r.clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Converts `A AND B AND C` => [A, B, C], and factors the conjuncts common
+/// to all the branches of the disjunctions out of them:
+/// `(A AND B) OR (A AND C)` => [A, B OR C]
+fn split_conjunction(predicate: &Expr, predicates: &mut Vec<Expr>) {
+    match predicate {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        } => {
+            split_conjunction(left, predicates);
+            split_conjunction(right, predicates);
+        }
+        Expr::BinaryExpr {
+            op: Operator::Or, ..
+        } => {
+            let mut disjuncts = vec![];
+            split_disjunction(predicate, &mut disjuncts);
+            let branches: Vec<Vec<Expr>> = disjuncts
+                .into_iter()
+                .map(|disjunct| {
+                    let mut conjuncts = vec![];
+                    split_conjunction(disjunct, &mut conjuncts);
+                    conjuncts
+                })
+                .collect();
+            let mut common: Vec<Expr> = vec![];
+            for conjunct in &branches[0] {
+                if branches[1..].iter().all(|branch| branch.contains(conjunct))
+                    && !common.contains(conjunct)
+                {
+                    common.push(conjunct.clone());
+                }
+            }
+            if common.is_empty() {
+                predicates.push(predicate.clone());
+                return;
+            }
+
+            // `A OR (A AND B)` is `A`
+            let rest = branches
+                .into_iter()
+                .map(|branch| {
+                    let branch: Vec<Expr> = branch
+                        .into_iter()
+                        .filter(|conjunct| !common.contains(conjunct))
+                        .collect();
+                    combine_filters(&branch)
+                })
+                .collect::<Option<Vec<_>>>();
+            predicates.extend(common);
+            if let Some(rest) = rest {
+                predicates.extend(rest.into_iter().reduce(or));
+            }
+        }
+        other => predicates.push(other.clone()),
+    }
+}
+
+/// Converts `A OR B OR C` => [A, B, C]
+fn split_disjunction<'a>(predicate: &'a Expr, predicates: &mut Vec<&'a Expr>) {
+    match predicate {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        } => {
+            split_disjunction(left, predicates);
+            split_disjunction(right, predicates);
+        }
+        other => predicates.push(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{col, lit};
+    use crate::test::*;
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let rule = EliminateCrossJoin::new();
+        let optimized_plan = rule
+            .optimize(plan, &ExecutionProps::new())
+            .expect("failed to optimize plan");
+        let formatted_plan = format!("{:?}", optimized_plan);
+        assert_eq!(formatted_plan, expected);
+        assert_eq!(plan.schema(), optimized_plan.schema());
+    }
+
+    #[test]
+    fn eliminate_cross_join() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .cross_join(&t2)?
+            .filter(
+                col("t1.a")
+                    .eq(col("t2.a"))
+                    .and(col("t2.c").lt(lit(20u32)))
+                    .and(col("t1.b").gt(col("t2.b"))),
+            )?
+            .build()?;
+
+        let expected = "Filter: #t2.c < UInt32(20) AND #t1.b > #t2.b\
+        \n  Join: #t1.a = #t2.a\
+        \n    TableScan: t1 projection=None\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn eliminate_cross_join_with_or() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .cross_join(&t2)?
+            .filter(
+                col("t1.a")
+                    .eq(col("t2.a"))
+                    .and(col("t2.c").lt(lit(20u32)))
+                    .or(col("t2.a").eq(col("t1.a")).and(col("t2.c").eq(lit(10u32)))),
+            )?
+            .build()?;
+
+        // `#t2.a = #t1.a` is not recognized as the same predicate
+        let expected = "Filter: #t1.a = #t2.a AND #t2.c < UInt32(20) OR #t2.a = #t1.a AND #t2.c = UInt32(10)\
+        \n  CrossJoin:\
+        \n    TableScan: t1 projection=None\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .cross_join(&t2)?
+            .filter(
+                col("t1.a")
+                    .eq(col("t2.a"))
+                    .and(col("t2.c").lt(lit(20u32)))
+                    .or(col("t1.a").eq(col("t2.a")).and(col("t2.c").eq(lit(10u32)))),
+            )?
+            .build()?;
+
+        let expected = "Filter: #t2.c < UInt32(20) OR #t2.c = UInt32(10)\
+        \n  Join: #t1.a = #t2.a\
+        \n    TableScan: t1 projection=None\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn eliminate_nested_cross_joins() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let t3 = test_table_scan_with_name("t3")?;
+        // t2 is only connected to t3
+        let plan = LogicalPlanBuilder::from(t1)
+            .cross_join(&t2)?
+            .cross_join(&t3)?
+            .filter(col("t1.a").eq(col("t3.a")).and(col("t3.b").eq(col("t2.b"))))?
+            .build()?;
+
+        let expected =
+            "Projection: #t1.a, #t1.b, #t1.c, #t2.a, #t2.b, #t2.c, #t3.a, #t3.b, #t3.c\
+        \n  Join: #t3.b = #t2.b\
+        \n    Join: #t1.a = #t3.a\
+        \n      TableScan: t1 projection=None\
+        \n      TableScan: t3 projection=None\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn keep_cross_join_without_join_keys() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .cross_join(&t2)?
+            .filter(col("t1.a").lt(col("t2.a")))?
+            .build()?;
+
+        let expected = "Filter: #t1.a < #t2.a\
+        \n  CrossJoin:\
+        \n    TableScan: t1 projection=None\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+}
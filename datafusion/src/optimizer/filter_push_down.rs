@@ -18,7 +18,7 @@ use crate::datasource::datasource::TableProviderFilterPushDown;
 use crate::execution::context::ExecutionProps;
 use crate::logical_plan::plan::{Aggregate, Filter, Join, Projection};
 use crate::logical_plan::{
-    and, replace_col, Column, CrossJoin, Limit, LogicalPlan, TableScan,
+    and, replace_col, Column, CrossJoin, Limit, LogicalPlan, TableScan, Unnest,
 };
 use crate::logical_plan::{DFSchema, Expr};
 use crate::optimizer::optimizer::OptimizerRule;
@@ -305,8 +305,8 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
                     utils::expr_to_columns(predicate, &mut columns)?;
                     if columns.is_empty() {
                         no_col_predicates.push(predicate)
-                    } else {
-                        // collect the predicate
+                    } else if !state.filters.iter().any(|(p, _)| p == predicate) {
+                        // collect the predicate, unless it is already collected
                         state.filters.push((predicate.clone(), columns));
                     }
                     Ok(())
@@ -395,6 +395,12 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
                 .collect::<HashSet<_>>();
             issue_filters(state, used_columns, plan)
         }
+        LogicalPlan::Unnest(Unnest { schema, .. }) => {
+            // unnest is filter-commutable for all but the unnested column
+            let unnested = schema.field(schema.fields().len() - 1);
+            let used_columns = HashSet::from([unnested.qualified_column()]);
+            issue_filters(state, used_columns, plan)
+        }
         LogicalPlan::CrossJoin(CrossJoin { left, right, .. }) => {
             optimize_join(state, plan, left, right)
         }
@@ -454,7 +460,11 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
                     Some(Ok((join_side_predicate, join_side_columns)))
                 })
                 .collect::<Result<Vec<_>>>()?;
-            state.filters.extend(join_side_filters);
+            for (predicate, columns) in join_side_filters {
+                if !state.filters.iter().any(|(p, _)| *p == predicate) {
+                    state.filters.push((predicate, columns));
+                }
+            }
 
             optimize_join(state, plan, left, right)
         }
@@ -485,7 +495,7 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
                     // Don't add expression again if it's already present in
                     // pushed down filters.
                     if new_filters.contains(filter_expr) {
-                        break;
+                        continue;
                     }
                     new_filters.push(filter_expr.clone());
                 }
@@ -1138,6 +1148,62 @@ mod tests {
         Ok(())
     }
 
+    /// a predicate already collected from a filter higher up the plan is not
+    /// pushed down twice
+    #[test]
+    fn filter_duplicate_predicate() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .filter(col("a").eq(lit(1i64)))?
+            .project(vec![col("a"), col("b")])?
+            .filter(col("a").eq(lit(1i64)))?
+            .build()?;
+
+        let expected = "\
+        Projection: #test.a, #test.b\
+        \n  Filter: #test.a = Int64(1)\
+        \n    TableScan: test projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    /// a predicate on a join key that is already present on the other side of
+    /// the join is not pushed down twice
+    #[test]
+    fn filter_join_on_both_keys() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let left = LogicalPlanBuilder::from(table_scan)
+            .project(vec![col("a"), col("b")])?
+            .build()?;
+        let table_scan_right = test_table_scan_with_name("test2")?;
+        let right = LogicalPlanBuilder::from(table_scan_right)
+            .project(vec![col("a"), col("c")])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(left)
+            .join(
+                &right,
+                JoinType::Inner,
+                (vec![Column::from_name("a")], vec![Column::from_name("a")]),
+            )?
+            .filter(
+                col("test.a")
+                    .lt_eq(lit(1i64))
+                    .and(col("test2.a").lt_eq(lit(1i64))),
+            )?
+            .build()?;
+
+        let expected = "\
+        Join: #test.a = #test2.a\
+        \n  Projection: #test.a, #test.b\
+        \n    Filter: #test.a <= Int64(1)\
+        \n      TableScan: test projection=None\
+        \n  Projection: #test2.a, #test2.c\
+        \n    Filter: #test2.a <= Int64(1)\
+        \n      TableScan: test2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
     struct PushDownProvider {
         pub filter_support: TableProviderFilterPushDown,
     }
@@ -1247,4 +1313,21 @@ mod tests {
         assert_optimized_plan_eq(&plan, expected);
         Ok(())
     }
+
+    #[test]
+    fn filter_with_table_provider_pushed_and_new_filters() -> Result<()> {
+        let plan = table_scan_with_pushdown_provider(TableProviderFilterPushDown::Exact)?;
+        let optimised_plan = optimize_plan(&plan);
+
+        // the filter already pushed to the provider does not stop the new one
+        // after it from being pushed
+        let plan = LogicalPlanBuilder::from(optimised_plan)
+            .filter(col("a").eq(lit(1i64)).and(col("a").lt(lit(10i64))))?
+            .build()?;
+
+        let expected = "\
+        TableScan: test projection=None, filters=[#a = Int64(1), #a < Int64(10)]";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
 }
@@ -0,0 +1,687 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule to infer predicates on the columns that are equal to each
+//! other through join keys and filters.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::ExecutionProps;
+use crate::logical_plan::plan::{Filter, Join, Projection};
+use crate::logical_plan::{
+    combine_filters, replace_col, Column, CrossJoin, DFField, Expr, JoinType,
+    LogicalPlan, Operator,
+};
+use crate::optimizer::optimizer::OptimizerRule;
+
+use super::utils;
+
+/// Optimization rule that builds the equivalence classes of the columns
+/// equal to each other through the keys of inner joins and the
+/// `<column> = <column>` conjuncts of filters, and adds the predicates known
+/// on a member of a class to the inputs producing the other members, e.g.
+///
+/// ```text
+/// Filter: #a.x < Int64(10)
+///   Join: #b.y = #c.z
+///     Join: #a.x = #b.y
+///       TableScan: a
+///       TableScan: b
+///     TableScan: c
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// Filter: #a.x < Int64(10)
+///   Join: #b.y = #c.z
+///     Join: #a.x = #b.y
+///       TableScan: a
+///       Filter: #b.y < Int64(10)
+///         TableScan: b
+///     Filter: #c.z < Int64(10)
+///       TableScan: c
+/// ```
+///
+/// The propagated predicates compare a column to literals (including `IN`
+/// lists and `BETWEEN`). As the members of a class are never null in the
+/// output, `IS NOT NULL` is added on the nullable members without any other
+/// predicate.
+///
+/// Through outer, semi and anti joins, the predicates are only added to the
+/// input whose rows without a match are not returned, e.g. to the right
+/// input of a left join.
+///
+/// The inferred predicates are then pushed to the scans by `FilterPushDown`.
+pub struct InferPredicates;
+
+impl InferPredicates {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for InferPredicates {
+    fn optimize(&self, plan: &LogicalPlan, _: &ExecutionProps) -> Result<LogicalPlan> {
+        Ok(infer(plan)?.0)
+    }
+
+    fn name(&self) -> &str {
+        "infer_predicates"
+    }
+}
+
+/// The predicates on a single column that are true for the rows of a plan
+/// where the column is not null, with the column they are on
+type Facts = Vec<(Column, Expr)>;
+
+/// Returns `plan` with the inferred predicates added to the inputs of its
+/// joins, and the predicates known to hold on its output
+fn infer(plan: &LogicalPlan) -> Result<(LogicalPlan, Facts)> {
+    match plan {
+        LogicalPlan::Filter(_)
+        | LogicalPlan::CrossJoin(_)
+        | LogicalPlan::Join(Join {
+            join_type: JoinType::Inner,
+            ..
+        }) => infer_inner(plan),
+        LogicalPlan::Join(join) => infer_join(plan, join),
+        LogicalPlan::Projection(Projection {
+            expr,
+            input,
+            schema,
+            ..
+        }) => {
+            let (input, input_facts) = infer(input)?;
+            // the predicates on the projected columns hold on their aliases
+            let mut facts = vec![];
+            for (expr, field) in expr.iter().zip(schema.fields()) {
+                let column = match expr {
+                    Expr::Alias(expr, _) => as_column(expr),
+                    expr => as_column(expr),
+                };
+                if let Some(column) = column {
+                    let alias = field.qualified_column();
+                    for predicate in facts_on(&input_facts, column) {
+                        let predicate = replace_column(predicate, column, &alias)?;
+                        facts.push((alias.clone(), predicate));
+                    }
+                }
+            }
+            let plan = utils::from_plan(plan, &plan.expressions(), &[input])?;
+            Ok((plan, facts))
+        }
+        _ if plan.inputs().is_empty() => Ok((plan.clone(), vec![])),
+        _ => {
+            let inputs = plan
+                .inputs()
+                .into_iter()
+                .map(|input| Ok(infer(input)?.0))
+                .collect::<Result<Vec<_>>>()?;
+            let plan = utils::from_plan(plan, &plan.expressions(), &inputs)?;
+            Ok((plan, vec![]))
+        }
+    }
+}
+
+/// Infers the predicates of the tree of filters, cross joins and inner joins
+/// `plan`: they all apply to every output row, wherever they are in the tree
+fn infer_inner(plan: &LogicalPlan) -> Result<(LogicalPlan, Facts)> {
+    let mut leaves = vec![];
+    let mut predicates = vec![];
+    let mut equalities = vec![];
+    collect_inner(plan, &mut leaves, &mut predicates, &mut equalities);
+
+    let (leaves, leaf_facts): (Vec<_>, Vec<_>) = leaves
+        .into_iter()
+        .map(infer)
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+
+    let mut facts: Facts = predicates
+        .iter()
+        .filter_map(|predicate| {
+            let column = as_column_predicate(predicate)?;
+            Some((column.clone(), predicate.clone()))
+        })
+        .collect();
+    for (column, predicate) in leaf_facts.into_iter().flatten() {
+        if !facts.contains(&(column.clone(), predicate.clone())) {
+            facts.push((column, predicate));
+        }
+    }
+
+    // the members of a class must have the same type for a predicate on
+    // one of them to apply to the others
+    let find_field = |column: &Column| {
+        leaves.iter().enumerate().find_map(|(i, leaf)| {
+            let field = leaf.schema().field_from_column(column).ok()?;
+            Some((i, field))
+        })
+    };
+    let equalities: Vec<(Column, Column)> = equalities
+        .into_iter()
+        .filter(
+            |(left, right)| match (find_field(left), find_field(right)) {
+                (Some((_, left)), Some((_, right))) => {
+                    left.data_type() == right.data_type()
+                }
+                _ => false,
+            },
+        )
+        .collect();
+
+    let mut leaf_predicates = vec![vec![]; leaves.len()];
+    let mut new_facts = vec![];
+    for class in equivalence_classes(&equalities) {
+        let representative = &class[0];
+        let mut class_predicates = vec![];
+        for member in &class {
+            for predicate in facts_on(&facts, member) {
+                let predicate = replace_column(predicate, member, representative)?;
+                if !class_predicates.contains(&predicate) {
+                    class_predicates.push(predicate);
+                }
+            }
+        }
+
+        for member in &class {
+            let (leaf, field) = match find_field(member) {
+                Some(found) => found,
+                None => continue,
+            };
+            let candidates = class_predicates
+                .iter()
+                .map(|predicate| replace_column(predicate, representative, member))
+                .collect::<Result<Vec<_>>>()?;
+            for predicate in new_predicates(member, field, candidates, &facts) {
+                new_facts.push((member.clone(), predicate.clone()));
+                leaf_predicates[leaf].push(predicate);
+            }
+        }
+    }
+    facts.extend(new_facts);
+
+    let mut leaves = leaves
+        .into_iter()
+        .zip(leaf_predicates)
+        .map(|(leaf, predicates)| add_filter(leaf, &predicates));
+    let plan = rebuild_inner(plan, &mut leaves)?;
+    Ok((plan, facts))
+}
+
+/// Collects the inputs of the tree of filters, cross joins and inner joins
+/// `plan`, the predicates of its filters and the columns it makes equal
+fn collect_inner<'a>(
+    plan: &'a LogicalPlan,
+    leaves: &mut Vec<&'a LogicalPlan>,
+    predicates: &mut Vec<Expr>,
+    equalities: &mut Vec<(Column, Column)>,
+) {
+    match plan {
+        LogicalPlan::Filter(Filter { predicate, input }) => {
+            let mut conjuncts = vec![];
+            split_conjunction(predicate, &mut conjuncts);
+            for conjunct in conjuncts {
+                match as_column_equality(conjunct) {
+                    Some(equality) => equalities.push(equality),
+                    None => predicates.push(conjunct.clone()),
+                }
+            }
+            collect_inner(input, leaves, predicates, equalities);
+        }
+        LogicalPlan::Join(Join {
+            left,
+            right,
+            on,
+            join_type: JoinType::Inner,
+            null_equals_null,
+            ..
+        }) => {
+            if !null_equals_null {
+                equalities.extend(on.iter().cloned());
+            }
+            collect_inner(left, leaves, predicates, equalities);
+            collect_inner(right, leaves, predicates, equalities);
+        }
+        LogicalPlan::CrossJoin(CrossJoin { left, right, .. }) => {
+            collect_inner(left, leaves, predicates, equalities);
+            collect_inner(right, leaves, predicates, equalities);
+        }
+        _ => leaves.push(plan),
+    }
+}
+
+/// Replaces the inputs of the tree of filters, cross joins and inner joins
+/// `plan` by `leaves`, in the order they were collected
+fn rebuild_inner(
+    plan: &LogicalPlan,
+    leaves: &mut impl Iterator<Item = LogicalPlan>,
+) -> Result<LogicalPlan> {
+    match plan {
+        LogicalPlan::Filter(_)
+        | LogicalPlan::CrossJoin(_)
+        | LogicalPlan::Join(Join {
+            join_type: JoinType::Inner,
+            ..
+        }) => {
+            let inputs = plan
+                .inputs()
+                .into_iter()
+                .map(|input| rebuild_inner(input, leaves))
+                .collect::<Result<Vec<_>>>()?;
+            utils::from_plan(plan, &plan.expressions(), &inputs)
+        }
+        _ => leaves.next().ok_or_else(|| {
+            DataFusionError::Internal("Missing input of inner join".to_string())
+        }),
+    }
+}
+
+/// Infers the predicates of an outer, semi or anti join, whose rows without
+/// a match may be returned from one of its inputs
+fn infer_join(plan: &LogicalPlan, join: &Join) -> Result<(LogicalPlan, Facts)> {
+    let (left, left_facts) = infer(&join.left)?;
+    let (right, right_facts) = infer(&join.right)?;
+
+    // the rows of an input that do not match any row of the other input can
+    // only be filtered out when they are not returned
+    let (to_left, to_right) = match join.join_type {
+        JoinType::Inner | JoinType::Semi => (true, true),
+        JoinType::Left | JoinType::Anti => (false, true),
+        JoinType::Right => (true, false),
+        JoinType::Full => (false, false),
+    };
+    let on: &[(Column, Column)] = if join.null_equals_null { &[] } else { &join.on };
+
+    let mut left_predicates = vec![];
+    let mut right_predicates = vec![];
+    for (left_key, right_key) in on {
+        let (left_field, right_field) = match (
+            left.schema().field_from_column(left_key),
+            right.schema().field_from_column(right_key),
+        ) {
+            (Ok(left_field), Ok(right_field))
+                if left_field.data_type() == right_field.data_type() =>
+            {
+                (left_field, right_field)
+            }
+            _ => continue,
+        };
+        if to_left {
+            let candidates = facts_on(&right_facts, right_key)
+                .map(|predicate| replace_column(predicate, right_key, left_key))
+                .collect::<Result<Vec<_>>>()?;
+            for predicate in new_predicates(left_key, left_field, candidates, &left_facts)
+            {
+                left_predicates.push((left_key.clone(), predicate));
+            }
+        }
+        if to_right {
+            let candidates = facts_on(&left_facts, left_key)
+                .map(|predicate| replace_column(predicate, left_key, right_key))
+                .collect::<Result<Vec<_>>>()?;
+            for predicate in
+                new_predicates(right_key, right_field, candidates, &right_facts)
+            {
+                right_predicates.push((right_key.clone(), predicate));
+            }
+        }
+    }
+
+    let left = add_filter(left, &unzip_predicates(&left_predicates));
+    let right = add_filter(right, &unzip_predicates(&right_predicates));
+    let plan = utils::from_plan(plan, &plan.expressions(), &[left, right])?;
+
+    // the rows without a match hold nulls for the columns of the other input
+    let facts = match join.join_type {
+        JoinType::Semi | JoinType::Anti => {
+            left_facts.into_iter().chain(left_predicates).collect()
+        }
+        _ => left_facts
+            .into_iter()
+            .chain(left_predicates)
+            .chain(right_facts)
+            .chain(right_predicates)
+            .collect(),
+    };
+    Ok((plan, facts))
+}
+
+/// Returns the `candidates` predicates on `column` that are not already
+/// known, and `column IS NOT NULL` if `column` is nullable and no other
+/// predicate on it is known, as they are all false for nulls
+fn new_predicates(
+    column: &Column,
+    field: &DFField,
+    candidates: Vec<Expr>,
+    facts: &Facts,
+) -> Vec<Expr> {
+    let mut predicates: Vec<Expr> = vec![];
+    for candidate in candidates {
+        if !matches!(candidate, Expr::IsNotNull(_))
+            && !facts.contains(&(column.clone(), candidate.clone()))
+            && !predicates.contains(&candidate)
+        {
+            predicates.push(candidate);
+        }
+    }
+    if field.is_nullable()
+        && predicates.is_empty()
+        && facts_on(facts, column).next().is_none()
+    {
+        predicates.push(Expr::IsNotNull(Box::new(Expr::Column(column.clone()))));
+    }
+    predicates
+}
+
+/// Groups the columns equal to each other through `equalities`
+fn equivalence_classes(equalities: &[(Column, Column)]) -> Vec<Vec<Column>> {
+    let mut classes: Vec<Vec<Column>> = vec![];
+    for (left, right) in equalities {
+        if left == right {
+            continue;
+        }
+        let left_class = classes.iter().position(|class| class.contains(left));
+        let right_class = classes.iter().position(|class| class.contains(right));
+        match (left_class, right_class) {
+            (Some(i), Some(j)) if i == j => {}
+            (Some(i), Some(j)) => {
+                let merged = classes.remove(i.max(j));
+                classes[i.min(j)].extend(merged);
+            }
+            (Some(i), None) => classes[i].push(right.clone()),
+            (None, Some(j)) => classes[j].push(left.clone()),
+            (None, None) => classes.push(vec![left.clone(), right.clone()]),
+        }
+    }
+    classes
+}
+
+fn facts_on<'a>(
+    facts: &'a Facts,
+    column: &'a Column,
+) -> impl Iterator<Item = &'a Expr> + 'a {
+    facts
+        .iter()
+        .filter(move |(fact_column, _)| fact_column == column)
+        .map(|(_, predicate)| predicate)
+}
+
+fn unzip_predicates(predicates: &Facts) -> Vec<Expr> {
+    predicates
+        .iter()
+        .map(|(_, predicate)| predicate.clone())
+        .collect()
+}
+
+fn replace_column(predicate: &Expr, from: &Column, to: &Column) -> Result<Expr> {
+    replace_col(predicate.clone(), &HashMap::from([(from, to)]))
+}
+
+fn add_filter(plan: LogicalPlan, predicates: &[Expr]) -> LogicalPlan {
+    match combine_filters(predicates) {
+        Some(predicate) => LogicalPlan::Filter(Filter {
+            predicate,
+            input: Arc::new(plan),
+        }),
+        None => plan,
+    }
+}
+
+fn as_column(expr: &Expr) -> Option<&Column> {
+    match expr {
+        Expr::Column(column) => Some(column),
+        _ => None,
+    }
+}
+
+fn as_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Literal(_))
+}
+
+/// Returns the column of the predicates propagated to the equal columns: the
+/// comparisons of a column with a literal, `IN` lists and `BETWEEN` of
+/// literals and `IS NOT NULL`
+fn as_column_predicate(predicate: &Expr) -> Option<&Column> {
+    match predicate {
+        Expr::BinaryExpr { left, op, right }
+            if matches!(
+                op,
+                Operator::Eq
+                    | Operator::NotEq
+                    | Operator::Lt
+                    | Operator::LtEq
+                    | Operator::Gt
+                    | Operator::GtEq
+            ) =>
+        {
+            match (left.as_ref(), right.as_ref()) {
+                (Expr::Column(column), literal) | (literal, Expr::Column(column))
+                    if as_literal(literal) =>
+                {
+                    Some(column)
+                }
+                _ => None,
+            }
+        }
+        Expr::InList {
+            expr,
+            list,
+            negated: false,
+        } if list.iter().all(as_literal) => as_column(expr),
+        Expr::Between {
+            expr,
+            low,
+            high,
+            negated: false,
+        } if as_literal(low) && as_literal(high) => as_column(expr),
+        Expr::IsNotNull(expr) => as_column(expr),
+        _ => None,
+    }
+}
+
+fn as_column_equality(predicate: &Expr) -> Option<(Column, Column)> {
+    match predicate {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::Eq,
+            right,
+        } => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(l), Expr::Column(r)) => Some((l.clone(), r.clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Converts `A AND B AND C` => [A, B, C]
+fn split_conjunction<'a>(predicate: &'a Expr, predicates: &mut Vec<&'a Expr>) {
+    match predicate {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        } => {
+            split_conjunction(left, predicates);
+            split_conjunction(right, predicates);
+        }
+        other => predicates.push(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{col, lit, LogicalPlanBuilder};
+    use crate::test::*;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let rule = InferPredicates::new();
+        let optimized_plan = rule
+            .optimize(plan, &ExecutionProps::new())
+            .expect("failed to optimize plan");
+        let formatted_plan = format!("{:?}", optimized_plan);
+        assert_eq!(formatted_plan, expected);
+        assert_eq!(plan.schema(), optimized_plan.schema());
+
+        // the inferred predicates are not inferred again
+        let optimized_again = rule
+            .optimize(&optimized_plan, &ExecutionProps::new())
+            .expect("failed to optimize plan");
+        assert_eq!(format!("{:?}", optimized_again), expected);
+    }
+
+    fn nullable_table_scan(name: &str) -> Result<LogicalPlan> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::UInt32, true),
+            Field::new("b", DataType::UInt32, true),
+        ]);
+        LogicalPlanBuilder::scan_empty(Some(name), &schema, None)?.build()
+    }
+
+    #[test]
+    fn propagate_through_join_chain() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let t3 = test_table_scan_with_name("t3")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(&t2, JoinType::Inner, (vec!["t1.a"], vec!["t2.a"]))?
+            .join(&t3, JoinType::Inner, (vec!["t2.a"], vec!["t3.a"]))?
+            .filter(col("t1.a").lt(lit(10u32)))?
+            .build()?;
+
+        let expected = "Filter: #t1.a < UInt32(10)\
+        \n  Join: #t2.a = #t3.a\
+        \n    Join: #t1.a = #t2.a\
+        \n      TableScan: t1 projection=None\
+        \n      Filter: #t2.a < UInt32(10)\
+        \n        TableScan: t2 projection=None\
+        \n    Filter: #t3.a < UInt32(10)\
+        \n      TableScan: t3 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn propagate_through_filter_equalities() -> Result<()> {
+        let t1 = test_table_scan_with_name("t1")?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .cross_join(&t2)?
+            .filter(
+                col("t1.b")
+                    .eq(col("t2.b"))
+                    .and(col("t2.b").in_list(vec![lit(1u32), lit(2u32)], false))
+                    .and(Expr::Between {
+                        expr: Box::new(col("t1.c")),
+                        negated: false,
+                        low: Box::new(lit(1u32)),
+                        high: Box::new(lit(5u32)),
+                    }),
+            )?
+            .build()?;
+
+        let expected = "Filter: #t1.b = #t2.b AND #t2.b IN ([UInt32(1), UInt32(2)]) AND #t1.c BETWEEN UInt32(1) AND UInt32(5)\
+        \n  CrossJoin:\
+        \n    Filter: #t1.b IN ([UInt32(1), UInt32(2)])\
+        \n      TableScan: t1 projection=None\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn derive_is_not_null_on_join_keys() -> Result<()> {
+        let t1 = nullable_table_scan("t1")?;
+        let t2 = nullable_table_scan("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(&t2, JoinType::Inner, (vec!["t1.a"], vec!["t2.a"]))?
+            .filter(col("t1.a").gt(lit(1u32)))?
+            .build()?;
+
+        // `#t1.a > UInt32(1)` already rejects the nulls of t1.a
+        let expected = "Filter: #t1.a > UInt32(1)\
+        \n  Join: #t1.a = #t2.a\
+        \n    TableScan: t1 projection=None\
+        \n    Filter: #t2.a > UInt32(1)\
+        \n      TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+
+        let t1 = nullable_table_scan("t1")?;
+        let t2 = nullable_table_scan("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(&t2, JoinType::Inner, (vec!["t1.a"], vec!["t2.a"]))?
+            .build()?;
+
+        let expected = "Join: #t1.a = #t2.a\
+        \n  Filter: #t1.a IS NOT NULL\
+        \n    TableScan: t1 projection=None\
+        \n  Filter: #t2.a IS NOT NULL\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn propagate_to_non_preserved_side_of_outer_join() -> Result<()> {
+        let t1 = nullable_table_scan("t1")?;
+        let t2 = LogicalPlanBuilder::from(nullable_table_scan("t2")?)
+            .filter(col("t2.a").lt(lit(5u32)))?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .filter(col("t1.a").gt(lit(1u32)))?
+            .join(&t2, JoinType::Left, (vec!["t1.a"], vec!["t2.a"]))?
+            .build()?;
+
+        // the predicate that filters the t2 input is not added to t1, whose
+        // rows are all returned
+        let expected = "Join: #t1.a = #t2.a\
+        \n  Filter: #t1.a > UInt32(1)\
+        \n    TableScan: t1 projection=None\
+        \n  Filter: #t2.a > UInt32(1)\
+        \n    Filter: #t2.a < UInt32(5)\
+        \n      TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn propagate_through_projection() -> Result<()> {
+        let t1 = LogicalPlanBuilder::from(test_table_scan_with_name("t1")?)
+            .filter(col("t1.a").eq(lit(3u32)))?
+            .project(vec![col("t1.a").alias("x")])?
+            .build()?;
+        let t2 = test_table_scan_with_name("t2")?;
+        let plan = LogicalPlanBuilder::from(t1)
+            .join(&t2, JoinType::Inner, (vec!["x"], vec!["t2.a"]))?
+            .build()?;
+
+        let expected = "Join: #x = #t2.a\
+        \n  Projection: #t1.a AS x\
+        \n    Filter: #t1.a = UInt32(3)\
+        \n      TableScan: t1 projection=None\
+        \n  Filter: #t2.a = UInt32(3)\
+        \n    TableScan: t2 projection=None";
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+}
@@ -87,14 +87,28 @@ fn limit_push_down(
             upper_limit,
         ) => {
             // Push down limit directly (projection doesn't change number of rows)
-            Ok(LogicalPlan::Projection(Projection {
-                expr: expr.clone(),
-                input: Arc::new(limit_push_down(
+            let input = match (input.as_ref(), upper_limit) {
+                // a limit right above a sort only sorts the first rows, see
+                // `DefaultPhysicalPlanner`
+                (LogicalPlan::Sort(_), Some(upper_limit)) => LogicalPlan::Limit(Limit {
+                    n: upper_limit,
+                    input: Arc::new(limit_push_down(
+                        optimizer,
+                        None,
+                        input.as_ref(),
+                        execution_props,
+                    )?),
+                }),
+                _ => limit_push_down(
                     optimizer,
                     upper_limit,
                     input.as_ref(),
                     execution_props,
-                )?),
+                )?,
+            };
+            Ok(LogicalPlan::Projection(Projection {
+                expr: expr.clone(),
+                input: Arc::new(input),
                 schema: schema.clone(),
                 alias: alias.clone(),
             }))
@@ -195,6 +209,29 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn limit_push_down_projection_of_sort() -> Result<()> {
+        let table_scan = test_table_scan()?;
+
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .sort(vec![col("b").sort(true, true)])?
+            .project(vec![col("a")])?
+            .limit(10)?
+            .build()?;
+
+        // Should push the limit right above the sort, but not below it
+        let expected = "Limit: 10\
+        \n  Projection: #test.a\
+        \n    Limit: 10\
+        \n      Sort: #test.b ASC NULLS FIRST\
+        \n        TableScan: test projection=None";
+
+        assert_optimized_plan_eq(&plan, expected);
+
+        Ok(())
+    }
+
     #[test]
     fn limit_push_down_take_smaller_limit() -> Result<()> {
         let table_scan = test_table_scan()?;
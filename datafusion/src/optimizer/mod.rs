@@ -18,9 +18,12 @@
 //! This module contains a query optimizer that operates against a logical plan and applies
 //! some simple rules to a logical plan, such as "Projection Push Down" and "Type Coercion".
 
+pub mod aggregate_push_down;
 pub mod common_subexpr_eliminate;
+pub mod eliminate_cross_join;
 pub mod eliminate_limit;
 pub mod filter_push_down;
+pub mod infer_predicates;
 pub mod limit_push_down;
 pub mod optimizer;
 pub mod projection_push_down;
@@ -436,8 +436,11 @@ fn optimize_plan(
         | LogicalPlan::Sort { .. }
         | LogicalPlan::CreateExternalTable(_)
         | LogicalPlan::CreateMemoryTable(_)
+        | LogicalPlan::Prepare(_)
         | LogicalPlan::DropTable(_)
+        | LogicalPlan::SetVariable(_)
         | LogicalPlan::CrossJoin(_)
+        | LogicalPlan::Unnest(_)
         | LogicalPlan::Extension { .. } => {
             let expr = plan.expressions();
             // collect all required columns by this plan
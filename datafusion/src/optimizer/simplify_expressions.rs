@@ -24,7 +24,9 @@ use arrow::record_batch::RecordBatch;
 use crate::error::DataFusionError;
 use crate::execution::context::{ExecutionContextState, ExecutionProps};
 use crate::logical_plan::{lit, DFSchemaRef, Expr};
-use crate::logical_plan::{DFSchema, ExprRewriter, LogicalPlan, RewriteRecursion};
+use crate::logical_plan::{
+    DFSchema, ExprRewriter, ExpressionVisitor, LogicalPlan, Recursion, RewriteRecursion,
+};
 use crate::optimizer::optimizer::OptimizerRule;
 use crate::optimizer::utils;
 use crate::physical_plan::functions::Volatility;
@@ -45,6 +47,10 @@ use crate::{error::Result, logical_plan::Operator};
 ///
 pub struct SimplifyExpressions {}
 
+/// Maximum number of times the constants of an expression are evaluated and
+/// the expression is simplified
+const MAX_SIMPLIFY_ITERATIONS: usize = 10;
+
 /// returns true if `needle` is found in a chain of search_op
 /// expressions. Such as: (A AND B) AND C
 fn expr_contains(expr: &Expr, needle: &Expr, search_op: Operator) -> bool {
@@ -134,6 +140,38 @@ fn as_bool_lit(expr: Expr) -> Option<bool> {
     }
 }
 
+/// returns true if `expr` calls a volatile function, so that two
+/// occurrences of it may evaluate to different values
+fn is_volatile(expr: &Expr) -> Result<bool> {
+    struct VolatilityVisitor {
+        is_volatile: bool,
+    }
+
+    impl ExpressionVisitor for VolatilityVisitor {
+        fn pre_visit(mut self, expr: &Expr) -> Result<Recursion<Self>> {
+            let volatile = match expr {
+                Expr::ScalarFunction { fun, .. } => {
+                    fun.volatility() == Volatility::Volatile
+                }
+                Expr::ScalarUDF { fun, .. } => {
+                    fun.signature.volatility == Volatility::Volatile
+                }
+                _ => false,
+            };
+            self.is_volatile |= volatile;
+            Ok(if volatile {
+                Recursion::Stop(self)
+            } else {
+                Recursion::Continue(self)
+            })
+        }
+    }
+
+    Ok(expr
+        .accept(VolatilityVisitor { is_volatile: false })?
+        .is_volatile)
+}
+
 impl OptimizerRule for SimplifyExpressions {
     fn name(&self) -> &str {
         "simplify_expressions"
@@ -167,15 +205,20 @@ impl OptimizerRule for SimplifyExpressions {
                 // Constant folding should not change expression name.
                 let name = &e.name(plan.schema());
 
-                // TODO iterate until no changes are made
-                // during rewrite (evaluating constants can
-                // enable new simplifications and
-                // simplifications can enable new constant
-                // evaluation)
-                let new_e = e
-                    // fold constants and then simplify
-                    .rewrite(&mut const_evaluator)?
-                    .rewrite(&mut simplifier)?;
+                // evaluating constants can enable new simplifications and
+                // simplifications can enable new constant evaluations, so
+                // iterate until no changes are made
+                let mut new_e = e;
+                for _ in 0..MAX_SIMPLIFY_ITERATIONS {
+                    let previous_e = new_e.clone();
+                    new_e = new_e
+                        // fold constants and then simplify
+                        .rewrite(&mut const_evaluator)?
+                        .rewrite(&mut simplifier)?;
+                    if new_e == previous_e {
+                        break;
+                    }
+                }
 
                 let new_name = &new_e.name(plan.schema());
 
@@ -336,8 +379,12 @@ impl ConstEvaluator {
             Expr::AggregateFunction { .. } => false,
             Expr::AggregateUDF { .. } => false,
             Expr::ScalarVariable(_) => false,
+            // Has no value until it is bound
+            Expr::Placeholder { .. } => false,
             Expr::Column(_) => false,
             Expr::ScalarFunction { fun, .. } => Self::volatility_ok(fun.volatility()),
+            // async UDFs can only be evaluated in projections and filters
+            Expr::ScalarUDF { fun, .. } if fun.async_fun.is_some() => false,
             Expr::ScalarUDF { fun, .. } => Self::volatility_ok(fun.signature.volatility),
             Expr::WindowFunction { .. } => false,
             Expr::Sort { .. } => false,
@@ -396,6 +443,8 @@ impl ConstEvaluator {
 /// * `false = true` and `true = false` to `false`
 /// * `!!expr` to `expr`
 /// * `expr = null` and `expr != null` to `null`
+/// * `CASE WHEN true THEN a ELSE b END` to `a` and `CASE WHEN false THEN a ELSE b END` to `b`
+/// * `expr IN (.., expr, ..)` to `true` when `expr` is not nullable
 pub(crate) struct Simplifier<'a> {
     /// input schemas
     schemas: Vec<&'a DFSchemaRef>,
@@ -416,6 +465,71 @@ impl<'a> Simplifier<'a> {
         false
     }
 
+    /// Returns the type of expr, if it can be computed with any input schema
+    fn get_type(&self, expr: &Expr) -> Option<DataType> {
+        self.schemas
+            .iter()
+            .find_map(|schema| expr.get_type(schema).ok())
+    }
+
+    /// Removes the branches of a `CASE` whose condition is a literal: the
+    /// branches after a `true` condition are never taken, as well as the
+    /// branches with a `false` or `null` condition
+    fn simplify_case(
+        &self,
+        when_then_expr: Vec<(Box<Expr>, Box<Expr>)>,
+        else_expr: Option<Box<Expr>>,
+    ) -> Expr {
+        let case = Expr::Case {
+            expr: None,
+            when_then_expr,
+            else_expr,
+        };
+        let data_type = match self.get_type(&case) {
+            Some(data_type) => data_type,
+            None => return case,
+        };
+        let (when_then_expr, mut else_expr) = match &case {
+            Expr::Case {
+                when_then_expr,
+                else_expr,
+                ..
+            } => (when_then_expr, else_expr.clone()),
+            _ => unreachable!(),
+        };
+
+        let mut branches = vec![];
+        for (when, then) in when_then_expr {
+            if is_true(when) {
+                else_expr = Some(then.clone());
+                break;
+            }
+            if !is_bool_lit(when) {
+                branches.push((when.clone(), then.clone()));
+            }
+        }
+        let simplified = match (branches.is_empty(), else_expr) {
+            (true, Some(else_expr)) => *else_expr,
+            (true, None) => match ScalarValue::try_from(&data_type) {
+                Ok(null) => Expr::Literal(null),
+                Err(_) => return case,
+            },
+            (false, else_expr) => Expr::Case {
+                expr: None,
+                when_then_expr: branches,
+                else_expr,
+            },
+        };
+
+        // the type of a CASE is the type of its first branch, which may
+        // have been removed
+        if self.get_type(&simplified) == Some(data_type) {
+            simplified
+        } else {
+            case
+        }
+    }
+
     /// Returns true if expr is nullable
     fn nullable(&self, expr: &Expr) -> Result<bool> {
         self.schemas
@@ -652,6 +766,37 @@ impl<'a> ExprRewriter for Simplifier<'a> {
                 right,
             } if !self.nullable(&left)? && left == right => lit(1),
 
+            //
+            // Rules for Case
+            //
+
+            // CASE WHEN true THEN A ELSE B END --> A
+            // CASE WHEN false THEN A ELSE B END --> B
+            Case {
+                expr: None,
+                when_then_expr,
+                else_expr,
+            } if when_then_expr.iter().any(|(when, _)| is_bool_lit(when)) => {
+                self.simplify_case(when_then_expr, else_expr)
+            }
+
+            //
+            // Rules for InList
+            //
+
+            // A IN (.., A, ..) --> true (if A not null and not volatile)
+            // A NOT IN (.., A, ..) --> false (if A not null and not volatile)
+            InList {
+                expr,
+                list,
+                negated,
+            } if list.contains(expr.as_ref())
+                && !self.nullable(&expr)?
+                && !is_volatile(&expr)? =>
+            {
+                lit(!negated)
+            }
+
             //
             // Rules for Not
             //
@@ -677,12 +822,16 @@ mod tests {
 
     use arrow::array::{ArrayRef, Int32Array};
     use chrono::{DateTime, TimeZone, Utc};
+    use futures::FutureExt;
 
     use super::*;
     use crate::assert_contains;
     use crate::logical_plan::{
-        and, binary_expr, col, create_udf, lit, lit_timestamp_nano, DFField, Expr,
-        LogicalPlanBuilder,
+        and, binary_expr, col, create_async_udf, create_udf, lit, lit_timestamp_nano,
+        random, when, DFField, Expr, LogicalPlanBuilder,
+    };
+    use crate::physical_plan::async_udf::{
+        AsyncScalarFunction, AsyncScalarFunctionImplementation,
     };
     use crate::physical_plan::functions::{make_scalar_function, BuiltinScalarFunction};
     use crate::physical_plan::udf::ScalarUDF;
@@ -1092,6 +1241,30 @@ mod tests {
         test_evaluate(expr, expected_expr);
     }
 
+    #[test]
+    fn test_evaluator_async_udfs() {
+        // async UDF should have args folded, even when immutable
+        // async_first(1+2, 30+40) --> async_first(3, 70)
+        let fun: AsyncScalarFunctionImplementation =
+            Arc::new(|args: Vec<ArrayRef>| async move { Ok(args[0].clone()) }.boxed());
+        let fun = Arc::new(create_async_udf(
+            "async_first",
+            vec![DataType::Int32, DataType::Int32],
+            Arc::new(DataType::Int32),
+            Volatility::Immutable,
+            AsyncScalarFunction::new(fun),
+        ));
+        let expr = Expr::ScalarUDF {
+            args: vec![lit(1) + lit(2), lit(30) + lit(40)],
+            fun: Arc::clone(&fun),
+        };
+        let expected_expr = Expr::ScalarUDF {
+            args: vec![lit(3), lit(70)],
+            fun,
+        };
+        test_evaluate(expr, expected_expr);
+    }
+
     // Make a UDF that adds its two values together, with the specified volatility
     fn make_udf_add(volatility: Volatility) -> Arc<ScalarUDF> {
         let input_types = vec![DataType::Int32, DataType::Int32];
@@ -1189,6 +1362,68 @@ mod tests {
         )
     }
 
+    #[test]
+    fn simplify_expr_case_literal_conditions() {
+        let case =
+            |when_then_expr: Vec<(Expr, Expr)>, else_expr: Option<Expr>| Expr::Case {
+                expr: None,
+                when_then_expr: when_then_expr
+                    .into_iter()
+                    .map(|(when, then)| (Box::new(when), Box::new(then)))
+                    .collect(),
+                else_expr: else_expr.map(Box::new),
+            };
+
+        // CASE WHEN true THEN c1 ELSE c1_non_null END --> c1
+        assert_eq!(
+            simplify(case(vec![(lit(true), col("c1"))], Some(col("c1_non_null")))),
+            col("c1")
+        );
+
+        // CASE WHEN false THEN c1 WHEN c2 THEN c1_non_null WHEN true THEN c1 END
+        // --> CASE WHEN c2 THEN c1_non_null ELSE c1 END
+        assert_eq!(
+            simplify(case(
+                vec![
+                    (lit(false), col("c1")),
+                    (col("c2"), col("c1_non_null")),
+                    (lit(true), col("c1")),
+                ],
+                None
+            )),
+            case(vec![(col("c2"), col("c1_non_null"))], Some(col("c1")))
+        );
+
+        // CASE WHEN NULL THEN c1 END --> NULL
+        assert_eq!(
+            simplify(case(vec![(lit_null(), col("c1"))], None)),
+            Expr::Literal(ScalarValue::Utf8(None))
+        );
+    }
+
+    #[test]
+    fn simplify_expr_in_list_containing_expr() {
+        let list = vec![lit("a"), col("c1_non_null")];
+        assert_eq!(
+            simplify(col("c1_non_null").in_list(list.clone(), false)),
+            lit(true)
+        );
+        assert_eq!(simplify(col("c1_non_null").in_list(list, true)), lit(false));
+
+        // c1 IN (c1) is null when c1 is null
+        let expr = col("c1").in_list(vec![col("c1")], false);
+        assert_eq!(simplify(expr.clone()), expr);
+
+        // the occurrences of a volatile expression may differ
+        let expr = random().in_list(vec![random()], false);
+        assert_eq!(simplify(expr.clone()), expr);
+        let coin = when(random().gt(lit(0.5)), lit(1))
+            .otherwise(lit(0))
+            .unwrap();
+        let expr = coin.clone().in_list(vec![lit(2), coin], true);
+        assert_eq!(simplify(expr.clone()), expr);
+    }
+
     #[test]
     fn simplify_expr_not_not() {
         assert_eq!(simplify(col("c2").not().not().not()), col("c2").not(),);
@@ -1617,6 +1852,28 @@ mod tests {
         assert_optimized_plan_eq(&plan, expected);
     }
 
+    #[test]
+    fn simplify_to_fixed_point() {
+        let table_scan = test_table_scan();
+        // the CASE is simplified to a literal, which can then be compared
+        let case = Expr::Case {
+            expr: None,
+            when_then_expr: vec![(Box::new(lit(true)), Box::new(lit(1u32)))],
+            else_expr: Some(Box::new(col("d"))),
+        };
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .filter(case.eq(lit(1u32)))
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let expected = "\
+        Filter: Boolean(true) AS CASE WHEN Boolean(true) THEN UInt32(1) ELSE test.d END = UInt32(1)\
+        \n  TableScan: test projection=None";
+
+        assert_optimized_plan_eq(&plan, expected);
+    }
+
     // expect optimizing will result in an error, returning the error string
     fn get_optimized_plan_err(plan: &LogicalPlan, date_time: &DateTime<Utc>) -> String {
         let rule = SimplifyExpressions::new();
@@ -65,22 +65,35 @@ fn optimize(plan: &LogicalPlan) -> Result<LogicalPlan> {
                 // remove distinct and collection args
                 let new_aggr_expr = aggr_expr
                     .iter()
-                    .map(|agg_expr| match agg_expr {
-                        Expr::AggregateFunction { fun, args, .. } => {
-                            // is_single_distinct_agg ensure args.len=1
+                    .map(|agg_expr| {
+                        // is_single_distinct_agg ensure args.len=1
+                        if let Some(args) = distinct_args(agg_expr) {
                             if group_fields_set
                                 .insert(args[0].name(input.schema()).unwrap())
                             {
                                 all_group_args
                                     .push(args[0].clone().alias(SINGLE_DISTINCT_ALIAS));
                             }
-                            Expr::AggregateFunction {
+                        }
+                        match agg_expr {
+                            Expr::AggregateFunction { fun, .. } => {
+                                Expr::AggregateFunction {
+                                    fun: fun.clone(),
+                                    args: vec![col(SINGLE_DISTINCT_ALIAS)],
+                                    distinct: false,
+                                    filter: None,
+                                    order_by: vec![],
+                                }
+                            }
+                            Expr::AggregateUDF { fun, .. } => Expr::AggregateUDF {
                                 fun: fun.clone(),
                                 args: vec![col(SINGLE_DISTINCT_ALIAS)],
                                 distinct: false,
-                            }
+                                filter: None,
+                                order_by: vec![],
+                            },
+                            _ => agg_expr.clone(),
                         }
-                        _ => agg_expr.clone(),
                     })
                     .collect::<Vec<_>>();
 
@@ -159,15 +172,14 @@ fn is_single_distinct_agg(plan: &LogicalPlan) -> bool {
             let mut fields_set = HashSet::new();
             aggr_expr
                 .iter()
-                .filter(|expr| {
-                    let mut is_distinct = false;
-                    if let Expr::AggregateFunction { distinct, args, .. } = expr {
-                        is_distinct = *distinct;
+                .filter(|expr| match distinct_args(expr) {
+                    Some(args) => {
                         args.iter().for_each(|expr| {
                             fields_set.insert(expr.name(input.schema()).unwrap());
-                        })
+                        });
+                        args.len() == 1
                     }
-                    is_distinct
+                    None => false,
                 })
                 .count()
                 == aggr_expr.len()
@@ -177,6 +189,28 @@ fn is_single_distinct_agg(plan: &LogicalPlan) -> bool {
     }
 }
 
+/// The arguments of a DISTINCT aggregate, built-in or user-defined, without
+/// FILTER and ORDER BY clauses, which the rewrite would lose
+fn distinct_args(expr: &Expr) -> Option<&[Expr]> {
+    match expr {
+        Expr::AggregateFunction {
+            distinct: true,
+            args,
+            filter: None,
+            order_by,
+            ..
+        }
+        | Expr::AggregateUDF {
+            distinct: true,
+            args,
+            filter: None,
+            order_by,
+            ..
+        } if order_by.is_empty() => Some(args.as_slice()),
+        _ => None,
+    }
+}
+
 impl OptimizerRule for SingleDistinctToGroupBy {
     fn optimize(
         &self,
@@ -193,9 +227,14 @@ impl OptimizerRule for SingleDistinctToGroupBy {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::logical_plan::{col, count, count_distinct, lit, max, LogicalPlanBuilder};
+    use crate::logical_plan::{
+        col, count, count_distinct, create_udaf, lit, max, LogicalPlanBuilder,
+    };
     use crate::physical_plan::aggregates;
+    use crate::physical_plan::expressions::MaxAccumulator;
+    use crate::physical_plan::functions::Volatility;
     use crate::test::*;
+    use arrow::datatypes::DataType;
 
     fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
         let rule = SingleDistinctToGroupBy::new();
@@ -307,6 +346,8 @@ mod tests {
                         fun: aggregates::AggregateFunction::Max,
                         distinct: true,
                         args: vec![col("b")],
+                        filter: None,
+                        order_by: vec![],
                     },
                 ],
             )?
@@ -339,4 +380,64 @@ mod tests {
         assert_optimized_plan_eq(&plan, expected);
         Ok(())
     }
+
+    #[test]
+    fn single_distinct_udaf() -> Result<()> {
+        let table_scan = test_table_scan()?;
+        let my_max = create_udaf(
+            "my_max",
+            DataType::UInt32,
+            Arc::new(DataType::UInt32),
+            Volatility::Immutable,
+            Arc::new(|| Ok(Box::new(MaxAccumulator::try_new(&DataType::UInt32)?))),
+            Arc::new(vec![DataType::UInt32]),
+        );
+
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .aggregate(
+                vec![col("a")],
+                vec![Expr::AggregateUDF {
+                    fun: Arc::new(my_max),
+                    args: vec![col("b")],
+                    distinct: true,
+                    filter: None,
+                    order_by: vec![],
+                }],
+            )?
+            .build()?;
+
+        // Should work
+        let expected = "Projection: #test.a AS a, #my_max(alias1) AS my_max(DISTINCT test.b) [a:UInt32, my_max(DISTINCT test.b):UInt32;N]\
+                            \n  Aggregate: groupBy=[[#test.a]], aggr=[[my_max(#alias1)]] [a:UInt32, my_max(alias1):UInt32;N]\
+                            \n    Aggregate: groupBy=[[#test.a, #test.b AS alias1]], aggr=[[]] [a:UInt32, alias1:UInt32]\
+                            \n      TableScan: test projection=None [a:UInt32, b:UInt32, c:UInt32]";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn filtered_distinct() -> Result<()> {
+        let table_scan = test_table_scan()?;
+
+        let plan = LogicalPlanBuilder::from(table_scan)
+            .aggregate(
+                vec![col("a")],
+                vec![Expr::AggregateFunction {
+                    fun: aggregates::AggregateFunction::Count,
+                    distinct: true,
+                    args: vec![col("b")],
+                    filter: Some(Box::new(col("c").gt(lit(1u32)))),
+                    order_by: vec![],
+                }],
+            )?
+            .build()?;
+
+        // Do nothing
+        let expected = "Aggregate: groupBy=[[#test.a]], aggr=[[COUNT(DISTINCT #test.b) FILTER (WHERE #test.c > UInt32(1))]] [a:UInt32, COUNT(DISTINCT test.b) FILTER (WHERE test.c > UInt32(1)):UInt64;N]\
+                            \n  TableScan: test projection=None [a:UInt32, b:UInt32, c:UInt32]";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
 }
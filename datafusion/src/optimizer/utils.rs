@@ -23,8 +23,9 @@ use crate::logical_plan::plan::{
     Aggregate, Analyze, Extension, Filter, Join, Projection, Sort, Window,
 };
 use crate::logical_plan::{
-    build_join_schema, Column, CreateMemoryTable, DFSchemaRef, Expr, Limit, LogicalPlan,
-    LogicalPlanBuilder, Operator, Partitioning, Recursion, Repartition, Union, Values,
+    build_join_schema, Column, CreateMemoryTable, DFSchema, DFSchemaRef, Expr, Limit,
+    LogicalPlan, LogicalPlanBuilder, Operator, Partitioning, Prepare, Recursion,
+    Repartition, Union, Unnest, Values,
 };
 use crate::prelude::lit;
 use crate::scalar::ScalarValue;
@@ -38,6 +39,8 @@ const CASE_EXPR_MARKER: &str = "__DATAFUSION_CASE_EXPR__";
 const CASE_ELSE_MARKER: &str = "__DATAFUSION_CASE_ELSE__";
 const WINDOW_PARTITION_MARKER: &str = "__DATAFUSION_WINDOW_PARTITION__";
 const WINDOW_SORT_MARKER: &str = "__DATAFUSION_WINDOW_SORT__";
+const AGGREGATE_FILTER_MARKER: &str = "__DATAFUSION_AGGREGATE_FILTER__";
+const AGGREGATE_SORT_MARKER: &str = "__DATAFUSION_AGGREGATE_SORT__";
 
 /// Recursively walk a list of expression trees, collecting the unique set of columns
 /// referenced in the expression
@@ -65,6 +68,7 @@ impl ExpressionVisitor for ColumnNameVisitor<'_> {
             }
             Expr::Alias(_, _) => {}
             Expr::Literal(_) => {}
+            Expr::Placeholder { .. } => {}
             Expr::BinaryExpr { .. } => {}
             Expr::Not(_) => {}
             Expr::IsNotNull(_) => {}
@@ -115,6 +119,82 @@ pub fn optimize_children(
     from_plan(plan, &new_exprs, &new_inputs)
 }
 
+/// Returns true if the plans `a` and `b` are known to be the same, comparing
+/// them node by node. Table providers and extension nodes are compared by
+/// identity, so that rebuilt extension nodes count as changed.
+pub fn same_plan(a: &LogicalPlan, b: &LogicalPlan) -> bool {
+    let same_inputs = || {
+        let (a_inputs, b_inputs) = (a.inputs(), b.inputs());
+        a_inputs.len() == b_inputs.len()
+            && a_inputs
+                .iter()
+                .zip(b_inputs.iter())
+                .all(|(a, b)| same_plan(a, b))
+    };
+    let same_node = match (a, b) {
+        (LogicalPlan::Projection(a), LogicalPlan::Projection(b)) => {
+            a.expr == b.expr && a.schema == b.schema && a.alias == b.alias
+        }
+        (LogicalPlan::Filter(a), LogicalPlan::Filter(b)) => a.predicate == b.predicate,
+        (LogicalPlan::Window(a), LogicalPlan::Window(b)) => {
+            a.window_expr == b.window_expr && a.schema == b.schema
+        }
+        (LogicalPlan::Aggregate(a), LogicalPlan::Aggregate(b)) => {
+            a.group_expr == b.group_expr
+                && a.aggr_expr == b.aggr_expr
+                && a.schema == b.schema
+        }
+        (LogicalPlan::Sort(a), LogicalPlan::Sort(b)) => a.expr == b.expr,
+        (LogicalPlan::Join(a), LogicalPlan::Join(b)) => {
+            a.on == b.on
+                && a.join_type == b.join_type
+                && a.join_constraint == b.join_constraint
+                && a.schema == b.schema
+                && a.null_equals_null == b.null_equals_null
+        }
+        (LogicalPlan::CrossJoin(a), LogicalPlan::CrossJoin(b)) => a.schema == b.schema,
+        (LogicalPlan::Repartition(a), LogicalPlan::Repartition(b)) => {
+            match (&a.partitioning_scheme, &b.partitioning_scheme) {
+                (Partitioning::RoundRobinBatch(a), Partitioning::RoundRobinBatch(b)) => {
+                    a == b
+                }
+                (Partitioning::Hash(a_expr, a_n), Partitioning::Hash(b_expr, b_n)) => {
+                    a_expr == b_expr && a_n == b_n
+                }
+                _ => false,
+            }
+        }
+        (LogicalPlan::Union(a), LogicalPlan::Union(b)) => {
+            a.schema == b.schema && a.alias == b.alias
+        }
+        (LogicalPlan::Unnest(a), LogicalPlan::Unnest(b)) => {
+            a.expr == b.expr && a.schema == b.schema
+        }
+        (LogicalPlan::TableScan(a), LogicalPlan::TableScan(b)) => {
+            a.table_name == b.table_name
+                && Arc::as_ptr(&a.source) as *const u8
+                    == Arc::as_ptr(&b.source) as *const u8
+                && a.projection == b.projection
+                && a.projected_schema == b.projected_schema
+                && a.filters == b.filters
+                && a.limit == b.limit
+        }
+        (LogicalPlan::EmptyRelation(a), LogicalPlan::EmptyRelation(b)) => {
+            a.produce_one_row == b.produce_one_row && a.schema == b.schema
+        }
+        (LogicalPlan::Limit(a), LogicalPlan::Limit(b)) => a.n == b.n,
+        (LogicalPlan::Values(a), LogicalPlan::Values(b)) => {
+            a.values == b.values && a.schema == b.schema
+        }
+        (LogicalPlan::Extension(a), LogicalPlan::Extension(b)) => {
+            Arc::as_ptr(&a.node) as *const u8 == Arc::as_ptr(&b.node) as *const u8
+        }
+        // the remaining nodes are statements, which the rules leave alone
+        _ => false,
+    };
+    same_node && same_inputs()
+}
+
 /// Returns a new logical plan based on the original one with inputs
 /// and expressions replaced.
 ///
@@ -223,12 +303,29 @@ pub fn from_plan(
             n: *n,
             input: Arc::new(inputs[0].clone()),
         })),
+        LogicalPlan::Unnest(Unnest { schema, .. }) => {
+            // the input may have changed, keep only the unnested field
+            let mut fields = inputs[0].schema().fields().clone();
+            fields.push(schema.field(schema.fields().len() - 1).clone());
+            Ok(LogicalPlan::Unnest(Unnest {
+                input: Arc::new(inputs[0].clone()),
+                expr: expr[0].clone(),
+                schema: Arc::new(DFSchema::new(fields)?),
+            }))
+        }
         LogicalPlan::CreateMemoryTable(CreateMemoryTable { name, .. }) => {
             Ok(LogicalPlan::CreateMemoryTable(CreateMemoryTable {
                 input: Arc::new(inputs[0].clone()),
                 name: name.clone(),
             }))
         }
+        LogicalPlan::Prepare(Prepare {
+            name, data_types, ..
+        }) => Ok(LogicalPlan::Prepare(Prepare {
+            name: name.clone(),
+            data_types: data_types.clone(),
+            input: Arc::new(inputs[0].clone()),
+        })),
         LogicalPlan::Extension(e) => Ok(LogicalPlan::Extension(Extension {
             node: e.node.from_template(expr, inputs),
         })),
@@ -265,7 +362,8 @@ pub fn from_plan(
         LogicalPlan::EmptyRelation(_)
         | LogicalPlan::TableScan { .. }
         | LogicalPlan::CreateExternalTable(_)
-        | LogicalPlan::DropTable(_) => {
+        | LogicalPlan::DropTable(_)
+        | LogicalPlan::SetVariable(_) => {
             // All of these plan types have no inputs / exprs so should not be called
             assert!(expr.is_empty(), "{:?} should have no exprs", plan);
             assert!(inputs.is_empty(), "{:?}  should have no inputs", plan);
@@ -299,8 +397,26 @@ pub fn expr_sub_expressions(expr: &Expr) -> Result<Vec<Expr>> {
             expr_list.extend(order_by.clone());
             Ok(expr_list)
         }
-        Expr::AggregateFunction { args, .. } => Ok(args.clone()),
-        Expr::AggregateUDF { args, .. } => Ok(args.clone()),
+        Expr::AggregateFunction {
+            args,
+            filter,
+            order_by,
+            ..
+        }
+        | Expr::AggregateUDF {
+            args,
+            filter,
+            order_by,
+            ..
+        } => {
+            let mut expr_list: Vec<Expr> = vec![];
+            expr_list.extend(args.clone());
+            expr_list.push(lit(AGGREGATE_FILTER_MARKER));
+            expr_list.extend(filter.iter().map(|e| e.as_ref().clone()));
+            expr_list.push(lit(AGGREGATE_SORT_MARKER));
+            expr_list.extend(order_by.clone());
+            Ok(expr_list)
+        }
         Expr::Case {
             expr,
             when_then_expr,
@@ -327,6 +443,7 @@ pub fn expr_sub_expressions(expr: &Expr) -> Result<Vec<Expr>> {
         Expr::Column(_) => Ok(vec![]),
         Expr::Alias(expr, ..) => Ok(vec![expr.as_ref().to_owned()]),
         Expr::Literal(_) => Ok(vec![]),
+        Expr::Placeholder { .. } => Ok(vec![]),
         Expr::ScalarVariable(_) => Ok(vec![]),
         Expr::Not(expr) => Ok(vec![expr.as_ref().to_owned()]),
         Expr::Negative(expr) => Ok(vec![expr.as_ref().to_owned()]),
@@ -352,6 +469,45 @@ pub fn expr_sub_expressions(expr: &Expr) -> Result<Vec<Expr>> {
     }
 }
 
+/// Splits the sub expressions of an aggregate expression, as returned by
+/// [`expr_sub_expressions`], into its arguments, filter and ordering
+#[allow(clippy::type_complexity)]
+fn split_aggregate_expressions(
+    expressions: &[Expr],
+) -> Result<(Vec<Expr>, Option<Box<Expr>>, Vec<Expr>)> {
+    let marker_index = |marker: &str| {
+        expressions
+            .iter()
+            .position(|expr| {
+                matches!(expr, Expr::Literal(ScalarValue::Utf8(Some(str)))
+            if str == marker)
+            })
+            .ok_or_else(|| {
+                DataFusionError::Internal(
+                    "Ill-formed aggregate function expressions".to_owned(),
+                )
+            })
+    };
+    let filter_index = marker_index(AGGREGATE_FILTER_MARKER)?;
+    let sort_index = marker_index(AGGREGATE_SORT_MARKER)?;
+
+    let filter = match &expressions[filter_index + 1..sort_index] {
+        [] => None,
+        [filter] => Some(Box::new(filter.clone())),
+        _ => {
+            return Err(DataFusionError::Internal(
+                "Ill-formed aggregate function expressions: more than one filter"
+                    .to_owned(),
+            ))
+        }
+    };
+    Ok((
+        expressions[..filter_index].to_vec(),
+        filter,
+        expressions[sort_index + 1..].to_vec(),
+    ))
+}
+
 /// returns a new expression where the expressions in `expr` are replaced by the ones in
 /// `expressions`.
 /// This is used in conjunction with ``expr_expressions`` to re-write expressions.
@@ -415,15 +571,26 @@ pub fn rewrite_expression(expr: &Expr, expressions: &[Expr]) -> Result<Expr> {
                 })
             }
         }
-        Expr::AggregateFunction { fun, distinct, .. } => Ok(Expr::AggregateFunction {
-            fun: fun.clone(),
-            args: expressions.to_vec(),
-            distinct: *distinct,
-        }),
-        Expr::AggregateUDF { fun, .. } => Ok(Expr::AggregateUDF {
-            fun: fun.clone(),
-            args: expressions.to_vec(),
-        }),
+        Expr::AggregateFunction { fun, distinct, .. } => {
+            let (args, filter, order_by) = split_aggregate_expressions(expressions)?;
+            Ok(Expr::AggregateFunction {
+                fun: fun.clone(),
+                args,
+                distinct: *distinct,
+                filter,
+                order_by,
+            })
+        }
+        Expr::AggregateUDF { fun, distinct, .. } => {
+            let (args, filter, order_by) = split_aggregate_expressions(expressions)?;
+            Ok(Expr::AggregateUDF {
+                fun: fun.clone(),
+                args,
+                distinct: *distinct,
+                filter,
+                order_by,
+            })
+        }
         Expr::Case { .. } => {
             let mut base_expr: Option<Box<Expr>> = None;
             let mut when_then: Vec<(Box<Expr>, Box<Expr>)> = vec![];
@@ -475,6 +642,7 @@ pub fn rewrite_expression(expr: &Expr, expressions: &[Expr]) -> Result<Expr> {
         Expr::Negative(_) => Ok(Expr::Negative(Box::new(expressions[0].clone()))),
         Expr::Column(_) => Ok(expr.clone()),
         Expr::Literal(_) => Ok(expr.clone()),
+        Expr::Placeholder { .. } => Ok(expr.clone()),
         Expr::ScalarVariable(_) => Ok(expr.clone()),
         Expr::Sort {
             asc, nulls_first, ..
@@ -522,6 +690,26 @@ mod tests {
     use arrow::datatypes::DataType;
     use std::collections::HashSet;
 
+    #[test]
+    fn test_same_plan() -> Result<()> {
+        let scan = crate::test::test_table_scan()?;
+        let plan = |n: usize| -> Result<LogicalPlan> {
+            LogicalPlanBuilder::from(scan.clone())
+                .filter(col("a").eq(lit(1u32)))?
+                .limit(n)?
+                .build()
+        };
+        assert!(same_plan(&plan(10)?, &plan(10)?));
+        assert!(!same_plan(&plan(10)?, &plan(5)?));
+
+        let other_filter = LogicalPlanBuilder::from(scan.clone())
+            .filter(col("a").eq(lit(2u32)))?
+            .limit(10)?
+            .build()?;
+        assert!(!same_plan(&plan(10)?, &other_filter));
+        Ok(())
+    }
+
     #[test]
     fn test_collect_expr() -> Result<()> {
         let mut accum: HashSet<Column> = HashSet::new();
@@ -0,0 +1,290 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! EnforceSorting sorts the inputs of the operators that require an ordering
+//! their input does not have, and removes or weakens the sorts whose input
+//! is already sorted
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::physical_plan::{
+    coalesce_partitions::CoalescePartitionsExec, ordering_satisfy, sort::SortExec,
+    sort_preserving_merge::SortPreservingMergeExec, ExecutionPlan,
+};
+use crate::{error::Result, execution::context::ExecutionConfig};
+
+/// Optimizer rule that uses the [`ExecutionPlan::output_ordering`] of the
+/// nodes of a plan to:
+/// - add a `SortExec` under the nodes whose input does not satisfy their
+///   [`ExecutionPlan::required_input_ordering`]
+/// - remove the `SortExec`s and `SortPreservingMergeExec`s whose input is
+///   already sorted
+/// - replace the `SortExec`s that merge several sorted partitions by a
+///   `SortPreservingMergeExec`
+pub struct EnforceSorting {}
+
+impl EnforceSorting {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for EnforceSorting {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &ExecutionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        // bottom-up, so that the ordering of the children is final
+        let plan = optimize_children(self, plan, config)?;
+        let plan = add_required_sorts(plan)?;
+        remove_redundant_sort(plan, config)
+    }
+
+    fn name(&self) -> &str {
+        "enforce_sorting"
+    }
+}
+
+/// Sorts each partition of the children of `plan` whose ordering does not
+/// satisfy the ordering `plan` requires
+fn add_required_sorts(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+    let children = plan.children();
+    let required_orderings = plan.required_input_ordering();
+    let is_satisfied = |child: &Arc<dyn ExecutionPlan>, required: &Option<Vec<_>>| {
+        required.as_ref().map_or(true, |required| {
+            ordering_satisfy(child.output_ordering().as_deref(), required)
+        })
+    };
+    if children
+        .iter()
+        .zip(&required_orderings)
+        .all(|(child, required)| is_satisfied(child, required))
+    {
+        return Ok(plan);
+    }
+
+    let children = children
+        .into_iter()
+        .zip(required_orderings)
+        .map(|(child, required)| match required {
+            Some(required)
+                if !ordering_satisfy(child.output_ordering().as_deref(), &required) =>
+            {
+                Arc::new(SortExec::new_with_partitioning(required, child, true))
+            }
+            _ => child,
+        })
+        .collect();
+    plan.with_new_children(children)
+}
+
+/// Returns the input of `plan` if `plan` sorts an input that is already
+/// sorted, or merges the input partitions if `plan` sorts several sorted
+/// partitions into one
+fn remove_redundant_sort(
+    plan: Arc<dyn ExecutionPlan>,
+    config: &ExecutionConfig,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    if let Some(sort) = plan.as_any().downcast_ref::<SortExec>() {
+        let input = sort.input();
+        if ordering_satisfy(input.output_ordering().as_deref(), sort.expr()) {
+            if sort.preserve_partitioning()
+                || input.output_partitioning().partition_count() == 1
+            {
+                return Ok(input.clone());
+            }
+            return Ok(Arc::new(
+                SortPreservingMergeExec::new(
+                    sort.expr().to_vec(),
+                    input.clone(),
+                    config.batch_size,
+                )
+                .with_fetch(sort.fetch()),
+            ));
+        }
+
+        // a sort into a single partition whose input partitions were
+        // coalesced, see `AddCoalescePartitionsExec`
+        if let Some(coalesce) = input.as_any().downcast_ref::<CoalescePartitionsExec>() {
+            let input = coalesce.input();
+            if !sort.preserve_partitioning()
+                && ordering_satisfy(input.output_ordering().as_deref(), sort.expr())
+            {
+                return Ok(Arc::new(
+                    SortPreservingMergeExec::new(
+                        sort.expr().to_vec(),
+                        input.clone(),
+                        config.batch_size,
+                    )
+                    .with_fetch(sort.fetch()),
+                ));
+            }
+        }
+    } else if let Some(merge) = plan.as_any().downcast_ref::<SortPreservingMergeExec>() {
+        let input = merge.input();
+        if input.output_partitioning().partition_count() == 1
+            && ordering_satisfy(input.output_ordering().as_deref(), merge.expr())
+        {
+            return Ok(input.clone());
+        }
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::{col, PhysicalSortExpr};
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::windows::{create_window_expr, WindowAggExec};
+    use crate::physical_plan::{
+        displayable, window_functions::BuiltInWindowFunction,
+        window_functions::WindowFunction,
+    };
+    use arrow::compute::SortOptions;
+    use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+        ]))
+    }
+
+    fn sort_expr(name: &str, schema: &Schema) -> PhysicalSortExpr {
+        PhysicalSortExpr {
+            expr: col(name, schema).unwrap(),
+            options: SortOptions::default(),
+        }
+    }
+
+    fn memory_exec(partitions: usize) -> Arc<dyn ExecutionPlan> {
+        Arc::new(MemoryExec::try_new(&vec![vec![]; partitions], schema(), None).unwrap())
+    }
+
+    fn optimize(plan: Arc<dyn ExecutionPlan>) -> Vec<String> {
+        let optimized = EnforceSorting::new()
+            .optimize(plan, &ExecutionConfig::new())
+            .unwrap();
+        let formatted = displayable(optimized.as_ref()).indent().to_string();
+        formatted
+            .trim()
+            .lines()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn remove_sort_of_sorted_input() {
+        let schema = schema();
+        let sorted = Arc::new(SortExec::new_with_partitioning(
+            vec![sort_expr("a", &schema), sort_expr("b", &schema)],
+            memory_exec(1),
+            true,
+        ));
+        let plan =
+            Arc::new(SortExec::try_new(vec![sort_expr("a", &schema)], sorted).unwrap());
+        assert_eq!(
+            optimize(plan),
+            vec![
+                "SortExec: [a@0 ASC,b@1 ASC]",
+                "  MemoryExec: partitions=1, partition_sizes=[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn keep_sort_of_other_order() {
+        let schema = schema();
+        let sorted = Arc::new(SortExec::new_with_partitioning(
+            vec![sort_expr("a", &schema)],
+            memory_exec(1),
+            true,
+        ));
+        let plan = Arc::new(
+            SortExec::try_new(
+                vec![sort_expr("a", &schema), sort_expr("b", &schema)],
+                sorted,
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            optimize(plan),
+            vec![
+                "SortExec: [a@0 ASC,b@1 ASC]",
+                "  SortExec: [a@0 ASC]",
+                "    MemoryExec: partitions=1, partition_sizes=[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_sorted_partitions() {
+        let schema = schema();
+        let sorted = Arc::new(SortExec::new_with_partitioning(
+            vec![sort_expr("a", &schema)],
+            memory_exec(2),
+            true,
+        ));
+        let plan = Arc::new(
+            SortExec::try_new(
+                vec![sort_expr("a", &schema)],
+                Arc::new(CoalescePartitionsExec::new(sorted)),
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            optimize(plan),
+            vec![
+                "SortPreservingMergeExec: [a@0 ASC]",
+                "  SortExec: [a@0 ASC]",
+                "    MemoryExec: partitions=2, partition_sizes=[0, 0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_window_input() {
+        let schema = schema();
+        let window_expr = create_window_expr(
+            &WindowFunction::BuiltInWindowFunction(BuiltInWindowFunction::RowNumber),
+            "row_number".to_owned(),
+            &[],
+            &[col("a", &schema).unwrap()],
+            &[sort_expr("b", &schema)],
+            None,
+            schema.as_ref(),
+        )
+        .unwrap();
+        let plan = Arc::new(
+            WindowAggExec::try_new(vec![window_expr], memory_exec(1), schema.clone())
+                .unwrap(),
+        );
+        let optimized = optimize(plan);
+        assert!(optimized[0].starts_with("WindowAggExec: wdw=[row_number"));
+        assert_eq!(
+            optimized[1..],
+            vec![
+                "  SortExec: [a@0 ASC,b@1 ASC]",
+                "    MemoryExec: partitions=1, partition_sizes=[0]",
+            ]
+        );
+    }
+}
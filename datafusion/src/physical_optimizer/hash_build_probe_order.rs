@@ -155,7 +155,14 @@ impl PhysicalOptimizerRule for HashBuildProbeOrder {
 #[cfg(test)]
 mod tests {
     use crate::{
-        physical_plan::{hash_join::PartitionMode, Statistics},
+        logical_plan::Operator,
+        physical_plan::{
+            expressions::{binary, lit},
+            filter::FilterExec,
+            hash_join::PartitionMode,
+            ColumnStatistics, Statistics,
+        },
+        scalar::ScalarValue,
         test::exec::StatisticsExec,
     };
 
@@ -257,6 +264,65 @@ mod tests {
         assert_eq!(swapped_join.right().statistics().num_rows, Some(100000));
     }
 
+    #[tokio::test]
+    async fn test_join_with_swap_on_estimated_statistics() {
+        let (big, small) = create_big_and_small();
+        let big_col = Column::new_with_schema("big_col", &big.schema()).unwrap();
+        let big = Arc::new(StatisticsExec::new(
+            Statistics {
+                column_statistics: Some(vec![ColumnStatistics {
+                    distinct_count: Some(100000),
+                    ..Default::default()
+                }]),
+                ..big.statistics()
+            },
+            big.schema().as_ref().clone(),
+        ));
+        // a single row is estimated to satisfy the filter
+        let filtered_big = Arc::new(
+            FilterExec::try_new(
+                binary(
+                    Arc::new(big_col.clone()),
+                    Operator::Eq,
+                    lit(ScalarValue::Int32(Some(1))),
+                    &big.schema(),
+                )
+                .unwrap(),
+                big,
+            )
+            .unwrap(),
+        );
+
+        let join = HashJoinExec::try_new(
+            Arc::clone(&small),
+            filtered_big,
+            vec![(
+                Column::new_with_schema("small_col", &small.schema()).unwrap(),
+                big_col,
+            )],
+            &JoinType::Inner,
+            PartitionMode::CollectLeft,
+            &false,
+        )
+        .unwrap();
+
+        let optimized_join = HashBuildProbeOrder::new()
+            .optimize(Arc::new(join), &ExecutionConfig::new())
+            .unwrap();
+
+        let swapped_join = optimized_join
+            .as_any()
+            .downcast_ref::<ProjectionExec>()
+            .expect("A proj is required to swap columns back to their original order")
+            .input()
+            .as_any()
+            .downcast_ref::<HashJoinExec>()
+            .expect("The type of the plan should not be changed");
+
+        assert_eq!(swapped_join.left().statistics().num_rows, Some(1));
+        assert_eq!(swapped_join.right().statistics().num_rows, Some(10));
+    }
+
     #[tokio::test]
     async fn test_swap_reverting_projection() {
         let left_schema = Schema::new(vec![
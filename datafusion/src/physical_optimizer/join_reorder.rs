@@ -0,0 +1,590 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Reorders the inputs of trees of inner joins using their statistics
+use std::sync::Arc;
+
+use crate::execution::context::ExecutionConfig;
+use crate::logical_plan::JoinType;
+use crate::physical_plan::cross_join::CrossJoinExec;
+use crate::physical_plan::expressions::Column;
+use crate::physical_plan::hash_join::{HashJoinExec, PartitionMode};
+use crate::physical_plan::join_utils::JoinOn;
+use crate::physical_plan::planner::create_hash_join;
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::{ExecutionPlan, Partitioning, PhysicalExpr};
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::error::Result;
+
+/// JoinReorder flattens the trees of inner hash joins and cross joins into
+/// a set of inputs and equi-join predicates between them, and joins them
+/// again in the order with the smallest estimated number of intermediate
+/// rows. Up to [`EXHAUSTIVE_MAX_INPUTS`] inputs, all the join trees of
+/// connected inputs are considered, the connected sets of inputs being
+/// cross joined last. Larger sets of inputs are joined in a greedy order:
+/// starting from the input with the fewest rows, the input that is connected
+/// by a predicate and gives the smallest estimated output is joined next.
+/// The order stays the same if the number of rows of an input is unknown.
+pub struct JoinReorder {}
+
+impl JoinReorder {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// The largest number of inputs whose join trees are all considered, the
+/// inputs of larger join trees are joined in a greedy order
+pub const EXHAUSTIVE_MAX_INPUTS: usize = 8;
+
+/// A column of an input of a join tree, as the index of the input and the
+/// index of the column in that input
+type InputColumn = (usize, usize);
+
+/// The inputs of a tree of inner joins and the equi-join predicates
+/// between them
+#[derive(Default)]
+struct JoinGraph {
+    inputs: Vec<Arc<dyn ExecutionPlan>>,
+    predicates: Vec<(InputColumn, InputColumn)>,
+}
+
+/// A join tree of some inputs of a [`JoinGraph`]
+#[derive(Clone)]
+struct JoinTree {
+    plan: Arc<dyn ExecutionPlan>,
+    /// The input columns of the output of `plan`
+    columns: Vec<InputColumn>,
+    /// The estimated number of rows of all the joins of `plan`
+    cost: usize,
+}
+
+impl JoinTree {
+    fn num_rows(&self) -> usize {
+        self.plan.statistics().num_rows.unwrap_or(usize::MAX)
+    }
+}
+
+impl JoinGraph {
+    /// Adds the inputs and predicates of the join tree `plan` to the graph,
+    /// and returns the input columns of the output of `plan`
+    fn flatten(&mut self, plan: &Arc<dyn ExecutionPlan>) -> Vec<InputColumn> {
+        if let Some(join) = plan.as_any().downcast_ref::<HashJoinExec>() {
+            if is_reorderable(join) {
+                let left = self.flatten(strip_join_repartition(join, join.left()));
+                let right = self.flatten(strip_join_repartition(join, join.right()));
+                self.predicates.extend(
+                    join.on()
+                        .iter()
+                        .map(|(l, r)| (left[l.index()], right[r.index()])),
+                );
+                return left.into_iter().chain(right).collect();
+            }
+        } else if let Some(join) = plan.as_any().downcast_ref::<CrossJoinExec>() {
+            let left = self.flatten(join.left());
+            let right = self.flatten(join.right());
+            return left.into_iter().chain(right).collect();
+        }
+
+        let input = self.inputs.len();
+        self.inputs.push(plan.clone());
+        (0..plan.schema().fields().len())
+            .map(|column| (input, column))
+            .collect()
+    }
+
+    /// Joins the inputs in the order with the fewest estimated intermediate
+    /// rows, or returns `None` if the number of rows of an input is unknown
+    fn reorder(&self, config: &ExecutionConfig) -> Result<Option<JoinTree>> {
+        if self
+            .inputs
+            .iter()
+            .any(|input| input.statistics().num_rows.is_none())
+        {
+            return Ok(None);
+        }
+        if self.inputs.len() <= EXHAUSTIVE_MAX_INPUTS {
+            self.reorder_exhaustive(config).map(Some)
+        } else {
+            self.reorder_greedy(config).map(Some)
+        }
+    }
+
+    /// Finds the cheapest join tree of each connected set of inputs out of
+    /// the cheapest join trees of its connected subsets, smallest sets first,
+    /// and cross joins the join trees of the connected components of the
+    /// graph
+    fn reorder_exhaustive(&self, config: &ExecutionConfig) -> Result<JoinTree> {
+        // the sets of inputs are bitmaps of the indices of the inputs
+        let neighbors = self.neighbors();
+        let mut best: Vec<Option<JoinTree>> = vec![None; 1 << self.inputs.len()];
+        for input in 0..self.inputs.len() {
+            best[1 << input] = Some(self.leaf(input));
+        }
+        for set in 1..best.len() {
+            if set.count_ones() < 2 || reachable(set, &neighbors) != set {
+                continue;
+            }
+            // each split of `set` is visited once, with the lowest input on
+            // the left, and only the connected subsets have a join tree
+            let lowest = set & set.wrapping_neg();
+            let mut set_best: Option<JoinTree> = None;
+            let mut left = (set - 1) & set;
+            while left > 0 {
+                if left & lowest != 0 {
+                    if let (Some(l), Some(r)) = (&best[left], &best[set ^ left]) {
+                        if let Some(join) = self.join(l, r, config)? {
+                            if set_best.as_ref().map_or(true, |b| join.cost < b.cost) {
+                                set_best = Some(join);
+                            }
+                        }
+                    }
+                }
+                left = (left - 1) & set;
+            }
+            best[set] = set_best;
+        }
+
+        let mut components = vec![];
+        let mut remaining = best.len() - 1;
+        while remaining != 0 {
+            let component = reachable(remaining, &neighbors);
+            components.push(best[component].take().unwrap());
+            remaining ^= component;
+        }
+        components.sort_by_key(|tree| tree.num_rows());
+        let mut components = components.into_iter();
+        let first = components.next().unwrap();
+        components.try_fold(first, cross_join)
+    }
+
+    /// Joins the inputs in a greedy order
+    fn reorder_greedy(&self, config: &ExecutionConfig) -> Result<JoinTree> {
+        let smallest = |trees: &[JoinTree]| {
+            (0..trees.len())
+                .min_by_key(|position| trees[*position].num_rows())
+                .unwrap()
+        };
+
+        let mut remaining: Vec<JoinTree> = (0..self.inputs.len())
+            .map(|input| self.leaf(input))
+            .collect();
+        let mut tree = remaining.remove(smallest(&remaining));
+        while !remaining.is_empty() {
+            let mut best: Option<(usize, JoinTree)> = None;
+            for (position, input) in remaining.iter().enumerate() {
+                if let Some(join) = self.join(&tree, input, config)? {
+                    if best.as_ref().map_or(true, |(_, b)| join.cost < b.cost) {
+                        best = Some((position, join));
+                    }
+                }
+            }
+            // avoid cross joins as long as a predicate connects an input
+            tree = match best {
+                Some((position, join)) => {
+                    remaining.remove(position);
+                    join
+                }
+                None => {
+                    let input = remaining.remove(smallest(&remaining));
+                    cross_join(tree, input)?
+                }
+            };
+        }
+        Ok(tree)
+    }
+
+    fn leaf(&self, input: usize) -> JoinTree {
+        let plan = self.inputs[input].clone();
+        let columns = (0..plan.schema().fields().len())
+            .map(|column| (input, column))
+            .collect();
+        JoinTree {
+            plan,
+            columns,
+            cost: 0,
+        }
+    }
+
+    /// The set of the inputs connected by a predicate to each input
+    fn neighbors(&self) -> Vec<usize> {
+        let mut neighbors = vec![0; self.inputs.len()];
+        for ((a, _), (b, _)) in &self.predicates {
+            neighbors[*a] |= 1 << b;
+            neighbors[*b] |= 1 << a;
+        }
+        neighbors
+    }
+
+    /// Creates the hash join of `left` and `right`, or returns `None` if no
+    /// predicate connects them
+    fn join(
+        &self,
+        left: &JoinTree,
+        right: &JoinTree,
+        config: &ExecutionConfig,
+    ) -> Result<Option<JoinTree>> {
+        let on = self.join_on(left, right);
+        if on.is_empty() {
+            return Ok(None);
+        }
+        let plan = create_hash_join(
+            left.plan.clone(),
+            right.plan.clone(),
+            on,
+            &JoinType::Inner,
+            &false,
+            config,
+        )?;
+        Ok(Some(joined(plan, left, right)))
+    }
+
+    /// The predicates between the columns of `left` and `right`
+    fn join_on(&self, left: &JoinTree, right: &JoinTree) -> JoinOn {
+        let left_schema = left.plan.schema();
+        let right_schema = right.plan.schema();
+        let position = |tree: &JoinTree, column: &InputColumn| {
+            tree.columns.iter().position(|c| c == column)
+        };
+        self.predicates
+            .iter()
+            .filter_map(|(a, b)| {
+                let (l, r) = match (position(left, a), position(right, b)) {
+                    (Some(l), Some(r)) => (l, r),
+                    _ => (position(left, b)?, position(right, a)?),
+                };
+                Some((
+                    Column::new(left_schema.field(l).name(), l),
+                    Column::new(right_schema.field(r).name(), r),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Returns the inputs of `set` reachable from its lowest input through the
+/// predicates, where `neighbors` are the neighbors of each input
+fn reachable(set: usize, neighbors: &[usize]) -> usize {
+    let mut reached = set & set.wrapping_neg();
+    loop {
+        let next = (0..neighbors.len())
+            .filter(|input| reached & (1 << input) != 0)
+            .fold(reached, |reached, input| reached | neighbors[input])
+            & set;
+        if next == reached {
+            return reached;
+        }
+        reached = next;
+    }
+}
+
+fn cross_join(left: JoinTree, right: JoinTree) -> Result<JoinTree> {
+    let plan = Arc::new(CrossJoinExec::try_new(
+        left.plan.clone(),
+        right.plan.clone(),
+    )?);
+    Ok(joined(plan, &left, &right))
+}
+
+/// The join tree of the join `plan` of `left` and `right`
+fn joined(plan: Arc<dyn ExecutionPlan>, left: &JoinTree, right: &JoinTree) -> JoinTree {
+    let columns = left.columns.iter().chain(&right.columns).cloned().collect();
+    let mut tree = JoinTree {
+        plan,
+        columns,
+        cost: 0,
+    };
+    tree.cost = left
+        .cost
+        .saturating_add(right.cost)
+        .saturating_add(tree.num_rows());
+    tree
+}
+
+fn is_reorderable(join: &HashJoinExec) -> bool {
+    *join.join_type() == JoinType::Inner && !*join.null_equals_null()
+}
+
+/// Returns the input of the hash repartitioning the planner adds under the
+/// partitioned joins, as the keys change with the order of the joins
+fn strip_join_repartition<'a>(
+    join: &HashJoinExec,
+    child: &'a Arc<dyn ExecutionPlan>,
+) -> &'a Arc<dyn ExecutionPlan> {
+    match child.as_any().downcast_ref::<RepartitionExec>() {
+        Some(repartition)
+            if *join.partition_mode() == PartitionMode::Partitioned
+                && matches!(repartition.partitioning(), Partitioning::Hash(_, _)) =>
+        {
+            repartition.input()
+        }
+        _ => child,
+    }
+}
+
+fn is_join_tree(plan: &Arc<dyn ExecutionPlan>) -> bool {
+    match plan.as_any().downcast_ref::<HashJoinExec>() {
+        Some(join) => is_reorderable(join),
+        None => plan.as_any().is::<CrossJoinExec>(),
+    }
+}
+
+impl PhysicalOptimizerRule for JoinReorder {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        execution_config: &ExecutionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if !is_join_tree(&plan) {
+            return optimize_children(self, plan, execution_config);
+        }
+        let mut graph = JoinGraph::default();
+        let output_columns = graph.flatten(&plan);
+        // two inputs are ordered by `HashBuildProbeOrder`
+        if graph.inputs.len() < 3 {
+            return optimize_children(self, plan, execution_config);
+        }
+        graph.inputs = graph
+            .inputs
+            .into_iter()
+            .map(|input| self.optimize(input, execution_config))
+            .collect::<Result<_>>()?;
+        let JoinTree {
+            plan: join,
+            columns,
+            ..
+        } = match graph.reorder(execution_config)? {
+            Some(reordered) => reordered,
+            None => return optimize_children(self, plan, execution_config),
+        };
+        if columns == output_columns {
+            return Ok(join);
+        }
+
+        // restore the order and names of the columns of the original plan
+        let schema = plan.schema();
+        let join_schema = join.schema();
+        let expr = output_columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let index = columns.iter().position(|c| c == column).unwrap();
+                (
+                    Arc::new(Column::new(join_schema.field(index).name(), index))
+                        as Arc<dyn PhysicalExpr>,
+                    schema.field(i).name().to_owned(),
+                )
+            })
+            .collect();
+        Ok(Arc::new(ProjectionExec::try_new(expr, join)?))
+    }
+
+    fn name(&self) -> &str {
+        "join_reorder"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::displayable;
+    use crate::physical_plan::{ColumnStatistics, Statistics};
+    use crate::test::exec::StatisticsExec;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    /// A table with `num_rows` rows of the columns `columns`, each with
+    /// `num_rows` distinct values
+    fn table(columns: &[&str], num_rows: usize) -> Arc<dyn ExecutionPlan> {
+        let columns = columns
+            .iter()
+            .map(|name| (*name, num_rows))
+            .collect::<Vec<_>>();
+        table_with_distinct_counts(&columns, num_rows)
+    }
+
+    /// A table with `num_rows` rows of the columns `columns`, given with
+    /// their number of distinct values
+    fn table_with_distinct_counts(
+        columns: &[(&str, usize)],
+        num_rows: usize,
+    ) -> Arc<dyn ExecutionPlan> {
+        Arc::new(StatisticsExec::new(
+            Statistics {
+                num_rows: Some(num_rows),
+                column_statistics: Some(
+                    columns
+                        .iter()
+                        .map(|(_, distinct_count)| ColumnStatistics {
+                            distinct_count: Some(*distinct_count),
+                            ..Default::default()
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            },
+            Schema::new(
+                columns
+                    .iter()
+                    .map(|(name, _)| Field::new(name, DataType::Int32, false))
+                    .collect(),
+            ),
+        ))
+    }
+
+    fn join(
+        left: Arc<dyn ExecutionPlan>,
+        right: Arc<dyn ExecutionPlan>,
+        on: &[(&str, &str)],
+    ) -> Arc<dyn ExecutionPlan> {
+        let on = on
+            .iter()
+            .map(|(l, r)| {
+                (
+                    Column::new_with_schema(l, &left.schema()).unwrap(),
+                    Column::new_with_schema(r, &right.schema()).unwrap(),
+                )
+            })
+            .collect();
+        Arc::new(
+            HashJoinExec::try_new(
+                left,
+                right,
+                on,
+                &JoinType::Inner,
+                PartitionMode::CollectLeft,
+                &false,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn optimize(plan: Arc<dyn ExecutionPlan>) -> Vec<String> {
+        let config = ExecutionConfig::new().with_target_partitions(1);
+        let optimized = JoinReorder::new().optimize(plan, &config).unwrap();
+        format_plan(optimized)
+    }
+
+    fn reorder_greedy(plan: Arc<dyn ExecutionPlan>) -> Vec<String> {
+        let config = ExecutionConfig::new().with_target_partitions(1);
+        let mut graph = JoinGraph::default();
+        graph.flatten(&plan);
+        format_plan(graph.reorder_greedy(&config).unwrap().plan)
+    }
+
+    fn format_plan(plan: Arc<dyn ExecutionPlan>) -> Vec<String> {
+        let formatted = displayable(plan.as_ref()).indent().to_string();
+        formatted
+            .trim()
+            .lines()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    /// (customers x products) JOIN sales
+    fn sales_plan() -> Arc<dyn ExecutionPlan> {
+        let customers = table(&["c_id"], 100);
+        let products = table(&["p_id"], 10);
+        let sales = table(&["s_c_id", "s_p_id"], 10000);
+        join(
+            Arc::new(CrossJoinExec::try_new(customers, products).unwrap()),
+            sales,
+            &[("c_id", "s_c_id"), ("p_id", "s_p_id")],
+        )
+    }
+
+    #[test]
+    fn avoid_cross_join() {
+        assert_eq!(
+            optimize(sales_plan()),
+            vec![
+                "HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(Column { name: \"c_id\", index: 0 }, Column { name: \"s_c_id\", index: 1 })]",
+                "  StatisticsExec: col_count=1, row_count=Some(100)",
+                "  HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(Column { name: \"p_id\", index: 0 }, Column { name: \"s_p_id\", index: 1 })]",
+                "    StatisticsExec: col_count=1, row_count=Some(10)",
+                "    StatisticsExec: col_count=2, row_count=Some(10000)",
+            ]
+        );
+    }
+
+    #[test]
+    fn greedy_avoid_cross_join() {
+        assert_eq!(
+            reorder_greedy(sales_plan()),
+            vec![
+                "HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(Column { name: \"s_c_id\", index: 1 }, Column { name: \"c_id\", index: 0 })]",
+                "  HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(Column { name: \"p_id\", index: 0 }, Column { name: \"s_p_id\", index: 1 })]",
+                "    StatisticsExec: col_count=1, row_count=Some(10)",
+                "    StatisticsExec: col_count=2, row_count=Some(10000)",
+                "  StatisticsExec: col_count=1, row_count=Some(100)",
+            ]
+        );
+    }
+
+    #[test]
+    fn bushy_join() {
+        // a JOIN b and c JOIN d are small, while b JOIN c is large: joining
+        // a, b, c and then d in a greedy order has a large intermediate result
+        let a = table_with_distinct_counts(&[("a", 10)], 10);
+        let b = table_with_distinct_counts(&[("b1", 1000), ("b2", 10)], 1000);
+        let c = table_with_distinct_counts(&[("c1", 10), ("c2", 1000)], 1000);
+        let d = table_with_distinct_counts(&[("d", 10)], 10);
+        let plan = join(
+            join(join(a, b, &[("a", "b1")]), c, &[("b2", "c1")]),
+            d,
+            &[("c2", "d")],
+        );
+
+        assert_eq!(
+            optimize(plan),
+            vec![
+                "HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(Column { name: \"b2\", index: 2 }, Column { name: \"c1\", index: 0 })]",
+                "  HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(Column { name: \"a\", index: 0 }, Column { name: \"b1\", index: 0 })]",
+                "    StatisticsExec: col_count=1, row_count=Some(10)",
+                "    StatisticsExec: col_count=2, row_count=Some(1000)",
+                "  HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(Column { name: \"c2\", index: 1 }, Column { name: \"d\", index: 0 })]",
+                "    StatisticsExec: col_count=2, row_count=Some(1000)",
+                "    StatisticsExec: col_count=1, row_count=Some(10)",
+            ]
+        );
+    }
+
+    #[test]
+    fn keep_order_without_statistics() {
+        let a = table(&["a"], 10);
+        let b = table(&["b"], 10);
+        let c: Arc<dyn ExecutionPlan> = Arc::new(StatisticsExec::new(
+            Statistics::default(),
+            Schema::new(vec![Field::new("c", DataType::Int32, false)]),
+        ));
+        let plan = join(join(a, b, &[("a", "b")]), c, &[("a", "c")]);
+
+        assert_eq!(
+            optimize(plan),
+            vec![
+                "HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(Column { name: \"a\", index: 0 }, Column { name: \"c\", index: 0 })]",
+                "  HashJoinExec: mode=CollectLeft, join_type=Inner, on=[(Column { name: \"a\", index: 0 }, Column { name: \"b\", index: 0 })]",
+                "    StatisticsExec: col_count=1, row_count=Some(10)",
+                "    StatisticsExec: col_count=1, row_count=Some(10)",
+                "  StatisticsExec: col_count=1, row_count=None",
+            ]
+        );
+    }
+}
@@ -20,9 +20,13 @@
 
 pub mod aggregate_statistics;
 pub mod coalesce_batches;
+pub mod enforce_sorting;
 pub mod hash_build_probe_order;
+pub mod join_reorder;
 pub mod merge_exec;
 pub mod optimizer;
+pub mod partial_aggregate_push_down;
 pub mod pruning;
 pub mod repartition;
+pub mod runtime_filters;
 mod utils;
@@ -0,0 +1,186 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Pushes the partial aggregations into the branches of unions
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::error::Result;
+use crate::execution::context::ExecutionConfig;
+use crate::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use crate::physical_plan::filter::FilterExec;
+use crate::physical_plan::hash_aggregate::{AggregateMode, HashAggregateExec};
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::union::UnionExec;
+use crate::physical_plan::ExecutionPlan;
+
+/// Optimizer that replaces a partial `HashAggregateExec` above a
+/// `UnionExec` by a `UnionExec` of partial aggregations of each of its
+/// branches, so that the rows of every branch are reduced before the
+/// operators planned above the union, e.g. repartitions and shuffles, see
+/// them. The projections, filters and batch coalescing between the
+/// aggregation and the union are applied to each branch.
+pub struct PartialAggregatePushDown {}
+
+impl PartialAggregatePushDown {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for PartialAggregatePushDown {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        execution_config: &ExecutionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let plan = optimize_children(self, plan, execution_config)?;
+        let aggregate = match plan.as_any().downcast_ref::<HashAggregateExec>() {
+            Some(aggregate) if *aggregate.mode() == AggregateMode::Partial => aggregate,
+            _ => return Ok(plan),
+        };
+        match union_branches(aggregate.input())? {
+            Some(branches) => {
+                let branches = branches
+                    .into_iter()
+                    .map(|branch| {
+                        let input_schema = branch.schema();
+                        Ok(Arc::new(HashAggregateExec::try_new(
+                            AggregateMode::Partial,
+                            aggregate.group_expr().to_vec(),
+                            aggregate.aggr_expr().to_vec(),
+                            branch,
+                            input_schema,
+                        )?) as Arc<dyn ExecutionPlan>)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(UnionExec::new(branches)))
+            }
+            None => Ok(plan),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "partial_aggregate_push_down"
+    }
+}
+
+/// Returns the branches of the union below the row by row operators `plan`,
+/// each one with these operators applied to it
+fn union_branches(
+    plan: &Arc<dyn ExecutionPlan>,
+) -> Result<Option<Vec<Arc<dyn ExecutionPlan>>>> {
+    let plan_any = plan.as_any();
+    if let Some(union) = plan_any.downcast_ref::<UnionExec>() {
+        return Ok(Some(union.children()));
+    }
+    let row_by_row = plan_any.downcast_ref::<ProjectionExec>().is_some()
+        || plan_any.downcast_ref::<FilterExec>().is_some()
+        || plan_any.downcast_ref::<CoalesceBatchesExec>().is_some();
+    if !row_by_row {
+        return Ok(None);
+    }
+    match union_branches(&plan.children()[0])? {
+        Some(branches) => Ok(Some(
+            branches
+                .into_iter()
+                .map(|branch| plan.clone().with_new_children(vec![branch]))
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+    use crate::physical_plan::expressions::{col, Count, Sum};
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::{collect, displayable, AggregateExpr};
+    use crate::test::{build_table_i32, columns};
+    use arrow::datatypes::DataType;
+
+    #[tokio::test]
+    async fn push_partial_aggregate_into_union_branches() -> Result<()> {
+        let branch = || {
+            let batch = build_table_i32(
+                ("a", &vec![1, 2, 1]),
+                ("b", &vec![10, 20, 30]),
+                ("c", &vec![0, 0, 0]),
+            );
+            Arc::new(
+                MemoryExec::try_new(&[vec![batch.clone()]], batch.schema(), None)
+                    .unwrap(),
+            ) as Arc<dyn ExecutionPlan>
+        };
+        let union = Arc::new(UnionExec::new(vec![branch(), branch()]));
+        let schema = union.schema();
+        let aggr_expr: Vec<Arc<dyn AggregateExpr>> = vec![
+            Arc::new(Sum::new(col("b", &schema)?, "SUM(b)", DataType::Int64)),
+            Arc::new(Count::new(col("b", &schema)?, "COUNT(b)", DataType::UInt64)),
+        ];
+        let partial = Arc::new(HashAggregateExec::try_new(
+            AggregateMode::Partial,
+            vec![(col("a", &schema)?, "a".to_string())],
+            aggr_expr.clone(),
+            union,
+            schema.clone(),
+        )?);
+        let final_aggregate = Arc::new(HashAggregateExec::try_new(
+            AggregateMode::Final,
+            vec![(col("a", &partial.schema())?, "a".to_string())],
+            aggr_expr,
+            Arc::new(CoalescePartitionsExec::new(partial)),
+            schema,
+        )?);
+
+        let optimized = PartialAggregatePushDown::new()
+            .optimize(final_aggregate.clone(), &ExecutionConfig::new())?;
+        let formatted = displayable(optimized.as_ref()).indent().to_string();
+        let actual: Vec<&str> = formatted.trim().lines().collect();
+        let expected = vec![
+            "HashAggregateExec: mode=Final, gby=[a@0 as a], aggr=[SUM(b), COUNT(b)]",
+            "  CoalescePartitionsExec",
+            "    UnionExec",
+            "      HashAggregateExec: mode=Partial, gby=[a@0 as a], aggr=[SUM(b), COUNT(b)]",
+            "        MemoryExec: partitions=1, partition_sizes=[1]",
+            "      HashAggregateExec: mode=Partial, gby=[a@0 as a], aggr=[SUM(b), COUNT(b)]",
+            "        MemoryExec: partitions=1, partition_sizes=[1]",
+        ];
+        assert_eq!(expected, actual);
+        assert_eq!(optimized.schema(), final_aggregate.schema());
+
+        let batches = collect(optimized).await?;
+        assert_eq!(
+            columns(&batches[0].schema()),
+            vec!["a", "SUM(b)", "COUNT(b)"]
+        );
+        let expected = vec![
+            "+---+--------+----------+",
+            "| a | SUM(b) | COUNT(b) |",
+            "+---+--------+----------+",
+            "| 1 | 80     | 4        |",
+            "| 2 | 40     | 2        |",
+            "+---+--------+----------+",
+        ];
+        crate::assert_batches_sorted_eq!(expected, &batches);
+        Ok(())
+    }
+}
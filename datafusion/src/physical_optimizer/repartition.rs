@@ -130,6 +130,7 @@ mod tests {
                     batch_size: 2048,
                     limit: None,
                     table_partition_cols: vec![],
+                    output_ordering: None,
                 },
                 None,
             )),
@@ -172,6 +173,7 @@ mod tests {
                         batch_size: 2048,
                         limit: None,
                         table_partition_cols: vec![],
+                        output_ordering: None,
                     },
                     None,
                 )),
@@ -0,0 +1,317 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! AddRuntimeFilters makes the hash joins publish the keys of their build
+//! side to the filters and parquet scans of their probe side
+use std::sync::Arc;
+
+use super::optimizer::PhysicalOptimizerRule;
+use super::utils::optimize_children;
+use crate::error::Result;
+use crate::execution::context::ExecutionConfig;
+use crate::logical_plan::JoinType;
+use crate::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use crate::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use crate::physical_plan::expressions::Column;
+use crate::physical_plan::file_format::ParquetExec;
+use crate::physical_plan::filter::FilterExec;
+use crate::physical_plan::hash_join::{HashJoinExec, PartitionMode};
+use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::repartition::RepartitionExec;
+use crate::physical_plan::runtime_filter::{
+    supports_key_type, ProbeFilter, ProbeFilterUse, RuntimeFilter,
+};
+use crate::physical_plan::ExecutionPlan;
+
+/// Optimizer rule that attaches a [`RuntimeFilter`] to the hash joins that
+/// collect their build side into a single partition, and to the
+/// `FilterExec`s and `ParquetExec`s of their probe side that produce the
+/// join keys, looking through projections, repartitions and the inputs of
+/// other hash joins.
+///
+/// The rows of the probe side of inner, left, semi and anti joins that do
+/// not match any build row are not used, so that the probe side can skip
+/// the rows whose keys are not in the build side. The runtime filter is
+/// published before the probe side is executed.
+pub struct AddRuntimeFilters {}
+
+impl AddRuntimeFilters {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PhysicalOptimizerRule for AddRuntimeFilters {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &ExecutionConfig,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if !config.runtime_filters {
+            return Ok(plan);
+        }
+        let plan = optimize_children(self, plan, config)?;
+        let join = match plan.as_any().downcast_ref::<HashJoinExec>() {
+            Some(join) => join,
+            None => return Ok(plan),
+        };
+        if join.partition_mode() != &PartitionMode::CollectLeft
+            || matches!(join.join_type(), JoinType::Right | JoinType::Full)
+            || *join.null_equals_null()
+            || join.runtime_filter().is_some()
+        {
+            return Ok(plan);
+        }
+
+        let left_schema = join.left().schema();
+        let right_schema = join.right().schema();
+        let keys: Vec<(usize, Column)> = join
+            .on()
+            .iter()
+            .enumerate()
+            .filter(|(_, (left, right))| {
+                let data_type = left_schema.field(left.index()).data_type();
+                data_type == right_schema.field(right.index()).data_type()
+                    && supports_key_type(data_type)
+            })
+            .map(|(key, (_, right))| (key, right.clone()))
+            .collect();
+
+        let runtime_filter = Arc::new(RuntimeFilter::new());
+        match push_down(join.right(), &runtime_filter, &keys)? {
+            Some(right) => Ok(Arc::new(
+                HashJoinExec::try_new(
+                    join.left().clone(),
+                    right,
+                    join.on().to_vec(),
+                    join.join_type(),
+                    *join.partition_mode(),
+                    join.null_equals_null(),
+                )?
+                .with_runtime_filter(runtime_filter),
+            )),
+            None => Ok(plan),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "add_runtime_filters"
+    }
+}
+
+/// Applies `runtime_filter` on the join keys held by the `keys` columns of
+/// `plan` to the nodes of `plan` that produce them, and returns `None` if
+/// there is no such node
+fn push_down(
+    plan: &Arc<dyn ExecutionPlan>,
+    runtime_filter: &Arc<RuntimeFilter>,
+    keys: &[(usize, Column)],
+) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    let probe_filter =
+        |filter_use| ProbeFilter::new(runtime_filter.clone(), keys.to_vec(), filter_use);
+
+    let any = plan.as_any();
+    if let Some(filter) = any.downcast_ref::<FilterExec>() {
+        let input = push_down(filter.input(), runtime_filter, keys)?
+            .unwrap_or_else(|| filter.input().clone());
+        let mut runtime_filters = filter.runtime_filters().to_vec();
+        runtime_filters.push(probe_filter(ProbeFilterUse::Rows));
+        Ok(Some(Arc::new(
+            FilterExec::try_new(filter.predicate().clone(), input)?
+                .with_runtime_filters(runtime_filters),
+        )))
+    } else if let Some(parquet) = any.downcast_ref::<ParquetExec>() {
+        let mut runtime_filters = parquet.runtime_filters().to_vec();
+        runtime_filters.push(probe_filter(ProbeFilterUse::Pruning));
+        Ok(Some(Arc::new(
+            parquet.clone().with_runtime_filters(runtime_filters),
+        )))
+    } else if let Some(projection) = any.downcast_ref::<ProjectionExec>() {
+        let keys: Vec<(usize, Column)> = keys
+            .iter()
+            .filter_map(|(key, column)| {
+                let expr = &projection.expr()[column.index()].0;
+                let column = expr.as_any().downcast_ref::<Column>()?;
+                Some((*key, column.clone()))
+            })
+            .collect();
+        push_down(projection.input(), runtime_filter, &keys)?
+            .map(|input| plan.with_new_children(vec![input]))
+            .transpose()
+    } else if any.is::<CoalesceBatchesExec>()
+        || any.is::<CoalescePartitionsExec>()
+        || any.is::<RepartitionExec>()
+    {
+        push_down(&plan.children()[0], runtime_filter, keys)?
+            .map(|input| plan.with_new_children(vec![input]))
+            .transpose()
+    } else if let Some(join) = any.downcast_ref::<HashJoinExec>() {
+        // the output rows of a join hold the keys of the input rows they are
+        // built from, or nulls that do not match any key either
+        let num_left_columns = join.left().schema().fields().len();
+        let (left_keys, right_keys): (Vec<_>, Vec<_>) = keys
+            .iter()
+            .cloned()
+            .partition(|(_, column)| column.index() < num_left_columns);
+        let right_keys: Vec<(usize, Column)> = right_keys
+            .into_iter()
+            .map(|(key, column)| {
+                let index = column.index() - num_left_columns;
+                (key, Column::new(column.name(), index))
+            })
+            .collect();
+        let left = push_down(join.left(), runtime_filter, &left_keys)?;
+        let right = push_down(join.right(), runtime_filter, &right_keys)?;
+        if left.is_none() && right.is_none() {
+            return Ok(None);
+        }
+        let children = vec![
+            left.unwrap_or_else(|| join.left().clone()),
+            right.unwrap_or_else(|| join.right().clone()),
+        ];
+        plan.with_new_children(children).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_sorted_eq;
+    use crate::logical_plan::Operator;
+    use crate::physical_plan::expressions::{binary, col, lit};
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::{collect, PhysicalExpr};
+    use crate::scalar::ScalarValue;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+
+    fn memory_exec(names: [&str; 2], values: Vec<i32>) -> Arc<dyn ExecutionPlan> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(names[0], DataType::Int32, true),
+            Field::new(names[1], DataType::Int32, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(values.clone())),
+                Arc::new(Int32Array::from(values)),
+            ],
+        )
+        .unwrap();
+        Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap())
+    }
+
+    /// A join of a build side with keys 3 and 5 to a filtered probe side
+    /// with keys 1 to 10
+    fn join(join_type: JoinType, mode: PartitionMode) -> Arc<dyn ExecutionPlan> {
+        let build = memory_exec(["a", "x"], vec![3, 5]);
+        let probe = memory_exec(["b", "y"], (1..=10).collect());
+        let predicate: Arc<dyn PhysicalExpr> = binary(
+            col("y", &probe.schema()).unwrap(),
+            Operator::Gt,
+            lit(ScalarValue::Int32(Some(0))),
+            &probe.schema(),
+        )
+        .unwrap();
+        let probe = Arc::new(FilterExec::try_new(predicate, probe).unwrap());
+        let on = vec![(
+            Column::new_with_schema("a", &build.schema()).unwrap(),
+            Column::new_with_schema("b", &probe.schema()).unwrap(),
+        )];
+        Arc::new(
+            HashJoinExec::try_new(build, probe, on, &join_type, mode, &false).unwrap(),
+        )
+    }
+
+    fn optimize(plan: Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan> {
+        AddRuntimeFilters::new()
+            .optimize(plan, &ExecutionConfig::new())
+            .unwrap()
+    }
+
+    fn probe_filter(plan: &Arc<dyn ExecutionPlan>) -> &FilterExec {
+        plan.as_any()
+            .downcast_ref::<HashJoinExec>()
+            .unwrap()
+            .right()
+            .as_any()
+            .downcast_ref::<FilterExec>()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn filter_probe_side() -> Result<()> {
+        let plan = optimize(join(JoinType::Inner, PartitionMode::CollectLeft));
+        assert_eq!(probe_filter(&plan).runtime_filters().len(), 1);
+
+        let batches = collect(plan.clone()).await?;
+        let expected = vec![
+            "+---+---+---+---+",
+            "| a | x | b | y |",
+            "+---+---+---+---+",
+            "| 3 | 3 | 3 | 3 |",
+            "| 5 | 5 | 5 | 5 |",
+            "+---+---+---+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        // the probe rows without match were filtered out before the join
+        let metrics = probe_filter(&plan).metrics().unwrap();
+        assert_eq!(metrics.output_rows(), Some(2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn filter_probe_side_of_semi_join() -> Result<()> {
+        let plan = optimize(join(JoinType::Semi, PartitionMode::CollectLeft));
+        assert_eq!(probe_filter(&plan).runtime_filters().len(), 1);
+
+        let batches = collect(plan).await?;
+        let expected = vec![
+            "+---+---+",
+            "| a | x |",
+            "+---+---+",
+            "| 3 | 3 |",
+            "| 5 | 5 |",
+            "+---+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+        Ok(())
+    }
+
+    #[test]
+    fn keep_probe_rows_of_right_joins() {
+        let plan = optimize(join(JoinType::Right, PartitionMode::CollectLeft));
+        assert!(probe_filter(&plan).runtime_filters().is_empty());
+
+        let plan = optimize(join(JoinType::Full, PartitionMode::CollectLeft));
+        assert!(probe_filter(&plan).runtime_filters().is_empty());
+    }
+
+    #[test]
+    fn skip_partitioned_joins() {
+        let plan = optimize(join(JoinType::Inner, PartitionMode::Partitioned));
+        assert!(probe_filter(&plan).runtime_filters().is_empty());
+    }
+}
@@ -0,0 +1,369 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Implementations of the clauses of aggregate calls, e.g.
+//! `SUM(c) FILTER (WHERE c > 0)` or `ARRAY_AGG(c ORDER BY t)`, that wrap the
+//! aggregate expression they apply to.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, BooleanArray};
+use arrow::compute::{self, lexsort_to_indices, SortColumn, SortOptions};
+use arrow::datatypes::{DataType, Field, Schema};
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::expressions::{format_state_name, PhysicalSortExpr};
+use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
+use crate::scalar::ScalarValue;
+
+/// Aggregate expression with a `FILTER (WHERE ...)` clause, which only passes
+/// the rows that satisfy the filter to the aggregate it wraps.
+#[derive(Debug)]
+pub struct FilteredAggregate {
+    inner: Arc<dyn AggregateExpr>,
+    filter: Arc<dyn PhysicalExpr>,
+}
+
+impl FilteredAggregate {
+    /// Create a new aggregation of the rows that satisfy the boolean `filter`
+    pub fn new(inner: Arc<dyn AggregateExpr>, filter: Arc<dyn PhysicalExpr>) -> Self {
+        Self { inner, filter }
+    }
+}
+
+impl AggregateExpr for FilteredAggregate {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        self.inner.field()
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(FilteredAccumulator {
+            inner: self.inner.create_accumulator()?,
+        }))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        self.inner.state_fields()
+    }
+
+    /// the expressions of the wrapped aggregate, followed by the filter
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        let mut expressions = self.inner.expressions();
+        expressions.push(self.filter.clone());
+        expressions
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+#[derive(Debug)]
+struct FilteredAccumulator {
+    inner: Box<dyn Accumulator>,
+}
+
+impl Accumulator for FilteredAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        self.inner.state()
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        match values.split_last() {
+            Some((ScalarValue::Boolean(Some(true)), values)) => self.inner.update(values),
+            _ => Ok(()),
+        }
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let (filter, values) = match values.split_last() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+        let filter = filter
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .ok_or_else(|| {
+                DataFusionError::Internal(format!(
+                    "Aggregate filter must be boolean, not {:?}",
+                    filter.data_type()
+                ))
+            })?;
+        // rows whose filter is NULL are skipped, just as the false ones
+        let filter = if filter.null_count() > 0 {
+            filter
+                .iter()
+                .map(|v| Some(v.unwrap_or(false)))
+                .collect::<BooleanArray>()
+        } else {
+            BooleanArray::from(filter.data().clone())
+        };
+        let values = values
+            .iter()
+            .map(|array| compute::filter(array.as_ref(), &filter))
+            .collect::<arrow::error::Result<Vec<_>>>()?;
+        self.inner.update_batch(&values)
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        self.inner.merge(states)
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        self.inner.merge_batch(states)
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        self.inner.evaluate()
+    }
+}
+
+/// Aggregate expression with an `ORDER BY` clause, which collects all of its
+/// rows and passes them to the aggregate it wraps in the requested order, for
+/// aggregates whose result depends on the order of their input.
+#[derive(Debug)]
+pub struct OrderedAggregate {
+    inner: Arc<dyn AggregateExpr>,
+    order_by: Vec<PhysicalSortExpr>,
+    /// The types of the arguments of `inner`, followed by those of the sort keys
+    data_types: Vec<DataType>,
+}
+
+impl OrderedAggregate {
+    /// Create a new aggregation of the rows sorted by `order_by`
+    pub fn try_new(
+        inner: Arc<dyn AggregateExpr>,
+        order_by: Vec<PhysicalSortExpr>,
+        input_schema: &Schema,
+    ) -> Result<Self> {
+        let data_types = inner
+            .expressions()
+            .iter()
+            .chain(order_by.iter().map(|sort_expr| &sort_expr.expr))
+            .map(|expr| expr.data_type(input_schema))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            inner,
+            order_by,
+            data_types,
+        })
+    }
+}
+
+impl AggregateExpr for OrderedAggregate {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        self.inner.field()
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(OrderedAccumulator {
+            inner: self.inner.clone(),
+            columns: vec![vec![]; self.data_types.len()],
+            data_types: self.data_types.clone(),
+            sort_options: self.order_by.iter().map(|e| e.options).collect(),
+        }))
+    }
+
+    /// the collected values of each argument and sort key
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        Ok(self
+            .data_types
+            .iter()
+            .map(|data_type| {
+                Field::new(
+                    &format_state_name(self.inner.name(), "ordered"),
+                    DataType::List(Box::new(Field::new("item", data_type.clone(), true))),
+                    false,
+                )
+            })
+            .collect())
+    }
+
+    /// the expressions of the wrapped aggregate, followed by the sort keys
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        let mut expressions = self.inner.expressions();
+        expressions.extend(self.order_by.iter().map(|e| e.expr.clone()));
+        expressions
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+#[derive(Debug)]
+struct OrderedAccumulator {
+    inner: Arc<dyn AggregateExpr>,
+    /// The values of each argument and sort key
+    columns: Vec<Vec<ScalarValue>>,
+    data_types: Vec<DataType>,
+    sort_options: Vec<SortOptions>,
+}
+
+impl Accumulator for OrderedAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(self
+            .columns
+            .iter()
+            .zip(&self.data_types)
+            .map(|(values, data_type)| {
+                ScalarValue::List(
+                    Some(Box::new(values.clone())),
+                    Box::new(data_type.clone()),
+                )
+            })
+            .collect())
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        self.columns
+            .iter_mut()
+            .zip(values)
+            .for_each(|(column, value)| column.push(value.clone()));
+        Ok(())
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        self.columns
+            .iter_mut()
+            .zip(states)
+            .try_for_each(|(column, state)| match state {
+                ScalarValue::List(Some(values), _) => {
+                    column.extend(values.iter().cloned());
+                    Ok(())
+                }
+                _ => Err(DataFusionError::Internal(format!(
+                    "Unexpected accumulator state {:?}",
+                    state
+                ))),
+            })
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        let mut accumulator = self.inner.create_accumulator()?;
+        if !self.columns[0].is_empty() {
+            let columns = self
+                .columns
+                .iter()
+                .map(|values| ScalarValue::iter_to_array(values.iter().cloned()))
+                .collect::<Result<Vec<_>>>()?;
+            let (args, keys) = columns.split_at(columns.len() - self.sort_options.len());
+            let sort_columns = keys
+                .iter()
+                .zip(&self.sort_options)
+                .map(|(values, options)| SortColumn {
+                    values: values.clone(),
+                    options: Some(*options),
+                })
+                .collect::<Vec<_>>();
+            let indices = lexsort_to_indices(&sort_columns, None)?;
+            let args = args
+                .iter()
+                .map(|values| compute::take(values.as_ref(), &indices, None))
+                .collect::<arrow::error::Result<Vec<_>>>()?;
+            accumulator.update_batch(&args)?;
+        }
+        accumulator.evaluate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::{col, ArrayAgg, Sum};
+    use arrow::array::Int64Array;
+
+    #[test]
+    fn filtered_aggregate() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Boolean, true),
+        ]);
+        let sum = Arc::new(Sum::new(col("a", &schema)?, "SUM(a)", DataType::Int64));
+        let agg = FilteredAggregate::new(sum, col("b", &schema)?);
+        assert_eq!(agg.expressions().len(), 2);
+
+        let a: ArrayRef = Arc::new(Int64Array::from(vec![1, 2, 3, 4]));
+        let b: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(false),
+            None,
+            Some(true),
+        ]));
+        let mut accum = agg.create_accumulator()?;
+        accum.update_batch(&[a, b])?;
+
+        assert_eq!(accum.evaluate()?, ScalarValue::Int64(Some(5)));
+        Ok(())
+    }
+
+    #[test]
+    fn ordered_aggregate() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("t", DataType::Int64, true),
+        ]);
+        let array_agg = Arc::new(ArrayAgg::new(
+            col("a", &schema)?,
+            "ARRAY_AGG(a)",
+            DataType::Int64,
+        ));
+        let order_by = vec![PhysicalSortExpr {
+            expr: col("t", &schema)?,
+            options: SortOptions {
+                descending: true,
+                nulls_first: false,
+            },
+        }];
+        let agg = OrderedAggregate::try_new(array_agg, order_by, &schema)?;
+
+        // partial aggregations of two batches, merged into a final one
+        let mut accum1 = agg.create_accumulator()?;
+        accum1.update_batch(&[
+            Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef,
+            Arc::new(Int64Array::from(vec![Some(10), None])) as ArrayRef,
+        ])?;
+        let mut accum2 = agg.create_accumulator()?;
+        accum2.update_batch(&[
+            Arc::new(Int64Array::from(vec![3])) as ArrayRef,
+            Arc::new(Int64Array::from(vec![30])) as ArrayRef,
+        ])?;
+        let mut accum = agg.create_accumulator()?;
+        accum.merge(&accum1.state()?)?;
+        accum.merge(&accum2.state()?)?;
+
+        let expected = ScalarValue::List(
+            Some(Box::new(vec![
+                ScalarValue::Int64(Some(3)),
+                ScalarValue::Int64(Some(1)),
+                ScalarValue::Int64(Some(2)),
+            ])),
+            Box::new(DataType::Int64),
+        );
+        assert_eq!(accum.evaluate()?, expected);
+        Ok(())
+    }
+}
@@ -18,10 +18,15 @@
 //! Array expressions
 
 use crate::error::{DataFusionError, Result};
+use crate::scalar::ScalarValue;
 use arrow::array::*;
-use arrow::datatypes::DataType;
+use arrow::buffer::Buffer;
+use arrow::compute::{cast, concat, sort, take, SortOptions};
+use arrow::datatypes::{DataType, Field};
+use std::collections::HashSet;
 use std::sync::Arc;
 
+use super::functions::make_scalar_function;
 use super::ColumnarValue;
 
 macro_rules! downcast_vec {
@@ -41,10 +46,8 @@ macro_rules! array {
         let args =
             downcast_vec!($ARGS, $ARRAY_TYPE).collect::<Result<Vec<&$ARRAY_TYPE>>>()?;
 
-        let mut builder = FixedSizeListBuilder::<$BUILDER_TYPE>::new(
-            <$BUILDER_TYPE>::new(args[0].len()),
-            args.len() as i32,
-        );
+        let mut builder =
+            ListBuilder::<$BUILDER_TYPE>::new(<$BUILDER_TYPE>::new(args[0].len()));
         // for each entry in the array
         for index in 0..args[0].len() {
             for arg in &args {
@@ -60,7 +63,7 @@ macro_rules! array {
     }};
 }
 
-fn array_array(args: &[&dyn Array]) -> Result<ArrayRef> {
+fn array_array(args: &[ArrayRef]) -> Result<ArrayRef> {
     // do not accept 0 arguments.
     if args.is_empty() {
         return Err(DataFusionError::Internal(
@@ -90,21 +93,11 @@ fn array_array(args: &[&dyn Array]) -> Result<ArrayRef> {
 }
 
 /// put values in an array.
+///
+/// The result is a `List` array. It used to be a `FixedSizeList` with one element
+/// per argument, which callers that downcast the result need to account for.
 pub fn array(values: &[ColumnarValue]) -> Result<ColumnarValue> {
-    let arrays: Vec<&dyn Array> = values
-        .iter()
-        .map(|value| {
-            if let ColumnarValue::Array(value) = value {
-                Ok(value.as_ref())
-            } else {
-                Err(DataFusionError::NotImplemented(
-                    "Array is not implemented for scalar values.".to_string(),
-                ))
-            }
-        })
-        .collect::<Result<_>>()?;
-
-    Ok(ColumnarValue::Array(array_array(&arrays)?))
+    make_scalar_function(array_array)(values)
 }
 
 /// Currently supported types by the array function.
@@ -125,3 +118,487 @@ pub static SUPPORTED_ARRAY_TYPES: &[DataType] = &[
     DataType::Utf8,
     DataType::LargeUtf8,
 ];
+
+/// Returns the element type of the list type `data_type`, the type of the
+/// first argument of the array function `name`.
+pub(crate) fn list_element_type<'a>(
+    name: &str,
+    data_type: &'a DataType,
+) -> Result<&'a DataType> {
+    match data_type {
+        DataType::List(field)
+        | DataType::LargeList(field)
+        | DataType::FixedSizeList(field, _) => Ok(field.data_type()),
+        other => Err(DataFusionError::Plan(format!(
+            "The function {} expects a list argument, got {:?}",
+            name, other
+        ))),
+    }
+}
+
+/// Returns the elements of each row of the list array `array`, or `None` for
+/// the null rows.
+fn list_rows(array: &ArrayRef) -> Result<Vec<Option<ArrayRef>>> {
+    fn rows(
+        array: &dyn Array,
+        value: impl Fn(usize) -> ArrayRef,
+    ) -> Vec<Option<ArrayRef>> {
+        (0..array.len())
+            .map(|row| (!array.is_null(row)).then(|| value(row)))
+            .collect()
+    }
+
+    match array.data_type() {
+        DataType::List(_) => {
+            let list = array.as_any().downcast_ref::<ListArray>().unwrap();
+            Ok(rows(list, |row| list.value(row)))
+        }
+        DataType::LargeList(_) => {
+            let list = array.as_any().downcast_ref::<LargeListArray>().unwrap();
+            Ok(rows(list, |row| list.value(row)))
+        }
+        DataType::FixedSizeList(_, _) => {
+            let list = array.as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+            Ok(rows(list, |row| list.value(row)))
+        }
+        other => Err(DataFusionError::Internal(format!(
+            "Array function expected a list argument, got {:?}",
+            other
+        ))),
+    }
+}
+
+/// Builds a `List` array with elements of type `element_type` out of `rows`,
+/// where `None` is a null list.
+fn build_list(element_type: &DataType, rows: &[Option<ArrayRef>]) -> Result<ArrayRef> {
+    let mut offsets = Vec::with_capacity(rows.len() + 1);
+    offsets.push(0i32);
+    let mut validity = BooleanBufferBuilder::new(rows.len());
+    let mut values: Vec<&dyn Array> = vec![];
+    for row in rows {
+        let len = row.as_ref().map(|row| row.len()).unwrap_or(0);
+        offsets.push(offsets[offsets.len() - 1] + len as i32);
+        validity.append(row.is_some());
+        if let Some(row) = row {
+            values.push(row.as_ref());
+        }
+    }
+    let values = if values.is_empty() {
+        new_empty_array(element_type)
+    } else {
+        concat(&values)?
+    };
+
+    let data = ArrayData::builder(DataType::List(Box::new(Field::new(
+        "item",
+        element_type.clone(),
+        true,
+    ))))
+    .len(rows.len())
+    .add_buffer(Buffer::from_slice_ref(&offsets))
+    .add_child_data(values.data().clone())
+    .null_bit_buffer(validity.finish())
+    .build()?;
+    Ok(Arc::new(ListArray::from(data)))
+}
+
+/// Casts the argument `array` of a function over the list `list` to the
+/// element type of the list.
+fn cast_to_element_type(
+    name: &str,
+    list: &ArrayRef,
+    array: &ArrayRef,
+) -> Result<ArrayRef> {
+    let element_type = list_element_type(name, list.data_type())?;
+    Ok(cast(array, element_type)?)
+}
+
+/// Returns the 0-based position of the first element of `row` equal to
+/// `value`
+fn position(row: &ArrayRef, value: &ScalarValue) -> Option<usize> {
+    (0..row.len()).find(|&index| value.eq_array(row, index))
+}
+
+/// Applies `op` to the list and the needle of each row of `args`, returning
+/// `None` if either is null
+fn search<T>(
+    name: &str,
+    args: &[ArrayRef],
+    op: impl Fn(&ArrayRef, &ScalarValue) -> T,
+) -> Result<Vec<Option<T>>> {
+    let needles = cast_to_element_type(name, &args[0], &args[1])?;
+    list_rows(&args[0])?
+        .iter()
+        .enumerate()
+        .map(|(index, row)| -> Result<Option<T>> {
+            match row {
+                Some(row) if needles.is_valid(index) => {
+                    let needle = ScalarValue::try_from_array(&needles, index)?;
+                    Ok(Some(op(row, &needle)))
+                }
+                _ => Ok(None),
+            }
+        })
+        .collect()
+}
+
+/// array_length(list) returns the number of elements of a list, or null if the
+/// list is empty.
+pub fn array_length(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let lengths = list_rows(&args[0])?
+        .iter()
+        .map(|row| match row {
+            Some(row) if !row.is_empty() => Some(row.len() as i64),
+            _ => None,
+        })
+        .collect::<Int64Array>();
+    Ok(Arc::new(lengths))
+}
+
+/// cardinality(list) returns the number of elements of a list.
+pub fn cardinality(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let lengths = list_rows(&args[0])?
+        .iter()
+        .map(|row| row.as_ref().map(|row| row.len() as i64))
+        .collect::<Int64Array>();
+    Ok(Arc::new(lengths))
+}
+
+/// array_contains(list, value) returns whether the list has an element equal to
+/// `value`.
+pub fn array_contains(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let found = search("array_contains", args, |row, needle| {
+        position(row, needle).is_some()
+    })?;
+    Ok(Arc::new(found.into_iter().collect::<BooleanArray>()))
+}
+
+/// array_position(list, value) returns the 1-based position of the first
+/// element of the list equal to `value`, or null if there is none.
+pub fn array_position(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let positions = search("array_position", args, |row, needle| {
+        position(row, needle).map(|index| index as i64 + 1)
+    })?;
+    Ok(Arc::new(
+        positions
+            .into_iter()
+            .map(Option::flatten)
+            .collect::<Int64Array>(),
+    ))
+}
+
+/// array_append(list, value) appends `value` to the list. A null list is
+/// treated as an empty list.
+pub fn array_append(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let values = cast_to_element_type("array_append", &args[0], &args[1])?;
+    let rows = list_rows(&args[0])?
+        .iter()
+        .enumerate()
+        .map(|(index, row)| -> Result<Option<ArrayRef>> {
+            let value = values.slice(index, 1);
+            Ok(Some(match row {
+                Some(row) => concat(&[row.as_ref(), value.as_ref()])?,
+                None => value,
+            }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    build_list(values.data_type(), &rows)
+}
+
+/// array_concat(list, ...) concatenates lists, skipping the null ones. The
+/// result is null if all the lists are null.
+pub fn array_concat(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let element_type = list_element_type("array_concat", args[0].data_type())?;
+    let lists = args.iter().map(list_rows).collect::<Result<Vec<_>>>()?;
+    let rows = (0..args[0].len())
+        .map(|index| -> Result<Option<ArrayRef>> {
+            let values = lists
+                .iter()
+                .filter_map(|list| list[index].as_ref().map(|row| row.as_ref()))
+                .collect::<Vec<_>>();
+            if values.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(concat(&values)?))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    build_list(element_type, &rows)
+}
+
+/// array_slice(list, from, to) returns the elements of the list between the
+/// 1-based positions `from` and `to`, inclusive. Negative positions count from
+/// the end of the list.
+pub fn array_slice(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let element_type = list_element_type("array_slice", args[0].data_type())?;
+    let from = cast(&args[1], &DataType::Int64)?;
+    let from = from.as_any().downcast_ref::<Int64Array>().unwrap();
+    let to = cast(&args[2], &DataType::Int64)?;
+    let to = to.as_any().downcast_ref::<Int64Array>().unwrap();
+
+    let rows = list_rows(&args[0])?
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| match row {
+            Some(row) if from.is_valid(index) && to.is_valid(index) => {
+                let len = row.len() as i64;
+                let resolve = |position: i64| {
+                    if position < 0 {
+                        len + position + 1
+                    } else {
+                        position
+                    }
+                };
+                let start = resolve(from.value(index)).max(1);
+                let end = resolve(to.value(index)).min(len);
+                Some(if start > end {
+                    row.slice(0, 0)
+                } else {
+                    row.slice(start as usize - 1, (end - start + 1) as usize)
+                })
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    build_list(element_type, &rows)
+}
+
+/// array_distinct(list) removes the duplicate elements of a list, keeping the
+/// first occurrence of each.
+pub fn array_distinct(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let element_type = list_element_type("array_distinct", args[0].data_type())?;
+    let rows = list_rows(&args[0])?
+        .into_iter()
+        .map(|row| {
+            row.map(|row| -> Result<ArrayRef> {
+                let mut seen = HashSet::new();
+                let mut indices = vec![];
+                for index in 0..row.len() {
+                    if seen.insert(ScalarValue::try_from_array(&row, index)?) {
+                        indices.push(index as u32);
+                    }
+                }
+                Ok(take(row.as_ref(), &UInt32Array::from(indices), None)?)
+            })
+            .transpose()
+        })
+        .collect::<Result<Vec<_>>>()?;
+    build_list(element_type, &rows)
+}
+
+/// array_sort(list) sorts the elements of a list in ascending order, nulls last.
+pub fn array_sort(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let element_type = list_element_type("array_sort", args[0].data_type())?;
+    let options = SortOptions {
+        descending: false,
+        nulls_first: false,
+    };
+    let rows = list_rows(&args[0])?
+        .into_iter()
+        .map(|row| row.map(|row| sort(&row, Some(options))).transpose())
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    build_list(element_type, &rows)
+}
+
+/// array_join(list, delimiter[, null_string]) joins the elements of a list
+/// into a string separated by `delimiter`. Null elements are replaced by
+/// `null_string`, or skipped if it is not given.
+pub fn array_join(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let delimiters = cast(&args[1], &DataType::Utf8)?;
+    let delimiters = delimiters.as_any().downcast_ref::<StringArray>().unwrap();
+    let null_strings = args
+        .get(2)
+        .map(|array| cast(array, &DataType::Utf8))
+        .transpose()?;
+    let null_strings = null_strings
+        .as_ref()
+        .map(|array| array.as_any().downcast_ref::<StringArray>().unwrap());
+
+    let joined = list_rows(&args[0])?
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| -> Result<Option<String>> {
+            match row {
+                Some(row) if delimiters.is_valid(index) => {
+                    let elements = cast(&row, &DataType::Utf8)?;
+                    let elements =
+                        elements.as_any().downcast_ref::<StringArray>().unwrap();
+                    let null_string = null_strings
+                        .filter(|array| array.is_valid(index))
+                        .map(|array| array.value(index));
+                    let parts = elements
+                        .iter()
+                        .filter_map(|element| element.or(null_string))
+                        .collect::<Vec<_>>();
+                    Ok(Some(parts.join(delimiters.value(index))))
+                }
+                _ => Ok(None),
+            }
+        })
+        .collect::<Result<StringArray>>()?;
+    Ok(Arc::new(joined))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::Int64Type;
+
+    fn int64_list(rows: Vec<Option<Vec<Option<i64>>>>) -> ArrayRef {
+        Arc::new(ListArray::from_iter_primitive::<Int64Type, _, _>(rows))
+    }
+
+    fn sample_lists() -> ArrayRef {
+        int64_list(vec![
+            Some(vec![Some(3), Some(1), None, Some(3)]),
+            Some(vec![]),
+            None,
+        ])
+    }
+
+    #[test]
+    fn array_of_scalars() -> Result<()> {
+        let values = vec![
+            ColumnarValue::Scalar(ScalarValue::Int64(Some(1))),
+            ColumnarValue::Scalar(ScalarValue::Int64(None)),
+        ];
+        match array(&values)? {
+            ColumnarValue::Scalar(value) => assert_eq!(
+                value,
+                ScalarValue::List(
+                    Some(Box::new(vec![
+                        ScalarValue::Int64(Some(1)),
+                        ScalarValue::Int64(None)
+                    ])),
+                    Box::new(DataType::Int64)
+                )
+            ),
+            ColumnarValue::Array(_) => panic!("expected a scalar"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn lengths() -> Result<()> {
+        let lists = sample_lists();
+        assert_eq!(
+            array_length(&[lists.clone()])?.data(),
+            Int64Array::from(vec![Some(4), None, None]).data()
+        );
+        assert_eq!(
+            cardinality(&[lists])?.data(),
+            Int64Array::from(vec![Some(4), Some(0), None]).data()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn search_elements() -> Result<()> {
+        let lists = sample_lists();
+        let needles: ArrayRef = Arc::new(Int32Array::from(vec![1, 1, 1]));
+        assert_eq!(
+            array_contains(&[lists.clone(), needles.clone()])?.data(),
+            BooleanArray::from(vec![Some(true), Some(false), None]).data()
+        );
+        assert_eq!(
+            array_position(&[lists, needles])?.data(),
+            Int64Array::from(vec![Some(2), None, None]).data()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn append_and_concat() -> Result<()> {
+        let lists = sample_lists();
+        let values: ArrayRef = Arc::new(Int64Array::from(vec![Some(4), None, Some(5)]));
+        let expected = int64_list(vec![
+            Some(vec![Some(3), Some(1), None, Some(3), Some(4)]),
+            Some(vec![None]),
+            Some(vec![Some(5)]),
+        ]);
+        assert_eq!(
+            array_append(&[lists.clone(), values])?.data(),
+            expected.data()
+        );
+
+        let expected = int64_list(vec![
+            Some(vec![
+                Some(3),
+                Some(1),
+                None,
+                Some(3),
+                Some(3),
+                Some(1),
+                None,
+                Some(3),
+            ]),
+            Some(vec![]),
+            None,
+        ]);
+        assert_eq!(
+            array_concat(&[lists.clone(), lists])?.data(),
+            expected.data()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn slice() -> Result<()> {
+        let lists = int64_list(vec![
+            Some(vec![Some(1), Some(2), Some(3), Some(4)]),
+            Some(vec![Some(1), Some(2), Some(3), Some(4)]),
+            Some(vec![Some(1), Some(2), Some(3), Some(4)]),
+            Some(vec![Some(1), Some(2), Some(3), Some(4)]),
+        ]);
+        let from: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(2), Some(-2), Some(3), None]));
+        let to: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(10), Some(-1), Some(2), Some(1)]));
+        let expected = int64_list(vec![
+            Some(vec![Some(2), Some(3), Some(4)]),
+            Some(vec![Some(3), Some(4)]),
+            Some(vec![]),
+            None,
+        ]);
+        assert_eq!(array_slice(&[lists, from, to])?.data(), expected.data());
+        Ok(())
+    }
+
+    #[test]
+    fn distinct_and_sort() -> Result<()> {
+        let lists = sample_lists();
+        let expected =
+            int64_list(vec![Some(vec![Some(3), Some(1), None]), Some(vec![]), None]);
+        assert_eq!(array_distinct(&[lists.clone()])?.data(), expected.data());
+
+        let expected = int64_list(vec![
+            Some(vec![Some(1), Some(3), Some(3), None]),
+            Some(vec![]),
+            None,
+        ]);
+        assert_eq!(array_sort(&[lists])?.data(), expected.data());
+        Ok(())
+    }
+
+    #[test]
+    fn join() -> Result<()> {
+        let lists = sample_lists();
+        let delimiters: ArrayRef = Arc::new(StringArray::from(vec![",", ",", ","]));
+        assert_eq!(
+            array_join(&[lists.clone(), delimiters.clone()])?.data(),
+            StringArray::from(vec![Some("3,1,3"), Some(""), None]).data()
+        );
+        let null_strings: ArrayRef = Arc::new(StringArray::from(vec!["*", "*", "*"]));
+        assert_eq!(
+            array_join(&[lists, delimiters, null_strings])?.data(),
+            StringArray::from(vec![Some("3,1,*,3"), Some(""), None]).data()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn non_list_argument() {
+        let values: ArrayRef = Arc::new(Int64Array::from(vec![1]));
+        assert!(array_distinct(&[values]).is_err());
+    }
+}
@@ -0,0 +1,570 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Async UDF support.
+//!
+//! The implementation of an async UDF returns a future, e.g. of a request to an
+//! external service, that is awaited by an [`AsyncFuncExec`]. The planner
+//! evaluates the async UDF calls of projections and filters in such a plan
+//! below the [`ProjectionExec`](super::projection::ProjectionExec) or
+//! [`FilterExec`](super::filter::FilterExec), which then refer to their results.
+
+use std::any::Any;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::array::{new_empty_array, ArrayRef, NullArray};
+use arrow::compute::concat;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use futures::future::{try_join_all, BoxFuture};
+use futures::lock::Mutex;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use futures::{ready, FutureExt};
+
+use super::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use super::type_coercion::coerce;
+use super::udf::ScalarUDF;
+use super::{
+    ColumnStatistics, DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
+    RecordBatchStream, SendableRecordBatchStream, Statistics,
+};
+use crate::error::{DataFusionError, Result};
+
+/// The implementation of an async UDF.
+///
+/// It is called with the arguments of up to [`AsyncScalarFunction::batch_size`]
+/// rows, as arrays of the same length, and must resolve to an array of that
+/// length. A function without parameters is called with a single null array
+/// that indicates the number of rows.
+pub type AsyncScalarFunctionImplementation =
+    Arc<dyn Fn(Vec<ArrayRef>) -> BoxFuture<'static, Result<ArrayRef>> + Send + Sync>;
+
+/// The number of rows that an async UDF is called with by default
+pub const DEFAULT_ASYNC_BATCH_SIZE: usize = 1024;
+
+/// The number of calls of an async UDF that are awaited concurrently by default
+pub const DEFAULT_ASYNC_MAX_CONCURRENCY: usize = 8;
+
+/// An async implementation of a scalar UDF, along with the limits of how it is
+/// called
+#[derive(Clone)]
+pub struct AsyncScalarFunction {
+    /// actual implementation
+    pub fun: AsyncScalarFunctionImplementation,
+    /// The maximum number of rows that the function is called with
+    pub batch_size: usize,
+    /// The maximum number of calls that each partition of a query awaits
+    /// concurrently, per call site of the function
+    pub max_concurrency: usize,
+}
+
+impl AsyncScalarFunction {
+    /// Create an async implementation with the default batch size and concurrency
+    pub fn new(fun: AsyncScalarFunctionImplementation) -> Self {
+        Self {
+            fun,
+            batch_size: DEFAULT_ASYNC_BATCH_SIZE,
+            max_concurrency: DEFAULT_ASYNC_MAX_CONCURRENCY,
+        }
+    }
+
+    /// Set the maximum number of rows that the function is called with
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set the maximum number of calls that are awaited concurrently
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+}
+
+impl fmt::Debug for AsyncScalarFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncScalarFunction")
+            .field("fun", &"<FUNC>")
+            .field("batch_size", &self.batch_size)
+            .field("max_concurrency", &self.max_concurrency)
+            .finish()
+    }
+}
+
+/// A call of an async UDF
+pub struct AsyncFuncExpr {
+    name: String,
+    fun: AsyncScalarFunction,
+    args: Vec<Arc<dyn PhysicalExpr>>,
+    return_type: DataType,
+}
+
+impl AsyncFuncExpr {
+    /// Create a call of the async UDF `fun`.
+    /// This function errors when `fun` is not async or when `args` can't be
+    /// coerced to a valid argument type of it.
+    pub fn try_new(
+        fun: &ScalarUDF,
+        args: &[Arc<dyn PhysicalExpr>],
+        input_schema: &Schema,
+    ) -> Result<Self> {
+        let async_fun = fun.async_fun.clone().ok_or_else(|| {
+            DataFusionError::Internal(format!("UDF {} is not async", fun.name))
+        })?;
+        let args = coerce(args, input_schema, &fun.signature)?;
+        let arg_types = args
+            .iter()
+            .map(|e| e.data_type(input_schema))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            name: fun.name.clone(),
+            fun: async_fun,
+            args,
+            return_type: (fun.return_type)(&arg_types)?.as_ref().clone(),
+        })
+    }
+
+    /// The name of the function
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The arguments of the call
+    pub fn args(&self) -> &[Arc<dyn PhysicalExpr>] {
+        &self.args
+    }
+
+    /// The type of the results
+    pub fn return_type(&self) -> &DataType {
+        &self.return_type
+    }
+
+    /// Evaluate the call for the rows of `batch`, calling the function with up to
+    /// `batch_size` rows at a time and awaiting up to `max_concurrency` of these
+    /// calls at once.
+    pub async fn evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let num_rows = batch.num_rows();
+        let args = if self.args.is_empty() {
+            vec![Arc::new(NullArray::new(num_rows)) as ArrayRef]
+        } else {
+            self.args
+                .iter()
+                .map(|e| e.evaluate(batch).map(|v| v.into_array(num_rows)))
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        let batch_size = self.fun.batch_size.max(1);
+        let calls = (0..num_rows).step_by(batch_size).map(|offset| {
+            let length = batch_size.min(num_rows - offset);
+            let slices = args.iter().map(|a| a.slice(offset, length)).collect();
+            let call = (self.fun.fun)(slices);
+            let name = self.name.clone();
+            let return_type = self.return_type.clone();
+            async move {
+                let array = call.await?;
+                if array.len() != length {
+                    return Err(DataFusionError::Execution(format!(
+                        "Async UDF {} returned {} rows instead of {}",
+                        name,
+                        array.len(),
+                        length
+                    )));
+                }
+                if array.data_type() != &return_type {
+                    return Err(DataFusionError::Execution(format!(
+                        "Async UDF {} returned {:?} instead of {:?}",
+                        name,
+                        array.data_type(),
+                        return_type
+                    )));
+                }
+                Ok(array)
+            }
+        });
+        let results = stream::iter(calls)
+            .buffered(self.fun.max_concurrency.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        match results.len() {
+            0 => Ok(new_empty_array(&self.return_type)),
+            1 => Ok(results[0].clone()),
+            _ => {
+                let results = results.iter().map(|a| a.as_ref()).collect::<Vec<_>>();
+                Ok(concat(&results)?)
+            }
+        }
+    }
+}
+
+impl fmt::Debug for AsyncFuncExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncFuncExpr")
+            .field("name", &self.name)
+            .field("fun", &self.fun)
+            .field("args", &self.args)
+            .field("return_type", &self.return_type)
+            .finish()
+    }
+}
+
+impl fmt::Display for AsyncFuncExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}({})",
+            self.name,
+            self.args
+                .iter()
+                .map(|e| format!("{}", e))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Execution plan that appends the results of async UDF calls to the rows of
+/// its input
+#[derive(Debug)]
+pub struct AsyncFuncExec {
+    /// The calls stored as tuples of (call, output column name)
+    expr: Vec<(Arc<AsyncFuncExpr>, String)>,
+    /// The schema of the input followed by the results of the calls
+    schema: SchemaRef,
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl AsyncFuncExec {
+    /// Create a plan that evaluates the calls `expr` on the rows of `input`
+    pub fn try_new(
+        expr: Vec<(Arc<AsyncFuncExpr>, String)>,
+        input: Arc<dyn ExecutionPlan>,
+    ) -> Result<Self> {
+        let input_schema = input.schema();
+        let fields = input_schema
+            .fields()
+            .iter()
+            .cloned()
+            .chain(
+                expr.iter()
+                    .map(|(e, name)| Field::new(name, e.return_type().clone(), true)),
+            )
+            .collect();
+        let schema = Arc::new(Schema::new_with_metadata(
+            fields,
+            input_schema.metadata().clone(),
+        ));
+
+        Ok(Self {
+            expr,
+            schema,
+            input,
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+
+    /// The calls stored as tuples of (call, output column name)
+    pub fn expr(&self) -> &[(Arc<AsyncFuncExpr>, String)] {
+        &self.expr
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for AsyncFuncExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(AsyncFuncExec::try_new(
+                self.expr.clone(),
+                children[0].clone(),
+            )?)),
+            _ => Err(DataFusionError::Internal(
+                "AsyncFuncExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        Ok(Box::pin(AsyncFuncStream {
+            schema: self.schema.clone(),
+            expr: self.expr.iter().map(|x| x.0.clone()).collect(),
+            input: self.input.execute(partition).await?,
+            pending: None,
+            baseline_metrics: BaselineMetrics::new(&self.metrics, partition),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let expr: Vec<String> = self
+                    .expr
+                    .iter()
+                    .map(|(e, name)| format!("{} as {}", e, name))
+                    .collect();
+
+                write!(f, "AsyncFuncExec: expr=[{}]", expr.join(", "))
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        let stats = self.input.statistics();
+        let column_statistics = stats.column_statistics.map(|mut column_statistics| {
+            column_statistics
+                .extend(self.expr.iter().map(|_| ColumnStatistics::default()));
+            column_statistics
+        });
+
+        Statistics {
+            is_exact: stats.is_exact,
+            num_rows: stats.num_rows,
+            column_statistics,
+            total_byte_size: None,
+        }
+    }
+}
+
+/// Evaluates the calls on `batch` and appends their results to it
+async fn append_results(
+    batch: RecordBatch,
+    expr: Vec<Arc<AsyncFuncExpr>>,
+    schema: SchemaRef,
+) -> ArrowResult<RecordBatch> {
+    let results = try_join_all(expr.iter().map(|e| e.evaluate(&batch)))
+        .await
+        .map_err(DataFusionError::into_arrow_external_error)?;
+    let mut columns = batch.columns().to_vec();
+    columns.extend(results);
+    RecordBatch::try_new(schema, columns)
+}
+
+/// Async UDF iterator
+struct AsyncFuncStream {
+    schema: SchemaRef,
+    expr: Vec<Arc<AsyncFuncExpr>>,
+    input: SendableRecordBatchStream,
+    /// The results of the batch that was last read from the input, behind a
+    /// mutex as the stream must be `Sync` while the future is only `Send`
+    pending: Option<Mutex<BoxFuture<'static, ArrowResult<RecordBatch>>>>,
+    baseline_metrics: BaselineMetrics,
+}
+
+impl Stream for AsyncFuncStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let poll = loop {
+            if let Some(pending) = self.pending.as_mut() {
+                let result = ready!(pending.get_mut().poll_unpin(cx));
+                self.pending = None;
+                break Poll::Ready(Some(result));
+            }
+            match ready!(self.input.poll_next_unpin(cx)) {
+                Some(Ok(batch)) => {
+                    let pending =
+                        append_results(batch, self.expr.clone(), self.schema.clone());
+                    self.pending = Some(Mutex::new(pending.boxed()));
+                }
+                other => break Poll::Ready(other),
+            }
+        };
+
+        self.baseline_metrics.record_poll(poll)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // same number of record batches
+        self.input.size_hint()
+    }
+}
+
+impl RecordBatchStream for AsyncFuncStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::create_async_udf;
+    use crate::physical_plan::collect;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::functions::Volatility;
+    use crate::physical_plan::memory::MemoryExec;
+    use arrow::array::{Array, Int64Array, StringArray};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A service that resolves ids to names, recording how it is called
+    #[derive(Default)]
+    struct MockService {
+        calls: AtomicUsize,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        max_rows: AtomicUsize,
+    }
+
+    impl MockService {
+        async fn lookup(&self, ids: ArrayRef) -> Result<ArrayRef> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.max_rows.fetch_max(ids.len(), Ordering::SeqCst);
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let ids = ids.as_any().downcast_ref::<Int64Array>().unwrap();
+            let names = ids
+                .iter()
+                .map(|id| id.map(|id| format!("name-{}", id)))
+                .collect::<StringArray>();
+            Ok(Arc::new(names))
+        }
+    }
+
+    fn lookup_udf(
+        service: Arc<MockService>,
+        batch_size: usize,
+        max_concurrency: usize,
+    ) -> ScalarUDF {
+        let fun: AsyncScalarFunctionImplementation =
+            Arc::new(move |args: Vec<ArrayRef>| {
+                let service = service.clone();
+                async move { service.lookup(args[0].clone()).await }.boxed()
+            });
+        create_async_udf(
+            "lookup",
+            vec![DataType::Int64],
+            Arc::new(DataType::Utf8),
+            Volatility::Volatile,
+            AsyncScalarFunction::new(fun)
+                .with_batch_size(batch_size)
+                .with_max_concurrency(max_concurrency),
+        )
+    }
+
+    fn ids_exec(num_rows: i64) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, true)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from(
+                (0..num_rows).collect::<Vec<_>>(),
+            ))],
+        )?;
+        Ok(Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?))
+    }
+
+    #[tokio::test]
+    async fn append_lookup_results() -> Result<()> {
+        let service = Arc::new(MockService::default());
+        let input = ids_exec(10)?;
+        let call = AsyncFuncExpr::try_new(
+            &lookup_udf(service.clone(), 3, 2),
+            &[col("id", &input.schema())?],
+            &input.schema(),
+        )?;
+        let exec =
+            AsyncFuncExec::try_new(vec![(Arc::new(call), "name".to_string())], input)?;
+
+        let batches = collect(Arc::new(exec)).await?;
+        assert_eq!(batches.len(), 1);
+        let names = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.len(), 10);
+        assert_eq!(names.value(0), "name-0");
+        assert_eq!(names.value(9), "name-9");
+
+        assert_eq!(service.calls.load(Ordering::SeqCst), 4);
+        assert_eq!(service.max_rows.load(Ordering::SeqCst), 3);
+        assert!(service.max_in_flight.load(Ordering::SeqCst) <= 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reject_wrong_number_of_results() -> Result<()> {
+        let fun: AsyncScalarFunctionImplementation =
+            Arc::new(|_| async { Ok(new_empty_array(&DataType::Utf8)) }.boxed());
+        let udf = create_async_udf(
+            "broken",
+            vec![DataType::Int64],
+            Arc::new(DataType::Utf8),
+            Volatility::Volatile,
+            AsyncScalarFunction::new(fun),
+        );
+        let input = ids_exec(2)?;
+        let call = AsyncFuncExpr::try_new(
+            &udf,
+            &[col("id", &input.schema())?],
+            &input.schema(),
+        )?;
+        let exec =
+            AsyncFuncExec::try_new(vec![(Arc::new(call), "name".to_string())], input)?;
+
+        let err = collect(Arc::new(exec)).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Async UDF broken returned 0 rows instead of 2"));
+        Ok(())
+    }
+}
@@ -33,15 +33,19 @@ use arrow::{
 };
 use arrow::{
     array::{
-        Date32Array, Date64Array, TimestampMicrosecondArray, TimestampMillisecondArray,
-        TimestampNanosecondArray, TimestampSecondArray,
+        Date32Array, Date64Array, StringArray, TimestampMicrosecondArray,
+        TimestampMillisecondArray, TimestampNanosecondArray, TimestampSecondArray,
+    },
+    compute::kernels::{
+        cast::{cast, CastOptions},
+        temporal,
     },
-    compute::kernels::temporal,
     datatypes::TimeUnit,
     temporal_conversions::timestamp_ns_to_datetime,
 };
 use chrono::prelude::*;
-use chrono::Duration;
+use chrono::{Duration, Offset};
+use chrono_tz::Tz;
 use std::borrow::Borrow;
 
 /// given a function `op` that maps a `&str` to a Result of an arrow native type,
@@ -168,6 +172,186 @@ pub fn to_timestamp_seconds(args: &[ColumnarValue]) -> Result<ColumnarValue> {
     )
 }
 
+/// A time zone as stored in the metadata of a `Timestamp(_, Some(tz))` type:
+/// either an IANA name such as `Europe/Berlin` or a fixed offset such as `+02:00`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParsedTimeZone {
+    /// A named time zone from the IANA database
+    Named(Tz),
+    /// A fixed offset from UTC
+    Fixed(FixedOffset),
+}
+
+impl ParsedTimeZone {
+    /// Converts the UTC timestamp `ts` (in nanoseconds) to the wall clock
+    /// time in this time zone
+    pub fn utc_to_local(&self, ts: i64) -> i64 {
+        let offset = match self {
+            Self::Named(tz) => tz
+                .offset_from_utc_datetime(&timestamp_ns_to_datetime(ts))
+                .fix(),
+            Self::Fixed(offset) => *offset,
+        };
+        ts + offset.local_minus_utc() as i64 * 1_000_000_000
+    }
+
+    /// Converts the wall clock time `ts` (in nanoseconds) in this time zone
+    /// to a UTC timestamp. Ambiguous times resolve to the earliest instant.
+    pub fn local_to_utc(&self, ts: i64) -> i64 {
+        let offset = match self {
+            Self::Named(tz) => {
+                let local = timestamp_ns_to_datetime(ts);
+                match tz.from_local_datetime(&local).earliest() {
+                    Some(datetime) => datetime.offset().fix(),
+                    // the wall clock time falls into a gap (e.g. a DST
+                    // transition), use the offset in effect before it. A day
+                    // earlier, read as UTC, is before the transition whatever
+                    // the offset of the time zone.
+                    None => tz
+                        .offset_from_utc_datetime(&(local - Duration::days(1)))
+                        .fix(),
+                }
+            }
+            Self::Fixed(offset) => *offset,
+        };
+        ts - offset.local_minus_utc() as i64 * 1_000_000_000
+    }
+}
+
+/// Parses a time zone name such as `Europe/Berlin` or `UTC`, or a fixed
+/// offset such as `+02:00`
+pub fn parse_time_zone(tz: &str) -> Result<ParsedTimeZone> {
+    if let Some(offset) = parse_fixed_offset(tz) {
+        return Ok(ParsedTimeZone::Fixed(offset));
+    }
+    tz.parse::<Tz>()
+        .map(ParsedTimeZone::Named)
+        .map_err(|_| DataFusionError::Plan(format!("Invalid time zone '{}'", tz)))
+}
+
+/// Parses offsets of the form `+HH`, `+HHMM` and `+HH:MM`
+fn parse_fixed_offset(tz: &str) -> Option<FixedOffset> {
+    let sign = match tz.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = tz[1..].replace(':', "");
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parses a timestamp string, reading it as a wall clock time in `tz`
+/// unless it carries an explicit UTC offset
+fn string_to_timestamp_nanos_in_time_zone(s: &str, tz: &ParsedTimeZone) -> Result<i64> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+        return Ok(ts.timestamp_nanos());
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(ts) = NaiveDateTime::parse_from_str(s, format) {
+            return Ok(tz.local_to_utc(ts.timestamp_nanos()));
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(tz.local_to_utc(date.and_hms(0, 0, 0).timestamp_nanos()));
+    }
+    string_to_timestamp_nanos_shim(s)
+}
+
+/// Returns the values of a timestamp array of any unit in nanoseconds
+pub(crate) fn timestamp_nanos(array: &ArrayRef) -> Result<Vec<Option<i64>>> {
+    let array = cast(array, &DataType::Timestamp(TimeUnit::Nanosecond, None))?;
+    let array = array
+        .as_any()
+        .downcast_ref::<TimestampNanosecondArray>()
+        .ok_or_else(|| {
+            DataFusionError::Internal("failed to downcast to timestamp".to_string())
+        })?;
+    Ok(array.iter().collect())
+}
+
+/// Returns true if casting `from` to `to` has to take a time zone into
+/// account, see [`cast_with_time_zone`]
+pub fn is_time_zone_cast(from: &DataType, to: &DataType) -> bool {
+    matches!(
+        (from, to),
+        (
+            DataType::Utf8 | DataType::LargeUtf8,
+            DataType::Timestamp(_, Some(_))
+        )
+    )
+}
+
+/// Casts a string array to a time zone aware timestamp type.
+///
+/// The values of a `Timestamp(_, Some(tz))` array are UTC instants displayed
+/// in `tz`, so strings without an explicit offset are read as wall clock
+/// times in `tz`. Casts between timestamp types don't need this, as they keep
+/// the instant and only change the time zone it is displayed in.
+///
+/// Strings that are not valid timestamps become nulls if `cast_options`
+/// are safe, as in `TRY_CAST`, and are an error otherwise.
+pub fn cast_with_time_zone(
+    array: &ArrayRef,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef> {
+    let to_tz = match (array.data_type(), to_type) {
+        (DataType::Utf8 | DataType::LargeUtf8, DataType::Timestamp(_, Some(tz))) => {
+            parse_time_zone(tz)?
+        }
+        (other, _) => {
+            return Err(DataFusionError::Internal(format!(
+                "Unsupported time zone cast from {:?} to {:?}",
+                other, to_type
+            )))
+        }
+    };
+
+    let strings = cast(array, &DataType::Utf8)?;
+    let strings = strings
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .ok_or_else(|| {
+            DataFusionError::Internal("failed to downcast to string".to_string())
+        })?;
+    let values = strings
+        .iter()
+        .map(|s| match s {
+            Some(s) => match string_to_timestamp_nanos_in_time_zone(s, &to_tz) {
+                Ok(ts) => Ok(Some(ts)),
+                Err(_) if cast_options.safe => Ok(None),
+                Err(e) => Err(e),
+            },
+            None => Ok(None),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let array: ArrayRef = Arc::new(TimestampNanosecondArray::from(values));
+    Ok(cast(&array, to_type)?)
+}
+
+/// Returns the wall clock times in time zone `tz` of the instants of a
+/// timestamp array
+fn wall_clock_times(array: &ArrayRef, tz: &str) -> Result<ArrayRef> {
+    let tz = parse_time_zone(tz)?;
+    let values: Vec<Option<i64>> = timestamp_nanos(array)?
+        .into_iter()
+        .map(|v| v.map(|v| tz.utc_to_local(v)))
+        .collect();
+    Ok(Arc::new(TimestampNanosecondArray::from(values)))
+}
+
 /// Create an implementation of `now()` that always returns the
 /// specified timestamp.
 ///
@@ -225,6 +409,21 @@ fn date_trunc_single(granularity: &str, value: i64) -> Result<i64> {
     Ok(value.unwrap().timestamp_nanos())
 }
 
+/// Truncates the UTC timestamp `value` on the wall clock of `tz`, so that
+/// e.g. `day` truncates to local midnight
+fn date_trunc_in_time_zone(
+    granularity: &str,
+    value: i64,
+    tz: Option<&ParsedTimeZone>,
+) -> Result<i64> {
+    match tz {
+        Some(tz) => {
+            Ok(tz.local_to_utc(date_trunc_single(granularity, tz.utc_to_local(value))?))
+        }
+        None => date_trunc_single(granularity, value),
+    }
+}
+
 /// date_trunc SQL function
 pub fn date_trunc(args: &[ColumnarValue]) -> Result<ColumnarValue> {
     let (granularity, array) = (&args[0], &args[1]);
@@ -238,7 +437,14 @@ pub fn date_trunc(args: &[ColumnarValue]) -> Result<ColumnarValue> {
             ));
         };
 
-    let f = |x: Option<i64>| x.map(|x| date_trunc_single(granularity, x)).transpose();
+    let tz = match array.data_type() {
+        DataType::Timestamp(_, Some(tz)) => Some(parse_time_zone(&tz)?),
+        _ => None,
+    };
+    let f = |x: Option<i64>| {
+        x.map(|x| date_trunc_in_time_zone(granularity, x, tz.as_ref()))
+            .transpose()
+    };
 
     Ok(match array {
         ColumnarValue::Scalar(ScalarValue::TimestampNanosecond(v, tz_opt)) => {
@@ -248,16 +454,20 @@ pub fn date_trunc(args: &[ColumnarValue]) -> Result<ColumnarValue> {
             ))
         }
         ColumnarValue::Array(array) => {
+            let data_type = array.data_type();
             let array = array
                 .as_any()
                 .downcast_ref::<TimestampNanosecondArray>()
                 .unwrap();
-            let array = array
-                .iter()
-                .map(f)
-                .collect::<Result<TimestampNanosecondArray>>()?;
-
-            ColumnarValue::Array(Arc::new(array))
+            let array: ArrayRef = Arc::new(
+                array
+                    .iter()
+                    .map(f)
+                    .collect::<Result<TimestampNanosecondArray>>()?,
+            );
+
+            // restore the time zone of the input
+            ColumnarValue::Array(cast(&array, data_type)?)
         }
         _ => {
             return Err(DataFusionError::Execution(
@@ -340,6 +550,12 @@ pub fn date_part(args: &[ColumnarValue]) -> Result<ColumnarValue> {
         ColumnarValue::Scalar(scalar) => scalar.to_array(),
     };
 
+    // extract the parts of the wall clock time in the time zone of the input
+    let array = match array.data_type() {
+        DataType::Timestamp(_, Some(tz)) => wall_clock_times(&array, tz)?,
+        _ => array,
+    };
+
     let arr = match date_part.to_lowercase().as_str() {
         "hour" => extract_date_part!(array, temporal::hour),
         "year" => extract_date_part!(array, temporal::year),
@@ -452,6 +668,157 @@ mod tests {
         });
     }
 
+    #[test]
+    fn date_trunc_time_zone_test() -> Result<()> {
+        let berlin = parse_time_zone("Europe/Berlin")?;
+        let cases = vec![
+            // CEST (+02:00)
+            (
+                "2020-09-08T23:42:29.190855Z",
+                "day",
+                "2020-09-08T22:00:00.000000Z",
+            ),
+            (
+                "2020-09-08T13:42:29.190855Z",
+                "hour",
+                "2020-09-08T13:00:00.000000Z",
+            ),
+            // CET (+01:00)
+            (
+                "2020-12-31T23:42:29.190855Z",
+                "year",
+                "2020-12-31T23:00:00.000000Z",
+            ),
+            (
+                "2020-11-01T00:42:29.190855Z",
+                "month",
+                "2020-10-31T23:00:00.000000Z",
+            ),
+        ];
+
+        cases.iter().for_each(|(original, granularity, expected)| {
+            let original = string_to_timestamp_nanos(original).unwrap();
+            let expected = string_to_timestamp_nanos(expected).unwrap();
+            let result =
+                date_trunc_in_time_zone(granularity, original, Some(&berlin)).unwrap();
+            assert_eq!(result, expected);
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn parse_time_zone_test() -> Result<()> {
+        assert_eq!(
+            parse_time_zone("+02:00")?,
+            ParsedTimeZone::Fixed(FixedOffset::east(7200))
+        );
+        assert_eq!(
+            parse_time_zone("-0530")?,
+            ParsedTimeZone::Fixed(FixedOffset::west(19800))
+        );
+        assert_eq!(
+            parse_time_zone("Europe/Berlin")?,
+            ParsedTimeZone::Named(Tz::Europe__Berlin)
+        );
+        assert!(parse_time_zone("Mars/Olympus_Mons").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn local_to_utc_in_gap_test() -> Result<()> {
+        let local = |s: &str| {
+            NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+                .unwrap()
+                .timestamp_nanos()
+        };
+
+        // Berlin skips from 02:00 CET (+01:00) to 03:00 CEST on 2021-03-28
+        let berlin = parse_time_zone("Europe/Berlin")?;
+        assert_eq!(
+            berlin.local_to_utc(local("2021-03-28T02:30:00")),
+            local("2021-03-28T01:30:00")
+        );
+        assert_eq!(
+            berlin.local_to_utc(local("2021-03-28T03:30:00")),
+            local("2021-03-28T01:30:00")
+        );
+
+        // New York skips from 02:00 EST (-05:00) to 03:00 EDT on 2021-03-14
+        let new_york = parse_time_zone("America/New_York")?;
+        assert_eq!(
+            new_york.local_to_utc(local("2021-03-14T02:30:00")),
+            local("2021-03-14T07:30:00")
+        );
+        assert_eq!(
+            new_york.local_to_utc(local("2021-03-14T03:30:00")),
+            local("2021-03-14T07:30:00")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cast_with_time_zone_test() -> Result<()> {
+        let tz_type = DataType::Timestamp(
+            TimeUnit::Nanosecond,
+            Some("America/New_York".to_owned()),
+        );
+        let naive_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let strict = CastOptions { safe: false };
+
+        // wall clock time in New York (EDT, -04:00)
+        let strings: ArrayRef = Arc::new(StringArray::from(vec![
+            Some("2021-07-01 08:00:00"),
+            Some("2021-07-01T08:00:00+00:00"),
+            None,
+        ]));
+        let instants = cast_with_time_zone(&strings, &tz_type, &strict)?;
+        assert_eq!(instants.data_type(), &tz_type);
+        let expected: Vec<Option<i64>> = vec![
+            Some(string_to_timestamp_nanos("2021-07-01T12:00:00Z")?),
+            Some(string_to_timestamp_nanos("2021-07-01T08:00:00Z")?),
+            None,
+        ];
+        assert_eq!(timestamp_nanos(&instants)?, expected);
+
+        // casting to another time zone keeps the instant
+        let berlin_type =
+            DataType::Timestamp(TimeUnit::Nanosecond, Some("Europe/Berlin".to_owned()));
+        assert!(!is_time_zone_cast(&tz_type, &berlin_type));
+        let berlin = cast(&instants, &berlin_type)?;
+        assert_eq!(berlin.data_type(), &berlin_type);
+        assert_eq!(timestamp_nanos(&berlin)?, expected);
+
+        // and so does casting to a timestamp without one
+        assert!(!is_time_zone_cast(&tz_type, &naive_type));
+        assert_eq!(timestamp_nanos(&cast(&instants, &naive_type)?)?, expected);
+
+        // the wall clock times in New York are what the strings say
+        let wall_clock = wall_clock_times(&instants, "America/New_York")?;
+        let expected: Vec<Option<i64>> = vec![
+            Some(string_to_timestamp_nanos("2021-07-01T08:00:00Z")?),
+            Some(string_to_timestamp_nanos("2021-07-01T04:00:00Z")?),
+            None,
+        ];
+        assert_eq!(timestamp_nanos(&wall_clock)?, expected);
+
+        // invalid strings are an error, unless the cast is safe
+        let strings: ArrayRef = Arc::new(StringArray::from(vec![
+            Some("2021-07-01 08:00:00"),
+            Some("foo"),
+        ]));
+        assert!(cast_with_time_zone(&strings, &tz_type, &strict).is_err());
+        let instants =
+            cast_with_time_zone(&strings, &tz_type, &CastOptions { safe: true })?;
+        assert_eq!(
+            timestamp_nanos(&instants)?,
+            vec![
+                Some(string_to_timestamp_nanos("2021-07-01T12:00:00Z")?),
+                None
+            ]
+        );
+        Ok(())
+    }
+
     #[test]
     fn to_timestamp_invalid_input_type() -> Result<()> {
         // pass the wrong type of input array to to_timestamp and test
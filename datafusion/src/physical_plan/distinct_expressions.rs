@@ -207,17 +207,163 @@ impl Accumulator for DistinctCountAccumulator {
     }
 }
 
+/// Expression for a DISTINCT aggregation with any other aggregate expression,
+/// e.g. `my_udaf(DISTINCT c)`, which is evaluated on the distinct values of its
+/// arguments once all of them are known.
+#[derive(Debug)]
+pub struct DistinctAggregate {
+    /// The aggregation of the distinct values
+    inner: Arc<dyn AggregateExpr>,
+    /// The DataType used to hold the state for each input
+    state_data_types: Vec<DataType>,
+}
+
+impl DistinctAggregate {
+    /// Create a new DISTINCT aggregation of `inner`, whose arguments are of the
+    /// types `input_data_types`
+    pub fn new(inner: Arc<dyn AggregateExpr>, input_data_types: Vec<DataType>) -> Self {
+        let state_data_types = input_data_types.into_iter().map(state_type).collect();
+
+        Self {
+            inner,
+            state_data_types,
+        }
+    }
+}
+
+impl AggregateExpr for DistinctAggregate {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        self.inner.field()
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        Ok(self
+            .state_data_types
+            .iter()
+            .map(|state_data_type| {
+                Field::new(
+                    &format_state_name(self.inner.name(), "distinct"),
+                    DataType::List(Box::new(Field::new(
+                        "item",
+                        state_data_type.clone(),
+                        true,
+                    ))),
+                    false,
+                )
+            })
+            .collect::<Vec<_>>())
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        self.inner.expressions()
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(DistinctAggregateAccumulator {
+            values: HashSet::default(),
+            state_data_types: self.state_data_types.clone(),
+            inner: self.inner.clone(),
+        }))
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+#[derive(Debug)]
+struct DistinctAggregateAccumulator {
+    values: HashSet<DistinctScalarValues, RandomState>,
+    state_data_types: Vec<DataType>,
+    inner: Arc<dyn AggregateExpr>,
+}
+
+impl Accumulator for DistinctAggregateAccumulator {
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        // unlike COUNT(DISTINCT), leave it to the aggregate to ignore NULLs
+        self.values.insert(DistinctScalarValues(values.to_vec()));
+        Ok(())
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+
+        let col_values = states
+            .iter()
+            .map(|state| match state {
+                ScalarValue::List(Some(values), _) => Ok(values),
+                _ => Err(DataFusionError::Internal(format!(
+                    "Unexpected accumulator state {:?}",
+                    state
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        (0..col_values[0].len()).try_for_each(|row_index| {
+            let row_values = col_values
+                .iter()
+                .map(|col| col[row_index].clone())
+                .collect::<Vec<_>>();
+            self.update(&row_values)
+        })
+    }
+
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(self
+            .state_data_types
+            .iter()
+            .enumerate()
+            .map(|(col_index, state_data_type)| {
+                let values = self
+                    .values
+                    .iter()
+                    .map(|distinct_values| distinct_values.0[col_index].clone())
+                    .collect::<Vec<_>>();
+                ScalarValue::List(
+                    Some(Box::new(values)),
+                    Box::new(state_data_type.clone()),
+                )
+            })
+            .collect())
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        let mut accumulator = self.inner.create_accumulator()?;
+        if !self.values.is_empty() {
+            let columns = (0..self.state_data_types.len())
+                .map(|col_index| {
+                    ScalarValue::iter_to_array(
+                        self.values
+                            .iter()
+                            .map(|distinct_values| distinct_values.0[col_index].clone()),
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?;
+            accumulator.update_batch(&columns)?;
+        }
+        accumulator.evaluate()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::physical_plan::expressions::{col, Sum};
     use arrow::array::{
         ArrayRef, BooleanArray, Float32Array, Float64Array, Int16Array, Int32Array,
         Int64Array, Int8Array, ListArray, UInt16Array, UInt32Array, UInt64Array,
         UInt8Array,
     };
     use arrow::array::{Int32Builder, ListBuilder, UInt64Builder};
-    use arrow::datatypes::DataType;
+    use arrow::datatypes::{DataType, Schema};
 
     macro_rules! build_list {
         ($LISTS:expr, $BUILDER_TYPE:ident) => {{
@@ -705,4 +851,35 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn distinct_aggregate() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let sum = Arc::new(Sum::new(
+            col("a", &schema)?,
+            "SUM(DISTINCT a)",
+            DataType::Int64,
+        ));
+        let agg = DistinctAggregate::new(sum, vec![DataType::Int64]);
+
+        let a1: ArrayRef =
+            Arc::new(Int64Array::from(vec![Some(1), Some(2), None, Some(2)]));
+        let a2: ArrayRef = Arc::new(Int64Array::from(vec![Some(3), Some(1)]));
+
+        // partial aggregations of two batches, merged into a final one
+        let mut accum1 = agg.create_accumulator()?;
+        accum1.update_batch(&[a1])?;
+        let mut accum2 = agg.create_accumulator()?;
+        accum2.update_batch(&[a2])?;
+        let mut accum = agg.create_accumulator()?;
+        accum.merge(&accum1.state()?)?;
+        accum.merge(&accum2.state()?)?;
+
+        assert_eq!(accum.evaluate()?, ScalarValue::Int64(Some(6)));
+        assert_eq!(
+            agg.create_accumulator()?.evaluate()?,
+            ScalarValue::Int64(None)
+        );
+        Ok(())
+    }
 }
@@ -233,6 +233,25 @@ impl Accumulator for AvgAccumulator {
             )),
         }
     }
+
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let values = &values[0];
+
+        self.count -= (values.len() - values.data().null_count()) as u64;
+        self.sum = if self.count == 0 {
+            ScalarValue::try_from(&self.sum.get_datatype())?
+        } else {
+            sum::sub(
+                &self.sum,
+                &sum::sum_batch_as(values, &self.sum.get_datatype())?,
+            )?
+        };
+        Ok(())
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -52,6 +52,7 @@ use crate::scalar::ScalarValue;
 use super::coercion::{
     eq_coercion, like_coercion, numerical_coercion, order_coercion, string_coercion,
 };
+use super::datetime::{date_time_arithmetic_type, DateTimeIntervalExpr};
 
 // Simple (low performance) kernels until optimized kernels are added to arrow
 // See https://github.com/apache/arrow-rs/issues/960
@@ -562,6 +563,11 @@ pub fn binary_operator_data_type(
     op: &Operator,
     rhs_type: &DataType,
 ) -> Result<DataType> {
+    // date, timestamp and interval arithmetic is evaluated by `DateTimeIntervalExpr`
+    if let Some(data_type) = date_time_arithmetic_type(lhs_type, op, rhs_type) {
+        return Ok(data_type);
+    }
+
     // validate that it is possible to perform the operation on incoming types.
     // (or the return datatype cannot be inferred)
     let common_type = common_binary_type(lhs_type, op, rhs_type)?;
@@ -886,6 +892,12 @@ pub fn binary(
     rhs: Arc<dyn PhysicalExpr>,
     input_schema: &Schema,
 ) -> Result<Arc<dyn PhysicalExpr>> {
+    let lhs_type = lhs.data_type(input_schema)?;
+    let rhs_type = rhs.data_type(input_schema)?;
+    if date_time_arithmetic_type(&lhs_type, &op, &rhs_type).is_some() {
+        return Ok(Arc::new(DateTimeIntervalExpr::new(lhs, op, rhs)));
+    }
+
     let (l, r) = binary_cast(lhs, &op, rhs, input_schema)?;
     Ok(Arc::new(BinaryExpr::new(l, op, r)))
 }
@@ -0,0 +1,599 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Arithmetic between dates, timestamps and intervals
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::compute::kernels::cast::cast;
+use arrow::datatypes::{DataType, IntervalUnit, Schema};
+use arrow::record_batch::RecordBatch;
+use arrow::temporal_conversions::timestamp_ns_to_datetime;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Operator;
+use crate::physical_plan::datetime_expressions::{
+    parse_time_zone, timestamp_nanos, ParsedTimeZone,
+};
+use crate::physical_plan::{ColumnarValue, PhysicalExpr};
+use crate::scalar::{make_interval_day_time, split_interval_day_time, ScalarValue};
+
+const NANOS_PER_MILLI: i64 = 1_000_000;
+const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+
+/// An interval split into months, days and nanoseconds
+type IntervalParts = (i32, i32, i64);
+
+fn is_temporal(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Timestamp(_, _) | DataType::Date32 | DataType::Date64
+    )
+}
+
+/// Returns the type of `lhs_type op rhs_type` if the operation is date,
+/// timestamp or interval arithmetic, and `None` otherwise:
+///
+/// * `timestamp ± interval` and `interval + timestamp` return the timestamp type
+/// * `date ± interval` and `interval + date` return the date type
+/// * `timestamp - timestamp` returns a day-time interval
+pub fn date_time_arithmetic_type(
+    lhs_type: &DataType,
+    op: &Operator,
+    rhs_type: &DataType,
+) -> Option<DataType> {
+    match (lhs_type, op, rhs_type) {
+        (DataType::Timestamp(_, _), Operator::Minus, DataType::Timestamp(_, _)) => {
+            Some(DataType::Interval(IntervalUnit::DayTime))
+        }
+        (lhs, Operator::Plus | Operator::Minus, rhs)
+            if is_temporal(lhs) && matches!(rhs, DataType::Interval(_)) =>
+        {
+            Some(lhs.clone())
+        }
+        (lhs, Operator::Plus, rhs)
+            if matches!(lhs, DataType::Interval(_)) && is_temporal(rhs) =>
+        {
+            Some(rhs.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Binary expression for date, timestamp and interval arithmetic, see
+/// [`date_time_arithmetic_type`] for the supported combinations.
+///
+/// Months and days are added to the wall clock time of time zone aware
+/// timestamps, so that `'2021-03-27 12:00 Europe/Berlin' + INTERVAL '1 day'`
+/// is noon on the next day, even though that day is only 23 hours long.
+#[derive(Debug)]
+pub struct DateTimeIntervalExpr {
+    lhs: Arc<dyn PhysicalExpr>,
+    op: Operator,
+    rhs: Arc<dyn PhysicalExpr>,
+}
+
+impl DateTimeIntervalExpr {
+    /// Create a new date time arithmetic expression
+    pub fn new(
+        lhs: Arc<dyn PhysicalExpr>,
+        op: Operator,
+        rhs: Arc<dyn PhysicalExpr>,
+    ) -> Self {
+        Self { lhs, op, rhs }
+    }
+
+    /// Get the left side of the expression
+    pub fn lhs(&self) -> &Arc<dyn PhysicalExpr> {
+        &self.lhs
+    }
+
+    /// Get the operator of the expression
+    pub fn op(&self) -> &Operator {
+        &self.op
+    }
+
+    /// Get the right side of the expression
+    pub fn rhs(&self) -> &Arc<dyn PhysicalExpr> {
+        &self.rhs
+    }
+
+    fn evaluate_arrays(&self, lhs: &ArrayRef, rhs: &ArrayRef) -> Result<ArrayRef> {
+        match (lhs.data_type(), rhs.data_type()) {
+            (DataType::Timestamp(_, _), DataType::Timestamp(_, _)) => {
+                timestamp_difference(lhs, rhs)
+            }
+            (DataType::Interval(_), _) => add_intervals(rhs, lhs, false),
+            _ => add_intervals(lhs, rhs, self.op == Operator::Minus),
+        }
+    }
+}
+
+impl std::fmt::Display for DateTimeIntervalExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.lhs, self.op, self.rhs)
+    }
+}
+
+impl PhysicalExpr for DateTimeIntervalExpr {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, input_schema: &Schema) -> Result<DataType> {
+        let lhs_type = self.lhs.data_type(input_schema)?;
+        let rhs_type = self.rhs.data_type(input_schema)?;
+        date_time_arithmetic_type(&lhs_type, &self.op, &rhs_type).ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "'{:?} {} {:?}' is not a valid date time arithmetic",
+                lhs_type, self.op, rhs_type
+            ))
+        })
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
+        Ok(self.lhs.nullable(input_schema)? || self.rhs.nullable(input_schema)?)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        match (self.lhs.evaluate(batch)?, self.rhs.evaluate(batch)?) {
+            (ColumnarValue::Scalar(lhs), ColumnarValue::Scalar(rhs)) => {
+                let result = self.evaluate_arrays(&lhs.to_array(), &rhs.to_array())?;
+                Ok(ColumnarValue::Scalar(ScalarValue::try_from_array(
+                    &result, 0,
+                )?))
+            }
+            (lhs, rhs) => {
+                let lhs = lhs.into_array(batch.num_rows());
+                let rhs = rhs.into_array(batch.num_rows());
+                Ok(ColumnarValue::Array(self.evaluate_arrays(&lhs, &rhs)?))
+            }
+        }
+    }
+}
+
+fn overflow_error() -> DataFusionError {
+    DataFusionError::Execution("Date time arithmetic overflowed".to_string())
+}
+
+/// Returns the values of an interval array of either unit as months, days and
+/// nanoseconds
+fn interval_parts(array: &ArrayRef) -> Result<Vec<Option<IntervalParts>>> {
+    match array.data_type() {
+        DataType::Interval(IntervalUnit::YearMonth) => {
+            let array = array
+                .as_any()
+                .downcast_ref::<IntervalYearMonthArray>()
+                .ok_or_else(|| {
+                    DataFusionError::Internal("failed to downcast interval".to_string())
+                })?;
+            Ok(array
+                .iter()
+                .map(|v| v.map(|months| (months, 0, 0)))
+                .collect())
+        }
+        DataType::Interval(IntervalUnit::DayTime) => {
+            let array = array
+                .as_any()
+                .downcast_ref::<IntervalDayTimeArray>()
+                .ok_or_else(|| {
+                    DataFusionError::Internal("failed to downcast interval".to_string())
+                })?;
+            Ok(array
+                .iter()
+                .map(|v| {
+                    v.map(|v| {
+                        let (days, millis) = split_interval_day_time(v);
+                        (0, days, millis as i64 * NANOS_PER_MILLI)
+                    })
+                })
+                .collect())
+        }
+        other => Err(DataFusionError::Internal(format!(
+            "Expected an interval array, got {:?}",
+            other
+        ))),
+    }
+}
+
+/// Returns the number of days in the given month
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    Some(
+        NaiveDate::from_ymd_opt(next_year, next_month, 1)?
+            .pred_opt()?
+            .day(),
+    )
+}
+
+/// Moves `date` by `months`, clamping the day to the length of the target
+/// month (`2021-01-31 + 1 month = 2021-02-28`)
+fn shift_months(date: NaiveDate, months: i32) -> Option<NaiveDate> {
+    let total = date.year() as i64 * 12 + date.month0() as i64 + months as i64;
+    let year = i32::try_from(total.div_euclid(12)).ok()?;
+    let month = total.rem_euclid(12) as u32 + 1;
+    let day = date.day().min(days_in_month(year, month)?);
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+fn datetime_to_nanos(datetime: NaiveDateTime) -> Option<i64> {
+    datetime
+        .timestamp()
+        .checked_mul(1_000_000_000)?
+        .checked_add(datetime.timestamp_subsec_nanos() as i64)
+}
+
+/// Adds `months` and `days` to the wall clock time `ts`, in nanoseconds
+fn add_months_days(ts: i64, months: i32, days: i32) -> Option<i64> {
+    if months == 0 && days == 0 {
+        return Some(ts);
+    }
+    let datetime = timestamp_ns_to_datetime(ts);
+    let datetime = shift_months(datetime.date(), months)?
+        .and_time(datetime.time())
+        .checked_add_signed(Duration::days(days as i64))?;
+    datetime_to_nanos(datetime)
+}
+
+/// Adds `interval` to the timestamp `ts`, in nanoseconds. As in PostgreSQL,
+/// months are added first, then days and then the remaining time.
+fn add_interval(
+    ts: i64,
+    interval: IntervalParts,
+    time_zone: Option<&ParsedTimeZone>,
+) -> Option<i64> {
+    let (months, days, nanos) = interval;
+    let ts = match time_zone {
+        Some(tz) => tz.local_to_utc(add_months_days(tz.utc_to_local(ts), months, days)?),
+        None => add_months_days(ts, months, days)?,
+    };
+    ts.checked_add(nanos)
+}
+
+fn negate_interval(interval: IntervalParts) -> Option<IntervalParts> {
+    let (months, days, nanos) = interval;
+    Some((
+        months.checked_neg()?,
+        days.checked_neg()?,
+        nanos.checked_neg()?,
+    ))
+}
+
+/// Dates can only be moved by whole days, as there is no time of day that
+/// the rest of an interval could be added to
+fn check_whole_days(intervals: &[Option<IntervalParts>]) -> Result<()> {
+    match intervals
+        .iter()
+        .flatten()
+        .find(|(_, _, nanos)| nanos % NANOS_PER_DAY != 0)
+    {
+        Some(_) => Err(DataFusionError::Execution(
+            "Cannot add an interval with a time part to a date, cast the date to a timestamp first"
+                .to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Converts dates in days or milliseconds since the epoch to nanoseconds
+fn date_nanos(
+    values: impl Iterator<Item = Option<i64>>,
+    nanos_per_unit: i64,
+) -> Result<Vec<Option<i64>>> {
+    values
+        .map(|v| {
+            v.map(|v| v.checked_mul(nanos_per_unit).ok_or_else(overflow_error))
+                .transpose()
+        })
+        .collect()
+}
+
+/// Adds (or subtracts, if `negate` is set) the intervals in `intervals` to
+/// the dates or timestamps in `temporal`
+fn add_intervals(
+    temporal: &ArrayRef,
+    intervals: &ArrayRef,
+    negate: bool,
+) -> Result<ArrayRef> {
+    let intervals = interval_parts(intervals)?;
+    let apply = |values: Vec<Option<i64>>, time_zone: Option<&ParsedTimeZone>| {
+        values
+            .into_iter()
+            .zip(intervals.iter())
+            .map(|(value, interval)| match (value, interval) {
+                (Some(value), Some(interval)) => {
+                    let interval = if negate {
+                        negate_interval(*interval)
+                    } else {
+                        Some(*interval)
+                    };
+                    interval
+                        .and_then(|interval| add_interval(value, interval, time_zone))
+                        .map(Some)
+                        .ok_or_else(overflow_error)
+                }
+                _ => Ok(None),
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+
+    match temporal.data_type() {
+        DataType::Timestamp(_, tz) => {
+            let time_zone = tz.as_deref().map(parse_time_zone).transpose()?;
+            let values = apply(timestamp_nanos(temporal)?, time_zone.as_ref())?;
+            let result: ArrayRef =
+                Arc::new(TimestampNanosecondArray::from_opt_vec(values, tz.clone()));
+            Ok(cast(&result, temporal.data_type())?)
+        }
+        DataType::Date32 => {
+            let array =
+                temporal
+                    .as_any()
+                    .downcast_ref::<Date32Array>()
+                    .ok_or_else(|| {
+                        DataFusionError::Internal("failed to downcast date".to_string())
+                    })?;
+            check_whole_days(&intervals)?;
+            let values =
+                date_nanos(array.iter().map(|v| v.map(i64::from)), NANOS_PER_DAY)?;
+            let days = apply(values, None)?
+                .into_iter()
+                .map(|v| {
+                    v.map(|ts| i32::try_from(ts.div_euclid(NANOS_PER_DAY)))
+                        .transpose()
+                        .map_err(|_| overflow_error())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Arc::new(Date32Array::from(days)))
+        }
+        DataType::Date64 => {
+            let array =
+                temporal
+                    .as_any()
+                    .downcast_ref::<Date64Array>()
+                    .ok_or_else(|| {
+                        DataFusionError::Internal("failed to downcast date".to_string())
+                    })?;
+            check_whole_days(&intervals)?;
+            let values = date_nanos(array.iter(), NANOS_PER_MILLI)?;
+            let millis = apply(values, None)?
+                .into_iter()
+                .map(|v| {
+                    v.map(|ts| {
+                        ts.div_euclid(NANOS_PER_DAY) * (NANOS_PER_DAY / NANOS_PER_MILLI)
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(Arc::new(Date64Array::from(millis)))
+        }
+        other => Err(DataFusionError::Internal(format!(
+            "Expected a date or timestamp array, got {:?}",
+            other
+        ))),
+    }
+}
+
+/// Returns `lhs - rhs` for two timestamp arrays as day-time intervals of
+/// whole days and the remaining milliseconds. Arrow has no interval type with
+/// a finer unit, so differences of less than a millisecond are truncated.
+fn timestamp_difference(lhs: &ArrayRef, rhs: &ArrayRef) -> Result<ArrayRef> {
+    let values = timestamp_nanos(lhs)?
+        .into_iter()
+        .zip(timestamp_nanos(rhs)?)
+        .map(|(lhs, rhs)| match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => {
+                let diff = lhs.checked_sub(rhs).ok_or_else(overflow_error)?;
+                make_interval_day_time(
+                    diff / NANOS_PER_DAY,
+                    diff % NANOS_PER_DAY / NANOS_PER_MILLI,
+                )
+                .map(Some)
+            }
+            _ => Ok(None),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Arc::new(IntervalDayTimeArray::from(values)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::{binary, col, lit};
+    use arrow::compute::kernels::cast_utils::string_to_timestamp_nanos;
+    use arrow::datatypes::{Field, TimeUnit};
+    use arrow::util::display::array_value_to_string;
+
+    fn evaluate(
+        array: ArrayRef,
+        op: Operator,
+        interval: ScalarValue,
+    ) -> Result<Vec<String>> {
+        let schema = Schema::new(vec![Field::new("a", array.data_type().clone(), true)]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![array])?;
+        let expr = binary(col("a", &schema)?, op, lit(interval), &schema)?;
+        assert_eq!(
+            expr.data_type(&schema)?,
+            batch.schema().field(0).data_type().clone()
+        );
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        (0..result.len())
+            .map(|i| Ok(array_value_to_string(&result, i)?))
+            .collect()
+    }
+
+    fn timestamps(values: &[&str], tz: Option<&str>) -> ArrayRef {
+        let values = values
+            .iter()
+            .map(|v| {
+                NaiveDateTime::parse_from_str(v, "%Y-%m-%dT%H:%M:%S")
+                    .ok()
+                    .map(|v| v.timestamp_nanos())
+            })
+            .collect();
+        Arc::new(TimestampNanosecondArray::from_opt_vec(
+            values,
+            tz.map(|tz| tz.to_string()),
+        ))
+    }
+
+    #[test]
+    fn timestamp_plus_interval() -> Result<()> {
+        let array = timestamps(&["2021-01-31T10:00:00", "2020-02-29T00:00:00"], None);
+        let one_month = ScalarValue::IntervalYearMonth(Some(1));
+        assert_eq!(
+            evaluate(array.clone(), Operator::Plus, one_month.clone())?,
+            vec!["2021-02-28 10:00:00", "2020-03-29 00:00:00"]
+        );
+        assert_eq!(
+            evaluate(array.clone(), Operator::Minus, one_month)?,
+            vec!["2020-12-31 10:00:00", "2020-01-29 00:00:00"]
+        );
+
+        let one_day_one_hour =
+            ScalarValue::IntervalDayTime(Some(make_interval_day_time(1, 3_600_000)?));
+        assert_eq!(
+            evaluate(array.clone(), Operator::Plus, one_day_one_hour.clone())?,
+            vec!["2021-02-01 11:00:00", "2020-03-01 01:00:00"]
+        );
+        assert_eq!(
+            evaluate(array, Operator::Minus, one_day_one_hour)?,
+            vec!["2021-01-30 09:00:00", "2020-02-27 23:00:00"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn timestamp_with_time_zone_plus_interval() -> Result<()> {
+        // 2021-03-27 12:00 in Berlin, the day before the switch to summer time
+        let array = timestamps(&["2021-03-27T11:00:00"], Some("Europe/Berlin"));
+        let one_day = ScalarValue::IntervalDayTime(Some(make_interval_day_time(1, 0)?));
+        let result = evaluate(array, Operator::Plus, one_day)?;
+        // noon on the next day is only 23 hours later
+        assert_eq!(result, vec!["2021-03-28 10:00:00"]);
+        Ok(())
+    }
+
+    #[test]
+    fn date_plus_interval() -> Result<()> {
+        // 2021-01-31
+        let array: ArrayRef = Arc::new(Date32Array::from(vec![Some(18658), None]));
+        let result = evaluate(
+            array.clone(),
+            Operator::Plus,
+            ScalarValue::IntervalYearMonth(Some(13)),
+        )?;
+        assert_eq!(result, vec!["2022-02-28", ""]);
+
+        let result = evaluate(
+            array.clone(),
+            Operator::Minus,
+            ScalarValue::IntervalDayTime(Some(make_interval_day_time(31, 0)?)),
+        )?;
+        assert_eq!(result, vec!["2020-12-31", ""]);
+
+        // a time part would be lost
+        let one_and_a_half_days =
+            ScalarValue::IntervalDayTime(Some(make_interval_day_time(1, 43_200_000)?));
+        let err = evaluate(array.clone(), Operator::Plus, one_and_a_half_days)
+            .expect_err("a time part should not be added to a date");
+        assert!(err.to_string().contains("interval with a time part"));
+
+        // dates in milliseconds that overflow in nanoseconds are an error
+        let array: ArrayRef = Arc::new(Date64Array::from(vec![Some(i64::MAX)]));
+        let err = evaluate(
+            array,
+            Operator::Plus,
+            ScalarValue::IntervalYearMonth(Some(1)),
+        )
+        .expect_err("the date should overflow");
+        assert!(err.to_string().contains("overflowed"));
+        Ok(())
+    }
+
+    #[test]
+    fn timestamp_minus_timestamp() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Timestamp(TimeUnit::Nanosecond, None), true),
+            Field::new("b", DataType::Timestamp(TimeUnit::Nanosecond, None), true),
+        ]);
+        // the last row differs by less than a millisecond
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    string_to_timestamp_nanos("2021-01-02T12:00:01")?,
+                    string_to_timestamp_nanos("2021-01-01T00:00:00")?,
+                    1_500,
+                ])),
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    string_to_timestamp_nanos("2021-01-01T00:00:00")?,
+                    string_to_timestamp_nanos("2021-01-01T06:00:00")?,
+                    1,
+                ])),
+            ],
+        )?;
+        let expr = binary(
+            col("a", &schema)?,
+            Operator::Minus,
+            col("b", &schema)?,
+            &schema,
+        )?;
+        assert_eq!(
+            expr.data_type(&schema)?,
+            DataType::Interval(IntervalUnit::DayTime)
+        );
+
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result
+            .as_any()
+            .downcast_ref::<IntervalDayTimeArray>()
+            .expect("the difference should be a day-time interval")
+            .iter()
+            .map(|v| v.map(split_interval_day_time))
+            .collect::<Vec<_>>();
+        // differences of less than a millisecond are truncated
+        assert_eq!(
+            result,
+            vec![Some((1, 43_201_000)), Some((0, -21_600_000)), Some((0, 0))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shift_months_clamps_day() {
+        let date = NaiveDate::from_ymd(2020, 1, 31);
+        assert_eq!(
+            shift_months(date, 1),
+            Some(NaiveDate::from_ymd(2020, 2, 29))
+        );
+        assert_eq!(
+            shift_months(date, -2),
+            Some(NaiveDate::from_ymd(2019, 11, 30))
+        );
+        assert_eq!(
+            shift_months(date, 12),
+            Some(NaiveDate::from_ymd(2021, 1, 31))
+        );
+    }
+}
@@ -36,6 +36,7 @@ mod coercion;
 mod column;
 mod count;
 mod cume_dist;
+mod datetime;
 mod get_indexed_field;
 mod in_list;
 mod is_not_null;
@@ -47,6 +48,7 @@ mod min_max;
 mod negative;
 mod not;
 mod nth_value;
+mod ntile;
 mod nullif;
 mod rank;
 mod row_number;
@@ -73,6 +75,7 @@ pub use cast::{
 pub use column::{col, Column};
 pub use count::Count;
 pub use cume_dist::cume_dist;
+pub use datetime::{date_time_arithmetic_type, DateTimeIntervalExpr};
 pub use get_indexed_field::GetIndexedFieldExpr;
 pub use in_list::{in_list, InListExpr};
 pub use is_not_null::{is_not_null, IsNotNullExpr};
@@ -84,6 +87,7 @@ pub(crate) use min_max::{MaxAccumulator, MinAccumulator};
 pub use negative::{negative, NegativeExpr};
 pub use not::{not, NotExpr};
 pub use nth_value::NthValue;
+pub use ntile::ntile;
 pub use nullif::{nullif_func, SUPPORTED_NULLIF_TYPES};
 pub use rank::{dense_rank, percent_rank, rank};
 pub use row_number::RowNumber;
@@ -126,6 +130,15 @@ impl std::fmt::Display for PhysicalSortExpr {
 }
 
 impl PhysicalSortExpr {
+    /// Returns true if rows sorted by this expression are also sorted by
+    /// `other`. Expressions are compared by their display form, which
+    /// includes the index of the columns they reference.
+    pub fn satisfies(&self, other: &PhysicalSortExpr) -> bool {
+        self.options.descending == other.options.descending
+            && self.options.nulls_first == other.options.nulls_first
+            && self.expr.to_string() == other.expr.to_string()
+    }
+
     /// evaluate the sort expression into SortColumn that can be passed into arrow sort kernel
     pub fn evaluate_to_sort_column(&self, batch: &RecordBatch) -> Result<SortColumn> {
         let value_to_sort = self.expr.evaluate(batch)?;
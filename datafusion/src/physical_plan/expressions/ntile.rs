@@ -0,0 +1,110 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the `ntile` window function, as a window function evaluated over whole partitions
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::functions::ReturnTypeFunction;
+use crate::physical_plan::udwf::{
+    PartitionEvaluator, PartitionEvaluatorFactory, WindowPartition, WindowUDF,
+};
+use crate::physical_plan::window_functions::{
+    return_type_for_built_in, signature_for_built_in, BuiltInWindowFunction,
+};
+use crate::scalar::ScalarValue;
+use arrow::array::{ArrayRef, UInt32Array};
+use std::sync::Arc;
+
+/// ntile window function, that divides the rows of each partition into buckets of
+/// sizes as equal as possible and returns the number of the bucket of each row
+pub fn ntile() -> WindowUDF {
+    let fun = BuiltInWindowFunction::Ntile;
+    let return_type: ReturnTypeFunction = Arc::new(|arg_types| {
+        Ok(Arc::new(return_type_for_built_in(
+            &BuiltInWindowFunction::Ntile,
+            arg_types,
+        )?))
+    });
+    let partition_evaluator: PartitionEvaluatorFactory =
+        Arc::new(|| Ok(Box::new(NtileEvaluator {})));
+    WindowUDF::new(
+        &fun.to_string(),
+        &signature_for_built_in(&fun),
+        &return_type,
+        &partition_evaluator,
+    )
+}
+
+#[derive(Debug)]
+struct NtileEvaluator {}
+
+impl PartitionEvaluator for NtileEvaluator {
+    fn evaluate(&mut self, partition: &WindowPartition) -> Result<ArrayRef> {
+        let num_rows = partition.num_rows();
+        let buckets = match ScalarValue::try_from_array(&partition.values()[0], 0)? {
+            ScalarValue::UInt64(Some(n)) if n > 0 => n as usize,
+            ScalarValue::Int64(Some(n)) if n > 0 => n as usize,
+            ScalarValue::UInt64(None) | ScalarValue::Int64(None) => {
+                return Ok(Arc::new(UInt32Array::from(vec![None; num_rows])))
+            }
+            _ => {
+                return Err(DataFusionError::Execution(
+                    "Argument of ntile must be greater than zero".to_owned(),
+                ))
+            }
+        };
+        // the first `num_rows % buckets` buckets have one more row than the others
+        let size = num_rows / buckets;
+        let larger = num_rows % buckets;
+        let larger_rows = larger * (size + 1);
+        Ok(Arc::new(UInt32Array::from_iter_values((0..num_rows).map(
+            |row| {
+                let bucket = if row < larger_rows {
+                    row / (size + 1)
+                } else {
+                    larger + (row - larger_rows) / size
+                };
+                bucket as u32 + 1
+            },
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::UInt64Array;
+
+    fn evaluate_ntile(buckets: u64, num_rows: usize) -> Result<Vec<u32>> {
+        let values: Vec<ArrayRef> =
+            vec![Arc::new(UInt64Array::from(vec![buckets; num_rows]))];
+        let peers = vec![0..num_rows];
+        let partition = WindowPartition::new(&values, &peers, &[], None);
+        let result = (ntile().partition_evaluator)()?.evaluate(&partition)?;
+        let result = result.as_any().downcast_ref::<UInt32Array>().unwrap();
+        Ok(result.values().to_vec())
+    }
+
+    #[test]
+    fn ntile_buckets() -> Result<()> {
+        assert_eq!(vec![1, 1, 1, 2, 2, 2, 3, 3], evaluate_ntile(3, 8)?);
+        assert_eq!(vec![1, 1, 2, 2], evaluate_ntile(2, 4)?);
+        assert_eq!(vec![1, 2, 3], evaluate_ntile(5, 3)?);
+        assert!(evaluate_ntile(0, 3).is_err());
+        Ok(())
+    }
+}
@@ -236,6 +236,14 @@ impl Accumulator for StddevAccumulator {
         self.variance.merge_batch(states)
     }
 
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        self.variance.retract_batch(values)
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        self.variance.supports_retract_batch()
+    }
+
     fn evaluate(&self) -> Result<ScalarValue> {
         let variance = self.variance.evaluate()?;
         match variance {
@@ -143,6 +143,9 @@ impl AggregateExpr for Sum {
 #[derive(Debug)]
 struct SumAccumulator {
     sum: ScalarValue,
+    // number of non null values in the sum, used to make it null again once all of
+    // them are retracted
+    count: u64,
 }
 
 impl SumAccumulator {
@@ -150,6 +153,7 @@ impl SumAccumulator {
     pub fn try_new(data_type: &DataType) -> Result<Self> {
         Ok(Self {
             sum: ScalarValue::try_from(data_type)?,
+            count: 0,
         })
     }
 }
@@ -348,6 +352,63 @@ pub(super) fn sum(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
     })
 }
 
+// returns the difference of two scalar values of the same type.
+macro_rules! typed_sub {
+    ($OLD_VALUE:expr, $DELTA:expr, $SCALAR:ident) => {{
+        ScalarValue::$SCALAR(match ($OLD_VALUE, $DELTA) {
+            (Some(a), Some(b)) => Some(*a - *b),
+            (a, _) => *a,
+        })
+    }};
+}
+
+/// Returns `lhs` minus `rhs`, the inverse of [`sum`] for a `rhs` summed by
+/// [`sum_batch_as`] into the type of `lhs`.
+pub(super) fn sub(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
+    Ok(match (lhs, rhs) {
+        (ScalarValue::Decimal128(v1, p1, s1), ScalarValue::Decimal128(v2, _, s2))
+            if s1 >= s2 =>
+        {
+            let value = match (v1, v2) {
+                (Some(a), Some(b)) => Some(a - b * 10_i128.pow((s1 - s2) as u32)),
+                (a, _) => *a,
+            };
+            ScalarValue::Decimal128(value, *p1, *s1)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::Float64(rhs)) => {
+            typed_sub!(lhs, rhs, Float64)
+        }
+        (ScalarValue::Float32(lhs), ScalarValue::Float32(rhs)) => {
+            typed_sub!(lhs, rhs, Float32)
+        }
+        (ScalarValue::UInt64(lhs), ScalarValue::UInt64(rhs)) => {
+            typed_sub!(lhs, rhs, UInt64)
+        }
+        (ScalarValue::Int64(lhs), ScalarValue::Int64(rhs)) => {
+            typed_sub!(lhs, rhs, Int64)
+        }
+        e => {
+            return Err(DataFusionError::Internal(format!(
+                "Sum is not expected to retract a scalar {:?}",
+                e
+            )));
+        }
+    })
+}
+
+/// Sums the array into a scalar value of `data_type`, the type of the sum it is
+/// retracted from. Decimals keep their type, as [`sub`] rescales them.
+pub(super) fn sum_batch_as(
+    values: &ArrayRef,
+    data_type: &DataType,
+) -> Result<ScalarValue> {
+    match values.data_type() {
+        DataType::Decimal(_, _) => sum_batch(values),
+        values_type if values_type == data_type => sum_batch(values),
+        _ => sum_batch(&compute::cast(values, data_type)?),
+    }
+}
+
 impl Accumulator for SumAccumulator {
     fn state(&self) -> Result<Vec<ScalarValue>> {
         Ok(vec![self.sum.clone()])
@@ -355,16 +416,33 @@ impl Accumulator for SumAccumulator {
 
     fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
         // sum(v1, v2, v3) = v1 + v2 + v3
+        self.count += (!values[0].is_null()) as u64;
         self.sum = sum(&self.sum, &values[0])?;
         Ok(())
     }
 
     fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
         let values = &values[0];
+        self.count += (values.len() - values.data().null_count()) as u64;
         self.sum = sum(&self.sum, &sum_batch(values)?)?;
         Ok(())
     }
 
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let values = &values[0];
+        self.count -= (values.len() - values.data().null_count()) as u64;
+        self.sum = if self.count == 0 {
+            ScalarValue::try_from(&self.sum.get_datatype())?
+        } else {
+            sub(&self.sum, &sum_batch_as(values, &self.sum.get_datatype())?)?
+        };
+        Ok(())
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        true
+    }
+
     fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
         // sum(sum1, sum2) = sum1 + sum2
         self.update(states)
@@ -605,6 +683,21 @@ mod tests {
         )
     }
 
+    #[test]
+    fn sum_retract() -> Result<()> {
+        let mut accum = SumAccumulator::try_new(&DataType::Int64)?;
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None, Some(3)]));
+        let b: ArrayRef = Arc::new(Int32Array::from(vec![Some(4), Some(5)]));
+        accum.update_batch(&[a.clone()])?;
+        accum.update_batch(&[b])?;
+        assert_eq!(accum.evaluate()?, ScalarValue::Int64(Some(13)));
+        accum.retract_batch(&[a])?;
+        assert_eq!(accum.evaluate()?, ScalarValue::Int64(Some(9)));
+        accum.retract_batch(&[Arc::new(Int32Array::from(vec![4, 5]))])?;
+        assert_eq!(accum.evaluate()?, ScalarValue::Int64(None));
+        Ok(())
+    }
+
     fn aggregate(
         batch: &RecordBatch,
         agg: Arc<dyn AggregateExpr>,
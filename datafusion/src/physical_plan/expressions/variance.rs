@@ -277,6 +277,36 @@ impl Accumulator for VarianceAccumulator {
         Ok(())
     }
 
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let values = &cast(&values[0], &DataType::Float64)?;
+        let arr = values.as_any().downcast_ref::<Float64Array>().unwrap();
+
+        for i in 0..arr.len() {
+            let value = arr.value(i);
+
+            if value == 0_f64 && values.is_null(i) {
+                continue;
+            }
+            // reverts the update of `update_batch` from the state of count - 1 values
+            let new_count = self.count - 1;
+            if new_count == 0 {
+                self.mean = 0_f64;
+                self.m2 = 0_f64;
+            } else {
+                let new_mean = (self.mean * self.count as f64 - value) / new_count as f64;
+                self.m2 -= (value - new_mean) * (value - self.mean);
+                self.mean = new_mean;
+            }
+            self.count = new_count;
+        }
+
+        Ok(())
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        true
+    }
+
     fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
         let counts = states[0].as_any().downcast_ref::<UInt64Array>().unwrap();
         let means = states[1].as_any().downcast_ref::<Float64Array>().unwrap();
@@ -605,6 +635,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn variance_retract() -> Result<()> {
+        let mut accum = VarianceAccumulator::try_new(StatsType::Population)?;
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![Some(10), None, Some(20)]));
+        let b: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3, 4]));
+        accum.update_batch(&[a.clone()])?;
+        accum.update_batch(&[b])?;
+        accum.retract_batch(&[a])?;
+        assert_eq!(accum.get_count(), 4);
+        assert!((accum.get_mean() - 2.5).abs() < 1e-9);
+        match accum.evaluate()? {
+            ScalarValue::Float64(Some(variance)) => {
+                assert!((variance - 1.25).abs() < 1e-9)
+            }
+            other => panic!("unexpected variance {:?}", other),
+        }
+        Ok(())
+    }
+
     fn aggregate(
         batch: &RecordBatch,
         agg: Arc<dyn AggregateExpr>,
@@ -20,7 +20,8 @@
 use crate::avro_to_arrow;
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
-    DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
+    expressions::PhysicalSortExpr, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
 };
 use arrow::datatypes::SchemaRef;
 #[cfg(feature = "avro")]
@@ -73,6 +74,10 @@ impl ExecutionPlan for AvroExec {
         Partitioning::UnknownPartitioning(self.base_config.file_groups.len())
     }
 
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        self.base_config.projected_output_ordering()
+    }
+
     fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
         Vec::new()
     }
@@ -182,6 +187,7 @@ mod tests {
             batch_size: 1024,
             limit: None,
             table_partition_cols: vec![],
+            output_ordering: None,
         });
         assert_eq!(avro_exec.output_partitioning().partition_count(), 1);
 
@@ -243,6 +249,7 @@ mod tests {
             batch_size: 1024,
             limit: None,
             table_partition_cols: vec!["date".to_owned()],
+            output_ordering: None,
         });
         assert_eq!(avro_exec.output_partitioning().partition_count(), 1);
 
@@ -19,7 +19,8 @@
 
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
-    DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
+    expressions::PhysicalSortExpr, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
 };
 
 use arrow::csv;
@@ -87,6 +88,10 @@ impl ExecutionPlan for CsvExec {
         Partitioning::UnknownPartitioning(self.base_config.file_groups.len())
     }
 
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        self.base_config.projected_output_ordering()
+    }
+
     fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
         // this is a leaf node and has no children
         vec![]
@@ -187,6 +192,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             true,
             b',',
@@ -233,6 +239,7 @@ mod tests {
                 batch_size: 1024,
                 limit: Some(5),
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             true,
             b',',
@@ -286,6 +293,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec!["date".to_owned()],
+                output_ordering: None,
             },
             true,
             b',',
@@ -20,7 +20,8 @@ use async_trait::async_trait;
 
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
-    DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
+    expressions::PhysicalSortExpr, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
 };
 use arrow::{datatypes::SchemaRef, json};
 use std::any::Any;
@@ -64,6 +65,10 @@ impl ExecutionPlan for NdJsonExec {
         Partitioning::UnknownPartitioning(self.base_config.file_groups.len())
     }
 
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        self.base_config.projected_output_ordering()
+    }
+
     fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
         Vec::new()
     }
@@ -165,6 +170,7 @@ mod tests {
             batch_size: 1024,
             limit: Some(3),
             table_partition_cols: vec![],
+            output_ordering: None,
         });
 
         // TODO: this is not where schema inference should be tested
@@ -219,6 +225,7 @@ mod tests {
             batch_size: 1024,
             limit: None,
             table_partition_cols: vec![],
+            output_ordering: None,
         });
         let inferred_schema = exec.schema();
         assert_eq!(inferred_schema.fields().len(), 2);
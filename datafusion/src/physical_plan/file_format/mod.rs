@@ -47,7 +47,10 @@ use std::{
     vec,
 };
 
-use super::{ColumnStatistics, Statistics};
+use super::{
+    expressions::{Column, PhysicalSortExpr},
+    ColumnStatistics, Statistics,
+};
 
 lazy_static! {
     /// The datatype used for all partitioning columns for now
@@ -76,6 +79,9 @@ pub struct PhysicalPlanConfig {
     pub limit: Option<usize>,
     /// The partitioning column names
     pub table_partition_cols: Vec<String>,
+    /// The order in which the rows of every file are sorted, on the columns of
+    /// `file_schema` followed by `table_partition_cols`
+    pub output_ordering: Option<Vec<PhysicalSortExpr>>,
 }
 
 impl PhysicalPlanConfig {
@@ -127,6 +133,34 @@ impl PhysicalPlanConfig {
         (table_schema, table_stats)
     }
 
+    /// The `output_ordering` on the projected schema, up to the first sort
+    /// column that is not projected. A partition that reads several files
+    /// is not sorted, as the files are read one after the other.
+    fn projected_output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        if self.file_groups.iter().any(|files| files.len() > 1) {
+            return None;
+        }
+        let ordering = self
+            .output_ordering
+            .as_ref()?
+            .iter()
+            .map_while(|sort| {
+                let column = sort.expr.as_any().downcast_ref::<Column>()?;
+                let index = match &self.projection {
+                    Some(projection) => {
+                        projection.iter().position(|idx| *idx == column.index())?
+                    }
+                    None => column.index(),
+                };
+                Some(PhysicalSortExpr {
+                    expr: Arc::new(Column::new(column.name(), index)),
+                    options: sort.options,
+                })
+            })
+            .collect::<Vec<_>>();
+        (!ordering.is_empty()).then(|| ordering)
+    }
+
     fn projected_file_column_names(&self) -> Option<Vec<String>> {
         self.projection.as_ref().map(|p| {
             p.iter()
@@ -485,6 +519,7 @@ mod tests {
             projection,
             statistics,
             table_partition_cols,
+            output_ordering: None,
         }
     }
 }
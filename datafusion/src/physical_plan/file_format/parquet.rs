@@ -29,8 +29,10 @@ use crate::{
     logical_plan::{Column, Expr},
     physical_optimizer::pruning::{PruningPredicate, PruningStatistics},
     physical_plan::{
+        expressions::PhysicalSortExpr,
         file_format::PhysicalPlanConfig,
         metrics::{self, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet},
+        runtime_filter::ProbeFilter,
         stream::RecordBatchReceiverStream,
         DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
         Statistics,
@@ -73,6 +75,8 @@ pub struct ParquetExec {
     metrics: ExecutionPlanMetricsSet,
     /// Optional predicate for pruning row groups
     pruning_predicate: Option<PruningPredicate>,
+    /// The runtime filters of hash joins also used to prune row groups
+    runtime_filters: Vec<ProbeFilter>,
 }
 
 /// Stores metrics about the parquet execution for a particular parquet file
@@ -120,6 +124,7 @@ impl ParquetExec {
             projected_statistics,
             metrics,
             pruning_predicate,
+            runtime_filters: vec![],
         }
     }
 
@@ -127,6 +132,17 @@ impl ParquetExec {
     pub fn base_config(&self) -> &PhysicalPlanConfig {
         &self.base_config
     }
+
+    /// Also prune row groups with the runtime filters published by hash joins
+    pub fn with_runtime_filters(mut self, runtime_filters: Vec<ProbeFilter>) -> Self {
+        self.runtime_filters = runtime_filters;
+        self
+    }
+
+    /// The runtime filters of hash joins also used to prune row groups
+    pub fn runtime_filters(&self) -> &[ProbeFilter] {
+        &self.runtime_filters
+    }
 }
 
 impl ParquetFileMetrics {
@@ -172,6 +188,10 @@ impl ExecutionPlan for ParquetExec {
         Partitioning::UnknownPartitioning(self.base_config.file_groups.len())
     }
 
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        self.base_config.projected_output_ordering()
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -200,7 +220,22 @@ impl ExecutionPlan for ParquetExec {
             Some(proj) => proj,
             None => (0..self.base_config.file_schema.fields().len()).collect(),
         };
-        let pruning_predicate = self.pruning_predicate.clone();
+        // the joins publish their runtime filters before executing their
+        // probe side
+        let runtime_pruning_predicates = self
+            .runtime_filters
+            .iter()
+            .filter_map(ProbeFilter::pruning_expr)
+            .filter_map(|expr| {
+                PruningPredicate::try_new(&expr, self.base_config.file_schema.clone())
+                    .ok()
+            });
+        let pruning_predicates: Vec<_> = self
+            .pruning_predicate
+            .iter()
+            .cloned()
+            .chain(runtime_pruning_predicates)
+            .collect();
         let batch_size = self.base_config.batch_size;
         let limit = self.base_config.limit;
         let object_store = Arc::clone(&self.base_config.object_store);
@@ -216,7 +251,7 @@ impl ExecutionPlan for ParquetExec {
                 partition,
                 metrics,
                 &projection,
-                &pruning_predicate,
+                &pruning_predicates,
                 batch_size,
                 response_tx,
                 limit,
@@ -388,7 +423,7 @@ fn read_partition(
     partition: Vec<PartitionedFile>,
     metrics: ExecutionPlanMetricsSet,
     projection: &[usize],
-    pruning_predicate: &Option<PruningPredicate>,
+    pruning_predicates: &[PruningPredicate],
     batch_size: usize,
     response_tx: Sender<ArrowResult<RecordBatch>>,
     limit: Option<usize>,
@@ -405,10 +440,10 @@ fn read_partition(
             object_store.file_reader(partitioned_file.file_meta.sized_file.clone())?;
         let mut file_reader =
             SerializedFileReader::new(ChunkObjectReader(object_reader))?;
-        if let Some(pruning_predicate) = pruning_predicate {
+        for pruning_predicate in pruning_predicates {
             let row_group_predicate = build_row_group_predicate(
                 pruning_predicate,
-                file_metrics,
+                file_metrics.clone(),
                 file_reader.metadata().row_groups(),
             );
             file_reader.filter_row_groups(&row_group_predicate);
@@ -485,6 +520,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             None,
         );
@@ -540,6 +576,7 @@ mod tests {
                     "month".to_owned(),
                     "day".to_owned(),
                 ],
+                output_ordering: None,
             },
             None,
         );
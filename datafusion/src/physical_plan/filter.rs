@@ -19,14 +19,21 @@
 //! include in its output batches.
 
 use std::any::Any;
+use std::cmp::Ordering;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use super::{RecordBatchStream, SendableRecordBatchStream, Statistics};
+use super::{ColumnStatistics, RecordBatchStream, SendableRecordBatchStream, Statistics};
 use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Operator;
 use crate::physical_plan::{
+    expressions::{
+        BinaryExpr, CastExpr, Column, InListExpr, IsNotNullExpr, IsNullExpr, Literal,
+        NotExpr, PhysicalSortExpr, TryCastExpr,
+    },
     metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet},
+    runtime_filter::ProbeFilter,
     DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
 };
 use arrow::array::BooleanArray;
@@ -35,6 +42,7 @@ use arrow::datatypes::{DataType, SchemaRef};
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 
+use crate::scalar::ScalarValue;
 use async_trait::async_trait;
 
 use futures::stream::{Stream, StreamExt};
@@ -47,6 +55,8 @@ pub struct FilterExec {
     predicate: Arc<dyn PhysicalExpr>,
     /// The input plan
     input: Arc<dyn ExecutionPlan>,
+    /// The runtime filters of hash joins applied in addition to the predicate
+    runtime_filters: Vec<ProbeFilter>,
     /// Execution metrics
     metrics: ExecutionPlanMetricsSet,
 }
@@ -61,6 +71,7 @@ impl FilterExec {
             DataType::Boolean => Ok(Self {
                 predicate,
                 input: input.clone(),
+                runtime_filters: vec![],
                 metrics: ExecutionPlanMetricsSet::new(),
             }),
             other => Err(DataFusionError::Plan(format!(
@@ -79,6 +90,17 @@ impl FilterExec {
     pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
         &self.input
     }
+
+    /// Also filter on the runtime filters published by hash joins
+    pub fn with_runtime_filters(mut self, runtime_filters: Vec<ProbeFilter>) -> Self {
+        self.runtime_filters = runtime_filters;
+        self
+    }
+
+    /// The runtime filters of hash joins applied in addition to the predicate
+    pub fn runtime_filters(&self) -> &[ProbeFilter] {
+        &self.runtime_filters
+    }
 }
 
 #[async_trait]
@@ -103,15 +125,19 @@ impl ExecutionPlan for FilterExec {
         self.input.output_partitioning()
     }
 
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        self.input.output_ordering()
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            1 => Ok(Arc::new(FilterExec::try_new(
-                self.predicate.clone(),
-                children[0].clone(),
-            )?)),
+            1 => Ok(Arc::new(
+                FilterExec::try_new(self.predicate.clone(), children[0].clone())?
+                    .with_runtime_filters(self.runtime_filters.clone()),
+            )),
             _ => Err(DataFusionError::Internal(
                 "FilterExec wrong number of children".to_string(),
             )),
@@ -121,10 +147,21 @@ impl ExecutionPlan for FilterExec {
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
         let baseline_metrics = BaselineMetrics::new(&self.metrics, partition);
 
+        let input = self.input.execute(partition).await?;
+        // the joins publish their runtime filters before executing their
+        // probe side
+        let predicate = self
+            .runtime_filters
+            .iter()
+            .filter_map(ProbeFilter::physical_expr)
+            .fold(self.predicate.clone(), |predicate, runtime_filter| {
+                Arc::new(BinaryExpr::new(predicate, Operator::And, runtime_filter)) as _
+            });
+
         Ok(Box::pin(FilterExecStream {
             schema: self.input.schema().clone(),
-            predicate: self.predicate.clone(),
-            input: self.input.execute(partition).await?,
+            predicate,
+            input,
             baseline_metrics,
         }))
     }
@@ -145,9 +182,278 @@ impl ExecutionPlan for FilterExec {
         Some(self.metrics.clone_inner())
     }
 
-    /// The output statistics of a filtering operation are unknown
+    /// The output statistics of a filtering operation are estimated from the
+    /// column statistics of its input, see [`estimate_selectivity`]
     fn statistics(&self) -> Statistics {
-        Statistics::default()
+        estimate_filter_statistics(self.input.statistics(), &self.predicate)
+    }
+}
+
+/// Selectivity of an equality predicate on a column without distinct count
+const DEFAULT_EQUALITY_SELECTIVITY: f64 = 0.1;
+/// Selectivity of a range predicate on a column without min/max values
+const DEFAULT_RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+/// Selectivity of the predicates that cannot be estimated
+const DEFAULT_SELECTIVITY: f64 = 0.5;
+
+/// Estimates the statistics of the rows of an input with the `input`
+/// statistics that satisfy `predicate`
+fn estimate_filter_statistics(
+    input: Statistics,
+    predicate: &Arc<dyn PhysicalExpr>,
+) -> Statistics {
+    let num_rows = match input.num_rows {
+        Some(num_rows) => num_rows,
+        None => return Statistics::default(),
+    };
+    let selectivity =
+        estimate_selectivity(predicate, input.column_statistics.as_deref(), num_rows)
+            .clamp(0.0, 1.0);
+    let scale = |n: usize| (n as f64 * selectivity).ceil() as usize;
+    let num_rows = scale(num_rows);
+
+    // min and max values of the input remain valid bounds of the output
+    let column_statistics = input.column_statistics.map(|column_statistics| {
+        column_statistics
+            .into_iter()
+            .map(|stats| ColumnStatistics {
+                null_count: stats.null_count.map(|n| n.min(num_rows)),
+                distinct_count: stats.distinct_count.map(|n| n.min(num_rows)),
+                ..stats
+            })
+            .collect()
+    });
+    Statistics {
+        num_rows: Some(num_rows),
+        total_byte_size: input.total_byte_size.map(scale),
+        column_statistics,
+        is_exact: false,
+    }
+}
+
+/// Estimates the fraction of the `num_rows` rows with the given column
+/// statistics that satisfy `predicate`
+pub(crate) fn estimate_selectivity(
+    predicate: &Arc<dyn PhysicalExpr>,
+    column_statistics: Option<&[ColumnStatistics]>,
+    num_rows: usize,
+) -> f64 {
+    let num_rows = num_rows.max(1) as f64;
+    let column_stats =
+        |expr: &Arc<dyn PhysicalExpr>| column_statistics?.get(as_column(expr)?.index());
+    let null_fraction = |expr: &Arc<dyn PhysicalExpr>| {
+        column_stats(expr)
+            .and_then(|stats| stats.null_count)
+            .map_or(DEFAULT_EQUALITY_SELECTIVITY, |n| n as f64 / num_rows)
+    };
+    let selectivity = |expr: &Arc<dyn PhysicalExpr>| {
+        estimate_selectivity(expr, column_statistics, num_rows as usize)
+    };
+
+    let expr = predicate.as_any();
+    if let Some(binary) = expr.downcast_ref::<BinaryExpr>() {
+        let (left, right, op) = (binary.left(), binary.right(), binary.op());
+        match op {
+            Operator::And => return selectivity(left) * selectivity(right),
+            Operator::Or => {
+                let (left, right) = (selectivity(left), selectivity(right));
+                return left + right - left * right;
+            }
+            _ => {}
+        }
+        if let Some(value) = literal_value(right) {
+            comparison_selectivity(column_stats(left), op, value, num_rows)
+        } else if let (Some(value), Some(op)) = (literal_value(left), swap_operator(op)) {
+            comparison_selectivity(column_stats(right), &op, value, num_rows)
+        } else if op == &Operator::Eq {
+            match (column_stats(left), column_stats(right)) {
+                (Some(left), Some(right)) => {
+                    match left.distinct_count.max(right.distinct_count) {
+                        Some(distinct_count) => 1.0 / distinct_count.max(1) as f64,
+                        None => DEFAULT_EQUALITY_SELECTIVITY,
+                    }
+                }
+                _ => DEFAULT_EQUALITY_SELECTIVITY,
+            }
+        } else {
+            default_selectivity(op)
+        }
+    } else if let Some(not) = expr.downcast_ref::<NotExpr>() {
+        1.0 - selectivity(not.arg())
+    } else if let Some(is_null) = expr.downcast_ref::<IsNullExpr>() {
+        null_fraction(is_null.arg())
+    } else if let Some(is_not_null) = expr.downcast_ref::<IsNotNullExpr>() {
+        1.0 - null_fraction(is_not_null.arg())
+    } else if let Some(in_list) = expr.downcast_ref::<InListExpr>() {
+        let stats = column_stats(in_list.expr());
+        let selectivity = in_list
+            .list()
+            .iter()
+            .map(|item| match literal_value(item) {
+                Some(value) => {
+                    comparison_selectivity(stats, &Operator::Eq, value, num_rows)
+                }
+                None => DEFAULT_EQUALITY_SELECTIVITY,
+            })
+            .sum::<f64>()
+            .min(1.0);
+        if in_list.negated() {
+            1.0 - selectivity
+        } else {
+            selectivity
+        }
+    } else if let Some(value) = literal_value(predicate) {
+        match value {
+            ScalarValue::Boolean(Some(true)) => 1.0,
+            _ => 0.0,
+        }
+    } else {
+        DEFAULT_SELECTIVITY
+    }
+}
+
+/// Returns the value of `expr` if it is a literal
+fn literal_value(expr: &Arc<dyn PhysicalExpr>) -> Option<&ScalarValue> {
+    expr.as_any()
+        .downcast_ref::<Literal>()
+        .map(|literal| literal.value())
+}
+
+/// Returns the column `expr` refers to, through the casts added by the type
+/// coercion
+fn as_column(expr: &Arc<dyn PhysicalExpr>) -> Option<&Column> {
+    let any = expr.as_any();
+    if let Some(cast) = any.downcast_ref::<CastExpr>() {
+        as_column(cast.expr())
+    } else if let Some(try_cast) = any.downcast_ref::<TryCastExpr>() {
+        as_column(try_cast.expr())
+    } else {
+        any.downcast_ref::<Column>()
+    }
+}
+
+/// Estimates the selectivity of `column <op> value`
+fn comparison_selectivity(
+    stats: Option<&ColumnStatistics>,
+    op: &Operator,
+    value: &ScalarValue,
+    num_rows: f64,
+) -> f64 {
+    let stats = match stats {
+        Some(stats) if matches!(op, Operator::Eq | Operator::NotEq) || is_range(op) => {
+            stats
+        }
+        _ => return default_selectivity(op),
+    };
+    // comparisons with null are never true
+    if value.is_null() {
+        return 0.0;
+    }
+    let non_null_fraction = stats
+        .null_count
+        .map_or(1.0, |n| 1.0 - (n as f64 / num_rows).min(1.0));
+    let compare = |bound: &Option<ScalarValue>| {
+        let bound = bound.as_ref()?;
+        match (scalar_to_f64(value), scalar_to_f64(bound)) {
+            (Some(value), Some(bound)) => value.partial_cmp(&bound),
+            _ => value.partial_cmp(bound),
+        }
+    };
+    let out_of_range = compare(&stats.min_value) == Some(Ordering::Less)
+        || compare(&stats.max_value) == Some(Ordering::Greater);
+    let equality = if out_of_range {
+        0.0
+    } else {
+        stats
+            .distinct_count
+            .map_or(DEFAULT_EQUALITY_SELECTIVITY, |n| 1.0 / n.max(1) as f64)
+    };
+    let selectivity = match op {
+        Operator::Eq => equality,
+        Operator::NotEq => 1.0 - equality,
+        _ => range_selectivity(stats, op, value).unwrap_or(DEFAULT_RANGE_SELECTIVITY),
+    };
+    selectivity * non_null_fraction
+}
+
+/// Estimates the selectivity of the range predicate `column <op> value`
+/// assuming the values are uniformly distributed between the min and max
+/// values of the column
+fn range_selectivity(
+    stats: &ColumnStatistics,
+    op: &Operator,
+    value: &ScalarValue,
+) -> Option<f64> {
+    let min = scalar_to_f64(stats.min_value.as_ref()?)?;
+    let max = scalar_to_f64(stats.max_value.as_ref()?)?;
+    let value = scalar_to_f64(value)?;
+    // fraction of the values smaller than `value`
+    let below = if value < min {
+        0.0
+    } else if value > max {
+        1.0
+    } else if max > min {
+        (value - min) / (max - min)
+    } else {
+        0.5
+    };
+    Some(match op {
+        Operator::Lt | Operator::LtEq => below,
+        _ => 1.0 - below,
+    })
+}
+
+fn default_selectivity(op: &Operator) -> f64 {
+    match op {
+        Operator::Eq | Operator::IsNotDistinctFrom => DEFAULT_EQUALITY_SELECTIVITY,
+        Operator::NotEq | Operator::IsDistinctFrom => 1.0 - DEFAULT_EQUALITY_SELECTIVITY,
+        op if is_range(op) => DEFAULT_RANGE_SELECTIVITY,
+        _ => DEFAULT_SELECTIVITY,
+    }
+}
+
+fn is_range(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq
+    )
+}
+
+/// Returns the operator of `b <op> a` equivalent to `a <op> b`, if any
+fn swap_operator(op: &Operator) -> Option<Operator> {
+    match op {
+        Operator::Eq => Some(Operator::Eq),
+        Operator::NotEq => Some(Operator::NotEq),
+        Operator::Lt => Some(Operator::Gt),
+        Operator::LtEq => Some(Operator::GtEq),
+        Operator::Gt => Some(Operator::Lt),
+        Operator::GtEq => Some(Operator::LtEq),
+        _ => None,
+    }
+}
+
+/// Converts the numeric and temporal values to `f64`, to interpolate them
+fn scalar_to_f64(value: &ScalarValue) -> Option<f64> {
+    match value {
+        ScalarValue::Int8(v) => v.map(f64::from),
+        ScalarValue::Int16(v) => v.map(f64::from),
+        ScalarValue::Int32(v) | ScalarValue::Date32(v) => v.map(f64::from),
+        ScalarValue::UInt8(v) => v.map(f64::from),
+        ScalarValue::UInt16(v) => v.map(f64::from),
+        ScalarValue::UInt32(v) => v.map(f64::from),
+        ScalarValue::UInt64(v) => v.map(|v| v as f64),
+        ScalarValue::Float32(v) => v.map(f64::from),
+        ScalarValue::Float64(v) => *v,
+        ScalarValue::Decimal128(v, _, scale) => {
+            v.map(|v| v as f64 / 10_f64.powi(*scale as i32))
+        }
+        ScalarValue::Int64(v)
+        | ScalarValue::Date64(v)
+        | ScalarValue::TimestampSecond(v, _)
+        | ScalarValue::TimestampMillisecond(v, _)
+        | ScalarValue::TimestampMicrosecond(v, _)
+        | ScalarValue::TimestampNanosecond(v, _) => v.map(|v| v as f64),
+        _ => None,
     }
 }
 
@@ -230,6 +536,7 @@ mod tests {
     use crate::test;
     use crate::test_util;
     use crate::{logical_plan::Operator, physical_plan::collect};
+    use arrow::datatypes::{Field, Schema};
     use std::iter::Iterator;
 
     #[tokio::test]
@@ -250,6 +557,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             true,
             b',',
@@ -285,4 +593,81 @@ mod tests {
 
         Ok(())
     }
+
+    fn statistics_exec() -> (Arc<dyn ExecutionPlan>, Schema) {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+        ]);
+        let stats = Statistics {
+            num_rows: Some(1000),
+            total_byte_size: Some(8000),
+            column_statistics: Some(vec![
+                ColumnStatistics {
+                    null_count: Some(0),
+                    min_value: Some(ScalarValue::Int32(Some(1))),
+                    max_value: Some(ScalarValue::Int32(Some(100))),
+                    distinct_count: Some(100),
+                },
+                ColumnStatistics {
+                    null_count: Some(500),
+                    ..Default::default()
+                },
+            ]),
+            is_exact: true,
+        };
+        let exec = Arc::new(test::exec::StatisticsExec::new(stats, schema.clone()));
+        (exec, schema)
+    }
+
+    fn estimated_rows(
+        input: &Arc<dyn ExecutionPlan>,
+        predicate: Arc<dyn PhysicalExpr>,
+    ) -> Result<Option<usize>> {
+        let filter = FilterExec::try_new(predicate, input.clone())?;
+        Ok(filter.statistics().num_rows)
+    }
+
+    #[test]
+    fn filter_statistics() -> Result<()> {
+        let (input, schema) = statistics_exec();
+        let a = || col("a", &schema).unwrap();
+        let b = || col("b", &schema).unwrap();
+        let int = |v: i32| lit(ScalarValue::Int32(Some(v)));
+
+        // 1 / distinct_count
+        let eq = binary(a(), Operator::Eq, int(10), &schema)?;
+        assert_eq!(estimated_rows(&input, eq)?, Some(10));
+        // outside of [min, max]
+        let eq = binary(int(200), Operator::Eq, a(), &schema)?;
+        assert_eq!(estimated_rows(&input, eq)?, Some(0));
+        // uniform distribution between min and max
+        let gt = binary(a(), Operator::Gt, int(34), &schema)?;
+        assert_eq!(estimated_rows(&input, gt.clone())?, Some(667));
+        let lt = binary(int(34), Operator::Lt, a(), &schema)?;
+        assert_eq!(estimated_rows(&input, lt)?, Some(667));
+        // unknown min/max, and half of the values are null
+        let lt = binary(b(), Operator::Lt, int(0), &schema)?;
+        assert_eq!(estimated_rows(&input, lt.clone())?, Some(167));
+        let and = binary(gt, Operator::And, lt, &schema)?;
+        assert_eq!(estimated_rows(&input, and)?, Some(112));
+        let null = is_null(b())?;
+        assert_eq!(estimated_rows(&input, null)?, Some(500));
+        let list = in_list(a(), vec![int(1), int(2), int(300)], &false)?;
+        assert_eq!(estimated_rows(&input, list)?, Some(20));
+
+        let filter = FilterExec::try_new(not(is_not_null(a())?, &schema)?, input)?;
+        let stats = filter.statistics();
+        assert_eq!(stats.num_rows, Some(0));
+        assert_eq!(stats.total_byte_size, Some(0));
+        assert!(!stats.is_exact);
+        let column_statistics = stats.column_statistics.unwrap();
+        assert_eq!(column_statistics[0].distinct_count, Some(0));
+        assert_eq!(
+            column_statistics[0].max_value,
+            Some(ScalarValue::Int32(Some(100)))
+        );
+
+        Ok(())
+    }
 }
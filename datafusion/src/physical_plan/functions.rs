@@ -56,6 +56,10 @@ use fmt::{Debug, Formatter};
 use std::convert::From;
 use std::{any::Any, fmt, str::FromStr, sync::Arc};
 
+/// Time zone of a `Timestamp` in a [`TypeSignature`] that matches timestamps
+/// in any time zone, e.g. `DataType::Timestamp(TimeUnit::Nanosecond, Some(TIMEZONE_WILDCARD.to_owned()))`
+pub const TIMEZONE_WILDCARD: &str = "+TZ";
+
 /// A function's type signature, which defines the function's supported argument types.
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum TypeSignature {
@@ -66,6 +70,10 @@ pub enum TypeSignature {
     // A function such as `array` is `VariadicEqual`
     // The first argument decides the type used for coercion
     VariadicEqual,
+    /// arbitrary number of arguments of arbitrary types
+    // A function such as `array_concat` is `VariadicAny`, and checks the
+    // argument types when computing its return type
+    VariadicAny,
     /// fixed number of arguments of an arbitrary but equal type out of a list of valid types
     // A function of one argument of f64 is `Uniform(1, vec![DataType::Float64])`
     // A function of one argument of f64 or f32 is `Uniform(1, vec![DataType::Float32, DataType::Float64])`
@@ -78,6 +86,66 @@ pub enum TypeSignature {
     OneOf(Vec<TypeSignature>),
 }
 
+impl TypeSignature {
+    /// Returns the lists of argument types accepted by this type signature, in a
+    /// human readable form. The last type of the lists of variadic signatures is `..`.
+    fn argument_types(&self) -> Vec<Vec<String>> {
+        match self {
+            TypeSignature::Variadic(types) => types
+                .iter()
+                .map(|t| vec![format!("{:?}", t), "..".to_string()])
+                .collect(),
+            TypeSignature::VariadicEqual => {
+                vec![vec!["T".to_string(), "..".to_string()]]
+            }
+            TypeSignature::VariadicAny => {
+                vec![vec!["Any".to_string(), "..".to_string()]]
+            }
+            TypeSignature::Uniform(number, types) => types
+                .iter()
+                .map(|t| vec![format!("{:?}", t); *number])
+                .collect(),
+            TypeSignature::Exact(types) => {
+                vec![types.iter().map(|t| format!("{:?}", t)).collect()]
+            }
+            TypeSignature::Any(number) => vec![vec!["Any".to_string(); *number]],
+            TypeSignature::OneOf(types) => {
+                types.iter().flat_map(|t| t.argument_types()).collect()
+            }
+        }
+    }
+}
+
+/// A named parameter of a function, that the arguments of a call can be passed to
+/// by name, e.g. `my_fn(x => 1)`
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Parameter {
+    /// name - The name of the parameter.
+    pub name: String,
+    /// default - The value of the parameter when a call has no argument for it, if
+    /// the parameter is optional.
+    pub default: Option<ScalarValue>,
+}
+
+impl Parameter {
+    /// new - Creates a parameter that every call must have an argument for.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            default: None,
+        }
+    }
+
+    /// with_default - Creates an optional parameter whose value is `default` when a
+    /// call has no argument for it.
+    pub fn with_default(name: impl Into<String>, default: ScalarValue) -> Self {
+        Self {
+            name: name.into(),
+            default: Some(default),
+        }
+    }
+}
+
 ///The Signature of a function defines its supported input types as well as its volatility.
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct Signature {
@@ -85,6 +153,9 @@ pub struct Signature {
     pub type_signature: TypeSignature,
     /// volatility - The volatility of the function. See [Volatility] for more information.
     pub volatility: Volatility,
+    /// parameters - The named parameters of the function, in order, empty if its
+    /// arguments can only be passed by position. See [Parameter] for more information.
+    pub parameters: Vec<Parameter>,
 }
 
 impl Signature {
@@ -93,6 +164,7 @@ impl Signature {
         Signature {
             type_signature,
             volatility,
+            parameters: vec![],
         }
     }
     /// variadic - Creates a variadic signature that represents an arbitrary number of arguments all from a type in common_types.
@@ -100,6 +172,7 @@ impl Signature {
         Self {
             type_signature: TypeSignature::Variadic(common_types),
             volatility,
+            parameters: vec![],
         }
     }
     /// variadic_equal - Creates a variadic signature that represents an arbitrary number of arguments of the same type.
@@ -107,6 +180,15 @@ impl Signature {
         Self {
             type_signature: TypeSignature::VariadicEqual,
             volatility,
+            parameters: vec![],
+        }
+    }
+    /// variadic_any - Creates a variadic signature that represents an arbitrary number of arguments of any type.
+    pub fn variadic_any(volatility: Volatility) -> Self {
+        Self {
+            type_signature: TypeSignature::VariadicAny,
+            volatility,
+            parameters: vec![],
         }
     }
     /// uniform - Creates a function with a fixed number of arguments of the same type, which must be from valid_types.
@@ -118,6 +200,7 @@ impl Signature {
         Self {
             type_signature: TypeSignature::Uniform(arg_count, valid_types),
             volatility,
+            parameters: vec![],
         }
     }
     /// exact - Creates a signture which must match the types in exact_types in order.
@@ -125,6 +208,7 @@ impl Signature {
         Signature {
             type_signature: TypeSignature::Exact(exact_types),
             volatility,
+            parameters: vec![],
         }
     }
     /// any - Creates a signature which can a be made of any type but of a specified number
@@ -132,6 +216,7 @@ impl Signature {
         Signature {
             type_signature: TypeSignature::Any(arg_count),
             volatility,
+            parameters: vec![],
         }
     }
     /// one_of Creates a signature which can match any of the [TypeSignature]s which are passed in.
@@ -139,7 +224,113 @@ impl Signature {
         Signature {
             type_signature: TypeSignature::OneOf(type_signatures),
             volatility,
+            parameters: vec![],
+        }
+    }
+    /// with_parameters - Names the parameters of the function, so that the arguments of
+    /// its calls can be passed by name, and gives the default values of the optional ones.
+    pub fn with_parameters(mut self, parameters: Vec<Parameter>) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    /// Returns the argument lists accepted by the function, in a human readable form
+    /// such as `(string Utf8, position Int64)`, for error messages and introspection
+    pub fn accepted_signatures(&self) -> Vec<String> {
+        self.type_signature
+            .argument_types()
+            .into_iter()
+            .map(|types| {
+                let arguments = types
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, t)| match self.parameters.get(i) {
+                        Some(parameter) if t != ".." => {
+                            format!("{} {}", parameter.name, t)
+                        }
+                        _ => t,
+                    })
+                    .collect::<Vec<_>>();
+                format!("({})", arguments.join(", "))
+            })
+            .collect()
+    }
+
+    /// Arranges the arguments of a call of the function `name`, passed either by
+    /// position or by name, in the order of its parameters. The arguments passed by
+    /// name must follow the ones passed by position. The parameters with a default
+    /// value that have no argument, either between the arguments or after them, get
+    /// their default value, as built by `default`.
+    pub fn arrange_arguments<T>(
+        &self,
+        name: &str,
+        args: Vec<(Option<String>, T)>,
+        default: impl Fn(&ScalarValue) -> T,
+    ) -> Result<Vec<T>> {
+        let mut arranged: Vec<Option<T>> = Vec::with_capacity(args.len());
+        let mut by_name = false;
+        for (parameter_name, arg) in args {
+            match parameter_name {
+                None if by_name => {
+                    return Err(DataFusionError::Plan(format!(
+                        "Function '{}' has an argument passed by position after an argument passed by name",
+                        name
+                    )))
+                }
+                None => arranged.push(Some(arg)),
+                Some(parameter_name) => {
+                    by_name = true;
+                    let position = self
+                        .parameters
+                        .iter()
+                        .position(|parameter| parameter.name == parameter_name)
+                        .ok_or_else(|| {
+                            DataFusionError::Plan(format!(
+                                "Function '{}' has no parameter named '{}'",
+                                name, parameter_name
+                            ))
+                        })?;
+                    if arranged.len() <= position {
+                        arranged.resize_with(position + 1, || None);
+                    }
+                    if arranged[position].is_some() {
+                        return Err(DataFusionError::Plan(format!(
+                            "Function '{}' has several arguments for parameter '{}'",
+                            name, parameter_name
+                        )));
+                    }
+                    arranged[position] = Some(arg);
+                }
+            }
+        }
+
+        let mut args = Vec::with_capacity(arranged.len());
+        for (i, arg) in arranged.into_iter().enumerate() {
+            match arg {
+                Some(arg) => args.push(arg),
+                // a gap left by the arguments passed by name
+                None => {
+                    let parameter = &self.parameters[i];
+                    match &parameter.default {
+                        Some(value) => args.push(default(value)),
+                        None => {
+                            return Err(DataFusionError::Plan(format!(
+                                "Function '{}' has no argument for parameter '{}'",
+                                name, parameter.name
+                            )))
+                        }
+                    }
+                }
+            }
         }
+        // the optional parameters after the arguments
+        for parameter in self.parameters.iter().skip(args.len()) {
+            match &parameter.default {
+                Some(value) => args.push(default(value)),
+                None => break,
+            }
+        }
+        Ok(args)
     }
 }
 
@@ -212,6 +403,28 @@ pub enum BuiltinScalarFunction {
     /// trunc
     Trunc,
 
+    // array functions
+    /// array_append
+    ArrayAppend,
+    /// array_concat
+    ArrayConcat,
+    /// array_contains
+    ArrayContains,
+    /// array_distinct
+    ArrayDistinct,
+    /// array_join
+    ArrayJoin,
+    /// array_length
+    ArrayLength,
+    /// array_position
+    ArrayPosition,
+    /// array_slice
+    ArraySlice,
+    /// array_sort
+    ArraySort,
+    /// cardinality
+    Cardinality,
+
     // string functions
     /// construct an array from columns
     Array,
@@ -334,6 +547,16 @@ impl BuiltinScalarFunction {
             BuiltinScalarFunction::Sqrt => Volatility::Immutable,
             BuiltinScalarFunction::Tan => Volatility::Immutable,
             BuiltinScalarFunction::Trunc => Volatility::Immutable,
+            BuiltinScalarFunction::ArrayAppend => Volatility::Immutable,
+            BuiltinScalarFunction::ArrayConcat => Volatility::Immutable,
+            BuiltinScalarFunction::ArrayContains => Volatility::Immutable,
+            BuiltinScalarFunction::ArrayDistinct => Volatility::Immutable,
+            BuiltinScalarFunction::ArrayJoin => Volatility::Immutable,
+            BuiltinScalarFunction::ArrayLength => Volatility::Immutable,
+            BuiltinScalarFunction::ArrayPosition => Volatility::Immutable,
+            BuiltinScalarFunction::ArraySlice => Volatility::Immutable,
+            BuiltinScalarFunction::ArraySort => Volatility::Immutable,
+            BuiltinScalarFunction::Cardinality => Volatility::Immutable,
             BuiltinScalarFunction::Array => Volatility::Immutable,
             BuiltinScalarFunction::Ascii => Volatility::Immutable,
             BuiltinScalarFunction::BitLength => Volatility::Immutable,
@@ -418,6 +641,18 @@ impl FromStr for BuiltinScalarFunction {
             "tan" => BuiltinScalarFunction::Tan,
             "trunc" => BuiltinScalarFunction::Trunc,
 
+            // array functions
+            "array_append" => BuiltinScalarFunction::ArrayAppend,
+            "array_concat" | "array_cat" => BuiltinScalarFunction::ArrayConcat,
+            "array_contains" => BuiltinScalarFunction::ArrayContains,
+            "array_distinct" => BuiltinScalarFunction::ArrayDistinct,
+            "array_join" | "array_to_string" => BuiltinScalarFunction::ArrayJoin,
+            "array_length" => BuiltinScalarFunction::ArrayLength,
+            "array_position" => BuiltinScalarFunction::ArrayPosition,
+            "array_slice" => BuiltinScalarFunction::ArraySlice,
+            "array_sort" => BuiltinScalarFunction::ArraySort,
+            "cardinality" => BuiltinScalarFunction::Cardinality,
+
             // string functions
             "array" => BuiltinScalarFunction::Array,
             "ascii" => BuiltinScalarFunction::Ascii,
@@ -498,6 +733,17 @@ make_utf8_to_return_type!(utf8_to_str_type, DataType::LargeUtf8, DataType::Utf8)
 make_utf8_to_return_type!(utf8_to_int_type, DataType::Int64, DataType::Int32);
 make_utf8_to_return_type!(utf8_to_binary_type, DataType::Binary, DataType::Binary);
 
+/// Returns a `List` of the elements of the list type `arg_type`, the type of
+/// the first argument of the array function `name`.
+fn list_type(arg_type: &DataType, name: &str) -> Result<DataType> {
+    let element_type = array_expressions::list_element_type(name, arg_type)?;
+    Ok(DataType::List(Box::new(Field::new(
+        "item",
+        element_type.clone(),
+        true,
+    ))))
+}
+
 /// Returns the datatype of the scalar function
 pub fn return_type(
     fun: &BuiltinScalarFunction,
@@ -519,10 +765,58 @@ pub fn return_type(
     // the return type of the built in function.
     // Some built-in functions' return type depends on the incoming type.
     match fun {
-        BuiltinScalarFunction::Array => Ok(DataType::FixedSizeList(
-            Box::new(Field::new("item", input_expr_types[0].clone(), true)),
-            input_expr_types.len() as i32,
-        )),
+        BuiltinScalarFunction::Array => Ok(DataType::List(Box::new(Field::new(
+            "item",
+            input_expr_types[0].clone(),
+            true,
+        )))),
+        BuiltinScalarFunction::ArrayAppend => {
+            list_type(&input_expr_types[0], "array_append")
+        }
+        BuiltinScalarFunction::ArrayConcat => {
+            let element_type = array_expressions::list_element_type(
+                "array_concat",
+                &input_expr_types[0],
+            )?;
+            for arg_type in &input_expr_types[1..] {
+                let other =
+                    array_expressions::list_element_type("array_concat", arg_type)?;
+                if other != element_type {
+                    return Err(DataFusionError::Plan(format!(
+                        "The function array_concat expects lists with elements of the same type, got {:?} and {:?}",
+                        element_type, other
+                    )));
+                }
+            }
+            list_type(&input_expr_types[0], "array_concat")
+        }
+        BuiltinScalarFunction::ArrayContains => {
+            array_expressions::list_element_type("array_contains", &input_expr_types[0])?;
+            Ok(DataType::Boolean)
+        }
+        BuiltinScalarFunction::ArrayDistinct => {
+            list_type(&input_expr_types[0], "array_distinct")
+        }
+        BuiltinScalarFunction::ArrayJoin => {
+            array_expressions::list_element_type("array_join", &input_expr_types[0])?;
+            Ok(DataType::Utf8)
+        }
+        BuiltinScalarFunction::ArrayLength => {
+            array_expressions::list_element_type("array_length", &input_expr_types[0])?;
+            Ok(DataType::Int64)
+        }
+        BuiltinScalarFunction::ArrayPosition => {
+            array_expressions::list_element_type("array_position", &input_expr_types[0])?;
+            Ok(DataType::Int64)
+        }
+        BuiltinScalarFunction::ArraySlice => {
+            list_type(&input_expr_types[0], "array_slice")
+        }
+        BuiltinScalarFunction::ArraySort => list_type(&input_expr_types[0], "array_sort"),
+        BuiltinScalarFunction::Cardinality => {
+            array_expressions::list_element_type("cardinality", &input_expr_types[0])?;
+            Ok(DataType::Int64)
+        }
         BuiltinScalarFunction::Ascii => Ok(DataType::Int32),
         BuiltinScalarFunction::BitLength => {
             utf8_to_int_type(&input_expr_types[0], "bit_length")
@@ -535,9 +829,12 @@ pub fn return_type(
         BuiltinScalarFunction::Concat => Ok(DataType::Utf8),
         BuiltinScalarFunction::ConcatWithSeparator => Ok(DataType::Utf8),
         BuiltinScalarFunction::DatePart => Ok(DataType::Int32),
-        BuiltinScalarFunction::DateTrunc => {
-            Ok(DataType::Timestamp(TimeUnit::Nanosecond, None))
-        }
+        BuiltinScalarFunction::DateTrunc => Ok(match &input_expr_types[1] {
+            DataType::Timestamp(_, tz) => {
+                DataType::Timestamp(TimeUnit::Nanosecond, tz.clone())
+            }
+            _ => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        }),
         BuiltinScalarFunction::InitCap => {
             utf8_to_str_type(&input_expr_types[0], "initcap")
         }
@@ -746,6 +1043,37 @@ pub fn create_physical_fun(
         BuiltinScalarFunction::Sqrt => Arc::new(math_expressions::sqrt),
         BuiltinScalarFunction::Tan => Arc::new(math_expressions::tan),
         BuiltinScalarFunction::Trunc => Arc::new(math_expressions::trunc),
+        // array functions
+        BuiltinScalarFunction::ArrayAppend => {
+            make_scalar_function(array_expressions::array_append)
+        }
+        BuiltinScalarFunction::ArrayConcat => {
+            make_scalar_function(array_expressions::array_concat)
+        }
+        BuiltinScalarFunction::ArrayContains => {
+            make_scalar_function(array_expressions::array_contains)
+        }
+        BuiltinScalarFunction::ArrayDistinct => {
+            make_scalar_function(array_expressions::array_distinct)
+        }
+        BuiltinScalarFunction::ArrayJoin => {
+            make_scalar_function(array_expressions::array_join)
+        }
+        BuiltinScalarFunction::ArrayLength => {
+            make_scalar_function(array_expressions::array_length)
+        }
+        BuiltinScalarFunction::ArrayPosition => {
+            make_scalar_function(array_expressions::array_position)
+        }
+        BuiltinScalarFunction::ArraySlice => {
+            make_scalar_function(array_expressions::array_slice)
+        }
+        BuiltinScalarFunction::ArraySort => {
+            make_scalar_function(array_expressions::array_sort)
+        }
+        BuiltinScalarFunction::Cardinality => {
+            make_scalar_function(array_expressions::cardinality)
+        }
         // string functions
         BuiltinScalarFunction::Array => Arc::new(array_expressions::array),
         BuiltinScalarFunction::Ascii => Arc::new(|args| match args[0].data_type() {
@@ -1266,7 +1594,7 @@ pub fn create_physical_expr(
 }
 
 /// the signatures supported by the function `fun`.
-fn signature(fun: &BuiltinScalarFunction) -> Signature {
+pub fn signature(fun: &BuiltinScalarFunction) -> Signature {
     // note: the physical expression must accept the type returned by this function or the execution panics.
 
     // for now, the list is small, as we do not have many built-in functions.
@@ -1275,6 +1603,21 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
             array_expressions::SUPPORTED_ARRAY_TYPES.to_vec(),
             fun.volatility(),
         ),
+        // lists of any kind and length can be concatenated, as long as their
+        // elements have the same type
+        BuiltinScalarFunction::ArrayConcat => Signature::variadic_any(fun.volatility()),
+        BuiltinScalarFunction::ArrayDistinct
+        | BuiltinScalarFunction::ArrayLength
+        | BuiltinScalarFunction::ArraySort
+        | BuiltinScalarFunction::Cardinality => Signature::any(1, fun.volatility()),
+        BuiltinScalarFunction::ArrayAppend
+        | BuiltinScalarFunction::ArrayContains
+        | BuiltinScalarFunction::ArrayPosition => Signature::any(2, fun.volatility()),
+        BuiltinScalarFunction::ArraySlice => Signature::any(3, fun.volatility()),
+        BuiltinScalarFunction::ArrayJoin => Signature::one_of(
+            vec![TypeSignature::Any(2), TypeSignature::Any(3)],
+            fun.volatility(),
+        ),
         BuiltinScalarFunction::Concat | BuiltinScalarFunction::ConcatWithSeparator => {
             Signature::variadic(vec![DataType::Utf8], fun.volatility())
         }
@@ -1334,7 +1677,8 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
                 ]),
             ],
             fun.volatility(),
-        ),
+        )
+        .with_parameters(named_parameters(&["string", "length", "fill"])),
         BuiltinScalarFunction::Left
         | BuiltinScalarFunction::Repeat
         | BuiltinScalarFunction::Right => Signature::one_of(
@@ -1391,13 +1735,23 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
         BuiltinScalarFunction::Digest => {
             Signature::exact(vec![DataType::Utf8, DataType::Utf8], fun.volatility())
         }
-        BuiltinScalarFunction::DateTrunc => Signature::exact(
+        BuiltinScalarFunction::DateTrunc => Signature::one_of(
             vec![
-                DataType::Utf8,
-                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                TypeSignature::Exact(vec![
+                    DataType::Utf8,
+                    DataType::Timestamp(TimeUnit::Nanosecond, None),
+                ]),
+                TypeSignature::Exact(vec![
+                    DataType::Utf8,
+                    DataType::Timestamp(
+                        TimeUnit::Nanosecond,
+                        Some(TIMEZONE_WILDCARD.to_owned()),
+                    ),
+                ]),
             ],
             fun.volatility(),
-        ),
+        )
+        .with_parameters(named_parameters(&["granularity", "timestamp"])),
         BuiltinScalarFunction::DatePart => Signature::one_of(
             vec![
                 TypeSignature::Exact(vec![DataType::Utf8, DataType::Date32]),
@@ -1418,9 +1772,17 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
                     DataType::Utf8,
                     DataType::Timestamp(TimeUnit::Nanosecond, None),
                 ]),
+                TypeSignature::Exact(vec![
+                    DataType::Utf8,
+                    DataType::Timestamp(
+                        TimeUnit::Nanosecond,
+                        Some(TIMEZONE_WILDCARD.to_owned()),
+                    ),
+                ]),
             ],
             fun.volatility(),
-        ),
+        )
+        .with_parameters(named_parameters(&["field", "source"])),
         BuiltinScalarFunction::SplitPart => Signature::one_of(
             vec![
                 TypeSignature::Exact(vec![
@@ -1445,7 +1807,8 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
                 ]),
             ],
             fun.volatility(),
-        ),
+        )
+        .with_parameters(named_parameters(&["string", "delimiter", "index"])),
 
         BuiltinScalarFunction::Strpos | BuiltinScalarFunction::StartsWith => {
             Signature::one_of(
@@ -1475,7 +1838,8 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
                 ]),
             ],
             fun.volatility(),
-        ),
+        )
+        .with_parameters(named_parameters(&["string", "position", "length"])),
 
         BuiltinScalarFunction::Replace | BuiltinScalarFunction::Translate => {
             Signature::one_of(
@@ -1502,7 +1866,13 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
                 ]),
             ],
             fun.volatility(),
-        ),
+        )
+        .with_parameters(named_parameters(&[
+            "string",
+            "pattern",
+            "replacement",
+            "flags",
+        ])),
 
         BuiltinScalarFunction::NullIf => {
             Signature::uniform(2, SUPPORTED_NULLIF_TYPES.to_vec(), fun.volatility())
@@ -1538,6 +1908,11 @@ fn signature(fun: &BuiltinScalarFunction) -> Signature {
     }
 }
 
+/// the parameters of a built-in function, that have no default values
+fn named_parameters(names: &[&str]) -> Vec<Parameter> {
+    names.iter().map(|name| Parameter::new(*name)).collect()
+}
+
 /// Physical expression of a scalar function
 pub struct ScalarFunctionExpr {
     fun: ScalarFunctionImplementation,
@@ -1702,8 +2077,8 @@ mod tests {
     };
     use arrow::{
         array::{
-            Array, ArrayRef, BinaryArray, BooleanArray, FixedSizeListArray, Float32Array,
-            Float64Array, Int32Array, StringArray, UInt32Array, UInt64Array,
+            Array, ArrayRef, BinaryArray, BooleanArray, Float32Array, Float64Array,
+            Int32Array, ListArray, StringArray, UInt32Array, UInt64Array,
         },
         datatypes::Field,
         record_batch::RecordBatch,
@@ -3966,7 +4341,7 @@ mod tests {
         assert_eq!(
             expr.data_type(&schema)?,
             // type equals to a common coercion
-            DataType::FixedSizeList(Box::new(Field::new("item", expected_type, true)), 2)
+            DataType::List(Box::new(Field::new("item", expected_type, true)))
         );
 
         // evaluate works
@@ -3974,10 +4349,7 @@ mod tests {
         let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
 
         // downcast works
-        let result = result
-            .as_any()
-            .downcast_ref::<FixedSizeListArray>()
-            .unwrap();
+        let result = result.as_any().downcast_ref::<ListArray>().unwrap();
 
         // value is correct
         assert_eq!(format!("{:?}", result.value(0)), expected);
@@ -4088,4 +4460,48 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn arrange_arguments_by_name() -> Result<()> {
+        let signature = Signature::any(3, Volatility::Immutable).with_parameters(vec![
+            Parameter::new("a"),
+            Parameter::with_default("b", ScalarValue::Int64(Some(2))),
+            Parameter::with_default("c", ScalarValue::Int64(Some(3))),
+        ]);
+        let arrange = |args: Vec<(Option<&str>, i64)>| {
+            signature.arrange_arguments(
+                "f",
+                args.into_iter()
+                    .map(|(name, arg)| (name.map(|name| name.to_string()), arg))
+                    .collect(),
+                |value| match value {
+                    ScalarValue::Int64(Some(v)) => *v,
+                    _ => unreachable!(),
+                },
+            )
+        };
+
+        assert_eq!(
+            arrange(vec![(None, 10), (None, 20), (None, 30)])?,
+            vec![10, 20, 30]
+        );
+        assert_eq!(arrange(vec![(None, 10)])?, vec![10, 2, 3]);
+        assert_eq!(
+            arrange(vec![(Some("c"), 30), (Some("a"), 10)])?,
+            vec![10, 2, 30]
+        );
+        assert_eq!(arrange(vec![(None, 10), (Some("b"), 20)])?, vec![10, 20, 3]);
+
+        let err = arrange(vec![(Some("a"), 10), (None, 20)]).unwrap_err();
+        assert!(err.to_string().contains("passed by position after"));
+        let err = arrange(vec![(Some("d"), 10)]).unwrap_err();
+        assert!(err.to_string().contains("no parameter named 'd'"));
+        let err = arrange(vec![(None, 10), (Some("a"), 10)]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("several arguments for parameter 'a'"));
+        let err = arrange(vec![(Some("b"), 20)]).unwrap_err();
+        assert!(err.to_string().contains("no argument for parameter 'a'"));
+        Ok(())
+    }
 }
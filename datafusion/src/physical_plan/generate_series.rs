@@ -0,0 +1,290 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Execution plan producing a series of integers, see the `generate_series`
+//! table function
+
+use std::any::Any;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::array::Int64Array;
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use futures::Stream;
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{
+    ColumnStatistics, DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+    RecordBatchStream, SendableRecordBatchStream, Statistics,
+};
+use crate::scalar::ScalarValue;
+
+/// Execution plan producing the integers from `start` to `stop` (inclusive),
+/// incremented by `step`, as a single `Int64` column
+#[derive(Debug, Clone)]
+pub struct GenerateSeriesExec {
+    schema: SchemaRef,
+    start: i64,
+    stop: i64,
+    step: i64,
+    batch_size: usize,
+    limit: Option<usize>,
+}
+
+impl GenerateSeriesExec {
+    /// Create a new GenerateSeriesExec. `schema` must have a single `Int64`
+    /// field and `step` must not be zero.
+    pub fn try_new(
+        schema: SchemaRef,
+        start: i64,
+        stop: i64,
+        step: i64,
+        batch_size: usize,
+        limit: Option<usize>,
+    ) -> Result<Self> {
+        if step == 0 {
+            return Err(DataFusionError::Plan(
+                "generate_series step must not be zero".to_string(),
+            ));
+        }
+        Ok(Self {
+            schema,
+            start,
+            stop,
+            step,
+            batch_size,
+            limit,
+        })
+    }
+
+    /// Number of values in the series, ignoring the limit
+    fn series_len(&self) -> u64 {
+        let (start, stop, step) =
+            (self.start as i128, self.stop as i128, self.step as i128);
+        if (step > 0 && start > stop) || (step < 0 && start < stop) {
+            0
+        } else {
+            ((stop - start) / step + 1) as u64
+        }
+    }
+
+    /// Number of values produced by this plan
+    fn num_rows(&self) -> usize {
+        let len = usize::try_from(self.series_len()).unwrap_or(usize::MAX);
+        match self.limit {
+            Some(limit) => len.min(limit),
+            None => len,
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for GenerateSeriesExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        Distribution::UnspecifiedDistribution
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.is_empty() {
+            Ok(Arc::new(self.clone()))
+        } else {
+            Err(DataFusionError::Internal(
+                "GenerateSeriesExec wrong number of children".to_string(),
+            ))
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "GenerateSeriesExec invalid partition {} (expected 0)",
+                partition
+            )));
+        }
+        Ok(Box::pin(GenerateSeriesStream {
+            schema: self.schema.clone(),
+            next: self.start as i128,
+            step: self.step as i128,
+            remaining: self.num_rows(),
+            batch_size: self.batch_size.max(1),
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(
+                    f,
+                    "GenerateSeriesExec: start={}, stop={}, step={}",
+                    self.start, self.stop, self.step
+                )?;
+                if let Some(limit) = self.limit {
+                    write!(f, ", limit={}", limit)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        let num_rows = self.num_rows();
+        let (min_value, max_value) = if num_rows == 0 {
+            (None, None)
+        } else {
+            let last = self.start as i128 + (num_rows as i128 - 1) * self.step as i128;
+            let (min, max) = if self.step > 0 {
+                (self.start as i128, last)
+            } else {
+                (last, self.start as i128)
+            };
+            (
+                Some(ScalarValue::Int64(Some(min as i64))),
+                Some(ScalarValue::Int64(Some(max as i64))),
+            )
+        };
+        Statistics {
+            num_rows: Some(num_rows),
+            total_byte_size: Some(num_rows * std::mem::size_of::<i64>()),
+            column_statistics: Some(vec![ColumnStatistics {
+                null_count: Some(0),
+                max_value,
+                min_value,
+                distinct_count: Some(num_rows),
+            }]),
+            is_exact: true,
+        }
+    }
+}
+
+struct GenerateSeriesStream {
+    schema: SchemaRef,
+    next: i128,
+    step: i128,
+    remaining: usize,
+    batch_size: usize,
+}
+
+impl Stream for GenerateSeriesStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        if self.remaining == 0 {
+            return Poll::Ready(None);
+        }
+        let len = self.remaining.min(self.batch_size);
+        let (next, step) = (self.next, self.step);
+        let values: Int64Array = (0..len as i128)
+            .map(|i| Some((next + i * step) as i64))
+            .collect();
+        self.next += len as i128 * step;
+        self.remaining -= len;
+        Poll::Ready(Some(RecordBatch::try_new(
+            self.schema.clone(),
+            vec![Arc::new(values)],
+        )))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let batches = (self.remaining + self.batch_size - 1) / self.batch_size;
+        (batches, Some(batches))
+    }
+}
+
+impl RecordBatchStream for GenerateSeriesStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![Field::new(
+            "generate_series",
+            DataType::Int64,
+            false,
+        )]))
+    }
+
+    async fn collect_values(exec: GenerateSeriesExec) -> Result<Vec<i64>> {
+        let batches = common::collect(exec.execute(0).await?).await?;
+        Ok(batches
+            .iter()
+            .flat_map(|batch| {
+                let array = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap();
+                array.values().to_vec()
+            })
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn generate_series() -> Result<()> {
+        let exec = GenerateSeriesExec::try_new(schema(), 1, 10, 3, 2, None)?;
+        assert_eq!(exec.statistics().num_rows, Some(4));
+        assert_eq!(collect_values(exec).await?, vec![1, 4, 7, 10]);
+
+        let exec = GenerateSeriesExec::try_new(schema(), 5, -5, -4, 8192, None)?;
+        assert_eq!(collect_values(exec).await?, vec![5, 1, -3]);
+
+        let exec = GenerateSeriesExec::try_new(schema(), 5, 1, 1, 8192, None)?;
+        assert_eq!(collect_values(exec).await?, Vec::<i64>::new());
+
+        let exec = GenerateSeriesExec::try_new(schema(), 1, i64::MAX, 1, 8192, Some(3))?;
+        assert_eq!(collect_values(exec).await?, vec![1, 2, 3]);
+
+        assert!(GenerateSeriesExec::try_new(schema(), 1, 10, 0, 8192, None).is_err());
+        Ok(())
+    }
+}
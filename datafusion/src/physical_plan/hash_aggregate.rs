@@ -54,8 +54,8 @@ use super::common::AbortOnDropSingle;
 use super::metrics::{
     self, BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet, RecordOutput,
 };
-use super::Statistics;
 use super::{expressions::Column, RecordBatchStream, SendableRecordBatchStream};
+use super::{ColumnStatistics, Statistics};
 
 /// Hash aggregate modes
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -175,6 +175,59 @@ impl HashAggregateExec {
     pub fn input_schema(&self) -> SchemaRef {
         self.input_schema.clone()
     }
+
+    /// Estimates the number of groups from the distinct counts of the group
+    /// columns, assuming that they are independent
+    fn estimate_group_statistics(&self) -> Statistics {
+        let input_stats = self.input.statistics();
+        let input_rows = match input_stats.num_rows {
+            Some(input_rows) => input_rows,
+            None => return Statistics::default(),
+        };
+        let group_stats: Vec<ColumnStatistics> = self
+            .group_expr
+            .iter()
+            .map(|(expr, _)| {
+                expr.as_any()
+                    .downcast_ref::<Column>()
+                    .and_then(|column| {
+                        input_stats.column_statistics.as_ref()?.get(column.index())
+                    })
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        // null is a group of its own
+        let num_rows = group_stats
+            .iter()
+            .try_fold(1_usize, |groups, stats| {
+                let nulls = matches!(stats.null_count, Some(n) if n > 0) as usize;
+                stats
+                    .distinct_count
+                    .map(|n| groups.saturating_mul(n + nulls))
+            })
+            .unwrap_or(input_rows)
+            .min(input_rows);
+
+        let aggr_fields = self.schema.fields().len() - self.group_expr.len();
+        let column_statistics = group_stats
+            .into_iter()
+            .map(|stats| ColumnStatistics {
+                // all the nulls are in a single group
+                null_count: stats.null_count.map(|n| n.min(1)),
+                distinct_count: stats.distinct_count.map(|n| n.min(num_rows)),
+                ..stats
+            })
+            .chain(std::iter::repeat(ColumnStatistics::default()).take(aggr_fields))
+            .collect();
+        Statistics {
+            num_rows: Some(num_rows),
+            total_byte_size: None,
+            column_statistics: Some(column_statistics),
+            is_exact: false,
+        }
+    }
 }
 
 #[async_trait]
@@ -291,7 +344,6 @@ impl ExecutionPlan for HashAggregateExec {
     fn statistics(&self) -> Statistics {
         // TODO stats: group expressions:
         // - once expressions will be able to compute their own stats, use it here
-        // - case where we group by on a column for which with have the `distinct` stat
         // TODO stats: aggr expression:
         // - aggregations somtimes also preserve invariants such as min, max...
         match self.mode {
@@ -304,7 +356,8 @@ impl ExecutionPlan for HashAggregateExec {
                     ..Default::default()
                 }
             }
-            _ => Statistics::default(),
+            _ if self.group_expr.is_empty() => Statistics::default(),
+            _ => self.estimate_group_statistics(),
         }
     }
 }
@@ -1011,7 +1064,9 @@ mod tests {
     use super::*;
     use crate::physical_plan::expressions::{col, Avg};
     use crate::test::assert_is_pending;
-    use crate::test::exec::{assert_strong_count_converges_to_zero, BlockingExec};
+    use crate::test::exec::{
+        assert_strong_count_converges_to_zero, BlockingExec, StatisticsExec,
+    };
     use crate::{assert_batches_sorted_eq, physical_plan::common};
 
     use crate::physical_plan::coalesce_partitions::CoalescePartitionsExec;
@@ -1303,4 +1358,75 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn group_statistics() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::UInt32, true),
+            Field::new("b", DataType::Float64, false),
+        ]);
+        let input: Arc<dyn ExecutionPlan> = Arc::new(StatisticsExec::new(
+            Statistics {
+                num_rows: Some(1000),
+                column_statistics: Some(vec![
+                    ColumnStatistics {
+                        null_count: Some(5),
+                        distinct_count: Some(10),
+                        ..Default::default()
+                    },
+                    ColumnStatistics::default(),
+                ]),
+                ..Default::default()
+            },
+            schema.clone(),
+        ));
+        let schema = Arc::new(schema);
+        let aggregates: Vec<Arc<dyn AggregateExpr>> = vec![Arc::new(Avg::new(
+            col("b", &schema)?,
+            "AVG(b)".to_string(),
+            DataType::Float64,
+        ))];
+        let aggregate = |mode: AggregateMode,
+                         group_expr: Vec<(Arc<dyn PhysicalExpr>, String)>,
+                         input: Arc<dyn ExecutionPlan>| {
+            HashAggregateExec::try_new(
+                mode,
+                group_expr,
+                aggregates.clone(),
+                input,
+                schema.clone(),
+            )
+        };
+
+        // 10 values and null
+        let partial: Arc<dyn ExecutionPlan> = Arc::new(aggregate(
+            AggregateMode::Partial,
+            vec![(col("a", &schema)?, "a".to_string())],
+            input.clone(),
+        )?);
+        let stats = partial.statistics();
+        assert_eq!(stats.num_rows, Some(11));
+        assert_eq!(stats.column_statistics.unwrap().len(), 3);
+        assert!(!stats.is_exact);
+
+        let final_aggregate = aggregate(
+            AggregateMode::Final,
+            vec![(col("a", &schema)?, "a".to_string())],
+            partial,
+        )?;
+        assert_eq!(final_aggregate.statistics().num_rows, Some(11));
+
+        // the distinct count of b is unknown
+        let partial = aggregate(
+            AggregateMode::Partial,
+            vec![
+                (col("a", &schema)?, "a".to_string()),
+                (col("b", &schema)?, "b".to_string()),
+            ],
+            input,
+        )?;
+        assert_eq!(partial.statistics().num_rows, Some(1000));
+
+        Ok(())
+    }
 }
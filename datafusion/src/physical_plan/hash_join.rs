@@ -54,13 +54,16 @@ use hashbrown::raw::RawTable;
 
 use super::{
     coalesce_partitions::CoalescePartitionsExec,
-    join_utils::{build_join_schema, check_join_is_valid, ColumnIndex, JoinOn, JoinSide},
+    join_utils::{
+        build_join_schema, check_join_is_valid, estimate_join_statistics, ColumnIndex,
+        JoinOn, JoinSide,
+    },
 };
 use super::{
     expressions::Column,
     metrics::{self, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet},
 };
-use super::{hash_utils::create_hashes, Statistics};
+use super::{hash_utils::create_hashes, runtime_filter::RuntimeFilter, Statistics};
 use crate::error::{DataFusionError, Result};
 use crate::logical_plan::JoinType;
 
@@ -123,6 +126,8 @@ pub struct HashJoinExec {
     column_indices: Vec<ColumnIndex>,
     /// If null_equals_null is true, null == null else null != null
     null_equals_null: bool,
+    /// Filter on the probe side keys, published once the build side is collected
+    runtime_filter: Option<Arc<RuntimeFilter>>,
 }
 
 /// Metrics for HashJoinExec
@@ -206,9 +211,23 @@ impl HashJoinExec {
             metrics: ExecutionPlanMetricsSet::new(),
             column_indices,
             null_equals_null: *null_equals_null,
+            runtime_filter: None,
         })
     }
 
+    /// Publish the keys of the build side to `runtime_filter` once collected.
+    /// Only used in [`PartitionMode::CollectLeft`], as the build side of
+    /// each partition only holds some of the keys otherwise.
+    pub fn with_runtime_filter(mut self, runtime_filter: Arc<RuntimeFilter>) -> Self {
+        self.runtime_filter = Some(runtime_filter);
+        self
+    }
+
+    /// Filter on the probe side keys, published once the build side is collected
+    pub fn runtime_filter(&self) -> Option<&Arc<RuntimeFilter>> {
+        self.runtime_filter.as_ref()
+    }
+
     /// left (build) side which gets hashed
     pub fn left(&self) -> &Arc<dyn ExecutionPlan> {
         &self.left
@@ -259,14 +278,18 @@ impl ExecutionPlan for HashJoinExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            2 => Ok(Arc::new(HashJoinExec::try_new(
-                children[0].clone(),
-                children[1].clone(),
-                self.on.clone(),
-                &self.join_type,
-                self.mode,
-                &self.null_equals_null,
-            )?)),
+            2 => {
+                let mut join = HashJoinExec::try_new(
+                    children[0].clone(),
+                    children[1].clone(),
+                    self.on.clone(),
+                    &self.join_type,
+                    self.mode,
+                    &self.null_equals_null,
+                )?;
+                join.runtime_filter = self.runtime_filter.clone();
+                Ok(Arc::new(join))
+            }
             _ => Err(DataFusionError::Internal(
                 "HashJoinExec wrong number of children".to_string(),
             )),
@@ -331,6 +354,10 @@ impl ExecutionPlan for HashJoinExec {
 
                             *build_side = Some(left_side.clone());
 
+                            if let Some(runtime_filter) = &self.runtime_filter {
+                                runtime_filter.publish(&left_side.1, &on_left);
+                            }
+
                             debug!(
                                 "Built build-side of hash join containing {} rows in {} ms",
                                 num_rows,
@@ -446,10 +473,15 @@ impl ExecutionPlan for HashJoinExec {
     }
 
     fn statistics(&self) -> Statistics {
-        // TODO stats: it is not possible in general to know the output size of joins
-        // There are some special cases though, for example:
-        // - `A LEFT JOIN B ON A.col=B.col` with `COUNT_DISTINCT(B.col)=COUNT(B.col)`
-        Statistics::default()
+        // it is not possible in general to know the output size of joins,
+        // so it is estimated from the distinct counts of the join keys
+        estimate_join_statistics(
+            &self.left,
+            &self.right,
+            &self.on,
+            &self.join_type,
+            &self.column_indices,
+        )
     }
 }
 
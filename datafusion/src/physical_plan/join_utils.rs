@@ -20,8 +20,10 @@
 use crate::error::{DataFusionError, Result};
 use crate::logical_plan::JoinType;
 use crate::physical_plan::expressions::Column;
+use crate::physical_plan::{ColumnStatistics, ExecutionPlan, Statistics};
 use arrow::datatypes::{Field, Schema};
 use std::collections::HashSet;
+use std::sync::Arc;
 
 /// The on clause of the join, as vector of (left, right) columns.
 pub type JoinOn = Vec<(Column, Column)>;
@@ -147,9 +149,94 @@ pub fn build_join_schema(
     (Schema::new(fields), column_indices)
 }
 
+/// Estimates the statistics of the output of the equi-join of `left` and
+/// `right` on the columns `on`, assuming that each join key is uniformly
+/// distributed and that the key values of the side with the fewest distinct
+/// values are all found on the other side. When the distinct count of a key
+/// is unknown, each row is assumed to have a distinct value.
+pub fn estimate_join_statistics(
+    left: &Arc<dyn ExecutionPlan>,
+    right: &Arc<dyn ExecutionPlan>,
+    on: JoinOnRef,
+    join_type: &JoinType,
+    column_indices: &[ColumnIndex],
+) -> Statistics {
+    let (left_stats, right_stats) = (left.statistics(), right.statistics());
+    let (left_rows, right_rows) = match (left_stats.num_rows, right_stats.num_rows) {
+        (Some(left_rows), Some(right_rows)) => (left_rows, right_rows),
+        _ => return Statistics::default(),
+    };
+    let left_columns = left_stats.column_statistics.unwrap_or_else(|| {
+        vec![ColumnStatistics::default(); left.schema().fields().len()]
+    });
+    let right_columns = right_stats.column_statistics.unwrap_or_else(|| {
+        vec![ColumnStatistics::default(); right.schema().fields().len()]
+    });
+
+    let distinct_count = |stats: &ColumnStatistics, num_rows: usize| {
+        stats
+            .distinct_count
+            .unwrap_or(num_rows)
+            .min(num_rows)
+            .max(1) as f64
+    };
+    let inner_rows = if left_rows == 0 || right_rows == 0 {
+        0
+    } else {
+        // each pair of rows matches with a probability of 1 / max(ndv) per key
+        let key_selectivity: f64 = on
+            .iter()
+            .map(|(l, r)| {
+                let left_distinct = distinct_count(&left_columns[l.index()], left_rows);
+                let right_distinct =
+                    distinct_count(&right_columns[r.index()], right_rows);
+                1.0 / left_distinct.max(right_distinct)
+            })
+            .product();
+        (left_rows as f64 * right_rows as f64 * key_selectivity).ceil() as usize
+    };
+    let num_rows = match join_type {
+        JoinType::Inner => inner_rows,
+        JoinType::Left => inner_rows.max(left_rows),
+        JoinType::Right => inner_rows.max(right_rows),
+        JoinType::Full => {
+            inner_rows.max(left_rows) + inner_rows.max(right_rows) - inner_rows
+        }
+        JoinType::Semi => inner_rows.min(left_rows),
+        JoinType::Anti => left_rows - inner_rows.min(left_rows),
+    };
+
+    // the null counts change with the matches and unmatched rows, but the
+    // other column statistics remain valid bounds
+    let column_statistics = column_indices
+        .iter()
+        .map(|column_index| {
+            let stats = match column_index.side {
+                JoinSide::Left => &left_columns[column_index.index],
+                JoinSide::Right => &right_columns[column_index.index],
+            };
+            ColumnStatistics {
+                null_count: None,
+                distinct_count: stats.distinct_count.map(|n| n.min(num_rows)),
+                min_value: stats.min_value.clone(),
+                max_value: stats.max_value.clone(),
+            }
+        })
+        .collect();
+
+    Statistics {
+        num_rows: Some(num_rows),
+        total_byte_size: None,
+        column_statistics: Some(column_statistics),
+        is_exact: false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test::exec::StatisticsExec;
+    use arrow::datatypes::DataType;
 
     fn check(left: &[Column], right: &[Column], on: &[(Column, Column)]) -> Result<()> {
         let left = left
@@ -209,4 +296,86 @@ mod tests {
 
         assert!(check(&left, &right, on).is_ok());
     }
+
+    fn statistics_exec(
+        name: &str,
+        num_rows: usize,
+        distinct_count: Option<usize>,
+    ) -> Arc<dyn ExecutionPlan> {
+        Arc::new(StatisticsExec::new(
+            Statistics {
+                num_rows: Some(num_rows),
+                column_statistics: Some(vec![ColumnStatistics {
+                    distinct_count,
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            },
+            Schema::new(vec![Field::new(name, DataType::Int32, false)]),
+        ))
+    }
+
+    fn estimated_rows(
+        left: &Arc<dyn ExecutionPlan>,
+        right: &Arc<dyn ExecutionPlan>,
+        join_type: JoinType,
+    ) -> Option<usize> {
+        let on = &[(Column::new("a", 0), Column::new("b", 0))];
+        let (_, column_indices) =
+            build_join_schema(&left.schema(), &right.schema(), &join_type);
+        estimate_join_statistics(left, right, on, &join_type, &column_indices).num_rows
+    }
+
+    #[test]
+    fn join_statistics() {
+        // 1000 orders of 50 customers, joined with 10 of these customers
+        let orders = statistics_exec("a", 1000, Some(50));
+        let customers = statistics_exec("b", 10, None);
+
+        assert_eq!(
+            estimated_rows(&orders, &customers, JoinType::Inner),
+            Some(200)
+        );
+        assert_eq!(
+            estimated_rows(&orders, &customers, JoinType::Left),
+            Some(1000)
+        );
+        assert_eq!(
+            estimated_rows(&orders, &customers, JoinType::Right),
+            Some(200)
+        );
+        assert_eq!(
+            estimated_rows(&orders, &customers, JoinType::Full),
+            Some(1000)
+        );
+        assert_eq!(
+            estimated_rows(&orders, &customers, JoinType::Semi),
+            Some(200)
+        );
+        assert_eq!(
+            estimated_rows(&orders, &customers, JoinType::Anti),
+            Some(800)
+        );
+        assert_eq!(
+            estimated_rows(&customers, &orders, JoinType::Semi),
+            Some(10)
+        );
+
+        let empty = statistics_exec("b", 0, None);
+        assert_eq!(estimated_rows(&orders, &empty, JoinType::Inner), Some(0));
+        assert_eq!(estimated_rows(&orders, &empty, JoinType::Left), Some(1000));
+
+        let stats = estimate_join_statistics(
+            &orders,
+            &customers,
+            &[(Column::new("a", 0), Column::new("b", 0))],
+            &JoinType::Inner,
+            &build_join_schema(&orders.schema(), &customers.schema(), &JoinType::Inner).1,
+        );
+        let column_statistics = stats.column_statistics.unwrap();
+        assert_eq!(column_statistics.len(), 2);
+        assert_eq!(column_statistics[0].distinct_count, Some(50));
+        assert_eq!(column_statistics[1].distinct_count, None);
+        assert!(!stats.is_exact);
+    }
 }
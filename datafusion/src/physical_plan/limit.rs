@@ -27,7 +27,8 @@ use futures::stream::StreamExt;
 
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
-    DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
+    expressions::PhysicalSortExpr, DisplayFormatType, Distribution, ExecutionPlan,
+    Partitioning,
 };
 use arrow::array::ArrayRef;
 use arrow::compute::limit;
@@ -98,6 +99,10 @@ impl ExecutionPlan for GlobalLimitExec {
         Partitioning::UnknownPartitioning(1)
     }
 
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        self.input.output_ordering()
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -227,6 +232,10 @@ impl ExecutionPlan for LocalLimitExec {
         self.input.output_partitioning()
     }
 
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        self.input.output_ordering()
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -303,7 +312,7 @@ pub fn truncate_batch(batch: &RecordBatch, n: usize) -> RecordBatch {
 }
 
 /// A Limit stream limits the stream to up to `limit` rows.
-struct LimitStream {
+pub(crate) struct LimitStream {
     /// The maximum number of rows to produce
     limit: usize,
     /// The input to read from. This is set to None once the limit is
@@ -318,7 +327,7 @@ struct LimitStream {
 }
 
 impl LimitStream {
-    fn new(
+    pub(crate) fn new(
         input: SendableRecordBatchStream,
         limit: usize,
         baseline_metrics: BaselineMetrics,
@@ -408,6 +417,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             true,
             b',',
@@ -142,6 +142,17 @@ pub trait ExecutionPlan: Debug + Send + Sync {
     fn required_child_distribution(&self) -> Distribution {
         Distribution::UnspecifiedDistribution
     }
+    /// Specifies the order in which the rows of each output partition are
+    /// sorted, or `None` if it is unknown
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        None
+    }
+    /// Specifies the order in which the rows of each partition of every child
+    /// must be sorted for this operator, or `None` for the children on which
+    /// there is no requirement
+    fn required_input_ordering(&self) -> Vec<Option<Vec<PhysicalSortExpr>>> {
+        vec![None; self.children().len()]
+    }
     /// Get a list of child execution plans that provide the input for this plan. The returned list
     /// will be empty for leaf nodes, will contain a single value for unary nodes, or two
     /// values for binary nodes (such as joins).
@@ -185,6 +196,24 @@ pub trait ExecutionPlan: Debug + Send + Sync {
     fn statistics(&self) -> Statistics;
 }
 
+/// Returns true if rows sorted by `provided` are also sorted by `required`,
+/// that is if `required` is a prefix of `provided`
+pub fn ordering_satisfy(
+    provided: Option<&[PhysicalSortExpr]>,
+    required: &[PhysicalSortExpr],
+) -> bool {
+    match provided {
+        Some(provided) => {
+            required.len() <= provided.len()
+                && required
+                    .iter()
+                    .zip(provided)
+                    .all(|(required, provided)| provided.satisfies(required))
+        }
+        None => required.is_empty(),
+    }
+}
+
 /// Return a [wrapper](DisplayableExecutionPlan) around an
 /// [`ExecutionPlan`] which can be displayed in various easier to
 /// understand ways.
@@ -559,6 +588,8 @@ pub trait WindowExpr: Send + Sync + Debug {
 /// * convert its internal state to a vector of scalar values
 /// * update its state from multiple accumulators' states via `merge`
 /// * compute the final value from its internal state via `evaluate`
+/// * optionally, remove inputs from its state via `retract_batch`, which lets sliding
+///   window frames be evaluated without recomputing every frame from scratch
 pub trait Accumulator: Send + Sync + Debug {
     /// Returns the state of the accumulator at the end of the accumulation.
     // in the case of an average on which we track `sum` and `n`, this function should return a vector
@@ -616,11 +647,27 @@ pub trait Accumulator: Send + Sync + Debug {
 
     /// returns its value based on its current state.
     fn evaluate(&self) -> Result<ScalarValue>;
+
+    /// Removes the values of the arrays, which were previously added via
+    /// [`update_batch`], from the accumulator's state. Only called when
+    /// [`supports_retract_batch`] returns true.
+    fn retract_batch(&mut self, _values: &[ArrayRef]) -> Result<()> {
+        Err(DataFusionError::NotImplemented(
+            "Retract is not implemented for this accumulator".to_owned(),
+        ))
+    }
+
+    /// Whether the accumulator implements [`retract_batch`].
+    fn supports_retract_batch(&self) -> bool {
+        false
+    }
 }
 
+pub mod aggregate_clauses;
 pub mod aggregates;
 pub mod analyze;
 pub mod array_expressions;
+pub mod async_udf;
 pub mod coalesce_batches;
 pub mod coalesce_partitions;
 mod coercion_rule;
@@ -637,6 +684,7 @@ pub mod expressions;
 pub mod file_format;
 pub mod filter;
 pub mod functions;
+pub mod generate_series;
 pub mod hash_aggregate;
 pub mod hash_join;
 pub mod hash_utils;
@@ -651,6 +699,7 @@ pub mod projection;
 #[cfg(feature = "regex_expressions")]
 pub mod regex_expressions;
 pub mod repartition;
+pub mod runtime_filter;
 pub mod sort;
 pub mod sort_preserving_merge;
 pub mod stream;
@@ -658,9 +707,11 @@ pub mod string_expressions;
 pub mod type_coercion;
 pub mod udaf;
 pub mod udf;
+pub mod udwf;
 #[cfg(feature = "unicode_expressions")]
 pub mod unicode_expressions;
 pub mod union;
+pub mod unnest;
 pub mod values;
 pub mod window_functions;
 pub mod windows;
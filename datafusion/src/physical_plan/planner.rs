@@ -22,18 +22,22 @@ use super::{
     aggregates, empty::EmptyExec, expressions::binary, functions,
     hash_join::PartitionMode, udaf, union::UnionExec, values::ValuesExec, windows,
 };
-use crate::execution::context::ExecutionContextState;
+use crate::execution::context::{ExecutionConfig, ExecutionContextState};
 use crate::logical_plan::plan::{
     Aggregate, EmptyRelation, Filter, Join, Projection, Sort, TableScan, Window,
 };
 use crate::logical_plan::{
-    unalias, unnormalize_cols, CrossJoin, DFSchema, Expr, LogicalPlan, Operator,
-    Partitioning as LogicalPartitioning, PlanType, Repartition, ToStringifiedPlan, Union,
-    UserDefinedLogicalNode,
+    unalias, unnormalize_cols, Column as LogicalColumn, CrossJoin, DFField, DFSchema,
+    Expr, ExprRewriter, JoinType, LogicalPlan, Operator,
+    Partitioning as LogicalPartitioning, PlanType, Repartition, RewriteRecursion,
+    ToStringifiedPlan, Union, UserDefinedLogicalNode,
 };
-use crate::logical_plan::{Limit, Values};
+use crate::logical_plan::{Limit, Unnest, Values};
 use crate::physical_optimizer::optimizer::PhysicalOptimizerRule;
+use crate::physical_plan::aggregate_clauses::{FilteredAggregate, OrderedAggregate};
+use crate::physical_plan::async_udf::{AsyncFuncExec, AsyncFuncExpr};
 use crate::physical_plan::cross_join::CrossJoinExec;
+use crate::physical_plan::distinct_expressions::DistinctAggregate;
 use crate::physical_plan::explain::ExplainExec;
 use crate::physical_plan::expressions;
 use crate::physical_plan::expressions::{
@@ -46,12 +50,16 @@ use crate::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
 use crate::physical_plan::projection::ProjectionExec;
 use crate::physical_plan::repartition::RepartitionExec;
 use crate::physical_plan::sort::SortExec;
+use crate::physical_plan::sort_preserving_merge::SortPreservingMergeExec;
 use crate::physical_plan::udf;
+use crate::physical_plan::unnest::UnnestExec;
 use crate::physical_plan::windows::WindowAggExec;
 use crate::physical_plan::{join_utils, Partitioning};
 use crate::physical_plan::{AggregateExpr, ExecutionPlan, PhysicalExpr, WindowExpr};
 use crate::scalar::ScalarValue;
-use crate::sql::utils::{generate_sort_key, window_expr_common_partition_keys};
+use crate::sql::utils::{
+    find_async_udf_exprs, generate_sort_key, window_expr_common_partition_keys,
+};
 use crate::variable::VarType;
 use crate::{
     error::{DataFusionError, Result},
@@ -84,6 +92,25 @@ fn create_function_physical_name(
     Ok(format!("{}({}{})", fun, distinct_str, names.join(",")))
 }
 
+fn create_aggregate_physical_name(
+    fun: &str,
+    distinct: bool,
+    args: &[Expr],
+    filter: &Option<Box<Expr>>,
+    order_by: &[Expr],
+) -> Result<String> {
+    let mut name = create_function_physical_name(fun, distinct, args)?;
+    if !order_by.is_empty() {
+        // move the closing parenthesis behind the ORDER BY clause
+        name.pop();
+        name += &format!(" ORDER BY {:?})", order_by);
+    }
+    if let Some(filter) = filter {
+        name += &format!(" FILTER (WHERE {})", create_physical_name(filter, false)?);
+    }
+    Ok(name)
+}
+
 fn physical_name(e: &Expr) -> Result<String> {
     create_physical_name(e, true)
 }
@@ -100,6 +127,7 @@ fn create_physical_name(e: &Expr, is_first_expr: bool) -> Result<String> {
         Expr::Alias(_, name) => Ok(name.clone()),
         Expr::ScalarVariable(variable_names) => Ok(variable_names.join(".")),
         Expr::Literal(value) => Ok(format!("{:?}", value)),
+        Expr::Placeholder { id, .. } => Ok(id.clone()),
         Expr::BinaryExpr { left, op, right } => {
             let left = create_physical_name(left, false)?;
             let right = create_physical_name(right, false)?;
@@ -164,15 +192,22 @@ fn create_physical_name(e: &Expr, is_first_expr: bool) -> Result<String> {
             fun,
             distinct,
             args,
-            ..
-        } => create_function_physical_name(&fun.to_string(), *distinct, args),
-        Expr::AggregateUDF { fun, args } => {
-            let mut names = Vec::with_capacity(args.len());
-            for e in args {
-                names.push(create_physical_name(e, false)?);
-            }
-            Ok(format!("{}({})", fun.name, names.join(",")))
-        }
+            filter,
+            order_by,
+        } => create_aggregate_physical_name(
+            &fun.to_string(),
+            *distinct,
+            args,
+            filter,
+            order_by,
+        ),
+        Expr::AggregateUDF {
+            fun,
+            args,
+            distinct,
+            filter,
+            order_by,
+        } => create_aggregate_physical_name(&fun.name, *distinct, args, filter, order_by),
         Expr::InList {
             expr,
             list,
@@ -578,7 +613,7 @@ impl DefaultPhysicalPlanner {
                     let input_exec = self.create_initial_plan(input, ctx_state).await?;
                     let input_schema = input.as_ref().schema();
 
-                    let physical_exprs = expr
+                    let physical_names = expr
                         .iter()
                         .map(|e| {
                             // For projections, SQL planner and logical plan builder may convert user
@@ -595,7 +630,7 @@ impl DefaultPhysicalPlanner {
                             //
                             // This depends on the invariant that logical schema field index MUST match
                             // with physical schema field index.
-                            let physical_name = if let Expr::Column(col) = e {
+                            if let Expr::Column(col) = e {
                                 match input_schema.index_of_column(col) {
                                     Ok(idx) => {
                                         // index physical field using logical field index
@@ -607,16 +642,28 @@ impl DefaultPhysicalPlanner {
                                 }
                             } else {
                                 physical_name(e)
-                            };
+                            }
+                        })
+                        .collect::<Result<Vec<_>>>()?;
 
+                    let (expr, input_schema, input_exec) = self.plan_async_calls(
+                        expr.clone(),
+                        input_schema,
+                        input_exec,
+                        ctx_state,
+                    )?;
+                    let physical_exprs = expr
+                        .iter()
+                        .zip(physical_names)
+                        .map(|(e, physical_name)| {
                             tuple_err((
                                 self.create_physical_expr(
                                     e,
-                                    input_schema,
+                                    &input_schema,
                                     &input_exec.schema(),
                                     ctx_state,
                                 ),
-                                physical_name,
+                                Ok(physical_name),
                             ))
                         })
                         .collect::<Result<Vec<_>>>()?;
@@ -633,13 +680,36 @@ impl DefaultPhysicalPlanner {
                     let input_schema = physical_input.as_ref().schema();
                     let input_dfschema = input.as_ref().schema();
 
+                    let (predicate, input_dfschema, physical_input) = self
+                        .plan_async_calls(
+                            vec![predicate.clone()],
+                            input_dfschema,
+                            physical_input,
+                            ctx_state,
+                        )?;
                     let runtime_expr = self.create_physical_expr(
-                        predicate,
-                        input_dfschema,
-                        &input_schema,
+                        &predicate[0],
+                        &input_dfschema,
+                        &physical_input.schema(),
                         ctx_state,
                     )?;
-                    Ok(Arc::new(FilterExec::try_new(runtime_expr, physical_input)?) )
+                    let filter = FilterExec::try_new(runtime_expr, physical_input)?;
+                    if filter.schema().fields().len() == input_schema.fields().len() {
+                        Ok(Arc::new(filter) )
+                    } else {
+                        // drop the results of the async UDF calls again
+                        let columns = input_schema
+                            .fields()
+                            .iter()
+                            .enumerate()
+                            .map(|(i, field)| {
+                                let column: Arc<dyn PhysicalExpr> =
+                                    Arc::new(Column::new(field.name(), i));
+                                (column, field.name().clone())
+                            })
+                            .collect();
+                        Ok(Arc::new(ProjectionExec::try_new(columns, Arc::new(filter))?) )
+                    }
                 }
                 LogicalPlan::Union(Union { inputs, .. }) => {
                     let physical_plans = futures::stream::iter(inputs)
@@ -729,50 +799,29 @@ impl DefaultPhysicalPlanner {
                         })
                         .collect::<Result<join_utils::JoinOn>>()?;
 
-                    if ctx_state.config.target_partitions > 1
-                        && ctx_state.config.repartition_joins
-                    {
-                        let (left_expr, right_expr) = join_on
-                            .iter()
-                            .map(|(l, r)| {
-                                (
-                                    Arc::new(l.clone()) as Arc<dyn PhysicalExpr>,
-                                    Arc::new(r.clone()) as Arc<dyn PhysicalExpr>,
-                                )
-                            })
-                            .unzip();
-
-                        // Use hash partition by default to parallelize hash joins
-                        Ok(Arc::new(HashJoinExec::try_new(
-                            Arc::new(RepartitionExec::try_new(
-                                physical_left,
-                                Partitioning::Hash(
-                                    left_expr,
-                                    ctx_state.config.target_partitions,
-                                ),
-                            )?),
-                            Arc::new(RepartitionExec::try_new(
-                                physical_right,
-                                Partitioning::Hash(
-                                    right_expr,
-                                    ctx_state.config.target_partitions,
-                                ),
-                            )?),
-                            join_on,
-                            join_type,
-                            PartitionMode::Partitioned,
-                            null_equals_null,
-                        )?))
-                    } else {
-                        Ok(Arc::new(HashJoinExec::try_new(
-                            physical_left,
-                            physical_right,
-                            join_on,
-                            join_type,
-                            PartitionMode::CollectLeft,
-                            null_equals_null,
-                        )?))
-                    }
+                    create_hash_join(
+                        physical_left,
+                        physical_right,
+                        join_on,
+                        join_type,
+                        null_equals_null,
+                        &ctx_state.config,
+                    )
+                }
+                LogicalPlan::Unnest(Unnest {
+                    input, expr, schema,
+                }) => {
+                    let physical_input = self.create_initial_plan(input, ctx_state).await?;
+                    let input_schema = physical_input.as_ref().schema();
+                    let input_dfschema = input.as_ref().schema();
+                    let runtime_expr = self.create_physical_expr(
+                        expr,
+                        input_dfschema,
+                        &input_schema,
+                        ctx_state,
+                    )?;
+                    let name = schema.field(schema.fields().len() - 1).name();
+                    Ok(Arc::new(UnnestExec::try_new(runtime_expr, name, physical_input)?))
                 }
                 LogicalPlan::CrossJoin(CrossJoin { left, right, .. }) => {
                     let left = self.create_initial_plan(left, ctx_state).await?;
@@ -790,13 +839,17 @@ impl DefaultPhysicalPlanner {
                     let limit = *n;
                     let input = self.create_initial_plan(input, ctx_state).await?;
 
-                    // GlobalLimitExec requires a single partition for input
-                    let input = if input.output_partitioning().partition_count() == 1 {
-                        input
-                    } else {
+                    let input = match input.as_any().downcast_ref::<SortExec>() {
+                        // only keep the first `limit` rows of the sorted input
+                        // instead of sorting all of it
+                        Some(sort) if !sort.preserve_partitioning() => {
+                            create_top_k(sort, limit, batch_size)?
+                        }
+                        // GlobalLimitExec requires a single partition for input
+                        _ if input.output_partitioning().partition_count() == 1 => input,
                         // Apply a LocalLimitExec to each partition. The optimizer will also insert
                         // a CoalescePartitionsExec between the GlobalLimitExec and LocalLimitExec
-                        Arc::new(LocalLimitExec::new(input, limit))
+                        _ => Arc::new(LocalLimitExec::new(input, limit)),
                     };
 
                     Ok(Arc::new(GlobalLimitExec::new(input, limit)))
@@ -810,7 +863,8 @@ impl DefaultPhysicalPlanner {
                         "Unsupported logical plan: CreateExternalTable".to_string(),
                     ))
                 }
-                | LogicalPlan::CreateMemoryTable(_) | LogicalPlan::DropTable (_) => {
+                | LogicalPlan::CreateMemoryTable(_) | LogicalPlan::DropTable (_)
+                | LogicalPlan::SetVariable(_) | LogicalPlan::Prepare(_) => {
                     // Create a dummy exec.
                     Ok(Arc::new(EmptyExec::new(
                         false,
@@ -1165,6 +1219,10 @@ impl DefaultPhysicalPlanner {
                     expressions::in_list(value_expr, list_exprs, negated)
                 }
             },
+            Expr::Placeholder { id, .. } => Err(DataFusionError::Plan(format!(
+                "Placeholder {} must be bound to a value before the plan is executed",
+                id
+            ))),
             other => Err(DataFusionError::NotImplemented(format!(
                 "Physical plan does not support logical expression {:?}",
                 other
@@ -1234,12 +1292,6 @@ impl DefaultPhysicalPlanner {
                         )),
                     })
                     .collect::<Result<Vec<_>>>()?;
-                if window_frame.is_some() {
-                    return Err(DataFusionError::NotImplemented(
-                            "window expression with window frame definition is not yet supported"
-                                .to_owned(),
-                        ));
-                }
                 windows::create_window_expr(
                     fun,
                     name,
@@ -1293,7 +1345,8 @@ impl DefaultPhysicalPlanner {
                 fun,
                 distinct,
                 args,
-                ..
+                filter,
+                order_by,
             } => {
                 let args = args
                     .iter()
@@ -1306,15 +1359,30 @@ impl DefaultPhysicalPlanner {
                         )
                     })
                     .collect::<Result<Vec<_>>>()?;
-                aggregates::create_aggregate_expr(
+                let aggregate = aggregates::create_aggregate_expr(
                     fun,
                     *distinct,
                     &args,
                     physical_input_schema,
                     name,
+                )?;
+                self.create_aggregate_clauses(
+                    aggregate,
+                    *distinct,
+                    filter,
+                    order_by,
+                    logical_input_schema,
+                    physical_input_schema,
+                    ctx_state,
                 )
             }
-            Expr::AggregateUDF { fun, args, .. } => {
+            Expr::AggregateUDF {
+                fun,
+                args,
+                distinct,
+                filter,
+                order_by,
+            } => {
                 let args = args
                     .iter()
                     .map(|e| {
@@ -1327,7 +1395,26 @@ impl DefaultPhysicalPlanner {
                     })
                     .collect::<Result<Vec<_>>>()?;
 
-                udaf::create_aggregate_expr(fun, &args, physical_input_schema, name)
+                let mut aggregate =
+                    udaf::create_aggregate_expr(fun, &args, physical_input_schema, name)?;
+                if *distinct {
+                    let input_data_types = aggregate
+                        .expressions()
+                        .iter()
+                        .map(|e| e.data_type(physical_input_schema))
+                        .collect::<Result<Vec<_>>>()?;
+                    aggregate =
+                        Arc::new(DistinctAggregate::new(aggregate, input_data_types));
+                }
+                self.create_aggregate_clauses(
+                    aggregate,
+                    *distinct,
+                    filter,
+                    order_by,
+                    logical_input_schema,
+                    physical_input_schema,
+                    ctx_state,
+                )
             }
             other => Err(DataFusionError::Internal(format!(
                 "Invalid aggregate expression '{:?}'",
@@ -1336,6 +1423,75 @@ impl DefaultPhysicalPlanner {
         }
     }
 
+    /// Wraps an aggregate expression into the ones that apply the ORDER BY and
+    /// FILTER clauses of its call
+    #[allow(clippy::too_many_arguments)]
+    fn create_aggregate_clauses(
+        &self,
+        aggregate: Arc<dyn AggregateExpr>,
+        distinct: bool,
+        filter: &Option<Box<Expr>>,
+        order_by: &[Expr],
+        logical_input_schema: &DFSchema,
+        physical_input_schema: &Schema,
+        ctx_state: &ExecutionContextState,
+    ) -> Result<Arc<dyn AggregateExpr>> {
+        let mut aggregate = aggregate;
+        if !order_by.is_empty() {
+            if distinct {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "ORDER BY is not supported in the DISTINCT aggregation {}",
+                    aggregate.name()
+                )));
+            }
+            let order_by = order_by
+                .iter()
+                .map(|e| match e {
+                    Expr::Sort {
+                        expr,
+                        asc,
+                        nulls_first,
+                    } => self.create_physical_sort_expr(
+                        expr,
+                        logical_input_schema,
+                        physical_input_schema,
+                        SortOptions {
+                            descending: !*asc,
+                            nulls_first: *nulls_first,
+                        },
+                        ctx_state,
+                    ),
+                    _ => Err(DataFusionError::Plan(
+                        "Sort only accepts sort expressions".to_string(),
+                    )),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            aggregate = Arc::new(OrderedAggregate::try_new(
+                aggregate,
+                order_by,
+                physical_input_schema,
+            )?);
+        }
+        if let Some(filter) = filter {
+            let filter = self.create_physical_expr(
+                filter,
+                logical_input_schema,
+                physical_input_schema,
+                ctx_state,
+            )?;
+            let data_type = filter.data_type(physical_input_schema)?;
+            if data_type != DataType::Boolean {
+                return Err(DataFusionError::Plan(format!(
+                    "The FILTER clause of {} must be a boolean expression, not {:?}",
+                    aggregate.name(),
+                    data_type
+                )));
+            }
+            aggregate = Arc::new(FilteredAggregate::new(aggregate, filter));
+        }
+        Ok(aggregate)
+    }
+
     /// Create an aggregate expression from a logical expression or an alias
     pub fn create_aggregate_expr(
         &self,
@@ -1419,6 +1575,79 @@ impl DefaultPhysicalPlanner {
         }
     }
 
+    /// Evaluates the calls of async UDFs in `exprs` in [`AsyncFuncExec`]s on top
+    /// of `input`, innermost calls first. Returns `exprs` with these calls
+    /// replaced by references to their results, along with the logical schema
+    /// and the plan that provide them.
+    fn plan_async_calls(
+        &self,
+        mut exprs: Vec<Expr>,
+        input_dfschema: &DFSchema,
+        mut input: Arc<dyn ExecutionPlan>,
+        ctx_state: &ExecutionContextState,
+    ) -> Result<(Vec<Expr>, DFSchema, Arc<dyn ExecutionPlan>)> {
+        let mut input_dfschema = input_dfschema.clone();
+        loop {
+            let calls = find_async_udf_exprs(&exprs);
+            if calls.is_empty() {
+                return Ok((exprs, input_dfschema, input));
+            }
+
+            let input_schema = input.schema();
+            let mut fields = vec![];
+            let mut physical_calls = vec![];
+            let mut replacements = vec![];
+            for call in calls {
+                let (fun, args) = match &call {
+                    Expr::ScalarUDF { fun, args } => (fun, args),
+                    _ => {
+                        return Err(DataFusionError::Internal(format!(
+                            "Expected a call of an async UDF, got {:?}",
+                            call
+                        )))
+                    }
+                };
+                let physical_args = args
+                    .iter()
+                    .map(|e| {
+                        self.create_physical_expr(
+                            e,
+                            &input_dfschema,
+                            &input_schema,
+                            ctx_state,
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let physical_call =
+                    AsyncFuncExpr::try_new(fun, &physical_args, &input_schema)?;
+
+                let name = format!(
+                    "__async_udf_{}",
+                    input_schema.fields().len() + physical_calls.len()
+                );
+                fields.push(DFField::new(
+                    None,
+                    &name,
+                    physical_call.return_type().clone(),
+                    true,
+                ));
+                physical_calls.push((Arc::new(physical_call), name.clone()));
+                replacements.push((call, name));
+            }
+
+            input_dfschema = input_dfschema.join(&DFSchema::new(fields)?)?;
+            input = Arc::new(AsyncFuncExec::try_new(physical_calls, input)?);
+            exprs = exprs
+                .into_iter()
+                .map(|e| {
+                    e.rewrite(&mut AsyncCallRewriter {
+                        replacements: &replacements,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+        }
+    }
+
     /// Optimize a physical plan by applying each physical optimizer,
     /// calling observer(plan, optimizer after each one)
     fn optimize_internal<F>(
@@ -1435,6 +1664,9 @@ impl DefaultPhysicalPlanner {
 
         let mut new_plan = plan;
         for optimizer in optimizers {
+            if !ctx_state.config.is_optimizer_rule_enabled(optimizer.name()) {
+                continue;
+            }
             new_plan = optimizer.optimize(new_plan, &ctx_state.config)?;
             observer(new_plan.as_ref(), optimizer.as_ref())
         }
@@ -1447,6 +1679,101 @@ impl DefaultPhysicalPlanner {
     }
 }
 
+/// Creates a hash join of `left` and `right` on `on`. When joins are
+/// repartitioned, both inputs are hash partitioned on their join keys to
+/// parallelize the join, otherwise the left input is collected.
+pub(crate) fn create_hash_join(
+    left: Arc<dyn ExecutionPlan>,
+    right: Arc<dyn ExecutionPlan>,
+    on: join_utils::JoinOn,
+    join_type: &JoinType,
+    null_equals_null: &bool,
+    config: &ExecutionConfig,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    if config.target_partitions > 1 && config.repartition_joins {
+        let (left_expr, right_expr) = on
+            .iter()
+            .map(|(l, r)| {
+                (
+                    Arc::new(l.clone()) as Arc<dyn PhysicalExpr>,
+                    Arc::new(r.clone()) as Arc<dyn PhysicalExpr>,
+                )
+            })
+            .unzip();
+
+        // Use hash partition by default to parallelize hash joins
+        Ok(Arc::new(HashJoinExec::try_new(
+            Arc::new(RepartitionExec::try_new(
+                left,
+                Partitioning::Hash(left_expr, config.target_partitions),
+            )?),
+            Arc::new(RepartitionExec::try_new(
+                right,
+                Partitioning::Hash(right_expr, config.target_partitions),
+            )?),
+            on,
+            join_type,
+            PartitionMode::Partitioned,
+            null_equals_null,
+        )?))
+    } else {
+        Ok(Arc::new(HashJoinExec::try_new(
+            left,
+            right,
+            on,
+            join_type,
+            PartitionMode::CollectLeft,
+            null_equals_null,
+        )?))
+    }
+}
+
+/// Replaces a sort into a single partition whose output is limited to
+/// `limit` rows by sorts that only keep the first `limit` rows of each input
+/// partition, and a merge of these partitions that stops after `limit` rows
+fn create_top_k(
+    sort: &SortExec,
+    limit: usize,
+    batch_size: usize,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let input = sort.input().clone();
+    let expr = sort.expr().to_vec();
+    if input.output_partitioning().partition_count() == 1 {
+        return Ok(Arc::new(
+            SortExec::try_new(expr, input)?.with_fetch(Some(limit)),
+        ));
+    }
+    let sorted = SortExec::new_with_partitioning(expr.clone(), input, true)
+        .with_fetch(Some(limit));
+    Ok(Arc::new(
+        SortPreservingMergeExec::new(expr, Arc::new(sorted), batch_size)
+            .with_fetch(Some(limit)),
+    ))
+}
+
+/// Replaces calls of async UDFs by references to the columns of their results
+struct AsyncCallRewriter<'a> {
+    /// The calls along with the names of their result columns
+    replacements: &'a [(Expr, String)],
+}
+
+impl ExprRewriter for AsyncCallRewriter<'_> {
+    fn pre_visit(&mut self, expr: &Expr) -> Result<RewriteRecursion> {
+        if self.replacements.iter().any(|(call, _)| call == expr) {
+            Ok(RewriteRecursion::Mutate)
+        } else {
+            Ok(RewriteRecursion::Continue)
+        }
+    }
+
+    fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+        match self.replacements.iter().find(|(call, _)| *call == expr) {
+            Some((_, name)) => Ok(Expr::Column(LogicalColumn::from_name(name))),
+            None => Ok(expr),
+        }
+    }
+}
+
 fn tuple_err<T, R>(value: (Result<T>, Result<R>)) -> Result<(T, R)> {
     match value {
         (Ok(e), Ok(e1)) => Ok((e, e1)),
@@ -34,7 +34,7 @@ use arrow::datatypes::{Field, Schema, SchemaRef};
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 
-use super::expressions::Column;
+use super::expressions::{Column, PhysicalSortExpr};
 use super::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
 use super::{RecordBatchStream, SendableRecordBatchStream, Statistics};
 use async_trait::async_trait;
@@ -121,6 +121,27 @@ impl ExecutionPlan for ProjectionExec {
         self.input.output_partitioning()
     }
 
+    /// The ordering of the input on the columns that are projected, up to
+    /// the first sort column that is not
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        let ordering = self
+            .input
+            .output_ordering()?
+            .into_iter()
+            .map_while(|sort| {
+                let column = sort.expr.as_any().downcast_ref::<Column>()?;
+                let index = self.expr.iter().position(|(expr, _)| {
+                    expr.as_any().downcast_ref::<Column>() == Some(column)
+                })?;
+                Some(PhysicalSortExpr {
+                    expr: Arc::new(Column::new(&self.expr[index].1, index)),
+                    options: sort.options,
+                })
+            })
+            .collect::<Vec<_>>();
+        (!ordering.is_empty()).then(|| ordering)
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -309,6 +330,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             true,
             b',',
@@ -32,6 +32,7 @@ use arrow::{compute::take, datatypes::SchemaRef};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 use super::common::{AbortOnDropMany, AbortOnDropSingle};
+use super::expressions::PhysicalSortExpr;
 use super::metrics::{self, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet};
 use super::{RecordBatchStream, SendableRecordBatchStream};
 use async_trait::async_trait;
@@ -164,6 +165,16 @@ impl ExecutionPlan for RepartitionExec {
         self.partitioning.clone()
     }
 
+    /// Each output partition receives the batches of every input partition,
+    /// so it is only sorted if there is a single input partition
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        if self.input.output_partitioning().partition_count() == 1 {
+            self.input.output_ordering()
+        } else {
+            None
+        }
+    }
+
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
         // lock mutexes
         let mut state = self.state.lock().await;
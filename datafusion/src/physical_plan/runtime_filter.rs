@@ -0,0 +1,583 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Runtime filters, which a hash join publishes from the keys of its build
+//! side once collected, so that the scans and filters of its probe side skip
+//! the rows that cannot match.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use ahash::RandomState;
+use arrow::array::{ArrayRef, BooleanArray};
+use arrow::datatypes::{DataType, Schema};
+use arrow::record_batch::RecordBatch;
+
+use super::expressions::{in_list, lit, BinaryExpr, Column, Max, Min};
+use super::hash_utils::create_hashes;
+use super::{Accumulator, AggregateExpr, ColumnarValue, PhysicalExpr};
+use crate::error::Result;
+use crate::logical_plan::{self, Expr, Operator};
+use crate::scalar::ScalarValue;
+
+/// Maximum number of distinct build keys kept in the list of a [`KeyFilter`]
+const MAX_IN_LIST_VALUES: usize = 32;
+/// Number of bits of a [`BloomFilter`] per build row
+const BLOOM_FILTER_BITS_PER_ROW: usize = 8;
+/// Number of bits set in a [`BloomFilter`] per value
+const BLOOM_FILTER_HASHES: u64 = 3;
+
+/// Returns true if runtime filters can be built for join keys of type
+/// `data_type`
+pub fn supports_key_type(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Utf8
+            | DataType::LargeUtf8
+    )
+}
+
+/// How the probe side of a join uses a [`ProbeFilter`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProbeFilterUse {
+    /// The rows are filtered with [`ProbeFilter::physical_expr`]
+    Rows,
+    /// The containers of rows are pruned with [`ProbeFilter::pruning_expr`]
+    Pruning,
+}
+
+/// A filter on the keys of the probe side of a hash join, which the join
+/// publishes once it has collected its build side
+#[derive(Debug, Default)]
+pub struct RuntimeFilter {
+    keys: Mutex<Option<Arc<Vec<KeyFilter>>>>,
+    /// How the probe side uses the filter of each join key, the filters of
+    /// the keys that are not used are not computed
+    uses: Mutex<HashMap<usize, ProbeFilterUse>>,
+}
+
+impl RuntimeFilter {
+    /// Create a filter that is not published yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the probe side uses the filter of the join key `key`
+    fn add_use(&self, key: usize, filter_use: ProbeFilterUse) {
+        let mut uses = self.uses.lock().unwrap();
+        let key_use = uses.entry(key).or_insert(filter_use);
+        // filtering the rows needs all that pruning needs
+        if filter_use == ProbeFilterUse::Rows {
+            *key_use = ProbeFilterUse::Rows;
+        }
+    }
+
+    /// Publishes the filter on the values of the `on` columns of the build
+    /// side `batch`
+    pub fn publish(&self, batch: &RecordBatch, on: &[Column]) {
+        let uses = self.uses.lock().unwrap().clone();
+        let keys = on
+            .iter()
+            .enumerate()
+            .map(|(key, column)| match uses.get(&key) {
+                Some(filter_use) => {
+                    KeyFilter::new(batch.column(column.index()), *filter_use)
+                }
+                None => KeyFilter::default(),
+            })
+            .collect();
+        *self.keys.lock().unwrap() = Some(Arc::new(keys));
+    }
+
+    /// The filters on each join key, if published
+    pub fn keys(&self) -> Option<Arc<Vec<KeyFilter>>> {
+        self.keys.lock().unwrap().clone()
+    }
+}
+
+/// The values of a join key on the build side of a hash join
+#[derive(Debug, Default)]
+pub struct KeyFilter {
+    /// The minimum and maximum non null values
+    range: Option<(ScalarValue, ScalarValue)>,
+    /// The distinct non null values, if there are few of them
+    values: Option<Vec<ScalarValue>>,
+    /// The non null values, if there are too many of them to be listed
+    bloom_filter: Option<Arc<BloomFilter>>,
+}
+
+impl KeyFilter {
+    /// Create the filter on the values of `array` for `filter_use`. Parts of
+    /// the filter that cannot be computed for the type of `array`, or that
+    /// are not needed, are left out.
+    pub fn new(array: &ArrayRef, filter_use: ProbeFilterUse) -> Self {
+        let values = distinct_values(array, MAX_IN_LIST_VALUES).unwrap_or(None);
+        if values.is_some() {
+            // the list of values supersedes the range and the bloom filter
+            return Self {
+                values,
+                ..Self::default()
+            };
+        }
+        let bloom_filter = match filter_use {
+            ProbeFilterUse::Rows => BloomFilter::try_new(array).ok().map(Arc::new),
+            ProbeFilterUse::Pruning => None,
+        };
+        Self {
+            range: min_max(array),
+            values,
+            bloom_filter,
+        }
+    }
+
+    /// A predicate that is true for the values of `column` that may be
+    /// equal to a key
+    pub fn physical_expr(&self, column: &Column) -> Option<Arc<dyn PhysicalExpr>> {
+        let column: Arc<dyn PhysicalExpr> = Arc::new(column.clone());
+        match (&self.values, &self.range) {
+            // all the keys are null
+            (Some(values), _) if values.is_empty() => {
+                Some(lit(ScalarValue::Boolean(Some(false))))
+            }
+            (Some(values), _) => {
+                let list = values.iter().cloned().map(lit).collect();
+                in_list(column, list, &false).ok()
+            }
+            (None, range) => {
+                let mut predicates: Vec<Arc<dyn PhysicalExpr>> = vec![];
+                if let Some((min, max)) = range {
+                    predicates.push(Arc::new(BinaryExpr::new(
+                        column.clone(),
+                        Operator::GtEq,
+                        lit(min.clone()),
+                    )));
+                    predicates.push(Arc::new(BinaryExpr::new(
+                        column.clone(),
+                        Operator::LtEq,
+                        lit(max.clone()),
+                    )));
+                }
+                if let Some(bloom_filter) = &self.bloom_filter {
+                    predicates.push(Arc::new(BloomFilterExpr::new(
+                        column,
+                        bloom_filter.clone(),
+                    )));
+                }
+                predicates.into_iter().reduce(|left, right| {
+                    Arc::new(BinaryExpr::new(left, Operator::And, right)) as _
+                })
+            }
+        }
+    }
+
+    /// A predicate on the column `name` that can be used to prune the
+    /// containers whose values are not equal to any key
+    pub fn pruning_expr(&self, name: &str) -> Option<Expr> {
+        let column = || Expr::Column(logical_plan::Column::from_name(name));
+        match (&self.values, &self.range) {
+            (Some(values), _) if !values.is_empty() => values
+                .iter()
+                .map(|value| column().eq(Expr::Literal(value.clone())))
+                .reduce(Expr::or),
+            (_, Some((min, max))) => Some(
+                column()
+                    .gt_eq(Expr::Literal(min.clone()))
+                    .and(column().lt_eq(Expr::Literal(max.clone()))),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the distinct non null values of `array`, or `None` as soon as
+/// there are more than `max_values` of them
+fn distinct_values(
+    array: &ArrayRef,
+    max_values: usize,
+) -> Result<Option<Vec<ScalarValue>>> {
+    let random_state = RandomState::with_seeds(0, 0, 0, 0);
+    let mut hashes = vec![0; array.len()];
+    create_hashes(&[array.clone()], &random_state, &mut hashes)?;
+
+    let mut values: Vec<ScalarValue> = vec![];
+    // the indices in `values` of the values with each hash
+    let mut indices: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (row, hash) in hashes.into_iter().enumerate() {
+        if array.is_null(row) {
+            continue;
+        }
+        let same_hash = indices.entry(hash).or_default();
+        if same_hash
+            .iter()
+            .any(|index| values[*index].eq_array(array, row))
+        {
+            continue;
+        }
+        if values.len() == max_values {
+            return Ok(None);
+        }
+        same_hash.push(values.len());
+        values.push(ScalarValue::try_from_array(array, row)?);
+    }
+    Ok(Some(values))
+}
+
+/// Returns the minimum and maximum non null values of `array`
+fn min_max(array: &ArrayRef) -> Option<(ScalarValue, ScalarValue)> {
+    let column: Arc<dyn PhysicalExpr> = Arc::new(Column::new("key", 0));
+    let data_type = array.data_type().clone();
+    let aggregate = |expr: Arc<dyn AggregateExpr>| -> Result<ScalarValue> {
+        let mut accumulator: Box<dyn Accumulator> = expr.create_accumulator()?;
+        accumulator.update_batch(&[array.clone()])?;
+        accumulator.evaluate()
+    };
+    let min = aggregate(Arc::new(Min::new(column.clone(), "min", data_type.clone())));
+    let max = aggregate(Arc::new(Max::new(column, "max", data_type)));
+    match (min, max) {
+        (Ok(min), Ok(max)) if !min.is_null() && !max.is_null() => Some((min, max)),
+        _ => None,
+    }
+}
+
+/// A bloom filter on the hashes of the non null values of an array
+#[derive(Debug)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    random_state: RandomState,
+}
+
+impl BloomFilter {
+    /// Create the filter of the non null values of `array`
+    pub fn try_new(array: &ArrayRef) -> Result<Self> {
+        let random_state = RandomState::with_seeds(0, 0, 0, 0);
+        let mut hashes = vec![0; array.len()];
+        create_hashes(&[array.clone()], &random_state, &mut hashes)?;
+
+        let num_bits = (array.len() * BLOOM_FILTER_BITS_PER_ROW)
+            .next_power_of_two()
+            .max(64);
+        let mut filter = Self {
+            bits: vec![0; num_bits / 64],
+            random_state,
+        };
+        for (index, hash) in hashes.into_iter().enumerate() {
+            if array.is_valid(index) {
+                for bit in filter.bit_positions(hash) {
+                    filter.bits[bit / 64] |= 1 << (bit % 64);
+                }
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Returns whether each value of `array` may be in the filter, which is
+    /// false for the null values
+    pub fn contains(&self, array: &ArrayRef) -> Result<BooleanArray> {
+        let mut hashes = vec![0; array.len()];
+        create_hashes(&[array.clone()], &self.random_state, &mut hashes)?;
+        Ok(hashes
+            .into_iter()
+            .enumerate()
+            .map(|(index, hash)| {
+                Some(
+                    array.is_valid(index)
+                        && self
+                            .bit_positions(hash)
+                            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0),
+                )
+            })
+            .collect())
+    }
+
+    fn bit_positions(&self, hash: u64) -> impl Iterator<Item = usize> {
+        // double hashing, see "Less Hashing, Same Performance: Building a
+        // Better Bloom Filter" by Kirsch and Mitzenmacher
+        let num_bits = self.bits.len() as u64 * 64;
+        let delta = hash.rotate_left(32) | 1;
+        (0..BLOOM_FILTER_HASHES)
+            .map(move |i| (hash.wrapping_add(i.wrapping_mul(delta)) % num_bits) as usize)
+    }
+}
+
+/// A predicate that is true for the values of an expression that may be in
+/// a [`BloomFilter`]
+#[derive(Debug)]
+pub struct BloomFilterExpr {
+    expr: Arc<dyn PhysicalExpr>,
+    bloom_filter: Arc<BloomFilter>,
+}
+
+impl BloomFilterExpr {
+    /// Create a new bloom filter expression
+    pub fn new(expr: Arc<dyn PhysicalExpr>, bloom_filter: Arc<BloomFilter>) -> Self {
+        Self { expr, bloom_filter }
+    }
+
+    /// Input expression
+    pub fn expr(&self) -> &Arc<dyn PhysicalExpr> {
+        &self.expr
+    }
+}
+
+impl fmt::Display for BloomFilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} IN BLOOM FILTER", self.expr)
+    }
+}
+
+impl PhysicalExpr for BloomFilterExpr {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, _input_schema: &Schema) -> Result<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn nullable(&self, _input_schema: &Schema) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        let array = self.expr.evaluate(batch)?.into_array(batch.num_rows());
+        Ok(ColumnarValue::Array(Arc::new(
+            self.bloom_filter.contains(&array)?,
+        )))
+    }
+}
+
+/// A [`RuntimeFilter`] applied to the columns of a plan that hold the
+/// probe side join keys
+#[derive(Debug, Clone)]
+pub struct ProbeFilter {
+    filter: Arc<RuntimeFilter>,
+    /// The index of each join key filtered, with the column holding it
+    columns: Vec<(usize, Column)>,
+}
+
+impl ProbeFilter {
+    /// Create a filter of the `columns` on the join keys with the given
+    /// indices, used by the probe side as `filter_use`
+    pub fn new(
+        filter: Arc<RuntimeFilter>,
+        columns: Vec<(usize, Column)>,
+        filter_use: ProbeFilterUse,
+    ) -> Self {
+        for (key, _) in &columns {
+            filter.add_use(*key, filter_use);
+        }
+        Self { filter, columns }
+    }
+
+    /// The filter published by the join
+    pub fn filter(&self) -> &Arc<RuntimeFilter> {
+        &self.filter
+    }
+
+    /// The index of each join key filtered, with the column holding it
+    pub fn columns(&self) -> &[(usize, Column)] {
+        &self.columns
+    }
+
+    /// The predicate on the columns, if the filter is published
+    pub fn physical_expr(&self) -> Option<Arc<dyn PhysicalExpr>> {
+        let keys = self.filter.keys()?;
+        self.columns
+            .iter()
+            .filter_map(|(key, column)| keys[*key].physical_expr(column))
+            .reduce(|left, right| {
+                Arc::new(BinaryExpr::new(left, Operator::And, right)) as _
+            })
+    }
+
+    /// The predicate to prune with the statistics of the columns, if the
+    /// filter is published
+    pub fn pruning_expr(&self) -> Option<Expr> {
+        let keys = self.filter.keys()?;
+        self.columns
+            .iter()
+            .filter_map(|(key, column)| keys[*key].pruning_expr(column.name()))
+            .reduce(Expr::and)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::Field;
+
+    fn evaluate(filter: &ProbeFilter, array: ArrayRef) -> Result<Vec<Option<bool>>> {
+        let schema = Schema::new(vec![Field::new("a", array.data_type().clone(), true)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![array])?;
+        let result = filter
+            .physical_expr()
+            .unwrap()
+            .evaluate(&batch)?
+            .into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        Ok(result.iter().collect())
+    }
+
+    fn publish(keys: ArrayRef, filter_use: ProbeFilterUse) -> Result<ProbeFilter> {
+        let schema = Schema::new(vec![Field::new("k", keys.data_type().clone(), true)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![keys])?;
+        let filter = Arc::new(RuntimeFilter::new());
+        let probe =
+            ProbeFilter::new(filter.clone(), vec![(0, Column::new("a", 0))], filter_use);
+        assert!(probe.physical_expr().is_none());
+        filter.publish(&batch, &[Column::new("k", 0)]);
+        Ok(probe)
+    }
+
+    #[test]
+    fn in_list_filter() -> Result<()> {
+        let probe = publish(
+            Arc::new(StringArray::from(vec![
+                Some("b"),
+                None,
+                Some("d"),
+                Some("b"),
+            ])),
+            ProbeFilterUse::Rows,
+        )?;
+        assert_eq!(
+            format!("{:?}", probe.pruning_expr().unwrap()),
+            "#a = Utf8(\"b\") OR #a = Utf8(\"d\")"
+        );
+
+        let probe_values = Arc::new(StringArray::from(vec![
+            Some("a"),
+            Some("b"),
+            None,
+            Some("d"),
+        ]));
+        assert_eq!(
+            evaluate(&probe, probe_values)?,
+            vec![Some(false), Some(true), None, Some(true)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "force_hash_collisions"))]
+    fn bloom_filter() -> Result<()> {
+        let keys: Int32Array = (0..100).map(|i| Some(i * 10)).collect();
+        let probe = publish(Arc::new(keys), ProbeFilterUse::Rows)?;
+        assert_eq!(
+            format!("{:?}", probe.pruning_expr().unwrap()),
+            "#a >= Int32(0) AND #a <= Int32(990)"
+        );
+
+        let probe_values: Int32Array = (-10..1000).map(Some).chain(Some(None)).collect();
+        let result = evaluate(&probe, Arc::new(probe_values))?;
+        // no false negatives
+        assert!((0..100).all(|i| result[i * 10 + 10] == Some(true)));
+        assert_eq!(result[0], Some(false));
+        assert_ne!(result[1010], Some(true));
+        let false_positives = result
+            .iter()
+            .enumerate()
+            .filter(|(i, matches)| i % 10 != 0 && **matches == Some(true))
+            .count();
+        assert!(false_positives < 100, "{} false positives", false_positives);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_filter() -> Result<()> {
+        let probe = publish(
+            Arc::new(Int32Array::from(vec![None, None])),
+            ProbeFilterUse::Rows,
+        )?;
+        assert!(probe.pruning_expr().is_none());
+        assert_eq!(
+            evaluate(&probe, Arc::new(Int32Array::from(vec![Some(1), None])))?,
+            vec![Some(false), Some(false)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn distinct_values_limit() -> Result<()> {
+        // many duplicates of few values
+        let keys: ArrayRef =
+            Arc::new((0..10_000).map(|i| Some(i % 32)).collect::<Int32Array>());
+        let values = distinct_values(&keys, MAX_IN_LIST_VALUES)?.unwrap();
+        assert_eq!(values.len(), 32);
+        assert_eq!(values[31], ScalarValue::Int32(Some(31)));
+
+        let keys: ArrayRef =
+            Arc::new((0..10_000).map(|i| Some(i % 33)).collect::<Int32Array>());
+        assert!(distinct_values(&keys, MAX_IN_LIST_VALUES)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn pruning_filter() -> Result<()> {
+        let keys: Int32Array = (0..100).map(|i| Some(i * 10)).collect();
+        let probe = publish(Arc::new(keys), ProbeFilterUse::Pruning)?;
+        assert_eq!(
+            format!("{:?}", probe.pruning_expr().unwrap()),
+            "#a >= Int32(0) AND #a <= Int32(990)"
+        );
+        // the bloom filter is only used to filter rows
+        let keys = probe.filter().keys().unwrap();
+        assert!(keys[0].bloom_filter.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn unused_key() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("k1", DataType::Int32, true),
+            Field::new("k2", DataType::Int32, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(Int32Array::from(vec![3, 4])),
+            ],
+        )?;
+        let filter = Arc::new(RuntimeFilter::new());
+        let probe = ProbeFilter::new(
+            filter.clone(),
+            vec![(1, Column::new("a", 0))],
+            ProbeFilterUse::Rows,
+        );
+        filter.publish(&batch, &[Column::new("k1", 0), Column::new("k2", 1)]);
+
+        let keys = filter.keys().unwrap();
+        assert!(keys[0].values.is_none() && keys[0].range.is_none());
+        assert_eq!(
+            evaluate(&probe, Arc::new(Int32Array::from(vec![1, 3])))?,
+            vec![Some(false), Some(true)]
+        );
+        Ok(())
+    }
+}
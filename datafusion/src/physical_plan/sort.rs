@@ -34,7 +34,7 @@ use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 use arrow::{array::ArrayRef, error::ArrowError};
 use async_trait::async_trait;
-use futures::stream::Stream;
+use futures::stream::{Stream, StreamExt};
 use futures::Future;
 use pin_project_lite::pin_project;
 use std::any::Any;
@@ -53,6 +53,9 @@ pub struct SortExec {
     metrics: ExecutionPlanMetricsSet,
     /// Preserve partitions of input plan
     preserve_partitioning: bool,
+    /// Only produce the first `fetch` rows of each partition, keeping no
+    /// more than `fetch` sorted rows in memory
+    fetch: Option<usize>,
 }
 
 impl SortExec {
@@ -76,9 +79,16 @@ impl SortExec {
             input,
             metrics: ExecutionPlanMetricsSet::new(),
             preserve_partitioning,
+            fetch: None,
         }
     }
 
+    /// Only produce the first `fetch` rows of each sorted partition
+    pub fn with_fetch(mut self, fetch: Option<usize>) -> Self {
+        self.fetch = fetch;
+        self
+    }
+
     /// Input schema
     pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
         &self.input
@@ -88,6 +98,16 @@ impl SortExec {
     pub fn expr(&self) -> &[PhysicalSortExpr] {
         &self.expr
     }
+
+    /// Whether the partitions of the input plan are sorted separately
+    pub fn preserve_partitioning(&self) -> bool {
+        self.preserve_partitioning
+    }
+
+    /// The maximum number of rows of each sorted partition, if any
+    pub fn fetch(&self) -> Option<usize> {
+        self.fetch
+    }
 }
 
 #[async_trait]
@@ -122,15 +142,23 @@ impl ExecutionPlan for SortExec {
         }
     }
 
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        Some(self.expr.clone())
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            1 => Ok(Arc::new(SortExec::try_new(
-                self.expr.clone(),
-                children[0].clone(),
-            )?)),
+            1 => Ok(Arc::new(
+                SortExec::new_with_partitioning(
+                    self.expr.clone(),
+                    children[0].clone(),
+                    self.preserve_partitioning,
+                )
+                .with_fetch(self.fetch),
+            )),
             _ => Err(DataFusionError::Internal(
                 "SortExec wrong number of children".to_string(),
             )),
@@ -160,6 +188,7 @@ impl ExecutionPlan for SortExec {
         Ok(Box::pin(SortStream::new(
             input,
             self.expr.clone(),
+            self.fetch,
             baseline_metrics,
         )))
     }
@@ -172,7 +201,11 @@ impl ExecutionPlan for SortExec {
         match t {
             DisplayFormatType::Default => {
                 let expr: Vec<String> = self.expr.iter().map(|e| e.to_string()).collect();
-                write!(f, "SortExec: [{}]", expr.join(","))
+                write!(f, "SortExec: [{}]", expr.join(","))?;
+                if let Some(fetch) = self.fetch {
+                    write!(f, ", fetch={}", fetch)?;
+                }
+                Ok(())
             }
         }
     }
@@ -182,23 +215,42 @@ impl ExecutionPlan for SortExec {
     }
 
     fn statistics(&self) -> Statistics {
-        self.input.statistics()
+        let input_stats = self.input.statistics();
+        // the partitions sorted separately each produce up to `fetch` rows
+        let fetch = self.fetch.map(|fetch| match self.preserve_partitioning {
+            true => fetch * self.input.output_partitioning().partition_count(),
+            false => fetch,
+        });
+        match (fetch, input_stats.num_rows) {
+            // if the input is greater than the fetch, the num_row will be the
+            // fetch but we won't be able to predict the other statistics
+            (Some(fetch), Some(nr)) if nr > fetch => Statistics {
+                num_rows: Some(fetch),
+                is_exact: input_stats.is_exact && !self.preserve_partitioning,
+                ..Default::default()
+            },
+            // if we don't know the input size, we can't predict the fetch's behaviour
+            (Some(_), None) => Statistics::default(),
+            _ => input_stats,
+        }
     }
 }
 
+/// Sorts `batch` and returns its first `fetch` rows, or all of them if
+/// `fetch` is `None`
 fn sort_batch(
     batch: RecordBatch,
     schema: SchemaRef,
     expr: &[PhysicalSortExpr],
+    fetch: Option<usize>,
 ) -> ArrowResult<RecordBatch> {
-    // TODO: pushup the limit expression to sort
     let indices = lexsort_to_indices(
         &expr
             .iter()
             .map(|e| e.evaluate_to_sort_column(&batch))
             .collect::<Result<Vec<SortColumn>>>()
             .map_err(DataFusionError::into_arrow_external_error)?,
-        None,
+        fetch.map(|fetch| fetch.min(batch.num_rows())),
     )?;
 
     // reorder all rows based on sorted indices
@@ -237,11 +289,19 @@ impl SortStream {
     fn new(
         input: SendableRecordBatchStream,
         expr: Vec<PhysicalSortExpr>,
+        fetch: Option<usize>,
         baseline_metrics: BaselineMetrics,
     ) -> Self {
         let (tx, rx) = futures::channel::oneshot::channel();
         let schema = input.schema();
         let join_handle = tokio::spawn(async move {
+            if let Some(fetch) = fetch {
+                let top_k = sort_top_k(input, &expr, fetch, &baseline_metrics).await;
+                // failing here is OK, the receiver is gone and does not care about the result
+                tx.send(top_k).ok();
+                return;
+            }
+
             let schema = input.schema();
             let sorted_batch = common::collect(input)
                 .await
@@ -252,7 +312,7 @@ impl SortStream {
                     let combined = common::combine_batches(&batches, schema.clone())?;
                     // sort combined record batch
                     let result = combined
-                        .map(|batch| sort_batch(batch, schema, &expr))
+                        .map(|batch| sort_batch(batch, schema, &expr, None))
                         .transpose()?
                         .record_output(&baseline_metrics);
                     timer.done();
@@ -272,6 +332,42 @@ impl SortStream {
     }
 }
 
+/// Sorts `input` keeping about `2 * fetch` rows in memory: the input
+/// batches are buffered until they hold more than `2 * fetch` rows, which are
+/// then sorted and truncated to their first `fetch` rows, so that each input
+/// row is only sorted a few times whatever `fetch`
+async fn sort_top_k(
+    mut input: SendableRecordBatchStream,
+    expr: &[PhysicalSortExpr],
+    fetch: usize,
+    baseline_metrics: &BaselineMetrics,
+) -> ArrowResult<Option<RecordBatch>> {
+    let schema = input.schema();
+    let top_k = |batches: &[RecordBatch]| {
+        common::combine_batches(batches, schema.clone())?
+            .map(|batch| sort_batch(batch, schema.clone(), expr, Some(fetch)))
+            .transpose()
+    };
+
+    let mut buffered: Vec<RecordBatch> = vec![];
+    let mut buffered_rows = 0;
+    while let Some(batch) = input.next().await {
+        let batch = batch?;
+        buffered_rows += batch.num_rows();
+        buffered.push(batch);
+        if buffered_rows > fetch.saturating_mul(2) {
+            let timer = baseline_metrics.elapsed_compute().timer();
+            buffered = top_k(&buffered)?.into_iter().collect();
+            buffered_rows = buffered.iter().map(|batch| batch.num_rows()).sum();
+            timer.done();
+        }
+    }
+    let timer = baseline_metrics.elapsed_compute().timer();
+    let result = top_k(&buffered)?;
+    timer.done();
+    Ok(result.record_output(baseline_metrics))
+}
+
 impl Stream for SortStream {
     type Item = ArrowResult<RecordBatch>;
 
@@ -345,6 +441,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             true,
             b',',
@@ -546,6 +643,101 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_sort_fetch() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let batches = vec![vec![5, 8, 1], vec![9, 3], vec![7, 2, 6]]
+            .into_iter()
+            .map(|values| {
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(Int32Array::from(values))],
+                )
+            })
+            .collect::<ArrowResult<Vec<_>>>()?;
+
+        let sort_exec = Arc::new(
+            SortExec::try_new(
+                vec![PhysicalSortExpr {
+                    expr: col("a", &schema)?,
+                    options: SortOptions {
+                        descending: true,
+                        nulls_first: true,
+                    },
+                }],
+                Arc::new(MemoryExec::try_new(&[batches], schema.clone(), None)?),
+            )?
+            .with_fetch(Some(4)),
+        );
+
+        let result: Vec<RecordBatch> = collect(sort_exec.clone()).await?;
+        let expected = vec![
+            "+---+", "| a |", "+---+", "| 9 |", "| 8 |", "| 7 |", "| 6 |", "+---+",
+        ];
+        crate::assert_batches_eq!(expected, &result);
+
+        let metrics = sort_exec.metrics().unwrap();
+        assert_eq!(metrics.output_rows().unwrap(), 4);
+
+        let plan: Arc<dyn ExecutionPlan> = sort_exec;
+        assert_eq!(
+            crate::physical_plan::displayable(plan.as_ref())
+                .indent()
+                .to_string()
+                .lines()
+                .next(),
+            Some("SortExec: [a@0 DESC], fetch=4")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sort_large_fetch() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        // 100 batches of 1000 distinct values in an arbitrary order
+        let values: Vec<i32> = (0..100_000).map(|i| (i * 7919) % 100_000).collect();
+        let batches = values
+            .chunks(1000)
+            .map(|values| {
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(Int32Array::from(values.to_vec()))],
+                )
+            })
+            .collect::<ArrowResult<Vec<_>>>()?;
+
+        for fetch in [5000, 99_999, 200_000] {
+            let sort_exec = Arc::new(
+                SortExec::try_new(
+                    vec![PhysicalSortExpr {
+                        expr: col("a", &schema)?,
+                        options: SortOptions::default(),
+                    }],
+                    Arc::new(MemoryExec::try_new(
+                        &[batches.clone()],
+                        schema.clone(),
+                        None,
+                    )?),
+                )?
+                .with_fetch(Some(fetch)),
+            );
+
+            let result: Vec<RecordBatch> = collect(sort_exec).await?;
+            let result = result
+                .iter()
+                .flat_map(|batch| {
+                    let column = batch.column(0);
+                    let column = column.as_any().downcast_ref::<Int32Array>().unwrap();
+                    column.values().to_vec()
+                })
+                .collect::<Vec<_>>();
+            let expected = (0..fetch.min(100_000) as i32).collect::<Vec<_>>();
+            assert_eq!(result, expected);
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_drop_cancel() -> Result<()> {
         let schema =
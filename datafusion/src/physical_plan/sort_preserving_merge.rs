@@ -42,9 +42,9 @@ use hashbrown::HashMap;
 
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
-    common::spawn_execution, expressions::PhysicalSortExpr, DisplayFormatType,
-    Distribution, ExecutionPlan, Partitioning, PhysicalExpr, RecordBatchStream,
-    SendableRecordBatchStream, Statistics,
+    common::spawn_execution, expressions::PhysicalSortExpr, limit::LimitStream,
+    DisplayFormatType, Distribution, ExecutionPlan, Partitioning, PhysicalExpr,
+    RecordBatchStream, SendableRecordBatchStream, Statistics,
 };
 
 /// Sort preserving merge execution plan
@@ -63,6 +63,8 @@ pub struct SortPreservingMergeExec {
     target_batch_size: usize,
     /// Execution metrics
     metrics: ExecutionPlanMetricsSet,
+    /// Stop merging after the first `fetch` rows
+    fetch: Option<usize>,
 }
 
 impl SortPreservingMergeExec {
@@ -77,9 +79,16 @@ impl SortPreservingMergeExec {
             expr,
             target_batch_size,
             metrics: ExecutionPlanMetricsSet::new(),
+            fetch: None,
         }
     }
 
+    /// Only produce the first `fetch` rows of the merged partitions
+    pub fn with_fetch(mut self, fetch: Option<usize>) -> Self {
+        self.fetch = fetch;
+        self
+    }
+
     /// Input schema
     pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
         &self.input
@@ -89,6 +98,11 @@ impl SortPreservingMergeExec {
     pub fn expr(&self) -> &[PhysicalSortExpr] {
         &self.expr
     }
+
+    /// The maximum number of rows produced, if any
+    pub fn fetch(&self) -> Option<usize> {
+        self.fetch
+    }
 }
 
 #[async_trait]
@@ -111,6 +125,14 @@ impl ExecutionPlan for SortPreservingMergeExec {
         Distribution::UnspecifiedDistribution
     }
 
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        Some(self.expr.clone())
+    }
+
+    fn required_input_ordering(&self) -> Vec<Option<Vec<PhysicalSortExpr>>> {
+        vec![Some(self.expr.clone())]
+    }
+
     fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
         vec![self.input.clone()]
     }
@@ -120,11 +142,14 @@ impl ExecutionPlan for SortPreservingMergeExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            1 => Ok(Arc::new(SortPreservingMergeExec::new(
-                self.expr.clone(),
-                children[0].clone(),
-                self.target_batch_size,
-            ))),
+            1 => Ok(Arc::new(
+                SortPreservingMergeExec::new(
+                    self.expr.clone(),
+                    children[0].clone(),
+                    self.target_batch_size,
+                )
+                .with_fetch(self.fetch),
+            )),
             _ => Err(DataFusionError::Internal(
                 "SortPreservingMergeExec wrong number of children".to_string(),
             )),
@@ -147,10 +172,15 @@ impl ExecutionPlan for SortPreservingMergeExec {
                 "SortPreservingMergeExec requires at least one input partition"
                     .to_owned(),
             )),
-            1 => {
+            1 => match self.fetch {
+                Some(fetch) => Ok(Box::pin(LimitStream::new(
+                    self.input.execute(0).await?,
+                    fetch,
+                    baseline_metrics,
+                ))),
                 // bypass if there is only one partition to merge (no metrics in this case either)
-                self.input.execute(0).await
-            }
+                None => self.input.execute(0).await,
+            },
             _ => {
                 let (receivers, join_handles) = (0..input_partitions)
                     .into_iter()
@@ -168,6 +198,7 @@ impl ExecutionPlan for SortPreservingMergeExec {
                     self.schema(),
                     &self.expr,
                     self.target_batch_size,
+                    self.fetch,
                     baseline_metrics,
                 )))
             }
@@ -182,7 +213,11 @@ impl ExecutionPlan for SortPreservingMergeExec {
         match t {
             DisplayFormatType::Default => {
                 let expr: Vec<String> = self.expr.iter().map(|e| e.to_string()).collect();
-                write!(f, "SortPreservingMergeExec: [{}]", expr.join(","))
+                write!(f, "SortPreservingMergeExec: [{}]", expr.join(","))?;
+                if let Some(fetch) = self.fetch {
+                    write!(f, ", fetch={}", fetch)?;
+                }
+                Ok(())
             }
         }
     }
@@ -192,7 +227,19 @@ impl ExecutionPlan for SortPreservingMergeExec {
     }
 
     fn statistics(&self) -> Statistics {
-        self.input.statistics()
+        let input_stats = self.input.statistics();
+        match (self.fetch, input_stats.num_rows) {
+            // if the input is greater than the fetch, the num_row will be the
+            // fetch but we won't be able to predict the other statistics
+            (Some(fetch), Some(nr)) if nr > fetch => Statistics {
+                num_rows: Some(fetch),
+                is_exact: input_stats.is_exact,
+                ..Default::default()
+            },
+            // if we don't know the input size, we can't predict the fetch's behaviour
+            (Some(_), None) => Statistics::default(),
+            _ => input_stats,
+        }
     }
 }
 
@@ -366,6 +413,12 @@ struct SortPreservingMergeStream {
     /// The desired RecordBatch size to yield
     target_batch_size: usize,
 
+    /// The maximum number of rows to yield, if any
+    fetch: Option<usize>,
+
+    /// The number of rows yielded so far
+    produced: usize,
+
     /// used to record execution metrics
     baseline_metrics: BaselineMetrics,
 
@@ -383,6 +436,7 @@ impl SortPreservingMergeStream {
         schema: SchemaRef,
         expressions: &[PhysicalSortExpr],
         target_batch_size: usize,
+        fetch: Option<usize>,
         baseline_metrics: BaselineMetrics,
     ) -> Self {
         let cursors = (0..receivers.len())
@@ -398,6 +452,8 @@ impl SortPreservingMergeStream {
             column_expressions: expressions.iter().map(|x| x.expr.clone()).collect(),
             sort_options: expressions.iter().map(|x| x.options).collect(),
             target_batch_size,
+            fetch,
+            produced: 0,
             baseline_metrics,
             aborted: false,
             in_progress: vec![],
@@ -546,6 +602,7 @@ impl SortPreservingMergeStream {
             })
             .collect();
 
+        self.produced += self.in_progress.len();
         self.in_progress.clear();
 
         // New cursors are only created once the previous cursor for the stream
@@ -580,12 +637,19 @@ impl Stream for SortPreservingMergeStream {
 }
 
 impl SortPreservingMergeStream {
+    /// Whether the rows yielded and in progress reach the `fetch` limit
+    fn fetch_reached(&self) -> bool {
+        self.fetch.map_or(false, |fetch| {
+            self.produced + self.in_progress.len() >= fetch
+        })
+    }
+
     #[inline]
     fn poll_next_inner(
         self: &mut Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<ArrowResult<RecordBatch>>> {
-        if self.aborted {
+        if self.aborted || self.fetch_reached() {
             return Poll::Ready(None);
         }
 
@@ -631,7 +695,7 @@ impl SortPreservingMergeStream {
                 row_idx,
             });
 
-            if self.in_progress.len() == self.target_batch_size {
+            if self.in_progress.len() == self.target_batch_size || self.fetch_reached() {
                 return Poll::Ready(Some(self.build_record_batch()));
             }
 
@@ -945,6 +1009,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             true,
             b',',
@@ -1028,6 +1093,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             true,
             b',',
@@ -1113,6 +1179,59 @@ mod tests {
         assert_eq!(basic, partition);
     }
 
+    #[tokio::test]
+    async fn test_partition_sort_fetch() {
+        let schema = test_util::aggr_test_schema();
+
+        let sort = vec![PhysicalSortExpr {
+            expr: col("c12", &schema).unwrap(),
+            options: Default::default(),
+        }];
+
+        let input = sorted_partitioned_input(sort.clone(), &[10, 5, 13]).await;
+        let basic = basic_sort(input.clone(), sort.clone()).await;
+
+        let merge =
+            Arc::new(SortPreservingMergeExec::new(sort, input, 23).with_fetch(Some(50)));
+        let merged = collect(merge.clone()).await.unwrap();
+
+        // the merge stops once the first 50 rows are produced
+        assert_eq!(
+            merged.iter().map(|x| x.num_rows()).collect::<Vec<_>>(),
+            vec![23, 23, 4]
+        );
+        assert_eq!(merge.metrics().unwrap().output_rows(), Some(50));
+
+        let basic =
+            arrow::util::pretty::pretty_format_batches(&[basic.slice(0, 50)]).unwrap();
+        let partition =
+            arrow::util::pretty::pretty_format_batches(merged.as_slice()).unwrap();
+
+        assert_eq!(basic, partition);
+    }
+
+    #[tokio::test]
+    async fn test_single_partition_fetch() {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5]));
+        let batch = RecordBatch::try_from_iter(vec![("a", a)]).unwrap();
+        let schema = batch.schema();
+
+        let sort = vec![PhysicalSortExpr {
+            expr: col("a", &schema).unwrap(),
+            options: Default::default(),
+        }];
+        let exec = MemoryExec::try_new(&[vec![batch]], schema, None).unwrap();
+        let merge = Arc::new(
+            SortPreservingMergeExec::new(sort, Arc::new(exec), 1024).with_fetch(Some(2)),
+        );
+
+        let collected = collect(merge).await.unwrap();
+        assert_batches_eq!(
+            &["+---+", "| a |", "+---+", "| 1 |", "| 2 |", "+---+"],
+            collected.as_slice()
+        );
+    }
+
     #[tokio::test]
     async fn test_nulls() {
         let a: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 7, 9, 3]));
@@ -1231,6 +1350,7 @@ mod tests {
             batches.schema(),
             sort.as_slice(),
             1024,
+            None,
             baseline_metrics,
         );
 
@@ -36,7 +36,7 @@ use arrow::datatypes::{DataType, Schema, TimeUnit};
 use super::{functions::Signature, PhysicalExpr};
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::expressions::try_cast;
-use crate::physical_plan::functions::TypeSignature;
+use crate::physical_plan::functions::{TypeSignature, TIMEZONE_WILDCARD};
 
 /// Returns `expressions` coerced to types compatible with
 /// `signature`, if possible.
@@ -93,8 +93,10 @@ pub fn data_types(
 
     // none possible -> Error
     Err(DataFusionError::Plan(format!(
-        "Coercion from {:?} to the signature {:?} failed.",
-        current_types, &signature.type_signature
+        "Coercion from {:?} to the signature {:?} failed. Accepted signatures: {}",
+        current_types,
+        &signature.type_signature,
+        signature.accepted_signatures().join(", ")
     )))
 }
 
@@ -118,6 +120,7 @@ fn get_valid_types(
                 .map(|_| current_types[0].clone())
                 .collect()]
         }
+        TypeSignature::VariadicAny => vec![current_types.to_vec()],
         TypeSignature::Exact(valid_types) => vec![valid_types.clone()],
         TypeSignature::Any(number) => {
             if current_types.len() != *number {
@@ -154,6 +157,9 @@ fn maybe_data_types(
 
         if current_type == valid_type {
             new_type.push(current_type.clone())
+        } else if let Some(data_type) = match_time_zone_wildcard(valid_type, current_type)
+        {
+            new_type.push(data_type)
         } else {
             // attempt to coerce
             if can_coerce_from(valid_type, current_type) {
@@ -167,6 +173,22 @@ fn maybe_data_types(
     Some(new_type)
 }
 
+/// Timestamps in any time zone match a signature type with the
+/// [`TIMEZONE_WILDCARD`] time zone and keep their own time zone
+fn match_time_zone_wildcard(
+    valid_type: &DataType,
+    current_type: &DataType,
+) -> Option<DataType> {
+    match (valid_type, current_type) {
+        (DataType::Timestamp(unit, Some(wildcard)), DataType::Timestamp(_, Some(tz)))
+            if wildcard == TIMEZONE_WILDCARD =>
+        {
+            Some(DataType::Timestamp(unit.clone(), Some(tz.clone())))
+        }
+        _ => None,
+    }
+}
+
 /// Return true if a value of type `type_from` can be coerced
 /// (losslessly converted) into a value of `type_to`
 ///
@@ -212,7 +234,7 @@ mod tests {
     use super::*;
     use crate::physical_plan::{
         expressions::col,
-        functions::{TypeSignature, Volatility},
+        functions::{Parameter, TypeSignature, Volatility},
     };
     use arrow::datatypes::{DataType, Field, Schema};
 
@@ -246,6 +268,30 @@ mod tests {
                 vec![DataType::Boolean, DataType::UInt16],
                 Some(vec![DataType::Boolean, DataType::UInt32]),
             ),
+            // any time zone matches the wildcard, keeping the time zone
+            (
+                vec![DataType::Timestamp(
+                    TimeUnit::Nanosecond,
+                    Some(TIMEZONE_WILDCARD.to_owned()),
+                )],
+                vec![DataType::Timestamp(
+                    TimeUnit::Second,
+                    Some("Europe/Berlin".to_owned()),
+                )],
+                Some(vec![DataType::Timestamp(
+                    TimeUnit::Nanosecond,
+                    Some("Europe/Berlin".to_owned()),
+                )]),
+            ),
+            // but naive timestamps do not
+            (
+                vec![DataType::Timestamp(
+                    TimeUnit::Nanosecond,
+                    Some(TIMEZONE_WILDCARD.to_owned()),
+                )],
+                vec![DataType::Timestamp(TimeUnit::Nanosecond, None)],
+                None,
+            ),
         ];
 
         for case in cases {
@@ -333,6 +379,12 @@ mod tests {
                 Signature::any(1, Volatility::Immutable),
                 vec![DataType::Float32],
             )?,
+            // any types are kept
+            case(
+                vec![DataType::UInt32, DataType::Boolean],
+                Signature::variadic_any(Volatility::Immutable),
+                vec![DataType::UInt32, DataType::Boolean],
+            )?,
         ];
 
         for case in cases {
@@ -404,4 +456,20 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn coercion_error_lists_accepted_signatures() {
+        let signature = Signature::one_of(
+            vec![
+                TypeSignature::Exact(vec![DataType::Utf8, DataType::Int64]),
+                TypeSignature::Variadic(vec![DataType::Float64]),
+            ],
+            Volatility::Immutable,
+        )
+        .with_parameters(vec![Parameter::new("a"), Parameter::new("b")]);
+        let err = data_types(&[DataType::Boolean], &signature).unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with("Accepted signatures: (a Utf8, b Int64), (a Float64, ..)"));
+    }
 }
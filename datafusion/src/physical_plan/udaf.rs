@@ -106,6 +106,9 @@ impl AggregateUDF {
         Expr::AggregateUDF {
             fun: Arc::new(self.clone()),
             args,
+            distinct: false,
+            filter: None,
+            order_by: vec![],
         }
     }
 }
@@ -143,6 +146,13 @@ pub struct AggregateFunctionExpr {
     name: String,
 }
 
+impl AggregateFunctionExpr {
+    /// The UDAF this expression calls
+    pub fn fun(&self) -> &AggregateUDF {
+        &self.fun
+    }
+}
+
 impl AggregateExpr for AggregateFunctionExpr {
     /// Return a reference to Any that can be used for downcasting
     fn as_any(&self) -> &dyn Any {
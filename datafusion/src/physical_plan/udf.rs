@@ -22,10 +22,11 @@ use std::fmt;
 
 use arrow::datatypes::Schema;
 
-use crate::error::Result;
+use crate::error::{DataFusionError, Result};
 use crate::{logical_plan::Expr, physical_plan::PhysicalExpr};
 
 use super::{
+    async_udf::AsyncScalarFunction,
     functions::{
         ReturnTypeFunction, ScalarFunctionExpr, ScalarFunctionImplementation, Signature,
     },
@@ -51,6 +52,9 @@ pub struct ScalarUDF {
     /// the batch's row count (so that the generative zero-argument function can know
     /// the result array size).
     pub fun: ScalarFunctionImplementation,
+    /// async implementation, which is used instead of `fun` when the UDF is
+    /// called in a projection or a filter, see [`ScalarUDF::new_async`]
+    pub async_fun: Option<AsyncScalarFunction>,
 }
 
 impl Debug for ScalarUDF {
@@ -93,6 +97,28 @@ impl ScalarUDF {
             signature: signature.clone(),
             return_type: return_type.clone(),
             fun: fun.clone(),
+            async_fun: None,
+        }
+    }
+
+    /// Create a new ScalarUDF whose implementation is async. Such a UDF can only
+    /// be called in projections and filters, where its calls are evaluated by an
+    /// [`AsyncFuncExec`](super::async_udf::AsyncFuncExec).
+    pub fn new_async(
+        name: &str,
+        signature: &Signature,
+        return_type: &ReturnTypeFunction,
+        fun: AsyncScalarFunction,
+    ) -> Self {
+        let message = format!(
+            "Async UDF {} can only be called in projections and filters",
+            name
+        );
+        let sync_fun: ScalarFunctionImplementation =
+            Arc::new(move |_| Err(DataFusionError::NotImplemented(message.clone())));
+        Self {
+            async_fun: Some(fun),
+            ..Self::new(name, signature, return_type, &sync_fun)
         }
     }
 
@@ -0,0 +1,304 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! This module contains functions and structs supporting user-defined window functions.
+
+use fmt::{Debug, Formatter};
+use std::any::Any;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use arrow::array::{new_empty_array, ArrayRef};
+use arrow::compute::concat;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::window_frames::WindowFrame;
+use crate::logical_plan::Expr;
+
+use super::{
+    expressions::PhysicalSortExpr,
+    functions::{ReturnTypeFunction, Signature},
+    type_coercion::coerce,
+    window_functions::WindowFunction,
+    windows::{find_ranges_in_range, frame_rows},
+    PhysicalExpr, WindowExpr,
+};
+
+/// The rows of a window partition that a window function is evaluated over,
+/// sorted by the ORDER BY of the window.
+#[derive(Debug)]
+pub struct WindowPartition<'a> {
+    values: &'a [ArrayRef],
+    peers: &'a [Range<usize>],
+    order_by: &'a [PhysicalSortExpr],
+    window_frame: Option<WindowFrame>,
+}
+
+impl<'a> WindowPartition<'a> {
+    /// Create a new partition with the values of the arguments of the function for
+    /// its rows and the ranges of its peer groups
+    pub fn new(
+        values: &'a [ArrayRef],
+        peers: &'a [Range<usize>],
+        order_by: &'a [PhysicalSortExpr],
+        window_frame: Option<WindowFrame>,
+    ) -> Self {
+        Self {
+            values,
+            peers,
+            order_by,
+            window_frame,
+        }
+    }
+
+    /// the number of rows of the partition
+    pub fn num_rows(&self) -> usize {
+        self.peers.last().map_or(0, |peers| peers.end)
+    }
+
+    /// the values of the arguments of the function for the rows of the partition
+    pub fn values(&self) -> &[ArrayRef] {
+        self.values
+    }
+
+    /// the ranges of the rows of the partition that are peers, i.e. that have the
+    /// same values for the ORDER BY of the window, in order
+    pub fn peers(&self) -> &[Range<usize>] {
+        self.peers
+    }
+
+    /// the sort expressions of the ORDER BY of the window
+    pub fn order_by(&self) -> &[PhysicalSortExpr] {
+        self.order_by
+    }
+
+    /// the frame of the window, if it has one
+    pub fn window_frame(&self) -> Option<&WindowFrame> {
+        self.window_frame.as_ref()
+    }
+
+    /// the rows of the frame of `row`, according to the frame of the window or the
+    /// default frame if it has none
+    pub fn frame(&self, row: usize) -> Result<Range<usize>> {
+        frame_rows(
+            &self.window_frame.unwrap_or_default(),
+            &(0..self.num_rows()),
+            self.peers,
+            row,
+        )
+    }
+}
+
+/// Evaluates a window function over the partitions of its input, one at a time.
+pub trait PartitionEvaluator: Send + Debug {
+    /// Returns the values of the function for the rows of the partition, as an
+    /// array with one value per row
+    fn evaluate(&mut self, partition: &WindowPartition) -> Result<ArrayRef>;
+}
+
+/// Creates the evaluator of a window function
+pub type PartitionEvaluatorFactory =
+    Arc<dyn Fn() -> Result<Box<dyn PartitionEvaluator>> + Send + Sync>;
+
+/// Logical representation of a user-defined window function (UDWF)
+/// A UDWF is evaluated over whole window partitions, rather than row by row.
+#[derive(Clone)]
+pub struct WindowUDF {
+    /// name
+    pub name: String,
+    /// signature
+    pub signature: Signature,
+    /// Return type
+    pub return_type: ReturnTypeFunction,
+    /// actual implementation
+    pub partition_evaluator: PartitionEvaluatorFactory,
+}
+
+impl Debug for WindowUDF {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowUDF")
+            .field("name", &self.name)
+            .field("signature", &self.signature)
+            .field("fun", &"<FUNC>")
+            .finish()
+    }
+}
+
+impl PartialEq for WindowUDF {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.signature == other.signature
+    }
+}
+
+impl Eq for WindowUDF {}
+
+impl PartialOrd for WindowUDF {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        let c = self.name.partial_cmp(&other.name);
+        if matches!(c, Some(std::cmp::Ordering::Equal)) {
+            self.signature.partial_cmp(&other.signature)
+        } else {
+            c
+        }
+    }
+}
+
+impl WindowUDF {
+    /// Create a new WindowUDF
+    pub fn new(
+        name: &str,
+        signature: &Signature,
+        return_type: &ReturnTypeFunction,
+        partition_evaluator: &PartitionEvaluatorFactory,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            signature: signature.clone(),
+            return_type: return_type.clone(),
+            partition_evaluator: partition_evaluator.clone(),
+        }
+    }
+
+    /// creates a logical expression with a call of the UDWF over a window without
+    /// partitioning, ordering or frame
+    /// This utility allows using the UDWF without requiring access to the registry.
+    pub fn call(&self, args: Vec<Expr>) -> Expr {
+        Expr::WindowFunction {
+            fun: WindowFunction::WindowUDF(Arc::new(self.clone())),
+            args,
+            partition_by: vec![],
+            order_by: vec![],
+            window_frame: None,
+        }
+    }
+}
+
+/// Creates a physical expression of the UDWF, that includes all necessary type coercion.
+/// This function errors when `args`' can't be coerced to a valid argument type of the UDWF.
+pub fn create_window_expr(
+    fun: &Arc<WindowUDF>,
+    name: impl Into<String>,
+    args: &[Arc<dyn PhysicalExpr>],
+    partition_by: &[Arc<dyn PhysicalExpr>],
+    order_by: &[PhysicalSortExpr],
+    window_frame: Option<WindowFrame>,
+    input_schema: &Schema,
+) -> Result<Arc<dyn WindowExpr>> {
+    // coerce
+    let coerced_args = coerce(args, input_schema, &fun.signature)?;
+
+    let coerced_args_types = coerced_args
+        .iter()
+        .map(|arg| arg.data_type(input_schema))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Arc::new(WindowUDFExpr {
+        fun: fun.clone(),
+        args: coerced_args,
+        data_type: (fun.return_type)(&coerced_args_types)?.as_ref().clone(),
+        name: name.into(),
+        partition_by: partition_by.to_vec(),
+        order_by: order_by.to_vec(),
+        window_frame,
+    }))
+}
+
+/// Physical window expression of a UDWF.
+#[derive(Debug)]
+pub struct WindowUDFExpr {
+    fun: Arc<WindowUDF>,
+    args: Vec<Arc<dyn PhysicalExpr>>,
+    data_type: DataType,
+    name: String,
+    partition_by: Vec<Arc<dyn PhysicalExpr>>,
+    order_by: Vec<PhysicalSortExpr>,
+    window_frame: Option<WindowFrame>,
+}
+
+impl WindowExpr for WindowUDFExpr {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, self.data_type.clone(), true))
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        self.args.clone()
+    }
+
+    fn partition_by(&self) -> &[Arc<dyn PhysicalExpr>] {
+        &self.partition_by
+    }
+
+    fn order_by(&self) -> &[PhysicalSortExpr] {
+        &self.order_by
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let num_rows = batch.num_rows();
+        if num_rows == 0 {
+            return Ok(new_empty_array(&self.data_type));
+        }
+        let partition_points =
+            self.evaluate_partition_points(num_rows, &self.partition_columns(batch)?)?;
+        let sort_partition_points =
+            self.evaluate_partition_points(num_rows, &self.sort_columns(batch)?)?;
+        let values = self.evaluate_args(batch)?;
+        let mut evaluator = (self.fun.partition_evaluator)()?;
+        let results = partition_points
+            .iter()
+            .map(|partition_range| {
+                let start = partition_range.start;
+                let len = partition_range.end - start;
+                let values = values
+                    .iter()
+                    .map(|v| v.slice(start, len))
+                    .collect::<Vec<_>>();
+                let peers = find_ranges_in_range(partition_range, &sort_partition_points)
+                    .iter()
+                    .map(|peers| peers.start - start..peers.end - start)
+                    .collect::<Vec<_>>();
+                let partition =
+                    WindowPartition::new(&values, &peers, &self.order_by, self.window_frame);
+                let result = evaluator.evaluate(&partition)?;
+                if result.len() != len || result.data_type() != &self.data_type {
+                    return Err(DataFusionError::Execution(format!(
+                        "Window function {} returned {} values of type {:?} for a partition of {} rows, expected {:?}",
+                        self.fun.name,
+                        result.len(),
+                        result.data_type(),
+                        len,
+                        self.data_type
+                    )));
+                }
+                Ok(result)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let results = results.iter().map(|i| i.as_ref()).collect::<Vec<_>>();
+        concat(&results).map_err(DataFusionError::ArrowError)
+    }
+}
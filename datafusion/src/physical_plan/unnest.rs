@@ -0,0 +1,368 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! UnnestExec repeats every input row once per element of a list expression,
+//! appending the element as a new column
+
+use std::any::Any;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::array::{
+    Array, ArrayRef, FixedSizeListArray, GenericListArray, OffsetSizeTrait, UInt32Array,
+    UInt64Array,
+};
+use arrow::compute::take;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use futures::stream::{Stream, StreamExt};
+
+use super::{RecordBatchStream, SendableRecordBatchStream, Statistics};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::expressions::PhysicalSortExpr;
+use crate::physical_plan::{
+    metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet},
+    DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
+};
+
+/// UnnestExec repeats every input row once per element of the list `expr`,
+/// appending the element as the last column. Rows whose list is null or empty
+/// produce no output.
+#[derive(Debug)]
+pub struct UnnestExec {
+    /// The list expression to unnest
+    expr: Arc<dyn PhysicalExpr>,
+    /// The input plan
+    input: Arc<dyn ExecutionPlan>,
+    /// The output schema: the input fields followed by the unnested field
+    schema: SchemaRef,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl UnnestExec {
+    /// Create an UnnestExec on an input, naming the unnested column `name`.
+    /// `expr` must evaluate to a `List`, a `LargeList` or a `FixedSizeList`.
+    pub fn try_new(
+        expr: Arc<dyn PhysicalExpr>,
+        name: &str,
+        input: Arc<dyn ExecutionPlan>,
+    ) -> Result<Self> {
+        let input_schema = input.schema();
+        let element_type = match expr.data_type(input_schema.as_ref())? {
+            DataType::List(field)
+            | DataType::LargeList(field)
+            | DataType::FixedSizeList(field, _) => field.data_type().clone(),
+            other => {
+                return Err(DataFusionError::Plan(format!(
+                    "UNNEST expects a list argument, not {:?}",
+                    other
+                )))
+            }
+        };
+        let mut fields = input_schema.fields().clone();
+        fields.push(Field::new(name, element_type, true));
+        Ok(Self {
+            expr,
+            input,
+            schema: Arc::new(Schema::new_with_metadata(
+                fields,
+                input_schema.metadata().clone(),
+            )),
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+
+    /// The list expression to unnest
+    pub fn expr(&self) -> &Arc<dyn PhysicalExpr> {
+        &self.expr
+    }
+
+    /// The input plan
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for UnnestExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    /// Get the output partitioning of this plan
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    /// The unnested rows of an input row are output together, in the order
+    /// of the input
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        self.input.output_ordering()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => {
+                let name = self.schema.field(self.schema.fields().len() - 1).name();
+                Ok(Arc::new(UnnestExec::try_new(
+                    self.expr.clone(),
+                    name,
+                    children[0].clone(),
+                )?))
+            }
+            _ => Err(DataFusionError::Internal(
+                "UnnestExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        let baseline_metrics = BaselineMetrics::new(&self.metrics, partition);
+
+        Ok(Box::pin(UnnestStream {
+            schema: self.schema.clone(),
+            expr: self.expr.clone(),
+            input: self.input.execute(partition).await?,
+            baseline_metrics,
+        }))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let name = self.schema.field(self.schema.fields().len() - 1).name();
+                write!(f, "UnnestExec: {} as {}", self.expr, name)
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    /// The output statistics of an unnest are unknown
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+/// Returns the indices of the rows of `list` to repeat, one per element,
+/// and the elements themselves. The elements of a row are the range of
+/// `values` returned by `range`.
+fn unnest_elements(
+    list: &dyn Array,
+    values: &ArrayRef,
+    range: impl Fn(usize) -> (usize, usize),
+) -> Result<(UInt32Array, ArrayRef)> {
+    let mut row_indices = vec![];
+    let mut value_indices = vec![];
+    for row in 0..list.len() {
+        if list.is_null(row) {
+            continue;
+        }
+        let (start, end) = range(row);
+        for index in start..end {
+            row_indices.push(row as u32);
+            value_indices.push(index as u64);
+        }
+    }
+    let values = take(values.as_ref(), &UInt64Array::from(value_indices), None)?;
+    Ok((UInt32Array::from(row_indices), values))
+}
+
+fn unnest_list<OffsetSize: OffsetSizeTrait>(
+    list: &GenericListArray<OffsetSize>,
+) -> Result<(UInt32Array, ArrayRef)> {
+    let offsets = list.value_offsets();
+    unnest_elements(list, &list.values(), |row| {
+        (
+            offsets[row].to_usize().unwrap(),
+            offsets[row + 1].to_usize().unwrap(),
+        )
+    })
+}
+
+fn unnest_fixed_size_list(list: &FixedSizeListArray) -> Result<(UInt32Array, ArrayRef)> {
+    let size = list.value_length() as usize;
+    unnest_elements(list, &list.values(), |row| {
+        let start = list.value_offset(row) as usize;
+        (start, start + size)
+    })
+}
+
+fn unnest_batch(
+    batch: &RecordBatch,
+    expr: &Arc<dyn PhysicalExpr>,
+    schema: &SchemaRef,
+) -> Result<RecordBatch> {
+    let array = expr.evaluate(batch)?.into_array(batch.num_rows());
+    let (row_indices, values) = match array.data_type() {
+        DataType::List(_) => unnest_list(
+            array
+                .as_any()
+                .downcast_ref::<GenericListArray<i32>>()
+                .unwrap(),
+        )?,
+        DataType::LargeList(_) => unnest_list(
+            array
+                .as_any()
+                .downcast_ref::<GenericListArray<i64>>()
+                .unwrap(),
+        )?,
+        DataType::FixedSizeList(_, _) => unnest_fixed_size_list(
+            array.as_any().downcast_ref::<FixedSizeListArray>().unwrap(),
+        )?,
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "UNNEST expression evaluated to non-list type {:?}",
+                other
+            )))
+        }
+    };
+    // a one row `EmptyExec` input carries a placeholder column that is not part
+    // of its schema, so only the columns the output schema declares are kept
+    let input_columns = schema.fields().len() - 1;
+    let mut columns = batch.columns()[..input_columns]
+        .iter()
+        .map(|column| take(column.as_ref(), &row_indices, None))
+        .collect::<ArrowResult<Vec<_>>>()?;
+    columns.push(values);
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+/// Stream of the unnested batches of one partition of the input
+struct UnnestStream {
+    /// Output schema
+    schema: SchemaRef,
+    /// The list expression to unnest
+    expr: Arc<dyn PhysicalExpr>,
+    /// The input partition to unnest
+    input: SendableRecordBatchStream,
+    /// runtime metrics recording
+    baseline_metrics: BaselineMetrics,
+}
+
+impl Stream for UnnestStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let poll = self.input.poll_next_unpin(cx).map(|x| match x {
+            Some(Ok(batch)) => {
+                let timer = self.baseline_metrics.elapsed_compute().timer();
+                let unnested = unnest_batch(&batch, &self.expr, &self.schema)
+                    .map_err(DataFusionError::into_arrow_external_error);
+                timer.done();
+                Some(unnested)
+            }
+            other => other,
+        });
+        self.baseline_metrics.record_poll(poll)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // same number of record batches
+        self.input.size_hint()
+    }
+}
+
+impl RecordBatchStream for UnnestStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::{collect, memory::MemoryExec};
+    use arrow::array::{Int32Array, ListArray, StringArray};
+    use arrow::datatypes::Int32Type;
+
+    #[tokio::test]
+    async fn unnest() -> Result<()> {
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+            Some(vec![]),
+            Some(vec![Some(3), None]),
+        ]);
+        let names = StringArray::from(vec!["a", "b", "c", "d"]);
+        let batch = RecordBatch::try_from_iter(vec![
+            ("name", Arc::new(names) as ArrayRef),
+            ("list", Arc::new(list) as ArrayRef),
+        ])?;
+        let schema = batch.schema();
+        let input = Arc::new(MemoryExec::try_new(&[vec![batch]], schema.clone(), None)?);
+
+        let unnest = UnnestExec::try_new(col("list", &schema)?, "element", input)?;
+        assert_eq!(unnest.schema().field(2).name(), "element");
+        assert_eq!(unnest.schema().field(2).data_type(), &DataType::Int32);
+
+        let batches = collect(Arc::new(unnest)).await?;
+        let expected = vec![
+            "+------+--------+---------+",
+            "| name | list   | element |",
+            "+------+--------+---------+",
+            "| a    | [1, 2] | 1       |",
+            "| a    | [1, 2] | 2       |",
+            "| d    | [3, ]  | 3       |",
+            "| d    | [3, ]  |         |",
+            "+------+--------+---------+",
+        ];
+        crate::assert_batches_eq!(expected, &batches);
+
+        let values = batches[0]
+            .column(2)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(values.null_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn unnest_non_list() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let input = Arc::new(MemoryExec::try_new(&[], schema.clone(), None)?);
+        assert!(UnnestExec::try_new(col("a", &schema)?, "a", input).is_err());
+        Ok(())
+    }
+}
@@ -24,7 +24,8 @@ use crate::error::{DataFusionError, Result};
 use crate::physical_plan::functions::{TypeSignature, Volatility};
 use crate::physical_plan::{
     aggregates, aggregates::AggregateFunction, functions::Signature,
-    type_coercion::data_types, windows::find_ranges_in_range, PhysicalExpr,
+    type_coercion::data_types, udwf::WindowUDF, windows::find_ranges_in_range,
+    PhysicalExpr,
 };
 use arrow::array::ArrayRef;
 use arrow::datatypes::DataType;
@@ -42,6 +43,8 @@ pub enum WindowFunction {
     AggregateFunction(AggregateFunction),
     /// window function that leverages a built-in window function
     BuiltInWindowFunction(BuiltInWindowFunction),
+    /// window function that is user-defined
+    WindowUDF(Arc<WindowUDF>),
 }
 
 impl FromStr for WindowFunction {
@@ -86,6 +89,7 @@ impl fmt::Display for WindowFunction {
         match self {
             WindowFunction::AggregateFunction(fun) => fun.fmt(f),
             WindowFunction::BuiltInWindowFunction(fun) => fun.fmt(f),
+            WindowFunction::WindowUDF(fun) => f.write_str(&fun.name),
         }
     }
 }
@@ -160,6 +164,9 @@ pub fn return_type(
         WindowFunction::BuiltInWindowFunction(fun) => {
             return_type_for_built_in(fun, input_expr_types)
         }
+        WindowFunction::WindowUDF(fun) => {
+            Ok((fun.return_type)(input_expr_types)?.as_ref().clone())
+        }
     }
 }
 
@@ -195,6 +202,7 @@ pub fn signature(fun: &WindowFunction) -> Signature {
     match fun {
         WindowFunction::AggregateFunction(fun) => aggregates::signature(fun),
         WindowFunction::BuiltInWindowFunction(fun) => signature_for_built_in(fun),
+        WindowFunction::WindowUDF(fun) => fun.signature.clone(),
     }
 }
 
@@ -218,9 +226,11 @@ pub(super) fn signature_for_built_in(fun: &BuiltInWindowFunction) -> Signature {
         BuiltInWindowFunction::FirstValue | BuiltInWindowFunction::LastValue => {
             Signature::any(1, Volatility::Immutable)
         }
-        BuiltInWindowFunction::Ntile => {
-            Signature::exact(vec![DataType::UInt64], Volatility::Immutable)
-        }
+        BuiltInWindowFunction::Ntile => Signature::uniform(
+            1,
+            vec![DataType::UInt64, DataType::Int64],
+            Volatility::Immutable,
+        ),
         BuiltInWindowFunction::NthValue => Signature::any(2, Volatility::Immutable),
     }
 }
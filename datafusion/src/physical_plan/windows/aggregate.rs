@@ -18,11 +18,15 @@
 //! Physical exec for aggregate window function expressions.
 
 use crate::error::{DataFusionError, Result};
-use crate::logical_plan::window_frames::{WindowFrame, WindowFrameUnits};
-use crate::physical_plan::windows::find_ranges_in_range;
+use crate::logical_plan::window_frames::{
+    WindowFrame, WindowFrameBound, WindowFrameUnits,
+};
+use crate::physical_plan::windows::{find_ranges_in_range, group_frame, row_frame};
 use crate::physical_plan::{
     expressions::PhysicalSortExpr, Accumulator, AggregateExpr, PhysicalExpr, WindowExpr,
 };
+use crate::scalar::ScalarValue;
+use arrow::array::new_empty_array;
 use arrow::compute::concat;
 use arrow::record_batch::RecordBatch;
 use arrow::{array::ArrayRef, datatypes::Field};
@@ -97,18 +101,97 @@ impl AggregateWindowExpr {
         concat(&results).map_err(DataFusionError::ArrowError)
     }
 
-    fn group_based_evaluate(&self, _batch: &RecordBatch) -> Result<ArrayRef> {
-        Err(DataFusionError::NotImplemented(format!(
-            "Group based evaluation for {} is not yet implemented",
-            self.name()
-        )))
+    /// group based evaluation, where the frame bounds count the peer groups before and after
+    /// the peer group of the current row.
+    fn group_based_evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let sort_partition_points =
+            self.evaluate_partition_points(batch.num_rows(), &self.sort_columns(batch)?)?;
+        self.frame_based_evaluate(batch, |partition_range| {
+            let groups = find_ranges_in_range(partition_range, &sort_partition_points);
+            let frame = self.window_frame.unwrap_or_default();
+            Ok(groups
+                .iter()
+                .enumerate()
+                .flat_map(|(index, group)| {
+                    let rows = group_frame(&frame, partition_range, groups, index);
+                    group.clone().map(move |_| rows.clone())
+                })
+                .collect())
+        })
+    }
+
+    /// row based evaluation, where the frame bounds count the rows before and after the
+    /// current row.
+    fn row_based_evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        self.frame_based_evaluate(batch, |partition_range| {
+            let frame = self.window_frame.unwrap_or_default();
+            Ok(partition_range
+                .clone()
+                .map(|row| row_frame(&frame, partition_range, row))
+                .collect())
+        })
+    }
+
+    /// evaluates the aggregate over the frame of every row, given the frames of the rows of
+    /// each partition. The frames start and end at non decreasing rows, so the accumulator
+    /// slides over them, retracting the rows leaving the frame if it supports it and starting
+    /// over otherwise.
+    fn frame_based_evaluate(
+        &self,
+        batch: &RecordBatch,
+        frames: impl Fn(&Range<usize>) -> Result<Vec<Range<usize>>>,
+    ) -> Result<ArrayRef> {
+        let num_rows = batch.num_rows();
+        if num_rows == 0 {
+            return Ok(new_empty_array(self.field()?.data_type()));
+        }
+        let partition_points =
+            self.evaluate_partition_points(num_rows, &self.partition_columns(batch)?)?;
+        let values = self.evaluate_args(batch)?;
+        let mut results = Vec::with_capacity(num_rows);
+        for partition_range in &partition_points {
+            let mut accumulator = self.create_accumulator()?;
+            let mut current = partition_range.start..partition_range.start;
+            for frame in frames(partition_range)? {
+                if !accumulator.can_slide(&current, &frame) {
+                    accumulator = self.create_accumulator()?;
+                    current = frame.start..frame.start;
+                }
+                accumulator.slide(&values, &current, &frame)?;
+                results.push(accumulator.accumulator.evaluate()?);
+                current = frame;
+            }
+        }
+        ScalarValue::iter_to_array(results)
     }
 
-    fn row_based_evaluate(&self, _batch: &RecordBatch) -> Result<ArrayRef> {
-        Err(DataFusionError::NotImplemented(format!(
-            "Row based evaluation for {} is not yet implemented",
-            self.name()
-        )))
+    /// range based evaluation, which falls back to the group based one for frames other than
+    /// the default cumulative frame, as the only offsets supported for ranges are 0.
+    fn range_based_evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        match self.window_frame {
+            None
+            | Some(WindowFrame {
+                start_bound: WindowFrameBound::Preceding(None),
+                end_bound: WindowFrameBound::CurrentRow,
+                ..
+            }) => self.peer_based_evaluate(batch),
+            Some(frame) => {
+                for bound in &[frame.start_bound, frame.end_bound] {
+                    if let WindowFrameBound::Preceding(Some(n))
+                    | WindowFrameBound::Following(Some(n)) = bound
+                    {
+                        if *n > 0 {
+                            return Err(DataFusionError::NotImplemented(format!(
+                                "Range based evaluation for {} with offset {} is not yet implemented",
+                                self.name(),
+                                n
+                            )));
+                        }
+                    }
+                }
+                self.group_based_evaluate(batch)
+            }
+        }
     }
 }
 
@@ -141,7 +224,7 @@ impl WindowExpr for AggregateWindowExpr {
     /// evaluate the window function values against the batch
     fn evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
         match self.evaluation_mode() {
-            WindowFrameUnits::Range => self.peer_based_evaluate(batch),
+            WindowFrameUnits::Range => self.range_based_evaluate(batch),
             WindowFrameUnits::Rows => self.row_based_evaluate(batch),
             WindowFrameUnits::Groups => self.group_based_evaluate(batch),
         }
@@ -177,4 +260,36 @@ impl AggregateWindowAccumulator {
         let value = self.accumulator.evaluate()?;
         Ok(value.to_array_of_size(len))
     }
+
+    /// whether the accumulated rows of the `current` frame can be moved to `frame`, which
+    /// neither starts nor ends before it, i.e. no rows leave the frame or they can be retracted.
+    fn can_slide(&self, current: &Range<usize>, frame: &Range<usize>) -> bool {
+        frame.start == current.start
+            || (frame.start <= current.end && self.accumulator.supports_retract_batch())
+    }
+
+    /// moves the accumulated rows of the `current` frame to `frame`, adding the rows entering
+    /// the frame and retracting the ones leaving it.
+    fn slide(
+        &mut self,
+        values: &[ArrayRef],
+        current: &Range<usize>,
+        frame: &Range<usize>,
+    ) -> Result<()> {
+        let slice = |range: Range<usize>| {
+            values
+                .iter()
+                .map(|v| v.slice(range.start, range.end - range.start))
+                .collect::<Vec<_>>()
+        };
+        if frame.end > current.end {
+            self.accumulator
+                .update_batch(&slice(current.end..frame.end))?;
+        }
+        if frame.start > current.start {
+            self.accumulator
+                .retract_batch(&slice(current.start..frame.start))?;
+        }
+        Ok(())
+    }
 }
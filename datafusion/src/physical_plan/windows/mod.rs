@@ -18,14 +18,17 @@
 //! Physical expressions for window functions
 
 use crate::error::{DataFusionError, Result};
-use crate::logical_plan::window_frames::WindowFrame;
+use crate::logical_plan::window_frames::{
+    WindowFrame, WindowFrameBound, WindowFrameUnits,
+};
 use crate::physical_plan::{
     aggregates,
     expressions::{
-        cume_dist, dense_rank, lag, lead, percent_rank, rank, Literal, NthValue,
+        cume_dist, dense_rank, lag, lead, ntile, percent_rank, rank, Literal, NthValue,
         PhysicalSortExpr, RowNumber,
     },
     type_coercion::coerce,
+    udwf,
     window_functions::{
         signature_for_built_in, BuiltInWindowFunction, BuiltInWindowFunctionExpr,
         WindowFunction,
@@ -63,11 +66,37 @@ pub fn create_window_expr(
             order_by,
             window_frame,
         )),
+        WindowFunction::BuiltInWindowFunction(_) if window_frame.is_some() => {
+            return Err(DataFusionError::NotImplemented(
+                "built-in window function with window frame definition is not yet supported"
+                    .to_owned(),
+            ))
+        }
+        WindowFunction::BuiltInWindowFunction(BuiltInWindowFunction::Ntile) => {
+            udwf::create_window_expr(
+                &Arc::new(ntile()),
+                name,
+                args,
+                partition_by,
+                order_by,
+                window_frame,
+                input_schema,
+            )?
+        }
         WindowFunction::BuiltInWindowFunction(fun) => Arc::new(BuiltInWindowExpr::new(
             create_built_in_window_expr(fun, args, input_schema, name)?,
             partition_by,
             order_by,
         )),
+        WindowFunction::WindowUDF(fun) => udwf::create_window_expr(
+            fun,
+            name,
+            args,
+            partition_by,
+            order_by,
+            window_frame,
+            input_schema,
+        )?,
     })
 }
 
@@ -170,6 +199,102 @@ pub(crate) fn find_ranges_in_range<'a>(
     &sort_partition_points[start_idx..end_idx]
 }
 
+/// the rows of the frame of `row` with ROWS frame units
+pub(crate) fn row_frame(
+    frame: &WindowFrame,
+    partition_range: &Range<usize>,
+    row: usize,
+) -> Range<usize> {
+    let start = match frame.start_bound {
+        WindowFrameBound::Preceding(None) => partition_range.start,
+        WindowFrameBound::Preceding(Some(n)) => {
+            row.saturating_sub(n as usize).max(partition_range.start)
+        }
+        WindowFrameBound::CurrentRow => row,
+        WindowFrameBound::Following(None) => partition_range.end,
+        WindowFrameBound::Following(Some(n)) => {
+            row.saturating_add(n as usize).min(partition_range.end)
+        }
+    };
+    let end = match frame.end_bound {
+        WindowFrameBound::Preceding(None) => partition_range.start,
+        WindowFrameBound::Preceding(Some(n)) => (row + 1)
+            .checked_sub(n as usize)
+            .map_or(partition_range.start, |end| end.max(partition_range.start)),
+        WindowFrameBound::CurrentRow => row + 1,
+        WindowFrameBound::Following(None) => partition_range.end,
+        WindowFrameBound::Following(Some(n)) => row
+            .saturating_add(n as usize)
+            .saturating_add(1)
+            .min(partition_range.end),
+    };
+    start..end.max(start)
+}
+
+/// the rows of the frame of the rows of the peer group `groups[index]` with GROUPS frame units
+pub(crate) fn group_frame(
+    frame: &WindowFrame,
+    partition_range: &Range<usize>,
+    groups: &[Range<usize>],
+    index: usize,
+) -> Range<usize> {
+    let start = match frame.start_bound {
+        WindowFrameBound::Preceding(None) => partition_range.start,
+        WindowFrameBound::Preceding(Some(n)) => {
+            groups[index.saturating_sub(n as usize)].start
+        }
+        WindowFrameBound::CurrentRow => groups[index].start,
+        WindowFrameBound::Following(n) => n
+            .and_then(|n| groups.get(index.saturating_add(n as usize)))
+            .map_or(partition_range.end, |group| group.start),
+    };
+    let end = match frame.end_bound {
+        WindowFrameBound::Preceding(None) => partition_range.start,
+        WindowFrameBound::Preceding(Some(n)) => index
+            .checked_sub(n as usize)
+            .map_or(partition_range.start, |index| groups[index].end),
+        WindowFrameBound::CurrentRow => groups[index].end,
+        WindowFrameBound::Following(None) => partition_range.end,
+        WindowFrameBound::Following(Some(n)) => {
+            groups[index.saturating_add(n as usize).min(groups.len() - 1)].end
+        }
+    };
+    start..end.max(start)
+}
+
+/// the rows of the frame of `row` of the partition, given the peer groups of the partition
+pub(crate) fn frame_rows(
+    frame: &WindowFrame,
+    partition_range: &Range<usize>,
+    peers: &[Range<usize>],
+    row: usize,
+) -> Result<Range<usize>> {
+    match frame.units {
+        WindowFrameUnits::Rows => Ok(row_frame(frame, partition_range, row)),
+        WindowFrameUnits::Range => {
+            for bound in &[frame.start_bound, frame.end_bound] {
+                if let WindowFrameBound::Preceding(Some(n))
+                | WindowFrameBound::Following(Some(n)) = bound
+                {
+                    if *n > 0 {
+                        return Err(DataFusionError::NotImplemented(format!(
+                            "Window frame with RANGE offset {} is not yet implemented",
+                            n
+                        )));
+                    }
+                }
+            }
+            // with offsets of 0, a range frame is made of the same peer groups as a groups frame
+            let index = peers.partition_point(|peer| peer.end <= row);
+            Ok(group_frame(frame, partition_range, peers, index))
+        }
+        WindowFrameUnits::Groups => {
+            let index = peers.partition_point(|peer| peer.end <= row);
+            Ok(group_frame(frame, partition_range, peers, index))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,6 +302,7 @@ mod tests {
     use crate::physical_plan::aggregates::AggregateFunction;
     use crate::physical_plan::expressions::col;
     use crate::physical_plan::file_format::{CsvExec, PhysicalPlanConfig};
+    use crate::physical_plan::memory::MemoryExec;
     use crate::physical_plan::{collect, Statistics};
     use crate::test::exec::{assert_strong_count_converges_to_zero, BlockingExec};
     use crate::test::{self, assert_is_pending};
@@ -200,6 +326,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                output_ordering: None,
             },
             true,
             b',',
@@ -269,6 +396,94 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn window_function_per_partition() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let batch = |values: Vec<i32>| {
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))])
+        };
+        // the partitions of the sorted input span several batches
+        let batches = vec![
+            batch(vec![1, 1])?,
+            batch(vec![2, 2])?,
+            batch(vec![2, 3])?,
+            batch(vec![3])?,
+        ];
+        let input = Arc::new(MemoryExec::try_new(&[batches], schema.clone(), None)?);
+        let window_exec = Arc::new(WindowAggExec::try_new(
+            vec![create_window_expr(
+                &WindowFunction::AggregateFunction(AggregateFunction::Count),
+                "count".to_owned(),
+                &[col("a", &schema)?],
+                &[col("a", &schema)?],
+                &[],
+                None,
+                schema.as_ref(),
+            )?],
+            input,
+            schema.clone(),
+        )?);
+
+        // every window partition is output as soon as it is complete
+        let result: Vec<RecordBatch> = collect(window_exec).await?;
+        let counts = result
+            .iter()
+            .map(|batch| {
+                let count: &UInt64Array = as_primitive_array(batch.column(0));
+                count.values().to_vec()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![vec![2, 2], vec![3, 3, 3], vec![2, 2]]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn window_function_mixed_partitions() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let batch = |values: Vec<i32>| {
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))])
+        };
+        let batches = vec![batch(vec![1, 1])?, batch(vec![2, 2])?, batch(vec![2, 3])?];
+        let input = Arc::new(MemoryExec::try_new(&[batches], schema.clone(), None)?);
+        // OVER (PARTITION BY a) and OVER (ORDER BY a) share the sort key [a] but
+        // not their partitions, so the input cannot be cut into partitions of a
+        let window_exec = Arc::new(WindowAggExec::try_new(
+            vec![
+                create_window_expr(
+                    &WindowFunction::AggregateFunction(AggregateFunction::Count),
+                    "count_partition".to_owned(),
+                    &[col("a", &schema)?],
+                    &[col("a", &schema)?],
+                    &[],
+                    None,
+                    schema.as_ref(),
+                )?,
+                create_window_expr(
+                    &WindowFunction::AggregateFunction(AggregateFunction::Count),
+                    "count_order".to_owned(),
+                    &[col("a", &schema)?],
+                    &[],
+                    &[PhysicalSortExpr {
+                        expr: col("a", &schema)?,
+                        options: Default::default(),
+                    }],
+                    None,
+                    schema.as_ref(),
+                )?,
+            ],
+            input,
+            schema.clone(),
+        )?);
+
+        let result: Vec<RecordBatch> = collect(window_exec).await?;
+        assert_eq!(result.len(), 1);
+        let count_partition: &UInt64Array = as_primitive_array(result[0].column(0));
+        assert_eq!(count_partition.values(), &[2, 2, 3, 3, 3, 1]);
+        let count_order: &UInt64Array = as_primitive_array(result[0].column(1));
+        assert_eq!(count_order.values(), &[2, 2, 5, 5, 5, 6]);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_drop_cancel() -> Result<()> {
         let schema =
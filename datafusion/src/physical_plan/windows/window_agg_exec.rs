@@ -18,24 +18,24 @@
 //! Stream and channel implementations for window function expressions.
 
 use crate::error::{DataFusionError, Result};
-use crate::physical_plan::common::AbortOnDropSingle;
 use crate::physical_plan::metrics::{
     BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet,
 };
 use crate::physical_plan::{
-    common, ColumnStatistics, DisplayFormatType, Distribution, ExecutionPlan,
-    Partitioning, RecordBatchStream, SendableRecordBatchStream, Statistics, WindowExpr,
+    common, expressions::PhysicalSortExpr, ColumnStatistics, DisplayFormatType,
+    Distribution, ExecutionPlan, Partitioning, PhysicalExpr, RecordBatchStream,
+    SendableRecordBatchStream, Statistics, WindowExpr,
 };
+use crate::scalar::ScalarValue;
 use arrow::{
     array::ArrayRef,
+    compute::{lexicographical_partition_ranges, SortColumn, SortOptions},
     datatypes::{Schema, SchemaRef},
-    error::{ArrowError, Result as ArrowResult},
+    error::Result as ArrowResult,
     record_batch::RecordBatch,
 };
 use async_trait::async_trait;
-use futures::stream::Stream;
-use futures::FutureExt;
-use pin_project_lite::pin_project;
+use futures::stream::{Stream, StreamExt};
 use std::any::Any;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -88,6 +88,32 @@ impl WindowAggExec {
     pub fn input_schema(&self) -> SchemaRef {
         self.input_schema.clone()
     }
+
+    /// The order in which the rows of each input partition must be sorted:
+    /// by the partition keys, ascending, and then by the order by keys. All
+    /// the window expressions share the same keys.
+    pub fn sort_keys(&self) -> Vec<PhysicalSortExpr> {
+        let mut sort_keys: Vec<PhysicalSortExpr> = vec![];
+        if let Some(window_expr) = self.window_expr.first() {
+            let partition_keys =
+                window_expr
+                    .partition_by()
+                    .iter()
+                    .map(|expr| PhysicalSortExpr {
+                        expr: expr.clone(),
+                        options: SortOptions {
+                            descending: false,
+                            nulls_first: true,
+                        },
+                    });
+            for sort_key in partition_keys.chain(window_expr.order_by().iter().cloned()) {
+                if !sort_keys.iter().any(|key| key.satisfies(&sort_key)) {
+                    sort_keys.push(sort_key);
+                }
+            }
+        }
+        sort_keys
+    }
 }
 
 #[async_trait]
@@ -113,6 +139,17 @@ impl ExecutionPlan for WindowAggExec {
         self.input.output_partitioning()
     }
 
+    /// Window functions append columns to the input rows without reordering
+    /// them
+    fn output_ordering(&self) -> Option<Vec<PhysicalSortExpr>> {
+        self.input.output_ordering()
+    }
+
+    fn required_input_ordering(&self) -> Vec<Option<Vec<PhysicalSortExpr>>> {
+        let sort_keys = self.sort_keys();
+        vec![(!sort_keys.is_empty()).then(|| sort_keys)]
+    }
+
     fn required_child_distribution(&self) -> Distribution {
         if self
             .window_expr()
@@ -219,16 +256,45 @@ fn compute_window_aggregates(
         .collect()
 }
 
-pin_project! {
-    /// stream for window aggregation plan
-    pub struct WindowAggStream {
-        schema: SchemaRef,
-        drop_helper: AbortOnDropSingle<()>,
-        #[pin]
-        output: futures::channel::oneshot::Receiver<ArrowResult<RecordBatch>>,
-        finished: bool,
-        baseline_metrics: BaselineMetrics,
-    }
+/// Returns the partition keys shared by all the window expressions. As they
+/// share the same sort key, which starts with their partition keys, these are
+/// the longest common prefix of their partition keys
+fn common_partition_keys(
+    window_expr: &[Arc<dyn WindowExpr>],
+) -> Vec<Arc<dyn PhysicalExpr>> {
+    let first = match window_expr.first() {
+        Some(first) => first.partition_by(),
+        None => return vec![],
+    };
+    first
+        .iter()
+        .enumerate()
+        .take_while(|(i, key)| {
+            window_expr.iter().all(|expr| {
+                expr.partition_by()
+                    .get(*i)
+                    .map_or(false, |other| other.to_string() == key.to_string())
+            })
+        })
+        .map(|(_, key)| key.clone())
+        .collect()
+}
+
+/// stream for window aggregation plan, that evaluates the window expressions over
+/// the window partitions of its input, sorted by the partition keys, as soon as
+/// they are complete, so that it only buffers the rows of the last partition.
+/// The chunks are cut on the partition keys common to all the window
+/// expressions, and the whole input is buffered if there are none.
+pub struct WindowAggStream {
+    schema: SchemaRef,
+    input: SendableRecordBatchStream,
+    window_expr: Vec<Arc<dyn WindowExpr>>,
+    /// the partition keys common to all the window expressions
+    partition_by: Vec<Arc<dyn PhysicalExpr>>,
+    /// the input rows of the window partition that is not complete yet
+    buffer: Vec<RecordBatch>,
+    finished: bool,
+    baseline_metrics: BaselineMetrics,
 }
 
 impl WindowAggStream {
@@ -239,55 +305,96 @@ impl WindowAggStream {
         input: SendableRecordBatchStream,
         baseline_metrics: BaselineMetrics,
     ) -> Self {
-        let (tx, rx) = futures::channel::oneshot::channel();
-        let schema_clone = schema.clone();
-        let elapsed_compute = baseline_metrics.elapsed_compute().clone();
-        let join_handle = tokio::spawn(async move {
-            let schema = schema_clone.clone();
-            let result =
-                WindowAggStream::process(input, window_expr, schema, elapsed_compute)
-                    .await;
-
-            // failing here is OK, the receiver is gone and does not care about the result
-            tx.send(result).ok();
-        });
-
+        let partition_by = common_partition_keys(&window_expr);
         Self {
             schema,
-            drop_helper: AbortOnDropSingle::new(join_handle),
-            output: rx,
+            input,
+            window_expr,
+            partition_by,
+            buffer: vec![],
             finished: false,
             baseline_metrics,
         }
     }
 
-    async fn process(
-        input: SendableRecordBatchStream,
-        window_expr: Vec<Arc<dyn WindowExpr>>,
-        schema: SchemaRef,
-        elapsed_compute: crate::physical_plan::metrics::Time,
-    ) -> ArrowResult<RecordBatch> {
-        let input_schema = input.schema();
-        let batches = common::collect(input)
-            .await
-            .map_err(DataFusionError::into_arrow_external_error)?;
-
-        // record compute time on drop
-        let _timer = elapsed_compute.timer();
-
-        let batch = common::combine_batches(&batches, input_schema.clone())?;
-        if let Some(batch) = batch {
-            // calculate window cols
-            let mut columns = compute_window_aggregates(window_expr, &batch)
-                .map_err(DataFusionError::into_arrow_external_error)?;
-            // combine with the original cols
-            // note the setup of window aggregates is that they newly calculated window
-            // expressions are always prepended to the columns
-            columns.extend_from_slice(batch.columns());
-            RecordBatch::try_new(schema, columns)
-        } else {
-            Ok(RecordBatch::new_empty(schema))
+    /// Buffers the rows of `batch` and returns the output of the window
+    /// partitions they complete, if any
+    fn push_batch(&mut self, batch: RecordBatch) -> Result<Option<RecordBatch>> {
+        let num_rows = batch.num_rows();
+        if num_rows == 0 {
+            return Ok(None);
+        }
+        if self.partition_by.is_empty() {
+            self.buffer.push(batch);
+            return Ok(None);
         }
+        let partition_points =
+            lexicographical_partition_ranges(&self.partition_columns(&batch)?)?
+                .collect::<Vec<_>>();
+        // the last partition of the batch may continue in the next batches
+        let last_start = partition_points.last().map_or(0, |range| range.start);
+        if last_start == 0 && self.continues_partition(&batch)? {
+            self.buffer.push(batch);
+            return Ok(None);
+        }
+        let mut complete = std::mem::take(&mut self.buffer);
+        if last_start > 0 {
+            complete.push(batch.slice(0, last_start));
+        }
+        self.buffer
+            .push(batch.slice(last_start, num_rows - last_start));
+        self.compute_window_aggregates(&complete)
+    }
+
+    /// Evaluates the common partition keys over `batch`
+    fn partition_columns(&self, batch: &RecordBatch) -> Result<Vec<SortColumn>> {
+        self.partition_by
+            .iter()
+            .map(|expr| {
+                PhysicalSortExpr {
+                    expr: expr.clone(),
+                    options: SortOptions::default(),
+                }
+                .evaluate_to_sort_column(batch)
+            })
+            .collect()
+    }
+
+    /// Returns true if the first row of `batch` is in the same window partition
+    /// as the last buffered row
+    fn continues_partition(&self, batch: &RecordBatch) -> Result<bool> {
+        let last = match self.buffer.last() {
+            Some(last) => last.slice(last.num_rows() - 1, 1),
+            None => return Ok(true),
+        };
+        let last_keys = self.partition_columns(&last)?;
+        let keys = self.partition_columns(&batch.slice(0, 1))?;
+        for (last_key, key) in last_keys.iter().zip(keys.iter()) {
+            if ScalarValue::try_from_array(&last_key.values, 0)?
+                != ScalarValue::try_from_array(&key.values, 0)?
+            {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Evaluates the window expressions over the rows of complete window partitions
+    fn compute_window_aggregates(
+        &self,
+        batches: &[RecordBatch],
+    ) -> Result<Option<RecordBatch>> {
+        let batch = match common::combine_batches(batches, self.input.schema())? {
+            Some(batch) if batch.num_rows() > 0 => batch,
+            _ => return Ok(None),
+        };
+        // calculate window cols
+        let mut columns = compute_window_aggregates(self.window_expr.clone(), &batch)?;
+        // combine with the original cols
+        // note the setup of window aggregates is that they newly calculated window
+        // expressions are always prepended to the columns
+        columns.extend_from_slice(batch.columns());
+        Ok(Some(RecordBatch::try_new(self.schema.clone(), columns)?))
     }
 }
 
@@ -309,24 +416,41 @@ impl WindowAggStream {
         self: &mut Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<ArrowResult<RecordBatch>>> {
-        if self.finished {
-            return Poll::Ready(None);
-        }
-
-        // is the output ready?
-        let output_poll = self.output.poll_unpin(cx);
-
-        match output_poll {
-            Poll::Ready(result) => {
-                self.finished = true;
-                // check for error in receiving channel and unwrap actual result
-                let result = match result {
-                    Err(e) => Some(Err(ArrowError::ExternalError(Box::new(e)))), // error receiving
-                    Ok(result) => Some(result),
-                };
-                Poll::Ready(result)
+        loop {
+            if self.finished {
+                return Poll::Ready(None);
+            }
+            let result = match futures::ready!(self.input.poll_next_unpin(cx)) {
+                Some(Ok(batch)) => {
+                    let elapsed_compute = self.baseline_metrics.elapsed_compute().clone();
+                    let timer = elapsed_compute.timer();
+                    let result = self.push_batch(batch);
+                    timer.done();
+                    result
+                }
+                Some(Err(e)) => {
+                    self.finished = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                None => {
+                    // the buffered rows are the last window partition
+                    self.finished = true;
+                    let elapsed_compute = self.baseline_metrics.elapsed_compute().clone();
+                    let timer = elapsed_compute.timer();
+                    let buffer = std::mem::take(&mut self.buffer);
+                    let result = self.compute_window_aggregates(&buffer);
+                    timer.done();
+                    result
+                }
+            };
+            match result {
+                Ok(Some(batch)) => return Poll::Ready(Some(Ok(batch))),
+                Ok(None) => continue,
+                Err(e) => {
+                    self.finished = true;
+                    return Poll::Ready(Some(Err(e.into_arrow_external_error())));
+                }
             }
-            Poll::Pending => Poll::Pending,
         }
     }
 }
@@ -930,6 +930,8 @@ impl ScalarValue {
                 | ScalarValue::TimestampMillisecond(None, _)
                 | ScalarValue::TimestampMicrosecond(None, _)
                 | ScalarValue::TimestampNanosecond(None, _)
+                | ScalarValue::IntervalYearMonth(None)
+                | ScalarValue::IntervalDayTime(None)
                 | ScalarValue::Struct(None, _)
                 | ScalarValue::Decimal128(None, _, _) // For decimal type, the value is null means ScalarValue::Decimal128 is null.
         )
@@ -1677,6 +1679,12 @@ impl ScalarValue {
                     None => values.data_type().try_into()?,
                 }
             }
+            DataType::Interval(IntervalUnit::DayTime) => {
+                typed_cast!(array, index, IntervalDayTimeArray, IntervalDayTime)
+            }
+            DataType::Interval(IntervalUnit::YearMonth) => {
+                typed_cast!(array, index, IntervalYearMonthArray, IntervalYearMonth)
+            }
             DataType::Struct(fields) => {
                 let array =
                     array
@@ -1780,7 +1788,9 @@ impl ScalarValue {
             ScalarValue::LargeBinary(val) => {
                 eq_array_primitive!(array, index, LargeBinaryArray, val)
             }
-            ScalarValue::List(_, _) => unimplemented!(),
+            ScalarValue::List(_, _) => ScalarValue::try_from_array(array, index)
+                .map(|value| &value == self)
+                .unwrap_or(false),
             ScalarValue::Date32(val) => {
                 eq_array_primitive!(array, index, Date32Array, val)
             }
@@ -2008,6 +2018,12 @@ impl TryFrom<&DataType> for ScalarValue {
             DataType::List(ref nested_type) => {
                 ScalarValue::List(None, Box::new(nested_type.data_type().clone()))
             }
+            DataType::Interval(IntervalUnit::YearMonth) => {
+                ScalarValue::IntervalYearMonth(None)
+            }
+            DataType::Interval(IntervalUnit::DayTime) => {
+                ScalarValue::IntervalDayTime(None)
+            }
             DataType::Struct(fields) => {
                 ScalarValue::Struct(None, Box::new(fields.clone()))
             }
@@ -2021,6 +2037,23 @@ impl TryFrom<&DataType> for ScalarValue {
     }
 }
 
+/// Packs the two parts of an arrow `Interval(DayTime)` value, which must
+/// both fit into an `i32`
+pub fn make_interval_day_time(days: i64, millis: i64) -> Result<i64> {
+    match (i32::try_from(days), i32::try_from(millis)) {
+        (Ok(days), Ok(millis)) => Ok(((days as i64) << 32) | (millis as u32 as i64)),
+        _ => Err(DataFusionError::Execution(format!(
+            "Interval of {} days and {} milliseconds is out of range",
+            days, millis
+        ))),
+    }
+}
+
+/// Splits an arrow `Interval(DayTime)` value into days and milliseconds
+pub fn split_interval_day_time(value: i64) -> (i32, i32) {
+    ((value >> 32) as i32, value as i32)
+}
+
 macro_rules! format_option {
     ($F:expr, $EXPR:expr) => {{
         match $EXPR {
@@ -2321,6 +2354,44 @@ mod tests {
         assert!(array.is_null(0));
     }
 
+    #[test]
+    fn scalar_list_eq_array() -> Result<()> {
+        let values = vec![
+            ScalarValue::List(
+                Some(Box::new(vec![
+                    ScalarValue::Int64(Some(1)),
+                    ScalarValue::Int64(None),
+                ])),
+                Box::new(DataType::Int64),
+            ),
+            ScalarValue::List(Some(Box::new(vec![])), Box::new(DataType::Int64)),
+            ScalarValue::List(None, Box::new(DataType::Int64)),
+        ];
+        let array = ScalarValue::iter_to_array(values.clone())?;
+        for (i, value) in values.iter().enumerate() {
+            assert!(value.eq_array(&array, i));
+            assert!(!value.eq_array(&array, (i + 1) % values.len()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn scalar_interval_day_time() -> Result<()> {
+        let value = make_interval_day_time(-1, 1500)?;
+        assert_eq!(split_interval_day_time(value), (-1, 1500));
+        assert!(make_interval_day_time(i32::MAX as i64 + 1, 0).is_err());
+
+        let values = vec![
+            ScalarValue::IntervalDayTime(Some(value)),
+            ScalarValue::IntervalDayTime(None),
+        ];
+        let array = ScalarValue::iter_to_array(values.clone())?;
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(&ScalarValue::try_from_array(&array, i)?, value);
+        }
+        Ok(())
+    }
+
     #[test]
     fn scalar_value_to_array_u32() {
         let value = ScalarValue::UInt32(Some(13u32));
@@ -79,6 +79,39 @@ pub struct CreateExternalTable {
     pub location: String,
 }
 
+/// DataFusion extension for `SET <name> { = | TO } <value>`, which changes a
+/// configuration setting of the session. Unlike the `SET` of sqlparser, the
+/// name may consist of several identifiers separated by periods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetVariable {
+    /// Name of the setting, e.g. `datafusion.execution.batch_size`
+    pub variable: String,
+    /// The new value. A list of values is joined by commas.
+    pub value: String,
+}
+
+/// Name of the custom SQL data type that `TIMESTAMP WITH TIME ZONE` and
+/// `AT TIME ZONE` are rewritten to, see [`DFParser::new_with_dialect`].
+/// The native parser reads `TIMESTAMP WITH TIME ZONE` as a plain `TIMESTAMP`.
+///
+/// As the tokenizer never produces unquoted identifiers with spaces, only the
+/// rewritten `AT TIME ZONE` can qualify the type with a time zone.
+pub const TIMESTAMP_WITH_TIME_ZONE_TYPE: &str = "TIMESTAMP WITH TIME ZONE";
+
+/// Name of the custom SQL data type `TIMESTAMPTZ`, which is short for
+/// `TIMESTAMP WITH TIME ZONE`
+pub const TIMESTAMP_TZ_TYPE: &str = "TIMESTAMPTZ";
+
+/// Name of the argument that the `FILTER (WHERE ...)` clause of an aggregate
+/// call is rewritten to, see [`DFParser::new_with_dialect`]. Like the names
+/// of the `ORDER BY` arguments, it can't be written as an unquoted identifier.
+pub const AGGREGATE_FILTER_ARG: &str = "FILTER WHERE";
+
+/// Prefix of the names of the arguments that the keys of an `ORDER BY` clause
+/// within an aggregate call are rewritten to. It is followed by `ASC` or `DESC`
+/// and optionally by ` NULLS FIRST` or ` NULLS LAST`.
+pub const AGGREGATE_ORDER_BY_ARG: &str = "ORDER BY ";
+
 /// DataFusion Statement representations.
 ///
 /// Tokens parsed by `DFParser` are converted into these values.
@@ -88,6 +121,8 @@ pub enum Statement {
     Statement(Box<SQLStatement>),
     /// Extension: `CREATE EXTERNAL TABLE`
     CreateExternalTable(CreateExternalTable),
+    /// Extension: `SET <name> = <value>`
+    SetVariable(SetVariable),
 }
 
 /// SQL Parser
@@ -103,12 +138,36 @@ impl<'a> DFParser<'a> {
     }
 
     /// Parse the specified tokens with dialect
+    ///
+    /// Time zone syntax that the native parser does not support is rewritten
+    /// into the custom [`TIMESTAMP_WITH_TIME_ZONE_TYPE`] type, written `TZ` here:
+    ///
+    /// * `TIMESTAMP WITH TIME ZONE` becomes `TZ`
+    /// * `TIMESTAMP WITHOUT TIME ZONE` becomes `TIMESTAMP`
+    /// * `<expr> AT TIME ZONE '<tz>'` becomes `<expr>::TZ."<tz>"`
+    ///
+    /// So are the clauses of aggregate calls, which are passed as named
+    /// arguments instead:
+    ///
+    /// * `f(x) FILTER (WHERE c)` becomes `f(x, FILTER WHERE => (c))`
+    /// * `f(x ORDER BY a, b DESC NULLS LAST)` becomes
+    ///   `f(x, ORDER BY ASC => (a), ORDER BY DESC NULLS LAST => (b))`
+    ///
+    /// where the argument names are single identifiers, which the tokenizer
+    /// never produces unquoted as they contain spaces.
+    ///
+    /// Placeholders `$1`, `$2`, ... become unquoted identifiers, which the
+    /// tokenizer never produces as they start with `$`. Positional `?`
+    /// placeholders are numbered like them in the order of their appearance
+    /// in each statement.
     pub fn new_with_dialect(
         sql: &str,
         dialect: &'a dyn Dialect,
     ) -> Result<Self, ParserError> {
         let mut tokenizer = Tokenizer::new(dialect, sql);
-        let tokens = tokenizer.tokenize()?;
+        let tokens = rewrite_time_zone_tokens(tokenizer.tokenize()?);
+        let tokens = rewrite_aggregate_clause_tokens(tokens);
+        let tokens = rewrite_placeholder_tokens(tokens);
 
         Ok(DFParser {
             parser: Parser::new(tokens, dialect),
@@ -165,6 +224,10 @@ impl<'a> DFParser<'a> {
                         // use custom parsing
                         self.parse_create()
                     }
+                    Keyword::SET => {
+                        self.parser.next_token();
+                        self.parse_set()
+                    }
                     _ => {
                         // use the native parser
                         Ok(Statement::Statement(Box::from(
@@ -191,6 +254,40 @@ impl<'a> DFParser<'a> {
         }
     }
 
+    /// Parse a SQL SET statement
+    pub fn parse_set(&mut self) -> Result<Statement, ParserError> {
+        let variable = self.parser.parse_object_name()?;
+        if !self.parser.consume_token(&Token::Eq)
+            && !self.parser.parse_keyword(Keyword::TO)
+        {
+            return self.expected("'=' or TO", self.parser.peek_token());
+        }
+
+        let mut values = vec![];
+        loop {
+            let value = match self.parser.next_token() {
+                Token::Word(w) => w.value,
+                Token::SingleQuotedString(s) => s,
+                Token::Number(n, _) => n,
+                unexpected => return self.expected("a value", unexpected),
+            };
+            values.push(value);
+            if !self.parser.consume_token(&Token::Comma) {
+                break;
+            }
+        }
+
+        Ok(Statement::SetVariable(SetVariable {
+            variable: variable
+                .0
+                .iter()
+                .map(|ident| ident.value.as_str())
+                .collect::<Vec<_>>()
+                .join("."),
+            value: values.join(","),
+        }))
+    }
+
     // This is a copy of the equivalent implementation in sqlparser.
     fn parse_columns(
         &mut self,
@@ -315,10 +412,298 @@ impl<'a> DFParser<'a> {
     }
 }
 
+/// Returns true if `token` is the unquoted word `word`, ignoring case
+fn is_word(token: &Token, word: &str) -> bool {
+    matches!(token, Token::Word(w) if w.quote_style.is_none() && w.value.eq_ignore_ascii_case(word))
+}
+
+/// Returns the index of the next token at or after `start` that is not whitespace
+fn skip_whitespace(tokens: &[Token], start: usize) -> usize {
+    let mut i = start;
+    while matches!(tokens.get(i), Some(Token::Whitespace(_))) {
+        i += 1;
+    }
+    i
+}
+
+/// If `words` follow `start` (separated by whitespace), returns the index
+/// after the last of them
+fn match_words(tokens: &[Token], start: usize, words: &[&str]) -> Option<usize> {
+    let mut i = start;
+    for word in words {
+        i = skip_whitespace(tokens, i);
+        if !tokens.get(i).map_or(false, |t| is_word(t, word)) {
+            return None;
+        }
+        i += 1;
+    }
+    Some(i)
+}
+
+/// See [`DFParser::new_with_dialect`]
+fn rewrite_time_zone_tokens(tokens: Vec<Token>) -> Vec<Token> {
+    let mut result = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Some(end) = match_words(&tokens, i, &["TIMESTAMP", "WITH", "TIME", "ZONE"])
+        {
+            let literal = skip_whitespace(&tokens, end);
+            match tokens.get(literal) {
+                // typed string: TIMESTAMP WITH TIME ZONE '...'
+                Some(value @ Token::SingleQuotedString(_)) => {
+                    result.push(value.clone());
+                    result.push(Token::DoubleColon);
+                    result.push(Token::make_word(TIMESTAMP_WITH_TIME_ZONE_TYPE, None));
+                    i = literal + 1;
+                }
+                _ => {
+                    result.push(Token::make_word(TIMESTAMP_WITH_TIME_ZONE_TYPE, None));
+                    i = end;
+                }
+            }
+        } else if let Some(end) =
+            match_words(&tokens, i, &["TIMESTAMP", "WITHOUT", "TIME", "ZONE"])
+        {
+            result.push(tokens[i].clone());
+            i = end;
+        } else if let Some((tz, end)) = match_words(&tokens, i, &["AT", "TIME", "ZONE"])
+            .and_then(|end| {
+                let literal = skip_whitespace(&tokens, end);
+                match tokens.get(literal) {
+                    Some(Token::SingleQuotedString(tz)) => {
+                        Some((tz.clone(), literal + 1))
+                    }
+                    _ => None,
+                }
+            })
+        {
+            result.push(Token::DoubleColon);
+            result.push(Token::make_word(TIMESTAMP_WITH_TIME_ZONE_TYPE, None));
+            result.push(Token::Period);
+            result.push(Token::make_word(&tz, Some('"')));
+            i = end;
+        } else {
+            result.push(tokens[i].clone());
+            i += 1;
+        }
+    }
+    result
+}
+
+/// See [`DFParser::new_with_dialect`]
+fn rewrite_placeholder_tokens(tokens: Vec<Token>) -> Vec<Token> {
+    let mut result = Vec::with_capacity(tokens.len());
+    let mut count = 0;
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Char('$')
+                if matches!(tokens.peek(), Some(Token::Number(_, false))) =>
+            {
+                if let Some(Token::Number(n, _)) = tokens.next() {
+                    result.push(Token::make_word(&format!("${}", n), None));
+                }
+            }
+            Token::Char('?') => {
+                count += 1;
+                result.push(Token::make_word(&format!("${}", count), None));
+            }
+            Token::SemiColon => {
+                count = 0;
+                result.push(token);
+            }
+            token => result.push(token),
+        }
+    }
+    result
+}
+
+/// Returns the last token that is not whitespace
+fn last_token(tokens: &[Token]) -> Option<&Token> {
+    tokens
+        .iter()
+        .rev()
+        .find(|t| !matches!(t, Token::Whitespace(_)))
+}
+
+/// Returns the index of the parenthesis that closes the one opened before `start`
+fn closing_paren(tokens: &[Token], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen if depth == 0 => return Some(i),
+            Token::RParen => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits `tokens` at the commas that are not nested in parentheses or brackets
+fn split_commas(tokens: &[Token]) -> Vec<&[Token]> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::LParen | Token::LBracket => depth += 1,
+            Token::RParen | Token::RBracket => depth -= 1,
+            Token::Comma if depth == 0 => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts
+}
+
+/// If the last token before `end` that is not whitespace is one of `words`,
+/// returns its index and the word
+fn last_word(
+    tokens: &[Token],
+    end: usize,
+    words: &[&'static str],
+) -> Option<(usize, &'static str)> {
+    let i = tokens[..end]
+        .iter()
+        .rposition(|t| !matches!(t, Token::Whitespace(_)))?;
+    words
+        .iter()
+        .find(|word| is_word(&tokens[i], word))
+        .map(|word| (i, *word))
+}
+
+/// Splits a key of an `ORDER BY` clause into its expression and the suffix of
+/// the [`AGGREGATE_ORDER_BY_ARG`] it is passed as
+fn order_by_key(tokens: &[Token]) -> (&[Token], String) {
+    let mut end = tokens.len();
+    let mut nulls = "";
+    if let Some((i, word)) = last_word(tokens, end, &["FIRST", "LAST"]) {
+        if let Some((j, _)) = last_word(tokens, i, &["NULLS"]) {
+            nulls = match word {
+                "FIRST" => " NULLS FIRST",
+                _ => " NULLS LAST",
+            };
+            end = j;
+        }
+    }
+    let mut direction = "ASC";
+    if let Some((i, word)) = last_word(tokens, end, &["ASC", "DESC"]) {
+        direction = word;
+        end = i;
+    }
+    (&tokens[..end], format!("{}{}", direction, nulls))
+}
+
+/// Appends `name => (value)` arguments to the argument list at the end of `result`
+fn push_named_args(result: &mut Vec<Token>, args: Vec<(String, &[Token])>) {
+    while matches!(result.last(), Some(Token::Whitespace(_))) {
+        result.pop();
+    }
+    for (name, value) in args {
+        if result.last() != Some(&Token::LParen) {
+            result.push(Token::Comma);
+        }
+        result.push(Token::make_word(&name, None));
+        result.push(Token::RArrow);
+        result.push(Token::LParen);
+        result.extend(rewrite_aggregate_clause_tokens(value.to_vec()));
+        result.push(Token::RParen);
+    }
+}
+
+/// Words that are followed by a parenthesis that doesn't enclose the arguments
+/// of a function call, e.g. a window specification or a list of values
+const NON_CALL_WORDS: &[&str] = &[
+    "OVER", "GROUP", "AS", "FROM", "JOIN", "ON", "USING", "IN", "EXISTS", "VALUES",
+    "SELECT", "WHERE", "HAVING", "AND", "OR", "NOT", "WHEN", "THEN", "ELSE", "BY",
+    "LATERAL",
+];
+
+/// See [`DFParser::new_with_dialect`]
+fn rewrite_aggregate_clause_tokens(tokens: Vec<Token>) -> Vec<Token> {
+    let mut result: Vec<Token> = Vec::with_capacity(tokens.len());
+    // whether each open parenthesis encloses the arguments of a function call,
+    // rather than e.g. a window specification or a subquery
+    let mut calls: Vec<bool> = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            Token::LParen => {
+                let is_call = match last_token(&result) {
+                    Some(previous @ Token::Word(_)) => {
+                        !NON_CALL_WORDS.iter().any(|word| is_word(previous, word))
+                    }
+                    _ => false,
+                } && !tokens.get(skip_whitespace(&tokens, i + 1)).map_or(
+                    false,
+                    |t| {
+                        ["SELECT", "WITH", "VALUES"]
+                            .iter()
+                            .any(|word| is_word(t, word))
+                    },
+                );
+                calls.push(is_call);
+                result.push(tokens[i].clone());
+                i += 1;
+                continue;
+            }
+            Token::RParen => {
+                calls.pop();
+            }
+            _ => {}
+        }
+
+        // f(x ORDER BY a, b)
+        if let Some((start, close)) = match_words(&tokens, i, &["ORDER", "BY"])
+            .filter(|_| calls.last() == Some(&true))
+            .and_then(|start| Some((start, closing_paren(&tokens, start)?)))
+        {
+            let args = split_commas(&tokens[start..close])
+                .into_iter()
+                .map(|key| {
+                    let (key, suffix) = order_by_key(key);
+                    (format!("{}{}", AGGREGATE_ORDER_BY_ARG, suffix), key)
+                })
+                .collect();
+            push_named_args(&mut result, args);
+            // the closing parenthesis is handled as usual
+            i = close;
+        }
+        // f(x) FILTER (WHERE c)
+        else if let Some((start, close)) = match_words(&tokens, i, &["FILTER"])
+            .filter(|_| last_token(&result) == Some(&Token::RParen))
+            .and_then(|end| {
+                let paren = skip_whitespace(&tokens, end);
+                if tokens.get(paren) != Some(&Token::LParen) {
+                    return None;
+                }
+                let start = match_words(&tokens, paren + 1, &["WHERE"])?;
+                Some((start, closing_paren(&tokens, start)?))
+            })
+        {
+            while result.pop() != Some(Token::RParen) {}
+            push_named_args(
+                &mut result,
+                vec![(AGGREGATE_FILTER_ARG.to_string(), &tokens[start..close])],
+            );
+            result.push(Token::RParen);
+            i = close + 1;
+        } else {
+            result.push(tokens[i].clone());
+            i += 1;
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sqlparser::ast::{DataType, Ident};
+    use sqlparser::ast::{DataType, Ident, ObjectName};
 
     fn expect_parse_ok(sql: &str, expected: Statement) -> Result<(), ParserError> {
         let statements = DFParser::parse_sql(sql)?;
@@ -432,6 +817,145 @@ mod tests {
             "CREATE EXTERNAL TABLE t(c1 int) STORED AS UNKNOWN_TYPE LOCATION 'foo.csv'";
         expect_parse_error(sql, "expect one of PARQUET, AVRO, NDJSON, or CSV");
 
+        Ok(())
+    }
+    #[test]
+    fn time_zone_syntax() -> Result<(), ParserError> {
+        let display = None;
+        let tz_type =
+            DataType::Custom(ObjectName(vec![Ident::new(TIMESTAMP_WITH_TIME_ZONE_TYPE)]));
+
+        // positive case: column types with and without time zone
+        let sql = "CREATE EXTERNAL TABLE t(c1 TIMESTAMP WITH TIME ZONE, c2 timestamp without time zone, c3 int) STORED AS CSV LOCATION 'foo.csv'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![
+                make_column_def("c1", tz_type),
+                make_column_def("c2", DataType::Timestamp),
+                make_column_def("c3", DataType::Int(display)),
+            ],
+            file_type: FileType::CSV,
+            has_header: false,
+            location: "foo.csv".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: the custom type name
+        let sql =
+            "CREATE EXTERNAL TABLE t(c1 timestamptz) STORED AS CSV LOCATION 'foo.csv'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![make_column_def(
+                "c1",
+                DataType::Custom(ObjectName(vec![Ident::new("timestamptz")])),
+            )],
+            file_type: FileType::CSV,
+            has_header: false,
+            location: "foo.csv".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: AT TIME ZONE and typed strings become casts
+        let statements = DFParser::parse_sql(
+            "SELECT c1 AT TIME ZONE 'Europe/Berlin', TIMESTAMP WITH TIME ZONE '2021-01-01 00:00:00' FROM t",
+        )?;
+        let statement = match &statements[0] {
+            Statement::Statement(statement) => statement.to_string(),
+            other => panic!("Expected a native statement, got {:?}", other),
+        };
+        assert_eq!(
+            statement,
+            "SELECT CAST(c1 AS TIMESTAMP WITH TIME ZONE.\"Europe/Berlin\"), CAST('2021-01-01 00:00:00' AS TIMESTAMP WITH TIME ZONE) FROM t"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn set_variable() -> Result<(), ParserError> {
+        let expected = Statement::SetVariable(SetVariable {
+            variable: "datafusion.execution.batch_size".into(),
+            value: "4096".into(),
+        });
+        expect_parse_ok(
+            "SET datafusion.execution.batch_size = 4096",
+            expected.clone(),
+        )?;
+        expect_parse_ok("set datafusion.execution.batch_size to '4096'", expected)?;
+
+        let expected = Statement::SetVariable(SetVariable {
+            variable: "datafusion.optimizer.disabled_rules".into(),
+            value: "eliminate_limit,limit_push_down".into(),
+        });
+        expect_parse_ok(
+            "SET datafusion.optimizer.disabled_rules = eliminate_limit, 'limit_push_down'",
+            expected,
+        )?;
+
+        // Error cases: missing operator or value
+        expect_parse_error(
+            "SET datafusion.execution.batch_size 4096",
+            "Expected '=' or TO, found: 4096",
+        );
+        expect_parse_error("SET datafusion.execution.batch_size =", "Expected a value");
+
+        Ok(())
+    }
+
+    #[test]
+    fn placeholder_syntax() -> Result<(), ParserError> {
+        let statements = DFParser::parse_sql(
+            "SELECT * FROM t WHERE c1 = ? AND c2 BETWEEN ? AND $1; SELECT ?",
+        )?;
+        let statements = statements
+            .iter()
+            .map(|statement| match statement {
+                Statement::Statement(statement) => statement.to_string(),
+                other => panic!("Expected a native statement, got {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statements,
+            vec![
+                "SELECT * FROM t WHERE c1 = $1 AND c2 BETWEEN $2 AND $1",
+                "SELECT $1"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_clause_syntax() -> Result<(), ParserError> {
+        let statements = DFParser::parse_sql(
+            "SELECT sum(c1) FILTER (WHERE c2 > 1), count(*) filter (where c1 IS NULL), \
+             array_agg(DISTINCT c1 ORDER BY c2, c3 DESC NULLS LAST) FILTER (WHERE c2 > 1), \
+             sum(c1) OVER (ORDER BY c2) \
+             FROM t WHERE c1 IN (SELECT c1 FROM u ORDER BY c1)",
+        )?;
+        let statement = match &statements[0] {
+            Statement::Statement(statement) => statement.to_string(),
+            other => panic!("Expected a native statement, got {:?}", other),
+        };
+        assert_eq!(
+            statement,
+            "SELECT sum(c1, FILTER WHERE => (c2 > 1)), \
+             count(*, FILTER WHERE => (c1 IS NULL)), \
+             array_agg(DISTINCT c1, ORDER BY ASC => (c2), \
+             ORDER BY DESC NULLS LAST => (c3), FILTER WHERE => (c2 > 1)), \
+             sum(c1) OVER (ORDER BY c2) \
+             FROM t WHERE c1 IN (SELECT c1 FROM u ORDER BY c1)"
+        );
+
+        // lists of values keep their ORDER BY, like the subquery above
+        let sql = "SELECT * FROM (VALUES (1), (2) ORDER BY 1) AS v";
+        let statements = DFParser::parse_sql(sql)?;
+        let statement = match &statements[0] {
+            Statement::Statement(statement) => statement.to_string(),
+            other => panic!("Expected a native statement, got {:?}", other),
+        };
+        assert!(!statement.contains("=>"), "{}", statement);
+
         Ok(())
     }
 }
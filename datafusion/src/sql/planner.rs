@@ -24,35 +24,46 @@ use std::sync::Arc;
 use std::{convert::TryInto, vec};
 
 use crate::catalog::TableReference;
+use crate::datasource::table_function::TableFunctionCall;
 use crate::datasource::TableProvider;
+use crate::execution::config::ConfigEntry;
+use crate::execution::context::{ExecutionConfig, ExecutionProps};
 use crate::logical_plan::window_frames::{WindowFrame, WindowFrameUnits};
 use crate::logical_plan::Expr::Alias;
 use crate::logical_plan::{
-    and, builder::expand_wildcard, col, lit, normalize_col, union_with_alias, Column,
+    builder::expand_wildcard, col, lit, normalize_col, union_with_alias, Column,
     CreateExternalTable as PlanCreateExternalTable, CreateMemoryTable, DFSchema,
     DFSchemaRef, DropTable, Expr, LogicalPlan, LogicalPlanBuilder, Operator, PlanType,
-    ToDFSchema, ToStringifiedPlan,
+    Prepare, SetVariable as PlanSetVariable, ToDFSchema, ToStringifiedPlan,
 };
+use crate::optimizer::simplify_expressions::ConstEvaluator;
 use crate::optimizer::utils::exprlist_to_columns;
+use crate::physical_plan::datetime_expressions::parse_time_zone;
 use crate::prelude::JoinType;
-use crate::scalar::ScalarValue;
+use crate::scalar::{make_interval_day_time, ScalarValue};
 use crate::sql::utils::make_decimal_type;
 use crate::{
     error::{DataFusionError, Result},
     physical_plan::udaf::AggregateUDF,
+    physical_plan::udwf::WindowUDF,
 };
 use crate::{
     physical_plan::udf::ScalarUDF,
     physical_plan::{aggregates, functions, window_functions},
-    sql::parser::{CreateExternalTable, FileType, Statement as DFStatement},
+    sql::parser::{
+        CreateExternalTable, FileType, SetVariable, Statement as DFStatement,
+        AGGREGATE_FILTER_ARG, AGGREGATE_ORDER_BY_ARG, TIMESTAMP_TZ_TYPE,
+        TIMESTAMP_WITH_TIME_ZONE_TYPE,
+    },
 };
 use arrow::datatypes::*;
 use hashbrown::HashMap;
 use sqlparser::ast::{
     BinaryOperator, DataType as SQLDataType, DateTimeField, Expr as SQLExpr, FunctionArg,
     HiveDistributionStyle, Ident, Join, JoinConstraint, JoinOperator, ObjectName, Query,
-    Select, SelectItem, SetExpr, SetOperator, ShowStatementFilter, TableFactor,
-    TableWithJoins, TrimWhereField, UnaryOperator, Value, Values as SQLValues,
+    Select, SelectItem, SetExpr, SetOperator, ShowStatementFilter, TableAlias,
+    TableFactor, TableWithJoins, TrimWhereField, UnaryOperator, Value,
+    Values as SQLValues,
 };
 use sqlparser::ast::{ColumnDef as SQLColumnDef, ColumnOption};
 use sqlparser::ast::{ObjectType, OrderByExpr, Statement};
@@ -67,7 +78,7 @@ use super::{
     },
 };
 use crate::logical_plan::builder::project_with_alias;
-use crate::logical_plan::plan::{Analyze, Explain};
+use crate::logical_plan::plan::{placeholder_index, Analyze, Explain};
 
 /// The ContextProvider trait allows the query planner to obtain meta-data about tables and
 /// functions referenced in SQL statements
@@ -78,11 +89,38 @@ pub trait ContextProvider {
     fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>>;
     /// Getter for a UDAF description
     fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>>;
+    /// Getter for a UDWF description
+    fn get_window_meta(&self, _name: &str) -> Option<Arc<WindowUDF>> {
+        None
+    }
+    /// Getter for the table created by a call of a table function, see
+    /// [`SqlToRel::table_function_calls`]
+    fn get_table_function_provider(
+        &self,
+        _call: &TableFunctionCall,
+    ) -> Option<Arc<dyn TableProvider>> {
+        None
+    }
+    /// Getter for the session time zone
+    fn get_time_zone(&self) -> String {
+        "UTC".to_owned()
+    }
+    /// Getter for the value of a configuration setting, see
+    /// [`ExecutionConfig::valid_entries`]
+    fn get_config_setting(&self, _name: &str) -> Option<String> {
+        None
+    }
+    /// Getter for the plan of a statement prepared with `PREPARE`
+    fn get_prepared_statement(&self, _name: &str) -> Option<LogicalPlan> {
+        None
+    }
 }
 
 /// SQL query planner
 pub struct SqlToRel<'a, S: ContextProvider> {
     schema_provider: &'a S,
+    /// The declared types of the placeholders `$1`, `$2`, ... of a prepared statement
+    param_types: Vec<DataType>,
 }
 
 fn plan_key(key: Value) -> ScalarValue {
@@ -114,7 +152,10 @@ fn plan_indexed(expr: Expr, mut keys: Vec<Value>) -> Expr {
 impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     /// Create a new query planner
     pub fn new(schema_provider: &'a S) -> Self {
-        SqlToRel { schema_provider }
+        SqlToRel {
+            schema_provider,
+            param_types: vec![],
+        }
     }
 
     /// Generate a logical plan from an DataFusion SQL statement
@@ -122,6 +163,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         match statement {
             DFStatement::CreateExternalTable(s) => self.external_table_to_plan(s),
             DFStatement::Statement(s) => self.sql_statement_to_plan(s),
+            DFStatement::SetVariable(s) => self.set_variable_to_plan(s),
         }
     }
 
@@ -192,6 +234,31 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 table_name,
                 filter,
             } => self.show_columns_to_plan(*extended, *full, table_name, filter.as_ref()),
+
+            Statement::Prepare {
+                name,
+                data_types,
+                statement,
+            } => {
+                let data_types = data_types
+                    .iter()
+                    .map(|data_type| self.convert_data_type(data_type))
+                    .collect::<Result<Vec<_>>>()?;
+                let planner = SqlToRel {
+                    schema_provider: self.schema_provider,
+                    param_types: data_types.clone(),
+                };
+                let plan = planner.sql_statement_to_plan(statement)?;
+                Ok(LogicalPlan::Prepare(Prepare {
+                    name: normalize_ident(name),
+                    data_types,
+                    input: Arc::new(plan),
+                }))
+            }
+
+            Statement::Execute { name, parameters } => {
+                self.execute_to_plan(&normalize_ident(name), parameters)
+            }
             _ => Err(DataFusionError::NotImplemented(format!(
                 "Unsupported SQL statement: {:?}",
                 sql
@@ -382,6 +449,9 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             SQLDataType::Date => Ok(DataType::Date32),
             SQLDataType::Time => Ok(DataType::Time64(TimeUnit::Millisecond)),
             SQLDataType::Timestamp => Ok(DataType::Timestamp(TimeUnit::Nanosecond, None)),
+            SQLDataType::Custom(name) if timestamp_tz_type(&name.0).is_some() => {
+                self.convert_data_type(sql_type)
+            }
             _ => Err(DataFusionError::NotImplemented(format!(
                 "The SQL data type {:?} is not implemented",
                 sql_type
@@ -389,6 +459,28 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
+    /// Maps the SQL type to the corresponding Arrow `DataType`, resolving
+    /// `TIMESTAMP WITH TIME ZONE` to the session time zone
+    fn convert_data_type(&self, sql_type: &SQLDataType) -> Result<DataType> {
+        match sql_type {
+            SQLDataType::Custom(ObjectName(idents)) => match timestamp_tz_type(idents) {
+                // AT TIME ZONE '<tz>'
+                Some(Some(tz)) => self.make_timestamp_tz_type(tz.to_owned()),
+                Some(None) => {
+                    self.make_timestamp_tz_type(self.schema_provider.get_time_zone())
+                }
+                None => convert_data_type(sql_type),
+            },
+            _ => convert_data_type(sql_type),
+        }
+    }
+
+    /// Returns the timestamp type in time zone `tz`, if `tz` is valid
+    fn make_timestamp_tz_type(&self, tz: String) -> Result<DataType> {
+        parse_time_zone(&tz)?;
+        Ok(DataType::Timestamp(TimeUnit::Nanosecond, Some(tz)))
+    }
+
     fn plan_from_tables(
         &self,
         from: &[TableWithJoins],
@@ -396,10 +488,23 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     ) -> Result<Vec<LogicalPlan>> {
         match from.len() {
             0 => Ok(vec![LogicalPlanBuilder::empty(true).build()?]),
-            _ => from
-                .iter()
-                .map(|t| self.plan_table_with_joins(t, ctes))
-                .collect::<Result<Vec<_>>>(),
+            _ => {
+                let mut plans: Vec<LogicalPlan> = Vec::with_capacity(from.len());
+                for t in from {
+                    let plan = match unnest_args(&t.relation) {
+                        // `FROM a, b, UNNEST(a.list)` unnests a list of any of the
+                        // preceding relations, so it is planned over their cross join
+                        Some((args, alias)) if !plans.is_empty() => {
+                            let preceding = cross_join_plans(std::mem::take(&mut plans))?;
+                            let left = self.unnest_to_plan(preceding, args, alias)?;
+                            self.plan_joins(left, &t.joins, ctes)?
+                        }
+                        _ => self.plan_table_with_joins(t, ctes)?,
+                    };
+                    plans.push(plan);
+                }
+                Ok(plans)
+            }
         }
     }
 
@@ -409,16 +514,18 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         ctes: &mut HashMap<String, LogicalPlan>,
     ) -> Result<LogicalPlan> {
         let left = self.create_relation(&t.relation, ctes)?;
-        match t.joins.len() {
-            0 => Ok(left),
-            n => {
-                let mut left = self.parse_relation_join(left, &t.joins[0], ctes)?;
-                for i in 1..n {
-                    left = self.parse_relation_join(left, &t.joins[i], ctes)?;
-                }
-                Ok(left)
-            }
-        }
+        self.plan_joins(left, &t.joins, ctes)
+    }
+
+    fn plan_joins(
+        &self,
+        left: LogicalPlan,
+        joins: &[Join],
+        ctes: &mut HashMap<String, LogicalPlan>,
+    ) -> Result<LogicalPlan> {
+        joins.iter().try_fold(left, |left, join| {
+            self.parse_relation_join(left, join, ctes)
+        })
     }
 
     fn parse_relation_join(
@@ -427,6 +534,12 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         join: &Join,
         ctes: &mut HashMap<String, LogicalPlan>,
     ) -> Result<LogicalPlan> {
+        // `CROSS JOIN UNNEST(t.list)` unnests a list of the left relation
+        if let (JoinOperator::CrossJoin, Some((args, alias))) =
+            (&join.join_operator, unnest_args(&join.relation))
+        {
+            return self.unnest_to_plan(left, args, alias);
+        }
         let right = self.create_relation(&join.relation, ctes)?;
         match &join.join_operator {
             JoinOperator::LeftOuter(constraint) => {
@@ -596,6 +709,17 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         ctes: &mut HashMap<String, LogicalPlan>,
     ) -> Result<LogicalPlan> {
         let (plan, alias) = match relation {
+            TableFactor::Table { .. } if unnest_args(relation).is_some() => {
+                let (args, alias) = unnest_args(relation).unwrap();
+                let input = LogicalPlanBuilder::empty(true).build()?;
+                // the alias names the unnested column, it is applied here
+                (self.unnest_to_plan(input, args, alias)?, &None)
+            }
+            TableFactor::Table {
+                name, alias, args, ..
+            } if !args.is_empty() => {
+                (self.table_function_to_plan(name, args, alias)?, alias)
+            }
             TableFactor::Table { name, alias, .. } => {
                 let table_name = name.to_string();
                 let cte = ctes.get(&table_name);
@@ -653,7 +777,6 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             TableFactor::NestedJoin(table_with_joins) => {
                 (self.plan_table_with_joins(table_with_joins, ctes)?, &None)
             }
-            // @todo Support TableFactory::TableFunction?
             _ => {
                 return Err(DataFusionError::NotImplemented(format!(
                     "Unsupported ast node {:?} in create_relation",
@@ -698,77 +821,20 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     ) -> Result<LogicalPlan> {
         let plans = self.plan_from_tables(&select.from, ctes)?;
 
+        // the relations are cross joined below the `WHERE` clause, so the
+        // unoptimized plan of `FROM a, b WHERE a.x = b.x` is a filter of a
+        // cross join, that the `EliminateCrossJoin` optimizer rule turns
+        // into an equi-join
+        let plan = cross_join_plans(plans)?;
         let plan = match &select.selection {
             Some(predicate_expr) => {
-                // build join schema
-                let mut fields = vec![];
-                for plan in &plans {
-                    fields.extend_from_slice(plan.schema().fields());
-                }
-                let join_schema = DFSchema::new(fields)?;
-
-                let filter_expr = self.sql_to_rex(predicate_expr, &join_schema)?;
-
-                // look for expressions of the form `<column> = <column>`
-                let mut possible_join_keys = vec![];
-                extract_possible_join_keys(&filter_expr, &mut possible_join_keys)?;
-
-                let mut all_join_keys = HashSet::new();
-                let mut left = plans[0].clone();
-                for right in plans.iter().skip(1) {
-                    let left_schema = left.schema();
-                    let right_schema = right.schema();
-                    let mut join_keys = vec![];
-                    for (l, r) in &possible_join_keys {
-                        if left_schema.field_from_column(l).is_ok()
-                            && right_schema.field_from_column(r).is_ok()
-                        {
-                            join_keys.push((l.clone(), r.clone()));
-                        } else if left_schema.field_from_column(r).is_ok()
-                            && right_schema.field_from_column(l).is_ok()
-                        {
-                            join_keys.push((r.clone(), l.clone()));
-                        }
-                    }
-                    if join_keys.is_empty() {
-                        left =
-                            LogicalPlanBuilder::from(left).cross_join(right)?.build()?;
-                    } else {
-                        let left_keys: Vec<Column> =
-                            join_keys.iter().map(|(l, _)| l.clone()).collect();
-                        let right_keys: Vec<Column> =
-                            join_keys.iter().map(|(_, r)| r.clone()).collect();
-                        let builder = LogicalPlanBuilder::from(left);
-                        left = builder
-                            .join(right, JoinType::Inner, (left_keys, right_keys))?
-                            .build()?;
-                    }
-
-                    all_join_keys.extend(join_keys);
-                }
-
-                // remove join expressions from filter
-                match remove_join_expressions(&filter_expr, &all_join_keys)? {
-                    Some(filter_expr) => {
-                        LogicalPlanBuilder::from(left).filter(filter_expr)?.build()
-                    }
-                    _ => Ok(left),
-                }
-            }
-            None => {
-                if plans.len() == 1 {
-                    Ok(plans[0].clone())
-                } else {
-                    let mut left = plans[0].clone();
-                    for right in plans.iter().skip(1) {
-                        left =
-                            LogicalPlanBuilder::from(left).cross_join(right)?.build()?;
-                    }
-                    Ok(left)
-                }
+                let filter_expr = self.sql_to_rex(predicate_expr, plan.schema())?;
+                LogicalPlanBuilder::from(plan)
+                    .filter(filter_expr)?
+                    .build()?
             }
+            None => plan,
         };
-        let plan = plan?;
 
         // The SELECT expressions, with wildcards expanded.
         let select_exprs = self.prepare_select_exprs(&plan, select)?;
@@ -1164,6 +1230,307 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
+    /// Plans a call to a table function in the FROM clause, such as
+    /// `generate_series(1, 10)`. The table it creates must have been resolved
+    /// before planning, see [`Self::table_function_calls`].
+    fn table_function_to_plan(
+        &self,
+        name: &ObjectName,
+        args: &[FunctionArg],
+        alias: &Option<TableAlias>,
+    ) -> Result<LogicalPlan> {
+        let call = self.table_function_call(name, args)?;
+        let provider = self
+            .schema_provider
+            .get_table_function_provider(&call)
+            .ok_or_else(|| {
+                DataFusionError::Plan(format!("Table function '{}' not found", call.name))
+            })?;
+        let table_name = alias
+            .as_ref()
+            .map(|a| a.name.value.clone())
+            .unwrap_or(call.name);
+        LogicalPlanBuilder::scan(table_name, provider, None)?.build()
+    }
+
+    /// Evaluates the arguments of a call to a table function, which must be
+    /// constant expressions
+    fn table_function_call(
+        &self,
+        name: &ObjectName,
+        args: &[FunctionArg],
+    ) -> Result<TableFunctionCall> {
+        let function_name = match name.0.as_slice() {
+            [ident] if ident.quote_style.is_none() => ident.value.to_ascii_lowercase(),
+            [ident] => ident.value.clone(),
+            _ => name.to_string(),
+        };
+
+        let schema = DFSchema::empty();
+        let mut const_evaluator = ConstEvaluator::new(&ExecutionProps::new());
+        let args = args
+            .iter()
+            .map(|arg| {
+                match self
+                    .sql_fn_arg_to_logical_expr(arg, &schema)?
+                    .rewrite(&mut const_evaluator)?
+                {
+                    Expr::Literal(value) => Ok(value),
+                    other => Err(DataFusionError::Plan(format!(
+                        "Arguments of table function '{}' must be constants, got {:?}",
+                        function_name, other
+                    ))),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(TableFunctionCall {
+            name: function_name,
+            args,
+        })
+    }
+
+    /// Returns the calls to table functions in `statement`. Creating their
+    /// tables may require IO, so they are resolved asynchronously before
+    /// planning and passed to the planner by the [`ContextProvider`].
+    pub fn table_function_calls(
+        &self,
+        statement: &DFStatement,
+    ) -> Result<Vec<TableFunctionCall>> {
+        let mut calls = vec![];
+        if let DFStatement::Statement(statement) = statement {
+            self.collect_statement_calls(statement, &mut calls)?;
+        }
+        Ok(calls)
+    }
+
+    fn collect_statement_calls(
+        &self,
+        statement: &Statement,
+        calls: &mut Vec<TableFunctionCall>,
+    ) -> Result<()> {
+        match statement {
+            Statement::Query(query)
+            | Statement::CreateTable {
+                query: Some(query), ..
+            } => self.collect_query_calls(query, calls),
+            Statement::Explain { statement, .. }
+            | Statement::Prepare { statement, .. } => {
+                self.collect_statement_calls(statement, calls)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn collect_query_calls(
+        &self,
+        query: &Query,
+        calls: &mut Vec<TableFunctionCall>,
+    ) -> Result<()> {
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                self.collect_query_calls(&cte.query, calls)?;
+            }
+        }
+        self.collect_set_expr_calls(&query.body, calls)?;
+        for order_by in &query.order_by {
+            self.collect_expr_calls(&order_by.expr, calls)?;
+        }
+        query
+            .limit
+            .iter()
+            .try_for_each(|limit| self.collect_expr_calls(limit, calls))
+    }
+
+    fn collect_set_expr_calls(
+        &self,
+        set_expr: &SetExpr,
+        calls: &mut Vec<TableFunctionCall>,
+    ) -> Result<()> {
+        match set_expr {
+            SetExpr::Select(select) => {
+                for t in &select.from {
+                    self.collect_table_with_joins_calls(t, calls)?;
+                }
+                for item in &select.projection {
+                    match item {
+                        SelectItem::UnnamedExpr(expr)
+                        | SelectItem::ExprWithAlias { expr, .. } => {
+                            self.collect_expr_calls(expr, calls)?
+                        }
+                        SelectItem::Wildcard | SelectItem::QualifiedWildcard(_) => {}
+                    }
+                }
+                select
+                    .selection
+                    .iter()
+                    .chain(&select.group_by)
+                    .chain(&select.having)
+                    .try_for_each(|expr| self.collect_expr_calls(expr, calls))
+            }
+            SetExpr::Query(query) => self.collect_query_calls(query, calls),
+            SetExpr::SetOperation { left, right, .. } => {
+                self.collect_set_expr_calls(left, calls)?;
+                self.collect_set_expr_calls(right, calls)
+            }
+            SetExpr::Values(values) => values
+                .0
+                .iter()
+                .flatten()
+                .try_for_each(|expr| self.collect_expr_calls(expr, calls)),
+            _ => Ok(()),
+        }
+    }
+
+    fn collect_table_with_joins_calls(
+        &self,
+        t: &TableWithJoins,
+        calls: &mut Vec<TableFunctionCall>,
+    ) -> Result<()> {
+        std::iter::once(&t.relation)
+            .chain(t.joins.iter().map(|join| &join.relation))
+            .try_for_each(|relation| match relation {
+                TableFactor::Table { name, args, .. }
+                    if !args.is_empty() && unnest_args(relation).is_none() =>
+                {
+                    calls.push(self.table_function_call(name, args)?);
+                    Ok(())
+                }
+                TableFactor::Derived { subquery, .. } => {
+                    self.collect_query_calls(subquery, calls)
+                }
+                TableFactor::NestedJoin(t) => {
+                    self.collect_table_with_joins_calls(t, calls)
+                }
+                _ => Ok(()),
+            })?;
+        t.joins
+            .iter()
+            .try_for_each(|join| match &join.join_operator {
+                JoinOperator::Inner(JoinConstraint::On(expr))
+                | JoinOperator::LeftOuter(JoinConstraint::On(expr))
+                | JoinOperator::RightOuter(JoinConstraint::On(expr))
+                | JoinOperator::FullOuter(JoinConstraint::On(expr)) => {
+                    self.collect_expr_calls(expr, calls)
+                }
+                _ => Ok(()),
+            })
+    }
+
+    /// Collects the calls to table functions in the subqueries of `expr`
+    fn collect_expr_calls(
+        &self,
+        expr: &SQLExpr,
+        calls: &mut Vec<TableFunctionCall>,
+    ) -> Result<()> {
+        match expr {
+            SQLExpr::Subquery(query) | SQLExpr::Exists(query) => {
+                self.collect_query_calls(query, calls)
+            }
+            SQLExpr::InSubquery { expr, subquery, .. } => {
+                self.collect_expr_calls(expr, calls)?;
+                self.collect_query_calls(subquery, calls)
+            }
+            SQLExpr::IsNull(expr)
+            | SQLExpr::IsNotNull(expr)
+            | SQLExpr::Nested(expr)
+            | SQLExpr::UnaryOp { expr, .. }
+            | SQLExpr::Cast { expr, .. }
+            | SQLExpr::TryCast { expr, .. }
+            | SQLExpr::Extract { expr, .. } => self.collect_expr_calls(expr, calls),
+            SQLExpr::IsDistinctFrom(left, right)
+            | SQLExpr::IsNotDistinctFrom(left, right)
+            | SQLExpr::BinaryOp { left, right, .. } => {
+                self.collect_expr_calls(left, calls)?;
+                self.collect_expr_calls(right, calls)
+            }
+            SQLExpr::Between {
+                expr, low, high, ..
+            } => [expr, low, high]
+                .iter()
+                .try_for_each(|expr| self.collect_expr_calls(expr, calls)),
+            SQLExpr::InList { expr, list, .. } => iter::once(expr.as_ref())
+                .chain(list)
+                .try_for_each(|expr| self.collect_expr_calls(expr, calls)),
+            SQLExpr::Trim { expr, trim_where } => iter::once(expr.as_ref())
+                .chain(trim_where.iter().map(|(_, expr)| expr.as_ref()))
+                .try_for_each(|expr| self.collect_expr_calls(expr, calls)),
+            SQLExpr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => operand
+                .iter()
+                .chain(else_result)
+                .map(|expr| expr.as_ref())
+                .chain(conditions)
+                .chain(results)
+                .try_for_each(|expr| self.collect_expr_calls(expr, calls)),
+            SQLExpr::Function(function) => {
+                for arg in &function.args {
+                    match arg {
+                        FunctionArg::Named { arg, .. } | FunctionArg::Unnamed(arg) => {
+                            self.collect_expr_calls(arg, calls)?
+                        }
+                    }
+                }
+                match &function.over {
+                    Some(window) => window
+                        .partition_by
+                        .iter()
+                        .chain(window.order_by.iter().map(|order_by| &order_by.expr))
+                        .try_for_each(|expr| self.collect_expr_calls(expr, calls)),
+                    None => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Plans `UNNEST(list)` over `input`: every row of `input` is repeated once
+    /// per element of the list, which is added as a column named after the
+    /// column alias if any, `unnest` otherwise
+    fn unnest_to_plan(
+        &self,
+        input: LogicalPlan,
+        args: &[FunctionArg],
+        alias: &Option<TableAlias>,
+    ) -> Result<LogicalPlan> {
+        let arg = match args {
+            [FunctionArg::Unnamed(arg)] => arg,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "UNNEST expects a single list argument, got {} arguments",
+                    args.len()
+                )))
+            }
+        };
+        let expr = self.sql_to_rex(arg, input.schema())?;
+        let output = match alias {
+            Some(TableAlias { name, columns }) => {
+                let column = match columns.as_slice() {
+                    [] => "unnest".to_string(),
+                    [column] => column.value.clone(),
+                    _ => {
+                        return Err(DataFusionError::Plan(format!(
+                            "UNNEST produces a single column but {} names given as column alias",
+                            columns.len()
+                        )))
+                    }
+                };
+                Column {
+                    relation: Some(name.value.clone()),
+                    name: column,
+                }
+            }
+            None => Column::from_name("unnest"),
+        };
+        LogicalPlanBuilder::from(input)
+            .unnest(expr, output)?
+            .build()
+    }
+
     fn sql_fn_arg_to_logical_expr(
         &self,
         sql: &FunctionArg,
@@ -1210,10 +1577,14 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             ))),
         }?;
 
+        let mut left = self.sql_expr_to_logical_expr(left, schema)?;
+        let mut right = self.sql_expr_to_logical_expr(right, schema)?;
+        infer_placeholder_type(&mut left, &right, schema);
+        infer_placeholder_type(&mut right, &left, schema);
         Ok(Expr::BinaryExpr {
-            left: Box::new(self.sql_expr_to_logical_expr(left, schema)?),
+            left: Box::new(left),
             op: operator,
-            right: Box::new(self.sql_expr_to_logical_expr(right, schema)?),
+            right: Box::new(right),
         })
     }
 
@@ -1270,6 +1641,9 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                             Ok(Expr::Literal(ScalarValue::Utf8(None)))
                         }
                         SQLExpr::Value(Value::Boolean(n)) => Ok(lit(*n)),
+                        SQLExpr::Identifier(id) if is_placeholder(id) => {
+                            self.placeholder_to_expr(&id.value)
+                        }
                         SQLExpr::UnaryOp { ref op, ref expr } => {
                             self.parse_sql_unary_op(op, expr, &schema)
                         }
@@ -1318,7 +1692,9 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             ),
 
             SQLExpr::Identifier(ref id) => {
-                if id.value.starts_with('@') {
+                if is_placeholder(id) {
+                    self.placeholder_to_expr(&id.value)
+                } else if id.value.starts_with('@') {
                     let var_names = vec![id.value.clone()];
                     Ok(Expr::ScalarVariable(var_names))
                 } else {
@@ -1410,7 +1786,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 ref data_type,
             } => Ok(Expr::Cast {
                 expr: Box::new(self.sql_expr_to_logical_expr(expr, schema)?),
-                data_type: convert_data_type(data_type)?,
+                data_type: self.convert_data_type(data_type)?,
             }),
 
             SQLExpr::TryCast {
@@ -1418,7 +1794,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 ref data_type,
             } => Ok(Expr::TryCast {
                 expr: Box::new(self.sql_expr_to_logical_expr(expr, schema)?),
-                data_type: convert_data_type(data_type)?,
+                data_type: self.convert_data_type(data_type)?,
             }),
 
             SQLExpr::TypedString {
@@ -1426,7 +1802,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 ref value,
             } => Ok(Expr::Cast {
                 expr: Box::new(lit(&**value)),
-                data_type: convert_data_type(data_type)?,
+                data_type: self.convert_data_type(data_type)?,
             }),
 
             SQLExpr::IsNull(ref expr) => Ok(Expr::IsNull(Box::new(
@@ -1458,25 +1834,44 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 ref negated,
                 ref low,
                 ref high,
-            } => Ok(Expr::Between {
-                expr: Box::new(self.sql_expr_to_logical_expr(expr, schema)?),
-                negated: *negated,
-                low: Box::new(self.sql_expr_to_logical_expr(low, schema)?),
-                high: Box::new(self.sql_expr_to_logical_expr(high, schema)?),
-            }),
+            } => {
+                let mut expr = self.sql_expr_to_logical_expr(expr, schema)?;
+                let mut low = self.sql_expr_to_logical_expr(low, schema)?;
+                let mut high = self.sql_expr_to_logical_expr(high, schema)?;
+                // the bounds take the type of the expression, or of each other
+                infer_placeholder_type(&mut low, &expr, schema);
+                infer_placeholder_type(&mut high, &expr, schema);
+                infer_placeholder_type(&mut low, &high, schema);
+                infer_placeholder_type(&mut high, &low, schema);
+                infer_placeholder_type(&mut expr, &low, schema);
+                Ok(Expr::Between {
+                    expr: Box::new(expr),
+                    negated: *negated,
+                    low: Box::new(low),
+                    high: Box::new(high),
+                })
+            }
 
             SQLExpr::InList {
                 ref expr,
                 ref list,
                 ref negated,
             } => {
+                let mut expr = self.sql_expr_to_logical_expr(expr, schema)?;
                 let list_expr = list
                     .iter()
-                    .map(|e| self.sql_expr_to_logical_expr(e, schema))
+                    .map(|e| {
+                        let mut e = self.sql_expr_to_logical_expr(e, schema)?;
+                        infer_placeholder_type(&mut e, &expr, schema);
+                        Ok(e)
+                    })
                     .collect::<Result<Vec<_>>>()?;
+                if let Some(first) = list_expr.first() {
+                    infer_placeholder_type(&mut expr, first, schema);
+                }
 
                 Ok(Expr::InList {
-                    expr: Box::new(self.sql_expr_to_logical_expr(expr, schema)?),
+                    expr: Box::new(expr),
                     list: list_expr,
                     negated: *negated,
                 })
@@ -1527,15 +1922,47 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                     }
                 };
 
+                let (function, aggregate_filter, aggregate_order_by) =
+                    self.split_aggregate_clauses(function, schema)?;
+                let function = &function;
+                let check_no_aggregate_clauses = || {
+                    if aggregate_filter.is_some() || !aggregate_order_by.is_empty() {
+                        Err(DataFusionError::Plan(format!(
+                            "FILTER and ORDER BY clauses are only supported in calls of aggregate functions, but '{}' is not called as one",
+                            name
+                        )))
+                    } else {
+                        Ok(())
+                    }
+                };
+
                 // first, scalar built-in
                 if let Ok(fun) = functions::BuiltinScalarFunction::from_str(&name) {
-                    let args = self.function_args_to_expr(function, schema)?;
+                    check_no_aggregate_clauses()?;
+                    let args = self.function_args_to_expr(
+                        &name,
+                        &functions::signature(&fun),
+                        function,
+                        schema,
+                    )?;
+
+                    // `now()` returns a UTC timestamp, display it in the session time zone
+                    if fun == functions::BuiltinScalarFunction::Now {
+                        let time_zone = self.schema_provider.get_time_zone();
+                        if time_zone != "UTC" {
+                            return Ok(Expr::Cast {
+                                expr: Box::new(Expr::ScalarFunction { fun, args }),
+                                data_type: self.make_timestamp_tz_type(time_zone)?,
+                            });
+                        }
+                    }
 
                     return Ok(Expr::ScalarFunction { fun, args });
                 };
 
                 // then, window function
                 if let Some(window) = &function.over {
+                    check_no_aggregate_clauses()?;
                     let partition_by = window
                         .partition_by
                         .iter()
@@ -1561,7 +1988,13 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                             }
                         })
                         .transpose()?;
-                    let fun = window_functions::WindowFunction::from_str(&name)?;
+                    let fun = match window_functions::WindowFunction::from_str(&name) {
+                        Ok(fun) => fun,
+                        Err(e) => match self.schema_provider.get_window_meta(&name) {
+                            Some(fm) => window_functions::WindowFunction::WindowUDF(fm),
+                            None => return Err(e),
+                        },
+                    };
                     match fun {
                         window_functions::WindowFunction::AggregateFunction(
                             aggregate_fun,
@@ -1583,11 +2016,31 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                         window_functions::WindowFunction::BuiltInWindowFunction(
                             window_fun,
                         ) => {
-                            return Ok(Expr::WindowFunction {
-                                fun: window_functions::WindowFunction::BuiltInWindowFunction(
+                            let fun =
+                                window_functions::WindowFunction::BuiltInWindowFunction(
                                     window_fun,
-                                ),
-                                args: self.function_args_to_expr(function, schema)?,
+                                );
+                            let signature = window_functions::signature(&fun);
+                            return Ok(Expr::WindowFunction {
+                                fun,
+                                args: self.function_args_to_expr(
+                                    &name, &signature, function, schema,
+                                )?,
+                                partition_by,
+                                order_by,
+                                window_frame,
+                            });
+                        }
+                        window_functions::WindowFunction::WindowUDF(fm) => {
+                            let args = self.function_args_to_expr(
+                                &name,
+                                &fm.signature,
+                                function,
+                                schema,
+                            )?;
+                            return Ok(Expr::WindowFunction {
+                                fun: window_functions::WindowFunction::WindowUDF(fm),
+                                args,
                                 partition_by,
                                 order_by,
                                 window_frame,
@@ -1603,20 +2056,39 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                         fun,
                         distinct: function.distinct,
                         args,
+                        filter: aggregate_filter,
+                        order_by: aggregate_order_by,
                     });
                 };
 
                 // finally, user-defined functions (UDF) and UDAF
                 match self.schema_provider.get_function_meta(&name) {
                     Some(fm) => {
-                        let args = self.function_args_to_expr(function, schema)?;
+                        check_no_aggregate_clauses()?;
+                        let args = self.function_args_to_expr(
+                            &name,
+                            &fm.signature,
+                            function,
+                            schema,
+                        )?;
 
                         Ok(Expr::ScalarUDF { fun: fm, args })
                     }
                     None => match self.schema_provider.get_aggregate_meta(&name) {
                         Some(fm) => {
-                            let args = self.function_args_to_expr(function, schema)?;
-                            Ok(Expr::AggregateUDF { fun: fm, args })
+                            let args = self.function_args_to_expr(
+                                &name,
+                                &fm.signature,
+                                function,
+                                schema,
+                            )?;
+                            Ok(Expr::AggregateUDF {
+                                fun: fm,
+                                args,
+                                distinct: function.distinct,
+                                filter: aggregate_filter,
+                                order_by: aggregate_order_by,
+                            })
                         }
                         _ => Err(DataFusionError::Plan(format!(
                             "Invalid function '{}'",
@@ -1635,16 +2107,86 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
+    /// Separates the FILTER and ORDER BY clauses of an aggregate call, which
+    /// [`DFParser`] passes as named arguments, from the other arguments
+    #[allow(clippy::type_complexity)]
+    fn split_aggregate_clauses(
+        &self,
+        function: &sqlparser::ast::Function,
+        schema: &DFSchema,
+    ) -> Result<(sqlparser::ast::Function, Option<Box<Expr>>, Vec<Expr>)> {
+        let mut args = Vec::with_capacity(function.args.len());
+        let mut filter = None;
+        let mut order_by = vec![];
+        for arg in &function.args {
+            match arg {
+                FunctionArg::Named { name, arg }
+                    if name.quote_style.is_none()
+                        && name.value == AGGREGATE_FILTER_ARG =>
+                {
+                    filter = Some(Box::new(self.sql_expr_to_logical_expr(arg, schema)?));
+                }
+                FunctionArg::Named { name, arg }
+                    if name.quote_style.is_none()
+                        && name.value.starts_with(AGGREGATE_ORDER_BY_ARG) =>
+                {
+                    let options = &name.value[AGGREGATE_ORDER_BY_ARG.len()..];
+                    let nulls_first = if options.ends_with(" NULLS FIRST") {
+                        Some(true)
+                    } else if options.ends_with(" NULLS LAST") {
+                        Some(false)
+                    } else {
+                        None
+                    };
+                    let expr = match arg {
+                        SQLExpr::Nested(expr) => expr.as_ref().clone(),
+                        expr => expr.clone(),
+                    };
+                    order_by.push(self.order_by_to_sort_expr(
+                        &OrderByExpr {
+                            expr,
+                            asc: Some(options.starts_with("ASC")),
+                            nulls_first,
+                        },
+                        schema,
+                    )?);
+                }
+                _ => args.push(arg.clone()),
+            }
+        }
+        let function = sqlparser::ast::Function {
+            args,
+            ..function.clone()
+        };
+        Ok((function, filter, order_by))
+    }
+
+    /// Plans the arguments of a call of the function `name`, whose arguments may be
+    /// passed by name to the named parameters of its `signature`
     fn function_args_to_expr(
         &self,
+        name: &str,
+        signature: &functions::Signature,
         function: &sqlparser::ast::Function,
         schema: &DFSchema,
     ) -> Result<Vec<Expr>> {
-        function
+        let args = function
             .args
             .iter()
-            .map(|a| self.sql_fn_arg_to_logical_expr(a, schema))
-            .collect::<Result<Vec<Expr>>>()
+            .map(|a| match a {
+                FunctionArg::Named { name, arg } => {
+                    let name = match name.quote_style {
+                        Some(_) => name.value.clone(),
+                        None => name.value.to_ascii_lowercase(),
+                    };
+                    Ok((Some(name), self.sql_expr_to_logical_expr(arg, schema)?))
+                }
+                FunctionArg::Unnamed(arg) => {
+                    Ok((None, self.sql_expr_to_logical_expr(arg, schema)?))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        signature.arrange_arguments(name, args, |value| Expr::Literal(value.clone()))
     }
 
     fn aggregate_fn_to_expr(
@@ -1666,7 +2208,13 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 })
                 .collect::<Result<Vec<Expr>>>()
         } else {
-            self.function_args_to_expr(function, schema)
+            let name = fun.to_string().to_lowercase();
+            self.function_args_to_expr(
+                &name,
+                &aggregates::signature(fun),
+                function,
+                schema,
+            )
         }
     }
 
@@ -1830,11 +2378,16 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
 
         if result_month != 0 {
             return Ok(Expr::Literal(ScalarValue::IntervalYearMonth(Some(
-                result_month as i32,
+                i32::try_from(result_month).map_err(|_| {
+                    DataFusionError::NotImplemented(format!(
+                        "Interval field value out of range: {:?}",
+                        value
+                    ))
+                })?,
             ))));
         }
 
-        let result: i64 = (result_days << 32) | result_millis;
+        let result = make_interval_day_time(result_days, result_millis)?;
         Ok(Expr::Literal(ScalarValue::IntervalDayTime(Some(result))))
     }
 
@@ -1852,12 +2405,103 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                         .to_string(),
                 ))
             }
+        } else if variable.as_str().eq_ignore_ascii_case("all") {
+            let mut entries = ExecutionConfig::valid_entries()
+                .into_iter()
+                .map(|(_, entry)| entry)
+                .collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.name.cmp(&b.name));
+            self.config_settings_to_plan(entries)
         } else {
-            Err(DataFusionError::NotImplemented(format!(
-                "SHOW {} not implemented. Supported syntax: SHOW <TABLES>",
-                variable
-            )))
+            match ExecutionConfig::valid_entries().remove(&variable.to_lowercase()) {
+                Some(entry) => self.config_settings_to_plan(vec![entry]),
+                None => Err(DataFusionError::NotImplemented(format!(
+                    "SHOW {} not implemented. Supported syntax: SHOW <TABLES | ALL | setting>",
+                    variable
+                ))),
+            }
+        }
+    }
+
+    /// Generate a relation of the `name`, current `setting` and `description`
+    /// of the configuration settings `entries`
+    fn config_settings_to_plan(&self, entries: Vec<ConfigEntry>) -> Result<LogicalPlan> {
+        let values = entries
+            .into_iter()
+            .map(|entry| {
+                let setting = self.schema_provider.get_config_setting(&entry.name);
+                vec![
+                    lit(entry.name),
+                    Expr::Literal(ScalarValue::Utf8(setting)),
+                    lit(entry.description),
+                ]
+            })
+            .collect();
+        LogicalPlanBuilder::values(values)?
+            .project(vec![
+                col("column1").alias("name"),
+                col("column2").alias("setting"),
+                col("column3").alias("description"),
+            ])?
+            .build()
+    }
+
+    /// Generate a logical plan from an `EXECUTE` statement, by binding the
+    /// placeholders of the prepared statement to the constant `parameters`
+    fn execute_to_plan(&self, name: &str, parameters: &[SQLExpr]) -> Result<LogicalPlan> {
+        let plan = self
+            .schema_provider
+            .get_prepared_statement(name)
+            .ok_or_else(|| {
+                DataFusionError::Plan(format!(
+                    "Prepared statement '{}' does not exist",
+                    name
+                ))
+            })?;
+
+        let schema = DFSchema::empty();
+        let mut const_evaluator = ConstEvaluator::new(&ExecutionProps::new());
+        let param_values = parameters
+            .iter()
+            .map(|parameter| {
+                match self
+                    .sql_to_rex(parameter, &schema)?
+                    .rewrite(&mut const_evaluator)?
+                {
+                    Expr::Literal(value) => Ok(value),
+                    other => Err(DataFusionError::Plan(format!(
+                        "Parameters of prepared statement '{}' must be constants, got {:?}",
+                        name, other
+                    ))),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        plan.with_param_values(param_values)
+    }
+
+    /// Generate a placeholder `$n`, of the type declared for it in `PREPARE`
+    fn placeholder_to_expr(&self, id: &str) -> Result<Expr> {
+        let index = placeholder_index(id)?;
+        Ok(Expr::Placeholder {
+            id: id.to_owned(),
+            data_type: self.param_types.get(index).cloned(),
+        })
+    }
+
+    /// Generate a logical plan from a `SET` statement
+    fn set_variable_to_plan(&self, statement: &SetVariable) -> Result<LogicalPlan> {
+        let variable = statement.variable.to_lowercase();
+        if !ExecutionConfig::valid_entries().contains_key(&variable) {
+            return Err(DataFusionError::Plan(format!(
+                "Unknown configuration setting '{}'",
+                statement.variable
+            )));
         }
+        Ok(LogicalPlan::SetVariable(PlanSetVariable {
+            variable,
+            value: statement.value.clone(),
+            schema: DFSchemaRef::new(DFSchema::empty()),
+        }))
     }
 
     fn show_columns_to_plan(
@@ -1927,38 +2571,32 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     }
 }
 
-/// Remove join expressions from a filter expression
-fn remove_join_expressions(
-    expr: &Expr,
-    join_columns: &HashSet<(Column, Column)>,
-) -> Result<Option<Expr>> {
-    match expr {
-        Expr::BinaryExpr { left, op, right } => match op {
-            Operator::Eq => match (left.as_ref(), right.as_ref()) {
-                (Expr::Column(l), Expr::Column(r)) => {
-                    if join_columns.contains(&(l.clone(), r.clone()))
-                        || join_columns.contains(&(r.clone(), l.clone()))
-                    {
-                        Ok(None)
-                    } else {
-                        Ok(Some(expr.clone()))
-                    }
-                }
-                _ => Ok(Some(expr.clone())),
-            },
-            Operator::And => {
-                let l = remove_join_expressions(left, join_columns)?;
-                let r = remove_join_expressions(right, join_columns)?;
-                match (l, r) {
-                    (Some(ll), Some(rr)) => Ok(Some(and(ll, rr))),
-                    (Some(ll), _) => Ok(Some(ll)),
-                    (_, Some(rr)) => Ok(Some(rr)),
-                    _ => Ok(None),
-                }
+/// Cross joins `plans` from left to right
+fn cross_join_plans(plans: Vec<LogicalPlan>) -> Result<LogicalPlan> {
+    let mut plans = plans.into_iter();
+    let first = plans.next().ok_or_else(|| {
+        DataFusionError::Internal("Expected at least one relation".to_string())
+    })?;
+    plans.try_fold(first, |left, right| {
+        LogicalPlanBuilder::from(left).cross_join(&right)?.build()
+    })
+}
+
+/// Returns the arguments and the alias of `relation` if it is a call to `UNNEST`
+fn unnest_args(relation: &TableFactor) -> Option<(&[FunctionArg], &Option<TableAlias>)> {
+    match relation {
+        TableFactor::Table {
+            name, alias, args, ..
+        } if !args.is_empty() => match name.0.as_slice() {
+            [ident]
+                if ident.quote_style.is_none()
+                    && ident.value.eq_ignore_ascii_case("unnest") =>
+            {
+                Some((args, alias))
             }
-            _ => Ok(Some(expr.clone())),
+            _ => None,
         },
-        _ => Ok(Some(expr.clone())),
+        _ => None,
     }
 }
 
@@ -2007,27 +2645,45 @@ fn extract_join_keys(
     }
 }
 
-/// Extract join keys from a WHERE clause
-fn extract_possible_join_keys(
-    expr: &Expr,
-    accum: &mut Vec<(Column, Column)>,
-) -> Result<()> {
-    match expr {
-        Expr::BinaryExpr { left, op, right } => match op {
-            Operator::Eq => match (left.as_ref(), right.as_ref()) {
-                (Expr::Column(l), Expr::Column(r)) => {
-                    accum.push((l.clone(), r.clone()));
-                    Ok(())
-                }
-                _ => Ok(()),
-            },
-            Operator::And => {
-                extract_possible_join_keys(left, accum)?;
-                extract_possible_join_keys(right, accum)
-            }
-            _ => Ok(()),
-        },
-        _ => Ok(()),
+/// Returns true if `ident` is a placeholder such as `$1`, which the parser
+/// turns into an unquoted identifier
+fn is_placeholder(ident: &Ident) -> bool {
+    ident.quote_style.is_none() && ident.value.starts_with('$')
+}
+
+/// Normalize an identifier to lowercase, unless it is quoted
+fn normalize_ident(ident: &Ident) -> String {
+    match ident.quote_style {
+        Some(_) => ident.value.clone(),
+        None => ident.value.to_ascii_lowercase(),
+    }
+}
+
+/// Gives an untyped placeholder the type of the expression it is compared
+/// with, e.g. `$1` in `age = $1` takes the type of `age`
+fn infer_placeholder_type(expr: &mut Expr, other: &Expr, schema: &DFSchema) {
+    if let Expr::Placeholder {
+        data_type: data_type @ None,
+        ..
+    } = expr
+    {
+        *data_type = other.get_type(schema).ok();
+    }
+}
+
+/// If `name` is a timestamp type with a time zone, returns the time zone that
+/// qualifies it, which only the rewritten `AT TIME ZONE` does, see
+/// [`TIMESTAMP_WITH_TIME_ZONE_TYPE`]
+fn timestamp_tz_type(name: &[Ident]) -> Option<Option<&str>> {
+    let is_rewritten = |ty: &Ident| {
+        ty.quote_style.is_none() && ty.value == TIMESTAMP_WITH_TIME_ZONE_TYPE
+    };
+    match name {
+        [ty] if is_rewritten(ty) || ty.value.eq_ignore_ascii_case(TIMESTAMP_TZ_TYPE) => {
+            Some(None)
+        }
+        [ty, tz] if is_rewritten(ty) => Some(Some(&tz.value)),
+        _ => None,
     }
 }
 
@@ -2202,6 +2858,95 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn select_at_time_zone() {
+        let sql = "SELECT birth_date AT TIME ZONE 'Europe/Berlin', \
+                   CAST('2020-01-01 00:00:00' AS TIMESTAMP WITH TIME ZONE) FROM person";
+        let expected = "Projection: CAST(#person.birth_date AS Timestamp(Nanosecond, Some(\"Europe/Berlin\"))), \
+            CAST(Utf8(\"2020-01-01 00:00:00\") AS Timestamp(Nanosecond, Some(\"UTC\")))\
+            \n  TableScan: person projection=None";
+
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_time_zone_qualified_type() {
+        // only AT TIME ZONE can qualify the type with a time zone
+        let sql = "SELECT CAST(birth_date AS TIMESTAMPTZ.\"Europe/Berlin\") FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert!(
+            format!("{:?}", err).contains("is not implemented"),
+            "unexpected error: {:?}",
+            err
+        );
+
+        let sql = "SELECT CAST(birth_date AS \"TIMESTAMP WITH TIME ZONE\".\"Europe/Berlin\") FROM person";
+        logical_plan(sql).expect_err("query should have failed");
+    }
+
+    #[test]
+    fn select_at_invalid_time_zone() {
+        let sql = "SELECT birth_date AT TIME ZONE 'Mars/Olympus_Mons' FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            r#"Plan("Invalid time zone 'Mars/Olympus_Mons'")"#,
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn set_variable() {
+        quick_test(
+            "SET Datafusion.Execution.Batch_Size TO 4096",
+            "SetVariable: datafusion.execution.batch_size = \"4096\"",
+        );
+
+        let err = logical_plan("SET datafusion.unknown = 1")
+            .expect_err("query should have failed");
+        assert_eq!(
+            r#"Plan("Unknown configuration setting 'datafusion.unknown'")"#,
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn prepare_statement() {
+        quick_test(
+            "PREPARE q(INT, DOUBLE) AS SELECT id FROM person WHERE age = $1 AND salary > $2",
+            "Prepare: \"q\" [Int32, Float64]\
+            \n  Projection: #person.id\
+            \n    Filter: #person.age = $1 AND #person.salary > $2\
+            \n      TableScan: person projection=None",
+        );
+    }
+
+    #[test]
+    fn placeholder_type_inference() {
+        let plan = logical_plan(
+            "SELECT id FROM person \
+             WHERE age = $1 AND salary BETWEEN $2 AND 1000 AND state IN ($3, 'CO')",
+        )
+        .unwrap();
+        let types = plan.get_parameter_types().unwrap();
+        assert_eq!(types["$1"], Some(DataType::Int32));
+        assert_eq!(types["$2"], Some(DataType::Float64));
+        assert_eq!(types["$3"], Some(DataType::Utf8));
+
+        // the declared types take precedence over the inferred ones
+        let plan =
+            logical_plan("PREPARE q(BIGINT) AS SELECT id FROM person WHERE $1 = age")
+                .unwrap();
+        let types = plan.get_parameter_types().unwrap();
+        assert_eq!(types["$1"], Some(DataType::Int64));
+
+        let err = logical_plan("SELECT id FROM person WHERE age = $0")
+            .expect_err("query should have failed");
+        assert_eq!(
+            r#"Plan("Invalid placeholder $0, expected one of $1, $2, ...")"#,
+            format!("{:?}", err)
+        );
+    }
+
     #[test]
     fn select_all_boolean_operators() {
         let sql = "SELECT age, first_name, last_name \
@@ -2303,6 +3048,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cross_join_unnest() {
+        let sql = "SELECT p.id, u.x
+                   FROM person p CROSS JOIN UNNEST(array(p.age, p.age)) AS u (x)";
+        let expected = "Projection: #p.id, #u.x\
+                        \n  Unnest: array(#p.age, #p.age) AS u.x\
+                        \n    TableScan: p projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn lateral_unnest() {
+        let sql = "SELECT id, unnest FROM person, UNNEST(array(person.age))";
+        let expected = "Projection: #person.id, #unnest\
+                        \n  Unnest: array(#person.age) AS unnest\
+                        \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn lateral_unnest_after_several_relations() {
+        let sql = "SELECT person.id, lineitem.price, unnest \
+                   FROM person, lineitem, UNNEST(array(person.age))";
+        let expected = "Projection: #person.id, #lineitem.price, #unnest\
+                        \n  Unnest: array(#person.age) AS unnest\
+                        \n    CrossJoin:\
+                        \n      TableScan: person projection=None\
+                        \n      TableScan: lineitem projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn unnest_without_input() {
+        let sql = "SELECT * FROM UNNEST(array(1, 2)) AS u (x)";
+        let expected = "Projection: #u.x\
+                        \n  Unnest: array(Int64(1), Int64(2)) AS u.x\
+                        \n    EmptyRelation";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn unnest_non_list() {
+        let sql = "SELECT * FROM person CROSS JOIN UNNEST(person.id)";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"UNNEST expects a list argument, got #person.id of type UInt32\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn table_function_calls_in_subqueries() {
+        let sql = "WITH s AS (SELECT * FROM f(1)) \
+                   SELECT (SELECT MAX(x) FROM f(2)), abs((SELECT 1 FROM f(3))) \
+                   FROM s JOIN (SELECT * FROM f(4)) AS t ON EXISTS (SELECT * FROM f(5)) \
+                   WHERE id IN (SELECT * FROM f(6)) OR NOT id BETWEEN 0 AND (SELECT 1 FROM f(7)) \
+                   GROUP BY id HAVING CASE WHEN (SELECT 1 FROM f(8)) > 0 THEN true END \
+                   ORDER BY (SELECT 1 FROM f(9))";
+        let statement = DFParser::parse_sql(sql).unwrap().remove(0);
+        let calls = SqlToRel::new(&MockContextProvider {})
+            .table_function_calls(&statement)
+            .unwrap();
+        let args: Vec<_> = calls
+            .iter()
+            .map(|call| {
+                assert_eq!(call.name, "f");
+                call.args[0].to_string()
+            })
+            .collect();
+        assert_eq!(args, vec!["1", "4", "5", "2", "3", "6", "7", "8", "9"]);
+    }
+
     #[test]
     fn select_with_having() {
         let sql = "SELECT id, age
@@ -2350,6 +3167,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn select_aggregate_clause_arguments() {
+        // the arguments the clauses are passed as can't be written in SQL
+        for sql in [
+            "SELECT sum(age, __aggregate_filter => age > 1) FROM person",
+            "SELECT sum(age, \"FILTER WHERE\" => age > 1) FROM person",
+        ] {
+            let err = logical_plan(sql).expect_err("query should have failed");
+            assert!(
+                err.to_string().contains("has no parameter named"),
+                "unexpected error: {}",
+                err
+            );
+        }
+    }
+
     #[test]
     fn select_aggregate_with_having_that_reuses_aggregate() {
         let sql = "SELECT MAX(age)
@@ -3200,6 +4033,20 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn equijoin_implicit_syntax() {
+        // the equi-join is planned by the `EliminateCrossJoin` optimizer rule
+        let sql = "SELECT id, order_id \
+            FROM person, orders \
+            WHERE id = customer_id AND order_id > 1";
+        let expected = "Projection: #person.id, #orders.order_id\
+        \n  Filter: #person.id = #orders.customer_id AND #orders.order_id > Int64(1)\
+        \n    CrossJoin:\
+        \n      TableScan: person projection=None\
+        \n      TableScan: orders projection=None";
+        quick_test(sql, expected);
+    }
+
     #[test]
     fn equijoin_unsupported_expression() {
         let sql = "SELECT id, order_id \
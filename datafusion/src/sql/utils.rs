@@ -61,6 +61,18 @@ pub(crate) fn find_column_exprs(exprs: &[Expr]) -> Vec<Expr> {
     find_exprs_in_exprs(exprs, &|nested_expr| matches!(nested_expr, Expr::Column(_)))
 }
 
+/// Collect all deeply nested `Expr::ScalarUDF` calls of async UDFs whose
+/// arguments don't call any async UDF themselves. They are returned in order of
+/// appearance (depth first), with duplicates omitted.
+pub(crate) fn find_async_udf_exprs(exprs: &[Expr]) -> Vec<Expr> {
+    find_exprs_in_exprs(exprs, &|nested_expr| match nested_expr {
+        Expr::ScalarUDF { fun, args } => {
+            fun.async_fun.is_some() && find_async_udf_exprs(args).is_empty()
+        }
+        _ => false,
+    })
+}
+
 /// Search the provided `Expr`'s, and all of their nested `Expr`, for any that
 /// pass the provided test. The returned `Expr`'s are deduplicated and returned
 /// in order of appearance (depth first).
@@ -218,6 +230,8 @@ where
                 fun,
                 args,
                 distinct,
+                filter,
+                order_by,
             } => Ok(Expr::AggregateFunction {
                 fun: fun.clone(),
                 args: args
@@ -225,6 +239,14 @@ where
                     .map(|e| clone_with_replacement(e, replacement_fn))
                     .collect::<Result<Vec<Expr>>>()?,
                 distinct: *distinct,
+                filter: filter
+                    .as_ref()
+                    .map(|e| clone_with_replacement(e, replacement_fn).map(Box::new))
+                    .transpose()?,
+                order_by: order_by
+                    .iter()
+                    .map(|e| clone_with_replacement(e, replacement_fn))
+                    .collect::<Result<Vec<_>>>()?,
             }),
             Expr::WindowFunction {
                 fun,
@@ -248,12 +270,27 @@ where
                     .collect::<Result<Vec<_>>>()?,
                 window_frame: *window_frame,
             }),
-            Expr::AggregateUDF { fun, args } => Ok(Expr::AggregateUDF {
+            Expr::AggregateUDF {
+                fun,
+                args,
+                distinct,
+                filter,
+                order_by,
+            } => Ok(Expr::AggregateUDF {
                 fun: fun.clone(),
                 args: args
                     .iter()
                     .map(|e| clone_with_replacement(e, replacement_fn))
                     .collect::<Result<Vec<Expr>>>()?,
+                distinct: *distinct,
+                filter: filter
+                    .as_ref()
+                    .map(|e| clone_with_replacement(e, replacement_fn).map(Box::new))
+                    .transpose()?,
+                order_by: order_by
+                    .iter()
+                    .map(|e| clone_with_replacement(e, replacement_fn))
+                    .collect::<Result<Vec<_>>>()?,
             }),
             Expr::Alias(nested_expr, alias_name) => Ok(Expr::Alias(
                 Box::new(clone_with_replacement(&**nested_expr, replacement_fn)?),
@@ -366,9 +403,10 @@ where
                 asc: *asc,
                 nulls_first: *nulls_first,
             }),
-            Expr::Column { .. } | Expr::Literal(_) | Expr::ScalarVariable(_) => {
-                Ok(expr.clone())
-            }
+            Expr::Column { .. }
+            | Expr::Literal(_)
+            | Expr::Placeholder { .. }
+            | Expr::ScalarVariable(_) => Ok(expr.clone()),
             Expr::Wildcard => Ok(Expr::Wildcard),
             Expr::GetIndexedField { expr, key } => Ok(Expr::GetIndexedField {
                 expr: Box::new(clone_with_replacement(expr.as_ref(), replacement_fn)?),
@@ -31,7 +31,7 @@ use arrow::{
 };
 use chrono::{Datelike, Duration};
 use datafusion::{
-    datasource::TableProvider,
+    datasource::{MemTable, TableProvider},
     logical_plan::{col, lit, Expr, LogicalPlan, LogicalPlanBuilder},
     physical_plan::{
         accept, file_format::ParquetExec, metrics::MetricsSet, ExecutionPlan,
@@ -235,6 +235,25 @@ async fn prune_int32_eq() {
     assert_eq!(output.result_rows, 1, "{}", output.description());
 }
 
+#[tokio::test]
+async fn prune_int32_join_runtime_filter() {
+    let config = ExecutionConfig::new().with_repartition_joins(false);
+    let mut context = ContextWithParquet::with_config(Scenario::Int32, config).await;
+    let schema = Arc::new(Schema::new(vec![Field::new("k", DataType::Int32, true)]));
+    let keys = Arc::new(Int32Array::from(vec![6, 7]));
+    let batch = RecordBatch::try_new(schema.clone(), vec![keys]).unwrap();
+    let dim = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+    context.ctx.register_table("dim", Arc::new(dim)).unwrap();
+
+    let output = context.query("SELECT i FROM dim JOIN t ON k = i").await;
+
+    println!("{}", output.description());
+    // the row groups without the keys of dim are pruned once dim is collected
+    assert_eq!(output.predicate_evaluation_errors(), Some(0));
+    assert_eq!(output.row_groups_pruned(), Some(3));
+    assert_eq!(output.result_rows, 2, "{}", output.description());
+}
+
 #[tokio::test]
 async fn prune_int32_scalar_fun_and_eq() {
     // resulrt of sql "SELECT * FROM t where abs(i) = 1 and i = 1"
@@ -16,6 +16,7 @@
 // under the License.
 
 use super::*;
+use datafusion::scalar::ScalarValue;
 
 #[tokio::test]
 async fn csv_query_avg_multi_batch() -> Result<()> {
@@ -385,3 +386,139 @@ async fn csv_query_array_agg_one() -> Result<()> {
     assert_batches_eq!(expected, &actual);
     Ok(())
 }
+
+fn aggregate_clauses_table() -> Result<Arc<MemTable>> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("k", DataType::Utf8, false),
+        Field::new("v", DataType::Int64, true),
+        Field::new("ts", DataType::Int64, false),
+    ]));
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from(vec!["a", "a", "a", "b", "b"])),
+            Arc::new(Int64Array::from(vec![
+                Some(1),
+                Some(2),
+                Some(2),
+                Some(5),
+                None,
+            ])),
+            Arc::new(Int64Array::from(vec![3, 1, 2, 1, 2])),
+        ],
+    )?;
+    Ok(Arc::new(MemTable::try_new(schema, vec![vec![data]])?))
+}
+
+#[tokio::test]
+async fn query_aggregate_filter() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", aggregate_clauses_table()?)?;
+    let sql = "SELECT k, sum(v) FILTER (WHERE v > 1) AS s, count(*) FILTER (WHERE ts > 1) AS c \
+               FROM t GROUP BY k ORDER BY k";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+---+---+",
+        "| k | s | c |",
+        "+---+---+---+",
+        "| a | 4 | 2 |",
+        "| b | 5 | 1 |",
+        "+---+---+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_aggregate_order_by() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", aggregate_clauses_table()?)?;
+    let sql =
+        "SELECT k, array_agg(v ORDER BY ts DESC) FILTER (WHERE v IS NOT NULL) AS vs \
+               FROM t GROUP BY k ORDER BY k";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+-----------+",
+        "| k | vs        |",
+        "+---+-----------+",
+        "| a | [1, 2, 2] |",
+        "| b | [5]       |",
+        "+---+-----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+/// Sums up its Int64 argument, ignoring NULLs
+#[derive(Debug, Default)]
+struct Int64SumAccumulator {
+    sum: i64,
+}
+
+impl datafusion::physical_plan::Accumulator for Int64SumAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Int64(Some(self.sum))])
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        if let ScalarValue::Int64(Some(value)) = values[0] {
+            self.sum += value;
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        self.update(states)
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(ScalarValue::Int64(Some(self.sum)))
+    }
+}
+
+#[tokio::test]
+async fn query_udaf_distinct() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", aggregate_clauses_table()?)?;
+    ctx.register_udaf(datafusion::logical_plan::create_udaf(
+        "my_sum",
+        DataType::Int64,
+        Arc::new(DataType::Int64),
+        Volatility::Immutable,
+        Arc::new(|| Ok(Box::new(Int64SumAccumulator::default()))),
+        Arc::new(vec![DataType::Int64]),
+    ));
+
+    let sql = "SELECT k, my_sum(v) AS total, my_sum(DISTINCT v) AS distinct_total \
+               FROM t GROUP BY k ORDER BY k";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+-------+----------------+",
+        "| k | total | distinct_total |",
+        "+---+-------+----------------+",
+        "| a | 5     | 3              |",
+        "| b | 5     | 5              |",
+        "+---+-------+----------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // rewritten into a grouping by the distinct values
+    let sql = "SELECT my_sum(DISTINCT v) AS s FROM t";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec!["+---+", "| s |", "+---+", "| 8 |", "+---+"];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_aggregate_clauses_of_scalar_function() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", aggregate_clauses_table()?)?;
+    let sql = "SELECT abs(v) FILTER (WHERE v > 1) FROM t";
+    let err = ctx.create_logical_plan(sql).unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "FILTER and ORDER BY clauses are only supported in calls of aggregate functions"
+    );
+    Ok(())
+}
@@ -134,15 +134,138 @@ async fn query_array() -> Result<()> {
     let sql = "SELECT array(c1, cast(c2 as varchar)) FROM test";
     let actual = execute(&mut ctx, sql).await;
     let expected = vec![
-        vec!["[,0]"],
-        vec!["[a,1]"],
-        vec!["[aa,NULL]"],
-        vec!["[aaa,3]"],
+        vec!["[, 0]"],
+        vec!["[a, 1]"],
+        vec!["[aa, ]"],
+        vec!["[aaa, 3]"],
     ];
     assert_eq!(expected, actual);
     Ok(())
 }
 
+#[tokio::test]
+async fn query_array_functions() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    unnest::register_list_table(&mut ctx)?;
+
+    let sql = "SELECT id, array_length(items) AS len, cardinality(items) AS card, \
+        array_contains(items, 2) AS has_2, array_position(items, 3) AS pos_3 \
+        FROM t ORDER BY id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+-----+------+-------+-------+",
+        "| id | len | card | has_2 | pos_3 |",
+        "+----+-----+------+-------+-------+",
+        "| 1  | 3   | 3    | true  | 3     |",
+        "| 2  |     | 0    | false |       |",
+        "| 3  |     |      |       |       |",
+        "| 4  | 1   | 1    | false |       |",
+        "+----+-----+------+-------+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // not sorted, as taking from a list array turns its empty lists into nulls
+    let sql = "SELECT id, array_append(items, id), array_concat(items, items), \
+        array_slice(items, 2, -1) FROM t";
+    let actual = execute(&mut ctx, sql).await;
+    let expected = vec![
+        vec!["1", "[1, 2, 3, 1]", "[1, 2, 3, 1, 2, 3]", "[2, 3]"],
+        vec!["2", "[2]", "[]", "[]"],
+        vec!["3", "[3]", "NULL", "NULL"],
+        vec!["4", "[4, 4]", "[4, 4]", "[]"],
+    ];
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_array_functions_on_literals() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "SELECT array_sort(array_distinct(array(3, 1, 3, 2))) AS sorted, \
+        array_join(array('a', 'b', 'c'), '-') AS joined, \
+        array_to_string(array_cat(array(1), array(2)), ',') AS concatenated";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-----------+--------+--------------+",
+        "| sorted    | joined | concatenated |",
+        "+-----------+--------+--------------+",
+        "| [1, 2, 3] | a-b-c  | 1,2          |",
+        "+-----------+--------+--------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let err = ctx
+        .create_logical_plan("SELECT array_length(1)")
+        .unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "The function array_length expects a list argument, got Int64"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_array_concat_mixed_lists() -> Result<()> {
+    let item = Box::new(Field::new("item", DataType::Int64, true));
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("items", DataType::List(item.clone()), true),
+        Field::new("pair", DataType::FixedSizeList(item, 2), true),
+    ]));
+
+    let mut items = ListBuilder::new(Int64Builder::new(4));
+    for list in [Some(vec![1, 2, 3]), Some(vec![]), None, Some(vec![4])] {
+        match list {
+            Some(values) => {
+                for value in values {
+                    items.values().append_value(value)?;
+                }
+                items.append(true)?;
+            }
+            None => items.append(false)?,
+        }
+    }
+    let mut pairs = FixedSizeListBuilder::new(Int64Builder::new(8), 2);
+    for (first, second) in [(10, 11), (20, 21), (30, 31), (40, 41)] {
+        pairs.values().append_value(first)?;
+        pairs.values().append_value(second)?;
+        pairs.append(true)?;
+    }
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(items.finish()), Arc::new(pairs.finish())],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", Arc::new(table))?;
+
+    // lists of different kinds and lengths
+    let sql = "SELECT array_concat(items, pair, array(5)) AS all_lists, \
+        array_concat(array(1, 2), array(3)) AS literals FROM t";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----------------------+-----------+",
+        "| all_lists            | literals  |",
+        "+----------------------+-----------+",
+        "| [1, 2, 3, 10, 11, 5] | [1, 2, 3] |",
+        "| [20, 21, 5]          | [1, 2, 3] |",
+        "| [30, 31, 5]          | [1, 2, 3] |",
+        "| [4, 40, 41, 5]       | [1, 2, 3] |",
+        "+----------------------+-----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // but their elements must have the same type
+    let err = ctx
+        .create_logical_plan("SELECT array_concat(items, array('a')) FROM t")
+        .unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "The function array_concat expects lists with elements of the same type, got Int64 and Utf8"
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn query_count_distinct() -> Result<()> {
     let schema = Arc::new(Schema::new(vec![Field::new("c1", DataType::Int32, true)]));
@@ -311,6 +311,43 @@ async fn equijoin_implicit_syntax() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn equijoin_implicit_syntax_plan() -> Result<()> {
+    let ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id, t1_name, t2_name FROM t1, t2 WHERE t1_id = t2_id";
+    // the SQL planner cross joins the relations below the WHERE clause
+    let plan = ctx.create_logical_plan(sql)?;
+    let formatted = format!("{:?}", plan);
+    assert_contains!(formatted, "Filter: #t1.t1_id = #t2.t2_id\n    CrossJoin:");
+
+    // which the optimizer turns into an equi-join
+    let plan = ctx.optimize(&plan)?;
+    let formatted = format!("{:?}", plan);
+    assert_contains!(&formatted, "Join: #t1.t1_id = #t2.t2_id");
+    assert_not_contains!(formatted, "CrossJoin");
+    Ok(())
+}
+
+#[tokio::test]
+async fn equijoin_inferred_filter_explain() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "EXPLAIN SELECT t1_id, t1_name, t2_name \
+        FROM t1 JOIN t2 ON t1_id = t2_id \
+        WHERE t1_id > 5";
+    let actual = execute(&mut ctx, sql).await;
+    assert_eq!(actual[0][0], "logical_plan");
+    // the filter on the join key is inferred for, and pushed to, both scans
+    let expected = "\
+        Projection: #t1.t1_id, #t1.t1_name, #t2.t2_name\
+        \n  Join: #t1.t1_id = #t2.t2_id\
+        \n    Filter: #t1.t1_id > Int64(5)\
+        \n      TableScan: t1 projection=Some([0, 1])\
+        \n    Filter: #t2.t2_id > Int64(5)\
+        \n      TableScan: t2 projection=Some([0, 1])";
+    assert_eq!(actual[0][1], expected);
+    Ok(())
+}
+
 #[tokio::test]
 async fn equijoin_implicit_syntax_with_filter() -> Result<()> {
     let mut ctx = create_join_context("t1_id", "t2_id")?;
@@ -353,6 +390,65 @@ async fn equijoin_implicit_syntax_reversed() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn equijoin_implicit_syntax_in_subquery() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    // the filter only reaches the cross join once pushed through the subquery
+    let sql = "SELECT t1_id, t1_name, t2_name \
+        FROM (SELECT * FROM t1, t2) AS t \
+        WHERE t1_id = t2_id \
+        ORDER BY t1_id";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let physical_plan = ctx.create_physical_plan(&plan).await?;
+    let formatted = displayable(physical_plan.as_ref()).indent().to_string();
+    assert_not_contains!(formatted, "CrossJoinExec");
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+---------+---------+",
+        "| t1_id | t1_name | t2_name |",
+        "+-------+---------+---------+",
+        "| 11    | a       | z       |",
+        "| 22    | b       | y       |",
+        "| 44    | d       | x       |",
+        "+-------+---------+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn equijoin_from_logical_plan_builder_cross_join() -> Result<()> {
+    use datafusion::logical_plan::LogicalPlanBuilder;
+
+    let ctx = create_join_context("t1_id", "t2_id")?;
+    let t2 = ctx.table("t2")?.to_logical_plan();
+    let plan = LogicalPlanBuilder::from(ctx.table("t1")?.to_logical_plan())
+        .cross_join(&t2)?
+        .filter(col("t1_id").eq(col("t2_id")))?
+        .project(vec![col("t1_id"), col("t1_name"), col("t2_name")])?
+        .sort(vec![col("t1_id").sort(true, true)])?
+        .build()?;
+    let plan = ctx.optimize(&plan)?;
+    let physical_plan = ctx.create_physical_plan(&plan).await?;
+    let formatted = displayable(physical_plan.as_ref()).indent().to_string();
+    assert_not_contains!(formatted, "CrossJoinExec");
+
+    let actual = collect(physical_plan).await?;
+    let expected = vec![
+        "+-------+---------+---------+",
+        "| t1_id | t1_name | t2_name |",
+        "+-------+---------+---------+",
+        "| 11    | a       | z       |",
+        "| 22    | b       | y       |",
+        "| 44    | d       | x       |",
+        "+-------+---------+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
 #[tokio::test]
 async fn cross_join() {
     let mut ctx = create_join_context("t1_id", "t2_id").unwrap();
@@ -685,3 +781,113 @@ async fn join_tables_with_duplicated_column_name_not_in_on_constraint() -> Resul
     assert_batches_eq!(expected, &actual);
     Ok(())
 }
+
+#[tokio::test]
+async fn join_reorder_avoids_cross_join() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let batch = RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int32Array::from(vec![1, 2])) as _),
+        (
+            "country",
+            Arc::new(StringArray::from(vec!["Germany", "Japan"])) as _,
+        ),
+    ])?;
+    let countries = MemTable::try_new(batch.schema(), vec![vec![batch]])?;
+    ctx.register_table("countries", Arc::new(countries))?;
+    let batch = RecordBatch::try_from_iter(vec![
+        (
+            "city",
+            Arc::new(StringArray::from(vec!["Berlin", "Osaka", "Tokyo"])) as _,
+        ),
+        (
+            "city_country_id",
+            Arc::new(Int32Array::from(vec![1, 2, 2])) as _,
+        ),
+    ])?;
+    let cities = MemTable::try_new(batch.schema(), vec![vec![batch]])?;
+    ctx.register_table("cities", Arc::new(cities))?;
+    let batch = RecordBatch::try_from_iter(vec![
+        (
+            "language",
+            Arc::new(StringArray::from(vec!["German", "Japanese"])) as _,
+        ),
+        (
+            "language_country_id",
+            Arc::new(Int32Array::from(vec![1, 2])) as _,
+        ),
+    ])?;
+    let languages = MemTable::try_new(batch.schema(), vec![vec![batch]])?;
+    ctx.register_table("languages", Arc::new(languages))?;
+
+    // no predicate connects cities and languages
+    let sql = "SELECT city, country, language FROM cities, languages, countries \
+               WHERE city_country_id = id AND language_country_id = id \
+               ORDER BY city";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let physical_plan = ctx.create_physical_plan(&plan).await?;
+    let formatted = displayable(physical_plan.as_ref()).indent().to_string();
+    assert_not_contains!(formatted, "CrossJoinExec");
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+--------+---------+----------+",
+        "| city   | country | language |",
+        "+--------+---------+----------+",
+        "| Berlin | Germany | German   |",
+        "| Osaka  | Japan   | Japanese |",
+        "| Tokyo  | Japan   | Japanese |",
+        "+--------+---------+----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn join_with_aggregate_push_down() -> Result<()> {
+    let config = ExecutionConfig::new().with_aggregate_push_down(true);
+    let mut ctx = ExecutionContext::with_config(config);
+    let batch = RecordBatch::try_from_iter(vec![
+        (
+            "sale_city_id",
+            Arc::new(Int32Array::from(vec![1, 1, 2, 3])) as _,
+        ),
+        (
+            "amount",
+            Arc::new(Int32Array::from(vec![10, 20, 30, 40])) as _,
+        ),
+    ])?;
+    let sales = MemTable::try_new(batch.schema(), vec![vec![batch]])?;
+    ctx.register_table("sales", Arc::new(sales))?;
+    let batch = RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int32Array::from(vec![1, 2, 4])) as _),
+        (
+            "city",
+            Arc::new(StringArray::from(vec!["Berlin", "Osaka", "Tokyo"])) as _,
+        ),
+    ])?;
+    let cities = MemTable::try_new(batch.schema(), vec![vec![batch]])?;
+    ctx.register_table("cities", Arc::new(cities))?;
+
+    let sql = "SELECT city, SUM(amount), COUNT(*) FROM sales JOIN cities \
+               ON sale_city_id = id GROUP BY city ORDER BY city";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let formatted = format!("{:?}", plan);
+    assert_contains!(
+        formatted,
+        "Aggregate: groupBy=[[#sales.sale_city_id]], aggr=[[SUM(#sales.amount), COUNT(UInt8(1))]]"
+    );
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+--------+-------------------+-----------------+",
+        "| city   | SUM(sales.amount) | COUNT(UInt8(1)) |",
+        "+--------+-------------------+-----------------+",
+        "| Berlin | 30                | 2               |",
+        "| Osaka  | 30                | 1               |",
+        "+--------+-------------------+-----------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
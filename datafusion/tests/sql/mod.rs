@@ -91,9 +91,11 @@ pub mod predicates;
 pub mod projection;
 pub mod references;
 pub mod select;
+pub mod table_functions;
 pub mod timestamp;
 pub mod udf;
 pub mod union;
+pub mod unnest;
 pub mod window;
 
 #[cfg_attr(not(feature = "unicode_expressions"), ignore)]
@@ -32,6 +32,32 @@ async fn test_sort_unprojected_col() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_sort_unprojected_col_limit() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_alltypes_parquet(&mut ctx).await;
+    // the limit is applied above the sort, under the projection of int_col
+    let sql = "SELECT int_col FROM alltypes_plain ORDER BY id DESC LIMIT 3";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let physical_plan = ctx.create_physical_plan(&plan).await?;
+    let formatted = displayable(physical_plan.as_ref()).indent().to_string();
+    assert_contains!(formatted, "fetch=3");
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------+",
+        "| int_col |",
+        "+---------+",
+        "| 1       |",
+        "| 0       |",
+        "| 1       |",
+        "+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_order_by_agg_expr() -> Result<()> {
     let mut ctx = ExecutionContext::new();
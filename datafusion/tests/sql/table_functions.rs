@@ -0,0 +1,198 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use super::*;
+use datafusion::datasource::table_function::TableFunction;
+use datafusion::datasource::TableProvider;
+use datafusion::scalar::ScalarValue;
+use futures::FutureExt;
+
+#[tokio::test]
+async fn generate_series() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "SELECT generate_series AS n FROM generate_series(1, 10, 4)";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+", "| n |", "+---+", "| 1 |", "| 5 |", "| 9 |", "+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "SELECT COUNT(*), SUM(s.generate_series) \
+        FROM generate_series(2 * 5, -10 - 5, -1) AS s";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-----------------+------------------------+",
+        "| COUNT(UInt8(1)) | SUM(s.generate_series) |",
+        "+-----------------+------------------------+",
+        "| 26              | -65                    |",
+        "+-----------------+------------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn generate_series_invalid_arguments() -> Result<()> {
+    let ctx = ExecutionContext::new();
+    let err = ctx
+        .create_logical_plan("SELECT * FROM generate_series(1, 10, 0)")
+        .expect_err("step of zero should fail");
+    assert_eq!(
+        err.to_string(),
+        "Error during planning: generate_series step must not be zero"
+    );
+
+    let err = ctx
+        .create_logical_plan("SELECT * FROM generate_series(1, 'a')")
+        .expect_err("string argument should fail");
+    assert_contains!(
+        err.to_string(),
+        "Argument 2 of generate_series must be an integer"
+    );
+
+    let err = ctx
+        .create_logical_plan("SELECT * FROM no_such_function(1)")
+        .expect_err("unknown function should fail");
+    assert_eq!(
+        err.to_string(),
+        "Error during planning: Table function 'no_such_function' not found"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn read_parquet() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let testdata = datafusion::test_util::parquet_test_data();
+
+    // the schema of the files is read before planning, so the query has to
+    // be planned asynchronously
+    let sql = format!(
+        "SELECT COUNT(*), MAX(id) FROM read_parquet('{}/alltypes_plain.parquet')",
+        testdata
+    );
+    let err = ctx
+        .create_logical_plan(&sql)
+        .expect_err("planning synchronously should fail");
+    assert_contains!(
+        err.to_string(),
+        "plan the query with ExecutionContext::create_logical_plan_async"
+    );
+    let plan = ctx.create_logical_plan_async(&sql).await?;
+    assert_contains!(format!("{:?}", plan), "TableScan: read_parquet");
+
+    let actual = ctx.sql(&sql).await?.collect().await?;
+    let expected = vec![
+        "+-----------------+----------------------+",
+        "| COUNT(UInt8(1)) | MAX(read_parquet.id) |",
+        "+-----------------+----------------------+",
+        "| 8               | 7                    |",
+        "+-----------------+----------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // only the files matching the pattern are read
+    let tmp_dir = tempfile::TempDir::new()?;
+    for file in ["a.parquet", "b.parquet", "c.parquet.bak"] {
+        std::fs::copy(
+            format!("{}/alltypes_plain.parquet", testdata),
+            tmp_dir.path().join(file),
+        )?;
+    }
+    let sql = format!(
+        "SELECT COUNT(*) AS n FROM read_parquet('{}/*.parquet')",
+        tmp_dir.path().display()
+    );
+    let actual = ctx.sql(&sql).await?.collect().await?;
+    let expected = vec!["+----+", "| n  |", "+----+", "| 16 |", "+----+"];
+    assert_batches_eq!(expected, &actual);
+
+    // the files are split into the target partitions of the session at the
+    // time of the call
+    let sql = format!(
+        "SELECT * FROM read_parquet('{}/*.parquet')",
+        tmp_dir.path().display()
+    );
+    let mut ctx =
+        ExecutionContext::with_config(ExecutionConfig::new().with_target_partitions(2));
+    assert_eq!(scan_partitions(&mut ctx, &sql).await?, 2);
+    ctx.sql("SET datafusion.execution.target_partitions = 1")
+        .await?;
+    assert_eq!(scan_partitions(&mut ctx, &sql).await?, 1);
+    Ok(())
+}
+
+/// Returns the number of partitions of the scan at the bottom of the plan of `sql`
+async fn scan_partitions(ctx: &mut ExecutionContext, sql: &str) -> Result<usize> {
+    let plan = ctx.sql(sql).await?.to_logical_plan();
+    let mut plan = ctx.create_physical_plan(&plan).await?;
+    while let Some(child) = plan.children().first().cloned() {
+        plan = child;
+    }
+    Ok(plan.output_partitioning().partition_count())
+}
+
+#[tokio::test]
+async fn register_table_function() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    // repeat(s, n) returns a table with n rows containing the string s
+    ctx.register_table_function(TableFunction::new(
+        "repeat",
+        Arc::new(|_, args| {
+            let table = || -> Result<Arc<dyn TableProvider>> {
+                let (value, n) = match args {
+                    [ScalarValue::Utf8(Some(value)), ScalarValue::Int64(Some(n))] => {
+                        (value.clone(), *n as usize)
+                    }
+                    _ => {
+                        return Err(DataFusionError::Plan(
+                            "repeat expects a string and an integer".to_string(),
+                        ))
+                    }
+                };
+                let schema = Arc::new(Schema::new(vec![Field::new(
+                    "value",
+                    DataType::Utf8,
+                    false,
+                )]));
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(StringArray::from(vec![value.as_str(); n]))],
+                )?;
+                Ok(Arc::new(MemTable::try_new(schema, vec![vec![batch]])?))
+            };
+            futures::future::ready(table()).boxed()
+        }),
+    ));
+
+    let sql = "SELECT r.value, t.generate_series AS i \
+        FROM REPEAT('x', 2) r CROSS JOIN generate_series(1, 2) t \
+        ORDER BY i";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+---+",
+        "| value | i |",
+        "+-------+---+",
+        "| x     | 1 |",
+        "| x     | 1 |",
+        "| x     | 2 |",
+        "| x     | 2 |",
+        "+-------+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
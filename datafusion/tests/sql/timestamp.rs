@@ -812,3 +812,108 @@ async fn group_by_timestamp_millis() -> Result<()> {
     assert_batches_eq!(expected, &actual);
     Ok(())
 }
+
+#[tokio::test]
+async fn date_trunc_at_time_zone() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    // midnight in Berlin is 22:00 UTC, the instant a cast to TIMESTAMP keeps
+    let sql = "SELECT \
+        CAST(date_trunc('day', ts AT TIME ZONE 'Europe/Berlin') AS TIMESTAMP) AS berlin_day, \
+        date_part('hour', ts AT TIME ZONE 'Europe/Berlin') AS berlin_hour, \
+        date_part('hour', ts) AS utc_hour \
+        FROM (SELECT CAST('2021-07-01T23:30:00Z' AS TIMESTAMP WITH TIME ZONE) AS ts) t";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------------------+-------------+----------+",
+        "| berlin_day          | berlin_hour | utc_hour |",
+        "+---------------------+-------------+----------+",
+        "| 2021-07-01 22:00:00 | 1           | 23       |",
+        "+---------------------+-------------+----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn session_time_zone() -> Result<()> {
+    let mut ctx = ExecutionContext::with_config(
+        ExecutionConfig::new().with_time_zone("America/New_York"),
+    );
+    // wall clock times without an offset are read in the session time zone
+    let sql = "SELECT \
+        CAST(TIMESTAMP WITH TIME ZONE '2021-07-01 08:00:00' AS TIMESTAMP) AS utc, \
+        CAST(TIMESTAMP WITH TIME ZONE '2021-07-01 08:00:00' AT TIME ZONE 'UTC' AS TIMESTAMP) AS at_utc";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------------------+---------------------+",
+        "| utc                 | at_utc              |",
+        "+---------------------+---------------------+",
+        "| 2021-07-01 12:00:00 | 2021-07-01 12:00:00 |",
+        "+---------------------+---------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // `now()` is displayed in the session time zone, but is still the
+    // current instant
+    let t1 = chrono::Utc::now().timestamp_nanos();
+    let actual = execute_to_batches(&mut ctx, "SELECT NOW()").await;
+    let t3 = chrono::Utc::now().timestamp_nanos();
+    let now = actual[0].column(0);
+    assert_eq!(
+        now.data_type(),
+        &DataType::Timestamp(TimeUnit::Nanosecond, Some("America/New_York".to_owned()))
+    );
+    let t2 = now
+        .as_any()
+        .downcast_ref::<TimestampNanosecondArray>()
+        .unwrap()
+        .value(0);
+    assert!(t1 <= t2 && t2 <= t3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn try_cast_to_timestamp_with_time_zone() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "SELECT \
+        TRY_CAST('2021-07-01 08:00:00' AS TIMESTAMP WITH TIME ZONE) AS valid, \
+        TRY_CAST('foo' AS TIMESTAMP WITH TIME ZONE) AS invalid";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------------------+---------+",
+        "| valid               | invalid |",
+        "+---------------------+---------+",
+        "| 2021-07-01 08:00:00 |         |",
+        "+---------------------+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // while a plain cast fails, whether in the optimizer or at execution
+    let sql = "SELECT CAST('foo' AS TIMESTAMP WITH TIME ZONE)";
+    let result = match ctx.sql(sql).await {
+        Ok(df) => df.collect().await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn date_time_interval_arithmetic() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "SELECT \
+        CAST('2021-01-31T10:00:00' AS TIMESTAMP) + INTERVAL '1 month' + INTERVAL '1 day' AS ts, \
+        INTERVAL '1 year' + CAST('2020-02-29' AS DATE) AS next_year, \
+        CAST('2021-03-01' AS DATE) - INTERVAL '1 day' AS prev_day, \
+        CAST('2021-01-02T12:00:00' AS TIMESTAMP) - CAST('2021-01-01T00:00:00' AS TIMESTAMP) AS diff";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------------------+------------+------------+-------------------------------------------------+",
+        "| ts                  | next_year  | prev_day   | diff                                            |",
+        "+---------------------+------------+------------+-------------------------------------------------+",
+        "| 2021-03-01 10:00:00 | 2021-02-28 | 2021-02-28 | 0 years 0 mons 1 days 12 hours 0 mins 0.00 secs |",
+        "+---------------------+------------+------------+-------------------------------------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
@@ -16,6 +16,19 @@
 // under the License.
 
 use super::*;
+use datafusion::logical_plan::{create_async_udf, create_udwf};
+use datafusion::physical_plan::async_udf::{
+    AsyncScalarFunction, AsyncScalarFunctionImplementation,
+};
+use datafusion::physical_plan::functions::{
+    make_scalar_function, Parameter, ReturnTypeFunction, Signature,
+};
+use datafusion::physical_plan::udf::ScalarUDF;
+use datafusion::physical_plan::udwf::{PartitionEvaluator, WindowPartition};
+use datafusion::scalar::ScalarValue;
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// test that casting happens on udfs.
 /// c11 is f32, but `custom_sqrt` requires f64. Casting happens but the logical plan and
@@ -30,3 +43,212 @@ async fn csv_query_custom_udf_with_cast() -> Result<()> {
     assert_float_eq(&expected, &actual);
     Ok(())
 }
+
+/// sums the values of the frame of every row
+#[derive(Debug)]
+struct FrameSum {}
+
+impl PartitionEvaluator for FrameSum {
+    fn evaluate(&mut self, partition: &WindowPartition) -> Result<ArrayRef> {
+        let values = partition.values()[0]
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .expect("cast failed");
+        let sums = (0..partition.num_rows())
+            .map(|row| Ok(partition.frame(row)?.map(|i| values.value(i)).sum()))
+            .collect::<Result<Vec<i64>>>()?;
+        Ok(Arc::new(Int64Array::from(sums)))
+    }
+}
+
+#[tokio::test]
+async fn query_window_udf() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    ctx.register_udwf(create_udwf(
+        "frame_sum",
+        vec![DataType::Int64],
+        Arc::new(DataType::Int64),
+        Volatility::Immutable,
+        Arc::new(|| Ok(Box::new(FrameSum {}))),
+    ));
+    let sql = "select t, x, \
+               frame_sum(x) over (order by t) as cumulative, \
+               frame_sum(x) over (order by x rows between 1 preceding and 1 following) as moving, \
+               frame_sum(x) over (partition by t) as total \
+               from (values (1, 1), (2, 2), (2, 3), (3, 4)) as v(t, x) \
+               order by t, x";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+---+------------+--------+-------+",
+        "| t | x | cumulative | moving | total |",
+        "+---+---+------------+--------+-------+",
+        "| 1 | 1 | 1          | 3      | 1     |",
+        "| 2 | 2 | 6          | 6      | 5     |",
+        "| 2 | 3 | 6          | 9      | 5     |",
+        "| 3 | 4 | 10         | 7      | 4     |",
+        "+---+---+------------+--------+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "select frame_sum(x, x) over () from (values (1)) as v(x)";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let err = ctx.create_physical_plan(&plan).await.unwrap_err();
+    assert_contains!(err.to_string(), "Coercion from [Int64, Int64]");
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_udf_with_named_arguments() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let add_n = |args: &[ArrayRef]| {
+        let x = args[0]
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .expect("cast failed");
+        let n = args[1]
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .expect("cast failed");
+        Ok(Arc::new(arrow::compute::add(x, n)?) as ArrayRef)
+    };
+    let signature = Signature::exact(
+        vec![DataType::Int64, DataType::Int64],
+        Volatility::Immutable,
+    )
+    .with_parameters(vec![
+        Parameter::new("x"),
+        Parameter::with_default("n", ScalarValue::Int64(Some(10))),
+    ]);
+    let return_type: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::Int64)));
+    ctx.register_udf(ScalarUDF::new(
+        "add_n",
+        &signature,
+        &return_type,
+        &make_scalar_function(add_n),
+    ));
+
+    let sql = "select add_n(1) as a, add_n(1, 2) as b, add_n(n => 3, x => 1) as c, \
+               add_n(x => 1) as d, \
+               substr(string => 'hello', position => 2, length => 3) as e";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+---+---+----+-----+",
+        "| a  | b | c | d  | e   |",
+        "+----+---+---+----+-----+",
+        "| 11 | 3 | 4 | 11 | ell |",
+        "+----+---+---+----+-----+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let err = ctx.create_logical_plan("select add_n(y => 1)").unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "Function 'add_n' has no parameter named 'y'"
+    );
+
+    let sql = "select add_n(x, 'a') from (values (1)) as v(x)";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let err = ctx.create_physical_plan(&plan).await.unwrap_err();
+    assert_contains!(err.to_string(), "Accepted signatures: (x Int64, n Int64)");
+    Ok(())
+}
+
+/// An in-process stand-in for a geo-IP service
+struct GeoService {
+    countries: HashMap<&'static str, &'static str>,
+    requests: AtomicUsize,
+}
+
+impl GeoService {
+    fn new() -> Self {
+        let countries = vec![("1.1.1.1", "AU"), ("8.8.8.8", "US"), ("9.9.9.9", "CH")];
+        Self {
+            countries: countries.into_iter().collect(),
+            requests: AtomicUsize::new(0),
+        }
+    }
+
+    async fn lookup(&self, ips: ArrayRef) -> Result<ArrayRef> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        tokio::task::yield_now().await;
+        let ips = ips
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .expect("cast failed");
+        let countries = ips
+            .iter()
+            .map(|ip| ip.and_then(|ip| self.countries.get(ip).copied()))
+            .collect::<StringArray>();
+        Ok(Arc::new(countries))
+    }
+}
+
+fn register_geo_udf(ctx: &mut ExecutionContext, service: Arc<GeoService>) {
+    let fun: AsyncScalarFunctionImplementation = Arc::new(move |args: Vec<ArrayRef>| {
+        let service = service.clone();
+        async move { service.lookup(args[0].clone()).await }.boxed()
+    });
+    ctx.register_udf(create_async_udf(
+        "geo",
+        vec![DataType::Utf8],
+        Arc::new(DataType::Utf8),
+        Volatility::Volatile,
+        AsyncScalarFunction::new(fun)
+            .with_batch_size(2)
+            .with_max_concurrency(2),
+    ));
+}
+
+#[tokio::test]
+async fn query_async_udf() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let service = Arc::new(GeoService::new());
+    register_geo_udf(&mut ctx, service.clone());
+
+    let sql = "select ip, geo(ip) as country, lower(geo(ip)) as lower \
+               from (values ('1.1.1.1'), ('8.8.8.8'), ('9.9.9.9'), ('10.0.0.1'), (null)) \
+               as v(ip) \
+               where geo(ip) is not null or ip is null \
+               order by ip";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------+---------+-------+",
+        "| ip      | country | lower |",
+        "+---------+---------+-------+",
+        "| 1.1.1.1 | AU      | au    |",
+        "| 8.8.8.8 | US      | us    |",
+        "| 9.9.9.9 | CH      | ch    |",
+        "|         |         |       |",
+        "+---------+---------+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    // 5 rows in batches of 2 in the filter, 4 of them again in the projection
+    assert_eq!(service.requests.load(Ordering::SeqCst), 5);
+
+    let sql = "select geo(ip) from (values ('1.1.1.1')) as v(ip)";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let plan = displayable(plan.as_ref()).indent().to_string();
+    assert_contains!(&plan, "AsyncFuncExec: expr=[geo(");
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_async_udf_outside_of_projection() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_geo_udf(&mut ctx, Arc::new(GeoService::new()));
+
+    let sql = "select count(*) from (values ('1.1.1.1')) as v(ip) group by geo(ip)";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let err = collect(plan).await.unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "Async UDF geo can only be called in projections and filters"
+    );
+    Ok(())
+}
@@ -64,3 +64,38 @@ async fn union_all_with_aggregate() -> Result<()> {
     assert_batches_eq!(expected, &actual);
     Ok(())
 }
+
+#[tokio::test]
+async fn union_all_with_group_by() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_aggregate_csv(&mut ctx).await?;
+    let sql = "SELECT c1, COUNT(*) FROM \
+               (SELECT c1 FROM aggregate_test_100 \
+                UNION ALL \
+                SELECT c1 FROM aggregate_test_100) AS t \
+               GROUP BY c1 ORDER BY c1";
+
+    // the rows of each branch are aggregated before the union
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let physical_plan = ctx.create_physical_plan(&plan).await?;
+    let formatted = displayable(physical_plan.as_ref()).indent().to_string();
+    let lines: Vec<&str> = formatted.lines().map(|line| line.trim()).collect();
+    let union = lines.iter().position(|line| *line == "UnionExec").unwrap();
+    assert!(lines[union + 1].starts_with("HashAggregateExec: mode=Partial"));
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+-----------------+",
+        "| c1 | COUNT(UInt8(1)) |",
+        "+----+-----------------+",
+        "| a  | 42              |",
+        "| b  | 38              |",
+        "| c  | 42              |",
+        "| d  | 36              |",
+        "| e  | 42              |",
+        "+----+-----------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
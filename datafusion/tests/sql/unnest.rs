@@ -0,0 +1,141 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use super::*;
+
+/// Registers a table `t` with an `id` column and an `items` list column
+pub fn register_list_table(ctx: &mut ExecutionContext) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new(
+            "items",
+            DataType::List(Box::new(Field::new("item", DataType::Int64, true))),
+            true,
+        ),
+    ]));
+
+    let mut items = ListBuilder::new(Int64Builder::new(4));
+    for list in [Some(vec![1, 2, 3]), Some(vec![]), None, Some(vec![4])] {
+        match list {
+            Some(values) => {
+                for value in values {
+                    items.values().append_value(value)?;
+                }
+                items.append(true)?;
+            }
+            None => items.append(false)?,
+        }
+    }
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int32Array::from(vec![1, 2, 3, 4])),
+            Arc::new(items.finish()),
+        ],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+    ctx.register_table("t", Arc::new(table))?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn cross_join_unnest() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_list_table(&mut ctx)?;
+
+    let sql = "SELECT t.id, u.x FROM t CROSS JOIN UNNEST(t.items) AS u (x) \
+        ORDER BY t.id, u.x";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+---+",
+        "| id | x |",
+        "+----+---+",
+        "| 1  | 1 |",
+        "| 1  | 2 |",
+        "| 1  | 3 |",
+        "| 4  | 4 |",
+        "+----+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // the other columns, including the list itself, are preserved
+    let sql = "SELECT * FROM t CROSS JOIN UNNEST(t.items) WHERE t.id = 4";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+-------+--------+",
+        "| id | items | unnest |",
+        "+----+-------+--------+",
+        "| 4  | [4]   | 4      |",
+        "+----+-------+--------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn lateral_unnest() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_list_table(&mut ctx)?;
+
+    let sql = "SELECT id, SUM(x) FROM t, UNNEST(t.items) AS u (x) \
+        WHERE x > 1 GROUP BY id ORDER BY id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+----------+",
+        "| id | SUM(u.x) |",
+        "+----+----------+",
+        "| 1  | 5        |",
+        "| 4  | 4        |",
+        "+----+----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn lateral_unnest_after_several_relations() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_list_table(&mut ctx)?;
+
+    // the list belongs to the first of the preceding relations
+    let sql = "SELECT t.id, s.n, x FROM t, (SELECT 10 AS n) AS s, \
+        UNNEST(t.items) AS u (x) WHERE t.id = 1 ORDER BY x";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+----+---+",
+        "| id | n  | x |",
+        "+----+----+---+",
+        "| 1  | 10 | 1 |",
+        "| 1  | 10 | 2 |",
+        "| 1  | 10 | 3 |",
+        "+----+----+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unnest_array() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "SELECT x * 2 AS y FROM UNNEST(array(1, 2, 3)) AS u (x)";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+", "| y |", "+---+", "| 2 |", "| 4 |", "| 6 |", "+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
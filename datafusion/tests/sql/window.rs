@@ -142,3 +142,106 @@ async fn csv_query_window_with_partition_by_order_by() -> Result<()> {
     assert_batches_eq!(expected, &actual);
     Ok(())
 }
+
+#[tokio::test]
+async fn csv_query_window_with_rows_frame() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_aggregate_csv(&mut ctx).await?;
+    let sql = "select \
+               c9, \
+               sum(c5) over (order by c9 rows between 2 preceding and current row) as s, \
+               avg(c5) over (order by c9 rows between 2 preceding and current row) as a, \
+               min(c5) over (order by c9 rows between 2 preceding and current row) as mn, \
+               max(c5) over (order by c9 rows 2 preceding) as mx, \
+               sum(c5) over (order by c9 rows between 2 preceding and 1 preceding) as p, \
+               count(c5) over (order by c9 rows between 2 preceding and 1 preceding) as c \
+               from aggregate_test_100 \
+               order by c9 \
+               limit 5";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-----------+-------------+--------------------+-------------+-----------+-------------+---+",
+        "| c9        | s           | a                  | mn          | mx        | p           | c |",
+        "+-----------+-------------+--------------------+-------------+-----------+-------------+---+",
+        "| 28774375  | 61035129    | 61035129           | 61035129    | 61035129  |             | 0 |",
+        "| 63044568  | -47938237   | -23969118.5        | -108973366  | 61035129  | 61035129    | 1 |",
+        "| 141047417 | 575165281   | 191721760.33333334 | -108973366  | 623103518 | -47938237   | 2 |",
+        "| 141680161 | -1413497958 | -471165986         | -1927628110 | 623103518 | 514130152   | 2 |",
+        "| 145294611 | -3203699703 | -1067899901        | -1927628110 | 623103518 | -1304524592 | 2 |",
+        "+-----------+-------------+--------------------+-------------+-----------+-------------+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn window_with_groups_frame() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "select \
+               t, \
+               x, \
+               sum(x) over (order by t groups between 1 preceding and current row) as s, \
+               count(x) over (order by t groups between current row and 1 following) as c, \
+               sum(x) over (order by t range between current row and unbounded following) as r \
+               from (values (1, 1), (1, 2), (2, 3), (3, 4), (3, 5), (4, 6)) as v(t, x) \
+               order by t, x";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+---+----+---+----+",
+        "| t | x | s  | c | r  |",
+        "+---+---+----+---+----+",
+        "| 1 | 1 | 3  | 3 | 21 |",
+        "| 1 | 2 | 3  | 3 | 21 |",
+        "| 2 | 3 | 6  | 3 | 18 |",
+        "| 3 | 4 | 12 | 3 | 15 |",
+        "| 3 | 5 | 12 | 3 | 15 |",
+        "| 4 | 6 | 15 | 1 | 6  |",
+        "+---+---+----+---+----+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn window_frame_for_built_in_window_function() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_aggregate_csv(&mut ctx).await?;
+    let sql = "select row_number() over (order by c9 rows between 1 preceding and current row) \
+               from aggregate_test_100";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let err = ctx.create_physical_plan(&plan).await.unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "built-in window function with window frame definition is not yet supported"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn window_ntile() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    let sql = "select \
+               t, \
+               x, \
+               ntile(2) over (order by x) as n2, \
+               ntile(3) over (partition by t order by x) as n3 \
+               from (values (1, 1), (1, 2), (1, 3), (1, 4), (2, 5), (2, 6), (2, 7)) as v(t, x) \
+               order by x";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+---+----+----+",
+        "| t | x | n2 | n3 |",
+        "+---+---+----+----+",
+        "| 1 | 1 | 1  | 1  |",
+        "| 1 | 2 | 1  | 1  |",
+        "| 1 | 3 | 1  | 2  |",
+        "| 1 | 4 | 1  | 3  |",
+        "| 2 | 5 | 2  | 1  |",
+        "| 2 | 6 | 2  | 2  |",
+        "| 2 | 7 | 2  | 3  |",
+        "+---+---+----+----+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
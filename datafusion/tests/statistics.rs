@@ -239,8 +239,41 @@ async fn sql_filter() -> Result<()> {
         .await
         .unwrap();
 
-    // with a filtering condition we loose all knowledge about the statistics
-    assert_eq!(Statistics::default(), physical_plan.statistics());
+    // with a filtering condition the statistics are estimated: half of the
+    // rows have one of the two distinct values of c1
+    assert_eq!(
+        Statistics {
+            num_rows: Some(7),
+            is_exact: false,
+            total_byte_size: None,
+            column_statistics: Some(vec![
+                ColumnStatistics {
+                    distinct_count: Some(2),
+                    max_value: Some(ScalarValue::Int32(Some(1023))),
+                    min_value: Some(ScalarValue::Int32(Some(-24))),
+                    null_count: Some(0),
+                },
+                ColumnStatistics {
+                    distinct_count: Some(7),
+                    max_value: Some(ScalarValue::Int64(Some(5486))),
+                    min_value: Some(ScalarValue::Int64(Some(-6783))),
+                    null_count: Some(5),
+                },
+            ]),
+        },
+        physical_plan.statistics()
+    );
+
+    // a value outside of the [min, max] range of c1 is not found
+    let df = ctx
+        .sql("SELECT * FROM stats_table WHERE c1 > 2000")
+        .await
+        .unwrap();
+    let physical_plan = ctx
+        .create_physical_plan(&df.to_logical_plan())
+        .await
+        .unwrap();
+    assert_eq!(Some(0), physical_plan.statistics().num_rows);
 
     Ok(())
 }
@@ -172,6 +172,8 @@ macro_rules! aggregate_function {
                 fun: AggregateFunction::$FUNC,
                 args: args.into_iter().map(|e| e.into()).collect(),
                 distinct,
+                filter: None,
+                order_by: vec![],
             };
             expr.into()
         }